@@ -0,0 +1,196 @@
+//! Derive macros for [`halo2_proofs`](https://docs.rs/halo2_proofs).
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Expr, Fields, GenericArgument,
+    Ident, PathArguments, Type,
+};
+
+/// Derives `configure`, `Clone` and `Debug` for a struct of columns and
+/// selectors.
+///
+/// The generated `configure` allocates every field from a `ConstraintSystem`:
+///
+/// - `Column<Advice>`, `Column<Fixed>` and `Column<Instance>` fields are new
+///   columns of that type. Columns marked `#[equality]` have equality enabled.
+/// - `Selector` fields are new simple selectors, or complex selectors if marked
+///   `#[complex]`.
+/// - `TableColumn` fields are new lookup table columns.
+/// - `PhantomData` fields are set to `PhantomData`.
+/// - Arrays of any of the above allocate each element in turn.
+///
+/// ```ignore
+/// #[derive(ChipConfig)]
+/// struct AddConfig {
+///     #[equality]
+///     advice: [Column<Advice>; 2],
+///     s_add: Selector,
+/// }
+///
+/// let config = AddConfig::configure(meta);
+/// ```
+#[proc_macro_derive(ChipConfig, attributes(equality, complex))]
+pub fn derive_chip_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    chip_config(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn chip_config(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "ChipConfig can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "ChipConfig can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut allocations = vec![];
+    let mut clones = vec![];
+    let mut debugs = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().expect("fields are named");
+        let equality = has_attr(&field.attrs, "equality");
+        let complex = has_attr(&field.attrs, "complex");
+        allocations.push(allocate(ident, &field.ty, equality, complex)?);
+        clones.push(quote!(#ident: ::core::clone::Clone::clone(&self.#ident)));
+        let label = ident.to_string();
+        debugs.push(quote!(.field(#label, &self.#ident)));
+    }
+    let idents = fields.iter().map(|field| &field.ident);
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let label = name.to_string();
+    let field = Ident::new("__F", Span::call_site());
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Allocates the columns and selectors of this configuration.
+            pub fn configure<#field: ::halo2_proofs::arithmetic::Field>(
+                meta: &mut ::halo2_proofs::plonk::ConstraintSystem<#field>,
+            ) -> Self {
+                #(#allocations)*
+                #name { #(#idents),* }
+            }
+        }
+
+        impl #impl_generics ::core::clone::Clone for #name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                #name { #(#clones),* }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#label) #(#debugs)* .finish()
+            }
+        }
+    })
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident(name))
+}
+
+/// Returns a statement binding `ident` to a newly allocated value of type `ty`.
+fn allocate(
+    ident: &Ident,
+    ty: &Type,
+    equality: bool,
+    complex: bool,
+) -> Result<TokenStream2, Error> {
+    let (value, columns) = allocation(ty, equality, complex)?;
+    let enable_equality = if equality {
+        if !columns {
+            return Err(Error::new(
+                ty.span(),
+                "#[equality] can only be used on columns",
+            ));
+        }
+        match ty {
+            Type::Array(_) => quote! {
+                for column in #ident.iter() {
+                    meta.enable_equality(*column);
+                }
+            },
+            _ => quote!(meta.enable_equality(#ident);),
+        }
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        let #ident = #value;
+        #enable_equality
+    })
+}
+
+/// Returns an expression allocating a value of type `ty`, and whether that value
+/// is (an array of) columns that can have equality enabled.
+fn allocation(ty: &Type, equality: bool, complex: bool) -> Result<(TokenStream2, bool), Error> {
+    match ty {
+        Type::Array(array) => {
+            let len: &Expr = &array.len;
+            let (element, columns) = allocation(&array.elem, equality, complex)?;
+            Ok((quote!([(); #len].map(|_| #element)), columns))
+        }
+        Type::Path(path) => {
+            let segment = path
+                .path
+                .segments
+                .last()
+                .ok_or_else(|| Error::new(ty.span(), "unsupported field type"))?;
+            match segment.ident.to_string().as_str() {
+                "Column" => {
+                    let column_type = match &segment.arguments {
+                        PathArguments::AngleBracketed(args) => match args.args.first() {
+                            Some(GenericArgument::Type(Type::Path(column_type))) => column_type
+                                .path
+                                .segments
+                                .last()
+                                .map(|segment| segment.ident.to_string()),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    let value = match column_type.as_deref() {
+                        Some("Advice") => quote!(meta.advice_column()),
+                        Some("Fixed") => quote!(meta.fixed_column()),
+                        Some("Instance") => quote!(meta.instance_column()),
+                        _ => {
+                            return Err(Error::new(
+                                ty.span(),
+                                "expected Column<Advice>, Column<Fixed> or Column<Instance>",
+                            ))
+                        }
+                    };
+                    Ok((value, true))
+                }
+                "Selector" if complex => Ok((quote!(meta.complex_selector()), false)),
+                "Selector" => Ok((quote!(meta.selector()), false)),
+                "TableColumn" => Ok((quote!(meta.lookup_table_column()), false)),
+                "PhantomData" => Ok((quote!(::core::marker::PhantomData), false)),
+                _ => Err(Error::new(
+                    ty.span(),
+                    "ChipConfig fields must be columns, selectors, table columns, PhantomData or arrays of these",
+                )),
+            }
+        }
+        _ => Err(Error::new(ty.span(), "unsupported field type")),
+    }
+}
@@ -0,0 +1,84 @@
+//! Arithmetic modulo a foreign modulus.
+//!
+//! Circuits sometimes need to compute in a field other than the one they are
+//! defined over, such as the base field of secp256k1 to verify an ECDSA
+//! signature, or the other field of a curve cycle. Elements of the foreign
+//! field are represented as [`NUM_LIMBS`] limbs of [`LIMB_BITS`] bits each,
+//! least significant first, and every limb is range-checked.
+//!
+//! To compute `a * b + c` modulo `p`, the chip witnesses the quotient `q` and
+//! the remainder `r`, and checks that `a * b + c - q * p - r` is zero both
+//! modulo `2^(NUM_LIMBS * LIMB_BITS)`, on the limbs with carries, and modulo
+//! the native modulus, on the limbs recomposed as native field elements. The
+//! magnitude of the integer is bounded by the range checks on its operands, so
+//! by the Chinese remainder theorem it is zero over the integers.
+
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::Error,
+};
+use num_bigint::BigUint;
+
+mod chip;
+pub use chip::{AssignedBigNum, BigNumChip, BigNumConfig};
+
+/// The number of limbs of a foreign field element.
+pub const NUM_LIMBS: usize = 4;
+
+/// The number of bits in each limb of a foreign field element.
+pub const LIMB_BITS: usize = 68;
+
+/// The set of circuit instructions required to compute modulo a foreign
+/// modulus.
+pub trait BigNumInstructions<F: FieldExt>: Chip<F> {
+    /// An element of the foreign field.
+    type BigNum: Clone + Debug;
+
+    /// Witnesses an element, constraining it to have no more bits than the
+    /// modulus.
+    fn witness(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<BigUint>,
+    ) -> Result<Self::BigNum, Error>;
+
+    /// Assigns a constant element, reduced modulo the modulus.
+    fn constant(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: &BigUint,
+    ) -> Result<Self::BigNum, Error>;
+
+    /// Returns `a + b` modulo the modulus.
+    fn add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error>;
+
+    /// Returns `a * b` modulo the modulus.
+    fn mul(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error>;
+
+    /// Returns `a * b + c` modulo the modulus.
+    fn mul_add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+        c: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error>;
+}
@@ -0,0 +1,514 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use num_bigint::BigUint;
+
+use super::{BigNumInstructions, LIMB_BITS, NUM_LIMBS};
+use crate::utilities::{
+    lookup_range_check::{RangeCheckChip, RangeCheckConfig, RangeCheckInstructions},
+    CellValue,
+};
+
+/// The number of bits of the carries between pairs of limbs.
+const CARRY_BITS: usize = LIMB_BITS + 4;
+
+/// Returns the integer represented by a native field element. This assumes the
+/// field uses a little-endian representation, as the Pasta and BN254 fields do.
+fn field_to_big<F: FieldExt>(value: &F) -> BigUint {
+    BigUint::from_bytes_le(value.to_repr().as_ref())
+}
+
+/// Returns `value` reduced modulo the native modulus.
+fn big_to_field<F: FieldExt>(value: &BigUint) -> F {
+    let two_pow_64 = F::from(u64::MAX) + F::one();
+    value
+        .iter_u64_digits()
+        .rev()
+        .fold(F::zero(), |acc, digit| acc * two_pow_64 + F::from(digit))
+}
+
+/// Returns the limbs of `value`, least significant first. The last limb keeps
+/// all the remaining bits, so that a value that is too large fails the range
+/// check on its last limb instead of being truncated.
+fn decompose(value: &BigUint) -> Vec<BigUint> {
+    let mask = (BigUint::from(1u32) << LIMB_BITS) - 1u32;
+    (0..NUM_LIMBS)
+        .map(|i| {
+            let limb = value >> (i * LIMB_BITS);
+            if i + 1 < NUM_LIMBS {
+                limb & &mask
+            } else {
+                limb
+            }
+        })
+        .collect()
+}
+
+/// An element of the foreign field, assigned in the circuit as its limbs.
+#[derive(Clone, Debug)]
+pub struct AssignedBigNum<F: FieldExt> {
+    limbs: Vec<CellValue<F>>,
+}
+
+impl<F: FieldExt> AssignedBigNum<F> {
+    /// Returns the limbs of this element, least significant first.
+    pub fn limbs(&self) -> &[CellValue<F>] {
+        &self.limbs
+    }
+
+    /// Returns the integer represented by this element, if known.
+    pub fn value(&self) -> Value<BigUint> {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(Value::known(BigUint::from(0u32)), |acc, limb| {
+                acc.zip(limb.value())
+                    .map(|(acc, limb)| (acc << LIMB_BITS) + field_to_big(&limb))
+            })
+    }
+}
+
+/// Configuration for a [`BigNumChip`].
+#[derive(Clone, Debug)]
+pub struct BigNumConfig<F: FieldExt, const K: usize> {
+    limbs: [Column<Advice>; NUM_LIMBS],
+    q_mul_add: Selector,
+    modulus: BigUint,
+    range_check: RangeCheckConfig<F, K>,
+}
+
+/// A chip implementing [`BigNumInstructions`] on top of a [`RangeCheckChip`].
+///
+/// An element is assigned as its limbs in one row of the limb columns. For a
+/// modulus of `m` bits, the limbs of every element the chip produces are
+/// range-checked so that the element has at most `m` bits. Elements are
+/// therefore not necessarily fully reduced: the remainder of an operation is
+/// only constrained to be congruent to the result, and less than `2^m`.
+///
+/// `a * b + c` takes six rows: `a`, `b`, `c`, the quotient `q`, the remainder
+/// `r`, and the carries between pairs of limbs. These are followed by the range
+/// checks of `q` to `m + 1` bits, of `r` to `m` bits, and of the carries. Since
+/// `a, b, c, r < 2^m` and `q < 2^(m + 1)`, the integer `a * b + c - q * p - r`
+/// is less than `2^(2m + 1)` in absolute value.
+#[derive(Clone, Debug)]
+pub struct BigNumChip<F: FieldExt, const K: usize> {
+    config: BigNumConfig<F, K>,
+}
+
+impl<F: FieldExt, const K: usize> Chip<F> for BigNumChip<F, K> {
+    type Config = BigNumConfig<F, K>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const K: usize> BigNumChip<F, K> {
+    /// Configures this chip for arithmetic modulo `modulus`, reusing the given
+    /// range check configuration. The limb columns are equality-enabled.
+    ///
+    /// The circuit must load the range check table with
+    /// [`RangeCheckChip::load`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus does not have more bits than the limbs below the
+    /// most significant one, or if it is too large for the product of two
+    /// elements to be checked modulo `2^(NUM_LIMBS * LIMB_BITS)` and the native
+    /// modulus.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        limbs: [Column<Advice>; NUM_LIMBS],
+        modulus: BigUint,
+        range_check: RangeCheckConfig<F, K>,
+    ) -> BigNumConfig<F, K> {
+        let width = NUM_LIMBS * LIMB_BITS;
+        let modulus_bits = modulus.bits() as usize;
+        assert!(
+            modulus_bits > width - LIMB_BITS && modulus_bits <= width,
+            "the modulus must have between {} and {} bits",
+            width - LIMB_BITS + 1,
+            width
+        );
+        // |a * b + c - q * p - r| < 2^(2m + 1) must be at most the product of
+        // 2^width and the native modulus, which is at least 2^(NUM_BITS - 1).
+        assert!(
+            2 * modulus_bits + 2 <= width + F::NUM_BITS as usize,
+            "the modulus is too large for the native field"
+        );
+        for column in limbs.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let two_pow_limb = big_to_field::<F>(&(BigUint::from(1u32) << LIMB_BITS));
+        let native_modulus = big_to_field::<F>(&modulus);
+        let neg_modulus: Vec<F> = decompose(&((BigUint::from(1u32) << width) - &modulus))
+            .iter()
+            .map(big_to_field)
+            .collect();
+
+        let q_mul_add = meta.selector();
+
+        meta.create_gate("bignum mul-add", |meta| {
+            let q_mul_add = meta.query_selector(q_mul_add);
+            let query_row =
+                |rotation| limbs.map(|column| meta.query_advice(column, Rotation(rotation)));
+            let [a, b, c, q, r] = [0, 1, 2, 3, 4].map(query_row);
+            let carries: Vec<_> = (0..NUM_LIMBS / 2)
+                .map(|i| meta.query_advice(limbs[i], Rotation(5)))
+                .collect();
+
+            let compose = |limbs: &[Expression<F>]| {
+                limbs
+                    .iter()
+                    .rev()
+                    .fold(Expression::Constant(F::zero()), |acc, limb| {
+                        acc * two_pow_limb + limb.clone()
+                    })
+            };
+            // a * b + c - q * p - r = 0 modulo the native modulus.
+            let native = compose(&a) * compose(&b) + compose(&c)
+                - compose(&q) * native_modulus
+                - compose(&r);
+
+            // a * b + c + q * (2^width - p) - r = 0 modulo 2^width. The sum of
+            // the terms at limb l is at most 2^(2 * LIMB_BITS + 3), so every
+            // pair of limbs is checked to be a multiple of 2^(2 * LIMB_BITS)
+            // after adding the carry from the pair below. The sum of a pair is
+            // greater than -2^(2 * LIMB_BITS), so its carry is not negative.
+            let limb_sum = |l: usize| {
+                (0..=l)
+                    .map(|i| a[i].clone() * b[l - i].clone() + q[i].clone() * neg_modulus[l - i])
+                    .fold(c[l].clone() - r[l].clone(), |acc, term| acc + term)
+            };
+            let mut carry = Expression::Constant(F::zero());
+            let mut constraints = vec![q_mul_add.clone() * native];
+            for (i, next_carry) in carries.into_iter().enumerate() {
+                let sum = limb_sum(2 * i) + limb_sum(2 * i + 1) * two_pow_limb + carry;
+                constraints
+                    .push(q_mul_add.clone() * (sum - next_carry.clone() * two_pow_limb.square()));
+                carry = next_carry;
+            }
+            constraints
+        });
+
+        BigNumConfig {
+            limbs,
+            q_mul_add,
+            modulus,
+            range_check,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: BigNumConfig<F, K>) -> Self {
+        BigNumChip { config }
+    }
+
+    /// Returns the number of bits of the modulus.
+    fn modulus_bits(&self) -> usize {
+        self.config.modulus.bits() as usize
+    }
+
+    /// Assigns the limbs of `value` in `row`, without range-checking them.
+    fn assign_limbs(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        value: Value<BigUint>,
+    ) -> Result<AssignedBigNum<F>, Error> {
+        let limbs = value
+            .map(|value| decompose(&value))
+            .transpose_vec(NUM_LIMBS);
+        let limbs = self
+            .config
+            .limbs
+            .iter()
+            .zip(limbs)
+            .map(|(column, limb)| {
+                CellValue::assign(region, *column, row, limb.map(|limb| big_to_field(&limb)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedBigNum { limbs })
+    }
+
+    /// Constrains the element with the given limbs to have at most `num_bits`
+    /// bits.
+    fn range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        limbs: &[CellValue<F>],
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        let range_check = RangeCheckChip::construct(self.config.range_check);
+        for (i, limb) in limbs.iter().enumerate() {
+            let limb_bits = (num_bits - i * LIMB_BITS).min(LIMB_BITS);
+            range_check.range_check(region, offset, limb, limb_bits)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FieldExt, const K: usize> BigNumInstructions<F> for BigNumChip<F, K> {
+    type BigNum = AssignedBigNum<F>;
+
+    fn witness(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<BigUint>,
+    ) -> Result<Self::BigNum, Error> {
+        let value = self.assign_limbs(region, *offset, value)?;
+        *offset += 1;
+        self.range_check(region, offset, &value.limbs, self.modulus_bits())?;
+        Ok(value)
+    }
+
+    fn constant(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: &BigUint,
+    ) -> Result<Self::BigNum, Error> {
+        let config = &self.config;
+        let value = value % &config.modulus;
+        let limbs = config
+            .limbs
+            .iter()
+            .zip(decompose(&value))
+            .map(|(column, limb)| {
+                CellValue::assign_constant(region, *column, *offset, big_to_field(&limb))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += 1;
+        Ok(AssignedBigNum { limbs })
+    }
+
+    fn add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error> {
+        let one = self.constant(region, offset, &BigUint::from(1u32))?;
+        self.mul_add(region, offset, a, &one, b)
+    }
+
+    fn mul(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error> {
+        let zero = self.constant(region, offset, &BigUint::from(0u32))?;
+        self.mul_add(region, offset, a, b, &zero)
+    }
+
+    fn mul_add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::BigNum,
+        b: &Self::BigNum,
+        c: &Self::BigNum,
+    ) -> Result<Self::BigNum, Error> {
+        let config = &self.config;
+        let modulus = &config.modulus;
+        let row = *offset;
+
+        config.q_mul_add.enable(region, row)?;
+        for (i, operand) in [a, b, c].into_iter().enumerate() {
+            for (column, limb) in config.limbs.iter().zip(operand.limbs.iter()) {
+                limb.copy_advice(region, *column, row + i)?;
+            }
+        }
+
+        let neg_modulus = decompose(&((BigUint::from(1u32) << (NUM_LIMBS * LIMB_BITS)) - modulus));
+        let witness = a.value().zip(b.value()).zip(c.value()).map(|((a, b), c)| {
+            let sum = &a * &b + &c;
+            let (q, r) = (&sum / modulus, &sum % modulus);
+            let [a, b, c, q_limbs, r_limbs] = [&a, &b, &c, &q, &r].map(decompose);
+
+            // Mirrors the carries in the "bignum mul-add" gate.
+            let mut carry = BigUint::from(0u32);
+            let carries: Vec<_> = (0..NUM_LIMBS / 2)
+                .map(|i| {
+                    let (sum, sub) = [2 * i, 2 * i + 1].iter().fold(
+                        (carry.clone(), BigUint::from(0u32)),
+                        |(sum, sub), &l| {
+                            let limb_sum = (0..=l)
+                                .map(|j| &a[j] * &b[l - j] + &q_limbs[j] * &neg_modulus[l - j])
+                                .sum::<BigUint>()
+                                + &c[l];
+                            let shift = (l - 2 * i) * LIMB_BITS;
+                            (sum + (limb_sum << shift), sub + (&r_limbs[l] << shift))
+                        },
+                    );
+                    carry = (sum - sub) >> (2 * LIMB_BITS);
+                    carry.clone()
+                })
+                .collect();
+
+            (q, r, carries)
+        });
+
+        let q = self.assign_limbs(region, row + 3, witness.as_ref().map(|(q, _, _)| q.clone()))?;
+        let r = self.assign_limbs(region, row + 4, witness.as_ref().map(|(_, r, _)| r.clone()))?;
+        let carries = witness
+            .map(|(_, _, carries)| carries)
+            .transpose_vec(NUM_LIMBS / 2);
+        let carries = config
+            .limbs
+            .iter()
+            .zip(carries)
+            .map(|(column, carry)| {
+                CellValue::assign(
+                    region,
+                    *column,
+                    row + 5,
+                    carry.map(|carry| big_to_field(&carry)),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset = row + 6;
+
+        let modulus_bits = self.modulus_bits();
+        self.range_check(region, offset, &q.limbs, modulus_bits + 1)?;
+        self.range_check(region, offset, &r.limbs, modulus_bits)?;
+        let range_check = RangeCheckChip::construct(config.range_check);
+        for carry in carries.iter() {
+            range_check.range_check(region, offset, carry, CARRY_BITS)?;
+        }
+
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+    use num_bigint::BigUint;
+
+    use super::{BigNumChip, BigNumConfig};
+    use crate::{
+        bignum::{BigNumInstructions, NUM_LIMBS},
+        utilities::lookup_range_check::RangeCheckChip,
+    };
+
+    const K: usize = 8;
+
+    /// The modulus of the base field of secp256k1.
+    fn secp256k1_modulus() -> BigUint {
+        (BigUint::from(1u32) << 256) - (BigUint::from(1u32) << 32) - 977u32
+    }
+
+    struct BigNumCircuit {
+        a: Value<BigUint>,
+        b: Value<BigUint>,
+        c: Value<BigUint>,
+    }
+
+    impl Circuit<Fp> for BigNumCircuit {
+        type Config = BigNumConfig<Fp, K>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            BigNumCircuit {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                c: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let limbs = [(); NUM_LIMBS].map(|_| meta.advice_column());
+            let z = meta.advice_column();
+            let table = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check = RangeCheckChip::configure(meta, z, table);
+            BigNumChip::configure(meta, limbs, secp256k1_modulus(), range_check)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            RangeCheckChip::construct(config.range_check).load(&mut layouter)?;
+            let chip = BigNumChip::construct(config);
+
+            layouter.assign_region(
+                || "bignum",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let a = chip.witness(region, offset, self.a.clone())?;
+                    let b = chip.witness(region, offset, self.b.clone())?;
+                    let c = chip.witness(region, offset, self.c.clone())?;
+
+                    let sum = chip.add(region, offset, &a, &b)?;
+                    let product = chip.mul(region, offset, &a, &b)?;
+                    let mul_add = chip.mul_add(region, offset, &a, &b, &c)?;
+
+                    let p = secp256k1_modulus();
+                    let expected =
+                        self.a.clone().zip(self.b.clone()).zip(self.c.clone()).map(
+                            |((a, b), c)| ((&a + &b) % &p, (&a * &b) % &p, (&a * &b + &c) % &p),
+                        );
+                    sum.value()
+                        .zip(product.value())
+                        .zip(mul_add.value())
+                        .zip(expected)
+                        .assert_if_known(|(((sum, product), mul_add), expected)| {
+                            *sum == expected.0 && *product == expected.1 && *mul_add == expected.2
+                        });
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(a: BigUint, b: BigUint, c: BigUint) -> bool {
+        let circuit = BigNumCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            c: Value::known(c),
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn mul_add() {
+        let p = secp256k1_modulus();
+        let max = (BigUint::from(1u32) << 256) - 1u32;
+        assert!(verify(3u32.into(), 5u32.into(), 7u32.into()));
+        assert!(verify(p.clone() - 1u32, p.clone() - 1u32, p - 1u32));
+        // Elements are not necessarily reduced below the modulus.
+        assert!(verify(max.clone(), max.clone(), max));
+    }
+
+    #[test]
+    fn element_out_of_range() {
+        let too_large = BigUint::from(1u32) << 256;
+        assert!(!verify(too_large, 1u32.into(), 0u32.into()));
+    }
+}
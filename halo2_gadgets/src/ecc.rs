@@ -0,0 +1,151 @@
+//! Elliptic curve operations over short Weierstrass curves `y^2 = x^3 + b`,
+//! such as the Pasta curves, with the circuit defined over the curve's base
+//! field.
+//!
+//! # Completeness of addition
+//!
+//! [`EccChip`] adds points with the complete addition formulae, which accept
+//! the identity as either input, doubling and `P + (-P)`. They rely on the
+//! following properties of the curve, and are not complete without them:
+//!
+//! - The identity is represented as `(0, 0)`, so no point on the curve may
+//!   have `x = 0`; that is, `b` must be a non-square in the base field. A
+//!   point with `x = 0` would be treated as the identity.
+//! - Doubling constrains `2 * y * λ = 3 * x^2`, which has no solution for a
+//!   point with `y = 0`. Such points have order two, and do not exist on
+//!   curves of odd order.
+//! - Points are only constrained to be on the curve, not to be in a subgroup,
+//!   so the curve must have prime order. Points with a small-order component
+//!   are outside the chip's domain.
+//! - The curve equation is `y^2 = x^3 + b`, so `a` must be zero;
+//!   [`EccChip::configure`] panics otherwise.
+//!
+//! The Pasta curves satisfy all of these, so the formulae have no exceptional
+//! inputs on them.
+
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{Chip, Region, Value},
+    plonk::Error,
+};
+
+mod chip;
+pub use chip::{
+    EccChip, EccConfig, EccPoint, EccScalar, FixedBaseChip, FixedBaseConfig, FixedScalar,
+    FIXED_BASE_WINDOW_SIZE,
+};
+
+/// The set of circuit instructions required to use elliptic curve arithmetic.
+pub trait EccInstructions<C: CurveAffine>: Chip<C::Base> {
+    /// A point on the curve, possibly the identity.
+    type Point: Clone + Debug;
+    /// A scalar that can be used in [`EccInstructions::mul`].
+    type Scalar: Clone + Debug;
+
+    /// Witnesses a point, constraining it to be on the curve or the identity.
+    fn witness_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C>,
+    ) -> Result<Self::Point, Error>;
+
+    /// Witnesses a point, constraining it to be on the curve and not the
+    /// identity.
+    fn witness_point_non_id(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C>,
+    ) -> Result<Self::Point, Error>;
+
+    /// Assigns a fixed point, constraining it to equal the constant `point`.
+    fn constant_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        point: C,
+    ) -> Result<Self::Point, Error>;
+
+    /// Witnesses a scalar for use in variable-base scalar multiplication.
+    fn witness_scalar(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C::Scalar>,
+    ) -> Result<Self::Scalar, Error>;
+
+    /// Constrains two points to be equal.
+    fn constrain_equal(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<(), Error>;
+
+    /// Adds two points, handling the identity, doubling and inverse cases.
+    fn add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<Self::Point, Error>;
+
+    /// Doubles a point.
+    fn double(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        a: &Self::Point,
+    ) -> Result<Self::Point, Error>;
+
+    /// Multiplies a point by a scalar.
+    fn mul(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        scalar: &Self::Scalar,
+        base: &Self::Point,
+    ) -> Result<Self::Point, Error>;
+}
+
+/// A set of fixed base points, such as the generators of a commitment scheme,
+/// for use in [`FixedBaseInstructions::mul_fixed`].
+pub trait FixedPoints<C: CurveAffine>: Copy + Debug + Eq {
+    /// Returns every base in the set, in a fixed order.
+    fn all() -> Vec<Self>;
+
+    /// Returns the base point.
+    fn generator(&self) -> C;
+}
+
+/// The set of circuit instructions required to multiply fixed base points by
+/// scalars.
+pub trait FixedBaseInstructions<C: CurveAffine>: Chip<C::Base> {
+    /// A point on the curve, possibly the identity.
+    type Point: Clone + Debug;
+    /// A scalar that can be used in [`FixedBaseInstructions::mul_fixed`].
+    type FixedScalar: Clone + Debug;
+    /// The fixed bases supported by the chip.
+    type FixedPoints: FixedPoints<C>;
+
+    /// Witnesses a scalar for use in fixed-base scalar multiplication.
+    fn witness_scalar_fixed(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C::Scalar>,
+    ) -> Result<Self::FixedScalar, Error>;
+
+    /// Multiplies a fixed base by a scalar.
+    fn mul_fixed(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        scalar: &Self::FixedScalar,
+        base: Self::FixedPoints,
+    ) -> Result<Self::Point, Error>;
+}
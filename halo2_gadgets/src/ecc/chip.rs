@@ -0,0 +1,840 @@
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField};
+use group::prime::PrimeCurveAffine;
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use halo2curves::Coordinates;
+
+use super::EccInstructions;
+use crate::utilities::CellValue;
+
+mod fixed_base;
+pub use fixed_base::{FixedBaseChip, FixedBaseConfig, FixedScalar, FIXED_BASE_WINDOW_SIZE};
+
+/// A point assigned in the circuit as its affine coordinates. The identity is
+/// represented as `(0, 0)`.
+#[derive(Clone, Copy, Debug)]
+pub struct EccPoint<C: CurveAffine> {
+    x: CellValue<C::Base>,
+    y: CellValue<C::Base>,
+}
+
+impl<C: CurveAffine> EccPoint<C> {
+    /// Wraps already-assigned coordinates. The caller is responsible for
+    /// constraining them to be a point on the curve, or `(0, 0)`.
+    pub(crate) fn from_coordinates_unchecked(x: CellValue<C::Base>, y: CellValue<C::Base>) -> Self {
+        EccPoint { x, y }
+    }
+
+    /// Returns the x-coordinate of the point.
+    pub fn x(&self) -> CellValue<C::Base> {
+        self.x
+    }
+
+    /// Returns the y-coordinate of the point.
+    pub fn y(&self) -> CellValue<C::Base> {
+        self.y
+    }
+
+    /// Returns the point, if known.
+    pub fn point(&self) -> Value<C> {
+        self.x.value().zip(self.y.value()).map(|(x, y)| {
+            if x.is_zero_vartime() && y.is_zero_vartime() {
+                C::identity()
+            } else {
+                C::from_xy(x, y).unwrap()
+            }
+        })
+    }
+}
+
+/// A scalar assigned in the circuit as its bits, most significant first.
+#[derive(Clone, Debug)]
+pub struct EccScalar<C: CurveAffine> {
+    bits: Vec<CellValue<C::Base>>,
+}
+
+/// Configuration for an [`EccChip`].
+#[derive(Clone, Debug)]
+pub struct EccConfig<C: CurveAffine> {
+    advices: [Column<Advice>; 9],
+    q_point: Selector,
+    q_point_non_id: Selector,
+    q_bool: Selector,
+    q_cond: Selector,
+    q_add: Selector,
+    _marker: PhantomData<C>,
+}
+
+/// A chip implementing [`EccInstructions`] for curves `y^2 = x^3 + b` on which
+/// no point has `x = 0`, such as the Pasta curves.
+///
+/// Addition uses the complete addition formulae, so it is correct for every
+/// pair of inputs, including the identity, doubling and `P + (-P)`, on the
+/// curves described in the [module documentation](super). It takes
+/// two rows. Scalar multiplication is double-and-add over the bits of the
+/// scalar, taking five rows per bit.
+#[derive(Clone, Debug)]
+pub struct EccChip<C: CurveAffine> {
+    config: EccConfig<C>,
+}
+
+impl<C: CurveAffine> Chip<C::Base> for EccChip<C> {
+    type Config = EccConfig<C>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<C: CurveAffine> EccChip<C> {
+    /// Configures this chip for use in a circuit. The advice columns are
+    /// equality-enabled.
+    ///
+    /// [`EccInstructions::constant_point`] additionally requires a fixed column
+    /// enabled via `ConstraintSystem::enable_constant`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the curve has a non-zero `a` coefficient.
+    pub fn configure(
+        meta: &mut ConstraintSystem<C::Base>,
+        advices: [Column<Advice>; 9],
+    ) -> EccConfig<C> {
+        assert!(
+            bool::from(C::a().is_zero()),
+            "the chip only supports curves with a = 0"
+        );
+        for column in advices.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let q_point = meta.selector();
+        let q_point_non_id = meta.selector();
+        let q_bool = meta.selector();
+        let q_cond = meta.selector();
+        let q_add = meta.selector();
+
+        let curve_eqn = |x: Expression<C::Base>, y: Expression<C::Base>| {
+            y.clone() * y - x.clone() * x.clone() * x - Expression::Constant(C::b())
+        };
+
+        meta.create_gate("witness point", |meta| {
+            let q_point = meta.query_selector(q_point);
+            let x = meta.query_advice(advices[0], Rotation::cur());
+            let y = meta.query_advice(advices[1], Rotation::cur());
+
+            // Either (x, y) is on the curve, or it is (0, 0).
+            vec![
+                q_point.clone() * x.clone() * curve_eqn(x.clone(), y.clone()),
+                q_point * y.clone() * curve_eqn(x, y),
+            ]
+        });
+
+        meta.create_gate("witness non-identity point", |meta| {
+            let q_point_non_id = meta.query_selector(q_point_non_id);
+            let x = meta.query_advice(advices[0], Rotation::cur());
+            let y = meta.query_advice(advices[1], Rotation::cur());
+
+            vec![q_point_non_id * curve_eqn(x, y)]
+        });
+
+        meta.create_gate("boolean", |meta| {
+            let q_bool = meta.query_selector(q_bool);
+            let bit = meta.query_advice(advices[0], Rotation::cur());
+
+            vec![q_bool * bit.clone() * (Expression::Constant(C::Base::one()) - bit)]
+        });
+
+        meta.create_gate("conditional point", |meta| {
+            let q_cond = meta.query_selector(q_cond);
+            let bit = meta.query_advice(advices[0], Rotation::cur());
+            let x_p = meta.query_advice(advices[1], Rotation::cur());
+            let y_p = meta.query_advice(advices[2], Rotation::cur());
+            let x_q = meta.query_advice(advices[3], Rotation::cur());
+            let y_q = meta.query_advice(advices[4], Rotation::cur());
+
+            // (x_q, y_q) is (x_p, y_p) if the bit is set, and the identity otherwise.
+            vec![
+                q_cond.clone() * (x_q - bit.clone() * x_p),
+                q_cond * (y_q - bit * y_p),
+            ]
+        });
+
+        meta.create_gate("complete addition", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let x_p = meta.query_advice(advices[0], Rotation::cur());
+            let y_p = meta.query_advice(advices[1], Rotation::cur());
+            let x_q = meta.query_advice(advices[2], Rotation::cur());
+            let y_q = meta.query_advice(advices[3], Rotation::cur());
+            let lambda = meta.query_advice(advices[4], Rotation::cur());
+            let alpha = meta.query_advice(advices[5], Rotation::cur());
+            let beta = meta.query_advice(advices[6], Rotation::cur());
+            let gamma = meta.query_advice(advices[7], Rotation::cur());
+            let delta = meta.query_advice(advices[8], Rotation::cur());
+            let x_r = meta.query_advice(advices[0], Rotation::next());
+            let y_r = meta.query_advice(advices[1], Rotation::next());
+
+            let one = Expression::Constant(C::Base::one());
+            let x_q_minus_x_p = x_q.clone() - x_p.clone();
+            let y_q_plus_y_p = y_q.clone() + y_p.clone();
+
+            // α = inv0(x_q - x_p), β = inv0(x_p), γ = inv0(x_q), δ = inv0(y_q + y_p)
+            let if_alpha = x_q_minus_x_p.clone() * alpha;
+            let if_beta = x_p.clone() * beta;
+            let if_gamma = x_q.clone() * gamma;
+            let if_delta = y_q_plus_y_p.clone() * delta;
+
+            // λ is the slope of the line through P and Q, or the tangent at P.
+            let poly1 = x_q_minus_x_p.clone()
+                * (x_q_minus_x_p.clone() * lambda.clone() - (y_q.clone() - y_p.clone()));
+            let poly2 = (one.clone() - if_alpha.clone())
+                * (y_p.clone() * lambda.clone() * C::Base::from(2)
+                    - x_p.clone() * x_p.clone() * C::Base::from(3));
+
+            // The result of the non-exceptional case, where neither input is the
+            // identity and the result is not the identity.
+            let nonexceptional_x =
+                lambda.clone() * lambda.clone() - x_p.clone() - x_q.clone() - x_r.clone();
+            let nonexceptional_y = lambda * (x_p.clone() - x_r.clone()) - y_p.clone() - y_r.clone();
+            let x_p_x_q = x_p.clone() * x_q.clone();
+            let poly3 = [nonexceptional_x.clone(), nonexceptional_y.clone()]
+                .map(|eqn| x_p_x_q.clone() * x_q_minus_x_p.clone() * eqn);
+            let poly4 = [nonexceptional_x, nonexceptional_y]
+                .map(|eqn| x_p_x_q.clone() * y_q_plus_y_p.clone() * eqn);
+
+            // P is the identity, so R = Q.
+            let poly5 = [
+                (one.clone() - if_beta.clone()) * (x_r.clone() - x_q.clone()),
+                (one.clone() - if_beta) * (y_r.clone() - y_q),
+            ];
+            // Q is the identity, so R = P.
+            let poly6 = [
+                (one.clone() - if_gamma.clone()) * (x_r.clone() - x_p),
+                (one.clone() - if_gamma) * (y_r.clone() - y_p),
+            ];
+            // Q = -P, so R is the identity.
+            let poly7 = [
+                (one.clone() - if_alpha.clone() - if_delta.clone()) * x_r,
+                (one - if_alpha - if_delta) * y_r,
+            ];
+
+            std::iter::empty()
+                .chain(Some(poly1))
+                .chain(Some(poly2))
+                .chain(poly3)
+                .chain(poly4)
+                .chain(poly5)
+                .chain(poly6)
+                .chain(poly7)
+                .map(|poly| q_add.clone() * poly)
+                .collect::<Vec<_>>()
+        });
+
+        EccConfig {
+            advices,
+            q_point,
+            q_point_non_id,
+            q_bool,
+            q_cond,
+            q_add,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: EccConfig<C>) -> Self {
+        EccChip { config }
+    }
+
+    fn witness_coordinates(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        coordinates: Value<(C::Base, C::Base)>,
+        selector: Selector,
+    ) -> Result<EccPoint<C>, Error> {
+        let config = &self.config;
+        let (x, y) = coordinates.unzip();
+        selector.enable(region, *offset)?;
+        let x = CellValue::assign(region, config.advices[0], *offset, x)?;
+        let y = CellValue::assign(region, config.advices[1], *offset, y)?;
+        *offset += 1;
+
+        Ok(EccPoint { x, y })
+    }
+
+    /// Returns `bit * base`, where `bit` is boolean.
+    fn conditional_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        bit: &CellValue<C::Base>,
+        base: &EccPoint<C>,
+    ) -> Result<EccPoint<C>, Error> {
+        let config = &self.config;
+        let row = *offset;
+        config.q_cond.enable(region, row)?;
+        let bit = bit.copy_advice(region, config.advices[0], row)?;
+        let x_p = base.x.copy_advice(region, config.advices[1], row)?;
+        let y_p = base.y.copy_advice(region, config.advices[2], row)?;
+        let x = CellValue::assign(region, config.advices[3], row, bit.value() * x_p.value())?;
+        let y = CellValue::assign(region, config.advices[4], row, bit.value() * y_p.value())?;
+        *offset += 1;
+
+        Ok(EccPoint { x, y })
+    }
+}
+
+fn inv0<F: Field>(value: F) -> F {
+    value.invert().unwrap_or(F::zero())
+}
+
+fn coordinates<C: CurveAffine>(point: C) -> (C::Base, C::Base) {
+    Option::<Coordinates<C>>::from(point.coordinates())
+        .map(|c| (*c.x(), *c.y()))
+        .unwrap_or((C::Base::zero(), C::Base::zero()))
+}
+
+impl<C: CurveAffine> EccInstructions<C> for EccChip<C> {
+    type Point = EccPoint<C>;
+    type Scalar = EccScalar<C>;
+
+    fn witness_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C>,
+    ) -> Result<Self::Point, Error> {
+        self.witness_coordinates(region, offset, value.map(coordinates), self.config.q_point)
+    }
+
+    fn witness_point_non_id(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C>,
+    ) -> Result<Self::Point, Error> {
+        self.witness_coordinates(
+            region,
+            offset,
+            value.map(coordinates),
+            self.config.q_point_non_id,
+        )
+    }
+
+    fn constant_point(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        point: C,
+    ) -> Result<Self::Point, Error> {
+        let config = &self.config;
+        let (x, y) = coordinates(point);
+        let x = CellValue::assign_constant(region, config.advices[0], *offset, x)?;
+        let y = CellValue::assign_constant(region, config.advices[1], *offset, y)?;
+        *offset += 1;
+
+        Ok(EccPoint { x, y })
+    }
+
+    fn witness_scalar(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C::Scalar>,
+    ) -> Result<Self::Scalar, Error> {
+        let config = &self.config;
+        let num_bits = C::Scalar::NUM_BITS as usize;
+        let repr = value.map(|scalar| scalar.to_repr());
+
+        let bits = (0..num_bits)
+            .map(|idx| {
+                // The representation is little-endian, and the bits are stored
+                // most significant first.
+                let i = num_bits - 1 - idx;
+                let bit = repr.map(|repr| {
+                    let bytes: &[u8] = repr.as_ref();
+                    C::Base::from(((bytes[i / 8] >> (i % 8)) & 1) as u64)
+                });
+                config.q_bool.enable(region, *offset + idx)?;
+                CellValue::assign(region, config.advices[0], *offset + idx, bit)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += num_bits;
+
+        Ok(EccScalar { bits })
+    }
+
+    fn constrain_equal(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<(), Error> {
+        region.constrain_equal(&a.x.cell(), &b.x.cell());
+        region.constrain_equal(&a.y.cell(), &b.y.cell());
+        Ok(())
+    }
+
+    fn add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<Self::Point, Error> {
+        let config = &self.config;
+        let row = *offset;
+        config.q_add.enable(region, row)?;
+
+        let x_p = a.x.copy_advice(region, config.advices[0], row)?;
+        let y_p = a.y.copy_advice(region, config.advices[1], row)?;
+        let x_q = b.x.copy_advice(region, config.advices[2], row)?;
+        let y_q = b.y.copy_advice(region, config.advices[3], row)?;
+
+        let inputs = x_p
+            .value()
+            .zip(y_p.value())
+            .zip(x_q.value().zip(y_q.value()));
+
+        let witnesses = inputs.map(|((x_p, y_p), (x_q, y_q))| {
+            let alpha = inv0(x_q - x_p);
+            let beta = inv0(x_p);
+            let gamma = inv0(x_q);
+            let delta = if x_q == x_p {
+                inv0(y_q + y_p)
+            } else {
+                C::Base::zero()
+            };
+            let lambda = if x_q != x_p {
+                (y_q - y_p) * alpha
+            } else if !bool::from(y_p.is_zero()) {
+                x_p.square() * C::Base::from(3) * inv0(y_p.double())
+            } else {
+                C::Base::zero()
+            };
+            [lambda, alpha, beta, gamma, delta]
+        });
+        for (idx, column) in config.advices[4..].iter().enumerate() {
+            region.assign_advice(*column, row, witnesses.map(|w| w[idx]))?;
+        }
+
+        let result = inputs
+            .zip(witnesses)
+            .map(|(((x_p, y_p), (x_q, y_q)), [lambda, ..])| {
+                if bool::from(x_p.is_zero()) {
+                    (x_q, y_q)
+                } else if bool::from(x_q.is_zero()) {
+                    (x_p, y_p)
+                } else if x_q == x_p && y_q == -y_p {
+                    (C::Base::zero(), C::Base::zero())
+                } else {
+                    let x_r = lambda.square() - x_p - x_q;
+                    let y_r = lambda * (x_p - x_r) - y_p;
+                    (x_r, y_r)
+                }
+            });
+        let (x_r, y_r) = result.unzip();
+        let x = CellValue::assign(region, config.advices[0], row + 1, x_r)?;
+        let y = CellValue::assign(region, config.advices[1], row + 1, y_r)?;
+        *offset += 2;
+
+        Ok(EccPoint { x, y })
+    }
+
+    fn double(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        a: &Self::Point,
+    ) -> Result<Self::Point, Error> {
+        self.add(region, offset, a, a)
+    }
+
+    fn mul(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        scalar: &Self::Scalar,
+        base: &Self::Point,
+    ) -> Result<Self::Point, Error> {
+        let (first, rest) = scalar
+            .bits
+            .split_first()
+            .expect("scalars have at least one bit");
+
+        let mut acc = self.conditional_point(region, offset, first, base)?;
+        for bit in rest {
+            let doubled = self.double(region, offset, &acc)?;
+            let addend = self.conditional_point(region, offset, bit, base)?;
+            acc = self.add(region, offset, &doubled, &addend)?;
+        }
+
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::{pallas, vesta};
+
+    use super::{EccChip, EccConfig, EccPoint};
+    use crate::ecc::EccInstructions;
+
+    const K: u32 = 11;
+
+    struct EccCircuit<C: CurveAffine> {
+        p: Value<C>,
+        q: Value<C>,
+        k: Value<C::Scalar>,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for EccCircuit<C> {
+        type Config = EccConfig<C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            EccCircuit {
+                p: Value::unknown(),
+                q: Value::unknown(),
+                k: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            EccChip::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config);
+            layouter.assign_region(
+                || "ecc",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let check = |point: &EccPoint<C>, expected: Value<C>| {
+                        point
+                            .point()
+                            .zip(expected)
+                            .assert_if_known(|(point, expected)| point == expected)
+                    };
+
+                    let p = chip.witness_point_non_id(region, offset, self.p)?;
+                    let q = chip.witness_point(region, offset, self.q)?;
+                    let neg_p = chip.witness_point(region, offset, self.p.map(|p| -p))?;
+                    let identity =
+                        chip.witness_point(region, offset, Value::known(C::identity()))?;
+
+                    // Generic addition, and addition with the identity.
+                    let sum = chip.add(region, offset, &p, &q)?;
+                    check(
+                        &sum,
+                        self.p
+                            .zip(self.q)
+                            .map(|(p, q)| (p.to_curve() + q).to_affine()),
+                    );
+                    check(&chip.add(region, offset, &identity, &p)?, self.p);
+                    check(&chip.add(region, offset, &p, &identity)?, self.p);
+
+                    // P + (-P) is the identity, and P + P is a doubling.
+                    let zero = chip.add(region, offset, &p, &neg_p)?;
+                    check(&zero, Value::known(C::identity()));
+                    chip.constrain_equal(region, &zero, &identity)?;
+                    check(
+                        &chip.add(region, offset, &p, &p)?,
+                        self.p.map(|p| (p.to_curve() + p).to_affine()),
+                    );
+                    check(
+                        &chip.double(region, offset, &identity)?,
+                        Value::known(C::identity()),
+                    );
+
+                    // Variable-base scalar multiplication.
+                    let k = chip.witness_scalar(region, offset, self.k)?;
+                    check(
+                        &chip.mul(region, offset, &k, &q)?,
+                        self.q.zip(self.k).map(|(q, k)| (q * k).to_affine()),
+                    );
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn run<C: CurveAffine>() {
+        let g = C::generator();
+        let circuit = EccCircuit::<C> {
+            p: Value::known((g * C::Scalar::from(5)).to_affine()),
+            q: Value::known((g * C::Scalar::from(11)).to_affine()),
+            k: Value::known(-C::Scalar::from(3)),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn ecc_chip_pallas() {
+        run::<pallas::Affine>();
+    }
+
+    #[test]
+    fn ecc_chip_vesta() {
+        run::<vesta::Affine>();
+    }
+
+    /// Adds `a` and `b`, and constrains the sum to equal the witnessed `sum`.
+    struct AddCircuit<C: CurveAffine> {
+        a: Value<C>,
+        b: Value<C>,
+        sum: Value<C>,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for AddCircuit<C> {
+        type Config = EccConfig<C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            AddCircuit {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                sum: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            EccChip::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config);
+            layouter.assign_region(
+                || "add",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let a = chip.witness_point(region, offset, self.a)?;
+                    let b = chip.witness_point(region, offset, self.b)?;
+                    let sum = chip.witness_point(region, offset, self.sum)?;
+                    let result = chip.add(region, offset, &a, &b)?;
+                    chip.constrain_equal(region, &result, &sum)
+                },
+            )
+        }
+    }
+
+    /// Checks each exceptional case of the complete addition formulae: the sum
+    /// must be accepted, and any other point rejected.
+    fn add_edge_cases<C: CurveAffine>() {
+        let g = C::generator();
+        let p = (g * C::Scalar::from(5)).to_affine();
+        let q = (g * C::Scalar::from(11)).to_affine();
+        let identity = C::identity();
+        let cases = [
+            ("P + Q", p, q),
+            ("identity + P", identity, p),
+            ("P + identity", p, identity),
+            ("P + P", p, p),
+            ("P + (-P)", p, -p),
+            ("identity + identity", identity, identity),
+        ];
+
+        for (name, a, b) in cases {
+            let sum = (a.to_curve() + b).to_affine();
+            let run = |sum: C| {
+                let circuit = AddCircuit {
+                    a: Value::known(a),
+                    b: Value::known(b),
+                    sum: Value::known(sum),
+                };
+                MockProver::run(K, &circuit, vec![]).unwrap().verify()
+            };
+            assert_eq!(run(sum), Ok(()), "{} is not accepted", name);
+            let wrong = (sum.to_curve() + g).to_affine();
+            assert!(run(wrong).is_err(), "{} accepts a wrong sum", name);
+            if sum != identity {
+                assert!(run(identity).is_err(), "{} accepts the identity", name);
+            }
+        }
+    }
+
+    #[test]
+    fn add_edge_cases_pallas() {
+        add_edge_cases::<pallas::Affine>();
+    }
+
+    #[test]
+    fn add_edge_cases_vesta() {
+        add_edge_cases::<vesta::Affine>();
+    }
+
+    /// Multiplies `base` by `k`, and constrains the product to equal the
+    /// witnessed `product`.
+    struct MulCircuit<C: CurveAffine> {
+        k: Value<C::Scalar>,
+        base: Value<C>,
+        product: Value<C>,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for MulCircuit<C> {
+        type Config = EccConfig<C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MulCircuit {
+                k: Value::unknown(),
+                base: Value::unknown(),
+                product: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            EccChip::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config);
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let k = chip.witness_scalar(region, offset, self.k)?;
+                    let base = chip.witness_point(region, offset, self.base)?;
+                    let product = chip.witness_point(region, offset, self.product)?;
+                    let result = chip.mul(region, offset, &k, &base)?;
+                    chip.constrain_equal(region, &result, &product)
+                },
+            )
+        }
+    }
+
+    /// Checks scalar multiplication by the extreme scalars, whose double-and-add
+    /// steps go through the identity, doublings and `P + (-P)`: the product must
+    /// be accepted, and any other point rejected.
+    fn mul_edge_cases<C: CurveAffine>() {
+        let g = C::generator();
+        let p = (g * C::Scalar::from(5)).to_affine();
+        let identity = C::identity();
+        let cases = [
+            ("0 * P", C::Scalar::zero(), p),
+            ("1 * P", C::Scalar::one(), p),
+            ("2 * P", C::Scalar::from(2), p),
+            ("(r - 1) * P", -C::Scalar::one(), p),
+            ("(r - 2) * P", -C::Scalar::from(2), p),
+            ("(r - 1) * identity", -C::Scalar::one(), identity),
+            ("1 * identity", C::Scalar::one(), identity),
+        ];
+
+        for (name, k, base) in cases {
+            let product = (base * k).to_affine();
+            let run = |product: C| {
+                let circuit = MulCircuit {
+                    k: Value::known(k),
+                    base: Value::known(base),
+                    product: Value::known(product),
+                };
+                MockProver::run(K, &circuit, vec![]).unwrap().verify()
+            };
+            assert_eq!(run(product), Ok(()), "{} is not accepted", name);
+            let wrong = (product.to_curve() + g).to_affine();
+            assert!(run(wrong).is_err(), "{} accepts a wrong product", name);
+            if product != identity {
+                assert!(run(identity).is_err(), "{} accepts the identity", name);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_edge_cases_pallas() {
+        mul_edge_cases::<pallas::Affine>();
+    }
+
+    #[test]
+    fn mul_edge_cases_vesta() {
+        mul_edge_cases::<vesta::Affine>();
+    }
+
+    #[test]
+    fn point_not_on_curve() {
+        struct OffCurve;
+
+        impl Circuit<pallas::Base> for OffCurve {
+            type Config = EccConfig<pallas::Affine>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                OffCurve
+            }
+
+            fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+                let advices = [(); 9].map(|_| meta.advice_column());
+                EccChip::configure(meta, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<pallas::Base>,
+            ) -> Result<(), Error> {
+                let chip = EccChip::construct(config);
+                layouter.assign_region(
+                    || "off curve",
+                    |mut region| {
+                        let coordinates = Value::known((pallas::Base::one(), pallas::Base::one()));
+                        let mut offset = 0;
+                        chip.witness_coordinates(
+                            &mut region,
+                            &mut offset,
+                            coordinates,
+                            chip.config.q_point,
+                        )?;
+                        chip.witness_coordinates(
+                            &mut region,
+                            &mut offset,
+                            Value::known((pallas::Base::zero(), pallas::Base::zero())),
+                            chip.config.q_point_non_id,
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &OffCurve, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
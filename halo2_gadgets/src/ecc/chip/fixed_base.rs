@@ -0,0 +1,343 @@
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::{coordinates, EccChip, EccConfig, EccPoint};
+use crate::{
+    ecc::{EccInstructions, FixedBaseInstructions, FixedPoints},
+    utilities::{le_bits, CellValue},
+};
+
+/// The number of bits in each window of a fixed-base scalar.
+pub const FIXED_BASE_WINDOW_SIZE: usize = 3;
+
+/// The number of points precomputed for each window.
+const H: usize = 1 << FIXED_BASE_WINDOW_SIZE;
+
+/// The number of windows in a full-width scalar of the curve `C`.
+fn num_windows<C: CurveAffine>() -> usize {
+    (C::Scalar::NUM_BITS as usize + FIXED_BASE_WINDOW_SIZE - 1) / FIXED_BASE_WINDOW_SIZE
+}
+
+/// A scalar assigned in the circuit as its windows, least significant first.
+///
+/// The windows are only constrained to be in range when they are used in
+/// [`FixedBaseInstructions::mul_fixed`].
+#[derive(Clone, Debug)]
+pub struct FixedScalar<C: CurveAffine> {
+    windows: Vec<CellValue<C::Base>>,
+}
+
+/// Configuration for a [`FixedBaseChip`].
+#[derive(Clone, Debug)]
+pub struct FixedBaseConfig<C: CurveAffine, B: FixedPoints<C>> {
+    ecc: EccConfig<C>,
+    tag: Column<Fixed>,
+    window: Column<Fixed>,
+    table: [TableColumn; 5],
+    q_mul_fixed: Selector,
+    _marker: PhantomData<B>,
+}
+
+/// A chip implementing [`FixedBaseInstructions`] on top of an [`EccChip`].
+///
+/// For every base in `B`, every window `w` and every `k < 2^3`, the point
+/// `k * 8^w * B` is precomputed into a lookup table keyed by the index of the
+/// base, `w` and `k`. Multiplication looks up one point per window of the
+/// scalar and sums them with complete addition, so the identity and the
+/// exceptional cases of addition need no special handling. A multiplication by
+/// a full-width scalar takes `3 * 85 - 2` rows on the Pasta curves, and the
+/// table takes `8 * 85` rows per base.
+#[derive(Clone, Debug)]
+pub struct FixedBaseChip<C: CurveAffine, B: FixedPoints<C>> {
+    config: FixedBaseConfig<C, B>,
+}
+
+impl<C: CurveAffine, B: FixedPoints<C>> Chip<C::Base> for FixedBaseChip<C, B> {
+    type Config = FixedBaseConfig<C, B>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<C: CurveAffine, B: FixedPoints<C>> FixedBaseChip<C, B> {
+    /// Configures this chip for use in a circuit, reusing the advice columns
+    /// of the given ECC configuration.
+    ///
+    /// The circuit must load the table with [`FixedBaseChip::load`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<C::Base>,
+        ecc: EccConfig<C>,
+    ) -> FixedBaseConfig<C, B> {
+        let tag = meta.fixed_column();
+        let window = meta.fixed_column();
+        let table = [(); 5].map(|_| meta.lookup_table_column());
+        let q_mul_fixed = meta.complex_selector();
+
+        // Rows without the selector look up the identity as the multiple zero
+        // of the first window of the first base.
+        meta.lookup("fixed-base window", |meta| {
+            let q_mul_fixed = meta.query_selector(q_mul_fixed);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let window = meta.query_fixed(window, Rotation::cur());
+            let [k, x, y] = [0, 1, 2].map(|i| meta.query_advice(ecc.advices[i], Rotation::cur()));
+
+            [tag, window, k, x, y]
+                .into_iter()
+                .zip(table)
+                .map(|(input, column)| (q_mul_fixed.clone() * input, column))
+                .collect()
+        });
+
+        FixedBaseConfig {
+            ecc,
+            tag,
+            window,
+            table,
+            q_mul_fixed,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: FixedBaseConfig<C, B>) -> Self {
+        FixedBaseChip { config }
+    }
+
+    /// Loads the precomputed windows of every base.
+    pub fn load(&self, layouter: &mut impl Layouter<C::Base>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "fixed-base windows",
+            |mut t| {
+                let mut row = 0;
+                for (tag, base) in B::all().iter().enumerate() {
+                    let mut window_base = base.generator().to_curve();
+                    for window in 0..num_windows::<C>() {
+                        let mut multiple = C::identity().to_curve();
+                        for k in 0..H {
+                            let (x, y) = coordinates(multiple.to_affine());
+                            let values = [
+                                C::Base::from(tag as u64),
+                                C::Base::from(window as u64),
+                                C::Base::from(k as u64),
+                                x,
+                                y,
+                            ];
+                            for (column, value) in table.iter().zip(values) {
+                                t.assign_cell(|| "window", *column, row, || Value::known(value))?;
+                            }
+                            multiple += window_base;
+                            row += 1;
+                        }
+                        window_base = multiple;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns the index of `base` in [`FixedPoints::all`].
+    fn tag(base: B) -> usize {
+        B::all()
+            .iter()
+            .position(|b| *b == base)
+            .expect("the base is one of FixedPoints::all()")
+    }
+}
+
+impl<C: CurveAffine, B: FixedPoints<C>> FixedBaseInstructions<C> for FixedBaseChip<C, B> {
+    type Point = EccPoint<C>;
+    type FixedScalar = FixedScalar<C>;
+    type FixedPoints = B;
+
+    fn witness_scalar_fixed(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        value: Value<C::Scalar>,
+    ) -> Result<Self::FixedScalar, Error> {
+        let column = self.config.ecc.advices[0];
+        let num_windows = num_windows::<C>();
+        let bits = value.map(|scalar| le_bits(&scalar, num_windows * FIXED_BASE_WINDOW_SIZE));
+
+        let windows = (0..num_windows)
+            .map(|w| {
+                let window = bits.as_ref().map(|bits| {
+                    bits[w * FIXED_BASE_WINDOW_SIZE..(w + 1) * FIXED_BASE_WINDOW_SIZE]
+                        .iter()
+                        .rev()
+                        .fold(0u64, |acc, bit| (acc << 1) | *bit as u64)
+                });
+                CellValue::assign(region, column, *offset + w, window.map(C::Base::from))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += num_windows;
+
+        Ok(FixedScalar { windows })
+    }
+
+    fn mul_fixed(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: &mut usize,
+        scalar: &Self::FixedScalar,
+        base: Self::FixedPoints,
+    ) -> Result<Self::Point, Error> {
+        let config = &self.config;
+        let [k_col, x_col, y_col] = [0, 1, 2].map(|i| config.ecc.advices[i]);
+        let tag = C::Base::from(Self::tag(base) as u64);
+        let h = C::Scalar::from(H as u64);
+
+        let mut points = Vec::with_capacity(scalar.windows.len());
+        for (w, window) in scalar.windows.iter().enumerate() {
+            let row = *offset;
+            config.q_mul_fixed.enable(region, row)?;
+            region.assign_fixed(config.tag, row, tag);
+            region.assign_fixed(config.window, row, C::Base::from(w as u64));
+            let k = window.copy_advice(region, k_col, row)?;
+
+            let (x, y) = k
+                .value()
+                .map(|k| {
+                    let k = le_bits(&k, FIXED_BASE_WINDOW_SIZE)
+                        .iter()
+                        .rev()
+                        .fold(0u64, |acc, bit| (acc << 1) | *bit as u64);
+                    let multiple = C::Scalar::from(k) * h.pow_vartime([w as u64]);
+                    coordinates((base.generator() * multiple).to_affine())
+                })
+                .unzip();
+            let x = CellValue::assign(region, x_col, row, x)?;
+            let y = CellValue::assign(region, y_col, row, y)?;
+            *offset += 1;
+
+            points.push(EccPoint { x, y });
+        }
+
+        let ecc = EccChip::construct(config.ecc.clone());
+        let (first, rest) = points
+            .split_first()
+            .expect("scalars have at least one window");
+        rest.iter()
+            .try_fold(*first, |acc, point| ecc.add(region, offset, &acc, point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2_proofs::{
+        arithmetic::CurveExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::pallas;
+
+    use super::{FixedBaseChip, FixedBaseConfig};
+    use crate::ecc::{EccChip, FixedBaseInstructions, FixedPoints};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum TestBases {
+        G,
+        H,
+    }
+
+    impl FixedPoints<pallas::Affine> for TestBases {
+        fn all() -> Vec<Self> {
+            vec![TestBases::G, TestBases::H]
+        }
+
+        fn generator(&self) -> pallas::Affine {
+            match self {
+                TestBases::G => pallas::Affine::generator(),
+                TestBases::H => {
+                    pallas::Point::hash_to_curve("halo2_gadgets:test")(b"H").to_affine()
+                }
+            }
+        }
+    }
+
+    struct MulFixedCircuit {
+        scalars: Vec<Value<pallas::Scalar>>,
+    }
+
+    impl Circuit<pallas::Base> for MulFixedCircuit {
+        type Config = FixedBaseConfig<pallas::Affine, TestBases>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MulFixedCircuit {
+                scalars: vec![Value::unknown(); self.scalars.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            let ecc = EccChip::configure(meta, advices);
+            FixedBaseChip::configure(meta, ecc)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let chip = FixedBaseChip::construct(config);
+            chip.load(&mut layouter)?;
+
+            layouter.assign_region(
+                || "fixed-base multiplication",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    for scalar in self.scalars.iter() {
+                        let k = chip.witness_scalar_fixed(region, offset, *scalar)?;
+                        for base in TestBases::all() {
+                            let product = chip.mul_fixed(region, offset, &k, base)?;
+                            product
+                                .point()
+                                .zip(*scalar)
+                                .assert_if_known(|(product, scalar)| {
+                                    *product == (base.generator() * *scalar).to_affine()
+                                });
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn mul_fixed() {
+        let circuit = MulFixedCircuit {
+            scalars: [
+                pallas::Scalar::zero(),
+                -pallas::Scalar::one(),
+                pallas::Scalar::from(0x1234_5678_9abc_def0),
+            ]
+            .into_iter()
+            .map(Value::known)
+            .collect(),
+        };
+        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
@@ -0,0 +1,26 @@
+//! # halo2_gadgets
+//!
+//! Reusable gadgets and chips for circuits built with [`halo2_proofs`].
+//!
+//! Regions in `halo2_proofs` are laid out from row zero, so the gadgets in this
+//! crate do not rely on the layouter to place their rows. Instead, every
+//! instruction takes the [`Region`] to assign in and an `offset` cursor: the
+//! instruction assigns its rows starting at `*offset`, and advances the cursor
+//! past the last row it used. Circuits that call several instructions in the
+//! same region simply keep passing the same cursor.
+//!
+//! [`Region`]: halo2_proofs::circuit::Region
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![deny(rustdoc::broken_intra_doc_links)]
+#![deny(missing_debug_implementations)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+#![allow(clippy::too_many_arguments)]
+
+pub mod bignum;
+pub mod ecc;
+pub mod poseidon;
+pub mod sha256;
+pub mod sinsemilla;
+pub mod utilities;
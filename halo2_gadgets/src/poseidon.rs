@@ -0,0 +1,188 @@
+//! The Poseidon algebraic hash function.
+//!
+//! The gadgets in this module hash with the same sponge as the native
+//! `poseidon::Poseidon` hasher used by `halo2_proofs` transcripts: the
+//! capacity element starts at `2^64`, inputs are absorbed `RATE` at a time into
+//! the remaining state words, and squeezing pads the pending inputs with a
+//! single `1` before the final permutation and returns the second state word.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region},
+    plonk::Error,
+};
+
+mod pow5;
+pub use pow5::{Pow5Chip, Pow5Config};
+
+/// The parameters of a Poseidon instance with the `x^5` S-box.
+pub trait Spec<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
+    /// The number of full rounds. This must be even.
+    fn full_rounds() -> usize;
+
+    /// The number of partial rounds.
+    fn partial_rounds() -> usize;
+
+    /// Generates the round constants, the MDS matrix and its inverse.
+    ///
+    /// The default implementation uses the reference Grain LFSR, which is also
+    /// what the native `poseidon::Poseidon` hasher uses.
+    fn constants() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>) {
+        let (round_constants, mds) = ::poseidon::Spec::<F, T, RATE>::reference_constants(
+            Self::full_rounds(),
+            Self::partial_rounds(),
+        );
+        (round_constants, mds.rows(), mds.invert().rows())
+    }
+}
+
+/// A `T x T` matrix, stored as rows.
+pub type Mds<F, const T: usize> = [[F; T]; T];
+
+/// A Poseidon instance with the `x^5` S-box, `R_F` full rounds and `R_P`
+/// partial rounds, for any width and rate.
+#[derive(Clone, Copy, Debug)]
+pub struct Pow5Spec<const R_F: usize, const R_P: usize>;
+
+impl<F: FieldExt, const T: usize, const RATE: usize, const R_F: usize, const R_P: usize>
+    Spec<F, T, RATE> for Pow5Spec<R_F, R_P>
+{
+    fn full_rounds() -> usize {
+        R_F
+    }
+
+    fn partial_rounds() -> usize {
+        R_P
+    }
+}
+
+/// Poseidon-128 with the `x^5` S-box, for width 3 and rate 2 over the Pasta
+/// fields.
+pub type P128Pow5T3 = Pow5Spec<8, 56>;
+
+/// The set of circuit instructions required to use the Poseidon sponge.
+pub trait PoseidonInstructions<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>:
+    Chip<F>
+{
+    /// A word of the Poseidon state.
+    type Word: Copy + fmt::Debug;
+
+    /// Assigns the initial state of the sponge, with the capacity word set to
+    /// `2^64` and the rate words set to zero.
+    fn initial_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<[Self::Word; T], Error>;
+
+    /// Adds `input` to the rate words of `state`.
+    ///
+    /// If `pad` is set, the input is followed by a single `1`, so `input` must
+    /// be shorter than `RATE`; otherwise it can have up to `RATE` words. Any
+    /// remaining rate words are left unchanged.
+    fn absorb(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; T],
+        input: &[Self::Word],
+        pad: bool,
+    ) -> Result<[Self::Word; T], Error>;
+
+    /// Applies the Poseidon permutation to `state`.
+    fn permute(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; T],
+    ) -> Result<[Self::Word; T], Error>;
+}
+
+/// A Poseidon hasher, absorbing words with [`Hasher::update`] and producing a
+/// digest with [`Hasher::squeeze`], in the same way as the native
+/// `poseidon::Poseidon` hasher.
+#[derive(Debug)]
+pub struct Hasher<
+    F: FieldExt,
+    PoseidonChip: PoseidonInstructions<F, S, T, RATE>,
+    S: Spec<F, T, RATE>,
+    const T: usize,
+    const RATE: usize,
+> {
+    chip: PoseidonChip,
+    state: [PoseidonChip::Word; T],
+    absorbing: Vec<PoseidonChip::Word>,
+    _marker: PhantomData<(F, S)>,
+}
+
+impl<
+        F: FieldExt,
+        PoseidonChip: PoseidonInstructions<F, S, T, RATE>,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+    > Hasher<F, PoseidonChip, S, T, RATE>
+{
+    /// Creates a hasher with a fresh sponge state.
+    pub fn new(
+        chip: PoseidonChip,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<Self, Error> {
+        let state = chip.initial_state(region, offset)?;
+        Ok(Hasher {
+            chip,
+            state,
+            absorbing: Vec::new(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Appends `words` to the input, permuting the state for every `RATE`
+    /// words absorbed.
+    pub fn update(
+        &mut self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        words: &[PoseidonChip::Word],
+    ) -> Result<(), Error> {
+        self.absorbing.extend_from_slice(words);
+        while self.absorbing.len() >= RATE {
+            let chunk: Vec<_> = self.absorbing.drain(..RATE).collect();
+            let state = self
+                .chip
+                .absorb(region, offset, &self.state, &chunk, false)?;
+            self.state = self.chip.permute(region, offset, &state)?;
+        }
+        Ok(())
+    }
+
+    /// Pads and absorbs the pending input, and returns the digest.
+    pub fn squeeze(
+        &mut self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<PoseidonChip::Word, Error> {
+        let chunk = std::mem::take(&mut self.absorbing);
+        let state = self
+            .chip
+            .absorb(region, offset, &self.state, &chunk, true)?;
+        self.state = self.chip.permute(region, offset, &state)?;
+        Ok(self.state[1])
+    }
+
+    /// Hashes `words` with a fresh hasher.
+    pub fn hash(
+        chip: PoseidonChip,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        words: &[PoseidonChip::Word],
+    ) -> Result<PoseidonChip::Word, Error> {
+        let mut hasher = Self::new(chip, region, offset)?;
+        hasher.update(region, offset, words)?;
+        hasher.squeeze(region, offset)
+    }
+}
@@ -0,0 +1,530 @@
+use std::convert::TryInto;
+use std::iter;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use super::{Mds, PoseidonInstructions, Spec};
+use crate::utilities::CellValue;
+
+/// Configuration for a [`Pow5Chip`].
+#[derive(Clone, Debug)]
+pub struct Pow5Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    state: [Column<Advice>; WIDTH],
+    partial_sbox: Column<Advice>,
+    rc_a: [Column<Fixed>; WIDTH],
+    rc_b: [Column<Fixed>; WIDTH],
+    s_full: Selector,
+    s_partial: Selector,
+    s_pad_and_add: Selector,
+
+    half_full_rounds: usize,
+    half_partial_rounds: usize,
+    round_constants: Vec<[F; WIDTH]>,
+    mds: Mds<F, WIDTH>,
+}
+
+/// A Poseidon chip using an `x^5` S-box.
+///
+/// The permutation of a `WIDTH`-word state takes `R_F + R_P / 2 + 1` rows: one
+/// row per full round, one row per pair of partial rounds, and a row for the
+/// output. Absorbing inputs takes three rows.
+#[derive(Clone, Debug)]
+pub struct Pow5Chip<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    config: Pow5Config<F, WIDTH, RATE>,
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// The state columns are equality-enabled. The chip assigns the initial
+    /// state and the padding from constants, so the circuit must also enable a
+    /// fixed column for constants via `ConstraintSystem::enable_constant`. That
+    /// column must not be one of `rc_a` or `rc_b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `RATE` is not `WIDTH - 1`, or if the spec has an odd number of
+    /// full or partial rounds.
+    pub fn configure<S: Spec<F, WIDTH, RATE>>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        partial_sbox: Column<Advice>,
+        rc_a: [Column<Fixed>; WIDTH],
+        rc_b: [Column<Fixed>; WIDTH],
+    ) -> Pow5Config<F, WIDTH, RATE> {
+        assert_eq!(RATE, WIDTH - 1, "the rate must be one less than the width");
+        assert_eq!(S::full_rounds() % 2, 0, "full rounds must be even");
+        assert_eq!(
+            S::partial_rounds() % 2,
+            0,
+            "partial rounds are applied in pairs, so must be even"
+        );
+        let half_full_rounds = S::full_rounds() / 2;
+        let half_partial_rounds = S::partial_rounds() / 2;
+        let (round_constants, mds, mds_inv) = S::constants();
+
+        for column in state.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let s_full = meta.selector();
+        let s_partial = meta.selector();
+        let s_pad_and_add = meta.selector();
+
+        let pow_5 = |v: Expression<F>| {
+            let v2 = v.clone() * v.clone();
+            v2.clone() * v2 * v
+        };
+
+        meta.create_gate("full round", |meta| {
+            let s_full = meta.query_selector(s_full);
+
+            (0..WIDTH)
+                .map(|next_idx| {
+                    let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                    let expr = (0..WIDTH).fold(Expression::Constant(F::zero()), |acc, idx| {
+                        let state_cur = meta.query_advice(state[idx], Rotation::cur());
+                        let rc_a = meta.query_fixed(rc_a[idx], Rotation::cur());
+                        acc + pow_5(state_cur + rc_a) * mds[next_idx][idx]
+                    });
+                    s_full.clone() * (expr - state_next)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("partial rounds", |meta| {
+            let cur_0 = meta.query_advice(state[0], Rotation::cur());
+            let mid_0 = meta.query_advice(partial_sbox, Rotation::cur());
+            let rc_a0 = meta.query_fixed(rc_a[0], Rotation::cur());
+            let rc_b0 = meta.query_fixed(rc_b[0], Rotation::cur());
+            let s_partial = meta.query_selector(s_partial);
+
+            // The state after the first of the two partial rounds.
+            let mid = |idx: usize, meta: &mut VirtualCells<'_, F>| {
+                (1..WIDTH).fold(mid_0.clone() * mds[idx][0], |acc, cur_idx| {
+                    let cur = meta.query_advice(state[cur_idx], Rotation::cur());
+                    let rc_a = meta.query_fixed(rc_a[cur_idx], Rotation::cur());
+                    acc + (cur + rc_a) * mds[idx][cur_idx]
+                })
+            };
+
+            // The state after the second partial round, before its MDS layer.
+            let next = |idx: usize, meta: &mut VirtualCells<'_, F>| {
+                (0..WIDTH).fold(Expression::Constant(F::zero()), |acc, next_idx| {
+                    let next = meta.query_advice(state[next_idx], Rotation::next());
+                    acc + next * mds_inv[idx][next_idx]
+                })
+            };
+
+            let partial_round_linear = |idx: usize, meta: &mut VirtualCells<'_, F>| {
+                let rc_b = meta.query_fixed(rc_b[idx], Rotation::cur());
+                mid(idx, meta) + rc_b - next(idx, meta)
+            };
+
+            let first = pow_5(cur_0 + rc_a0) - mid_0.clone();
+            let second = pow_5(mid(0, meta) + rc_b0) - next(0, meta);
+            let linear: Vec<_> = (1..WIDTH)
+                .map(|idx| partial_round_linear(idx, meta))
+                .collect();
+
+            iter::empty()
+                .chain(Some(first))
+                .chain(Some(second))
+                .chain(linear)
+                .map(|constraint| s_partial.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("pad-and-add", |meta| {
+            let s_pad_and_add = meta.query_selector(s_pad_and_add);
+
+            let capacity = meta.query_advice(state[0], Rotation::prev())
+                - meta.query_advice(state[0], Rotation::next());
+            let rate: Vec<_> = (0..RATE)
+                .map(|idx| {
+                    let initial = meta.query_advice(state[idx + 1], Rotation::prev());
+                    let input = meta.query_advice(state[idx], Rotation::cur());
+                    let output = meta.query_advice(state[idx + 1], Rotation::next());
+                    initial + input - output
+                })
+                .collect();
+
+            iter::once(capacity)
+                .chain(rate)
+                .map(|constraint| s_pad_and_add.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+
+        Pow5Config {
+            state,
+            partial_sbox,
+            rc_a,
+            rc_b,
+            s_full,
+            s_partial,
+            s_pad_and_add,
+            half_full_rounds,
+            half_partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: Pow5Config<F, WIDTH, RATE>) -> Self {
+        Pow5Chip { config }
+    }
+
+    /// Returns the number of rows used by [`PoseidonInstructions::permute`].
+    pub fn permutation_rows(&self) -> usize {
+        2 * self.config.half_full_rounds + self.config.half_partial_rounds + 1
+    }
+
+    fn full_round(&self, state: [F; WIDTH], rc: &[F; WIDTH]) -> [F; WIDTH] {
+        let mut sboxed = [F::zero(); WIDTH];
+        for ((sboxed, word), rc) in sboxed.iter_mut().zip(state.iter()).zip(rc.iter()) {
+            *sboxed = pow_5(*word + rc);
+        }
+        mds_mul(&self.config.mds, &sboxed)
+    }
+
+    fn partial_round(&self, state: [F; WIDTH], rc: &[F; WIDTH]) -> [F; WIDTH] {
+        let mut sboxed = [F::zero(); WIDTH];
+        for ((sboxed, word), rc) in sboxed.iter_mut().zip(state.iter()).zip(rc.iter()) {
+            *sboxed = *word + rc;
+        }
+        sboxed[0] = pow_5(sboxed[0]);
+        mds_mul(&self.config.mds, &sboxed)
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; WIDTH]>,
+    ) -> Result<[CellValue<F>; WIDTH], Error> {
+        let words = (0..WIDTH)
+            .map(|idx| {
+                CellValue::assign(
+                    region,
+                    self.config.state[idx],
+                    offset,
+                    state.map(|state| state[idx]),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(to_array(words))
+    }
+
+    fn assign_round_constants(
+        region: &mut Region<'_, F>,
+        columns: &[Column<Fixed>; WIDTH],
+        offset: usize,
+        rc: &[F; WIDTH],
+    ) {
+        for (column, rc) in columns.iter().zip(rc.iter()) {
+            region.assign_fixed(*column, offset, *rc);
+        }
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Chip<F> for Pow5Chip<F, WIDTH, RATE> {
+    type Config = Pow5Config<F, WIDTH, RATE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    PoseidonInstructions<F, S, WIDTH, RATE> for Pow5Chip<F, WIDTH, RATE>
+{
+    type Word = CellValue<F>;
+
+    fn initial_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<[Self::Word; WIDTH], Error> {
+        let words = (0..WIDTH)
+            .map(|idx| {
+                let value = if idx == 0 {
+                    F::from_u128(1 << 64)
+                } else {
+                    F::zero()
+                };
+                CellValue::assign_constant(region, self.config.state[idx], *offset, value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += 1;
+
+        Ok(to_array(words))
+    }
+
+    fn absorb(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; WIDTH],
+        input: &[Self::Word],
+        pad: bool,
+    ) -> Result<[Self::Word; WIDTH], Error> {
+        if pad {
+            assert!(input.len() < RATE, "no room for padding after the input");
+        } else {
+            assert!(input.len() <= RATE, "input is longer than the rate");
+        }
+        let config = &self.config;
+        let row = *offset;
+
+        // Copy the current state into the first row.
+        let initial = (0..WIDTH)
+            .map(|idx| state[idx].copy_advice(region, config.state[idx], row))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Lay out the input words, followed by the padding, in the second row.
+        config.s_pad_and_add.enable(region, row + 1)?;
+        let rate = (0..RATE)
+            .map(|idx| {
+                let column = config.state[idx];
+                if let Some(word) = input.get(idx) {
+                    word.copy_advice(region, column, row + 1)
+                } else if pad && idx == input.len() {
+                    CellValue::assign_constant(region, column, row + 1, F::one())
+                } else {
+                    CellValue::assign_constant(region, column, row + 1, F::zero())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Add the input to the rate words in the third row.
+        let output = (0..WIDTH)
+            .map(|idx| {
+                let value = if idx == 0 {
+                    initial[0].value()
+                } else {
+                    initial[idx].value() + rate[idx - 1].value()
+                };
+                CellValue::assign(region, config.state[idx], row + 2, value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += 3;
+
+        Ok(to_array(output))
+    }
+
+    fn permute(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; WIDTH],
+    ) -> Result<[Self::Word; WIDTH], Error> {
+        let config = &self.config;
+        let mut row = *offset;
+
+        // Copy the input state into the first row.
+        let mut words = to_array(
+            (0..WIDTH)
+                .map(|idx| state[idx].copy_advice(region, config.state[idx], row))
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+        let mut values: Value<[F; WIDTH]> = words
+            .iter()
+            .map(|word| word.value())
+            .collect::<Value<Vec<_>>>()
+            .map(to_array);
+
+        let first_full_rounds = 0..config.half_full_rounds;
+        let last_full_rounds = (config.half_full_rounds + 2 * config.half_partial_rounds)
+            ..config.round_constants.len();
+
+        for round in first_full_rounds {
+            let rc = config.round_constants[round];
+            config.s_full.enable(region, row)?;
+            Self::assign_round_constants(region, &config.rc_a, row, &rc);
+            values = values.map(|state| self.full_round(state, &rc));
+            row += 1;
+            words = self.assign_state(region, row, values)?;
+        }
+
+        for pair in 0..config.half_partial_rounds {
+            let round = config.half_full_rounds + 2 * pair;
+            let (rc_a, rc_b) = (
+                config.round_constants[round],
+                config.round_constants[round + 1],
+            );
+            config.s_partial.enable(region, row)?;
+            Self::assign_round_constants(region, &config.rc_a, row, &rc_a);
+            Self::assign_round_constants(region, &config.rc_b, row, &rc_b);
+            region.assign_advice(
+                config.partial_sbox,
+                row,
+                values.map(|state| pow_5(state[0] + rc_a[0])),
+            )?;
+            values = values.map(|state| {
+                let mid = self.partial_round(state, &rc_a);
+                self.partial_round(mid, &rc_b)
+            });
+            row += 1;
+            words = self.assign_state(region, row, values)?;
+        }
+
+        for round in last_full_rounds {
+            let rc = config.round_constants[round];
+            config.s_full.enable(region, row)?;
+            Self::assign_round_constants(region, &config.rc_a, row, &rc);
+            values = values.map(|state| self.full_round(state, &rc));
+            row += 1;
+            words = self.assign_state(region, row, values)?;
+        }
+        *offset = row + 1;
+
+        Ok(words)
+    }
+}
+
+fn pow_5<F: FieldExt>(x: F) -> F {
+    x.square().square() * x
+}
+
+fn mds_mul<F: FieldExt, const WIDTH: usize>(mds: &Mds<F, WIDTH>, state: &[F; WIDTH]) -> [F; WIDTH] {
+    let mut result = [F::zero(); WIDTH];
+    for (result, row) in result.iter_mut().zip(mds.iter()) {
+        for (m, word) in row.iter().zip(state.iter()) {
+            *result += *m * word;
+        }
+    }
+    result
+}
+
+fn to_array<T: std::fmt::Debug, const N: usize>(v: Vec<T>) -> [T; N] {
+    v.try_into().expect("length is checked by the caller")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::{bn256, pasta::Fp};
+
+    use super::{Pow5Chip, Pow5Config};
+    use crate::poseidon::{Hasher, P128Pow5T3, Pow5Spec, Spec};
+    use crate::utilities::CellValue;
+
+    struct HashCircuit<F: FieldExt, S, const WIDTH: usize, const RATE: usize> {
+        message: Value<Vec<F>>,
+        len: usize,
+        _spec: PhantomData<S>,
+    }
+
+    impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Circuit<F>
+        for HashCircuit<F, S, WIDTH, RATE>
+    {
+        type Config = (Pow5Config<F, WIDTH, RATE>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            HashCircuit {
+                message: Value::unknown(),
+                len: self.len,
+                _spec: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let state = [(); WIDTH].map(|_| meta.advice_column());
+            let partial_sbox = meta.advice_column();
+            let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+            let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let digest = meta.instance_column();
+            meta.enable_equality(digest);
+
+            (
+                Pow5Chip::configure::<S>(meta, state, partial_sbox, rc_a, rc_b),
+                digest,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, digest): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Pow5Chip::construct(config.clone());
+            let output = layouter.assign_region(
+                || "hash",
+                |mut region| {
+                    let message = (0..self.len)
+                        .map(|i| {
+                            CellValue::assign(
+                                &mut region,
+                                config.state[0],
+                                i,
+                                self.message.as_ref().map(|message| message[i]),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let mut offset = self.len;
+                    Hasher::<_, _, S, WIDTH, RATE>::hash(chip, &mut region, &mut offset, &message)
+                },
+            )?;
+            layouter.constrain_instance(output.cell(), digest, 0);
+            Ok(())
+        }
+    }
+
+    fn check<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>(
+        k: u32,
+        message: Vec<F>,
+    ) {
+        let mut native = ::poseidon::Poseidon::<F, WIDTH, RATE>::new(
+            <S as Spec<F, WIDTH, RATE>>::full_rounds(),
+            <S as Spec<F, WIDTH, RATE>>::partial_rounds(),
+        );
+        native.update(&message);
+        let expected = native.squeeze();
+
+        let circuit = HashCircuit::<F, S, WIDTH, RATE> {
+            len: message.len(),
+            message: Value::known(message),
+            _spec: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![expected + F::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn p128_pow5_t3_matches_native() {
+        for len in 0..5 {
+            let message = (0..len).map(|i| Fp::from(i as u64 + 1)).collect();
+            check::<Fp, P128Pow5T3, 3, 2>(8, message);
+        }
+    }
+
+    #[test]
+    fn wider_state_matches_native() {
+        let message = (0..6).map(|i| bn256::Fr::from(i as u64)).collect();
+        check::<bn256::Fr, Pow5Spec<8, 60>, 5, 4>(8, message);
+    }
+}
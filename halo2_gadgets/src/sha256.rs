@@ -0,0 +1,151 @@
+//! The SHA-256 compression function.
+//!
+//! The chip represents each 32-bit word both in dense form and in "spread"
+//! form, where bit `i` of the word is moved to bit `2i`. Adding spread words
+//! computes every bit position independently: the even bits of the sum of
+//! three spread words are their XOR, and the odd bits are their majority. The
+//! spread forms of small chunks are looked up in a fixed table, so the bitwise
+//! functions of SHA-256 cost a handful of rows each instead of a gate per bit.
+
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::Error,
+};
+
+mod chip;
+pub use chip::{Sha256Chip, Sha256Config, Sha256Word};
+
+/// The number of words in a block.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The number of words in the state, and in a digest.
+pub const DIGEST_SIZE: usize = 8;
+
+/// The initial state of SHA-256.
+pub const IV: [u32; DIGEST_SIZE] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+/// The round constants of SHA-256.
+pub const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// The set of circuit instructions required to use the SHA-256 compression
+/// function.
+pub trait Sha256Instructions<F: FieldExt>: Chip<F> {
+    /// A 32-bit word.
+    type Word: Clone + Debug;
+
+    /// Witnesses a word, constraining it to 32 bits.
+    fn witness_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<u32>,
+    ) -> Result<Self::Word, Error>;
+
+    /// Assigns the initial state [`IV`].
+    fn initial_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<[Self::Word; DIGEST_SIZE], Error>;
+
+    /// Applies the compression function to `state` and a 512-bit `block`,
+    /// returning the next state.
+    fn compress(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; DIGEST_SIZE],
+        block: &[Self::Word; BLOCK_SIZE],
+    ) -> Result<[Self::Word; DIGEST_SIZE], Error>;
+}
+
+/// Computes the SHA-256 digest of `blocks`, which must already be padded.
+pub fn digest<F: FieldExt, Sha256Chip: Sha256Instructions<F>>(
+    chip: &Sha256Chip,
+    region: &mut Region<'_, F>,
+    offset: &mut usize,
+    blocks: &[[Sha256Chip::Word; BLOCK_SIZE]],
+) -> Result<[Sha256Chip::Word; DIGEST_SIZE], Error> {
+    let mut state = chip.initial_state(region, offset)?;
+    for block in blocks {
+        state = chip.compress(region, offset, &state, block)?;
+    }
+    Ok(state)
+}
@@ -0,0 +1,746 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::{Sha256Instructions, BLOCK_SIZE, DIGEST_SIZE, IV, ROUND_CONSTANTS};
+use crate::utilities::CellValue;
+
+/// The widest chunk in the spread table.
+const MAX_CHUNK_BITS: usize = 11;
+
+/// The number of accumulators in each row.
+const ACCUMULATORS: usize = 4;
+
+/// The chunks a word is split into when it is not rotated.
+const WORD_CHUNKS: [usize; 3] = [11, 11, 10];
+
+/// The width of the carry of a sum of at most seven words.
+const CARRY_BITS: usize = 3;
+
+/// A right rotation or right shift of a word.
+#[derive(Clone, Copy, Debug)]
+enum Shift {
+    Rotr(usize),
+    Shr(usize),
+}
+
+impl Shift {
+    fn apply(self, value: u32) -> u32 {
+        match self {
+            Shift::Rotr(r) => value.rotate_right(r as u32),
+            Shift::Shr(n) => value >> n,
+        }
+    }
+
+    /// Returns the position that bit `p` is moved to, or `None` if it is
+    /// shifted out.
+    fn position(self, p: usize) -> Option<usize> {
+        match self {
+            Shift::Rotr(r) => Some((p + 32 - r) % 32),
+            Shift::Shr(n) => p.checked_sub(n),
+        }
+    }
+}
+
+/// The XOR of three shifts of a word. The word is split into chunks that do
+/// not cross any of the shift boundaries, so each chunk stays contiguous.
+#[derive(Debug)]
+struct Sigma {
+    shifts: [Shift; 3],
+    chunks: &'static [usize],
+}
+
+const UPPER_SIGMA_0: Sigma = Sigma {
+    shifts: [Shift::Rotr(2), Shift::Rotr(13), Shift::Rotr(22)],
+    chunks: &[2, 11, 9, 10],
+};
+
+const UPPER_SIGMA_1: Sigma = Sigma {
+    shifts: [Shift::Rotr(6), Shift::Rotr(11), Shift::Rotr(25)],
+    chunks: &[6, 5, 7, 7, 7],
+};
+
+const LOWER_SIGMA_0: Sigma = Sigma {
+    shifts: [Shift::Rotr(7), Shift::Rotr(18), Shift::Shr(3)],
+    chunks: &[3, 4, 11, 7, 7],
+};
+
+const LOWER_SIGMA_1: Sigma = Sigma {
+    shifts: [Shift::Rotr(17), Shift::Rotr(19), Shift::Shr(10)],
+    chunks: &[10, 7, 2, 7, 6],
+};
+
+/// Moves bit `i` of `value` to bit `2i`.
+fn spread_word(value: u32) -> u64 {
+    (0..32).fold(0, |acc, i| acc | (((value as u64 >> i) & 1) << (2 * i)))
+}
+
+fn pow2<F: FieldExt>(n: usize) -> F {
+    F::from_u128(1 << n)
+}
+
+/// Splits `value` into chunks of the given widths, returning the position,
+/// width and value of each chunk.
+fn chunks(value: Value<u32>, widths: &[usize]) -> Vec<(usize, usize, Value<u32>)> {
+    let mut position = 0;
+    widths
+        .iter()
+        .map(|&bits| {
+            let p = position;
+            position += bits;
+            let mask = ((1u64 << bits) - 1) as u32;
+            (p, bits, value.map(|v| (v >> p) & mask))
+        })
+        .collect()
+}
+
+/// A term assigned in the `dense` and `spread` columns of a row.
+#[derive(Clone, Copy, Debug)]
+enum Term<F: FieldExt> {
+    /// A copy of an assigned cell. The spread value is zero.
+    Cell(CellValue<F>),
+    /// A constant. The spread value is zero.
+    Constant(F),
+    /// A chunk of `bits` bits and its spread form, looked up in the table.
+    Chunk { bits: usize, value: Value<u32> },
+}
+
+/// A row adding the dense and spread values of its term, each multiplied by a
+/// fixed coefficient, to every accumulator.
+#[derive(Debug)]
+struct Row<F: FieldExt> {
+    term: Term<F>,
+    coeffs: [(F, F); ACCUMULATORS],
+}
+
+impl<F: FieldExt> Row<F> {
+    fn new(term: Term<F>) -> Self {
+        Row {
+            term,
+            coeffs: [(F::zero(), F::zero()); ACCUMULATORS],
+        }
+    }
+
+    fn dense(mut self, acc: usize, coeff: F) -> Self {
+        self.coeffs[acc].0 += coeff;
+        self
+    }
+
+    fn spread(mut self, acc: usize, coeff: F) -> Self {
+        self.coeffs[acc].1 += coeff;
+        self
+    }
+}
+
+/// Returns rows subtracting `spread(even) + 2 * spread(odd)` from the
+/// accumulator `acc`, optionally adding `even` or `odd` to another
+/// accumulator.
+fn split_rows<F: FieldExt>(
+    acc: usize,
+    even: Value<u32>,
+    odd: Value<u32>,
+    even_output: Option<usize>,
+    odd_output: Option<usize>,
+) -> Vec<Row<F>> {
+    let mut rows = vec![];
+    for (value, factor, output) in [(even, 1, even_output), (odd, 2, odd_output)] {
+        for (p, bits, chunk) in chunks(value, &WORD_CHUNKS) {
+            let mut row = Row::new(Term::Chunk { bits, value: chunk })
+                .spread(acc, -pow2::<F>(2 * p) * F::from(factor));
+            if let Some(output) = output {
+                row = row.dense(output, pow2(p));
+            }
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+fn word_value<F: FieldExt>(cell: &CellValue<F>) -> Value<u32> {
+    cell.value().map(|value| value.get_lower_32())
+}
+
+/// A 32-bit word assigned in the circuit, together with its spread form.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Word<F: FieldExt> {
+    dense: CellValue<F>,
+    spread: CellValue<F>,
+}
+
+impl<F: FieldExt> Sha256Word<F> {
+    /// Returns the cell holding the word.
+    pub fn dense(&self) -> CellValue<F> {
+        self.dense
+    }
+
+    /// Returns the value of the word, if known.
+    pub fn value(&self) -> Value<u32> {
+        word_value(&self.dense)
+    }
+}
+
+/// Configuration for a [`Sha256Chip`].
+#[derive(Clone, Debug)]
+pub struct Sha256Config<F: FieldExt> {
+    dense: Column<Advice>,
+    spread: Column<Advice>,
+    accumulators: [Column<Advice>; ACCUMULATORS],
+    coeffs: [[Column<Fixed>; 2]; ACCUMULATORS],
+    bits: Column<Fixed>,
+    table: [TableColumn; 3],
+    q_acc: Selector,
+    q_lookup: Selector,
+    _marker: PhantomData<F>,
+}
+
+/// A chip implementing [`Sha256Instructions`].
+///
+/// Every operation is a run of rows that add fixed multiples of their terms
+/// to four accumulators. A term is either a copied word, a constant, or a
+/// chunk of at most 11 bits whose spread form is looked up in the spread
+/// table. Accumulators start at zero, and at the end of a run are either
+/// constrained to zero or returned as outputs. For example, `x + y` is
+/// computed by adding `x` and `y` to an accumulator and subtracting the
+/// chunks of the result and the carry, which must bring it back to zero,
+/// while the chunks are also added to a second accumulator that outputs the
+/// result.
+///
+/// A compression takes about 6,700 rows, so it fits in a circuit with
+/// `k = 13`.
+#[derive(Clone, Debug)]
+pub struct Sha256Chip<F: FieldExt> {
+    config: Sha256Config<F>,
+}
+
+impl<F: FieldExt> Chip<F> for Sha256Chip<F> {
+    type Config = Sha256Config<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> Sha256Chip<F> {
+    /// Configures this chip for use in a circuit. The chip allocates its own
+    /// fixed columns for the coefficients of each row.
+    ///
+    /// The circuit must also enable a fixed column via
+    /// `ConstraintSystem::enable_constant`, and load the spread table with
+    /// [`Sha256Chip::load`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 6],
+        table: [TableColumn; 3],
+    ) -> Sha256Config<F> {
+        let [dense, spread, acc_0, acc_1, acc_2, acc_3] = advices;
+        let accumulators = [acc_0, acc_1, acc_2, acc_3];
+        meta.enable_equality(dense);
+        for column in accumulators.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let coeffs = [(); ACCUMULATORS].map(|_| [meta.fixed_column(), meta.fixed_column()]);
+        let bits = meta.fixed_column();
+        let q_acc = meta.selector();
+        let q_lookup = meta.complex_selector();
+
+        meta.create_gate("accumulate", |meta| {
+            let q_acc = meta.query_selector(q_acc);
+            let dense = meta.query_advice(dense, Rotation::cur());
+            let spread = meta.query_advice(spread, Rotation::cur());
+
+            accumulators
+                .iter()
+                .zip(coeffs.iter())
+                .map(|(acc, [c_dense, c_spread])| {
+                    let acc_next = meta.query_advice(*acc, Rotation::next());
+                    let acc = meta.query_advice(*acc, Rotation::cur());
+                    let c_dense = meta.query_fixed(*c_dense, Rotation::cur());
+                    let c_spread = meta.query_fixed(*c_spread, Rotation::cur());
+                    q_acc.clone()
+                        * (acc_next - acc - c_dense * dense.clone() - c_spread * spread.clone())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // Rows without the selector look up the first table row, (0, 0, 0).
+        meta.lookup("spread table", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let bits = meta.query_fixed(bits, Rotation::cur());
+            let dense = meta.query_advice(dense, Rotation::cur());
+            let spread = meta.query_advice(spread, Rotation::cur());
+
+            vec![
+                (q_lookup.clone() * bits, table[0]),
+                (q_lookup.clone() * dense, table[1]),
+                (q_lookup * spread, table[2]),
+            ]
+        });
+
+        Sha256Config {
+            dense,
+            spread,
+            accumulators,
+            coeffs,
+            bits,
+            table,
+            q_acc,
+            q_lookup,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: Sha256Config<F>) -> Self {
+        Sha256Chip { config }
+    }
+
+    /// Loads the spread table, which holds `(bits, x, spread(x))` for every
+    /// `x` of at most `bits` bits, for `bits` up to 11.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_table(
+            || "spread table",
+            |mut table| {
+                let entries = std::iter::once((0, 0)).chain(
+                    (1..=MAX_CHUNK_BITS)
+                        .flat_map(|bits| (0..(1u32 << bits)).map(move |x| (bits, x))),
+                );
+                for (row, (bits, x)) in entries.enumerate() {
+                    table.assign_cell(
+                        || "bits",
+                        config.table[0],
+                        row,
+                        || Value::known(F::from(bits as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "dense",
+                        config.table[1],
+                        row,
+                        || Value::known(F::from(x as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "spread",
+                        config.table[2],
+                        row,
+                        || Value::known(F::from(spread_word(x))),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns a run of rows, returning the final value of each accumulator.
+    /// Accumulators for which `outputs` is not set are constrained to end at
+    /// zero.
+    fn assign_rows(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        rows: &[Row<F>],
+        outputs: [bool; ACCUMULATORS],
+    ) -> Result<[CellValue<F>; ACCUMULATORS], Error> {
+        let config = &self.config;
+        let start = *offset;
+
+        let mut accs = config
+            .accumulators
+            .iter()
+            .map(|column| CellValue::assign_constant(region, *column, start, F::zero()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (idx, row) in rows.iter().enumerate() {
+            let r = start + idx;
+            config.q_acc.enable(region, r)?;
+            for ([c_dense, c_spread], (dense, spread)) in config.coeffs.iter().zip(row.coeffs) {
+                region.assign_fixed(*c_dense, r, dense);
+                region.assign_fixed(*c_spread, r, spread);
+            }
+
+            let dense = match row.term {
+                Term::Cell(cell) => cell.copy_advice(region, config.dense, r)?,
+                Term::Constant(constant) => {
+                    CellValue::assign_constant(region, config.dense, r, constant)?
+                }
+                Term::Chunk { bits, value } => {
+                    config.q_lookup.enable(region, r)?;
+                    region.assign_fixed(config.bits, r, F::from(bits as u64));
+                    CellValue::assign(region, config.dense, r, value.map(|v| F::from(v as u64)))?
+                }
+            }
+            .value();
+            let spread = match row.term {
+                Term::Chunk { value, .. } => value.map(|v| F::from(spread_word(v))),
+                _ => Value::known(F::zero()),
+            };
+            region.assign_advice(config.spread, r, spread)?;
+
+            let last = idx + 1 == rows.len();
+            for (k, acc) in accs.iter_mut().enumerate() {
+                let (c_dense, c_spread) = row.coeffs[k];
+                let next =
+                    acc.value() + dense * Value::known(c_dense) + spread * Value::known(c_spread);
+                *acc = if last && !outputs[k] {
+                    CellValue::assign_constant(region, config.accumulators[k], r + 1, F::zero())?
+                } else {
+                    CellValue::assign(region, config.accumulators[k], r + 1, next)?
+                };
+            }
+        }
+        *offset = start + rows.len() + 1;
+
+        Ok(accs.try_into().unwrap())
+    }
+
+    /// Returns `(words + constant) mod 2^32`. The sum of the inputs must be
+    /// less than `2^35`, which holds for up to seven words including the
+    /// constant.
+    fn add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        words: &[CellValue<F>],
+        constant: u32,
+    ) -> Result<Sha256Word<F>, Error> {
+        assert!(words.len() < 7);
+        let sum = words
+            .iter()
+            .fold(Value::known(constant as u64), |acc, word| {
+                acc + word_value(word).map(u64::from)
+            });
+
+        let mut rows: Vec<_> = words
+            .iter()
+            .map(|word| Row::new(Term::Cell(*word)).dense(0, F::one()))
+            .collect();
+        if constant != 0 {
+            rows.push(Row::new(Term::Constant(F::from(constant as u64))).dense(0, F::one()));
+        }
+        for (p, bits, chunk) in chunks(sum.map(|sum| sum as u32), &WORD_CHUNKS) {
+            rows.push(
+                Row::new(Term::Chunk { bits, value: chunk })
+                    .dense(0, -pow2::<F>(p))
+                    .dense(1, pow2(p))
+                    .spread(2, pow2(2 * p)),
+            );
+        }
+        let carry = sum.map(|sum| (sum >> 32) as u32);
+        rows.push(
+            Row::new(Term::Chunk {
+                bits: CARRY_BITS,
+                value: carry,
+            })
+            .dense(0, -pow2::<F>(32)),
+        );
+
+        let [_, dense, spread, _] =
+            self.assign_rows(region, offset, &rows, [false, true, true, false])?;
+        Ok(Sha256Word { dense, spread })
+    }
+
+    /// Returns the XOR of the three shifts of `word` described by `sigma`.
+    fn sigma(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        word: &Sha256Word<F>,
+        sigma: &Sigma,
+    ) -> Result<CellValue<F>, Error> {
+        let value = word.value();
+        let shifted = value.map(|v| sigma.shifts.map(|shift| shift.apply(v)));
+        let even = shifted.map(|[a, b, c]| a ^ b ^ c);
+        let odd = shifted.map(|[a, b, c]| (a & b) ^ (a & c) ^ (b & c));
+
+        // The spread forms of the three shifts are summed in accumulator 1.
+        let mut rows = vec![Row::new(Term::Cell(word.dense)).dense(0, F::one())];
+        for (p, bits, chunk) in chunks(value, sigma.chunks) {
+            let coeff = sigma
+                .shifts
+                .iter()
+                .filter_map(|shift| shift.position(p))
+                .fold(F::zero(), |acc, q| acc + pow2::<F>(2 * q));
+            rows.push(
+                Row::new(Term::Chunk { bits, value: chunk })
+                    .dense(0, -pow2::<F>(p))
+                    .spread(1, coeff),
+            );
+        }
+        rows.extend(split_rows(1, even, odd, Some(2), None));
+
+        let [_, _, output, _] =
+            self.assign_rows(region, offset, &rows, [false, false, true, false])?;
+        Ok(output)
+    }
+
+    /// Returns `(e AND f) XOR (NOT e AND g)`.
+    fn ch(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        e: &Sha256Word<F>,
+        f: &Sha256Word<F>,
+        g: &Sha256Word<F>,
+    ) -> Result<CellValue<F>, Error> {
+        let (e_value, f_value, g_value) = (e.value(), f.value(), g.value());
+        let e_f = e_value.zip(f_value);
+        let not_e_g = e_value.map(|e| !e).zip(g_value);
+
+        // Accumulator 0 sums spread(e) + spread(f), and accumulator 1 sums
+        // spread(NOT e) + spread(g). The odd bits of each are the ANDs, which
+        // never overlap, so their sum is the XOR.
+        let mut rows = vec![
+            Row::new(Term::Cell(e.spread))
+                .dense(0, F::one())
+                .dense(1, -F::one()),
+            Row::new(Term::Cell(f.spread)).dense(0, F::one()),
+            Row::new(Term::Cell(g.spread)).dense(1, F::one()),
+            Row::new(Term::Constant(F::from(spread_word(u32::MAX)))).dense(1, F::one()),
+        ];
+        rows.extend(split_rows(
+            0,
+            e_f.map(|(e, f)| e ^ f),
+            e_f.map(|(e, f)| e & f),
+            None,
+            Some(2),
+        ));
+        rows.extend(split_rows(
+            1,
+            not_e_g.map(|(not_e, g)| not_e ^ g),
+            not_e_g.map(|(not_e, g)| not_e & g),
+            None,
+            Some(2),
+        ));
+
+        let [_, _, output, _] =
+            self.assign_rows(region, offset, &rows, [false, false, true, false])?;
+        Ok(output)
+    }
+
+    /// Returns the bitwise majority of `a`, `b` and `c`.
+    fn maj(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Sha256Word<F>,
+        b: &Sha256Word<F>,
+        c: &Sha256Word<F>,
+    ) -> Result<CellValue<F>, Error> {
+        let values = a.value().zip(b.value()).zip(c.value());
+
+        let mut rows: Vec<_> = [a, b, c]
+            .iter()
+            .map(|word| Row::new(Term::Cell(word.spread)).dense(0, F::one()))
+            .collect();
+        rows.extend(split_rows(
+            0,
+            values.map(|((a, b), c)| a ^ b ^ c),
+            values.map(|((a, b), c)| (a & b) ^ (a & c) ^ (b & c)),
+            None,
+            Some(1),
+        ));
+
+        let [_, output, _, _] =
+            self.assign_rows(region, offset, &rows, [false, true, false, false])?;
+        Ok(output)
+    }
+}
+
+impl<F: FieldExt> Sha256Instructions<F> for Sha256Chip<F> {
+    type Word = Sha256Word<F>;
+
+    fn witness_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<u32>,
+    ) -> Result<Self::Word, Error> {
+        let cell = CellValue::assign(
+            region,
+            self.config.dense,
+            *offset,
+            value.map(|v| F::from(v as u64)),
+        )?;
+        *offset += 1;
+        self.add(region, offset, &[cell], 0)
+    }
+
+    fn initial_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+    ) -> Result<[Self::Word; DIGEST_SIZE], Error> {
+        let state = IV
+            .iter()
+            .map(|iv| self.add(region, offset, &[], *iv))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(state.try_into().unwrap())
+    }
+
+    fn compress(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        state: &[Self::Word; DIGEST_SIZE],
+        block: &[Self::Word; BLOCK_SIZE],
+    ) -> Result<[Self::Word; DIGEST_SIZE], Error> {
+        // Message schedule.
+        let mut w = block.to_vec();
+        for t in BLOCK_SIZE..ROUND_CONSTANTS.len() {
+            let s0 = self.sigma(region, offset, &w[t - 15], &LOWER_SIGMA_0)?;
+            let s1 = self.sigma(region, offset, &w[t - 2], &LOWER_SIGMA_1)?;
+            let word = self.add(
+                region,
+                offset,
+                &[s1, w[t - 7].dense, s0, w[t - 16].dense],
+                0,
+            )?;
+            w.push(word);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (k, w) in ROUND_CONSTANTS.iter().zip(w.iter()) {
+            let s1 = self.sigma(region, offset, &e, &UPPER_SIGMA_1)?;
+            let ch = self.ch(region, offset, &e, &f, &g)?;
+            let s0 = self.sigma(region, offset, &a, &UPPER_SIGMA_0)?;
+            let maj = self.maj(region, offset, &a, &b, &c)?;
+
+            let new_e = self.add(region, offset, &[d.dense, h.dense, s1, ch, w.dense], *k)?;
+            let new_a = self.add(region, offset, &[h.dense, s1, ch, w.dense, s0, maj], *k)?;
+
+            h = g;
+            g = f;
+            f = e;
+            e = new_e;
+            d = c;
+            c = b;
+            b = a;
+            a = new_a;
+        }
+
+        let next = state
+            .iter()
+            .zip([a, b, c, d, e, f, g, h].iter())
+            .map(|(x, y)| self.add(region, offset, &[x.dense, y.dense], 0))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(next.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::{spread_word, Sha256Chip, Sha256Config};
+    use crate::sha256::{digest, Sha256Instructions, BLOCK_SIZE};
+
+    const K: u32 = 13;
+
+    struct Sha256Circuit {
+        block: [Value<u32>; BLOCK_SIZE],
+    }
+
+    impl Circuit<Fp> for Sha256Circuit {
+        type Config = (Sha256Config<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Sha256Circuit {
+                block: [Value::unknown(); BLOCK_SIZE],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 6].map(|_| meta.advice_column());
+            let table = [(); 3].map(|_| meta.lookup_table_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (Sha256Chip::configure(meta, advices, table), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Sha256Chip::construct(config);
+            chip.load(&mut layouter)?;
+
+            let output = layouter.assign_region(
+                || "sha256",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let block = self
+                        .block
+                        .iter()
+                        .map(|word| chip.witness_word(region, offset, *word))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    digest(&chip, region, offset, &[block.try_into().unwrap()])
+                },
+            )?;
+            for (row, word) in output.iter().enumerate() {
+                layouter.constrain_instance(word.dense().cell(), instance, row);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spread() {
+        assert_eq!(spread_word(0), 0);
+        assert_eq!(spread_word(0b1011), 0b1000101);
+        assert_eq!(spread_word(u32::MAX), 0x5555_5555_5555_5555);
+    }
+
+    #[test]
+    fn sha256_abc() {
+        // "abc", padded to a single block.
+        let mut block = [0; BLOCK_SIZE];
+        block[0] = 0x6162_6380;
+        block[15] = 24;
+        let expected: Vec<_> = [
+            0xba78_16bf,
+            0x8f01_cfea,
+            0x4141_40de,
+            0x5dae_2223,
+            0xb003_61a3,
+            0x9617_7a9c,
+            0xb410_ff61,
+            0xf200_15ad,
+        ]
+        .iter()
+        .map(|word: &u32| Fp::from(*word as u64))
+        .collect();
+
+        let circuit = Sha256Circuit {
+            block: block.map(Value::known),
+        };
+        let prover = MockProver::run(K, &circuit, vec![expected.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = expected;
+        wrong[7] += Fp::one();
+        let prover = MockProver::run(K, &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
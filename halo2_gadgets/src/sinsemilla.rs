@@ -0,0 +1,152 @@
+//! The Sinsemilla hash function over the Pallas curve.
+//!
+//! A message is split into `K`-bit chunks `m_1, ..., m_n`, and hashed by
+//! starting from a domain-specific point `Q` and computing
+//! `Acc = (Acc + S(m_i)) + Acc` for each chunk, where `S` is a table of `2^K`
+//! fixed generators. The chip looks the generators up in a fixed table, so each
+//! chunk costs a single row.
+
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    circuit::{Chip, Region, Value},
+    plonk::Error,
+};
+use halo2curves::pasta::pallas;
+
+use crate::ecc::EccInstructions;
+
+mod chip;
+pub mod primitives;
+pub use chip::{SinsemillaChip, SinsemillaConfig};
+
+/// The set of circuit instructions required to use the Sinsemilla hash
+/// function.
+pub trait SinsemillaInstructions: Chip<pallas::Base> {
+    /// A `K`-bit chunk of a message.
+    type Chunk: Copy + Debug;
+    /// The x-coordinate of a point.
+    type X: Copy + Debug;
+    /// A point on the Pallas curve.
+    type Point: Clone + Debug;
+
+    /// Witnesses `bits` as `K`-bit message chunks, padding the last chunk
+    /// with zeros. The chunks are range-checked when they are hashed.
+    fn witness_message(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        bits: &[Value<bool>],
+    ) -> Result<Vec<Self::Chunk>, Error>;
+
+    /// Hashes `message` to a point, starting from the initial point `q`.
+    fn hash_to_point(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        q: pallas::Affine,
+        message: &[Self::Chunk],
+    ) -> Result<Self::Point, Error>;
+
+    /// Returns the x-coordinate of `point`.
+    fn extract(point: &Self::Point) -> Self::X;
+}
+
+/// A domain in which messages are hashed with Sinsemilla.
+#[derive(Clone, Debug)]
+pub struct HashDomain<SinsemillaChip: SinsemillaInstructions> {
+    chip: SinsemillaChip,
+    q: pallas::Affine,
+}
+
+impl<SinsemillaChip: SinsemillaInstructions> HashDomain<SinsemillaChip> {
+    /// Constructs the domain with the given personalization.
+    pub fn new(chip: SinsemillaChip, domain: &str) -> Self {
+        HashDomain {
+            chip,
+            q: primitives::q(domain),
+        }
+    }
+
+    /// Hashes `message` to a point.
+    pub fn hash_to_point(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        message: &[SinsemillaChip::Chunk],
+    ) -> Result<SinsemillaChip::Point, Error> {
+        self.chip.hash_to_point(region, offset, self.q, message)
+    }
+
+    /// Hashes `message` to the x-coordinate of
+    /// [`HashDomain::hash_to_point`]. This is the form used for Merkle tree
+    /// nodes.
+    pub fn hash(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        message: &[SinsemillaChip::Chunk],
+    ) -> Result<SinsemillaChip::X, Error> {
+        let point = self.hash_to_point(region, offset, message)?;
+        Ok(SinsemillaChip::extract(&point))
+    }
+}
+
+/// A domain in which messages are committed to with Sinsemilla, blinded by a
+/// scalar multiple of a fixed base.
+#[derive(Clone, Debug)]
+pub struct CommitDomain<SinsemillaChip, EccChip>
+where
+    SinsemillaChip: SinsemillaInstructions,
+    EccChip: EccInstructions<pallas::Affine, Point = SinsemillaChip::Point>,
+{
+    m: HashDomain<SinsemillaChip>,
+    r: pallas::Affine,
+    ecc_chip: EccChip,
+}
+
+impl<SinsemillaChip, EccChip> CommitDomain<SinsemillaChip, EccChip>
+where
+    SinsemillaChip: SinsemillaInstructions,
+    EccChip: EccInstructions<pallas::Affine, Point = SinsemillaChip::Point>,
+{
+    /// Constructs the domain with the given personalization, matching
+    /// [`primitives::CommitDomain::new`].
+    pub fn new(sinsemilla_chip: SinsemillaChip, ecc_chip: EccChip, domain: &str) -> Self {
+        let native = primitives::CommitDomain::new(domain);
+        CommitDomain {
+            m: HashDomain {
+                chip: sinsemilla_chip,
+                q: native.hash_domain().q(),
+            },
+            r: native.r(),
+            ecc_chip,
+        }
+    }
+
+    /// Commits to `message` with the blinding factor `r`.
+    pub fn commit(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        message: &[SinsemillaChip::Chunk],
+        r: &EccChip::Scalar,
+    ) -> Result<SinsemillaChip::Point, Error> {
+        let hash = self.m.hash_to_point(region, offset, message)?;
+        let base = self.ecc_chip.constant_point(region, offset, self.r)?;
+        let blind = self.ecc_chip.mul(region, offset, r, &base)?;
+        self.ecc_chip.add(region, offset, &hash, &blind)
+    }
+
+    /// Commits to `message` and returns the x-coordinate of the commitment.
+    pub fn short_commit(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        message: &[SinsemillaChip::Chunk],
+        r: &EccChip::Scalar,
+    ) -> Result<SinsemillaChip::X, Error> {
+        let point = self.commit(region, offset, message, r)?;
+        Ok(SinsemillaChip::extract(&point))
+    }
+}
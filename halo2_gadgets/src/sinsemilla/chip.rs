@@ -0,0 +1,382 @@
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+use halo2curves::pasta::pallas;
+
+use super::{primitives, SinsemillaInstructions};
+use crate::{ecc::EccPoint, utilities::CellValue};
+
+/// Configuration for a [`SinsemillaChip`].
+#[derive(Clone, Debug)]
+pub struct SinsemillaConfig {
+    advices: [Column<Advice>; 9],
+    table: [TableColumn; 3],
+    q_sinsemilla: Selector,
+    generators: Vec<(pallas::Base, pallas::Base)>,
+}
+
+/// A chip implementing [`SinsemillaInstructions`].
+///
+/// Each message chunk takes one row, holding the accumulator `A`, the chunk
+/// `m`, the generator `P = S(m)`, the intermediate point `R = A + P` and the
+/// slopes of the two additions. The next row holds `A' = R + A`, so successive
+/// rows chain directly. `(m, P)` is looked up in the generator table, which
+/// also range-checks `m` to `K` bits.
+///
+/// The additions are incomplete: they do not handle the identity or inputs
+/// with equal x-coordinates. Finding a message that hits these cases is as
+/// hard as computing discrete logarithms between the generators, so honest
+/// provers never encounter them and malicious provers cannot exploit them.
+#[derive(Clone, Debug)]
+pub struct SinsemillaChip {
+    config: SinsemillaConfig,
+}
+
+impl Chip<pallas::Base> for SinsemillaChip {
+    type Config = SinsemillaConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+fn coordinates(point: &pallas::Affine) -> (pallas::Base, pallas::Base) {
+    let coordinates = point.coordinates().unwrap();
+    (*coordinates.x(), *coordinates.y())
+}
+
+impl SinsemillaChip {
+    /// Configures this chip for use in a circuit. The advice columns can be
+    /// shared with an [`EccChip`](crate::ecc::EccChip), and the first three of
+    /// them are equality-enabled.
+    ///
+    /// Hashing requires a fixed column enabled via
+    /// `ConstraintSystem::enable_constant` for the initial points, and the
+    /// generator table must be loaded with [`SinsemillaChip::load`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<pallas::Base>,
+        advices: [Column<Advice>; 9],
+        table: [TableColumn; 3],
+    ) -> SinsemillaConfig {
+        for column in advices[..3].iter() {
+            meta.enable_equality(*column);
+        }
+
+        let generators: Vec<_> = primitives::generators().iter().map(coordinates).collect();
+        let q_sinsemilla = meta.complex_selector();
+
+        meta.lookup("generator table", |meta| {
+            let q_sinsemilla = meta.query_selector(q_sinsemilla);
+            let m = meta.query_advice(advices[2], Rotation::cur());
+            let x_p = meta.query_advice(advices[3], Rotation::cur());
+            let y_p = meta.query_advice(advices[4], Rotation::cur());
+
+            // Rows without the selector look up the first table row, (0, S(0)).
+            let not_q = Expression::Constant(pallas::Base::one()) - q_sinsemilla.clone();
+            let (x_0, y_0) = generators[0];
+            vec![
+                (q_sinsemilla.clone() * m, table[0]),
+                (q_sinsemilla.clone() * x_p + not_q.clone() * x_0, table[1]),
+                (q_sinsemilla * y_p + not_q * y_0, table[2]),
+            ]
+        });
+
+        meta.create_gate("sinsemilla", |meta| {
+            let q_sinsemilla = meta.query_selector(q_sinsemilla);
+            let x_a = meta.query_advice(advices[0], Rotation::cur());
+            let y_a = meta.query_advice(advices[1], Rotation::cur());
+            let x_p = meta.query_advice(advices[3], Rotation::cur());
+            let y_p = meta.query_advice(advices[4], Rotation::cur());
+            let lambda_1 = meta.query_advice(advices[5], Rotation::cur());
+            let lambda_2 = meta.query_advice(advices[6], Rotation::cur());
+            let x_r = meta.query_advice(advices[7], Rotation::cur());
+            let y_r = meta.query_advice(advices[8], Rotation::cur());
+            let x_a_next = meta.query_advice(advices[0], Rotation::next());
+            let y_a_next = meta.query_advice(advices[1], Rotation::next());
+
+            // R = A + P
+            let r_slope = lambda_1.clone() * (x_a.clone() - x_p.clone()) - (y_a.clone() - y_p);
+            let r_x = lambda_1.clone() * lambda_1.clone() - x_a.clone() - x_p - x_r.clone();
+            let r_y = lambda_1 * (x_a.clone() - x_r.clone()) - y_a.clone() - y_r.clone();
+
+            // A' = R + A
+            let a_slope = lambda_2.clone() * (x_a.clone() - x_r.clone()) - (y_a - y_r.clone());
+            let a_x = lambda_2.clone() * lambda_2.clone() - x_r.clone() - x_a - x_a_next.clone();
+            let a_y = lambda_2 * (x_r - x_a_next) - y_r - y_a_next;
+
+            [r_slope, r_x, r_y, a_slope, a_x, a_y]
+                .into_iter()
+                .map(|poly| q_sinsemilla.clone() * poly)
+                .collect::<Vec<_>>()
+        });
+
+        SinsemillaConfig {
+            advices,
+            table,
+            q_sinsemilla,
+            generators,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: SinsemillaConfig) -> Self {
+        SinsemillaChip { config }
+    }
+
+    /// Loads the generator table.
+    pub fn load(&self, layouter: &mut impl Layouter<pallas::Base>) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_table(
+            || "generator table",
+            |mut table| {
+                for (index, (x, y)) in config.generators.iter().enumerate() {
+                    table.assign_cell(
+                        || "index",
+                        config.table[0],
+                        index,
+                        || Value::known(pallas::Base::from(index as u64)),
+                    )?;
+                    table.assign_cell(|| "x", config.table[1], index, || Value::known(*x))?;
+                    table.assign_cell(|| "y", config.table[2], index, || Value::known(*y))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns the generator for the chunk `m`, or `(0, 0)` if `m` is not a
+    /// valid chunk, in which case the lookup fails.
+    fn generator(&self, m: pallas::Base) -> (pallas::Base, pallas::Base) {
+        let repr = m.to_repr();
+        let bytes: &[u8] = repr.as_ref();
+        let index = m.get_lower_32() as usize;
+        if bytes[4..].iter().any(|byte| *byte != 0) || index >= self.config.generators.len() {
+            (pallas::Base::zero(), pallas::Base::zero())
+        } else {
+            self.config.generators[index]
+        }
+    }
+}
+
+impl SinsemillaInstructions for SinsemillaChip {
+    type Chunk = CellValue<pallas::Base>;
+    type X = CellValue<pallas::Base>;
+    type Point = EccPoint<pallas::Affine>;
+
+    fn witness_message(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        bits: &[Value<bool>],
+    ) -> Result<Vec<Self::Chunk>, Error> {
+        let config = &self.config;
+        let chunks = bits
+            .chunks(primitives::K)
+            .enumerate()
+            .map(|(idx, chunk)| {
+                let value = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(Value::known(0u64), |acc, (i, bit)| {
+                        acc.zip(*bit).map(|(acc, bit)| acc + ((bit as u64) << i))
+                    })
+                    .map(pallas::Base::from);
+                CellValue::assign(region, config.advices[2], *offset + idx, value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += chunks.len();
+
+        Ok(chunks)
+    }
+
+    fn hash_to_point(
+        &self,
+        region: &mut Region<'_, pallas::Base>,
+        offset: &mut usize,
+        q: pallas::Affine,
+        message: &[Self::Chunk],
+    ) -> Result<Self::Point, Error> {
+        let config = &self.config;
+        let (x_q, y_q) = coordinates(&q);
+        let mut x_a = CellValue::assign_constant(region, config.advices[0], *offset, x_q)?;
+        let mut y_a = CellValue::assign_constant(region, config.advices[1], *offset, y_q)?;
+
+        for chunk in message {
+            let row = *offset;
+            config.q_sinsemilla.enable(region, row)?;
+            let m = chunk.copy_advice(region, config.advices[2], row)?;
+
+            let (x_p, y_p) = m.value().map(|m| self.generator(m)).unzip();
+            let a = x_a.value().zip(y_a.value());
+            let p = x_p.zip(y_p);
+
+            // Incomplete addition: the exceptional cases are not reachable, as
+            // described on the chip, so the inversions are of non-zero values.
+            let add = |(x_a, y_a): (pallas::Base, pallas::Base),
+                       (x_p, y_p): (pallas::Base, pallas::Base)| {
+                let lambda = (y_a - y_p) * (x_a - x_p).invert().unwrap_or(pallas::Base::zero());
+                let x_r = lambda.square() - x_a - x_p;
+                let y_r = lambda * (x_a - x_r) - y_a;
+                (lambda, (x_r, y_r))
+            };
+            let step = a.zip(p).map(|(a, p)| {
+                let (lambda_1, r) = add(a, p);
+                let (lambda_2, a_next) = add(r, a);
+                (lambda_1, lambda_2, r, a_next)
+            });
+
+            for (column, value) in [
+                (config.advices[3], x_p),
+                (config.advices[4], y_p),
+                (config.advices[5], step.map(|(lambda_1, ..)| lambda_1)),
+                (config.advices[6], step.map(|(_, lambda_2, ..)| lambda_2)),
+                (config.advices[7], step.map(|(_, _, r, _)| r.0)),
+                (config.advices[8], step.map(|(_, _, r, _)| r.1)),
+            ] {
+                region.assign_advice(column, row, value)?;
+            }
+
+            let (x_next, y_next) = step.map(|(.., a_next)| a_next).unzip();
+            x_a = CellValue::assign(region, config.advices[0], row + 1, x_next)?;
+            y_a = CellValue::assign(region, config.advices[1], row + 1, y_next)?;
+            *offset += 1;
+        }
+        *offset += 1;
+
+        Ok(EccPoint::from_coordinates_unchecked(x_a, y_a))
+    }
+
+    fn extract(point: &Self::Point) -> Self::X {
+        point.x()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::pasta::pallas;
+
+    use super::{SinsemillaChip, SinsemillaConfig};
+    use crate::{
+        ecc::{EccChip, EccConfig, EccInstructions},
+        sinsemilla::{primitives, CommitDomain, HashDomain, SinsemillaInstructions},
+    };
+
+    const K: u32 = 11;
+
+    struct SinsemillaCircuit {
+        message: Vec<Value<bool>>,
+        r: Value<pallas::Scalar>,
+    }
+
+    impl Circuit<pallas::Base> for SinsemillaCircuit {
+        type Config = (
+            SinsemillaConfig,
+            EccConfig<pallas::Affine>,
+            Column<Instance>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SinsemillaCircuit {
+                message: vec![Value::unknown(); self.message.len()],
+                r: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 9].map(|_| meta.advice_column());
+            let table = [(); 3].map(|_| meta.lookup_table_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (
+                SinsemillaChip::configure(meta, advices, table),
+                EccChip::configure(meta, advices),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (sinsemilla_config, ecc_config, instance): Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let sinsemilla_chip = SinsemillaChip::construct(sinsemilla_config);
+            let ecc_chip = EccChip::construct(ecc_config);
+            sinsemilla_chip.load(&mut layouter)?;
+
+            let (hash, commitment) = layouter.assign_region(
+                || "sinsemilla",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let message = sinsemilla_chip.witness_message(region, offset, &self.message)?;
+
+                    let hash_domain = HashDomain::new(sinsemilla_chip.clone(), "test-hash");
+                    let hash = hash_domain.hash(region, offset, &message)?;
+
+                    let commit_domain =
+                        CommitDomain::new(sinsemilla_chip.clone(), ecc_chip.clone(), "test-commit");
+                    let r = ecc_chip.witness_scalar(region, offset, self.r)?;
+                    let commitment = commit_domain.short_commit(region, offset, &message, &r)?;
+
+                    Ok((hash, commitment))
+                },
+            )?;
+            layouter.constrain_instance(hash.cell(), instance, 0);
+            layouter.constrain_instance(commitment.cell(), instance, 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sinsemilla_matches_native() {
+        let bits: Vec<bool> = (0..25).map(|i| (i * 7) % 3 == 0).collect();
+        let r = pallas::Scalar::from(0x1234_5678);
+
+        let hash = primitives::HashDomain::new("test-hash")
+            .hash(&bits)
+            .unwrap();
+        let commitment = primitives::CommitDomain::new("test-commit")
+            .short_commit(&bits, &r)
+            .unwrap();
+
+        let circuit = SinsemillaCircuit {
+            message: bits.iter().map(|bit| Value::known(*bit)).collect(),
+            r: Value::known(r),
+        };
+        let prover = MockProver::run(K, &circuit, vec![vec![hash, commitment]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(
+            K,
+            &circuit,
+            vec![vec![hash + pallas::Base::one(), commitment]],
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn empty_message_hashes_to_q() {
+        let domain = primitives::HashDomain::new("test-hash");
+        assert_eq!(domain.hash_to_point(&[]), Some(domain.q()));
+    }
+}
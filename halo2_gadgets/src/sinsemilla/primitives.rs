@@ -0,0 +1,137 @@
+//! A native implementation of the Sinsemilla hash function, used to compute
+//! the generator table and to check the chip.
+
+use ff::Field;
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::arithmetic::{CurveAffine, CurveExt};
+use halo2curves::{pasta::pallas, Coordinates};
+
+/// The number of message bits absorbed by each step of the hash.
+pub const K: usize = 10;
+
+/// The personalization used to derive the initial point `Q` of a domain.
+pub const Q_PERSONALIZATION: &str = "z.cash:SinsemillaQ";
+
+/// The personalization used to derive the generators `S(j)`.
+pub const S_PERSONALIZATION: &str = "z.cash:SinsemillaS";
+
+/// Returns the initial point `Q` for the given domain.
+pub fn q(domain: &str) -> pallas::Affine {
+    pallas::Point::hash_to_curve(Q_PERSONALIZATION)(domain.as_bytes()).to_affine()
+}
+
+/// Returns the generators `S(0), ..., S(2^K - 1)`.
+pub fn generators() -> Vec<pallas::Affine> {
+    let hasher = pallas::Point::hash_to_curve(S_PERSONALIZATION);
+    let points: Vec<_> = (0..(1u32 << K)).map(|j| hasher(&j.to_le_bytes())).collect();
+    let mut affine = vec![pallas::Affine::identity(); points.len()];
+    pallas::Point::batch_normalize(&points, &mut affine);
+    affine
+}
+
+/// Splits `bits` into `K`-bit chunks, padding the last chunk with zeros. Each
+/// chunk is read little-endian.
+pub fn chunks(bits: &[bool]) -> Vec<u32> {
+    bits.chunks(K)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, bit)| acc + ((*bit as u32) << i))
+        })
+        .collect()
+}
+
+/// Returns the x-coordinate of `point`, or zero for the identity.
+pub fn extract_p(point: &pallas::Affine) -> pallas::Base {
+    Option::<Coordinates<pallas::Affine>>::from(point.coordinates())
+        .map(|c| *c.x())
+        .unwrap_or_else(pallas::Base::zero)
+}
+
+/// Adds two points, returning `None` in the cases that incomplete addition
+/// does not handle: either input is the identity, or they share an
+/// x-coordinate.
+fn incomplete_add(a: pallas::Affine, b: pallas::Affine) -> Option<pallas::Affine> {
+    let x = |point: &pallas::Affine| {
+        Option::<Coordinates<pallas::Affine>>::from(point.coordinates()).map(|c| *c.x())
+    };
+    if x(&a)? == x(&b)? {
+        None
+    } else {
+        Some((a.to_curve() + b).to_affine())
+    }
+}
+
+/// A domain in which Sinsemilla hashes messages.
+#[derive(Clone, Debug)]
+pub struct HashDomain {
+    q: pallas::Affine,
+}
+
+impl HashDomain {
+    /// Constructs the domain with the given personalization.
+    pub fn new(domain: &str) -> Self {
+        HashDomain { q: q(domain) }
+    }
+
+    /// Returns the initial point `Q` of this domain.
+    pub fn q(&self) -> pallas::Affine {
+        self.q
+    }
+
+    /// Hashes `bits` to a point, returning `None` if an exceptional case of
+    /// incomplete addition is hit.
+    pub fn hash_to_point(&self, bits: &[bool]) -> Option<pallas::Affine> {
+        let generators = generators();
+        chunks(bits).into_iter().try_fold(self.q, |acc, chunk| {
+            incomplete_add(incomplete_add(acc, generators[chunk as usize])?, acc)
+        })
+    }
+
+    /// Hashes `bits` to the x-coordinate of [`HashDomain::hash_to_point`].
+    pub fn hash(&self, bits: &[bool]) -> Option<pallas::Base> {
+        self.hash_to_point(bits).map(|point| extract_p(&point))
+    }
+}
+
+/// A domain in which Sinsemilla commits to messages.
+#[derive(Clone, Debug)]
+pub struct CommitDomain {
+    m: HashDomain,
+    r: pallas::Affine,
+}
+
+impl CommitDomain {
+    /// Constructs the domain with the given personalization. The message is
+    /// hashed in the domain `"{domain}-M"`, and the blinding base is derived
+    /// from `"{domain}-r"`.
+    pub fn new(domain: &str) -> Self {
+        let m = HashDomain::new(&format!("{}-M", domain));
+        let r_prefix = format!("{}-r", domain);
+        let r = pallas::Point::hash_to_curve(&r_prefix)(&[]).to_affine();
+        CommitDomain { m, r }
+    }
+
+    /// Returns the domain in which messages are hashed.
+    pub fn hash_domain(&self) -> &HashDomain {
+        &self.m
+    }
+
+    /// Returns the blinding base `R` of this domain.
+    pub fn r(&self) -> pallas::Affine {
+        self.r
+    }
+
+    /// Commits to `bits` with the blinding factor `r`.
+    pub fn commit(&self, bits: &[bool], r: &pallas::Scalar) -> Option<pallas::Affine> {
+        self.m
+            .hash_to_point(bits)
+            .map(|hash| (hash.to_curve() + self.r * r).to_affine())
+    }
+
+    /// Commits to `bits` and returns the x-coordinate of the commitment.
+    pub fn short_commit(&self, bits: &[bool], r: &pallas::Scalar) -> Option<pallas::Base> {
+        self.commit(bits, r).map(|point| extract_p(&point))
+    }
+}
@@ -0,0 +1,95 @@
+//! Utility types and functions shared by the gadgets in this crate.
+
+use ff::PrimeField;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Region, Value},
+    plonk::{Advice, Column, Error},
+};
+
+pub mod boolean;
+pub mod comparison;
+pub mod cond_swap;
+pub mod decompose_running_sum;
+pub mod lookup_range_check;
+
+/// A cell assigned in the circuit, together with its value.
+#[derive(Clone, Copy, Debug)]
+pub struct CellValue<F: FieldExt> {
+    cell: Cell,
+    value: Value<F>,
+}
+
+impl<F: FieldExt> CellValue<F> {
+    /// Wraps an assigned cell and its value.
+    pub fn new(cell: Cell, value: Value<F>) -> Self {
+        CellValue { cell, value }
+    }
+
+    /// Assigns `value` to the given advice cell.
+    pub fn assign(
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<Self, Error> {
+        let cell = *region.assign_advice(column, offset, value)?.cell();
+        Ok(CellValue { cell, value })
+    }
+
+    /// Assigns a constant to the given advice cell, constraining it to equal
+    /// the constant through one of the fixed columns configured via
+    /// `ConstraintSystem::enable_constant`.
+    pub fn assign_constant(
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+        constant: F,
+    ) -> Result<Self, Error> {
+        let cell = *region
+            .assign_advice_from_constant(|| "constant", column, offset, constant)?
+            .cell();
+        Ok(CellValue {
+            cell,
+            value: Value::known(constant),
+        })
+    }
+
+    /// Copies this value to the given advice cell and constrains the two cells
+    /// to be equal.
+    pub fn copy_advice(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        let copied = Self::assign(region, column, offset, self.value)?;
+        region.constrain_equal(&copied.cell, &self.cell);
+        Ok(copied)
+    }
+
+    /// Returns the cell.
+    pub fn cell(&self) -> Cell {
+        self.cell
+    }
+
+    /// Returns the value of the cell, if known.
+    pub fn value(&self) -> Value<F> {
+        self.value
+    }
+}
+
+/// Returns the `num_bits` least significant bits of `value`, least significant
+/// first. This assumes the field uses a little-endian representation, as the
+/// Pasta and BN254 fields do.
+pub fn le_bits<F: PrimeField>(value: &F, num_bits: usize) -> Vec<bool> {
+    let repr = value.to_repr();
+    let bytes: &[u8] = repr.as_ref();
+    (0..num_bits)
+        .map(|i| {
+            bytes
+                .get(i / 8)
+                .map_or(false, |byte| (byte >> (i % 8)) & 1 == 1)
+        })
+        .collect()
+}
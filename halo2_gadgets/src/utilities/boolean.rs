@@ -0,0 +1,577 @@
+//! Boolean logic over bits constrained in the circuit.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::{le_bits, CellValue};
+
+/// The set of circuit instructions required for boolean logic.
+pub trait BooleanInstructions<F: FieldExt>: Chip<F> {
+    /// A bit, constrained to be zero or one.
+    type Bit: Copy + Debug;
+
+    /// Witnesses a bit.
+    fn witness_bit(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<bool>,
+    ) -> Result<Self::Bit, Error>;
+
+    /// Returns `a AND b`.
+    fn and(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error>;
+
+    /// Returns `a OR b`.
+    fn or(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error>;
+
+    /// Returns `a XOR b`.
+    fn xor(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error>;
+
+    /// Returns `NOT a`.
+    fn not(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+    ) -> Result<Self::Bit, Error>;
+
+    /// Packs `bits`, least significant first, into a field element.
+    fn pack(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        bits: &[Self::Bit],
+    ) -> Result<CellValue<F>, Error>;
+
+    /// Unpacks `value` into `num_bits` bits, least significant first,
+    /// constraining it to fit in `num_bits` bits.
+    fn unpack(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<Vec<Self::Bit>, Error>;
+}
+
+/// A bit assigned in the circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct AssignedBit<F: FieldExt>(CellValue<F>);
+
+impl<F: FieldExt> AssignedBit<F> {
+    /// Wraps a cell that the caller has already constrained to be boolean.
+    pub(crate) fn from_cell_unchecked(cell: CellValue<F>) -> Self {
+        AssignedBit(cell)
+    }
+
+    /// Returns the cell holding the bit.
+    pub fn cell(&self) -> CellValue<F> {
+        self.0
+    }
+
+    /// Returns the value of the bit, if known.
+    pub fn value(&self) -> Value<bool> {
+        self.0.value().map(|value| value == F::one())
+    }
+}
+
+/// Configuration for a [`BooleanChip`].
+#[derive(Clone, Copy, Debug)]
+pub struct BooleanConfig<F: FieldExt> {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    q_bool: Selector,
+    q_and: Selector,
+    q_or: Selector,
+    q_xor: Selector,
+    q_not: Selector,
+    q_pack: Selector,
+    _marker: PhantomData<F>,
+}
+
+/// A chip implementing [`BooleanInstructions`] with three advice columns.
+///
+/// Binary operations take one row, with the inputs copied into `a` and `b`
+/// and the output in `c`. Every bit is constrained to be boolean when it is
+/// witnessed or unpacked, and the outputs of the operations are boolean
+/// whenever their inputs are, so no bit handed out by the chip is
+/// unconstrained. Packing `n` bits takes `n + 1` rows.
+#[derive(Clone, Debug)]
+pub struct BooleanChip<F: FieldExt> {
+    config: BooleanConfig<F>,
+}
+
+impl<F: FieldExt> Chip<F> for BooleanChip<F> {
+    type Config = BooleanConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> BooleanChip<F> {
+    /// Configures this chip for use in a circuit. The advice columns are
+    /// equality-enabled.
+    ///
+    /// Packing also requires a fixed column enabled via
+    /// `ConstraintSystem::enable_constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+    ) -> BooleanConfig<F> {
+        let [a, b, c] = advices;
+        for column in advices.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let q_bool = meta.selector();
+        let q_and = meta.selector();
+        let q_or = meta.selector();
+        let q_xor = meta.selector();
+        let q_not = meta.selector();
+        let q_pack = meta.selector();
+        let one = || Expression::Constant(F::one());
+
+        meta.create_gate("bool", |meta| {
+            let q_bool = meta.query_selector(q_bool);
+            let a = meta.query_advice(a, Rotation::cur());
+
+            vec![q_bool * a.clone() * (one() - a)]
+        });
+
+        // Each binary operation is a polynomial that agrees with the truth
+        // table on boolean inputs.
+        let binary_gate =
+            |meta: &mut ConstraintSystem<F>,
+             name: &'static str,
+             selector: Selector,
+             op: fn(Expression<F>, Expression<F>) -> Expression<F>| {
+                meta.create_gate(name, |meta| {
+                    let selector = meta.query_selector(selector);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    let c = meta.query_advice(c, Rotation::cur());
+
+                    vec![selector * (c - op(a, b))]
+                });
+            };
+        binary_gate(meta, "and", q_and, |a, b| a * b);
+        binary_gate(meta, "or", q_or, |a, b| a.clone() + b.clone() - a * b);
+        binary_gate(meta, "xor", q_xor, |a, b| {
+            a.clone() + b.clone() - a * b * F::from(2)
+        });
+
+        meta.create_gate("not", |meta| {
+            let q_not = meta.query_selector(q_not);
+            let a = meta.query_advice(a, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+
+            vec![q_not * (c - (one() - a))]
+        });
+
+        // Horner's rule, most significant bit first: acc' = 2 * acc + bit.
+        meta.create_gate("pack", |meta| {
+            let q_pack = meta.query_selector(q_pack);
+            let bit = meta.query_advice(a, Rotation::cur());
+            let acc = meta.query_advice(b, Rotation::cur());
+            let acc_next = meta.query_advice(b, Rotation::next());
+
+            vec![q_pack * (acc_next - acc * F::from(2) - bit)]
+        });
+
+        BooleanConfig {
+            a,
+            b,
+            c,
+            q_bool,
+            q_and,
+            q_or,
+            q_xor,
+            q_not,
+            q_pack,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: BooleanConfig<F>) -> Self {
+        BooleanChip { config }
+    }
+
+    /// Assigns `value` in the `a` column and constrains it to be boolean.
+    fn assign_bit(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        value: Value<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.config.q_bool.enable(region, row)?;
+        CellValue::assign(region, self.config.a, row, value).map(AssignedBit)
+    }
+
+    fn binary_op(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        selector: Selector,
+        a: &AssignedBit<F>,
+        b: &AssignedBit<F>,
+        op: fn(bool, bool) -> bool,
+    ) -> Result<AssignedBit<F>, Error> {
+        let config = &self.config;
+        let row = *offset;
+        selector.enable(region, row)?;
+        a.0.copy_advice(region, config.a, row)?;
+        b.0.copy_advice(region, config.b, row)?;
+        let c = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| F::from(op(a, b) as u64));
+        let c = CellValue::assign(region, config.c, row, c)?;
+        *offset += 1;
+
+        Ok(AssignedBit(c))
+    }
+
+    /// Packs `bits` with a running sum whose last value is `packed`, or a new
+    /// cell if `packed` is `None`.
+    fn assign_pack(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        bits: &[AssignedBit<F>],
+        packed: Option<&CellValue<F>>,
+    ) -> Result<CellValue<F>, Error> {
+        let config = &self.config;
+        let start = *offset;
+
+        let mut acc = CellValue::assign_constant(region, config.b, start, F::zero())?;
+        for (i, bit) in bits.iter().rev().enumerate() {
+            let row = start + i;
+            config.q_pack.enable(region, row)?;
+            bit.0.copy_advice(region, config.a, row)?;
+
+            let next_row = row + 1;
+            acc = match packed {
+                Some(packed) if i + 1 == bits.len() => {
+                    packed.copy_advice(region, config.b, next_row)?
+                }
+                _ => {
+                    let value = acc.value() * Value::known(F::from(2)) + bit.0.value();
+                    CellValue::assign(region, config.b, next_row, value)?
+                }
+            };
+        }
+        *offset = start + bits.len() + 1;
+
+        Ok(acc)
+    }
+}
+
+impl<F: FieldExt> BooleanInstructions<F> for BooleanChip<F> {
+    type Bit = AssignedBit<F>;
+
+    fn witness_bit(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: Value<bool>,
+    ) -> Result<Self::Bit, Error> {
+        let bit = self.assign_bit(region, *offset, value.map(|b| F::from(b as u64)))?;
+        *offset += 1;
+        Ok(bit)
+    }
+
+    fn and(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error> {
+        self.binary_op(region, offset, self.config.q_and, a, b, |a, b| a & b)
+    }
+
+    fn or(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error> {
+        self.binary_op(region, offset, self.config.q_or, a, b, |a, b| a | b)
+    }
+
+    fn xor(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+        b: &Self::Bit,
+    ) -> Result<Self::Bit, Error> {
+        self.binary_op(region, offset, self.config.q_xor, a, b, |a, b| a ^ b)
+    }
+
+    fn not(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &Self::Bit,
+    ) -> Result<Self::Bit, Error> {
+        let config = &self.config;
+        let row = *offset;
+        config.q_not.enable(region, row)?;
+        a.0.copy_advice(region, config.a, row)?;
+        let c = a.value().map(|a| F::from(!a as u64));
+        let c = CellValue::assign(region, config.c, row, c)?;
+        *offset += 1;
+
+        Ok(AssignedBit(c))
+    }
+
+    fn pack(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        bits: &[Self::Bit],
+    ) -> Result<CellValue<F>, Error> {
+        self.assign_pack(region, offset, bits, None)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is zero, or too large for values of that many bits
+    /// to be distinct field elements.
+    fn unpack(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        value: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<Vec<Self::Bit>, Error> {
+        assert!(num_bits > 0, "cannot unpack to zero bits");
+        assert!(
+            num_bits <= F::CAPACITY as usize,
+            "cannot unpack to more bits than the field can hold"
+        );
+
+        let bit_values = value
+            .value()
+            .map(|value| le_bits(&value, num_bits))
+            .transpose_vec(num_bits);
+        let bits = bit_values
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| self.assign_bit(region, *offset + i, bit.map(|b| F::from(b as u64))))
+            .collect::<Result<Vec<_>, _>>()?;
+        *offset += num_bits;
+
+        self.assign_pack(region, offset, &bits, Some(value))?;
+        Ok(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::{BooleanChip, BooleanConfig, BooleanInstructions};
+    use crate::utilities::CellValue;
+
+    struct LogicCircuit {
+        a: Value<bool>,
+        b: Value<bool>,
+    }
+
+    impl Circuit<Fp> for LogicCircuit {
+        type Config = BooleanConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            LogicCircuit {
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 3].map(|_| meta.advice_column());
+            BooleanChip::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BooleanChip::construct(config);
+            layouter.assign_region(
+                || "logic",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let a = chip.witness_bit(region, offset, self.a)?;
+                    let b = chip.witness_bit(region, offset, self.b)?;
+
+                    let check = |bit: &super::AssignedBit<Fp>, expected: Value<bool>| {
+                        bit.value()
+                            .zip(expected)
+                            .assert_if_known(|(bit, expected)| bit == expected)
+                    };
+                    let ab = self.a.zip(self.b);
+                    check(&chip.and(region, offset, &a, &b)?, ab.map(|(a, b)| a & b));
+                    check(&chip.or(region, offset, &a, &b)?, ab.map(|(a, b)| a | b));
+                    check(&chip.xor(region, offset, &a, &b)?, ab.map(|(a, b)| a ^ b));
+                    check(&chip.not(region, offset, &a)?, self.a.map(|a| !a));
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn truth_tables() {
+        for a in [false, true] {
+            for b in [false, true] {
+                let circuit = LogicCircuit {
+                    a: Value::known(a),
+                    b: Value::known(b),
+                };
+                let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+                assert_eq!(prover.verify(), Ok(()));
+            }
+        }
+    }
+
+    struct PackCircuit {
+        value: Value<Fp>,
+        num_bits: usize,
+    }
+
+    impl Circuit<Fp> for PackCircuit {
+        type Config = (BooleanConfig<Fp>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            PackCircuit {
+                value: Value::unknown(),
+                num_bits: self.num_bits,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 3].map(|_| meta.advice_column());
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            (BooleanChip::configure(meta, advices), input)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BooleanChip::construct(config);
+            layouter.assign_region(
+                || "pack",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let value = CellValue::assign(region, input, 0, self.value)?;
+                    let bits = chip.unpack(region, offset, &value, self.num_bits)?;
+                    let packed = chip.pack(region, offset, &bits)?;
+                    region.constrain_equal(&packed.cell(), &value.cell());
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn unpack_and_pack() {
+        for (value, num_bits, valid) in [(0xa5, 8, true), (0xff, 8, true), (0x100, 8, false)] {
+            let circuit = PackCircuit {
+                value: Value::known(Fp::from(value)),
+                num_bits,
+            };
+            let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify().is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn non_boolean_bit() {
+        struct NonBoolean;
+
+        impl Circuit<Fp> for NonBoolean {
+            type Config = BooleanConfig<Fp>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                NonBoolean
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let advices = [(); 3].map(|_| meta.advice_column());
+                BooleanChip::configure(meta, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let chip = BooleanChip::construct(config);
+                layouter.assign_region(
+                    || "non-boolean",
+                    |mut region| {
+                        chip.assign_bit(&mut region, 0, Value::known(Fp::one().double()))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(4, &NonBoolean, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Comparisons of small values.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{
+    boolean::AssignedBit,
+    le_bits,
+    lookup_range_check::{RangeCheckChip, RangeCheckConfig, RangeCheckInstructions},
+    CellValue,
+};
+
+/// The set of circuit instructions required to compare values.
+pub trait ComparisonInstructions<F: FieldExt>: Chip<F> {
+    /// Returns whether `a < b`, constraining both to be less than
+    /// `2^num_bits`.
+    fn is_less_than(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<AssignedBit<F>, Error>;
+}
+
+/// Configuration for a [`ComparisonChip`].
+#[derive(Clone, Copy, Debug)]
+pub struct ComparisonConfig<F: FieldExt, const K: usize> {
+    advices: [Column<Advice>; 4],
+    two_pow_n: Column<Fixed>,
+    q_lt: Selector,
+    range_check: RangeCheckConfig<F, K>,
+    _marker: PhantomData<F>,
+}
+
+/// A chip implementing [`ComparisonInstructions`] on top of a
+/// [`RangeCheckChip`].
+///
+/// For `a, b < 2^n`, the chip witnesses a bit `lt` and constrains
+/// `r = a - b + lt * 2^n` to be less than `2^n`. If `a < b`, `r` is only in
+/// range when `lt = 1`; otherwise it is only in range when `lt = 0`. The
+/// inputs must be range-checked for this to hold, so the chip checks them
+/// itself rather than trusting the caller, and `n` must leave room for
+/// `a - b + 2^n` not to wrap around the modulus. A comparison of `n`-bit values
+/// takes one row plus three `n`-bit range checks.
+#[derive(Clone, Debug)]
+pub struct ComparisonChip<F: FieldExt, const K: usize> {
+    config: ComparisonConfig<F, K>,
+}
+
+impl<F: FieldExt, const K: usize> Chip<F> for ComparisonChip<F, K> {
+    type Config = ComparisonConfig<F, K>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const K: usize> ComparisonChip<F, K> {
+    /// Configures this chip for use in a circuit, reusing the given range check
+    /// configuration. The advice columns are equality-enabled.
+    ///
+    /// The circuit must load the range check table with
+    /// [`RangeCheckChip::load`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 4],
+        range_check: RangeCheckConfig<F, K>,
+    ) -> ComparisonConfig<F, K> {
+        for column in advices.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let two_pow_n = meta.fixed_column();
+        let q_lt = meta.selector();
+
+        meta.create_gate("less than", |meta| {
+            let q_lt = meta.query_selector(q_lt);
+            let [a, b, lt, r] = advices.map(|column| meta.query_advice(column, Rotation::cur()));
+            let two_pow_n = meta.query_fixed(two_pow_n, Rotation::cur());
+
+            vec![
+                q_lt.clone() * lt.clone() * (Expression::Constant(F::one()) - lt.clone()),
+                q_lt * (r - (a - b + lt * two_pow_n)),
+            ]
+        });
+
+        ComparisonConfig {
+            advices,
+            two_pow_n,
+            q_lt,
+            range_check,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: ComparisonConfig<F, K>) -> Self {
+        ComparisonChip { config }
+    }
+}
+
+impl<F: FieldExt, const K: usize> ComparisonInstructions<F> for ComparisonChip<F, K> {
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is zero, or too large for `a - b + 2^num_bits` to
+    /// fit in the field.
+    fn is_less_than(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<AssignedBit<F>, Error> {
+        assert!(num_bits > 0, "cannot compare zero-bit values");
+        assert!(
+            num_bits < F::CAPACITY as usize,
+            "cannot compare values of more bits than the field can hold"
+        );
+        let config = &self.config;
+        let [a_col, b_col, lt_col, r_col] = config.advices;
+        let two_pow_n = F::from(2).pow_vartime([num_bits as u64]);
+        let row = *offset;
+
+        config.q_lt.enable(region, row)?;
+        region.assign_fixed(config.two_pow_n, row, two_pow_n);
+        let a = a.copy_advice(region, a_col, row)?;
+        let b = b.copy_advice(region, b_col, row)?;
+
+        // a - b + 2^n has bit n clear exactly when a < b.
+        let lt = (a.value() - b.value()).map(|diff| {
+            let bits = le_bits(&(diff + two_pow_n), num_bits + 1);
+            !bits[num_bits]
+        });
+        let lt_value = lt.map(|lt| F::from(lt as u64));
+        let r = a.value() - b.value() + lt_value * Value::known(two_pow_n);
+        let lt = CellValue::assign(region, lt_col, row, lt_value)?;
+        let r = CellValue::assign(region, r_col, row, r)?;
+        *offset += 1;
+
+        let range_check = RangeCheckChip::construct(config.range_check);
+        for value in [&a, &b, &r] {
+            range_check.range_check(region, offset, value, num_bits)?;
+        }
+
+        Ok(AssignedBit::from_cell_unchecked(lt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::{ComparisonChip, ComparisonConfig, ComparisonInstructions};
+    use crate::utilities::{lookup_range_check::RangeCheckChip, CellValue};
+
+    const K: usize = 8;
+
+    struct LessThanCircuit {
+        a: Value<Fp>,
+        b: Value<Fp>,
+        num_bits: usize,
+        expected: Value<bool>,
+    }
+
+    impl Circuit<Fp> for LessThanCircuit {
+        type Config = ComparisonConfig<Fp, K>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            LessThanCircuit {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                num_bits: self.num_bits,
+                expected: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 4].map(|_| meta.advice_column());
+            let z = meta.advice_column();
+            let table = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check = RangeCheckChip::configure(meta, z, table);
+            ComparisonChip::configure(meta, advices, range_check)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            RangeCheckChip::construct(config.range_check).load(&mut layouter)?;
+            let chip = ComparisonChip::construct(config);
+
+            layouter.assign_region(
+                || "less than",
+                |mut region| {
+                    let [a_col, b_col, _, _] = config.advices;
+                    let a = CellValue::assign(&mut region, a_col, 0, self.a)?;
+                    let b = CellValue::assign(&mut region, b_col, 0, self.b)?;
+                    let lt = chip.is_less_than(&mut region, &mut 1, &a, &b, self.num_bits)?;
+                    lt.value()
+                        .zip(self.expected)
+                        .assert_if_known(|(lt, expected)| lt == expected);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn is_less_than(a: u64, b: u64, num_bits: usize) -> bool {
+        let circuit = LessThanCircuit {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+            num_bits,
+            expected: Value::known(a < b),
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn less_than() {
+        for num_bits in [1, 8, 13, 64] {
+            let max = u64::MAX >> (64 - num_bits);
+            for (a, b) in [(0, 0), (0, 1), (1, 0), (0, max), (max, 0), (max, max)] {
+                assert!(is_less_than(a, b, num_bits));
+            }
+        }
+        assert!(is_less_than(1000, 1001, 10));
+        assert!(is_less_than(1001, 1000, 10));
+    }
+
+    #[test]
+    fn input_out_of_range() {
+        assert!(!is_less_than(256, 3, 8));
+        assert!(!is_less_than(3, 256, 8));
+    }
+}
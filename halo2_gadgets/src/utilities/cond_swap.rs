@@ -0,0 +1,289 @@
+//! Conditional selection and swapping of values.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use super::{boolean::AssignedBit, CellValue};
+
+/// The set of circuit instructions required to select between and swap
+/// values.
+pub trait CondSwapInstructions<F: FieldExt>: Chip<F> {
+    /// Returns `a` if `cond` is set, and `b` otherwise.
+    fn select(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        cond: &AssignedBit<F>,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+    ) -> Result<CellValue<F>, Error>;
+
+    /// Returns `(b, a)` if `cond` is set, and `(a, b)` otherwise.
+    fn cond_swap(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        cond: &AssignedBit<F>,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+    ) -> Result<(CellValue<F>, CellValue<F>), Error>;
+}
+
+/// Configuration for a [`CondSwapChip`].
+#[derive(Clone, Copy, Debug)]
+pub struct CondSwapConfig<F: FieldExt> {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    cond: Column<Advice>,
+    a_out: Column<Advice>,
+    b_out: Column<Advice>,
+    q_select: Selector,
+    q_swap: Selector,
+    _marker: PhantomData<F>,
+}
+
+/// A chip implementing [`CondSwapInstructions`] with five advice columns.
+///
+/// Each instruction takes one row. The condition is an [`AssignedBit`], so it
+/// has already been constrained to be boolean by the chip that produced it.
+#[derive(Clone, Debug)]
+pub struct CondSwapChip<F: FieldExt> {
+    config: CondSwapConfig<F>,
+}
+
+impl<F: FieldExt> Chip<F> for CondSwapChip<F> {
+    type Config = CondSwapConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> CondSwapChip<F> {
+    /// Configures this chip for use in a circuit. The advice columns are
+    /// equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 5],
+    ) -> CondSwapConfig<F> {
+        let [a, b, cond, a_out, b_out] = advices;
+        for column in advices.iter() {
+            meta.enable_equality(*column);
+        }
+
+        let q_select = meta.selector();
+        let q_swap = meta.selector();
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let out = meta.query_advice(a_out, Rotation::cur());
+
+            vec![q_select * (out - (cond * (a - b.clone()) + b))]
+        });
+
+        meta.create_gate("conditional swap", |meta| {
+            let q_swap = meta.query_selector(q_swap);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let a_out = meta.query_advice(a_out, Rotation::cur());
+            let b_out = meta.query_advice(b_out, Rotation::cur());
+
+            vec![
+                q_swap.clone() * (a_out.clone() - (cond * (b.clone() - a.clone()) + a.clone())),
+                q_swap * (a_out + b_out - (a + b)),
+            ]
+        });
+
+        CondSwapConfig {
+            a,
+            b,
+            cond,
+            a_out,
+            b_out,
+            q_select,
+            q_swap,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: CondSwapConfig<F>) -> Self {
+        CondSwapChip { config }
+    }
+
+    /// Copies the operands of an instruction into `row`.
+    fn assign_inputs(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        cond: &AssignedBit<F>,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+    ) -> Result<(CellValue<F>, CellValue<F>), Error> {
+        let config = &self.config;
+        cond.cell().copy_advice(region, config.cond, row)?;
+        let a = a.copy_advice(region, config.a, row)?;
+        let b = b.copy_advice(region, config.b, row)?;
+        Ok((a, b))
+    }
+}
+
+impl<F: FieldExt> CondSwapInstructions<F> for CondSwapChip<F> {
+    fn select(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        cond: &AssignedBit<F>,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+    ) -> Result<CellValue<F>, Error> {
+        let config = &self.config;
+        let row = *offset;
+        config.q_select.enable(region, row)?;
+        let (a, b) = self.assign_inputs(region, row, cond, a, b)?;
+
+        let out = cond
+            .value()
+            .zip(a.value().zip(b.value()))
+            .map(|(cond, (a, b))| if cond { a } else { b });
+        let out = CellValue::assign(region, config.a_out, row, out)?;
+        *offset += 1;
+
+        Ok(out)
+    }
+
+    fn cond_swap(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        cond: &AssignedBit<F>,
+        a: &CellValue<F>,
+        b: &CellValue<F>,
+    ) -> Result<(CellValue<F>, CellValue<F>), Error> {
+        let config = &self.config;
+        let row = *offset;
+        config.q_swap.enable(region, row)?;
+        let (a, b) = self.assign_inputs(region, row, cond, a, b)?;
+
+        let (a_out, b_out) = cond
+            .value()
+            .zip(a.value().zip(b.value()))
+            .map(|(cond, (a, b))| if cond { (b, a) } else { (a, b) })
+            .unzip();
+        let a_out = CellValue::assign(region, config.a_out, row, a_out)?;
+        let b_out = CellValue::assign(region, config.b_out, row, b_out)?;
+        *offset += 1;
+
+        Ok((a_out, b_out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::{CondSwapChip, CondSwapConfig, CondSwapInstructions};
+    use crate::utilities::{
+        boolean::{BooleanChip, BooleanConfig, BooleanInstructions},
+        CellValue,
+    };
+
+    struct CondSwapCircuit {
+        cond: Value<bool>,
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for CondSwapCircuit {
+        type Config = (CondSwapConfig<Fp>, BooleanConfig<Fp>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            CondSwapCircuit {
+                cond: Value::unknown(),
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [(); 5].map(|_| meta.advice_column());
+            (
+                CondSwapChip::configure(meta, advices),
+                BooleanChip::configure(meta, [advices[0], advices[1], advices[2]]),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, bool_config): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = CondSwapChip::construct(config);
+            let bool_chip = BooleanChip::construct(bool_config);
+
+            layouter.assign_region(
+                || "conditional swap",
+                |mut region| {
+                    let region = &mut region;
+                    let offset = &mut 0;
+                    let cond = bool_chip.witness_bit(region, offset, self.cond)?;
+                    let a = CellValue::assign(region, config.a, *offset, self.a)?;
+                    let b = CellValue::assign(region, config.b, *offset, self.b)?;
+                    *offset += 1;
+
+                    let selected = chip.select(region, offset, &cond, &a, &b)?;
+                    let (a_out, b_out) = chip.cond_swap(region, offset, &cond, &a, &b)?;
+
+                    let expected =
+                        self.cond
+                            .zip(self.a.zip(self.b))
+                            .map(|(cond, (a, b))| if cond { (b, a) } else { (a, b) });
+                    selected
+                        .value()
+                        .zip(expected)
+                        .assert_if_known(|(selected, (_, b))| selected == b);
+                    a_out
+                        .value()
+                        .zip(b_out.value())
+                        .zip(expected)
+                        .assert_if_known(|(actual, expected)| actual == expected);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn cond_swap() {
+        for cond in [false, true] {
+            let circuit = CondSwapCircuit {
+                cond: Value::known(cond),
+                a: Value::known(Fp::from(3)),
+                b: Value::known(Fp::from(5)),
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+}
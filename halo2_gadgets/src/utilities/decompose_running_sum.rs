@@ -0,0 +1,465 @@
+//! Decomposition of a field element into `WINDOW_NUM_BITS`-bit windows with a
+//! running sum.
+//!
+//! Given `alpha = k_0 + 2^w k_1 + 2^{2w} k_2 + ...`, the running sum is
+//! `z_0 = alpha` and `z_{i+1} = (z_i - k_i) / 2^w`, so each window is
+//! `k_i = z_i - 2^w z_{i+1}` and is constrained to `w` bits. After `n`
+//! windows, `z_n = alpha >> (n * w)`, and a strict decomposition constrains it
+//! to zero.
+//!
+//! A strict decomposition into `n * w` bits shows that `alpha` has an integer
+//! representation `sum k_i 2^{iw}` below `2^{n * w}`. If `n * w` is at least the
+//! bit length of the field, that representation may be `alpha + p` rather than
+//! `alpha` itself, so [`RunningSumConfig::decompose_canonical`] additionally
+//! constrains the windows to be at most those of `p - 1`.
+
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{le_bits, CellValue};
+
+/// The running sum `z_0, ..., z_n` of a decomposition.
+#[derive(Clone, Debug)]
+pub struct RunningSum<F: FieldExt>(Vec<CellValue<F>>);
+
+impl<F: FieldExt> RunningSum<F> {
+    /// Returns the running sum, starting with `z_0 = alpha` and ending with
+    /// `z_n`.
+    pub fn zs(&self) -> &[CellValue<F>] {
+        &self.0
+    }
+
+    /// Returns the values of the windows `k_i = z_i - 2^w z_{i+1}`, if known.
+    pub fn windows(&self, window_num_bits: usize) -> Vec<Value<F>> {
+        let two_pow_w = Value::known(F::from(1 << window_num_bits));
+        self.0
+            .windows(2)
+            .map(|z| z[0].value() - z[1].value() * two_pow_w)
+            .collect()
+    }
+}
+
+/// Returns the polynomial that is zero exactly when `value` is in `0..range`.
+fn range_check<F: FieldExt>(value: Expression<F>, range: usize) -> Expression<F> {
+    (1..range).fold(value.clone(), |acc, i| {
+        acc * (value.clone() - Expression::Constant(F::from(i as u64)))
+    })
+}
+
+/// Configuration for running-sum decompositions into `WINDOW_NUM_BITS`-bit
+/// windows. Each window is range-checked with a polynomial of degree
+/// `2^WINDOW_NUM_BITS`, so windows are at most three bits wide.
+#[derive(Clone, Copy, Debug)]
+pub struct RunningSumConfig<F: FieldExt, const WINDOW_NUM_BITS: usize> {
+    z: Column<Advice>,
+    difference: Column<Advice>,
+    borrow: Column<Advice>,
+    max_window: Column<Fixed>,
+    q_range_check: Selector,
+    q_canonical: Selector,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const WINDOW_NUM_BITS: usize> RunningSumConfig<F, WINDOW_NUM_BITS> {
+    /// Configures running-sum decompositions, with the running sum in `z` and
+    /// the canonicity check in `difference` and `borrow`. `z` is
+    /// equality-enabled.
+    ///
+    /// Decompositions also require a fixed column enabled via
+    /// `ConstraintSystem::enable_constant`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `WINDOW_NUM_BITS` is zero or larger than three.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        z: Column<Advice>,
+        difference: Column<Advice>,
+        borrow: Column<Advice>,
+    ) -> Self {
+        assert!(
+            (1..=3).contains(&WINDOW_NUM_BITS),
+            "windows must have between 1 and 3 bits"
+        );
+        meta.enable_equality(z);
+        meta.enable_equality(borrow);
+
+        let max_window = meta.fixed_column();
+        let q_range_check = meta.selector();
+        let q_canonical = meta.selector();
+        let two_pow_w = F::from(1 << WINDOW_NUM_BITS);
+
+        meta.create_gate("running sum window", |meta| {
+            let q_range_check = meta.query_selector(q_range_check);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+
+            let window = z_cur - z_next * two_pow_w;
+            vec![q_range_check * range_check(window, 1 << WINDOW_NUM_BITS)]
+        });
+
+        // Subtracts the windows from those of p - 1 with a borrow chain:
+        // difference = max_window - window - borrow_in + 2^w * borrow_out.
+        meta.create_gate("canonical window", |meta| {
+            let q_canonical = meta.query_selector(q_canonical);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let difference = meta.query_advice(difference, Rotation::cur());
+            let borrow_in = meta.query_advice(borrow, Rotation::cur());
+            let borrow_out = meta.query_advice(borrow, Rotation::next());
+            let max_window = meta.query_fixed(max_window, Rotation::cur());
+
+            let window = z_cur - z_next * two_pow_w;
+            let one = Expression::Constant(F::one());
+            vec![
+                q_canonical.clone() * range_check(difference.clone(), 1 << WINDOW_NUM_BITS),
+                q_canonical.clone() * borrow_out.clone() * (one - borrow_out.clone()),
+                q_canonical
+                    * (difference - (max_window - window - borrow_in + borrow_out * two_pow_w)),
+            ]
+        });
+
+        RunningSumConfig {
+            z,
+            difference,
+            borrow,
+            max_window,
+            q_range_check,
+            q_canonical,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decomposes `alpha` into `num_windows` windows, copying it into the
+    /// first row. If `strict` is set, `z_n` is constrained to zero, so `alpha`
+    /// must fit in `num_windows * WINDOW_NUM_BITS` bits. Otherwise `z_n` holds
+    /// the remaining high bits of `word_num_bits`-bit `alpha`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `word_num_bits` exceeds the bit length of the field, or if
+    /// `num_windows` covers more than `word_num_bits` bits plus a partial
+    /// window.
+    pub fn decompose(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        alpha: &CellValue<F>,
+        strict: bool,
+        word_num_bits: usize,
+        num_windows: usize,
+    ) -> Result<RunningSum<F>, Error> {
+        assert!(word_num_bits <= F::NUM_BITS as usize);
+        assert!(WINDOW_NUM_BITS * num_windows < word_num_bits + WINDOW_NUM_BITS);
+
+        let windows = alpha
+            .value()
+            .map(|alpha| Self::windows_of(&alpha, num_windows));
+        self.assign(region, offset, alpha, windows, num_windows, strict, false)
+    }
+
+    /// Decomposes `alpha` into windows covering the whole field, and
+    /// constrains the decomposition to be the canonical one, i.e. to
+    /// represent an integer less than the field modulus.
+    pub fn decompose_canonical(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        alpha: &CellValue<F>,
+    ) -> Result<RunningSum<F>, Error> {
+        let num_windows = Self::canonical_num_windows();
+        let windows = alpha
+            .value()
+            .map(|alpha| Self::windows_of(&alpha, num_windows));
+        self.assign(region, offset, alpha, windows, num_windows, true, true)
+    }
+
+    fn canonical_num_windows() -> usize {
+        (F::NUM_BITS as usize + WINDOW_NUM_BITS - 1) / WINDOW_NUM_BITS
+    }
+
+    fn windows_of(value: &F, num_windows: usize) -> Vec<u8> {
+        le_bits(value, num_windows * WINDOW_NUM_BITS)
+            .chunks(WINDOW_NUM_BITS)
+            .map(|bits| {
+                bits.iter()
+                    .rev()
+                    .fold(0, |acc, bit| (acc << 1) + (*bit as u8))
+            })
+            .collect()
+    }
+
+    /// Assigns the running sum for the given windows of `alpha`, and the
+    /// canonicity check if `canonical` is set.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        alpha: &CellValue<F>,
+        windows: Value<Vec<u8>>,
+        num_windows: usize,
+        strict: bool,
+        canonical: bool,
+    ) -> Result<RunningSum<F>, Error> {
+        let start = *offset;
+        let inv_two_pow_w = F::from(1 << WINDOW_NUM_BITS).invert().unwrap();
+
+        let mut zs = vec![alpha.copy_advice(region, self.z, start)?];
+        for i in 0..num_windows {
+            self.q_range_check.enable(region, start + i)?;
+            let window = windows.as_ref().map(|windows| F::from(windows[i] as u64));
+            let z_next = (zs[i].value() - window) * Value::known(inv_two_pow_w);
+            let z_next = if strict && i + 1 == num_windows {
+                // A witness that does not fit leaves a non-zero z_n, which
+                // fails the range check of the last window.
+                CellValue::assign_constant(region, self.z, start + i + 1, F::zero())?
+            } else {
+                CellValue::assign(region, self.z, start + i + 1, z_next)?
+            };
+            zs.push(z_next);
+        }
+
+        if canonical {
+            let max_windows = Self::windows_of(&-F::one(), num_windows);
+            let two_pow_w = 1i16 << WINDOW_NUM_BITS;
+
+            CellValue::assign_constant(region, self.borrow, start, F::zero())?;
+            let mut borrow = windows.as_ref().map(|_| 0);
+            for (i, max_window) in max_windows.iter().enumerate() {
+                let row = start + i;
+                self.q_canonical.enable(region, row)?;
+                region.assign_fixed(self.max_window, row, F::from(*max_window as u64));
+
+                let step = windows.as_ref().zip(borrow).map(|(windows, borrow)| {
+                    let difference = *max_window as i16 - windows[i] as i16 - borrow;
+                    if difference < 0 {
+                        (difference + two_pow_w, 1)
+                    } else {
+                        (difference, 0)
+                    }
+                });
+                let (difference, borrow_out) = step.unzip();
+                region.assign_advice(
+                    self.difference,
+                    row,
+                    difference.map(|d| F::from(d as u64)),
+                )?;
+                borrow = borrow_out;
+                if i + 1 == num_windows {
+                    // A decomposition of at least the modulus borrows out of
+                    // the last window, which fails this constraint.
+                    CellValue::assign_constant(region, self.borrow, row + 1, F::zero())?;
+                } else {
+                    region.assign_advice(
+                        self.borrow,
+                        row + 1,
+                        borrow.map(|b| F::from(b as u64)),
+                    )?;
+                }
+            }
+        }
+        *offset = start + num_windows + 1;
+
+        Ok(RunningSum(zs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::RunningSumConfig;
+    use crate::utilities::CellValue;
+
+    const WINDOW_NUM_BITS: usize = 3;
+
+    #[derive(Clone, Copy)]
+    enum Mode {
+        Strict {
+            word_num_bits: usize,
+        },
+        Partial {
+            word_num_bits: usize,
+            num_windows: usize,
+        },
+        Canonical,
+        // Decomposes zero as the windows of the field modulus.
+        NonCanonical,
+    }
+
+    struct DecomposeCircuit {
+        alpha: Value<Fp>,
+        mode: Mode,
+    }
+
+    impl Circuit<Fp> for DecomposeCircuit {
+        type Config = (RunningSumConfig<Fp, WINDOW_NUM_BITS>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            DecomposeCircuit {
+                alpha: Value::unknown(),
+                mode: self.mode,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let [z, difference, borrow] = [(); 3].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            (
+                RunningSumConfig::configure(meta, z, difference, borrow),
+                input,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, input): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "decompose",
+                |mut region| {
+                    let alpha = CellValue::assign(&mut region, input, 0, self.alpha)?;
+                    let mut offset = 0;
+                    let running_sum = match self.mode {
+                        Mode::Strict { word_num_bits } => config.decompose(
+                            &mut region,
+                            &mut offset,
+                            &alpha,
+                            true,
+                            word_num_bits,
+                            (word_num_bits + WINDOW_NUM_BITS - 1) / WINDOW_NUM_BITS,
+                        )?,
+                        Mode::Partial {
+                            word_num_bits,
+                            num_windows,
+                        } => config.decompose(
+                            &mut region,
+                            &mut offset,
+                            &alpha,
+                            false,
+                            word_num_bits,
+                            num_windows,
+                        )?,
+                        Mode::Canonical => {
+                            config.decompose_canonical(&mut region, &mut offset, &alpha)?
+                        }
+                        Mode::NonCanonical => {
+                            let num_windows =
+                                RunningSumConfig::<Fp, WINDOW_NUM_BITS>::canonical_num_windows();
+                            // p = (p - 1) + 1, with the carry propagated through the windows.
+                            let mut windows = RunningSumConfig::<Fp, WINDOW_NUM_BITS>::windows_of(
+                                &-Fp::one(),
+                                num_windows,
+                            );
+                            for window in windows.iter_mut() {
+                                if *window == (1 << WINDOW_NUM_BITS) - 1 {
+                                    *window = 0;
+                                } else {
+                                    *window += 1;
+                                    break;
+                                }
+                            }
+                            config.assign(
+                                &mut region,
+                                &mut offset,
+                                &alpha,
+                                Value::known(windows),
+                                num_windows,
+                                true,
+                                true,
+                            )?
+                        }
+                    };
+
+                    // The windows recompose to alpha.
+                    let recomposed = running_sum
+                        .windows(WINDOW_NUM_BITS)
+                        .into_iter()
+                        .rev()
+                        .fold(running_sum.zs().last().unwrap().value(), |acc, window| {
+                            acc * Value::known(Fp::from(1 << WINDOW_NUM_BITS)) + window
+                        });
+                    if !matches!(self.mode, Mode::NonCanonical) {
+                        recomposed
+                            .zip(self.alpha)
+                            .assert_if_known(|(recomposed, alpha)| recomposed == alpha);
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(alpha: Fp, mode: Mode) -> bool {
+        let circuit = DecomposeCircuit {
+            alpha: Value::known(alpha),
+            mode,
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn strict_decomposition() {
+        let word_num_bits = 64;
+        assert!(verify(Fp::from(u64::MAX), Mode::Strict { word_num_bits }));
+        assert!(verify(Fp::zero(), Mode::Strict { word_num_bits }));
+        // 66 bits fit in 22 windows, but 2^66 does not.
+        assert!(verify(
+            Fp::from_u128((1 << 66) - 1),
+            Mode::Strict { word_num_bits }
+        ));
+        assert!(!verify(
+            Fp::from_u128(1 << 66),
+            Mode::Strict { word_num_bits }
+        ));
+    }
+
+    #[test]
+    fn partial_decomposition() {
+        let alpha = Fp::from_u128(0x1234_5678_9abc_def0_1234);
+        assert!(verify(
+            alpha,
+            Mode::Partial {
+                word_num_bits: 80,
+                num_windows: 10,
+            }
+        ));
+    }
+
+    #[test]
+    fn canonical_decomposition() {
+        assert!(verify(-Fp::one(), Mode::Canonical));
+        assert!(verify(Fp::zero(), Mode::Canonical));
+        assert!(verify(Fp::from(0xdead_beef), Mode::Canonical));
+        assert!(!verify(Fp::zero(), Mode::NonCanonical));
+    }
+
+    #[test]
+    fn windows_cover_the_field() {
+        assert!(
+            RunningSumConfig::<Fp, WINDOW_NUM_BITS>::canonical_num_windows() * WINDOW_NUM_BITS
+                >= Fp::NUM_BITS as usize
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Range checks backed by a lookup table of `K`-bit values.
+
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::CellValue;
+
+/// The set of circuit instructions required to range-check values.
+pub trait RangeCheckInstructions<F: FieldExt>: Chip<F> {
+    /// Constrains `x` to be less than `2^num_bits`.
+    fn range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        x: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<(), Error>;
+}
+
+/// Configuration for a [`RangeCheckChip`].
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckConfig<F: FieldExt, const K: usize> {
+    z: Column<Advice>,
+    shift: Column<Fixed>,
+    table: TableColumn,
+    q_running: Selector,
+    q_short: Selector,
+    _marker: PhantomData<F>,
+}
+
+/// A chip implementing [`RangeCheckInstructions`] with a table of the values
+/// `0..2^K`.
+///
+/// `x` is decomposed into `K`-bit chunks with a running sum: `z_0 = x` and
+/// `z_{i+1} = (z_i - k_i) / 2^K`, where each chunk `k_i = z_i - 2^K z_{i+1}`
+/// is looked up in the table, and the final `z_n` is constrained to zero.
+/// If `num_bits` is not a multiple of `K`, the last chunk `z_{n-1}` is also
+/// looked up after being shifted left by the missing bits. A check of
+/// `num_bits` bits takes `ceil(num_bits / K) + 1` rows.
+#[derive(Clone, Debug)]
+pub struct RangeCheckChip<F: FieldExt, const K: usize> {
+    config: RangeCheckConfig<F, K>,
+}
+
+impl<F: FieldExt, const K: usize> Chip<F> for RangeCheckChip<F, K> {
+    type Config = RangeCheckConfig<F, K>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const K: usize> RangeCheckChip<F, K> {
+    /// Configures this chip for use in a circuit. The running sum column is
+    /// equality-enabled.
+    ///
+    /// The circuit must also enable a fixed column via
+    /// `ConstraintSystem::enable_constant`, and load the table with
+    /// [`RangeCheckChip::load`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is zero or larger than 16.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        z: Column<Advice>,
+        table: TableColumn,
+    ) -> RangeCheckConfig<F, K> {
+        assert!(
+            (1..=16).contains(&K),
+            "the table must have between 1 and 16 bits"
+        );
+        meta.enable_equality(z);
+
+        let shift = meta.fixed_column();
+        let q_running = meta.complex_selector();
+        let q_short = meta.complex_selector();
+
+        // Rows without either selector look up zero.
+        meta.lookup("range check", |meta| {
+            let q_running = meta.query_selector(q_running);
+            let q_short = meta.query_selector(q_short);
+            let z_prev = meta.query_advice(z, Rotation::prev());
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let shift = meta.query_fixed(shift, Rotation::cur());
+
+            let chunk = z_cur - z_next * Expression::Constant(F::from(1 << K));
+            vec![(q_running * chunk + q_short * z_prev * shift, table)]
+        });
+
+        RangeCheckConfig {
+            z,
+            shift,
+            table,
+            q_running,
+            q_short,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: RangeCheckConfig<F, K>) -> Self {
+        RangeCheckChip { config }
+    }
+
+    /// Loads the table of `K`-bit values.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "range check table",
+            |mut t| {
+                for value in 0..(1 << K) {
+                    t.assign_cell(
+                        || "value",
+                        table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const K: usize> RangeCheckInstructions<F> for RangeCheckChip<F, K> {
+    /// # Panics
+    ///
+    /// Panics if `num_bits` is zero, or too large for values of that many bits
+    /// to be distinct field elements.
+    fn range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        x: &CellValue<F>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        assert!(num_bits > 0, "cannot range-check to zero bits");
+        assert!(
+            num_bits <= F::CAPACITY as usize,
+            "cannot range-check to more bits than the field can hold"
+        );
+        let config = &self.config;
+        let num_chunks = (num_bits + K - 1) / K;
+        let start = *offset;
+        let inv_two_pow_k = F::from(1 << K).invert().unwrap();
+
+        let mut z = x.copy_advice(region, config.z, start)?.value();
+        for i in 0..num_chunks {
+            config.q_running.enable(region, start + i)?;
+            let chunk = z.map(|z| F::from((z.get_lower_32() & ((1 << K) - 1)) as u64));
+            z = (z - chunk) * Value::known(inv_two_pow_k);
+            if i + 1 < num_chunks {
+                CellValue::assign(region, config.z, start + i + 1, z)?;
+            }
+        }
+        // A witness that does not fit leaves a non-zero remainder here, which
+        // fails the running-sum lookup on the previous row.
+        CellValue::assign_constant(region, config.z, start + num_chunks, F::zero())?;
+
+        let short_bits = num_bits % K;
+        if short_bits != 0 {
+            config.q_short.enable(region, start + num_chunks)?;
+            region.assign_fixed(
+                config.shift,
+                start + num_chunks,
+                F::from(1 << (K - short_bits)),
+            );
+        }
+        *offset = start + num_chunks + 1;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::Fp;
+
+    use super::{RangeCheckChip, RangeCheckConfig, RangeCheckInstructions};
+    use crate::utilities::CellValue;
+
+    const K: usize = 10;
+
+    struct RangeCheckCircuit {
+        checks: Vec<(Value<Fp>, usize)>,
+    }
+
+    impl Circuit<Fp> for RangeCheckCircuit {
+        type Config = (RangeCheckConfig<Fp, K>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            RangeCheckCircuit {
+                checks: self
+                    .checks
+                    .iter()
+                    .map(|(_, num_bits)| (Value::unknown(), *num_bits))
+                    .collect(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let z = meta.advice_column();
+            let table = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            (RangeCheckChip::configure(meta, z, table), input)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = RangeCheckChip::construct(config);
+            chip.load(&mut layouter)?;
+
+            layouter.assign_region(
+                || "range checks",
+                |mut region| {
+                    let mut offset = 0;
+                    for (row, (value, num_bits)) in self.checks.iter().enumerate() {
+                        let x = CellValue::assign(&mut region, input, row, *value)?;
+                        chip.range_check(&mut region, &mut offset, &x, *num_bits)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn check(value: u64, num_bits: usize) -> bool {
+        let circuit = RangeCheckCircuit {
+            checks: vec![(Value::known(Fp::from(value)), num_bits)],
+        };
+        let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn range_check() {
+        for num_bits in [1, 5, 10, 13, 20, 32] {
+            let max = (1u64 << num_bits) - 1;
+            assert!(check(0, num_bits));
+            assert!(check(max, num_bits));
+            assert!(!check(max + 1, num_bits));
+        }
+    }
+
+    #[test]
+    fn several_checks_in_one_region() {
+        let circuit = RangeCheckCircuit {
+            checks: vec![
+                (Value::known(Fp::from(3)), 2),
+                (Value::known(Fp::from(1000)), 10),
+                (Value::known(Fp::from(1u64 << 40)), 41),
+            ],
+        };
+        let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn large_value_fails() {
+        // -1 is not a small value, even though its low bits are all set.
+        let circuit = RangeCheckCircuit {
+            checks: vec![(Value::known(-Fp::from(1)), 64)],
+        };
+        let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -5,16 +5,19 @@ use group::ff::Field;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_proofs::plonk::*;
-use halo2_proofs::poly::{commitment::ParamsProver, Rotation};
+use halo2_proofs::poly::{
+    commitment::{CommitmentScheme, Prover, Verifier},
+    Rotation,
+};
 use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
-use halo2curves::pasta::{EqAffine, Fp};
+use halo2curves::pasta::EqAffine;
 use rand_core::OsRng;
 
 use halo2_proofs::{
     poly::{
         ipa::{
-            commitment::{IPACommitmentScheme, ParamsIPA},
-            multiopen::ProverIPA,
+            commitment::IPACommitmentScheme,
+            multiopen::{ProverIPA, VerifierIPA},
             strategy::SingleStrategy,
         },
         VerificationStrategy,
@@ -265,9 +268,11 @@ fn criterion_benchmark(c: &mut Criterion) {
         }
     }
 
-    fn keygen(k: u32) -> (ParamsIPA<EqAffine>, ProvingKey<EqAffine>) {
-        let params: ParamsIPA<EqAffine> = ParamsIPA::new(k);
-        let empty_circuit: MyCircuit<Fp> = MyCircuit {
+    fn keygen<Scheme: CommitmentScheme>(
+        k: u32,
+    ) -> (Scheme::ParamsProver, ProvingKey<Scheme::Curve>) {
+        let params = Scheme::new_params(k);
+        let empty_circuit: MyCircuit<Scheme::Scalar> = MyCircuit {
             a: Value::unknown(),
             k,
         };
@@ -276,40 +281,51 @@ fn criterion_benchmark(c: &mut Criterion) {
         (params, pk)
     }
 
-    fn prover(k: u32, params: &ParamsIPA<EqAffine>, pk: &ProvingKey<EqAffine>) -> Vec<u8> {
+    fn prover<'params, Scheme: CommitmentScheme, P: Prover<'params, Scheme>>(
+        k: u32,
+        params: &'params Scheme::ParamsProver,
+        pk: &ProvingKey<Scheme::Curve>,
+    ) -> Vec<u8> {
         let rng = OsRng;
 
-        let circuit: MyCircuit<Fp> = MyCircuit {
-            a: Value::known(Fp::random(rng)),
+        let circuit: MyCircuit<Scheme::Scalar> = MyCircuit {
+            a: Value::known(Scheme::Scalar::random(rng)),
             k,
         };
 
-        let mut transcript = Blake2bWrite::<_, _, Challenge255<EqAffine>>::init(vec![]);
-        create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<EqAffine>, _, _, _, _>(
-            params,
-            pk,
-            &[circuit],
-            &[&[]],
-            rng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<Scheme, P, _, _, _, _>(params, pk, &[circuit], &[&[]], rng, &mut transcript)
+            .expect("proof generation should not fail");
         transcript.finalize()
     }
 
-    fn verifier(params: &ParamsIPA<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &[u8]) {
-        let strategy = SingleStrategy::new(params);
+    fn verifier<
+        'params,
+        Scheme: CommitmentScheme,
+        V: Verifier<'params, Scheme>,
+        Strategy: VerificationStrategy<'params, Scheme, V>,
+    >(
+        params: &'params Scheme::ParamsVerifier,
+        vk: &VerifyingKey<Scheme::Curve>,
+        proof: &[u8],
+    ) {
+        let strategy = Strategy::new(params);
         let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
-        assert!(verify_proof(params, vk, strategy, &[&[]], &mut transcript).is_ok());
+        assert!(
+            verify_proof::<Scheme, V, _, _, _>(params, vk, strategy, &[&[]], &mut transcript)
+                .is_ok()
+        );
     }
 
+    type Scheme = IPACommitmentScheme<EqAffine>;
+
     let k_range = 8..=16;
 
     let mut keygen_group = c.benchmark_group("plonk-keygen");
     keygen_group.sample_size(10);
     for k in k_range.clone() {
         keygen_group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, &k| {
-            b.iter(|| keygen(k));
+            b.iter(|| keygen::<Scheme>(k));
         });
     }
     keygen_group.finish();
@@ -317,13 +333,13 @@ fn criterion_benchmark(c: &mut Criterion) {
     let mut prover_group = c.benchmark_group("plonk-prover");
     prover_group.sample_size(10);
     for k in k_range.clone() {
-        let (params, pk) = keygen(k);
+        let (params, pk) = keygen::<Scheme>(k);
 
         prover_group.bench_with_input(
             BenchmarkId::from_parameter(k),
             &(k, &params, &pk),
             |b, &(k, params, pk)| {
-                b.iter(|| prover(k, params, pk));
+                b.iter(|| prover::<Scheme, ProverIPA<_>>(k, params, pk));
             },
         );
     }
@@ -331,14 +347,14 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     let mut verifier_group = c.benchmark_group("plonk-verifier");
     for k in k_range {
-        let (params, pk) = keygen(k);
-        let proof = prover(k, &params, &pk);
+        let (params, pk) = keygen::<Scheme>(k);
+        let proof = prover::<Scheme, ProverIPA<_>>(k, &params, &pk);
 
         verifier_group.bench_with_input(
             BenchmarkId::from_parameter(k),
             &(&params, pk.get_vk(), &proof[..]),
             |b, &(params, vk, proof)| {
-                b.iter(|| verifier(params, vk, proof));
+                b.iter(|| verifier::<Scheme, VerifierIPA<_>, SingleStrategy<_>>(params, vk, proof));
             },
         );
     }
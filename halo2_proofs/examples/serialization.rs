@@ -7,14 +7,14 @@ use ff::Field;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{
-        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
-        ConstraintSystem, Error, Fixed, Instance, ProvingKey,
+        create_proof, instances_to_bytes, keygen_pk, keygen_vk, verify_proof, verify_proof_bytes,
+        Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, ProvingKey,
     },
     poly::{
         kzg::{
             commitment::{KZGCommitmentScheme, ParamsKZG},
             multiopen::{ProverGWC, VerifierGWC},
-            strategy::SingleStrategy,
+            strategy::{AccumulatorStrategy, SingleStrategy},
         },
         Rotation,
     },
@@ -181,4 +181,20 @@ fn main() {
         &mut transcript
     )
     .is_ok());
+
+    // The same proof can be checked from byte buffers alone.
+    let vk_bytes = pk.get_vk().to_bytes(SerdeFormat::Processed);
+    assert!(verify_proof_bytes::<
+        StandardPlonk,
+        KZGCommitmentScheme<Bn256>,
+        VerifierGWC<'_, Bn256>,
+        AccumulatorStrategy<'_, Bn256>,
+    >(
+        &params,
+        &vk_bytes,
+        SerdeFormat::Processed,
+        &instances_to_bytes(instances),
+        &proof,
+    )
+    .unwrap());
 }
@@ -0,0 +1,146 @@
+//! A minimal verifier module for a single circuit, intended to be compiled to WASM:
+//!
+//! ```text
+//! cargo build --release --target wasm32-unknown-unknown --example wasm-verifier \
+//!     --no-default-features --features batch
+//! ```
+//!
+//! Without the default features, the module has neither the rayon thread pool
+//! (`multicore`) nor keygen and the prover (`prover`), which a verifier does not need.
+//!
+//! The module exports `alloc`/`dealloc` so the host can copy byte buffers into
+//! linear memory, and `verify`, which checks a SHPLONK proof over BN254 for
+//! `SquareCircuit` given the verifier parameters, the verifying key (in
+//! `SerdeFormat::Processed`), the instances (as produced by
+//! `plonk::instances_to_bytes`) and the proof.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        verify_proof_bytes, Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector,
+    },
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::AccumulatorStrategy,
+        },
+        Rotation,
+    },
+    SerdeFormat,
+};
+use halo2curves::bn256::{Bn256, Fr};
+
+#[derive(Clone, Copy)]
+struct SquareConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    instance: Column<Instance>,
+    s: Selector,
+}
+
+/// Proves knowledge of `a` such that `a * a` is the public instance.
+#[derive(Clone, Default)]
+struct SquareCircuit {
+    a: Value<Fr>,
+}
+
+impl Circuit<Fr> for SquareCircuit {
+    type Config = SquareConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let instance = meta.instance_column();
+        let s = meta.selector();
+        meta.enable_equality(b);
+        meta.enable_equality(instance);
+
+        meta.create_gate("square", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let s = meta.query_selector(s);
+            vec![s * (a.clone() * a - b)]
+        });
+
+        SquareConfig { a, b, instance, s }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let b = layouter.assign_region(
+            || "square",
+            |mut region| {
+                config.s.enable(&mut region, 0)?;
+                region.assign_advice(config.a, 0, self.a)?;
+                let b = region.assign_advice(config.b, 0, self.a.map(|a| a * a))?;
+                Ok(*b.cell())
+            },
+        )?;
+        layouter.constrain_instance(b, config.instance, 0);
+        Ok(())
+    }
+}
+
+/// Allocates `len` bytes in linear memory for the host to write into.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Frees a buffer previously returned by [`alloc`].
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `alloc(len)` and not freed since.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Returns 1 if the proof is valid, and 0 otherwise.
+///
+/// # Safety
+///
+/// Each `(ptr, len)` pair must describe an initialized buffer.
+#[no_mangle]
+pub unsafe extern "C" fn verify(
+    params_ptr: *const u8,
+    params_len: usize,
+    vk_ptr: *const u8,
+    vk_len: usize,
+    instances_ptr: *const u8,
+    instances_len: usize,
+    proof_ptr: *const u8,
+    proof_len: usize,
+) -> u32 {
+    let params = std::slice::from_raw_parts(params_ptr, params_len);
+    let vk = std::slice::from_raw_parts(vk_ptr, vk_len);
+    let instances = std::slice::from_raw_parts(instances_ptr, instances_len);
+    let proof = std::slice::from_raw_parts(proof_ptr, proof_len);
+
+    let params = match ParamsKZG::<Bn256>::read(&mut &params[..]) {
+        Ok(params) => params,
+        Err(_) => return 0,
+    };
+
+    verify_proof_bytes::<
+        SquareCircuit,
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        AccumulatorStrategy<'_, Bn256>,
+    >(&params, vk, SerdeFormat::Processed, instances, proof)
+    .unwrap_or(false) as u32
+}
@@ -0,0 +1,62 @@
+/*
+ * C bindings for halo2_proofs, enabled by its `ffi` feature.
+ *
+ * See the documentation of the `halo2_proofs::ffi` module for the encodings of
+ * parameters, circuits, witnesses and instances.
+ *
+ * The library must be built with `panic = "unwind"`, so that panics are
+ * reported as HALO2_PANIC rather than aborting the process.
+ */
+
+#ifndef HALO2_PROOFS_H
+#define HALO2_PROOFS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum Halo2Status {
+    HALO2_OK = 0,
+    HALO2_NULL_POINTER = 1,
+    HALO2_INVALID_INPUT = 2,
+    HALO2_SYNTHESIS = 3,
+    HALO2_VERIFICATION_FAILED = 4,
+    HALO2_PANIC = 5,
+} Halo2Status;
+
+typedef struct Halo2Params Halo2Params;
+typedef struct Halo2ProvingKey Halo2ProvingKey;
+typedef struct Halo2VerifyingKey Halo2VerifyingKey;
+
+Halo2Status halo2_params_read(const uint8_t *bytes, size_t len, Halo2Params **out);
+void halo2_params_free(Halo2Params *params);
+
+Halo2Status halo2_verifying_key_new(const Halo2Params *params,
+                                    const uint8_t *circuit, size_t circuit_len,
+                                    Halo2VerifyingKey **out);
+void halo2_verifying_key_free(Halo2VerifyingKey *vk);
+
+Halo2Status halo2_proving_key_new(const Halo2Params *params,
+                                  const uint8_t *circuit, size_t circuit_len,
+                                  Halo2ProvingKey **out);
+void halo2_proving_key_free(Halo2ProvingKey *pk);
+
+Halo2Status halo2_prove(const Halo2Params *params, const Halo2ProvingKey *pk,
+                        const uint8_t *witness, size_t witness_len,
+                        const uint8_t *instance, size_t instance_len,
+                        uint8_t **proof_out, size_t *proof_len_out);
+
+Halo2Status halo2_verify(const Halo2Params *params, const Halo2VerifyingKey *vk,
+                         const uint8_t *instance, size_t instance_len,
+                         const uint8_t *proof, size_t proof_len);
+
+void halo2_bytes_free(uint8_t *ptr, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HALO2_PROOFS_H */
@@ -1,6 +1,12 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use std::any::{Any, TypeId};
+use std::cmp;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
 use super::multicore;
 pub use ff::Field;
 use group::{
@@ -8,98 +14,294 @@ use group::{
     Curve, Group as _,
 };
 
+use crate::multicore::prelude::*;
 pub use halo2curves::{CurveAffine, CurveExt, FieldExt, Group};
+use num_bigint::{BigInt, Sign};
+use once_cell::sync::Lazy;
 
-fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
-    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+/// The number of bits of the scalars handled by [`best_multiexp`].
+const SCALAR_BITS: usize = 256;
 
-    let c = if bases.len() < 4 {
-        1
-    } else if bases.len() < 32 {
-        3
-    } else {
-        (f64::from(bases.len() as u32)).ln().ceil() as usize
-    };
+/// The largest window size considered by [`multiexp_window`].
+const MAX_WINDOW: usize = 16;
 
-    fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
-        let skip_bits = segment * c;
-        let skip_bytes = skip_bits / 8;
+/// The number of bits of the halves of scalars split by an [`Endomorphism`].
+const ENDO_SCALAR_BITS: usize = 128;
 
-        if skip_bytes >= 32 {
-            return 0;
-        }
+/// The number of terms from which [`best_multiexp`] splits scalars with the
+/// endomorphism of the curve, if it has one. Halving the number of windows saves
+/// more than splitting costs for all but the smallest multi-exponentiations,
+/// such as those of the verifier.
+const ENDO_MIN_TERMS: usize = 1 << 4;
 
-        let mut v = [0; 8];
-        for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
-            *v = *o;
-        }
+fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
+    let skip_bits = segment * c;
+    let skip_bytes = skip_bits / 8;
 
-        let mut tmp = u64::from_le_bytes(v);
-        tmp >>= skip_bits - (skip_bytes * 8);
-        tmp = tmp % (1 << c);
+    if skip_bytes >= 32 {
+        return 0;
+    }
 
-        tmp as usize
+    let mut v = [0; 8];
+    for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
+        *v = *o;
     }
 
-    let segments = (256 / c) + 1;
+    let mut tmp = u64::from_le_bytes(v);
+    tmp >>= skip_bits - (skip_bytes * 8);
+    tmp = tmp % (1 << c);
 
-    for current_segment in (0..segments).rev() {
-        for _ in 0..c {
-            *acc = acc.double();
-        }
+    tmp as usize
+}
 
-        #[derive(Clone, Copy)]
-        enum Bucket<C: CurveAffine> {
-            None,
-            Affine(C),
-            Projective(C::Curve),
-        }
+#[derive(Clone, Copy)]
+enum Bucket<C: CurveAffine> {
+    None,
+    Affine(C),
+    Projective(C::Curve),
+}
 
-        impl<C: CurveAffine> Bucket<C> {
-            fn add_assign(&mut self, other: &C) {
-                *self = match *self {
-                    Bucket::None => Bucket::Affine(*other),
-                    Bucket::Affine(a) => Bucket::Projective(a + *other),
-                    Bucket::Projective(mut a) => {
-                        a += *other;
-                        Bucket::Projective(a)
-                    }
-                }
+impl<C: CurveAffine> Bucket<C> {
+    fn add_assign(&mut self, other: &C) {
+        *self = match *self {
+            Bucket::None => Bucket::Affine(*other),
+            Bucket::Affine(a) => Bucket::Projective(a + *other),
+            Bucket::Projective(mut a) => {
+                a += *other;
+                Bucket::Projective(a)
             }
+        }
+    }
 
-            fn add(self, mut other: C::Curve) -> C::Curve {
-                match self {
-                    Bucket::None => other,
-                    Bucket::Affine(a) => {
-                        other += a;
-                        other
-                    }
-                    Bucket::Projective(a) => other + &a,
-                }
+    fn add(self, mut other: C::Curve) -> C::Curve {
+        match self {
+            Bucket::None => other,
+            Bucket::Affine(a) => {
+                other += a;
+                other
             }
+            Bucket::Projective(a) => other + &a,
         }
+    }
+}
 
-        let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
-
-        for (coeff, base) in coeffs.iter().zip(bases.iter()) {
-            let coeff = get_at::<C::Scalar>(current_segment, c, coeff);
-            if coeff != 0 {
-                buckets[coeff - 1].add_assign(base);
-            }
+/// Returns the sum of `bases`, each multiplied by the `c`-bit window of its
+/// coefficient at `segment`.
+fn multiexp_segment<C: CurveAffine>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    segment: usize,
+    c: usize,
+) -> C::Curve {
+    let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
+
+    for (coeff, base) in coeffs.iter().zip(bases.iter()) {
+        let coeff = get_at::<C::Scalar>(segment, c, coeff);
+        if coeff != 0 {
+            buckets[coeff - 1].add_assign(base);
         }
+    }
+
+    // Summation by parts
+    // e.g. 3a + 2b + 1c = a +
+    //                    (a) + b +
+    //                    ((a) + b) + c
+    let mut running_sum = C::Curve::identity();
+    let mut acc = C::Curve::identity();
+    for exp in buckets.into_iter().rev() {
+        running_sum = exp.add(running_sum);
+        acc = acc + &running_sum;
+    }
+    acc
+}
 
-        // Summation by parts
-        // e.g. 3a + 2b + 1c = a +
-        //                    (a) + b +
-        //                    ((a) + b) + c
-        let mut running_sum = C::Curve::identity();
-        for exp in buckets.into_iter().rev() {
-            running_sum = exp.add(running_sum);
-            *acc = *acc + &running_sum;
+/// Returns the window size `c` and the number of chunks the terms are split into
+/// that minimize the estimated running time of a multi-exponentiation of `len`
+/// terms with `bits`-bit scalars on `num_threads` threads.
+///
+/// Each window of `c` bits is computed by its own tasks, each of which adds its
+/// chunk of the terms into `2^c - 1` buckets and then sums the buckets with about
+/// `2^(c + 1)` additions. Windows are only split into chunks when there are fewer
+/// windows than threads.
+fn multiexp_window(len: usize, num_threads: usize, bits: usize) -> (usize, usize) {
+    let num_threads = num_threads.max(1);
+    (1..=MAX_WINDOW)
+        .map(|c| {
+            let segments = (bits + c - 1) / c;
+            let chunks = (num_threads / segments).clamp(1, len.max(1));
+            let rounds = (segments * chunks + num_threads - 1) / num_threads;
+            let cost = rounds * ((len + chunks - 1) / chunks + (1 << (c + 1)));
+            (cost, c, chunks)
+        })
+        .min()
+        .map(|(_, c, chunks)| (c, chunks))
+        .unwrap()
+}
+
+/// The decomposition of scalars for a curve with an efficiently computable
+/// endomorphism, as described by Gallant, Lambert and Vanstone.
+///
+/// The endomorphism of the curve acts on its points as multiplication by a
+/// scalar `lambda`. Every scalar `k` is split into `k1 + k2 * lambda`, where `k1`
+/// and `k2` have about half the bits of `k`, so that `[k] P` can be computed as
+/// `[k1] P + [k2] endo(P)` with half the doublings.
+#[derive(Clone, Debug)]
+pub struct Endomorphism<C: CurveAffine> {
+    lambda: C::Scalar,
+    modulus: BigInt,
+    // A reduced basis of the lattice of `(a, b)` with `a + b * lambda = 0`.
+    basis: [(BigInt, BigInt); 2],
+    _marker: PhantomData<C>,
+}
+
+impl<C: CurveAffine> Endomorphism<C> {
+    /// Returns the decomposition for the curve `C`, or `None` if the curve has no
+    /// endomorphism that acts as multiplication by a root of unity.
+    pub fn new() -> Option<Self> {
+        let zeta = C::Scalar::ZETA;
+        let generator: C::CurveExt = C::generator().to_curve();
+        let endo = generator.endo();
+        let lambda = [zeta, zeta.square(), -zeta, -zeta.square()]
+            .into_iter()
+            .filter(|lambda| !bool::from(lambda.is_zero()) && *lambda != C::Scalar::one())
+            .find(|lambda| generator * *lambda == endo)?;
+
+        let modulus = to_bigint(&-C::Scalar::one()) + 1;
+
+        // Run the extended Euclidean algorithm on the modulus and lambda, keeping
+        // remainders r = s * modulus + t * lambda, until the remainder drops below
+        // the square root of the modulus.
+        let (mut r0, mut r1) = (modulus.clone(), to_bigint(&lambda));
+        let (mut t0, mut t1) = (BigInt::from(0), BigInt::from(1));
+        while &r1 * &r1 >= modulus {
+            let q = &r0 / &r1;
+            let r2 = &r0 - &q * &r1;
+            let t2 = &t0 - &q * &t1;
+            r0 = std::mem::replace(&mut r1, r2);
+            t0 = std::mem::replace(&mut t1, t2);
+        }
+        let q = &r0 / &r1;
+        let r2 = &r0 - &q * &r1;
+        let t2 = &t0 - &q * &t1;
+
+        // Each (r, -t) is in the lattice, and the shortest of them form a basis.
+        let norm = |a: &BigInt, b: &BigInt| a * a + b * b;
+        let first = (r1, -t1);
+        let second = if norm(&r0, &t0) <= norm(&r2, &t2) {
+            (r0, -t0)
+        } else {
+            (r2, -t2)
+        };
+
+        // The halves of decomposed scalars are bounded by the basis.
+        let bound = BigInt::from(1) << (ENDO_SCALAR_BITS - 1);
+        if [&first.0, &first.1, &second.0, &second.1]
+            .iter()
+            .any(|x| x.magnitude() >= bound.magnitude())
+        {
+            return None;
         }
+
+        Some(Endomorphism {
+            lambda,
+            modulus,
+            basis: [first, second],
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the decomposition for the curve `C` like [`Self::new`], computing
+    /// it only once per curve.
+    fn cached() -> Option<&'static Self> {
+        static CACHE: Lazy<Mutex<HashMap<TypeId, &'static (dyn Any + Send + Sync)>>> =
+            Lazy::new(Default::default);
+
+        let mut cache = CACHE.lock().unwrap();
+        let endo = *cache
+            .entry(TypeId::of::<C>())
+            .or_insert_with(|| Box::leak(Box::new(Self::new())) as &(dyn Any + Send + Sync));
+        endo.downcast_ref::<Option<Self>>()
+            .expect("cached for the same curve")
+            .as_ref()
+    }
+
+    /// Returns the scalar by which the endomorphism of the curve multiplies points.
+    pub fn lambda(&self) -> C::Scalar {
+        self.lambda
+    }
+
+    /// Splits `k` into `[(k1_neg, k1), (k2_neg, k2)]` such that `k = ±k1 ± k2 *
+    /// lambda`, where the sign of each half is negative if its flag is set.
+    pub fn decompose(&self, k: &C::Scalar) -> [(bool, u128); 2] {
+        let k = to_bigint(k);
+        let [(a1, b1), (a2, b2)] = &self.basis;
+
+        // Round (k, 0) to the closest lattice point c1 * (a1, b1) + c2 * (a2, b2),
+        // and take the difference.
+        let c1 = div_round(&(b2 * &k), &self.modulus);
+        let c2 = div_round(&-(b1 * &k), &self.modulus);
+        let k1 = &k - &c1 * a1 - &c2 * a2;
+        let k2 = -(&c1 * b1) - &c2 * b2;
+
+        [to_signed_u128(&k1), to_signed_u128(&k2)]
+    }
+
+    // Returns the terms of a multi-exponentiation with half-length scalars equal to
+    // the one given.
+    fn split_terms(
+        &self,
+        coeffs: &[C::Scalar],
+        bases: &[C],
+    ) -> (Vec<<C::Scalar as PrimeField>::Repr>, Vec<C>) {
+        let mut split_coeffs = vec![Default::default(); 2 * coeffs.len()];
+        let mut split_bases = vec![C::Curve::identity(); 2 * coeffs.len()];
+        coeffs
+            .par_iter()
+            .zip(bases.par_iter())
+            .zip(split_coeffs.par_chunks_mut(2))
+            .zip(split_bases.par_chunks_mut(2))
+            .for_each(|(((coeff, base), split_coeffs), split_bases)| {
+                let [(k1_neg, k1), (k2_neg, k2)] = self.decompose(coeff);
+                let base: C::CurveExt = base.to_curve();
+                let endo = base.endo();
+                split_coeffs[0] = C::Scalar::from_u128(k1).to_repr();
+                split_coeffs[1] = C::Scalar::from_u128(k2).to_repr();
+                split_bases[0] = if k1_neg { -base } else { base };
+                split_bases[1] = if k2_neg { -endo } else { endo };
+            });
+
+        let mut affine = vec![C::identity(); split_bases.len()];
+        parallelize(&mut affine, |affine, start| {
+            C::Curve::batch_normalize(&split_bases[start..(start + affine.len())], affine);
+        });
+        (split_coeffs, affine)
     }
 }
 
+fn to_bigint<F: PrimeField>(x: &F) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, x.to_repr().as_ref())
+}
+
+// Returns `x / d` rounded to the closest integer, for positive `d`.
+fn div_round(x: &BigInt, d: &BigInt) -> BigInt {
+    // floor((2x + d) / 2d), where division truncates towards zero.
+    let (num, den) = (x * 2 + d, d * 2);
+    let q = &num / &den;
+    if num.sign() == Sign::Minus && &q * &den != num {
+        q - 1
+    } else {
+        q
+    }
+}
+
+fn to_signed_u128(x: &BigInt) -> (bool, u128) {
+    let (sign, bytes) = x.to_bytes_le();
+    assert!(bytes.len() <= 16);
+    let mut value = [0; 16];
+    value[..bytes.len()].copy_from_slice(&bytes);
+    (sign == Sign::Minus, u128::from_le_bytes(value))
+}
+
 /// Performs a small multi-exponentiation operation.
 /// Uses the double-and-add algorithm with doublings shared across points.
 pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
@@ -128,34 +330,116 @@ pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::C
 ///
 /// This function will panic if coeffs and bases have a different length.
 ///
-/// This will use multithreading if beneficial.
+/// This uses Pippenger's algorithm, with the window size chosen from the number of
+/// terms and threads. Windows are computed in parallel, and the terms of each
+/// window are split across threads when there are fewer windows than threads.
+///
+/// For large multi-exponentiations on curves with an efficient endomorphism, each
+/// scalar is split into two halves with [`Endomorphism::decompose`], which halves
+/// the number of windows.
 pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
     assert_eq!(coeffs.len(), bases.len());
 
-    let num_threads = multicore::current_num_threads();
-    if coeffs.len() > num_threads {
-        let chunk = coeffs.len() / num_threads;
-        let num_chunks = coeffs.chunks(chunk).len();
-        let mut results = vec![C::Curve::identity(); num_chunks];
-        multicore::scope(|scope| {
-            let chunk = coeffs.len() / num_threads;
+    if coeffs.len() >= ENDO_MIN_TERMS {
+        if let Some(endo) = Endomorphism::<C>::cached() {
+            let (coeffs, bases) = endo.split_terms(coeffs, bases);
+            return multiexp_repr(&coeffs, &bases, ENDO_SCALAR_BITS);
+        }
+    }
 
-            for ((coeffs, bases), acc) in coeffs
-                .chunks(chunk)
-                .zip(bases.chunks(chunk))
-                .zip(results.iter_mut())
-            {
+    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+    multiexp_repr(&coeffs, bases, SCALAR_BITS)
+}
+
+// Performs a multi-exponentiation of scalars of at most `bits` bits, given in
+// their little-endian representation.
+fn multiexp_repr<C: CurveAffine>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    bits: usize,
+) -> C::Curve {
+    // Spawning tasks costs more than it saves for the handful of terms
+    // multiplied in each round of the inner product argument.
+    let num_threads = if coeffs.len() > multicore::current_num_threads() {
+        multicore::current_num_threads()
+    } else {
+        1
+    };
+    let (c, chunks) = multiexp_window(coeffs.len(), num_threads, bits);
+    let segments = (bits + c - 1) / c;
+    let chunk_len = cmp::max((coeffs.len() + chunks - 1) / chunks, 1);
+
+    // The sums of every window, arranged as [segment][chunk].
+    let mut sums = vec![C::Curve::identity(); segments * chunks];
+    if num_threads == 1 {
+        for (segment, sum) in sums.iter_mut().enumerate() {
+            *sum = multiexp_segment(coeffs, bases, segment, c);
+        }
+    } else {
+        multicore::scope(|scope| {
+            for (i, sum) in sums.iter_mut().enumerate() {
+                let (segment, chunk) = (i / chunks, i % chunks);
+                let start = cmp::min(chunk * chunk_len, coeffs.len());
+                let end = cmp::min(start + chunk_len, coeffs.len());
                 scope.spawn(move |_| {
-                    multiexp_serial(coeffs, bases, acc);
+                    *sum = multiexp_segment(&coeffs[start..end], &bases[start..end], segment, c);
                 });
             }
         });
-        results.iter().fold(C::Curve::identity(), |a, b| a + b)
-    } else {
-        let mut acc = C::Curve::identity();
-        multiexp_serial(coeffs, bases, &mut acc);
-        acc
     }
+
+    let mut acc = C::Curve::identity();
+    for segment_sums in sums.chunks(chunks).rev() {
+        for _ in 0..c {
+            acc = acc.double();
+        }
+        for sum in segment_sums {
+            acc += sum;
+        }
+    }
+    acc
+}
+
+/// Returns the number of terms of a multi-exponentiation whose coefficients and
+/// bases fit within `budget` bytes of memory, which is always at least one.
+pub fn multiexp_chunk_len<C: CurveAffine>(budget: usize) -> usize {
+    (budget / (std::mem::size_of::<C::Scalar>() + std::mem::size_of::<C>())).max(1)
+}
+
+/// Performs a multi-exponentiation operation on a device with a limited amount of
+/// memory, such as a GPU.
+///
+/// The terms are split into chunks whose coefficients and bases fit within
+/// `budget` bytes, and each chunk is passed to `device` in turn. Once `device`
+/// declines a chunk by returning `None`, that chunk and the remaining ones are
+/// computed with [`best_multiexp`] instead.
+///
+/// This function will panic if coeffs and bases have a different length.
+pub fn chunked_multiexp<C: CurveAffine>(
+    coeffs: &[C::Scalar],
+    bases: &[C],
+    budget: usize,
+    mut device: impl FnMut(&[C::Scalar], &[C]) -> Option<C::Curve>,
+) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+
+    let chunk = multiexp_chunk_len::<C>(budget);
+    let mut acc = C::Curve::identity();
+    let mut offset = 0;
+    while offset < coeffs.len() {
+        let end = std::cmp::min(offset + chunk, coeffs.len());
+        match device(&coeffs[offset..end], &bases[offset..end]) {
+            Some(result) => acc += result,
+            None => break,
+        }
+        offset = end;
+    }
+
+    // Fall back to the CPU for whatever the device did not compute.
+    if offset < coeffs.len() {
+        acc += best_multiexp(&coeffs[offset..], &bases[offset..]);
+    }
+    acc
 }
 
 /// Performs a radix-$2$ Fast-Fourier Transformation (FFT) on a vector of size
@@ -183,23 +467,23 @@ pub fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
     let n = a.len() as usize;
     assert_eq!(n, 1 << log_n);
 
-    for k in 0..n {
-        let rk = bitreverse(k, log_n as usize);
-        if k < rk {
-            a.swap(rk, k);
+    if log_n <= log_threads {
+        for k in 0..n {
+            let rk = bitreverse(k, log_n as usize);
+            if k < rk {
+                a.swap(rk, k);
+            }
         }
-    }
 
-    // precompute twiddle factors
-    let twiddles: Vec<_> = (0..(n / 2) as usize)
-        .scan(G::Scalar::one(), |w, _| {
-            let tw = *w;
-            w.group_scale(&omega);
-            Some(tw)
-        })
-        .collect();
+        // precompute twiddle factors
+        let twiddles: Vec<_> = (0..(n / 2) as usize)
+            .scan(G::Scalar::one(), |w, _| {
+                let tw = *w;
+                w.group_scale(&omega);
+                Some(tw)
+            })
+            .collect();
 
-    if log_n <= log_threads {
         let mut chunk = 2_usize;
         let mut twiddle_chunk = (n / 2) as usize;
         for _ in 0..log_n {
@@ -229,10 +513,33 @@ pub fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
             twiddle_chunk /= 2;
         }
     } else {
+        // For large domains the permutation and the twiddle factors are also
+        // computed across threads, rather than serially before the butterflies.
+        let permuted = a.to_vec();
+        parallelize(a, |a, start| {
+            for (k, a) in a.iter_mut().enumerate() {
+                *a = permuted[bitreverse(start + k, log_n as usize)];
+            }
+        });
+        drop(permuted);
+
+        let mut twiddles = vec![G::Scalar::zero(); n / 2];
+        parallelize(&mut twiddles, |twiddles, start| {
+            let mut w = omega.pow_vartime(&[start as u64, 0, 0, 0]);
+            for tw in twiddles.iter_mut() {
+                *tw = w;
+                w.group_scale(&omega);
+            }
+        });
+
         recursive_butterfly_arithmetic(a, n, 1, &twiddles)
     }
 }
 
+/// The minimum number of butterflies that [`recursive_butterfly_arithmetic`]
+/// hands to a single thread when combining two halves.
+const MIN_BUTTERFLY_CHUNK: usize = 1 << 10;
+
 /// This perform recursive butterfly arithmetic
 pub fn recursive_butterfly_arithmetic<G: Group>(
     a: &mut [G],
@@ -247,7 +554,7 @@ pub fn recursive_butterfly_arithmetic<G: Group>(
         a[1].group_sub(&t);
     } else {
         let (left, right) = a.split_at_mut(n / 2);
-        rayon::join(
+        multicore::join(
             || recursive_butterfly_arithmetic(left, n / 2, twiddle_chunk * 2, twiddles),
             || recursive_butterfly_arithmetic(right, n / 2, twiddle_chunk * 2, twiddles),
         );
@@ -260,15 +567,26 @@ pub fn recursive_butterfly_arithmetic<G: Group>(
         a[0].group_add(&t);
         b[0].group_sub(&t);
 
-        left.iter_mut()
-            .zip(right.iter_mut())
+        // The top layers have few halves to combine, so each combination is
+        // itself split across threads, breadth-first.
+        let chunk = cmp::max(
+            n / 2 / multicore::current_num_threads(),
+            MIN_BUTTERFLY_CHUNK,
+        );
+        left.par_chunks_mut(chunk)
+            .zip(right.par_chunks_mut(chunk))
             .enumerate()
-            .for_each(|(i, (a, b))| {
-                let mut t = *b;
-                t.group_scale(&twiddles[(i + 1) * twiddle_chunk]);
-                *b = *a;
-                a.group_add(&t);
-                b.group_sub(&t);
+            .for_each(|(j, (left, right))| {
+                left.iter_mut()
+                    .zip(right.iter_mut())
+                    .enumerate()
+                    .for_each(|(i, (a, b))| {
+                        let mut t = *b;
+                        t.group_scale(&twiddles[(j * chunk + i + 1) * twiddle_chunk]);
+                        *b = *a;
+                        a.group_add(&t);
+                        b.group_sub(&t);
+                    });
             });
     }
 }
@@ -387,6 +705,19 @@ pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mu
     });
 }
 
+/// Replaces every element of `values` with its inverse, using Montgomery's trick
+/// on chunks of `values` in parallel. Each chunk takes a single field inversion
+/// and three multiplications per element, which is much faster than inverting
+/// each element separately. Zero elements are left unchanged.
+pub fn batch_invert<F: Field>(values: &mut [F]) {
+    if values.is_empty() {
+        return;
+    }
+    parallelize(values, |values, _| {
+        values.iter_mut().batch_invert();
+    });
+}
+
 fn log2_floor(num: usize) -> u32 {
     assert!(num > 0);
 
@@ -506,3 +837,150 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+#[test]
+fn test_batch_invert() {
+    let rng = OsRng;
+
+    for len in [0, 1, 7, 1000] {
+        let mut values = (0..len).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        if len > 1 {
+            values[1] = Fp::zero();
+        }
+        let expected = values
+            .iter()
+            .map(|value| value.invert().unwrap_or(Fp::zero()))
+            .collect::<Vec<_>>();
+
+        batch_invert(&mut values);
+        assert_eq!(values, expected);
+    }
+}
+
+#[test]
+fn test_chunked_multiexp() {
+    use crate::halo2curves::pasta::EqAffine;
+
+    let rng = OsRng;
+
+    let coeffs = (0..100).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+    let bases = (0..100)
+        .map(|_| <EqAffine as CurveAffine>::CurveExt::random(rng).to_affine())
+        .collect::<Vec<_>>();
+    let expected = best_multiexp(&coeffs, &bases);
+
+    // Room for ten terms per chunk.
+    let budget = 10 * (std::mem::size_of::<Fp>() + std::mem::size_of::<EqAffine>());
+    assert_eq!(multiexp_chunk_len::<EqAffine>(budget), 10);
+
+    // A device that computes every chunk.
+    let mut chunks = 0;
+    let result = chunked_multiexp(&coeffs, &bases, budget, |coeffs, bases| {
+        assert!(coeffs.len() <= 10);
+        chunks += 1;
+        Some(small_multiexp(coeffs, bases))
+    });
+    assert_eq!(result, expected);
+    assert_eq!(chunks, 10);
+
+    // A device that runs out of memory after three chunks.
+    let mut chunks = 0;
+    let result = chunked_multiexp(&coeffs, &bases, budget, |coeffs, bases| {
+        chunks += 1;
+        (chunks <= 3).then(|| small_multiexp(coeffs, bases))
+    });
+    assert_eq!(result, expected);
+    assert_eq!(chunks, 4);
+}
+
+#[test]
+fn test_best_multiexp() {
+    use crate::halo2curves::pasta::EqAffine;
+
+    let rng = OsRng;
+
+    let coeffs = (0..1000).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+    let bases = (0..1000)
+        .map(|_| <EqAffine as CurveAffine>::CurveExt::random(rng).to_affine())
+        .collect::<Vec<_>>();
+
+    // Cover the serial path for a few terms, the one that splits scalars with the
+    // endomorphism, and the multithreaded one.
+    for len in [0, 1, 3, ENDO_MIN_TERMS - 1, ENDO_MIN_TERMS, 1000] {
+        assert_eq!(
+            best_multiexp(&coeffs[..len], &bases[..len]),
+            small_multiexp(&coeffs[..len], &bases[..len]),
+        );
+    }
+}
+
+#[test]
+fn test_endomorphism() {
+    fn check<C: CurveAffine>() {
+        let endo = Endomorphism::<C>::new().unwrap();
+        assert_eq!(
+            Endomorphism::<C>::cached().map(Endomorphism::<C>::lambda),
+            Some(endo.lambda())
+        );
+        let generator: C::CurveExt = C::generator().to_curve();
+        assert_eq!(generator * endo.lambda(), generator.endo());
+
+        let signed = |(neg, k): (bool, u128)| {
+            let k = C::Scalar::from_u128(k);
+            if neg {
+                -k
+            } else {
+                k
+            }
+        };
+        for k in [
+            C::Scalar::zero(),
+            -C::Scalar::one(),
+            C::Scalar::random(OsRng),
+        ] {
+            let [k1, k2] = endo.decompose(&k);
+            assert_eq!(signed(k1) + signed(k2) * endo.lambda(), k);
+        }
+    }
+
+    check::<crate::halo2curves::pasta::EqAffine>();
+    check::<crate::halo2curves::pasta::EpAffine>();
+    check::<crate::halo2curves::bn256::G1Affine>();
+}
+
+#[test]
+fn test_multiexp_window() {
+    // Wider windows amortize the bucket sums over more terms.
+    let (small, _) = multiexp_window(1 << 10, 1, SCALAR_BITS);
+    let (large, _) = multiexp_window(1 << 20, 1, SCALAR_BITS);
+    assert!(small < large);
+    assert_eq!(multiexp_window(1 << 20, 1, SCALAR_BITS).1, 1);
+
+    // With more threads than windows, the terms are split into chunks.
+    let (c, chunks) = multiexp_window(1 << 20, 256, SCALAR_BITS);
+    assert!(chunks > 1);
+    assert!(((SCALAR_BITS + c - 1) / c) * chunks <= 256);
+}
+
+#[test]
+fn test_best_fft() {
+    let rng = OsRng;
+
+    // Cover both the serial path for small domains and the multithreaded one.
+    for log_n in [1, 3, 12] {
+        let n = 1 << log_n;
+        let mut omega = Fp::root_of_unity();
+        for _ in log_n..Fp::S {
+            omega = omega.square();
+        }
+
+        let coeffs = (0..n).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        let mut evals = coeffs.clone();
+        best_fft(&mut evals, omega, log_n);
+
+        for (i, eval) in evals.iter().enumerate() {
+            let point = omega.pow_vartime(&[i as u64, 0, 0, 0]);
+            assert_eq!(eval_polynomial(&coeffs, point), *eval);
+        }
+    }
+}
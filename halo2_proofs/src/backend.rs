@@ -0,0 +1,102 @@
+//! Backends that perform the expensive arithmetic of the prover.
+//!
+//! The prover routes multi-exponentiations, FFTs and batch inversions through the
+//! [`Backend`] of the commitment parameters, so that hardware acceleration such as
+//! a GPU or FPGA can be supplied by another crate. Each method defaults to the CPU
+//! implementation, so a backend only overrides the operations it accelerates.
+
+use std::fmt;
+
+use crate::arithmetic::{batch_invert, best_fft, best_multiexp, CurveAffine};
+
+/// Performs the expensive arithmetic of the prover for the curve `C`.
+pub trait Backend<C: CurveAffine>: fmt::Debug + Send + Sync {
+    /// Performs a multi-exponentiation operation, as [`best_multiexp`] does.
+    ///
+    /// `coeffs` and `bases` have the same length.
+    fn msm(&self, coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+        best_multiexp(coeffs, bases)
+    }
+
+    /// Performs an FFT of size `2^log_n` in place, as [`best_fft`] does.
+    fn fft(&self, a: &mut [C::Scalar], omega: C::Scalar, log_n: u32) {
+        best_fft(a, omega, log_n)
+    }
+
+    /// Replaces every element of `values` with its inverse, as [`batch_invert`]
+    /// does. Zero elements are left unchanged.
+    fn batch_invert(&self, values: &mut [C::Scalar]) {
+        batch_invert(values)
+    }
+}
+
+/// The default backend, which runs on the CPU with multithreading.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cpu;
+
+impl<C: CurveAffine> Backend<C> for Cpu {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use ff::Field;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine, G1};
+    use rand_core::OsRng;
+
+    use super::Backend;
+    use crate::poly::{
+        commitment::{Blind, Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+        EvaluationDomain,
+    };
+
+    // A backend that counts the operations routed through it.
+    #[derive(Debug, Default)]
+    struct Counting {
+        msms: Arc<AtomicUsize>,
+        ffts: Arc<AtomicUsize>,
+    }
+
+    impl Backend<G1Affine> for Counting {
+        fn msm(&self, coeffs: &[Fr], bases: &[G1Affine]) -> G1 {
+            self.msms.fetch_add(1, Ordering::Relaxed);
+            crate::arithmetic::best_multiexp(coeffs, bases)
+        }
+
+        fn fft(&self, a: &mut [Fr], omega: Fr, log_n: u32) {
+            self.ffts.fetch_add(1, Ordering::Relaxed);
+            crate::arithmetic::best_fft(a, omega, log_n)
+        }
+    }
+
+    #[test]
+    fn routes_through_backend() {
+        const K: u32 = 4;
+
+        let backend = Counting::default();
+        let (msms, ffts) = (backend.msms.clone(), backend.ffts.clone());
+        let cpu = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let params = cpu.clone().with_backend(backend);
+
+        let domain = EvaluationDomain::new(3, K);
+        let values = domain.lagrange_from_vec((0..1 << K).map(|_| Fr::random(OsRng)).collect());
+        let poly = domain.lagrange_to_coeff_with(params.backend(), values.clone());
+        assert_eq!(
+            poly.to_vec(),
+            domain.lagrange_to_coeff(values.clone()).to_vec()
+        );
+        assert_eq!(ffts.load(Ordering::Relaxed), 1);
+
+        assert_eq!(
+            params.commit_lagrange(&values, Blind::default()),
+            cpu.commit_lagrange(&values, Blind::default())
+        );
+        assert_eq!(
+            params.commit(&poly, Blind::default()),
+            cpu.commit(&poly, Blind::default())
+        );
+        assert_eq!(msms.load(Ordering::Relaxed), 2);
+    }
+}
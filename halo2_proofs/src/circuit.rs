@@ -341,6 +341,9 @@ impl<'r, F: Field> Region<'r, F> {
 
     /// Constrains two cells to have the same value.
     ///
+    /// The cells may be in advice, fixed or instance columns, so for example an
+    /// advice cell can be wired to a constant held in a fixed column.
+    ///
     /// Returns an error if either of the cells are in columns where equality
     /// has not been enabled.
     pub fn constrain_equal(&mut self, left: &Cell, right: &Cell) {
@@ -427,6 +430,62 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assigns several regions, possibly in parallel, returning their results in order.
+    ///
+    /// The regions must not assign any cell that another region in the same call
+    /// assigns. Within them, [`Region::assign_advice_from_instance`] fails with
+    /// [`Error::InstanceInParallelRegion`], challenges are unknown, and the synthesis cannot move to
+    /// the next phase.
+    ///
+    /// ```ignore
+    /// let rows = layouter.assign_regions(|| "rows", (0..n).map(|i| {
+    ///     move |mut region: Region<'_, F>| region.assign_advice(config.a, i, values[i])
+    /// }).collect())?;
+    /// ```
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnOnce(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .map(|assignment| self.assign_region(&name, assignment))
+            .collect()
+    }
+
+    /// Assigns `count` copies of a region of `height` rows as a single region, the `i`-th
+    /// copy starting at row `start + i * height`, and returns their results in order.
+    ///
+    /// `assignment` is called with each copy and its index, and uses offsets relative to
+    /// the start of the copy; an offset of `height` or more fails with
+    /// [`Error::RowOutsideRegion`]. The selectors and fixed cells of the first copy are recorded
+    /// once and replayed in the other copies, whose own selectors and fixed cells are
+    /// ignored, so every copy must enable the same selectors and assign the same fixed
+    /// cells. Debug builds panic if a copy does not.
+    fn assign_repeated_region<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        start: usize,
+        height: usize,
+        count: usize,
+        assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>, usize) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.assign_region(name, |region| {
+            layouter::assign_repeated(region.region, start, height, count, false, assignment)
+        })
+    }
+
     /// Assign a table region to an absolute row number.
     ///
     /// ```ignore
@@ -497,6 +556,37 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_region(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnOnce(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
+    fn assign_repeated_region<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        start: usize,
+        height: usize,
+        count: usize,
+        assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>, usize) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0
+            .assign_repeated_region(name, start, height, count, assignment)
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -5,14 +5,17 @@ use std::marker::PhantomData;
 use ff::Field;
 use rustc_hash::FxHashMap;
 
+use crate::multicore::prelude::*;
 use crate::{
     circuit::{
-        layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
+        layouter::{
+            self, RegionColumn, RegionLayouter, RegionRecorder, RegionShape, TableLayouter,
+        },
         AssignedCell, Cell, Layouter, Region, RegionIndex, RegionStart, Table, Value,
     },
     plonk::{
         Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, Error, Fixed, FloorPlanner,
-        Instance, Selector, TableColumn,
+        Instance, Selector, TableColumn, TableError,
     },
 };
 
@@ -21,6 +24,10 @@ use crate::{
 /// This floor planner is suitable for debugging circuits. It aims to reflect the circuit
 /// "business logic" in the circuit layout as closely as possible. It uses a single-pass
 /// layouter that does not reorder regions for optimal packing.
+///
+/// Every region starts at row 0, so region offsets are absolute rows and the circuit
+/// decides where each region's cells go. Regions that use disjoint columns can share
+/// rows simply by using the same offsets; the layouter does not move them.
 #[derive(Debug)]
 pub struct SimpleFloorPlanner;
 
@@ -47,6 +54,9 @@ pub struct SingleChipLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
     columns: FxHashMap<RegionColumn, usize>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
+    /// Keeps the advice values of regions assigned in parallel alive, as their cells
+    /// refer to them.
+    recorded_values: Vec<Box<Assigned<F>>>,
     _marker: PhantomData<F>,
 }
 
@@ -68,10 +78,52 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
             // regions: vec![],
             columns: FxHashMap::default(),
             table_columns: vec![],
+            recorded_values: vec![],
             _marker: PhantomData,
         };
         Ok(ret)
     }
+
+    /// Assigns the constants of a region and copies them to their advice cells.
+    fn assign_constants(
+        &mut self,
+        constants_to_assign: Vec<(Assigned<F>, Cell)>,
+    ) -> Result<(), Error> {
+        // Assign constants. For the simple floor planner, we assign constants in order in
+        // the first `constants` column. When only collecting the witness, the constants
+        // are already in the proving key.
+        if self.cs.is_witness_only() {
+            return Ok(());
+        }
+        if self.constants.is_empty() {
+            if !constants_to_assign.is_empty() {
+                return Err(Error::NotEnoughColumnsForConstants);
+            }
+        } else {
+            let constants_column = self.constants[0];
+            let next_constant_row = self
+                .columns
+                .entry(Column::<Any>::from(constants_column).into())
+                .or_default();
+            for (constant, advice) in constants_to_assign {
+                self.cs.assign_fixed(
+                    //|| format!("Constant({:?})", constant.evaluate()),
+                    constants_column,
+                    *next_constant_row,
+                    constant,
+                );
+                self.cs.copy(
+                    constants_column.into(),
+                    *next_constant_row,
+                    advice.column,
+                    advice.row_offset, // *self.regions[*advice.region_index] + advice.row_offset,
+                );
+                *next_constant_row += 1;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a, F, CS> {
@@ -116,44 +168,92 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         let constants_to_assign = region.constants;
         self.cs.exit_region();
 
-        // Assign constants. For the simple floor planner, we assign constants in order in
-        // the first `constants` column.
-        if self.constants.is_empty() {
-            if !constants_to_assign.is_empty() {
-                return Err(Error::NotEnoughColumnsForConstants);
-            }
-        } else {
-            let constants_column = self.constants[0];
-            let next_constant_row = self
-                .columns
-                .entry(Column::<Any>::from(constants_column).into())
-                .or_default();
-            for (constant, advice) in constants_to_assign {
-                self.cs.assign_fixed(
-                    //|| format!("Constant({:?})", constant.evaluate()),
-                    constants_column,
-                    *next_constant_row,
-                    constant,
-                );
-                self.cs.copy(
-                    constants_column.into(),
-                    *next_constant_row,
-                    advice.column,
-                    advice.row_offset, // *self.regions[*advice.region_index] + advice.row_offset,
-                );
-                *next_constant_row += 1;
-            }
-        }
+        self.assign_constants(constants_to_assign)?;
 
         Ok(result)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnOnce(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        // Synthesize the regions on the thread pool, recording their assignments, and
+        // then make the assignments in order.
+        let recorded = assignments
+            .into_par_iter()
+            .map(|assignment| {
+                let mut recorder = RegionRecorder::new();
+                let result = {
+                    let region: &mut dyn RegionLayouter<F> = &mut recorder;
+                    assignment(region.into())
+                }?;
+                Ok((result, recorder))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut results = Vec::with_capacity(recorded.len());
+        for (result, recorder) in recorded {
+            self.cs.enter_region(&name);
+            let mut region = SingleChipLayouterRegion::new(self, 0.into());
+            let values = recorder.replay(&mut region)?;
+            let constants_to_assign = region.constants;
+            self.cs.exit_region();
+            self.assign_constants(constants_to_assign)?;
+
+            self.recorded_values.extend(values);
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    fn assign_repeated_region<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        start: usize,
+        height: usize,
+        count: usize,
+        assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>, usize) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        // The selectors and fixed cells are already in the proving key when only
+        // collecting the witness, so the template is neither recorded nor replayed.
+        let witness_only = self.cs.is_witness_only();
+        self.assign_region(name, |region| {
+            layouter::assign_repeated(
+                region.region,
+                start,
+                height,
+                count,
+                witness_only,
+                assignment,
+            )
+        })
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, mut assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
         N: Fn() -> NR,
         NR: Into<String>,
     {
+        // The table is already in the proving key when only collecting the witness, so
+        // skip computing it.
+        if self.cs.is_witness_only() {
+            return Ok(());
+        }
+
         // Maintenance hazard: there is near-duplicate code in `v1::AssignmentPass::assign_table`.
         // Assign table cells.
         self.cs.enter_region(name);
@@ -167,24 +267,7 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
 
         // Check that all table columns have the same length `first_unused`,
         // and all cells up to that length are assigned.
-        let first_unused = {
-            match default_and_assigned
-                .values()
-                .map(|(_, assigned)| {
-                    if assigned.iter().all(|b| *b) {
-                        Some(assigned.len())
-                    } else {
-                        None
-                    }
-                })
-                .reduce(|acc, item| match (acc, item) {
-                    (Some(a), Some(b)) if a == b => Some(a),
-                    _ => None,
-                }) {
-                Some(Some(len)) => len,
-                _ => return Err(Error::Synthesis), // TODO better error
-            }
-        };
+        let first_unused = compute_table_lengths(&default_and_assigned)?;
 
         // Record these columns so that we can prevent them from being used again.
         for column in default_and_assigned.keys() {
@@ -269,6 +352,9 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
         selector: &Selector,
         offset: usize,
     ) -> Result<(), Error> {
+        if self.layouter.cs.is_witness_only() {
+            return Ok(());
+        }
         self.layouter.cs.enable_selector(
             annotation, selector,
             offset, // *self.layouter.regions[*self.region_index] + offset,
@@ -345,10 +431,12 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
         offset: usize,
         to: Assigned<F>,
     ) -> Cell {
-        self.layouter.cs.assign_fixed(
-            column, offset, // *self.layouter.regions[*self.region_index] + offset,
-            to,
-        );
+        if !self.layouter.cs.is_witness_only() {
+            self.layouter.cs.assign_fixed(
+                column, offset, // *self.layouter.regions[*self.region_index] + offset,
+                to,
+            );
+        }
 
         Cell {
             // region_index: self.region_index,
@@ -358,7 +446,9 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
     }
 
     fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
-        self.constants.push((constant, cell));
+        if !self.layouter.cs.is_witness_only() {
+            self.constants.push((constant, cell));
+        }
         Ok(())
     }
 
@@ -389,6 +479,30 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
 ///   witnesses or not.
 type DefaultTableValue<F> = Option<Value<Assigned<F>>>;
 
+/// Checks that all table columns are assigned at every row up to the same length,
+/// and returns that length.
+pub(crate) fn compute_table_lengths<F: Field>(
+    default_and_assigned: &FxHashMap<TableColumn, (DefaultTableValue<F>, Vec<bool>)>,
+) -> Result<usize, Error> {
+    let mut table_len: Option<(TableColumn, usize)> = None;
+    for (col, (default_value, assigned)) in default_and_assigned.iter() {
+        if default_value.is_none() || !assigned.iter().all(|b| *b) {
+            return Err(Error::TableError(TableError::ColumnNotAssigned(*col)));
+        }
+        match table_len {
+            None => table_len = Some((*col, assigned.len())),
+            Some((table, len)) if len != assigned.len() => {
+                return Err(Error::TableError(TableError::UnevenColumnLengths(
+                    (*col, assigned.len()),
+                    (table, len),
+                )))
+            }
+            Some(_) => (),
+        }
+    }
+    table_len.map(|(_, len)| len).ok_or(Error::Synthesis)
+}
+
 pub(crate) struct SimpleTableLayouter<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
     cs: &'a mut CS,
     used_columns: &'r [TableColumn],
@@ -426,7 +540,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
         to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
     ) -> Result<(), Error> {
         if self.used_columns.contains(&column) {
-            return Err(Error::Synthesis); // TODO better error
+            return Err(Error::TableError(TableError::UsedColumn(column)));
         }
 
         let entry = self.default_and_assigned.entry(column).or_default();
@@ -439,7 +553,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
             {
                 let res = to();
                 value = res;
-                res.assign()?
+                res.assign(column.inner(), offset)?
             },
         );
 
@@ -448,7 +562,13 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
             (true, 0) => entry.0 = Some(value),
             // Since there is already an existing default value for this table column,
             // the caller should not be attempting to assign another value at offset 0.
-            (false, 0) => return Err(Error::Synthesis), // TODO better error
+            (false, 0) => {
+                return Err(Error::TableError(TableError::OverwriteDefault(
+                    column,
+                    format!("{:?}", entry.0.unwrap()),
+                    format!("{:?}", value),
+                )))
+            }
             _ => (),
         }
         if entry.1.len() <= offset {
@@ -466,8 +586,9 @@ mod tests {
 
     use super::SimpleFloorPlanner;
     use crate::{
+        circuit::{Layouter, Value},
         dev::MockProver,
-        plonk::{Advice, Circuit, Column, Error},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, TableColumn, TableError},
     };
 
     #[test]
@@ -513,4 +634,432 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn table_errors() {
+        /// Assigns a table with columns of the given lengths, assigning offset 0 of
+        /// the first column twice if `overwrite` is set.
+        struct MyCircuit {
+            lengths: [usize; 2],
+            overwrite: bool,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = [TableColumn; 2];
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    lengths: self.lengths,
+                    overwrite: self.overwrite,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                [meta.lookup_table_column(), meta.lookup_table_column()]
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "table",
+                    |mut table| {
+                        for (column, len) in config.iter().zip(self.lengths) {
+                            for offset in 0..len {
+                                let value = vesta::Scalar::from(offset as u64);
+                                table.assign_cell(
+                                    || "",
+                                    *column,
+                                    offset,
+                                    || Value::known(value),
+                                )?;
+                            }
+                        }
+                        if self.overwrite {
+                            let value = vesta::Scalar::one();
+                            table.assign_cell(|| "", config[0], 0, || Value::known(value))?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let run = |lengths, overwrite| {
+            MockProver::run(4, &MyCircuit { lengths, overwrite }, vec![]).map(|_| ())
+        };
+        assert!(run([2, 2], false).is_ok());
+        assert!(matches!(
+            run([2, 3], false).unwrap_err(),
+            Error::TableError(TableError::UnevenColumnLengths(..)),
+        ));
+        assert!(matches!(
+            run([2, 2], true).unwrap_err(),
+            Error::TableError(TableError::OverwriteDefault(..)),
+        ));
+    }
+
+    #[test]
+    fn parallel_regions() {
+        use crate::plonk::{Expression, Instance, Selector};
+        use crate::poly::Rotation;
+        use ff::Field;
+
+        #[derive(Clone, Copy)]
+        struct MyConfig {
+            a: Column<Advice>,
+            s: Selector,
+            instance: Column<Instance>,
+        }
+
+        /// Each region witnesses `x` and `x + 1` in consecutive rows.
+        struct MyCircuit {
+            xs: Vec<Value<vesta::Scalar>>,
+            increment: vesta::Scalar,
+            use_instance: bool,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = MyConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    xs: vec![Value::unknown(); self.xs.len()],
+                    increment: self.increment,
+                    use_instance: self.use_instance,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let a = meta.advice_column();
+                let s = meta.selector();
+                let instance = meta.instance_column();
+                let constants = meta.fixed_column();
+                meta.enable_equality(a);
+                meta.enable_equality(instance);
+                meta.enable_constant(constants);
+                meta.create_gate("increment", |meta| {
+                    let s = meta.query_selector(s);
+                    let cur = meta.query_advice(a, Rotation::cur());
+                    let next = meta.query_advice(a, Rotation::next());
+                    vec![s * (next - cur - Expression::Constant(vesta::Scalar::one()))]
+                });
+                MyConfig { a, s, instance }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                let assignments = self
+                    .xs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| {
+                        let x = *x;
+                        let increment = self.increment;
+                        let use_instance = self.use_instance;
+                        move |mut region: crate::circuit::Region<'_, vesta::Scalar>| {
+                            let row = 2 * i;
+                            config.s.enable(&mut region, row)?;
+                            let x = if i == 0 && use_instance {
+                                region
+                                    .assign_advice_from_instance(
+                                        || "x",
+                                        config.instance,
+                                        0,
+                                        config.a,
+                                        row,
+                                    )?
+                                    .value()
+                                    .copied()
+                            } else if i == 0 {
+                                region.assign_advice_from_constant(
+                                    || "x",
+                                    config.a,
+                                    row,
+                                    vesta::Scalar::zero(),
+                                )?;
+                                Value::known(vesta::Scalar::zero())
+                            } else {
+                                region.assign_advice(config.a, row, x)?;
+                                x
+                            };
+                            region.assign_advice(config.a, row + 1, x + Value::known(increment))?;
+                            Ok(x)
+                        }
+                    })
+                    .collect();
+                let xs = layouter.assign_regions(|| "increments", assignments)?;
+
+                // The results come back in the order of the regions.
+                for (x, expected) in xs.iter().zip(self.xs.iter()) {
+                    x.zip(*expected)
+                        .assert_if_known(|(x, expected)| x == expected);
+                }
+                Ok(())
+            }
+        }
+
+        let xs: Vec<_> = (0..8u64)
+            .map(|x| Value::known(vesta::Scalar::from(x * x)))
+            .collect();
+        let run = |increment: u64, use_instance| {
+            let circuit = MyCircuit {
+                xs: xs.clone(),
+                increment: vesta::Scalar::from(increment),
+                use_instance,
+            };
+            MockProver::run(5, &circuit, vec![vec![]]).map(|prover| prover.verify().is_ok())
+        };
+        assert!(matches!(run(1, false), Ok(true)));
+        assert!(matches!(run(2, false), Ok(false)));
+        // Instance cells cannot be read while regions are assigned in parallel.
+        assert!(matches!(
+            run(1, true),
+            Err(Error::InstanceInParallelRegion { row: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn repeated_region() {
+        use crate::plonk::{Expression, Selector};
+        use crate::poly::Rotation;
+        use ff::Field;
+
+        /// Witnesses `x` and `x + increment` in each of four copies of a two-row region.
+        struct MyCircuit {
+            increment: u64,
+            height: usize,
+            // Whether only the first copy enables the selector.
+            first_selector_only: bool,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = (Column<Advice>, Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    increment: self.increment,
+                    height: self.height,
+                    first_selector_only: self.first_selector_only,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let a = meta.advice_column();
+                let s = meta.selector();
+                meta.create_gate("increment", |meta| {
+                    let s = meta.query_selector(s);
+                    let cur = meta.query_advice(a, Rotation::cur());
+                    let next = meta.query_advice(a, Rotation::next());
+                    vec![s * (next - cur - Expression::Constant(vesta::Scalar::one()))]
+                });
+                (a, s)
+            }
+
+            fn synthesize(
+                &self,
+                (a, s): Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                let cells = layouter.assign_repeated_region(
+                    || "increments",
+                    1,
+                    self.height,
+                    4,
+                    |mut region, i| {
+                        if i == 0 || !self.first_selector_only {
+                            s.enable(&mut region, 0)?;
+                        }
+                        let x = Value::known(vesta::Scalar::from(10 * i as u64));
+                        region.assign_advice(a, 0, x)?;
+                        let y = x + Value::known(vesta::Scalar::from(self.increment));
+                        Ok(*region.assign_advice(a, 1, y)?.cell())
+                    },
+                )?;
+                assert_eq!(
+                    cells
+                        .iter()
+                        .map(|cell| cell.row_offset())
+                        .collect::<Vec<_>>(),
+                    (0..4).map(|i| 2 + i * self.height).collect::<Vec<_>>()
+                );
+                Ok(())
+            }
+        }
+
+        let run = |increment, height, first_selector_only| {
+            let circuit = MyCircuit {
+                increment,
+                height,
+                first_selector_only,
+            };
+            MockProver::run(5, &circuit, vec![]).map(|prover| prover.verify().is_ok())
+        };
+        assert!(matches!(run(1, 2, false), Ok(true)));
+        assert!(matches!(run(2, 2, false), Ok(false)));
+        // The second row of each copy is outside a region of one row.
+        assert!(matches!(
+            run(1, 1, false),
+            Err(Error::RowOutsideRegion {
+                offset: 1,
+                height: 1
+            })
+        ));
+        // Debug builds catch copies that differ from the first one.
+        if cfg!(debug_assertions) {
+            let result = std::panic::catch_unwind(|| run(1, 2, true));
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn repeated_region_matches_separate_regions() {
+        use crate::circuit::{Cell, Region};
+        use crate::plonk::{keygen_pk, keygen_vk, Fixed, Selector};
+        use crate::poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG, Rotation};
+        use crate::SerdeFormat;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        #[derive(Clone, Copy)]
+        struct MyConfig {
+            a: Column<Advice>,
+            f: Column<Fixed>,
+            s: Selector,
+        }
+
+        /// Witnesses `x` and `x + f` in each copy of a two-row region, with `x` copied
+        /// from the previous copy.
+        struct MyCircuit {
+            copies: usize,
+            repeated: bool,
+            fixed_offset: usize,
+        }
+
+        impl Circuit<Fr> for MyCircuit {
+            type Config = MyConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    copies: self.copies,
+                    repeated: self.repeated,
+                    fixed_offset: self.fixed_offset,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+                let a = meta.advice_column();
+                let f = meta.fixed_column();
+                let s = meta.selector();
+                meta.enable_equality(a);
+                meta.create_gate("add fixed", |meta| {
+                    let s = meta.query_selector(s);
+                    let cur = meta.query_advice(a, Rotation::cur());
+                    let next = meta.query_advice(a, Rotation::next());
+                    let f = meta.query_fixed(f, Rotation::cur());
+                    vec![s * (next - cur - f)]
+                });
+                MyConfig { a, f, s }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fr>,
+            ) -> Result<(), Error> {
+                let fixed_offset = self.fixed_offset;
+                let assign_copy = |region: &mut Region<'_, Fr>,
+                                   row: usize,
+                                   i: usize,
+                                   prev: Option<Cell>|
+                 -> Result<Cell, Error> {
+                    config.s.enable(region, row)?;
+                    region.assign_fixed(config.f, row + fixed_offset, Fr::from(3));
+                    let x = Value::known(Fr::from(3 * i as u64));
+                    let cur = *region.assign_advice(config.a, row, x)?.cell();
+                    let next =
+                        region.assign_advice(config.a, row + 1, x + Value::known(Fr::from(3)))?;
+                    if let Some(prev) = prev {
+                        region.constrain_equal(&prev, &cur);
+                    }
+                    Ok(*next.cell())
+                };
+
+                let mut prev = None;
+                if self.repeated {
+                    layouter.assign_repeated_region(
+                        || "copies",
+                        0,
+                        2,
+                        self.copies,
+                        |mut region, i| {
+                            prev = Some(assign_copy(&mut region, 0, i, prev)?);
+                            Ok(())
+                        },
+                    )?;
+                } else {
+                    for i in 0..self.copies {
+                        layouter.assign_region(
+                            || "copy",
+                            |mut region| {
+                                prev = Some(assign_copy(&mut region, 2 * i, i, prev)?);
+                                Ok(())
+                            },
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        const K: u32 = 5;
+        let params = ParamsKZG::<Bn256>::new(K);
+        let pk_bytes = |copies, repeated| {
+            let circuit = MyCircuit {
+                copies,
+                repeated,
+                fixed_offset: 0,
+            };
+            let vk = keygen_vk(&params, &circuit).unwrap();
+            keygen_pk(&params, vk, &circuit)
+                .unwrap()
+                .to_bytes(SerdeFormat::RawBytes)
+        };
+        for copies in [1, 3, 8] {
+            assert_eq!(pk_bytes(copies, true), pk_bytes(copies, false));
+        }
+
+        let run = |repeated| {
+            let circuit = MyCircuit {
+                copies: 3,
+                repeated,
+                fixed_offset: 0,
+            };
+            MockProver::run(K, &circuit, vec![]).map(|prover| prover.verify().is_ok())
+        };
+        assert!(matches!(run(true), Ok(true)));
+        assert!(matches!(run(false), Ok(true)));
+
+        // A fixed cell outside a copy of two rows.
+        let circuit = MyCircuit {
+            copies: 3,
+            repeated: true,
+            fixed_offset: 2,
+        };
+        assert!(matches!(
+            MockProver::run(K, &circuit, vec![]),
+            Err(Error::RowOutsideRegion {
+                offset: 2,
+                height: 2
+            })
+        ));
+    }
 }
@@ -3,10 +3,11 @@
 use std::cmp;
 use std::collections::HashSet;
 use std::fmt;
+use std::marker::PhantomData;
 
 use ff::Field;
 
-use super::{AssignedCell, Cell, RegionIndex, Value};
+use super::{AssignedCell, Cell, Region, RegionIndex, Value};
 use crate::plonk::{
     Advice, Any, Assigned, Challenge, Column, Error, Fixed, Instance, Selector, TableColumn,
 };
@@ -309,3 +310,415 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
     }
 }
 */
+
+/// A [`RegionLayouter`] that records the assignments of a region so that they can be
+/// made later, for synthesizing regions on several threads.
+///
+/// Instance cells and challenges cannot be queried while recording, and regions cannot
+/// move to the next phase.
+#[derive(Debug)]
+pub(crate) struct RegionRecorder<F: Field> {
+    ops: Vec<RecordedOp<F>>,
+    /// The known values of advice cells. They are boxed so that the references handed
+    /// out by `assign_advice` stay valid as the region grows.
+    values: Vec<Box<Assigned<F>>>,
+}
+
+#[derive(Debug)]
+enum RecordedOp<F: Field> {
+    EnableSelector(Selector, usize),
+    AssignAdvice(Column<Advice>, usize, Value<Assigned<F>>),
+    AssignFixed(Column<Fixed>, usize, Assigned<F>),
+    ConstrainConstant(Cell, Assigned<F>),
+    ConstrainEqual(Cell, Cell),
+}
+
+impl<F: Field> RegionRecorder<F> {
+    pub(crate) fn new() -> Self {
+        RegionRecorder {
+            ops: vec![],
+            values: vec![],
+        }
+    }
+
+    /// Makes the recorded assignments in `region`.
+    ///
+    /// Returns the recorded advice values, which must outlive any cell handed out while
+    /// recording.
+    pub(crate) fn replay(
+        self,
+        region: &mut dyn RegionLayouter<F>,
+    ) -> Result<Vec<Box<Assigned<F>>>, Error> {
+        for op in self.ops {
+            match op {
+                RecordedOp::EnableSelector(selector, offset) => {
+                    region.enable_selector(&String::new, &selector, offset)?
+                }
+                RecordedOp::AssignAdvice(column, offset, to) => {
+                    region.assign_advice(column, offset, to)?;
+                }
+                RecordedOp::AssignFixed(column, offset, to) => {
+                    region.assign_fixed(column, offset, to);
+                }
+                RecordedOp::ConstrainConstant(cell, constant) => {
+                    region.constrain_constant(cell, constant)?
+                }
+                RecordedOp::ConstrainEqual(left, right) => region.constrain_equal(&left, &right),
+            }
+        }
+        Ok(self.values)
+    }
+}
+
+impl<F: Field> RegionLayouter<F> for RegionRecorder<F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.ops.push(RecordedOp::EnableSelector(*selector, offset));
+        Ok(())
+    }
+
+    fn assign_advice<'b, 'v>(
+        &'b mut self,
+        column: Column<Advice>,
+        offset: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<&'v Assigned<F>, F>, Error> {
+        self.ops.push(RecordedOp::AssignAdvice(column, offset, to));
+        let value = to.map(|to| {
+            let value = Box::new(to);
+            let ptr = &*value as *const Assigned<F>;
+            self.values.push(value);
+            // The box is kept alive by `replay`'s caller, and its contents never move.
+            unsafe { &*ptr }
+        });
+
+        Ok(AssignedCell {
+            value,
+            cell: Cell {
+                row_offset: offset,
+                column: column.into(),
+            },
+            _marker: PhantomData,
+        })
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let advice = self
+            .assign_advice(column, offset, Value::known(constant))?
+            .cell;
+        self.constrain_constant(advice, constant)?;
+
+        Ok(advice)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        _: Column<Advice>,
+        _: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        // The instance values are held by the assignment, which is not shared across
+        // threads.
+        Err(Error::InstanceInParallelRegion {
+            column: instance,
+            row,
+        })
+    }
+
+    fn assign_fixed(&mut self, column: Column<Fixed>, offset: usize, to: Assigned<F>) -> Cell {
+        self.ops.push(RecordedOp::AssignFixed(column, offset, to));
+
+        Cell {
+            row_offset: offset,
+            column: column.into(),
+        }
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.ops.push(RecordedOp::ConstrainConstant(cell, constant));
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: &Cell, right: &Cell) {
+        self.ops.push(RecordedOp::ConstrainEqual(*left, *right));
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn next_phase(&mut self) {
+        panic!("Regions assigned in parallel cannot move to the next phase");
+    }
+}
+
+/// Assigns `count` copies of a region of `height` rows within `region`, the `i`-th copy
+/// starting at row `start + i * height`.
+///
+/// Unless `witness_only` is set, the selectors and fixed cells of the first copy are
+/// recorded once and replayed in the other copies, whose own are ignored. In debug builds,
+/// the other copies are checked to enable the same selectors and assign the same fixed
+/// cells as the first one. When `witness_only` is set, none of the copies assign selectors
+/// or fixed cells, as they are already part of the proving key.
+pub(crate) fn assign_repeated<F: Field, A, AR>(
+    region: &mut dyn RegionLayouter<F>,
+    start: usize,
+    height: usize,
+    count: usize,
+    witness_only: bool,
+    mut assignment: A,
+) -> Result<Vec<AR>, Error>
+where
+    A: FnMut(Region<'_, F>, usize) -> Result<AR, Error>,
+{
+    let mut template = RegionTemplate::new();
+    (0..count)
+        .map(|i| {
+            let copy_start = start + i * height;
+            let mode = if witness_only {
+                TemplateMode::Skip
+            } else if i == 0 {
+                TemplateMode::Record(&mut template)
+            } else if cfg!(debug_assertions) {
+                TemplateMode::Check(RegionTemplate::new())
+            } else {
+                TemplateMode::Skip
+            };
+            let mut copy = RepeatedRegion::new(&mut *region, mode, copy_start, height);
+            let result = {
+                let copy: &mut dyn RegionLayouter<F> = &mut copy;
+                assignment(copy.into(), i)
+            }?;
+            if let Some(recorded) = copy.finish()? {
+                debug_assert!(
+                    recorded.matches(&template),
+                    "copy {} of a repeated region does not enable the same selectors and \
+                     assign the same fixed cells as the first copy",
+                    i
+                );
+            }
+            if !witness_only && i > 0 {
+                template.apply(&mut *region, copy_start)?;
+            }
+            Ok(result)
+        })
+        .collect()
+}
+
+/// The selectors and fixed cells of the first copy of a repeated region, relative to
+/// its first row.
+#[derive(Debug)]
+struct RegionTemplate<F: Field> {
+    selectors: Vec<(Selector, usize)>,
+    fixed: Vec<(Column<Fixed>, usize, Assigned<F>)>,
+}
+
+impl<F: Field> RegionTemplate<F> {
+    fn new() -> Self {
+        RegionTemplate {
+            selectors: vec![],
+            fixed: vec![],
+        }
+    }
+
+    /// Returns whether both templates enable the same selectors and assign the same fixed
+    /// cells, in any order.
+    fn matches(&self, other: &Self) -> bool {
+        self.selectors.len() == other.selectors.len()
+            && self.fixed.len() == other.fixed.len()
+            && self
+                .selectors
+                .iter()
+                .all(|selector| other.selectors.contains(selector))
+            && self.fixed.iter().all(|fixed| other.fixed.contains(fixed))
+    }
+
+    /// Enables the selectors and assigns the fixed cells of the template in the copy of
+    /// the region starting at `start`.
+    fn apply(&self, region: &mut dyn RegionLayouter<F>, start: usize) -> Result<(), Error> {
+        for (selector, offset) in self.selectors.iter() {
+            region.enable_selector(&String::new, selector, start + offset)?;
+        }
+        for (column, offset, to) in self.fixed.iter() {
+            region.assign_fixed(*column, start + offset, *to);
+        }
+        Ok(())
+    }
+}
+
+/// What a copy of a repeated region does with its selectors and fixed cells.
+#[derive(Debug)]
+enum TemplateMode<'r, F: Field> {
+    /// Assigns them, and records them in the template.
+    Record(&'r mut RegionTemplate<F>),
+    /// Only records them, to be checked against the template.
+    Check(RegionTemplate<F>),
+    /// Ignores them.
+    Skip,
+}
+
+/// A [`RegionLayouter`] for one copy of a repeated region, translating offsets relative
+/// to the copy into rows of the underlying region.
+#[derive(Debug)]
+struct RepeatedRegion<'r, F: Field> {
+    region: &'r mut dyn RegionLayouter<F>,
+    mode: TemplateMode<'r, F>,
+    start: usize,
+    height: usize,
+    /// The offset of a fixed cell assigned outside the copy, which
+    /// [`RegionLayouter::assign_fixed`] cannot report.
+    fixed_out_of_range: Option<usize>,
+}
+
+impl<'r, F: Field> RepeatedRegion<'r, F> {
+    fn new(
+        region: &'r mut dyn RegionLayouter<F>,
+        mode: TemplateMode<'r, F>,
+        start: usize,
+        height: usize,
+    ) -> Self {
+        RepeatedRegion {
+            region,
+            mode,
+            start,
+            height,
+            fixed_out_of_range: None,
+        }
+    }
+
+    /// Returns the row of `offset`, or an error if it is outside the copy.
+    fn row(&self, offset: usize) -> Result<usize, Error> {
+        if offset < self.height {
+            Ok(self.start + offset)
+        } else {
+            Err(Error::RowOutsideRegion {
+                offset,
+                height: self.height,
+            })
+        }
+    }
+
+    /// Returns an error if a fixed cell was assigned outside the copy, and otherwise the
+    /// selectors and fixed cells recorded to be checked against the template, if any.
+    fn finish(self) -> Result<Option<RegionTemplate<F>>, Error> {
+        if let Some(offset) = self.fixed_out_of_range {
+            return Err(Error::RowOutsideRegion {
+                offset,
+                height: self.height,
+            });
+        }
+        match self.mode {
+            TemplateMode::Check(recorded) => Ok(Some(recorded)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'r, F: Field> RegionLayouter<F> for RepeatedRegion<'r, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        let row = self.row(offset)?;
+        match &mut self.mode {
+            TemplateMode::Record(template) => {
+                template.selectors.push((*selector, offset));
+                self.region.enable_selector(annotation, selector, row)
+            }
+            TemplateMode::Check(recorded) => {
+                recorded.selectors.push((*selector, offset));
+                Ok(())
+            }
+            TemplateMode::Skip => Ok(()),
+        }
+    }
+
+    fn assign_advice<'b, 'v>(
+        &'b mut self,
+        column: Column<Advice>,
+        offset: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<AssignedCell<&'v Assigned<F>, F>, Error> {
+        let row = self.row(offset)?;
+        self.region.assign_advice(column, row, to)
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let row = self.row(offset)?;
+        self.region
+            .assign_advice_from_constant(annotation, column, row, constant)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        let offset = self.row(offset)?;
+        self.region
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+    }
+
+    fn assign_fixed(&mut self, column: Column<Fixed>, offset: usize, to: Assigned<F>) -> Cell {
+        let row = self.start + offset;
+        let cell = Cell {
+            row_offset: row,
+            column: column.into(),
+        };
+        if offset >= self.height {
+            // Reported by `finish` once the copy has been assigned.
+            self.fixed_out_of_range.get_or_insert(offset);
+            return cell;
+        }
+        match &mut self.mode {
+            TemplateMode::Record(template) => {
+                template.fixed.push((column, offset, to));
+                self.region.assign_fixed(column, row, to)
+            }
+            TemplateMode::Check(recorded) => {
+                recorded.fixed.push((column, offset, to));
+                cell
+            }
+            TemplateMode::Skip => cell,
+        }
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: &Cell, right: &Cell) {
+        self.region.constrain_equal(left, right)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.region.get_challenge(challenge)
+    }
+
+    fn next_phase(&mut self) {
+        self.region.next_phase()
+    }
+}
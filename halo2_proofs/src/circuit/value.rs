@@ -3,7 +3,7 @@ use std::ops::{Add, Mul, Neg, Sub};
 
 use group::ff::Field;
 
-use crate::plonk::{Assigned, Error};
+use crate::plonk::{Any, Assigned, Column, Error};
 
 /// A value that might exist within a circuit.
 ///
@@ -42,11 +42,14 @@ impl<V> Value<V> {
         Self { inner: Some(value) }
     }
 
-    /// Obtains the inner value for assigning into the circuit.
+    /// Obtains the inner value for assigning into the cell of `column` at `row`.
     ///
-    /// Returns `Error::Synthesis` if this is [`Value::unknown()`].
-    pub(crate) fn assign(self) -> Result<V, Error> {
-        self.inner.ok_or(Error::Synthesis)
+    /// Returns `Error::UnknownValue` if this is [`Value::unknown()`].
+    pub(crate) fn assign(self, column: impl Into<Column<Any>>, row: usize) -> Result<V, Error> {
+        self.inner.ok_or_else(|| Error::UnknownValue {
+            column: column.into(),
+            row,
+        })
     }
 
     /// Converts from `&Value<V>` to `Value<&V>`.
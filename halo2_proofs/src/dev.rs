@@ -11,6 +11,7 @@ use std::time::{Duration, Instant};
 use blake2b_simd::blake2b;
 use ff::Field;
 
+use crate::multicore::prelude::*;
 use crate::{
     arithmetic::{FieldExt, Group},
     circuit,
@@ -21,12 +22,6 @@ use crate::{
     },
     poly::Rotation,
 };
-use rayon::{
-    iter::{
-        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
-    },
-    slice::ParallelSliceMut,
-};
 
 pub mod metadata;
 mod util;
@@ -34,12 +29,43 @@ mod util;
 mod failure;
 pub use failure::{FailureLocation, VerifyFailure};
 
-//pub mod cost;
-//pub use cost::CircuitCost;
+#[cfg(feature = "prover")]
+pub mod bench;
+
+pub mod cost;
+pub use cost::CircuitCost;
 
 mod gates;
 pub use gates::CircuitGates;
 
+mod gate_check;
+pub use gate_check::GateCheck;
+
+mod profile;
+pub use profile::{CircuitProfile, GateProfile};
+
+mod occupancy;
+pub use occupancy::{CircuitOccupancy, ColumnOccupancy, RegionOccupancy};
+
+mod explore;
+pub use explore::InvolvedConstraint;
+
+pub mod equivalence;
+
+mod mutation;
+pub use mutation::SurvivingMutation;
+
+pub mod fuzz;
+
+#[cfg(feature = "gadget-testing")]
+mod gadget_test;
+
+#[cfg(feature = "gadget-testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gadget-testing")))]
+pub use gadget_test::{
+    check_gadget, field_element, prop_test_gadget, prop_test_gadget_pasta, GadgetUnderTest,
+};
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -81,6 +107,34 @@ impl Region {
     }
 }
 
+/// The number of advice cells assigned during a single invocation of a namespace
+/// (for example, a single call to a chip instruction that uses
+/// [`Layouter::namespace`](crate::circuit::Layouter::namespace)).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamespaceAdviceCells {
+    /// The path of the namespace, with the names of enclosing namespaces joined by `/`.
+    pub path: String,
+    /// The gadget that exited the namespace, if known. This is only populated when the
+    /// `gadget-traces` feature is enabled.
+    pub gadget_name: Option<String>,
+    /// The number of advice cells assigned within this invocation, including those
+    /// assigned within nested namespaces.
+    pub advice_cells: usize,
+}
+
+/// How [`MockProver`] treats advice cells that the circuit does not assign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnassignedAdvice<F> {
+    /// Unassigned advice cells hold the given padding value. This allows gates to
+    /// query unassigned rotations on purpose, for example to reduce the number of
+    /// distinct rotation sets for SHPLONK. [`MockProver::run`] pads with zero.
+    Pad(F),
+    /// Every cell queried by an enabled gate must be assigned within the gate's
+    /// region, and verification reports [`VerifyFailure::CellNotAssigned`] for each
+    /// one that is not.
+    Error,
+}
+
 /// The value of a particular cell within the circuit.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum CellValue<F: Group + Field> {
@@ -283,6 +337,7 @@ impl<F: Group + Field> Mul<F> for Value<F> {
 ///     MockProver::<Fp>::run(2, &circuit, vec![]).unwrap_err(),
 ///     Error::NotEnoughRowsAvailable {
 ///         current_k,
+///         ..
 ///     } if current_k == 2,
 /// ));
 /// ```
@@ -313,6 +368,16 @@ pub struct MockProver<F: Group + Field> {
 
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
+
+    /// The namespaces we are currently in, along with the number of advice cells
+    /// assigned in each of them so far.
+    namespaces: Vec<(String, usize)>,
+    /// The advice cell counts of every namespace invocation that has been exited, in
+    /// the order in which they were exited.
+    namespace_advice_cells: Vec<NamespaceAdviceCells>,
+
+    // How unassigned advice cells are treated.
+    unassigned_advice: UnassignedAdvice<F>,
 }
 
 impl<F: Field + Group> Assignment<F> for MockProver<F> {
@@ -341,7 +406,7 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         // Track that this selector was enabled. We require that all selectors are enabled
@@ -365,14 +430,17 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         row: usize,
     ) -> Result<circuit::Value<F>, Error> {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         self.instance
             .get(column.index())
             .and_then(|column| column.get(row))
             .map(|v| circuit::Value::known(*v))
-            .ok_or(Error::BoundsFailure)
+            .ok_or(Error::BoundsFailure {
+                column: column.into(),
+                row,
+            })
     }
 
     fn assign_advice<'r, 'v>(
@@ -384,7 +452,7 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         to: circuit::Value<Assigned<F>>,
     ) -> Result<circuit::Value<&'v Assigned<F>>, Error> {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         if let Some(region) = self.current_region.as_mut() {
@@ -396,13 +464,20 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
                 .or_default();
         }
 
+        for (_, advice_cells) in self.namespaces.iter_mut() {
+            *advice_cells += 1;
+        }
+
         let advice_get_mut = self
             .advice
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .ok_or(Error::BoundsFailure)?;
+            .ok_or(Error::BoundsFailure {
+                column: column.into(),
+                row,
+            })?;
 
-        let val = Arc::new(to.assign()?);
+        let val = Arc::new(to.assign(column, row)?);
         let val_ref = Arc::downgrade(&val);
         *advice_get_mut = AdviceCellValue::Assigned(val);
 
@@ -411,7 +486,7 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
 
     fn assign_fixed(&mut self, column: Column<Fixed>, row: usize, to: Assigned<F>) {
         if !self.usable_rows.contains(&row) {
-            panic!("{:?}", Error::not_enough_rows_available(self.k));
+            panic!("{:?}", Error::row_not_available(self.k, row));
         }
 
         if let Some(region) = self.current_region.as_mut() {
@@ -427,8 +502,15 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             .fixed
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .unwrap_or_else(|| panic!("{:?}", Error::BoundsFailure)) =
-            CellValue::Assigned(to.evaluate());
+            .unwrap_or_else(|| {
+                panic!(
+                    "{:?}",
+                    Error::BoundsFailure {
+                        column: column.into(),
+                        row
+                    }
+                )
+            }) = CellValue::Assigned(to.evaluate());
     }
 
     fn copy(
@@ -438,8 +520,10 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         right_column: Column<Any>,
         right_row: usize,
     ) {
-        if !self.usable_rows.contains(&left_row) || !self.usable_rows.contains(&right_row) {
-            panic!("{:?}", Error::not_enough_rows_available(self.k));
+        for row in [left_row, right_row] {
+            if !self.usable_rows.contains(&row) {
+                panic!("{:?}", Error::row_not_available(self.k, row));
+            }
         }
 
         self.permutation
@@ -454,11 +538,11 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         to: circuit::Value<Assigned<F>>,
     ) -> Result<(), Error> {
         if !self.usable_rows.contains(&from_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, from_row));
         }
 
         for row in self.usable_rows.clone().skip(from_row) {
-            self.assign_fixed(col, row, to.assign()?);
+            self.assign_fixed(col, row, to.assign(col, row)?);
         }
 
         Ok(())
@@ -468,30 +552,58 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         circuit::Value::known(self.challenges[challenge.index()])
     }
 
-    fn push_namespace<NR, N>(&mut self, _: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // TODO: Do something with namespaces :)
+        let name = name_fn().into();
+        let path = match self.namespaces.last() {
+            Some((parent, _)) => format!("{}/{}", parent, name),
+            None => name,
+        };
+        self.namespaces.push((path, 0));
     }
 
-    fn pop_namespace(&mut self, _: Option<String>) {
-        // TODO: Do something with namespaces :)
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        let (path, advice_cells) = self
+            .namespaces
+            .pop()
+            .expect("pop_namespace called without a matching push_namespace");
+        self.namespace_advice_cells.push(NamespaceAdviceCells {
+            path,
+            gadget_name,
+            advice_cells,
+        });
     }
 }
 
 impl<F: FieldExt> MockProver<F> {
     /// Runs a synthetic keygen-and-prove operation on the given circuit, collecting data
     /// about the constraints and their assignments.
+    ///
+    /// Unassigned advice cells are treated as zero; use
+    /// [`MockProver::run_with_unassigned_advice`] to choose another behaviour.
     pub fn run<ConcreteCircuit: Circuit<F>>(
         k: u32,
         circuit: &ConcreteCircuit,
         instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        Self::run_with_unassigned_advice(k, circuit, instance, UnassignedAdvice::Pad(F::zero()))
+    }
+
+    /// Runs a synthetic keygen-and-prove operation on the given circuit like
+    /// [`MockProver::run`], treating unassigned advice cells as specified by
+    /// `unassigned_advice`.
+    pub fn run_with_unassigned_advice<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+        unassigned_advice: UnassignedAdvice<F>,
     ) -> Result<Self, Error> {
         let n = 1 << k;
 
-        let mut cs = ConstraintSystem::default();
+        let mut cs = ConstraintSystem::with_k(k);
         let config = ConcreteCircuit::configure(&mut cs);
         let cs = cs;
 
@@ -506,7 +618,7 @@ impl<F: FieldExt> MockProver<F> {
         let instance = instance
             .into_iter()
             .map(|mut instance| {
-                if instance.len() > n - (cs.blinding_factors() + 1) {
+                if instance.len() > n - cs.unusable_rows() {
                     return Err(Error::InstanceTooLarge);
                 }
 
@@ -524,9 +636,13 @@ impl<F: FieldExt> MockProver<F> {
         let advice = vec![
             {
                 // let mut column = vec![AdviceCellValue::Unassigned; n];
-                // Assign advice to 0 by default so we can have gates that query unassigned rotations to minimize number of distinct rotation sets, for SHPLONK optimization
+                // Assign advice to the padding value by default so we can have gates that query unassigned rotations to minimize number of distinct rotation sets, for SHPLONK optimization
+                let padding = match unassigned_advice {
+                    UnassignedAdvice::Pad(padding) => padding,
+                    UnassignedAdvice::Error => F::zero(),
+                };
                 let mut column =
-                    vec![AdviceCellValue::Assigned(Arc::new(Assigned::Trivial(F::zero()))); n];
+                    vec![AdviceCellValue::Assigned(Arc::new(Assigned::Trivial(padding))); n];
                 // Poison unusable rows.
                 for (i, cell) in column.iter_mut().enumerate().skip(usable_rows) {
                     *cell = AdviceCellValue::Poison(i);
@@ -562,11 +678,14 @@ impl<F: FieldExt> MockProver<F> {
             challenges,
             permutation,
             usable_rows: 0..usable_rows,
+            namespaces: vec![],
+            namespace_advice_cells: vec![],
+            unassigned_advice,
         };
 
         ConcreteCircuit::FloorPlanner::synthesize(&mut prover, circuit, config, constants)?;
 
-        let (cs, selector_polys) = prover.cs.compress_selectors(prover.selectors.clone());
+        let (cs, selector_polys) = prover.cs.compress_selectors(prover.selectors.clone())?;
         prover.cs = cs;
         prover.fixed.extend(selector_polys.into_iter().map(|poly| {
             let mut v = vec![CellValue::Unassigned; n];
@@ -579,6 +698,178 @@ impl<F: FieldExt> MockProver<F> {
         Ok(prover)
     }
 
+    /// Returns the number of advice cells assigned during each namespace invocation,
+    /// in the order in which the namespaces were exited.
+    ///
+    /// Gadget authors can use this to document the witness cost of each instruction
+    /// and to catch regressions in it.
+    pub fn namespace_advice_cells(&self) -> &[NamespaceAdviceCells] {
+        &self.namespace_advice_cells
+    }
+
+    /// Returns a [`VerifyFailure::CellNotAssigned`] for every cell that is queried by
+    /// an enabled gate but not assigned within the gate's region, regardless of the
+    /// [`UnassignedAdvice`] behaviour.
+    ///
+    /// This distinguishes intentionally sparse layouts from cells that were forgotten.
+    pub fn unassigned_queried_cells(&self) -> Vec<VerifyFailure> {
+        let n = self.n as i32;
+
+        self.regions
+            .iter()
+            .enumerate()
+            .flat_map(|(r_i, r)| {
+                r.enabled_selectors.iter().flat_map(move |(selector, at)| {
+                    // Find the gates enabled by this selector
+                    self.cs
+                        .gates
+                        .iter()
+                        // Assume that if a queried selector is enabled, the user wants to use the
+                        // corresponding gate in some way.
+                        //
+                        // TODO: This will trip up on the reverse case, where leaving a selector
+                        // un-enabled keeps a gate enabled. We could alternatively require that
+                        // every selector is explicitly enabled or disabled on every row? But that
+                        // seems messy and confusing.
+                        .enumerate()
+                        .filter(move |(_, g)| g.queried_selectors().contains(selector))
+                        .flat_map(move |(gate_index, gate)| {
+                            at.iter().flat_map(move |selector_row| {
+                                // Selectors are queried with no rotation.
+                                let gate_row = *selector_row as i32;
+
+                                gate.queried_cells().iter().filter_map(move |cell| {
+                                    // Determine where this cell should have been assigned.
+                                    let cell_row = ((gate_row + n + cell.rotation.0) % n) as usize;
+
+                                    // Check that it was assigned!
+                                    if r.cells.contains_key(&(cell.column, cell_row)) {
+                                        None
+                                    } else {
+                                        Some(VerifyFailure::CellNotAssigned {
+                                            gate: (gate_index, gate.name()).into(),
+                                            region: (r_i, r.name.clone()).into(),
+                                            gate_offset: *selector_row,
+                                            column: cell.column,
+                                            offset: cell_row as isize - r.rows.unwrap().0 as isize,
+                                        })
+                                    }
+                                })
+                            })
+                        })
+                })
+            })
+            .collect()
+    }
+
+    // The advice cells in the circuit with their assigned values evaluated, arranged as
+    // [column][row].
+    fn advice_values(&self) -> Vec<Vec<CellValue<F>>> {
+        self.advice
+            .iter()
+            .map(|advice| {
+                advice
+                    .iter()
+                    .map(|rc| match *rc {
+                        AdviceCellValue::Assigned(ref a) => CellValue::Assigned(match a.as_ref() {
+                            Assigned::Trivial(a) => *a,
+                            Assigned::Rational(a, b) => *a * b.invert().unwrap(),
+                            _ => F::zero(),
+                        }),
+                        AdviceCellValue::Poison(i) => CellValue::Poison(i),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    }
+
+    // The shuffle failures that verification reports. A shuffle relates all of the
+    // usable rows at once, so unlike lookups it is always checked on every one of them.
+    fn shuffle_errors(&self, advice: &[Vec<CellValue<F>>]) -> Vec<VerifyFailure> {
+        let n = self.n as i32;
+        let load = |expressions: &[Expression<F>], row: usize| -> Vec<Value<F>> {
+            let row = row as i32 + n;
+            expressions
+                .iter()
+                .map(|expression| {
+                    expression.evaluate_lazy(
+                        &|scalar| Value::Real(scalar),
+                        &|_| panic!("virtual selectors are removed during optimization"),
+                        &util::load(n, row, &self.cs.fixed_queries, &self.fixed),
+                        &util::load(n, row, &self.cs.advice_queries, advice),
+                        &util::load_instance(n, row, &self.cs.instance_queries, &self.instance),
+                        &|challenge| Value::Real(self.challenges[challenge.index()]),
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &|a, scalar| a * scalar,
+                        &Value::Real(F::zero()),
+                    )
+                })
+                .collect()
+        };
+
+        self.cs
+            .shuffles
+            .iter()
+            .enumerate()
+            .flat_map(|(shuffle_index, shuffle)| {
+                assert!(shuffle.shuffle_expressions.len() == shuffle.input_expressions.len());
+
+                let mut shuffle_rows: Vec<_> = self
+                    .usable_rows
+                    .clone()
+                    .map(|row| load(&shuffle.shuffle_expressions, row))
+                    .collect();
+                shuffle_rows.sort_unstable();
+
+                // Also keep track of the original input row, since we're going to sort.
+                let mut input_rows: Vec<_> = self
+                    .usable_rows
+                    .clone()
+                    .map(|row| (load(&shuffle.input_expressions, row), row))
+                    .collect();
+                input_rows.sort_unstable();
+
+                // Match each input row with an equal shuffle row that has not been
+                // matched yet. Both sides have the same number of rows, so every
+                // unmatched shuffle row leaves an input row unmatched.
+                let mut i = 0;
+                input_rows
+                    .into_iter()
+                    .filter_map(|(input, input_row)| {
+                        while i < shuffle_rows.len() && shuffle_rows[i] < input {
+                            i += 1;
+                        }
+                        if i < shuffle_rows.len() && shuffle_rows[i] == input {
+                            i += 1;
+                            None
+                        } else {
+                            Some(VerifyFailure::Shuffle {
+                                name: shuffle.name,
+                                shuffle_index,
+                                location: FailureLocation::find_expressions(
+                                    &self.cs,
+                                    &self.regions,
+                                    input_row,
+                                    shuffle.input_expressions.iter(),
+                                ),
+                            })
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    // The unassigned cell failures that verification reports.
+    fn selector_errors(&self) -> Vec<VerifyFailure> {
+        match self.unassigned_advice {
+            UnassignedAdvice::Pad(_) => vec![],
+            UnassignedAdvice::Error => self.unassigned_queried_cells(),
+        }
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
     pub fn verify(&self) -> Result<(), Vec<VerifyFailure>> {
@@ -593,6 +884,48 @@ impl<F: FieldExt> MockProver<F> {
         &self,
         gate_row_ids: I,
         lookup_input_row_ids: I,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        self.verify_rows(gate_row_ids, lookup_input_row_ids, None)
+    }
+
+    /// Returns `Ok(())` if the regions named in `region_names` are satisfied, or a list
+    /// of errors indicating the reasons that they are not.
+    ///
+    /// Constraints and lookup inputs are only checked on the rows of those regions, and
+    /// equality constraints only on cells in those rows. Lookup tables are still read
+    /// from every usable row. For large circuits this is much faster than
+    /// [`MockProver::verify`], but the rest of the circuit may still be unsatisfied.
+    ///
+    /// Regions are selected by row, so a region cannot be checked in isolation from
+    /// another region that shares any of its rows: failures in either are reported.
+    ///
+    /// Panics if no region has one of the given names.
+    pub fn verify_regions(&self, region_names: &[&str]) -> Result<(), Vec<VerifyFailure>> {
+        let mut rows = vec![false; self.n as usize];
+        for name in region_names {
+            let mut found = false;
+            for region in self.regions.iter().filter(|region| region.name == *name) {
+                found = true;
+                if let Some((start, end)) = region.rows {
+                    rows[start..=end].iter_mut().for_each(|row| *row = true);
+                }
+            }
+            if !found {
+                panic!("no region named {}", name);
+            }
+        }
+
+        let row_ids = (0..self.n as usize).filter(|&row| rows[row]);
+        self.verify_rows(row_ids.clone(), row_ids, Some(&rows))
+    }
+
+    // Checks constraints at `gate_row_ids`, lookup inputs at `lookup_input_row_ids`, and
+    // equality constraints on the rows for which `copy_rows` is set (or all rows).
+    fn verify_rows<I: Clone + Iterator<Item = usize>>(
+        &self,
+        gate_row_ids: I,
+        lookup_input_row_ids: I,
+        copy_rows: Option<&[bool]>,
     ) -> Result<(), Vec<VerifyFailure>> {
         let n = self.n as i32;
 
@@ -611,68 +944,12 @@ impl<F: FieldExt> MockProver<F> {
         // Check that within each region, all cells used in instantiated gates have been
         // assigned to.
 
-        // Turn off this check because we might query unassigned cells to increase the rotation set size of a gate (for SHPLONK optimization)
-        /*
-        let selector_errors = self.regions.iter().enumerate().flat_map(|(r_i, r)| {
-            r.enabled_selectors.iter().flat_map(move |(selector, at)| {
-                // Find the gates enabled by this selector
-                self.cs
-                    .gates
-                    .iter()
-                    // Assume that if a queried selector is enabled, the user wants to use the
-                    // corresponding gate in some way.
-                    //
-                    // TODO: This will trip up on the reverse case, where leaving a selector
-                    // un-enabled keeps a gate enabled. We could alternatively require that
-                    // every selector is explicitly enabled or disabled on every row? But that
-                    // seems messy and confusing.
-                    .enumerate()
-                    .filter(move |(_, g)| g.queried_selectors().contains(selector))
-                    .flat_map(move |(gate_index, gate)| {
-                        at.iter().flat_map(move |selector_row| {
-                            // Selectors are queried with no rotation.
-                            let gate_row = *selector_row as i32;
-
-                            gate.queried_cells().iter().filter_map(move |cell| {
-                                // Determine where this cell should have been assigned.
-                                let cell_row = ((gate_row + n + cell.rotation.0) % n) as usize;
-
-                                // Check that it was assigned!
-                                if r.cells.contains_key(&(cell.column, cell_row)) {
-                                    None
-                                } else {
-                                    Some(VerifyFailure::CellNotAssigned {
-                                        gate: (gate_index, gate.name()).into(),
-                                        region: (r_i, r.name.clone()).into(),
-                                        gate_offset: *selector_row,
-                                        column: cell.column,
-                                        offset: cell_row as isize - r.rows.unwrap().0 as isize,
-                                    })
-                                }
-                            })
-                        })
-                    })
-            })
-        });
-        */
+        // This check is only on with `UnassignedAdvice::Error`, because we might query
+        // unassigned cells to increase the rotation set size of a gate (for SHPLONK
+        // optimization).
+        let selector_errors = self.selector_errors();
 
-        let advice = self
-            .advice
-            .iter()
-            .map(|advice| {
-                advice
-                    .iter()
-                    .map(|rc| match *rc {
-                        AdviceCellValue::Assigned(ref a) => CellValue::Assigned(match a.as_ref() {
-                            Assigned::Trivial(a) => *a,
-                            Assigned::Rational(a, b) => *a * b.invert().unwrap(),
-                            _ => F::zero(),
-                        }),
-                        AdviceCellValue::Poison(i) => CellValue::Poison(i),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+        let advice = self.advice_values();
         let advice = &advice;
         // Check that all gates are satisfied for all rows.
         let gate_errors =
@@ -682,7 +959,7 @@ impl<F: FieldExt> MockProver<F> {
                 .enumerate()
                 .flat_map(|(gate_index, gate)| {
                     let blinding_rows =
-                        (self.n as usize - (self.cs.blinding_factors() + 1))..(self.n as usize);
+                        (self.n as usize - self.cs.unusable_rows())..(self.n as usize);
                     (gate_row_ids
                         .clone()
                         .into_iter()
@@ -912,31 +1189,36 @@ impl<F: FieldExt> MockProver<F> {
                 .flat_map(move |(column, values)| {
                     // Iterate over each row of the column to check that the cell's
                     // value is preserved by the mapping.
-                    values.iter().enumerate().filter_map(move |(row, cell)| {
-                        let original_cell = original(column, row);
-                        let permuted_cell = original(cell.0, cell.1);
-                        if original_cell == permuted_cell {
-                            None
-                        } else {
-                            let columns = self.cs.permutation.get_columns();
-                            let column = columns.get(column).unwrap();
-                            Some(VerifyFailure::Permutation {
-                                column: (*column).into(),
-                                location: FailureLocation::find(
-                                    &self.regions,
-                                    row,
-                                    Some(column).into_iter().cloned().collect(),
-                                ),
-                            })
-                        }
-                    })
+                    values
+                        .iter()
+                        .enumerate()
+                        .filter(move |(row, _)| copy_rows.map_or(true, |rows| rows[*row]))
+                        .filter_map(move |(row, cell)| {
+                            let original_cell = original(column, row);
+                            let permuted_cell = original(cell.0, cell.1);
+                            if original_cell == permuted_cell {
+                                None
+                            } else {
+                                let columns = self.cs.permutation.get_columns();
+                                let column = columns.get(column).unwrap();
+                                Some(VerifyFailure::Permutation {
+                                    column: (*column).into(),
+                                    location: FailureLocation::find(
+                                        &self.regions,
+                                        row,
+                                        Some(column).into_iter().cloned().collect(),
+                                    ),
+                                })
+                            }
+                        })
                 })
         };
 
         let mut errors: Vec<_> = iter::empty()
-            //.chain(selector_errors)
+            .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(self.shuffle_errors(advice))
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -991,72 +1273,9 @@ impl<F: FieldExt> MockProver<F> {
 
         // Check that within each region, all cells used in instantiated gates have been
         // assigned to.
-        let selector_errors = self.regions.iter().enumerate().flat_map(|(r_i, r)| {
-            r.enabled_selectors.iter().flat_map(move |(selector, at)| {
-                // Find the gates enabled by this selector
-                self.cs
-                    .gates
-                    .iter()
-                    // Assume that if a queried selector is enabled, the user wants to use the
-                    // corresponding gate in some way.
-                    //
-                    // TODO: This will trip up on the reverse case, where leaving a selector
-                    // un-enabled keeps a gate enabled. We could alternatively require that
-                    // every selector is explicitly enabled or disabled on every row? But that
-                    // seems messy and confusing.
-                    .enumerate()
-                    .filter(move |(_, g)| g.queried_selectors().contains(selector))
-                    .flat_map(move |(gate_index, gate)| {
-                        at.par_iter()
-                            .flat_map(move |selector_row| {
-                                // Selectors are queried with no rotation.
-                                let gate_row = *selector_row as i32;
-
-                                gate.queried_cells()
-                                    .iter()
-                                    .filter_map(move |cell| {
-                                        // Determine where this cell should have been assigned.
-                                        let cell_row =
-                                            ((gate_row + n + cell.rotation.0) % n) as usize;
-
-                                        // Check that it was assigned!
-                                        if r.cells.contains_key(&(cell.column, cell_row)) {
-                                            None
-                                        } else {
-                                            Some(VerifyFailure::CellNotAssigned {
-                                                gate: (gate_index, gate.name()).into(),
-                                                region: (r_i, r.name.clone()).into(),
-                                                gate_offset: *selector_row,
-                                                column: cell.column,
-                                                offset: cell_row as isize
-                                                    - r.rows.unwrap().0 as isize,
-                                            })
-                                        }
-                                    })
-                                    .collect::<Vec<_>>()
-                            })
-                            .collect::<Vec<_>>()
-                    })
-            })
-        });
+        let selector_errors = self.selector_errors();
 
-        let advice = self
-            .advice
-            .iter()
-            .map(|advice| {
-                advice
-                    .iter()
-                    .map(|rc| match *rc {
-                        AdviceCellValue::Assigned(ref a) => CellValue::Assigned(match a.as_ref() {
-                            Assigned::Trivial(a) => *a,
-                            Assigned::Rational(a, b) => *a * b.invert().unwrap(),
-                            _ => F::zero(),
-                        }),
-                        AdviceCellValue::Poison(i) => CellValue::Poison(i),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+        let advice = self.advice_values();
         let advice = &advice;
         // Check that all gates are satisfied for all rows.
         let gate_errors = self
@@ -1065,8 +1284,7 @@ impl<F: FieldExt> MockProver<F> {
             .iter()
             .enumerate()
             .flat_map(|(gate_index, gate)| {
-                let blinding_rows =
-                    (self.n as usize - (self.cs.blinding_factors() + 1))..(self.n as usize);
+                let blinding_rows = (self.n as usize - self.cs.unusable_rows())..(self.n as usize);
                 (gate_row_ids
                     .clone()
                     .into_par_iter()
@@ -1316,6 +1534,7 @@ impl<F: FieldExt> MockProver<F> {
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(self.shuffle_errors(advice))
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -1378,9 +1597,12 @@ impl<F: FieldExt> MockProver<F> {
 
 #[cfg(test)]
 mod tests {
-    use halo2curves::pasta::Fp;
+    use halo2curves::pasta::{vesta, Fp};
 
-    use super::{FailureLocation, MockProver, VerifyFailure};
+    use super::{
+        CircuitCost, FailureLocation, GateCheck, InvolvedConstraint, MockProver, UnassignedAdvice,
+        VerifyFailure,
+    };
     use crate::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         plonk::{
@@ -1454,17 +1676,41 @@ mod tests {
             }
         }
 
-        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
-        assert_eq!(
-            prover.verify(),
-            Err(vec![VerifyFailure::CellNotAssigned {
+        let unassigned = || {
+            vec![VerifyFailure::CellNotAssigned {
                 gate: (0, "Equality check").into(),
                 region: (0, "Faulty synthesis".to_owned()).into(),
                 gate_offset: 1,
                 column: Column::new(1, Any::advice()),
                 offset: 1,
-            }])
-        );
+            }]
+        };
+
+        let prover = MockProver::run_with_unassigned_advice(
+            K,
+            &FaultyCircuit {},
+            vec![],
+            UnassignedAdvice::Error,
+        )
+        .unwrap();
+        assert_eq!(prover.verify(), Err(unassigned()));
+        assert_eq!(prover.verify_par(), Err(unassigned()));
+
+        // Padding with zero satisfies the gate, but the unassigned cell is still
+        // reported.
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(prover.unassigned_queried_cells(), unassigned());
+
+        // Padding with another value does not satisfy the gate.
+        let prover = MockProver::run_with_unassigned_advice(
+            K,
+            &FaultyCircuit {},
+            vec![],
+            UnassignedAdvice::Pad(Fp::one()),
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
     }
 
     #[test]
@@ -1595,4 +1841,568 @@ mod tests {
             }])
         );
     }
+
+    #[test]
+    fn bad_shuffle() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            q: Selector,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let q = meta.complex_selector();
+
+                meta.shuffle("shuffle", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // Where q is enabled, the values of b must be a permutation of the
+                    // values of a.
+                    vec![(q.clone() * a, q * b)]
+                });
+
+                FaultyCircuitConfig { a, b, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // BUG: b holds 5 instead of 1, so it is not a permutation of a.
+                        for (row, (a, b)) in [(1, 4), (2, 3), (3, 2), (4, 5)].iter().enumerate() {
+                            config.q.enable(&mut region, row)?;
+                            region.assign_advice(
+                                config.a,
+                                row,
+                                Value::known(Assigned::Trivial(Fp::from(*a))),
+                            )?;
+                            region.assign_advice(
+                                config.b,
+                                row,
+                                Value::known(Assigned::Trivial(Fp::from(*b))),
+                            )?;
+                        }
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let failure = || {
+            vec![VerifyFailure::Shuffle {
+                name: "shuffle",
+                shuffle_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (0, "Faulty synthesis").into(),
+                    offset: 0,
+                },
+            }]
+        };
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Err(failure()));
+        assert_eq!(prover.verify_par(), Err(failure()));
+    }
+
+    #[test]
+    fn namespace_advice_cells() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct CountingConfig {
+            a: Column<Advice>,
+        }
+
+        struct CountingCircuit {}
+
+        impl Circuit<Fp> for CountingCircuit {
+            type Config = CountingConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                CountingConfig {
+                    a: meta.advice_column(),
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let mut outer = layouter.namespace(|| "outer");
+                {
+                    let mut inner = outer.namespace(|| "inner");
+                    inner.assign_region(
+                        || "two cells",
+                        |mut region| {
+                            for offset in 0..2 {
+                                region.assign_advice(
+                                    config.a,
+                                    offset,
+                                    Value::known(Assigned::Trivial(Fp::zero())),
+                                )?;
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                outer.assign_region(
+                    || "one cell",
+                    |mut region| {
+                        region.assign_advice(
+                            config.a,
+                            0,
+                            Value::known(Assigned::Trivial(Fp::one())),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &CountingCircuit {}, vec![]).unwrap();
+        let counts: Vec<_> = prover
+            .namespace_advice_cells()
+            .iter()
+            .map(|cells| (cells.path.as_str(), cells.advice_cells))
+            .collect();
+        assert_eq!(counts, vec![("outer/inner", 2), ("outer", 3)]);
+    }
+
+    #[test]
+    fn circuit_cost_copies() {
+        const K: usize = 4;
+
+        #[derive(Clone)]
+        struct CopyConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+        }
+
+        struct CopyCircuit {}
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = CopyConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                meta.enable_equality(a);
+                meta.enable_equality(b);
+                CopyConfig { a, b }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "copies",
+                    |mut region| {
+                        let mut cells = vec![];
+                        for offset in 0..3 {
+                            for column in [config.a, config.b] {
+                                let cell = region.assign_advice(
+                                    column,
+                                    offset,
+                                    Value::known(Assigned::Trivial(Fp::zero())),
+                                )?;
+                                cells.push(*cell.cell());
+                            }
+                        }
+
+                        // One cycle of four cells, built from three copies, and a
+                        // second cycle of two cells.
+                        region.constrain_equal(&cells[0], &cells[1]);
+                        region.constrain_equal(&cells[2], &cells[3]);
+                        region.constrain_equal(&cells[1], &cells[3]);
+                        region.constrain_equal(&cells[4], &cells[5]);
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let cost = CircuitCost::<vesta::Point, _>::measure(K, &CopyCircuit {});
+        assert_eq!(cost.copy_constraints(), 4);
+        assert_eq!(cost.copy_cycles(), 2);
+        assert_eq!(cost.permutation_columns(), 2);
+        // With only degree-3 gates, each grand product covers a single column.
+        assert_eq!(cost.permutation_product_polys(), 2);
+    }
+
+    #[test]
+    fn gate_check() {
+        use rand_core::OsRng;
+
+        #[derive(Clone)]
+        struct GatesConfig {}
+
+        struct GatesCircuit {}
+
+        impl Circuit<Fp> for GatesCircuit {
+            type Config = GatesConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let c = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("add", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let c = cells.query_advice(c, Rotation::cur());
+                    let q = cells.query_selector(q);
+                    vec![q * (a + b - c)]
+                });
+
+                // The sign typo makes this gate constrain nothing.
+                meta.create_gate("double", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+                    vec![q * (a.clone() + a.clone() - a * Fp::from(2))]
+                });
+
+                // No value of `a` is both 2 and boolean.
+                meta.create_gate("unsatisfiable", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+                    vec![
+                        q.clone() * (a.clone() - Expression::Constant(Fp::from(2))),
+                        q * a.clone() * (Expression::Constant(Fp::one()) - a),
+                    ]
+                });
+
+                GatesConfig {}
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(&self, _: Self::Config, _: impl Layouter<Fp>) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        let checks = GateCheck::run::<GatesCircuit>(OsRng);
+        let results: Vec<_> = checks
+            .iter()
+            .map(|check| {
+                (
+                    check.name(),
+                    check.satisfying_assignment().is_some(),
+                    check.accepts_random(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("add", true, false),
+                ("double", true, true),
+                ("unsatisfiable", false, false),
+            ]
+        );
+        assert!(checks[0].passed());
+    }
+
+    #[test]
+    fn table_size_from_k() {
+        #[derive(Clone)]
+        struct RangeConfig {
+            a: Column<Advice>,
+            q: Selector,
+            table: TableColumn,
+            bits: usize,
+        }
+
+        struct RangeCircuit {}
+
+        impl Circuit<Fp> for RangeCircuit {
+            type Config = RangeConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.complex_selector();
+                let table = meta.lookup_table_column();
+
+                // Use the largest table that fits in the circuit.
+                let bits = match meta.k() {
+                    Some(k) if k >= 10 => 8,
+                    _ => 4,
+                };
+
+                meta.lookup("range check", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+                    vec![(q * a, table)]
+                });
+
+                RangeConfig { a, q, table, bits }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "range table",
+                    |mut table| {
+                        for i in 0..(1 << config.bits) {
+                            table.assign_cell(
+                                || format!("table[{}]", i),
+                                config.table,
+                                i,
+                                || Value::known(Fp::from(i as u64)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "range check",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(
+                            config.a,
+                            0,
+                            Value::known(Assigned::Trivial(Fp::from((1 << config.bits) - 1))),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        // A 256-row table would not fit in a circuit with 2^5 rows.
+        for k in [5, 10] {
+            let prover = MockProver::run(k, &RangeCircuit {}, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+        assert_eq!(ConstraintSystem::<Fp>::default().k(), None);
+        assert_eq!(ConstraintSystem::<Fp>::with_k(10).k(), Some(10));
+    }
+
+    #[test]
+    fn explore_failure() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct SquareConfig {
+            a: Column<Advice>,
+            q: Selector,
+        }
+
+        struct SquareCircuit {}
+
+        impl Circuit<Fp> for SquareCircuit {
+            type Config = SquareConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+                meta.enable_equality(a);
+
+                meta.create_gate("square", |cells| {
+                    let cur = cells.query_advice(a, Rotation::cur());
+                    let next = cells.query_advice(a, Rotation::next());
+                    let q = cells.query_selector(q);
+                    vec![q * (cur.clone() * cur - next)]
+                });
+
+                SquareConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "squares",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        let mut cells = vec![];
+                        for (offset, value) in [3, 10, 10].into_iter().enumerate() {
+                            let cell = region.assign_advice(
+                                config.a,
+                                offset,
+                                Value::known(Assigned::Trivial(Fp::from(value))),
+                            )?;
+                            cells.push(*cell.cell());
+                        }
+                        region.constrain_equal(&cells[1], &cells[2]);
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &SquareCircuit {}, vec![]).unwrap();
+        let a = Column::new(0, Any::advice());
+        let location = |offset| FailureLocation::InRegion {
+            region: (0, "squares").into(),
+            offset,
+        };
+        assert!(prover.verify().is_err());
+
+        assert_eq!(prover.cell_value(0, a, 1), Some(Fp::from(10)));
+        // The cell below the region was never assigned.
+        assert_eq!(prover.cell_value(0, a, 3), None);
+        assert_eq!(prover.cell_value(1, a, 0), None);
+
+        assert_eq!(
+            prover.constraints_involving(0, a, 1),
+            vec![
+                InvolvedConstraint::Gate {
+                    constraint: ((0, "square").into(), 0, "").into(),
+                    location: location(0),
+                    rotation: 1,
+                    value: Some(-Fp::one()),
+                },
+                InvolvedConstraint::Equality {
+                    column: a.into(),
+                    location: location(2),
+                },
+            ]
+        );
+        assert_eq!(
+            prover.constraints_involving(0, a, 2),
+            vec![InvolvedConstraint::Equality {
+                column: a.into(),
+                location: location(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn verify_regions() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct SquareConfig {
+            a: Column<Advice>,
+            q: Selector,
+        }
+
+        struct SquareCircuit {}
+
+        impl Circuit<Fp> for SquareCircuit {
+            type Config = SquareConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+                meta.enable_equality(a);
+
+                meta.create_gate("square", |cells| {
+                    let cur = cells.query_advice(a, Rotation::cur());
+                    let next = cells.query_advice(a, Rotation::next());
+                    let q = cells.query_selector(q);
+                    vec![q * (cur.clone() * cur - next)]
+                });
+
+                SquareConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                // The "bad" region fails both its gate and its copy constraint.
+                // It is placed at offsets 2-3 so that it shares no rows with
+                // the "good" region.
+                for (name, start, values) in [("good", 0, [2, 4]), ("bad", 2, [3, 10])] {
+                    layouter.assign_region(
+                        || name,
+                        |mut region| {
+                            config.q.enable(&mut region, start)?;
+                            let mut cells = vec![];
+                            for (offset, value) in values.into_iter().enumerate() {
+                                let cell = region.assign_advice(
+                                    config.a,
+                                    start + offset,
+                                    Value::known(Fp::from(value)),
+                                )?;
+                                cells.push(*cell.cell());
+                            }
+                            if name == "bad" {
+                                region.constrain_equal(&cells[0], &cells[1]);
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        let prover = MockProver::run(K, &SquareCircuit {}, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+        assert_eq!(prover.verify_regions(&["good"]), Ok(()));
+
+        let failures = prover.verify_regions(&["good", "bad"]).unwrap_err();
+        assert_eq!(failures.len(), 3);
+        assert!(matches!(
+            failures[0],
+            VerifyFailure::ConstraintNotSatisfied { .. }
+        ));
+        assert!(failures[1..]
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+    }
 }
@@ -0,0 +1,359 @@
+//! Developer tools for benchmarking key generation, proving and verification.
+//!
+//! [`Bench::run`] measures every step of the life of a circuit with the same
+//! transcript and randomness, so that the numbers reported by different projects
+//! can be compared. The raw samples are kept in the report, so they can also be
+//! fed to an external harness such as `criterion`'s `iter_custom`.
+
+use std::time::{Duration, Instant};
+
+use rand_core::SeedableRng;
+
+use crate::{
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, DeterministicRng, Error,
+        ProvingKey,
+    },
+    poly::{
+        commitment::{CommitmentScheme, ParamsProver, Prover, Verifier},
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+
+/// The prover's timings of each phase, if they are recorded.
+#[cfg(feature = "profile")]
+type Timings = crate::plonk::ProverTimings;
+#[cfg(not(feature = "profile"))]
+type Timings = ();
+
+/// Summary statistics of the samples of a measurement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    /// The samples, in the order in which they were taken.
+    pub samples: Vec<Duration>,
+    /// The shortest sample.
+    pub min: Duration,
+    /// The longest sample.
+    pub max: Duration,
+    /// The mean of the samples.
+    pub mean: Duration,
+    /// The median of the samples.
+    pub median: Duration,
+}
+
+impl Stats {
+    /// Computes the statistics of a non-empty set of samples.
+    ///
+    /// Panics if `samples` is empty.
+    pub fn new(samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "at least one sample is required");
+
+        let mut sorted = samples.clone();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+
+        Stats {
+            min: sorted[0],
+            max: sorted[n - 1],
+            mean: sorted.iter().sum::<Duration>() / n as u32,
+            median,
+            samples,
+        }
+    }
+}
+
+/// The measurements of a circuit taken by [`Bench::run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchReport {
+    /// The base-2 logarithm of the number of rows of the circuit.
+    pub k: u32,
+    /// The size of a proof, in bytes.
+    pub proof_size: usize,
+    /// Generating the verifying key.
+    pub keygen_vk: Stats,
+    /// Generating the proving key from the verifying key.
+    pub keygen_pk: Stats,
+    /// Creating a proof.
+    pub prove: Stats,
+    /// Verifying a proof.
+    pub verify: Stats,
+    /// The time spent in each phase of creating a proof, named as in
+    /// [`ProverTimings::phases`](crate::plonk::ProverTimings::phases).
+    #[cfg(feature = "profile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+    pub prover_phases: Vec<(&'static str, Stats)>,
+}
+
+/// Benchmarks key generation, proving and verification of a circuit.
+///
+/// Each sample runs every step once, after a number of warmup runs that are not
+/// measured. Proofs are created with a [`DeterministicRng`] and a BLAKE2b
+/// transcript.
+#[derive(Clone, Debug)]
+pub struct Bench {
+    warmups: usize,
+    samples: usize,
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Bench {
+            warmups: 1,
+            samples: 10,
+        }
+    }
+}
+
+impl Bench {
+    /// Creates a benchmark with one warmup run and ten samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of runs before the samples are taken.
+    pub fn warmups(mut self, warmups: usize) -> Self {
+        self.warmups = warmups;
+        self
+    }
+
+    /// Sets the number of samples taken of each step.
+    ///
+    /// Panics if `samples` is zero.
+    pub fn samples(mut self, samples: usize) -> Self {
+        assert!(samples > 0, "at least one sample is required");
+        self.samples = samples;
+        self
+    }
+
+    /// Benchmarks `circuit` with the given `instances`, using the multiopen
+    /// prover `P` and verifier `V`.
+    ///
+    /// Returns an error if a step fails, including [`Error::Opening`] if a proof
+    /// does not verify.
+    pub fn run<'params, Scheme, P, V, Strategy, ConcreteCircuit>(
+        &self,
+        params: &'params Scheme::ParamsProver,
+        circuit: &ConcreteCircuit,
+        instances: &[&[Scheme::Scalar]],
+    ) -> Result<BenchReport, Error>
+    where
+        Scheme: CommitmentScheme,
+        P: Prover<'params, Scheme>,
+        V: Verifier<'params, Scheme>,
+        Strategy: VerificationStrategy<'params, Scheme, V, Output = Strategy>,
+        ConcreteCircuit: Circuit<Scheme::Scalar>,
+    {
+        let empty_circuit = circuit.without_witnesses();
+        let instances = &[instances];
+
+        let keygen = || -> Result<(Duration, Duration, ProvingKey<Scheme::Curve>), Error> {
+            let start = Instant::now();
+            let vk = keygen_vk(params, &empty_circuit)?;
+            let keygen_vk = start.elapsed();
+
+            let start = Instant::now();
+            let pk = keygen_pk(params, vk, &empty_circuit)?;
+            Ok((keygen_vk, start.elapsed(), pk))
+        };
+
+        let prove =
+            |pk: &ProvingKey<Scheme::Curve>| -> Result<(Duration, Vec<u8>, Timings), Error> {
+                let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+                let rng = DeterministicRng::from_seed([0; 32]);
+                let circuits = std::slice::from_ref(circuit);
+                let start = Instant::now();
+                #[cfg(feature = "profile")]
+                let timings = crate::plonk::create_proof_with_timings::<Scheme, P, _, _, _, _>(
+                    params,
+                    pk,
+                    circuits,
+                    instances,
+                    rng,
+                    &mut transcript,
+                )?;
+                #[cfg(not(feature = "profile"))]
+                #[allow(clippy::let_unit_value)]
+                let timings = create_proof::<Scheme, P, _, _, _, _>(
+                    params,
+                    pk,
+                    circuits,
+                    instances,
+                    rng,
+                    &mut transcript,
+                )?;
+                Ok((start.elapsed(), transcript.finalize(), timings))
+            };
+
+        let verify = |pk: &ProvingKey<Scheme::Curve>, proof: &[u8]| -> Result<Duration, Error> {
+            let verifier_params = params.verifier_params();
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+            let start = Instant::now();
+            let strategy = verify_proof::<Scheme, V, _, _, _>(
+                verifier_params,
+                pk.get_vk(),
+                Strategy::new(verifier_params),
+                instances,
+                &mut transcript,
+            )?;
+            if !strategy.finalize() {
+                return Err(Error::Opening);
+            }
+            Ok(start.elapsed())
+        };
+
+        for _ in 0..self.warmups {
+            let (_, _, pk) = keygen()?;
+            let (_, proof, _) = prove(&pk)?;
+            verify(&pk, &proof)?;
+        }
+
+        let mut samples = [(); 4].map(|_| Vec::with_capacity(self.samples));
+        let mut prover_timings: Vec<Timings> = Vec::with_capacity(self.samples);
+        let mut k = 0;
+        let mut proof_size = 0;
+        for _ in 0..self.samples {
+            let (keygen_vk_time, keygen_pk_time, pk) = keygen()?;
+            let (prove_time, proof, timings) = prove(&pk)?;
+            let verify_time = verify(&pk, &proof)?;
+
+            let sample = [keygen_vk_time, keygen_pk_time, prove_time, verify_time];
+            for (samples, sample) in samples.iter_mut().zip(sample) {
+                samples.push(sample);
+            }
+            prover_timings.push(timings);
+            k = pk.get_vk().get_domain().k();
+            proof_size = proof.len();
+        }
+
+        let [keygen_vk, keygen_pk, prove, verify] = samples.map(Stats::new);
+        Ok(BenchReport {
+            k,
+            proof_size,
+            keygen_vk,
+            keygen_pk,
+            prove,
+            verify,
+            #[cfg(feature = "profile")]
+            prover_phases: (0..prover_timings[0].phases().len())
+                .map(|phase| {
+                    let name = prover_timings[0].phases()[phase].0;
+                    let samples = prover_timings
+                        .iter()
+                        .map(|timings| timings.phases()[phase].1)
+                        .collect();
+                    (name, Stats::new(samples))
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    use super::{Bench, Stats};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::{
+            commitment::ParamsProver,
+            ipa::{
+                commitment::{IPACommitmentScheme, ParamsIPA},
+                multiopen::{ProverIPA, VerifierIPA},
+                strategy::AccumulatorStrategy,
+            },
+            Rotation,
+        },
+    };
+
+    #[test]
+    fn stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::new(vec![ms(4), ms(1), ms(3), ms(2)]);
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.max, ms(4));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.samples, vec![ms(4), ms(1), ms(3), ms(2)]);
+
+        assert_eq!(Stats::new(vec![ms(3), ms(1), ms(2)]).median, ms(2));
+    }
+
+    #[derive(Clone, Copy)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    struct SquareCircuit(Value<Fp>);
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SquareCircuit(Value::unknown())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("square", |meta| {
+                let s = meta.query_selector(s);
+                let cur = meta.query_advice(a, Rotation::cur());
+                let next = meta.query_advice(a, Rotation::next());
+                vec![s * (cur.clone() * cur - next)]
+            });
+            SquareConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(config.a, 0, self.0)?;
+                    region.assign_advice(config.a, 1, self.0.map(|a| a * a))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn run() {
+        let params = ParamsIPA::<EqAffine>::new(4);
+        let circuit = SquareCircuit(Value::known(Fp::from(3)));
+
+        let report = Bench::new()
+            .warmups(0)
+            .samples(2)
+            .run::<IPACommitmentScheme<_>, ProverIPA<_>, VerifierIPA<_>, AccumulatorStrategy<_>, _>(
+                &params,
+                &circuit,
+                &[],
+            )
+            .unwrap();
+        assert_eq!(report.k, 4);
+        assert_eq!(report.prove.samples.len(), 2);
+        assert_eq!(report.verify.samples.len(), 2);
+        assert!(report.proof_size > 0);
+        #[cfg(feature = "profile")]
+        assert_eq!(report.prover_phases[0].0, "synthesis");
+    }
+}
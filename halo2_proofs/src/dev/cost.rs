@@ -34,8 +34,15 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     fixed_queries: usize,
     /// Number of lookup arguments.
     lookups: usize,
+    /// Number of shuffle arguments.
+    shuffles: usize,
     /// Number of columns in the global permutation.
     permutation_cols: usize,
+    /// Number of equality constraints applied during synthesis.
+    copies: usize,
+    /// Number of cycles (sets of at least two cells constrained to be equal) in the
+    /// global permutation.
+    cycles: usize,
     /// Number of distinct sets of points in the multiopening argument.
     point_sets: usize,
 
@@ -44,6 +51,7 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
 
 struct Assembly {
     selectors: Vec<Vec<bool>>,
+    copies: Vec<((Column<Any>, usize), (Column<Any>, usize))>,
 }
 
 impl<F: Field> Assignment<F> for Assembly {
@@ -73,40 +81,26 @@ impl<F: Field> Assignment<F> for Assembly {
         Ok(Value::unknown())
     }
 
-    fn assign_advice(
-        //<V, VR, A, AR>(
-        &mut self,
-        //_: A,
+    fn assign_advice<'r, 'v>(
+        &'r mut self,
         _: Column<Advice>,
         _: usize,
-        to: Value<Assigned<F>>,
-    ) -> Result<Value<&Assigned<F>>, Error>
-/*where
-        V: FnOnce() -> Value<VR>,
-        VR: Into<Assigned<F>>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,*/ {
-        Ok(to.as_ref())
+        _: Value<Assigned<F>>,
+    ) -> Result<Value<&'v Assigned<F>>, Error> {
+        Ok(Value::unknown())
     }
 
-    fn assign_fixed<V, VR, A, AR>(
-        &mut self,
-        _: A,
-        _: Column<Fixed>,
-        _: usize,
-        _: V,
-    ) -> Result<(), Error>
-    where
-        V: FnOnce() -> Value<VR>,
-        VR: Into<Assigned<F>>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        Ok(())
-    }
+    fn assign_fixed(&mut self, _: Column<Fixed>, _: usize, _: Assigned<F>) {}
 
-    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
-        Ok(())
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) {
+        self.copies
+            .push(((left_column, left_row), (right_column, right_row)));
     }
 
     fn fill_from_row(
@@ -141,10 +135,11 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
     /// Panics if `k` is not large enough for the circuit.
     pub fn measure(k: usize, circuit: &ConcreteCircuit) -> Self {
         // Collect the layout details.
-        let mut cs = ConstraintSystem::default();
+        let mut cs = ConstraintSystem::with_k(k as u32);
         let config = ConcreteCircuit::configure(&mut cs);
         let mut assembly = Assembly {
             selectors: vec![vec![false; 1 << k]; cs.num_selectors],
+            copies: vec![],
         };
         ConcreteCircuit::FloorPlanner::synthesize(
             &mut assembly,
@@ -153,7 +148,43 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             cs.constants.clone(),
         )
         .unwrap();
-        let (cs, _) = cs.compress_selectors(assembly.selectors);
+        let (cs, _) = cs.compress_selectors(assembly.selectors).unwrap();
+
+        // Merge the copied cells into cycles.
+        let mut cycle_of: HashMap<(Column<Any>, usize), usize> = HashMap::new();
+        let mut cycles: Vec<Vec<(Column<Any>, usize)>> = vec![];
+        for &(left, right) in &assembly.copies {
+            match (cycle_of.get(&left).copied(), cycle_of.get(&right).copied()) {
+                (Some(l), Some(r)) if l == r => (),
+                (Some(l), Some(r)) => {
+                    // Merge the smaller cycle into the larger one.
+                    let (big, small) = if cycles[l].len() < cycles[r].len() {
+                        (r, l)
+                    } else {
+                        (l, r)
+                    };
+                    let moved = std::mem::take(&mut cycles[small]);
+                    for cell in &moved {
+                        cycle_of.insert(*cell, big);
+                    }
+                    cycles[big].extend(moved);
+                }
+                (Some(c), None) | (None, Some(c)) => {
+                    let cell = if cycle_of.contains_key(&left) {
+                        right
+                    } else {
+                        left
+                    };
+                    cycle_of.insert(cell, c);
+                    cycles[c].push(cell);
+                }
+                (None, None) => {
+                    cycle_of.insert(left, cycles.len());
+                    cycle_of.insert(right, cycles.len());
+                    cycles.push(vec![left, right]);
+                }
+            }
+        }
 
         assert!((1 << k) >= cs.minimum_rows());
 
@@ -189,13 +220,18 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         point_sets.insert(vec![-1, 0]); // permuted_input_poly
         point_sets.insert(vec![0]); // permuted_table_poly
 
+        // Include shuffle polynomials in point sets:
+        if !cs.shuffles.is_empty() {
+            point_sets.insert(vec![0, 1]); // product_poly
+        }
+
         // Include permutation polynomials in point sets.
         point_sets.insert(vec![0, 1]); // permutation_product_poly
         let max_deg = cs.degree();
         let permutation_cols = cs.permutation.get_columns().len();
         if permutation_cols > max_deg - 2 {
             // permutation_product_poly for chaining chunks.
-            point_sets.insert(vec![-((cs.blinding_factors() + 1) as i32), 0, 1]);
+            point_sets.insert(vec![-(cs.unusable_rows() as i32), 0, 1]);
         }
 
         CircuitCost {
@@ -206,7 +242,10 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             advice_queries: cs.advice_queries.len(),
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
+            shuffles: cs.shuffles.len(),
             permutation_cols,
+            copies: assembly.copies.len(),
+            cycles: cycles.iter().filter(|cycle| !cycle.is_empty()).count(),
             point_sets: point_sets.len(),
             _marker: PhantomData::default(),
         }
@@ -217,6 +256,29 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         (self.permutation_cols + chunk_size - 1) / chunk_size
     }
 
+    /// Returns the number of equality constraints applied by the circuit.
+    pub fn copy_constraints(&self) -> usize {
+        self.copies
+    }
+
+    /// Returns the number of cycles in the global permutation, i.e. the number of
+    /// distinct sets of cells that are constrained to be equal.
+    pub fn copy_cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Returns the number of columns in the global permutation, each of which
+    /// requires a permutation (sigma) polynomial in the proving key.
+    pub fn permutation_columns(&self) -> usize {
+        self.permutation_cols
+    }
+
+    /// Returns the number of grand product polynomials the prover commits to for
+    /// the global permutation, per instance of the circuit.
+    pub fn permutation_product_polys(&self) -> usize {
+        self.permutation_chunks()
+    }
+
     /// Returns the marginal proof size per instance of this circuit.
     pub fn marginal_proof_size(&self) -> MarginalProofSize<G> {
         let chunks = self.permutation_chunks();
@@ -234,6 +296,11 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - 5 evals per lookup argument per instance
             lookups: ProofContribution::new(3 * self.lookups, 5 * self.lookups),
 
+            // Shuffle arguments:
+            // - 1 commitment per shuffle argument per instance
+            // - 2 evals per shuffle argument per instance
+            shuffles: ProofContribution::new(self.shuffles, 2 * self.shuffles),
+
             // Global permutation argument:
             // - chunks commitments per instance
             // - 2*chunks + (chunks - 1) evals per instance
@@ -259,6 +326,10 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - marginal cost per instance
             lookups: marginal.lookups * instances,
 
+            // Shuffle arguments:
+            // - marginal cost per instance
+            shuffles: marginal.shuffles * instances,
+
             // Global permutation argument:
             // - marginal cost per instance
             // - 1 eval per column
@@ -335,6 +406,7 @@ pub struct MarginalProofSize<G: PrimeGroup> {
     instance: ProofContribution,
     advice: ProofContribution,
     lookups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     _marker: PhantomData<G>,
 }
@@ -347,6 +419,7 @@ impl<G: PrimeGroup> From<MarginalProofSize<G>> for usize {
         proof.instance.len(point, scalar)
             + proof.advice.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
     }
 }
@@ -358,6 +431,7 @@ pub struct ProofSize<G: PrimeGroup> {
     advice: ProofContribution,
     fixed: ProofContribution,
     lookups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     vanishing: ProofContribution,
     multiopen: ProofContribution,
@@ -374,6 +448,7 @@ impl<G: PrimeGroup> From<ProofSize<G>> for usize {
             + proof.advice.len(point, scalar)
             + proof.fixed.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
             + proof.vanishing.len(point, scalar)
             + proof.multiopen.len(point, scalar)
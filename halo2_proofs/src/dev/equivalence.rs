@@ -0,0 +1,419 @@
+//! Developer tools for checking that two circuits constrain the same relation.
+//!
+//! This is useful when refactoring a chip: [`check_structure`] confirms that a
+//! change left the constraint system, fixed columns and copy constraints
+//! untouched, and [`check_witnesses`] confirms that a circuit whose layout did
+//! change still accepts and rejects the same witnesses as the original.
+
+use std::fmt;
+
+use halo2curves::FieldExt;
+
+use super::{MockProver, Value, VerifyFailure};
+use crate::plonk::{Circuit, Error};
+
+/// The outcome of running a circuit on a witness with [`MockProver`].
+#[derive(Debug)]
+pub enum Outcome {
+    /// The witness satisfies the circuit.
+    Accepted,
+    /// The witness violates the constraints of the circuit.
+    Rejected(Vec<VerifyFailure>),
+    /// The circuit could not be synthesized with the witness.
+    Failed(Error),
+}
+
+impl Outcome {
+    pub(super) fn of<F: FieldExt, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instance: Vec<Vec<F>>,
+    ) -> Self {
+        match MockProver::run(k, circuit, instance) {
+            Ok(prover) => match prover.verify() {
+                Ok(()) => Outcome::Accepted,
+                Err(failures) => Outcome::Rejected(failures),
+            },
+            Err(error) => Outcome::Failed(error),
+        }
+    }
+
+    /// Returns `true` if the witness satisfies the circuit.
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Outcome::Accepted)
+    }
+}
+
+/// A difference between two circuits, found by [`check_structure`] or
+/// [`check_witnesses`].
+#[derive(Debug)]
+pub enum EquivalenceFailure {
+    /// One of the circuits could not be synthesized.
+    Synthesis(Error),
+    /// The circuits configure different constraint systems.
+    ConstraintSystem {
+        /// The first line of the pretty-printed constraint systems that differs.
+        line: usize,
+        /// The line in the constraint system of the left circuit.
+        left: String,
+        /// The line in the constraint system of the right circuit.
+        right: String,
+    },
+    /// The circuits assign different values to a fixed cell.
+    Fixed {
+        /// The index of the fixed column.
+        column: usize,
+        /// The row of the cell.
+        row: usize,
+    },
+    /// The circuits enable a selector on different rows.
+    Selector {
+        /// The index of the selector.
+        selector: usize,
+        /// The row on which only one of the circuits enables the selector.
+        row: usize,
+    },
+    /// The circuits constrain a cell to equal different sets of cells.
+    CopyConstraint {
+        /// The index of the column among the columns of the permutation argument.
+        column: usize,
+        /// The row of the cell.
+        row: usize,
+    },
+    /// Only one of the circuits accepts a witness.
+    Outcome {
+        /// The index of the witness among those that were checked.
+        case: usize,
+        /// The outcome of the left circuit.
+        left: Outcome,
+        /// The outcome of the right circuit.
+        right: Outcome,
+    },
+}
+
+impl fmt::Display for EquivalenceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Synthesis(error) => write!(f, "Failed to synthesize a circuit: {}", error),
+            Self::ConstraintSystem { line, left, right } => write!(
+                f,
+                "Constraint systems differ on line {}: `{}` != `{}`",
+                line,
+                left.trim(),
+                right.trim()
+            ),
+            Self::Fixed { column, row } => {
+                write!(f, "Fixed column {} differs on row {}", column, row)
+            }
+            Self::Selector { selector, row } => {
+                write!(f, "Selector {} differs on row {}", selector, row)
+            }
+            Self::CopyConstraint { column, row } => write!(
+                f,
+                "Copy constraints of permutation column {} differ on row {}",
+                column, row
+            ),
+            Self::Outcome { case, left, right } => write!(
+                f,
+                "Witness {} is {} by the left circuit and {} by the right circuit",
+                case,
+                if left.is_accepted() {
+                    "accepted"
+                } else {
+                    "rejected"
+                },
+                if right.is_accepted() {
+                    "accepted"
+                } else {
+                    "rejected"
+                },
+            ),
+        }
+    }
+}
+
+/// Checks that two circuits are structurally identical: that they configure the
+/// same constraint system, and assign the same fixed cells, selectors and copy
+/// constraints with parameter constant `k`.
+///
+/// The circuits are synthesized with [`MockProver`], so they need witnesses, but
+/// the advice cells that they assign are not compared.
+pub fn check_structure<F: FieldExt, A: Circuit<F>, B: Circuit<F>>(
+    k: u32,
+    left: &A,
+    right: &B,
+    instance: Vec<Vec<F>>,
+) -> Result<(), EquivalenceFailure> {
+    let left = MockProver::run(k, left, instance.clone()).map_err(EquivalenceFailure::Synthesis)?;
+    let right = MockProver::run(k, right, instance).map_err(EquivalenceFailure::Synthesis)?;
+
+    let left_cs = format!("{:#?}", left.cs.pinned());
+    let right_cs = format!("{:#?}", right.cs.pinned());
+    if left_cs != right_cs {
+        let mut left_lines = left_cs.lines().map(str::to_owned);
+        let mut right_lines = right_cs.lines().map(str::to_owned);
+        let mut line = 1;
+        loop {
+            match (left_lines.next(), right_lines.next()) {
+                (Some(l), Some(r)) if l == r => line += 1,
+                (left, right) => {
+                    return Err(EquivalenceFailure::ConstraintSystem {
+                        line,
+                        left: left.unwrap_or_default(),
+                        right: right.unwrap_or_default(),
+                    })
+                }
+            }
+        }
+    }
+
+    for (column, (l, r)) in left.fixed.iter().zip(right.fixed.iter()).enumerate() {
+        if let Some(row) = l
+            .iter()
+            .zip(r.iter())
+            .position(|(l, r)| Value::from(*l) != Value::from(*r))
+        {
+            return Err(EquivalenceFailure::Fixed { column, row });
+        }
+    }
+
+    for (selector, (l, r)) in left
+        .selectors
+        .iter()
+        .zip(right.selectors.iter())
+        .enumerate()
+    {
+        if let Some(row) = l.iter().zip(r.iter()).position(|(l, r)| l != r) {
+            return Err(EquivalenceFailure::Selector { selector, row });
+        }
+    }
+
+    let left_classes = copy_classes(&left.permutation.mapping);
+    let right_classes = copy_classes(&right.permutation.mapping);
+    for (column, (l, r)) in left_classes.iter().zip(right_classes.iter()).enumerate() {
+        if let Some(row) = l.iter().zip(r.iter()).position(|(l, r)| l != r) {
+            return Err(EquivalenceFailure::CopyConstraint { column, row });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that two circuits accept the same witnesses with parameter constant
+/// `k`.
+///
+/// Each case is a pair of circuits carrying the same witness, together with
+/// their instance columns. Unlike [`check_structure`], this allows the circuits
+/// to lay out the witness differently. A circuit that fails to synthesize a
+/// witness is considered to reject it.
+pub fn check_witnesses<F: FieldExt, A: Circuit<F>, B: Circuit<F>>(
+    k: u32,
+    cases: impl IntoIterator<Item = (A, B, Vec<Vec<F>>)>,
+) -> Result<(), EquivalenceFailure> {
+    for (case, (left, right, instance)) in cases.into_iter().enumerate() {
+        let left = Outcome::of(k, &left, instance.clone());
+        let right = Outcome::of(k, &right, instance);
+        if left.is_accepted() != right.is_accepted() {
+            return Err(EquivalenceFailure::Outcome { case, left, right });
+        }
+    }
+    Ok(())
+}
+
+/// Labels each cell of the permutation argument with the first cell of the cycle
+/// that contains it, so that copy constraints can be compared regardless of the
+/// order in which they were added.
+fn copy_classes(mapping: &[Vec<(usize, usize)>]) -> Vec<Vec<(usize, usize)>> {
+    let mut classes: Vec<Vec<Option<(usize, usize)>>> = mapping
+        .iter()
+        .map(|column| vec![None; column.len()])
+        .collect();
+    let cells = mapping
+        .iter()
+        .enumerate()
+        .flat_map(|(column, rows)| (0..rows.len()).map(move |row| (column, row)));
+    // Cells are visited in order, so the first unlabelled cell of a cycle is its
+    // smallest.
+    for first in cells {
+        let mut cell = first;
+        while classes[cell.0][cell.1].is_none() {
+            classes[cell.0][cell.1] = Some(first);
+            cell = mapping[cell.0][cell.1];
+        }
+    }
+    classes
+        .into_iter()
+        .map(|column| column.into_iter().map(Option::unwrap).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::{check_structure, check_witnesses, EquivalenceFailure};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    /// Constrains `b = a^2`, with `a` and `b` on the same row.
+    struct Square {
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for Square {
+        type Config = (Column<Advice>, Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Square {
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("square", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                vec![s * (a.clone() * a - b)]
+            });
+            (a, b, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, b, s): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_advice(a, 0, self.a)?;
+                    region.assign_advice(b, 0, self.b)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Constrains `b = a^2` if `DOUBLE` is false, and `b = 2a` otherwise, with `b`
+    /// on the row after `a`.
+    struct SquareNext<const DOUBLE: bool> {
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl<const DOUBLE: bool> Circuit<Fp> for SquareNext<DOUBLE> {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SquareNext {
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("square", |meta| {
+                let s = meta.query_selector(s);
+                let cur = meta.query_advice(a, Rotation::cur());
+                let next = meta.query_advice(a, Rotation::next());
+                let result = if DOUBLE {
+                    cur.clone() + cur
+                } else {
+                    cur.clone() * cur
+                };
+                vec![s * (result - next)]
+            });
+            (a, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, s): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_advice(a, 0, self.a)?;
+                    region.assign_advice(a, 1, self.b)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn witnesses() -> impl Iterator<Item = (Value<Fp>, Value<Fp>)> {
+        [(2, 4), (3, 9), (3, 8)]
+            .into_iter()
+            .map(|(a, b)| (Value::known(Fp::from(a)), Value::known(Fp::from(b))))
+    }
+
+    #[test]
+    fn same_structure() {
+        let [first, second] = [(3, 9), (3, 8)].map(|(a, b)| Square {
+            a: Value::known(Fp::from(a)),
+            b: Value::known(Fp::from(b)),
+        });
+        assert!(check_structure(4, &first, &second, vec![]).is_ok());
+    }
+
+    #[test]
+    fn different_structure() {
+        let (a, b) = witnesses().next().unwrap();
+        assert!(matches!(
+            check_structure(4, &Square { a, b }, &SquareNext::<false> { a, b }, vec![]),
+            Err(EquivalenceFailure::ConstraintSystem { .. })
+        ));
+        assert!(matches!(
+            check_structure(
+                4,
+                &SquareNext::<false> { a, b },
+                &SquareNext::<true> { a, b },
+                vec![]
+            ),
+            Err(EquivalenceFailure::ConstraintSystem { .. })
+        ));
+    }
+
+    #[test]
+    fn same_witnesses() {
+        assert!(check_witnesses(
+            4,
+            witnesses().map(|(a, b)| (Square { a, b }, SquareNext::<false> { a, b }, vec![]))
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn different_witnesses() {
+        // 2 * 2 = 2 + 2, so the circuits only disagree on the second witness.
+        let failure = check_witnesses(
+            4,
+            witnesses().map(|(a, b)| (Square { a, b }, SquareNext::<true> { a, b }, vec![])),
+        )
+        .unwrap_err();
+        match failure {
+            EquivalenceFailure::Outcome { case, left, right } => {
+                assert_eq!(case, 1);
+                assert!(left.is_accepted());
+                assert!(!right.is_accepted());
+            }
+            _ => panic!("unexpected failure: {}", failure),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Developer tools for exploring a completed [`MockProver`] run, for example from an
+//! interactive debugger, without synthesizing the circuit again.
+
+use std::collections::{BTreeSet, HashSet};
+
+use ff::Field;
+use halo2curves::FieldExt;
+
+use super::{metadata, util, AdviceCellValue, CellValue, FailureLocation, MockProver, Value};
+use crate::plonk::{Any, Column, ConstraintSystem, Expression};
+
+/// A constraint that involves a particular cell, as returned by
+/// [`MockProver::constraints_involving`].
+#[derive(Debug, PartialEq)]
+pub enum InvolvedConstraint<F: Field> {
+    /// A gate constraint, enabled at `location`, that queries the cell.
+    Gate {
+        /// The constraint that queries the cell.
+        constraint: metadata::Constraint,
+        /// The location at which the constraint is enabled.
+        location: FailureLocation,
+        /// The rotation at which the constraint queries the cell.
+        rotation: i32,
+        /// The value of the constraint polynomial at `location`, or `None` if it
+        /// depends on a poisoned cell. The constraint is satisfied if this is zero.
+        value: Option<F>,
+    },
+    /// A lookup whose input expressions, evaluated at `location`, query the cell.
+    Lookup {
+        /// The name of the lookup.
+        name: &'static str,
+        /// The index of the lookup. These indices are assigned in the order in which
+        /// `ConstraintSystem::lookup` is called during `Circuit::configure`.
+        lookup_index: usize,
+        /// The location at which the lookup input queries the cell.
+        location: FailureLocation,
+        /// The rotation at which the lookup input queries the cell.
+        rotation: i32,
+    },
+    /// An equality constraint between the cell and the cell at `location` in `column`.
+    Equality {
+        /// The column of the other cell.
+        column: metadata::Column,
+        /// The location of the other cell.
+        location: FailureLocation,
+    },
+}
+
+// The columns and rotations queried by an expression.
+fn queried_cells<F: Field>(
+    cs: &ConstraintSystem<F>,
+    expression: &Expression<F>,
+) -> BTreeSet<(Column<Any>, i32)> {
+    expression.evaluate(
+        &|_| BTreeSet::new(),
+        &|_| panic!("virtual selectors are removed during optimization"),
+        &|query| {
+            let (column, rotation) = cs.fixed_queries[query.index];
+            Some((column.into(), rotation.0)).into_iter().collect()
+        },
+        &|query| {
+            let (column, rotation) = cs.advice_queries[query.index];
+            Some((column.into(), rotation.0)).into_iter().collect()
+        },
+        &|query| {
+            let (column, rotation) = cs.instance_queries[query.index];
+            Some((column.into(), rotation.0)).into_iter().collect()
+        },
+        &|_| BTreeSet::new(),
+        &|a| a,
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+impl<F: FieldExt> MockProver<F> {
+    // The row of the cell at `offset` within the region at index `region`.
+    fn cell_row(&self, region: usize, offset: usize) -> Option<usize> {
+        let (start, _) = self.regions.get(region)?.rows?;
+        Some(start + offset).filter(|row| *row < self.n as usize)
+    }
+
+    /// Returns the value of the cell at `offset` in `column` within the region at index
+    /// `region`, as reported by [`VerifyFailure`](super::VerifyFailure) locations.
+    ///
+    /// Returns `None` if the region has no rows, if a fixed or advice cell was not
+    /// assigned within the region, or if the cell is poisoned.
+    pub fn cell_value(
+        &self,
+        region: usize,
+        column: impl Into<Column<Any>>,
+        offset: usize,
+    ) -> Option<F> {
+        let column = column.into();
+        let row = self.cell_row(region, offset)?;
+        if *column.column_type() != Any::Instance
+            && !self.regions[region].cells.contains_key(&(column, row))
+        {
+            return None;
+        }
+
+        match column.column_type() {
+            Any::Fixed => match self.fixed[column.index()][row] {
+                CellValue::Assigned(value) => Some(value),
+                CellValue::Unassigned | CellValue::Poison(_) => None,
+            },
+            Any::Advice(_) => match &self.advice[column.index()][row] {
+                AdviceCellValue::Assigned(value) => Some(value.as_ref().evaluate()),
+                AdviceCellValue::Poison(_) => None,
+            },
+            Any::Instance => Some(self.instance[column.index()][row]),
+        }
+    }
+
+    /// Returns every gate constraint, lookup and equality constraint that involves the
+    /// cell at `offset` in `column` within the region at index `region`.
+    ///
+    /// Cells are addressed by region and offset, as in [`FailureLocation`], so that the
+    /// cells of a failure can be explored directly. Gate constraints are only returned
+    /// at rows where one of the gate's selectors is enabled (or where the gate has no
+    /// selectors), together with their value at that row.
+    pub fn constraints_involving(
+        &self,
+        region: usize,
+        column: impl Into<Column<Any>>,
+        offset: usize,
+    ) -> Vec<InvolvedConstraint<F>> {
+        let column = column.into();
+        let row = match self.cell_row(region, offset) {
+            Some(row) => row,
+            None => return vec![],
+        };
+        let n = self.n as i32;
+        // The row at which an expression querying the cell at `rotation` is evaluated.
+        let row_at = |rotation: i32| ((row as i32 - rotation).rem_euclid(n)) as usize;
+        let locate = |row, column| {
+            FailureLocation::find(&self.regions, row, Some(column).into_iter().collect())
+        };
+
+        let advice = self.advice_values();
+        let gates = self
+            .cs
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(gate_index, gate)| {
+                gate.polynomials()
+                    .iter()
+                    .enumerate()
+                    .map(move |(poly_index, poly)| (gate_index, gate, poly_index, poly))
+            })
+            .flat_map(|(gate_index, gate, poly_index, poly)| {
+                queried_cells(&self.cs, poly)
+                    .into_iter()
+                    .filter(move |(queried, _)| *queried == column)
+                    .map(move |(_, rotation)| (gate_index, gate, poly_index, poly, rotation))
+            })
+            .filter(|(_, gate, _, _, rotation)| {
+                let gate_row = row_at(*rotation);
+                self.usable_rows.contains(&gate_row)
+                    && (gate.queried_selectors().is_empty()
+                        || gate
+                            .queried_selectors()
+                            .iter()
+                            .any(|selector| self.selectors[selector.0][gate_row]))
+            })
+            .map(|(gate_index, gate, poly_index, poly, rotation)| {
+                let gate_row = row_at(rotation);
+                let eval_row = gate_row as i32 + n;
+                let value = poly.evaluate_lazy(
+                    &|scalar| Value::Real(scalar),
+                    &|_| panic!("virtual selectors are removed during optimization"),
+                    &util::load(n, eval_row, &self.cs.fixed_queries, &self.fixed),
+                    &util::load(n, eval_row, &self.cs.advice_queries, &advice),
+                    &util::load_instance(n, eval_row, &self.cs.instance_queries, &self.instance),
+                    &|challenge| Value::Real(self.challenges[challenge.index()]),
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                    &Value::Real(F::zero()),
+                );
+                InvolvedConstraint::Gate {
+                    constraint: (
+                        (gate_index, gate.name()).into(),
+                        poly_index,
+                        gate.constraint_name(poly_index),
+                    )
+                        .into(),
+                    location: FailureLocation::find_expressions(
+                        &self.cs,
+                        &self.regions,
+                        gate_row,
+                        Some(poly).into_iter(),
+                    ),
+                    rotation,
+                    value: match value {
+                        Value::Real(value) => Some(value),
+                        Value::Poison => None,
+                    },
+                }
+            });
+
+        let lookups = self
+            .cs
+            .lookups
+            .iter()
+            .enumerate()
+            .flat_map(|(lookup_index, lookup)| {
+                let queried: BTreeSet<_> = lookup
+                    .input_expressions
+                    .iter()
+                    .flat_map(|expression| queried_cells(&self.cs, expression))
+                    .collect();
+                let columns: HashSet<_> = queried.iter().map(|(column, _)| *column).collect();
+                queried
+                    .into_iter()
+                    .filter(|(queried, _)| *queried == column)
+                    .map(|(_, rotation)| (rotation, row_at(rotation)))
+                    .filter(|(_, input_row)| self.usable_rows.contains(input_row))
+                    .map(|(rotation, input_row)| InvolvedConstraint::Lookup {
+                        name: lookup.name,
+                        lookup_index,
+                        location: FailureLocation::find(&self.regions, input_row, columns.clone()),
+                        rotation,
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        // Walk the cycle of the permutation that contains the cell.
+        let columns = self.cs.permutation.get_columns();
+        let mut equalities = vec![];
+        if let Some(start) = columns.iter().position(|c| *c == column) {
+            let mut cell = self.permutation.mapping[start][row];
+            while cell != (start, row) {
+                equalities.push(InvolvedConstraint::Equality {
+                    column: columns[cell.0].into(),
+                    location: locate(cell.1, columns[cell.0]),
+                });
+                cell = self.permutation.mapping[cell.0][cell.1];
+            }
+        }
+
+        gates.chain(lookups).chain(equalities).collect()
+    }
+}
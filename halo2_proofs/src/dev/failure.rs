@@ -171,6 +171,18 @@ pub enum VerifyFailure {
         ///   lookup is active on a row adjacent to an unrelated region.
         location: FailureLocation,
     },
+    /// A shuffle input did not match the corresponding shuffle row.
+    Shuffle {
+        /// The name of the shuffle that is not satisfied.
+        name: &'static str,
+        /// The index of the shuffle that is not satisfied. These indices are assigned in
+        /// the order in which `ConstraintSystem::shuffle` is called during
+        /// `Circuit::configure`.
+        shuffle_index: usize,
+        /// The location of an input row that has no counterpart among the rows of the
+        /// shuffle expressions.
+        location: FailureLocation,
+    },
     /// A permutation did not preserve the original value of a cell.
     Permutation {
         /// The column in which this permutation is not satisfied.
@@ -225,6 +237,17 @@ impl fmt::Display for VerifyFailure {
                     name, lookup_index, location
                 )
             }
+            Self::Shuffle {
+                name,
+                shuffle_index,
+                location,
+            } => {
+                write!(
+                    f,
+                    "Shuffle {}(index: {}) is not satisfied {}",
+                    name, shuffle_index, location
+                )
+            }
             Self::Permutation { column, location } => {
                 write!(
                     f,
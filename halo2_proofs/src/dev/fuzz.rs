@@ -0,0 +1,286 @@
+//! Developer tools for fuzz-testing circuits, for example with `cargo-fuzz`.
+//!
+//! A fuzz target builds a circuit from fuzzer input, such as
+//! [`ArbitraryField`] elements with the `arbitrary` feature, and passes it to
+//! [`FuzzReport::run`]. The report records which constraints reject each
+//! witness, so a fuzzer can check its coverage of the circuit's constraints and
+//! assert that known-good witnesses are accepted.
+//!
+//! ```ignore
+//! #![no_main]
+//! use halo2_proofs::{circuit::Value, dev::fuzz::{ArbitraryField, FuzzReport}};
+//! use halo2curves::pasta::Fp;
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|input: (ArbitraryField<Fp>, ArbitraryField<Fp>)| {
+//!     let circuit = MyCircuit {
+//!         a: Value::known(input.0 .0),
+//!         b: Value::known(input.1 .0),
+//!     };
+//!     let mut report = FuzzReport::default();
+//!     if report.run(4, &circuit, vec![]).is_accepted() {
+//!         assert_eq!(input.0 .0.square(), input.1 .0);
+//!     }
+//! });
+//! ```
+
+use std::collections::BTreeMap;
+
+use halo2curves::FieldExt;
+
+use super::{equivalence::Outcome, metadata, VerifyFailure};
+use crate::plonk::Circuit;
+
+/// A field element that can be generated from unstructured fuzzer input.
+///
+/// Most generated elements are small, the negation of a small value, or one of
+/// `0`, `1` and `-1`, because those are the values that tend to expose missing
+/// range checks and boolean constraints. The remaining elements are uniformly
+/// distributed.
+#[cfg(feature = "arbitrary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArbitraryField<F>(pub F);
+
+#[cfg(feature = "arbitrary")]
+impl<'a, F: FieldExt> arbitrary::Arbitrary<'a> for ArbitraryField<F> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let value = match u.int_in_range(0u8..=3)? {
+            0 => *u.choose(&[F::zero(), F::one(), -F::one()])?,
+            1 => F::from(u.arbitrary::<u64>()?),
+            2 => -F::from(u.arbitrary::<u64>()?),
+            _ => {
+                let mut bytes = [0; 64];
+                u.fill_buffer(&mut bytes)?;
+                F::from_bytes_wide(&bytes)
+            }
+        };
+        Ok(ArbitraryField(value))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(65))
+    }
+}
+
+/// A reason for which [`MockProver`](super::MockProver) rejected a witness, as
+/// recorded by [`FuzzReport`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rejection {
+    /// A cell queried by an enabled gate was not assigned.
+    CellNotAssigned(metadata::Gate),
+    /// A gate constraint was not satisfied, or was active on an unusable row.
+    Constraint(metadata::Constraint),
+    /// A lookup input did not exist in its table.
+    Lookup {
+        /// The name of the lookup.
+        name: &'static str,
+        /// The index of the lookup.
+        lookup_index: usize,
+    },
+    /// A shuffle input did not match the shuffle rows.
+    Shuffle {
+        /// The name of the shuffle.
+        name: &'static str,
+        /// The index of the shuffle.
+        shuffle_index: usize,
+    },
+    /// An equality constraint on a cell of the column was not satisfied.
+    Permutation(metadata::Column),
+}
+
+impl From<&VerifyFailure> for Rejection {
+    fn from(failure: &VerifyFailure) -> Self {
+        match failure {
+            VerifyFailure::CellNotAssigned { gate, .. } => Rejection::CellNotAssigned(gate.clone()),
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+            | VerifyFailure::ConstraintPoisoned { constraint } => {
+                Rejection::Constraint(constraint.clone())
+            }
+            VerifyFailure::Lookup {
+                name, lookup_index, ..
+            } => Rejection::Lookup {
+                name: *name,
+                lookup_index: *lookup_index,
+            },
+            VerifyFailure::Shuffle {
+                name,
+                shuffle_index,
+                ..
+            } => Rejection::Shuffle {
+                name: *name,
+                shuffle_index: *shuffle_index,
+            },
+            VerifyFailure::Permutation { column, .. } => Rejection::Permutation(*column),
+        }
+    }
+}
+
+/// The outcomes of running a circuit on many witnesses.
+#[derive(Clone, Debug, Default)]
+pub struct FuzzReport {
+    runs: usize,
+    accepted: usize,
+    synthesis_failures: usize,
+    rejections: BTreeMap<Rejection, usize>,
+}
+
+impl FuzzReport {
+    /// Runs `circuit` with [`MockProver`](super::MockProver) and records the
+    /// outcome.
+    ///
+    /// A witness that fails several constraints, or the same constraint on several
+    /// rows, is counted once for each distinct [`Rejection`].
+    pub fn run<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+        &mut self,
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+    ) -> Outcome {
+        let outcome = Outcome::of(k, circuit, instance);
+
+        self.runs += 1;
+        match &outcome {
+            Outcome::Accepted => self.accepted += 1,
+            Outcome::Rejected(failures) => {
+                let mut rejections: Vec<Rejection> = failures.iter().map(Rejection::from).collect();
+                rejections.sort();
+                rejections.dedup();
+                for rejection in rejections {
+                    *self.rejections.entry(rejection).or_default() += 1;
+                }
+            }
+            Outcome::Failed(_) => self.synthesis_failures += 1,
+        }
+        outcome
+    }
+
+    /// Returns the number of witnesses that were run.
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Returns the number of witnesses that were accepted.
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    /// Returns the number of witnesses with which the circuit could not be
+    /// synthesized.
+    pub fn synthesis_failures(&self) -> usize {
+        self.synthesis_failures
+    }
+
+    /// Returns the number of witnesses rejected for each reason. Reasons that
+    /// never rejected a witness are absent.
+    pub fn rejections(&self) -> &BTreeMap<Rejection, usize> {
+        &self.rejections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::{FuzzReport, Rejection};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::metadata,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+        poly::Rotation,
+    };
+
+    /// Constrains `a` to be boolean and `b = a^2`.
+    struct BoolSquare {
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for BoolSquare {
+        type Config = (Column<Advice>, Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            BoolSquare {
+                a: Value::unknown(),
+                b: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("bool square", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                vec![
+                    (
+                        "bool",
+                        s.clone() * a.clone() * (Expression::Constant(Fp::one()) - a.clone()),
+                    ),
+                    ("square", s * (a.clone() * a - b)),
+                ]
+            });
+            (a, b, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, b, s): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "bool square",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_advice(a, 0, self.a)?;
+                    region.assign_advice(b, 0, self.b)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn records_rejections() {
+        let mut report = FuzzReport::default();
+        for (a, b) in [(1, 1), (0, 1), (2, 4), (2, 5)] {
+            let circuit = BoolSquare {
+                a: Value::known(Fp::from(a)),
+                b: Value::known(Fp::from(b)),
+            };
+            report.run(4, &circuit, vec![]);
+        }
+
+        let gate = metadata::Gate::from((0, "bool square"));
+        let boolean = Rejection::Constraint((gate.clone(), 0, "bool").into());
+        let square = Rejection::Constraint((gate, 1, "square").into());
+        assert_eq!(report.runs(), 4);
+        assert_eq!(report.accepted(), 1);
+        assert_eq!(report.synthesis_failures(), 0);
+        assert_eq!(
+            report.rejections().iter().collect::<Vec<_>>(),
+            vec![(&boolean, &2), (&square, &2)]
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_field() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        use super::ArbitraryField;
+
+        let bytes = [0xa5; 1024];
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..16 {
+            ArbitraryField::<Fp>::arbitrary(&mut u).unwrap();
+        }
+
+        // Exhausted input still produces a value.
+        let mut u = Unstructured::new(&[]);
+        assert!(ArbitraryField::<Fp>::arbitrary(&mut u).is_ok());
+    }
+}
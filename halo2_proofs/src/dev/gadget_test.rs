@@ -0,0 +1,227 @@
+//! Property-based testing of gadgets with [`MockProver`].
+
+use std::marker::PhantomData;
+
+use halo2curves::pasta::{pallas, vesta};
+use proptest::{
+    collection::vec,
+    prelude::*,
+    test_runner::{Config, TestRunner},
+};
+
+use super::{MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+/// A gadget that can be tested with [`prop_test_gadget`].
+///
+/// The harness builds a circuit that configures the gadget, synthesizes it on the
+/// inputs, and constrains the returned output cells to equal the expected outputs.
+/// The output cells must therefore be in columns with equality enabled.
+pub trait GadgetUnderTest<F: FieldExt> {
+    /// The configuration of the gadget.
+    type Config: Clone;
+
+    /// The number of field elements the gadget takes as input.
+    const NUM_INPUTS: usize;
+
+    /// Configures the gadget.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config;
+
+    /// Assigns the gadget's inputs and returns its output cells.
+    fn synthesize(
+        config: Self::Config,
+        layouter: impl Layouter<F>,
+        inputs: &[Value<F>],
+    ) -> Result<Vec<Cell>, Error>;
+}
+
+/// The circuit that wraps a gadget under test.
+struct GadgetCircuit<F: FieldExt, G: GadgetUnderTest<F>> {
+    inputs: Vec<Value<F>>,
+    num_outputs: usize,
+    _marker: PhantomData<G>,
+}
+
+impl<F: FieldExt, G: GadgetUnderTest<F>> Circuit<F> for GadgetCircuit<F, G> {
+    type Config = (G::Config, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        GadgetCircuit {
+            inputs: vec![Value::unknown(); self.inputs.len()],
+            num_outputs: self.num_outputs,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (G::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let outputs = G::synthesize(config, layouter.namespace(|| "gadget"), &self.inputs)?;
+        assert_eq!(
+            outputs.len(),
+            self.num_outputs,
+            "the gadget returned a different number of outputs than expected"
+        );
+        for (row, output) in outputs.iter().enumerate() {
+            layouter.constrain_instance(*output, instance, row);
+        }
+        Ok(())
+    }
+}
+
+/// Runs the gadget `G` on `inputs` in a circuit of `2^k` rows, and checks that
+/// the circuit is satisfied when its outputs are constrained to `expected(inputs)`.
+///
+/// Panics if the circuit cannot be synthesized.
+pub fn check_gadget<F: FieldExt, G: GadgetUnderTest<F>>(
+    k: u32,
+    inputs: &[F],
+    expected: impl Fn(&[F]) -> Vec<F>,
+) -> Result<(), Vec<VerifyFailure>> {
+    let outputs = expected(inputs);
+    let circuit = GadgetCircuit::<F, G> {
+        inputs: inputs.iter().copied().map(Value::known).collect(),
+        num_outputs: outputs.len(),
+        _marker: PhantomData,
+    };
+    let prover = MockProver::run(k, &circuit, vec![outputs])
+        .unwrap_or_else(|e| panic!("failed to synthesize the gadget: {:?}", e));
+    prover.verify()
+}
+
+/// Returns a strategy that generates field elements, choosing `0`, `1` and `-1`
+/// more often than uniformly random elements would.
+pub fn field_element<F: FieldExt>() -> impl Strategy<Value = F> {
+    let uniform = vec(any::<u8>(), 64).prop_map(|bytes| {
+        let bytes: [u8; 64] = bytes.try_into().unwrap();
+        F::from_bytes_wide(&bytes)
+    });
+    prop_oneof![
+        1 => Just(F::zero()),
+        1 => Just(F::one()),
+        1 => Just(-F::one()),
+        7 => uniform,
+    ]
+}
+
+/// Checks the gadget `G` with [`check_gadget`] on `cases` randomized inputs
+/// generated by [`field_element`].
+///
+/// Panics with the (shrunk) failing inputs if any check fails.
+pub fn prop_test_gadget<F: FieldExt, G: GadgetUnderTest<F>>(
+    k: u32,
+    cases: u32,
+    expected: impl Fn(&[F]) -> Vec<F>,
+) {
+    let mut runner = TestRunner::new(Config::with_cases(cases));
+    let result = runner.run(&vec(field_element::<F>(), G::NUM_INPUTS), |inputs| {
+        let result = check_gadget::<F, G>(k, &inputs, &expected);
+        prop_assert!(result.is_ok(), "{:?}", result);
+        Ok(())
+    });
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
+
+/// Checks the gadget `G` with [`prop_test_gadget`] over both Pasta base fields,
+/// with `expected_pallas` and `expected_vesta` computing the expected outputs in
+/// each field.
+pub fn prop_test_gadget_pasta<G>(
+    k: u32,
+    cases: u32,
+    expected_pallas: impl Fn(&[pallas::Base]) -> Vec<pallas::Base>,
+    expected_vesta: impl Fn(&[vesta::Base]) -> Vec<vesta::Base>,
+) where
+    G: GadgetUnderTest<pallas::Base> + GadgetUnderTest<vesta::Base>,
+{
+    prop_test_gadget::<pallas::Base, G>(k, cases, expected_pallas);
+    prop_test_gadget::<vesta::Base, G>(k, cases, expected_vesta);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_gadget, prop_test_gadget_pasta, GadgetUnderTest};
+    use crate::{
+        arithmetic::FieldExt,
+        circuit::{Cell, Layouter, Value},
+        plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+    use halo2curves::pasta::{Fp, Fq};
+
+    /// Computes `x^2 + y`.
+    struct SquarePlus;
+
+    impl<F: FieldExt> GadgetUnderTest<F> for SquarePlus {
+        type Config = (Column<Advice>, Column<Advice>, Column<Advice>, Selector);
+
+        const NUM_INPUTS: usize = 2;
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (x, y, z) = (
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            );
+            let s = meta.selector();
+            meta.enable_equality(z);
+            meta.create_gate("square plus", |meta| {
+                let x = meta.query_advice(x, Rotation::cur());
+                let y = meta.query_advice(y, Rotation::cur());
+                let z = meta.query_advice(z, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (x.clone() * x + y - z)]
+            });
+            (x, y, z, s)
+        }
+
+        fn synthesize(
+            (x, y, z, s): Self::Config,
+            mut layouter: impl Layouter<F>,
+            inputs: &[Value<F>],
+        ) -> Result<Vec<Cell>, Error> {
+            layouter.assign_region(
+                || "square plus",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_advice(x, 0, inputs[0])?;
+                    region.assign_advice(y, 0, inputs[1])?;
+                    let out = inputs[0] * inputs[0] + inputs[1];
+                    let out = region.assign_advice(z, 0, out)?;
+                    Ok(vec![*out.cell()])
+                },
+            )
+        }
+    }
+
+    fn square_plus<F: FieldExt>(inputs: &[F]) -> Vec<F> {
+        vec![inputs[0].square() + inputs[1]]
+    }
+
+    #[test]
+    fn gadget_proptest() {
+        prop_test_gadget_pasta::<SquarePlus>(4, 16, square_plus::<Fp>, square_plus::<Fq>);
+
+        // A wrong reference implementation is caught.
+        assert!(
+            check_gadget::<Fp, SquarePlus>(4, &[Fp::from(3), Fp::one()], |inputs| {
+                vec![inputs[0].square()]
+            })
+            .is_err()
+        );
+    }
+}
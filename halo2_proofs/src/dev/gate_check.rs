@@ -0,0 +1,190 @@
+//! Developer tools for smoke-testing the algebra of individual gates.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use ff::PrimeField;
+use rand_core::RngCore;
+
+use crate::plonk::{Circuit, ConstraintSystem, Expression};
+
+/// The number of assignments tried when searching for a satisfying assignment.
+const SEARCH_BUDGET: usize = 1 << 12;
+
+/// The number of uniformly random assignments each gate must reject.
+const RANDOM_TRIALS: usize = 16;
+
+/// The outcome of checking a single gate with [`GateCheck::run`].
+#[derive(Debug)]
+pub struct GateCheck<F: PrimeField> {
+    name: &'static str,
+    satisfying_assignment: Option<Vec<(String, F)>>,
+    accepts_random: bool,
+}
+
+impl<F: PrimeField> GateCheck<F> {
+    /// Checks each gate of the circuit in isolation, with all of its selectors
+    /// enabled.
+    ///
+    /// - Completeness: searches for an assignment of small values (`0`, `1`, `2` and
+    ///   `-1`) to the gate's queried cells, not all of them zero, that satisfies
+    ///   every constraint of the gate.
+    /// - Soundness smoke test: confirms that uniformly random assignments violate
+    ///   at least one constraint of the gate.
+    ///
+    /// Both checks are heuristics that catch typos such as wrong signs or
+    /// rotations; they prove nothing about a gate that passes. Challenges are
+    /// replaced with random values.
+    pub fn run<C: Circuit<F>>(mut rng: impl RngCore) -> Vec<Self> {
+        let mut cs = ConstraintSystem::default();
+        let _ = C::configure(&mut cs);
+
+        let challenges: Vec<F> = (0..cs.num_challenges)
+            .map(|_| F::random(&mut rng))
+            .collect();
+
+        cs.gates
+            .iter()
+            .map(|gate| {
+                let polys = gate.polynomials();
+                let cells: Vec<String> = polys
+                    .iter()
+                    .flat_map(queried_cells)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+
+                let satisfied = |values: &[F]| {
+                    let assignment: BTreeMap<&str, F> = cells
+                        .iter()
+                        .map(|cell| cell.as_str())
+                        .zip(values.iter().copied())
+                        .collect();
+                    polys
+                        .iter()
+                        .all(|poly| bool::from(evaluate(poly, &assignment, &challenges).is_zero()))
+                };
+
+                // Enumerate every assignment of small values if there are few
+                // enough of them, and sample them randomly otherwise.
+                let small = [F::zero(), F::one(), F::from(2), -F::one()];
+                let exhaustive = small
+                    .len()
+                    .checked_pow(cells.len() as u32)
+                    .filter(|&total| total <= SEARCH_BUDGET);
+                let satisfying_assignment = (0..exhaustive.unwrap_or(SEARCH_BUDGET))
+                    .map(|attempt| -> Vec<F> {
+                        let mut index = attempt;
+                        cells
+                            .iter()
+                            .map(|_| {
+                                if exhaustive.is_none() {
+                                    index = rng.next_u32() as usize;
+                                }
+                                let value = small[index % small.len()];
+                                index /= small.len();
+                                value
+                            })
+                            .collect()
+                    })
+                    .find(|values| {
+                        values.iter().any(|value| !bool::from(value.is_zero())) && satisfied(values)
+                    })
+                    .map(|values| cells.iter().cloned().zip(values).collect());
+
+                let accepts_random = (0..RANDOM_TRIALS).any(|_| {
+                    let values: Vec<F> = cells.iter().map(|_| F::random(&mut rng)).collect();
+                    satisfied(&values)
+                });
+
+                GateCheck {
+                    name: gate.name(),
+                    satisfying_assignment,
+                    accepts_random,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the name of the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns a non-zero assignment of small values to the gate's queried cells
+    /// that satisfies the gate, if one was found.
+    pub fn satisfying_assignment(&self) -> Option<&[(String, F)]> {
+        self.satisfying_assignment.as_deref()
+    }
+
+    /// Returns `true` if a uniformly random assignment satisfied the gate, which
+    /// usually means that the gate constrains nothing.
+    pub fn accepts_random(&self) -> bool {
+        self.accepts_random
+    }
+
+    /// Returns `true` if the gate passed both checks.
+    pub fn passed(&self) -> bool {
+        self.satisfying_assignment.is_some() && !self.accepts_random
+    }
+}
+
+impl<F: PrimeField> fmt::Display for GateCheck<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}: ", self.name)?;
+        match (&self.satisfying_assignment, self.accepts_random) {
+            (Some(_), false) => write!(f, "ok"),
+            (None, false) => write!(f, "no satisfying assignment of small values found"),
+            (Some(_), true) => write!(f, "satisfied by a random assignment"),
+            (None, true) => write!(
+                f,
+                "satisfied by a random assignment, but no assignment of small values found"
+            ),
+        }
+    }
+}
+
+/// Names the cells queried by an expression in the same notation as
+/// [`CircuitGates`](super::CircuitGates).
+fn queried_cells<F: PrimeField>(expression: &Expression<F>) -> Vec<String> {
+    expression.evaluate(
+        &|_| vec![],
+        &|_| vec![],
+        &|query| vec![format!("F{}@{}", query.column_index, query.rotation.0)],
+        &|query| vec![format!("A{}@{}", query.column_index, query.rotation.0)],
+        &|query| vec![format!("I{}@{}", query.column_index, query.rotation.0)],
+        &|_| vec![],
+        &|a| a,
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+/// Evaluates an expression with every selector enabled.
+fn evaluate<F: PrimeField>(
+    expression: &Expression<F>,
+    assignment: &BTreeMap<&str, F>,
+    challenges: &[F],
+) -> F {
+    expression.evaluate(
+        &|scalar| scalar,
+        &|_| F::one(),
+        &|query| assignment[format!("F{}@{}", query.column_index, query.rotation.0).as_str()],
+        &|query| assignment[format!("A{}@{}", query.column_index, query.rotation.0).as_str()],
+        &|query| assignment[format!("I{}@{}", query.column_index, query.rotation.0).as_str()],
+        &|challenge| challenges[challenge.index()],
+        &|a| -a,
+        &|a, b| a + b,
+        &|a, b| a * b,
+        &|a, scalar| a * scalar,
+    )
+}
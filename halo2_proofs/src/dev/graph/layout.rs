@@ -96,7 +96,7 @@ impl CircuitLayout {
 
         let n = 1 << k;
         // Collect the layout details.
-        let mut cs = ConstraintSystem::default();
+        let mut cs = ConstraintSystem::with_k(k);
         let config = ConcreteCircuit::configure(&mut cs);
         let mut layout = Layout::new(k, n, cs.num_selectors);
         ConcreteCircuit::FloorPlanner::synthesize(
@@ -106,7 +106,7 @@ impl CircuitLayout {
             cs.constants.clone(),
         )
         .unwrap();
-        let (cs, selector_polys) = cs.compress_selectors(layout.selectors);
+        let (cs, selector_polys) = cs.compress_selectors(layout.selectors).unwrap();
         let non_selector_fixed_columns = cs.num_fixed_columns - selector_polys.len();
 
         // Figure out what order to render the columns in.
@@ -171,7 +171,7 @@ impl CircuitLayout {
         }
 
         // Mark the unusable rows of the circuit.
-        let usable_rows = n - (cs.blinding_factors() + 1);
+        let usable_rows = n - cs.unusable_rows();
         if view_bottom > usable_rows {
             root.draw(&Rectangle::new(
                 [(0, usable_rows), (total_columns, view_bottom)],
@@ -425,7 +425,7 @@ impl<F: Field> Assignment<F> for Layout {
         if let Some(cell) = self.selectors[selector.0].get_mut(row) {
             *cell = true;
         } else {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         self.update((*selector).into(), row);
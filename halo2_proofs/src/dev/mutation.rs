@@ -0,0 +1,209 @@
+//! Developer tools for finding under-constrained advice cells by mutating a
+//! satisfied witness.
+
+use std::{collections::BTreeSet, fmt, sync::Arc};
+
+use ff::Field;
+use halo2curves::FieldExt;
+use rand_core::RngCore;
+
+use super::{metadata, AdviceCellValue, FailureLocation, MockProver, VerifyFailure};
+use crate::plonk::{Any, Assigned};
+
+/// A change to a single advice cell of a satisfied circuit that still satisfies
+/// it, as returned by [`MockProver::surviving_mutations`].
+#[derive(Debug, PartialEq)]
+pub struct SurvivingMutation<F: Field> {
+    /// The column of the mutated cell.
+    pub column: metadata::Column,
+    /// The location of the mutated cell.
+    pub location: FailureLocation,
+    /// The value that the circuit assigned to the cell.
+    pub original: F,
+    /// The value that the cell was changed to.
+    pub mutated: F,
+}
+
+impl<F: Field> fmt::Display for SurvivingMutation<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cell {} {} can be changed from {:?} to {:?} and still verify",
+            self.column, self.location, self.original, self.mutated
+        )
+    }
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Changes each advice cell assigned within a region, one at a time, and
+    /// returns every change that still satisfies the circuit.
+    ///
+    /// Each cell with value `x` is set to `x + 1`, `-x`, zero and a random value.
+    /// A surviving mutation means that the cell is not fully constrained by the
+    /// gates, lookups and copy constraints of the circuit, which is usually a
+    /// soundness bug. The circuit is verified once per mutation, so this is only
+    /// practical for small circuits.
+    ///
+    /// Returns the failures of the circuit if it is not satisfied to begin with.
+    /// The prover is restored to its original state before returning.
+    pub fn surviving_mutations(
+        &mut self,
+        mut rng: impl RngCore,
+    ) -> Result<Vec<SurvivingMutation<F>>, Vec<VerifyFailure>> {
+        self.verify()?;
+
+        let cells: Vec<_> = self
+            .regions
+            .iter()
+            .enumerate()
+            .flat_map(|(index, region)| {
+                let start = region.rows.map_or(0, |(start, _)| start);
+                region
+                    .cells
+                    .keys()
+                    .filter(|(column, _)| matches!(column.column_type(), Any::Advice(_)))
+                    .map(move |(column, row)| (index, start, *column, *row))
+                    .collect::<BTreeSet<_>>()
+            })
+            .collect();
+
+        let mut survivors = vec![];
+        for (region, start, column, row) in cells {
+            let original = match &self.advice[column.index()][row] {
+                AdviceCellValue::Assigned(value) => value.as_ref().evaluate(),
+                AdviceCellValue::Poison(_) => continue,
+            };
+
+            let mut candidates = vec![];
+            for mutated in [
+                original + F::one(),
+                -original,
+                F::zero(),
+                F::random(&mut rng),
+            ] {
+                if mutated != original && !candidates.contains(&mutated) {
+                    candidates.push(mutated);
+                }
+            }
+            for mutated in candidates {
+                let assigned = std::mem::replace(
+                    &mut self.advice[column.index()][row],
+                    AdviceCellValue::Assigned(Arc::new(Assigned::Trivial(mutated))),
+                );
+                let verified = self.verify().is_ok();
+                self.advice[column.index()][row] = assigned;
+
+                if verified {
+                    survivors.push(SurvivingMutation {
+                        column: column.into(),
+                        location: FailureLocation::InRegion {
+                            region: (region, self.regions[region].name.clone()).into(),
+                            offset: row - start,
+                        },
+                        original,
+                        mutated,
+                    });
+                }
+            }
+        }
+
+        Ok(survivors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+    use rand_core::OsRng;
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::{metadata, FailureLocation, MockProver},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone, Copy)]
+    struct Config {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        unconstrained: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Constrains `b = 2a`, and assigns a cell that no constraint uses.
+    struct Double {
+        a: u64,
+        b: u64,
+    }
+
+    impl Circuit<Fp> for Double {
+        type Config = Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Double { a: 0, b: 0 }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let unconstrained = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("double", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                vec![s * (a.clone() + a - b)]
+            });
+            Config {
+                a,
+                b,
+                unconstrained,
+                s,
+            }
+        }
+
+        fn synthesize(&self, config: Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            layouter.assign_region(
+                || "double",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(config.a, 0, Value::known(Fp::from(self.a)))?;
+                    region.assign_advice(config.b, 0, Value::known(Fp::from(self.b)))?;
+                    region.assign_advice(config.unconstrained, 0, Value::known(Fp::from(5)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn finds_unconstrained_cell() {
+        let mut prover = MockProver::run(4, &Double { a: 3, b: 6 }, vec![]).unwrap();
+        let survivors = prover.surviving_mutations(OsRng).unwrap();
+
+        // Every mutation of the unconstrained cell survives, and no other.
+        assert_eq!(survivors.len(), 4);
+        for survivor in survivors {
+            assert_eq!(survivor.column, metadata::Column::from((Any::advice(), 2)));
+            assert_eq!(
+                survivor.location,
+                FailureLocation::InRegion {
+                    region: (0, "double").into(),
+                    offset: 0,
+                }
+            );
+            assert_eq!(survivor.original, Fp::from(5));
+        }
+
+        // The prover is left as it was.
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn unsatisfied_circuit() {
+        let mut prover = MockProver::run(4, &Double { a: 3, b: 7 }, vec![]).unwrap();
+        assert!(prover.surviving_mutations(OsRng).is_err());
+    }
+}
@@ -0,0 +1,502 @@
+//! Developer tools for finding how much of a circuit's area its regions use.
+
+use std::{collections::HashSet, fmt, ops::Range};
+
+use ff::Field;
+
+use crate::{
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+
+/// The rows and cells used by a single region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionOccupancy {
+    name: String,
+    rows: Option<Range<usize>>,
+    columns: usize,
+    cells: usize,
+}
+
+impl RegionOccupancy {
+    /// Returns the name of the region.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the rows that the region spans, from the first to the last row in
+    /// which it assigns a cell or enables a selector, or `None` if it does
+    /// neither.
+    pub fn rows(&self) -> Option<Range<usize>> {
+        self.rows.clone()
+    }
+
+    /// Returns the number of rows that the region spans.
+    pub fn num_rows(&self) -> usize {
+        self.rows.as_ref().map_or(0, |rows| rows.len())
+    }
+
+    /// Returns the number of advice and fixed columns in which the region assigns
+    /// cells.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of advice and fixed cells assigned by the region.
+    pub fn cells(&self) -> usize {
+        self.cells
+    }
+}
+
+/// The cells assigned in a single column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnOccupancy {
+    column_type: Any,
+    index: usize,
+    assigned: usize,
+    available: usize,
+}
+
+impl ColumnOccupancy {
+    /// Returns the type of the column.
+    pub fn column_type(&self) -> Any {
+        self.column_type
+    }
+
+    /// Returns the index of the column among the columns of its type.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of distinct cells assigned in the column, including
+    /// any beyond its usable rows.
+    pub fn assigned(&self) -> usize {
+        self.assigned
+    }
+
+    /// Returns the number of usable rows of the column.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+
+    /// Returns the fraction of the usable rows of the column that are assigned.
+    pub fn utilization(&self) -> f64 {
+        self.assigned as f64 / self.available as f64
+    }
+}
+
+/// A report on the rows used by each region of a circuit and the cells assigned in
+/// each of its columns.
+///
+/// Unlike [`MockProver`](super::MockProver), the measurement succeeds for
+/// circuits that need more rows than are usable at `k`, so it can show which
+/// regions to shrink for the circuit to fit. Selectors and instance columns are
+/// not counted as cells.
+///
+/// # Examples
+///
+/// ```
+/// use halo2_proofs::{
+///     circuit::{Layouter, SimpleFloorPlanner, Value},
+///     dev::CircuitOccupancy,
+///     plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+/// };
+/// use halo2curves::pasta::Fp;
+///
+/// struct MyCircuit;
+///
+/// impl Circuit<Fp> for MyCircuit {
+///     type Config = [Column<Advice>; 2];
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         MyCircuit
+///     }
+///
+///     fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+///         [meta.advice_column(), meta.advice_column()]
+///     }
+///
+///     fn synthesize(&self, [a, b]: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+///         layouter.assign_region(
+///             || "column a",
+///             |mut region| {
+///                 for row in 0..4 {
+///                     region.assign_advice(a, row, Value::known(Fp::one()))?;
+///                 }
+///                 Ok(())
+///             },
+///         )?;
+///         layouter.assign_region(
+///             || "column b",
+///             |mut region| {
+///                 region.assign_advice(b, 0, Value::known(Fp::one()))?;
+///                 Ok(())
+///             },
+///         )
+///     }
+/// }
+///
+/// let occupancy = CircuitOccupancy::measure(4, &MyCircuit).unwrap();
+/// assert_eq!(
+///     format!("{}", occupancy),
+///     r#####"Rows used: 4 of 10 usable
+/// Regions:
+/// - column a: rows 0..4 (4 rows), 4 cells in 1 columns
+/// - column b: rows 0..1 (1 rows), 1 cells in 1 columns
+/// Columns:
+/// - Advice 0: 4 of 10 cells (40.0%)
+/// - Advice 1: 1 of 10 cells (10.0%)
+/// Utilization: 25.0%
+/// "#####,
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct CircuitOccupancy {
+    usable_rows: usize,
+    rows_used: usize,
+    regions: Vec<RegionOccupancy>,
+    columns: Vec<ColumnOccupancy>,
+}
+
+struct Region {
+    name: String,
+    rows: Option<(usize, usize)>,
+    cells: HashSet<(Column<Any>, usize)>,
+}
+
+impl Region {
+    fn update_extent(&mut self, row: usize) {
+        let (start, end) = self.rows.unwrap_or((row, row));
+        self.rows = Some((start.min(row), end.max(row)));
+    }
+}
+
+struct Assembly {
+    usable_rows: usize,
+    regions: Vec<Region>,
+    current_region: Option<Region>,
+    cells: HashSet<(Column<Any>, usize)>,
+    rows_used: usize,
+}
+
+impl Assembly {
+    fn assign(&mut self, column: Column<Any>, row: usize) {
+        if let Some(region) = self.current_region.as_mut() {
+            region.update_extent(row);
+            region.cells.insert((column, row));
+        }
+        self.cells.insert((column, row));
+        self.rows_used = self.rows_used.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for Assembly {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        assert!(self.current_region.is_none());
+        self.current_region = Some(Region {
+            name: name().into(),
+            rows: None,
+            cells: HashSet::default(),
+        });
+    }
+
+    fn exit_region(&mut self) {
+        self.regions.push(self.current_region.take().unwrap());
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if let Some(region) = self.current_region.as_mut() {
+            region.update_extent(row);
+        }
+        self.rows_used = self.rows_used.max(row + 1);
+
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<'r, 'v>(
+        &'r mut self,
+        column: Column<Advice>,
+        row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<Value<&'v Assigned<F>>, Error> {
+        self.assign(column.into(), row);
+        Ok(Value::unknown())
+    }
+
+    fn assign_fixed(&mut self, column: Column<Fixed>, row: usize, _: Assigned<F>) {
+        self.assign(column.into(), row);
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) {
+        // Do nothing; copying doesn't assign cells.
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        for row in from_row..self.usable_rows {
+            self.assign(column.into(), row);
+        }
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+impl CircuitOccupancy {
+    /// Lays out the circuit with parameter constant `k` and measures the area
+    /// that it uses.
+    pub fn measure<F: Field, ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::with_k(k);
+        let config = ConcreteCircuit::configure(&mut cs);
+        let usable_rows = cs.usable_rows(k).len();
+
+        let mut assembly = Assembly {
+            usable_rows,
+            regions: vec![],
+            current_region: None,
+            cells: HashSet::default(),
+            rows_used: 0,
+        };
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut assembly,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )?;
+
+        let regions = assembly
+            .regions
+            .into_iter()
+            .map(|region| RegionOccupancy {
+                rows: region.rows.map(|(start, end)| start..end + 1),
+                columns: region
+                    .cells
+                    .iter()
+                    .map(|(column, _)| *column)
+                    .collect::<HashSet<_>>()
+                    .len(),
+                cells: region.cells.len(),
+                name: region.name,
+            })
+            .collect();
+
+        let columns = cs
+            .advice_column_phase
+            .iter()
+            .map(|phase| Any::Advice(Advice { phase: *phase }))
+            .enumerate()
+            .chain((0..cs.num_fixed_columns).map(|index| (index, Any::Fixed)))
+            .map(|(index, column_type)| ColumnOccupancy {
+                column_type,
+                index,
+                assigned: assembly
+                    .cells
+                    .iter()
+                    .filter(|(c, _)| *c.column_type() == column_type && c.index() == index)
+                    .count(),
+                available: usable_rows,
+            })
+            .collect();
+
+        Ok(CircuitOccupancy {
+            usable_rows,
+            rows_used: assembly.rows_used,
+            regions,
+            columns,
+        })
+    }
+
+    /// Returns the number of usable rows at the measured `k`.
+    pub fn usable_rows(&self) -> usize {
+        self.usable_rows
+    }
+
+    /// Returns the number of rows up to and including the last row in which a cell
+    /// is assigned or a selector enabled. The circuit fits if this is at most
+    /// [`CircuitOccupancy::usable_rows`].
+    pub fn rows_used(&self) -> usize {
+        self.rows_used
+    }
+
+    /// Returns the occupancy of each region, in the order in which the regions
+    /// were assigned.
+    pub fn regions(&self) -> &[RegionOccupancy] {
+        &self.regions
+    }
+
+    /// Returns the occupancy of each advice column, then of each fixed column.
+    pub fn columns(&self) -> &[ColumnOccupancy] {
+        &self.columns
+    }
+
+    /// Returns the fraction of the usable cells of the advice and fixed columns
+    /// that are assigned.
+    pub fn utilization(&self) -> f64 {
+        let assigned: usize = self.columns.iter().map(|c| c.assigned).sum();
+        let available: usize = self.columns.iter().map(|c| c.available).sum();
+        assigned as f64 / available as f64
+    }
+}
+
+impl fmt::Display for CircuitOccupancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(
+            f,
+            "Rows used: {} of {} usable",
+            self.rows_used, self.usable_rows
+        )?;
+        writeln!(f, "Regions:")?;
+        for region in self.regions.iter() {
+            write!(f, "- {}: ", region.name)?;
+            match &region.rows {
+                Some(rows) => write!(f, "rows {:?} ({} rows)", rows, rows.len())?,
+                None => write!(f, "empty")?,
+            }
+            writeln!(f, ", {} cells in {} columns", region.cells, region.columns)?;
+        }
+        writeln!(f, "Columns:")?;
+        for column in self.columns.iter() {
+            let column_type = match column.column_type {
+                Any::Advice(_) => "Advice",
+                Any::Fixed => "Fixed",
+                Any::Instance => "Instance",
+            };
+            writeln!(
+                f,
+                "- {} {}: {} of {} cells ({:.1}%)",
+                column_type,
+                column.index,
+                column.assigned,
+                column.available,
+                100.0 * column.utilization()
+            )?;
+        }
+        writeln!(f, "Utilization: {:.1}%", 100.0 * self.utilization())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::CircuitOccupancy;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+    };
+
+    #[derive(Clone, Copy)]
+    struct Config {
+        a: Column<Advice>,
+        f: Column<Fixed>,
+        s: Selector,
+    }
+
+    struct TooTall;
+
+    impl Circuit<Fp> for TooTall {
+        type Config = Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            TooTall
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Config {
+            Config {
+                a: meta.advice_column(),
+                f: meta.fixed_column(),
+                s: meta.selector(),
+            }
+        }
+
+        fn synthesize(&self, config: Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            layouter.assign_region(
+                || "tall",
+                |mut region| {
+                    for row in 0..20 {
+                        region.assign_advice(config.a, row, Value::known(Fp::one()))?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "short",
+                |mut region| {
+                    config.s.enable(&mut region, 2)?;
+                    region.assign_fixed(config.f, 0, Fp::one());
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(|| "empty", |_| Ok(()))
+        }
+    }
+
+    #[test]
+    fn circuit_that_does_not_fit() {
+        let occupancy = CircuitOccupancy::measure(4, &TooTall).unwrap();
+
+        // The second region uses other columns, so it is laid out beside the
+        // first, and the selector extends it past its only cell.
+        assert!(occupancy.rows_used() > occupancy.usable_rows());
+        assert_eq!(occupancy.rows_used(), 20);
+        let regions = occupancy.regions();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].name(), "tall");
+        assert_eq!(regions[0].rows(), Some(0..20));
+        assert_eq!(regions[0].cells(), 20);
+        assert_eq!(regions[1].rows(), Some(0..3));
+        assert_eq!(regions[1].num_rows(), 3);
+        assert_eq!(regions[1].cells(), 1);
+        assert_eq!(regions[2].rows(), None);
+        assert_eq!(regions[2].num_rows(), 0);
+
+        let columns = occupancy.columns();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].column_type(), Any::advice());
+        assert_eq!(columns[0].assigned(), 20);
+        assert_eq!(columns[1].column_type(), Any::Fixed);
+        assert_eq!(columns[1].assigned(), 1);
+        assert_eq!(
+            occupancy.utilization(),
+            21.0 / (2 * occupancy.usable_rows()) as f64
+        );
+    }
+}
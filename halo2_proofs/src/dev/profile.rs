@@ -0,0 +1,300 @@
+//! Developer tools for finding which gates dominate the cost of a circuit.
+
+use std::{collections::BTreeSet, fmt};
+
+use ff::Field;
+
+use crate::plonk::{Circuit, ConstraintSystem, Expression};
+
+/// The estimated cost of a single gate.
+#[derive(Debug)]
+pub struct GateProfile {
+    name: &'static str,
+    constraints: usize,
+    degree: usize,
+    rotations: BTreeSet<i32>,
+    new_rotations: BTreeSet<i32>,
+    negations: usize,
+    additions: usize,
+    multiplications: usize,
+    bounds_degree: bool,
+}
+
+impl GateProfile {
+    /// Returns the name of the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the number of constraint polynomials in the gate.
+    pub fn constraints(&self) -> usize {
+        self.constraints
+    }
+
+    /// Returns the maximum degree of the gate's constraint polynomials.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Returns the rotations at which the gate queries cells.
+    pub fn rotations(&self) -> &BTreeSet<i32> {
+        &self.rotations
+    }
+
+    /// Returns the rotations that are queried by this gate and by no other gate or
+    /// lookup. Each of these adds evaluation points to the multiopening argument.
+    pub fn new_rotations(&self) -> &BTreeSet<i32> {
+        &self.new_rotations
+    }
+
+    /// Returns the estimated number of field operations (negations, additions and
+    /// multiplications) the prover spends evaluating this gate at a single row of the
+    /// extended domain.
+    pub fn cost_per_row(&self) -> usize {
+        self.negations + self.additions + self.multiplications
+    }
+
+    /// Returns `true` if the gate has the maximum degree of the circuit, and thus
+    /// determines the size of the extended domain and the quotient polynomial.
+    pub fn bounds_degree(&self) -> bool {
+        self.bounds_degree
+    }
+}
+
+/// A report on the gates within a circuit, ranked by their estimated contribution
+/// to prover time.
+///
+/// The profile is collected from the circuit's configuration alone, before
+/// selectors are combined into fixed columns.
+///
+/// # Examples
+///
+/// ```
+/// use ff::Field;
+/// use halo2_proofs::{
+///     circuit::{Layouter, SimpleFloorPlanner},
+///     dev::CircuitProfile,
+///     plonk::{Circuit, ConstraintSystem, Error},
+///     poly::Rotation,
+/// };
+/// use halo2curves::pasta::pallas;
+///
+/// #[derive(Copy, Clone)]
+/// struct MyConfig {}
+///
+/// #[derive(Clone, Default)]
+/// struct MyCircuit {}
+///
+/// impl<F: Field> Circuit<F> for MyCircuit {
+///     type Config = MyConfig;
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         Self::default()
+///     }
+///
+///     fn configure(meta: &mut ConstraintSystem<F>) -> MyConfig {
+///         let a = meta.advice_column();
+///         let b = meta.advice_column();
+///         let c = meta.advice_column();
+///         let s = meta.selector();
+///
+///         meta.create_gate("R1CS constraint", |meta| {
+///             let a = meta.query_advice(a, Rotation::cur());
+///             let b = meta.query_advice(b, Rotation::cur());
+///             let c = meta.query_advice(c, Rotation::cur());
+///             let s = meta.query_selector(s);
+///
+///             Some(("R1CS", s * (a * b - c)))
+///         });
+///
+///         meta.create_gate("running sum", |meta| {
+///             let a_cur = meta.query_advice(a, Rotation::cur());
+///             let a_next = meta.query_advice(a, Rotation::next());
+///             let s = meta.query_selector(s);
+///
+///             Some(("sum", s * (a_next - a_cur)))
+///         });
+///
+///         MyConfig {}
+///     }
+///
+///     fn synthesize(&self, _: MyConfig, _: impl Layouter<F>) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let profile = CircuitProfile::collect::<pallas::Base, MyCircuit>();
+/// assert_eq!(
+///     format!("{}", profile),
+///     r#####"Maximum degree: 3
+/// 1. R1CS constraint: degree 3 (maximum), 1 constraints, rotations [0], ~4 ops/row
+/// 2. running sum: degree 2, 1 constraints, rotations [0, 1] (new: [1]), ~3 ops/row
+/// "#####,
+/// );
+/// ```
+#[derive(Debug)]
+pub struct CircuitProfile {
+    degree: usize,
+    gates: Vec<GateProfile>,
+}
+
+impl CircuitProfile {
+    /// Collects the gate profile of the circuit.
+    pub fn collect<F: Field, C: Circuit<F>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        let _ = C::configure(&mut cs);
+        let degree = cs.degree();
+
+        let gate_rotations: Vec<BTreeSet<i32>> = cs
+            .gates
+            .iter()
+            .map(|gate| {
+                gate.queried_cells()
+                    .iter()
+                    .map(|cell| cell.rotation.0)
+                    .collect()
+            })
+            .collect();
+        let lookup_rotations: BTreeSet<i32> = cs
+            .lookups
+            .iter()
+            .flat_map(|lookup| {
+                lookup
+                    .input_expressions
+                    .iter()
+                    .chain(lookup.table_expressions.iter())
+            })
+            .flat_map(rotations)
+            .collect();
+
+        let mut gates: Vec<_> = cs
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(i, gate)| {
+                let rotations = gate_rotations[i].clone();
+                let new_rotations = rotations
+                    .iter()
+                    .filter(|rotation| {
+                        !lookup_rotations.contains(rotation)
+                            && !gate_rotations
+                                .iter()
+                                .enumerate()
+                                .any(|(j, other)| j != i && other.contains(rotation))
+                    })
+                    .copied()
+                    .collect();
+
+                let (negations, additions, multiplications) = gate
+                    .polynomials()
+                    .iter()
+                    .map(operations)
+                    .fold((0, 0, 0), |(acc_n, acc_a, acc_m), (n, a, m)| {
+                        (acc_n + n, acc_a + a, acc_m + m)
+                    });
+                let gate_degree = gate
+                    .polynomials()
+                    .iter()
+                    .map(|poly| poly.degree())
+                    .max()
+                    .unwrap_or(0);
+
+                GateProfile {
+                    name: gate.name(),
+                    constraints: gate.polynomials().len(),
+                    degree: gate_degree,
+                    rotations,
+                    new_rotations,
+                    negations,
+                    additions,
+                    multiplications,
+                    bounds_degree: gate_degree == degree,
+                }
+            })
+            .collect();
+
+        // Rank the most expensive gates first; the sort is stable, so gates of
+        // equal cost keep their configuration order.
+        gates.sort_by(|a, b| (b.cost_per_row(), b.degree).cmp(&(a.cost_per_row(), a.degree)));
+
+        CircuitProfile { degree, gates }
+    }
+
+    /// Returns the maximum degree of the circuit, including the lookup and
+    /// permutation arguments.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Returns the profiles of the circuit's gates, most expensive first.
+    pub fn gates(&self) -> &[GateProfile] {
+        &self.gates
+    }
+}
+
+impl fmt::Display for CircuitProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        writeln!(f, "Maximum degree: {}", self.degree)?;
+        for (i, gate) in self.gates.iter().enumerate() {
+            write!(f, "{}. {}: degree {}", i + 1, gate.name, gate.degree)?;
+            if gate.bounds_degree {
+                write!(f, " (maximum)")?;
+            }
+            write!(
+                f,
+                ", {} constraints, rotations {:?}",
+                gate.constraints,
+                gate.rotations.iter().collect::<Vec<_>>()
+            )?;
+            if !gate.new_rotations.is_empty() {
+                write!(
+                    f,
+                    " (new: {:?})",
+                    gate.new_rotations.iter().collect::<Vec<_>>()
+                )?;
+            }
+            writeln!(f, ", ~{} ops/row", gate.cost_per_row())?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the rotations at which an expression queries cells.
+fn rotations<F: Field>(expression: &Expression<F>) -> BTreeSet<i32> {
+    expression.evaluate(
+        &|_| BTreeSet::new(),
+        &|_| BTreeSet::new(),
+        &|query| Some(query.rotation.0).into_iter().collect(),
+        &|query| Some(query.rotation.0).into_iter().collect(),
+        &|query| Some(query.rotation.0).into_iter().collect(),
+        &|_| BTreeSet::new(),
+        &|a| a,
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+/// Counts the (negations, additions, multiplications) needed to evaluate an
+/// expression once.
+fn operations<F: Field>(expression: &Expression<F>) -> (usize, usize, usize) {
+    expression.evaluate(
+        &|_| (0, 0, 0),
+        &|_| (0, 0, 0),
+        &|_| (0, 0, 0),
+        &|_| (0, 0, 0),
+        &|_| (0, 0, 0),
+        &|_| (0, 0, 0),
+        &|(a_n, a_a, a_m)| (a_n + 1, a_a, a_m),
+        &|(a_n, a_a, a_m), (b_n, b_a, b_m)| (a_n + b_n, a_a + b_a + 1, a_m + b_m),
+        &|(a_n, a_a, a_m), (b_n, b_a, b_m)| (a_n + b_n, a_a + b_a, a_m + b_m + 1),
+        &|(a_n, a_a, a_m), _| (a_n, a_a, a_m + 1),
+    )
+}
@@ -0,0 +1,575 @@
+//! C bindings for creating and verifying proofs of described circuits.
+//!
+//! Circuits are passed as [`CircuitDescription`]s, so the bindings can prove any
+//! circuit whose advice columns are all in the first phase, without the
+//! circuit's Rust type. Proofs use KZG commitments on BN254 with the SHPLONK
+//! multiopen argument and a BLAKE2b transcript.
+//!
+//! The functions are exported from any `cdylib` or `staticlib` that links this
+//! crate with the `ffi` feature, for example a crate containing
+//! `pub use halo2_proofs::ffi::*;`. They are declared for C in
+//! `include/halo2_proofs.h`.
+//!
+//! # Encodings
+//!
+//! - Parameters are encoded as by [`Params::write`](crate::poly::commitment::Params::write).
+//! - Circuits are encoded as by [`CircuitDescription::to_bytes`].
+//! - Witnesses and instances are encoded as columns of field elements: the
+//!   number of columns as a big-endian `u32`, then for each column the number of
+//!   values as a big-endian `u32`, followed by each value in its 32-byte
+//!   little-endian representation.
+//!
+//! # Handles and errors
+//!
+//! Parameters and keys are returned as opaque handles, which must be released
+//! with the matching `_free` function. Every function returns a
+//! [`Halo2Status`], and writes its results through its `out` pointers only when
+//! it returns [`Halo2Status::Ok`]. Panics are caught and reported as
+//! [`Halo2Status::Panic`] rather than unwinding into the caller.
+//!
+//! Catching panics requires them to unwind, so the library exporting the
+//! bindings must be built with `panic = "unwind"`, the default. With
+//! `panic = "abort"`, which the release profile of this workspace sets, a panic
+//! aborts the calling process instead of being reported.
+//!
+//! Byte buffers are checked before they are read: a null pointer is accepted
+//! only with a length of zero, and a length over `isize::MAX` is rejected as
+//! [`Halo2Status::InvalidInput`].
+
+use std::{
+    io,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+
+use crate::{
+    helpers::SerdePrimeField,
+    plonk::{
+        create_proof, keygen_pk_from_description, keygen_vk_from_description, verify_proof,
+        CircuitDescription, DescribedWitness, ProvingKey, VerifyingKey,
+    },
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+
+/// The result of a call to one of the bindings.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Halo2Status {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An encoded input was malformed, or a witness or instance did not have the
+    /// shape of the circuit.
+    InvalidInput = 2,
+    /// Key generation or proving failed, for example because the parameters are
+    /// for a different number of rows or the witness does not fit the circuit.
+    Synthesis = 3,
+    /// The proof did not verify.
+    VerificationFailed = 4,
+    /// The call panicked. This indicates a bug in this crate.
+    Panic = 5,
+}
+
+/// Parameters for KZG commitments on BN254.
+#[derive(Debug)]
+pub struct Halo2Params(ParamsKZG<Bn256>);
+
+/// A proving key, together with the circuit that it was generated for.
+#[derive(Debug)]
+pub struct Halo2ProvingKey {
+    pk: ProvingKey<G1Affine>,
+    description: CircuitDescription<Fr>,
+}
+
+/// A verifying key.
+#[derive(Debug)]
+pub struct Halo2VerifyingKey(VerifyingKey<G1Affine>);
+
+/// Runs `f`, catching any panic.
+fn guard(f: impl FnOnce() -> Result<(), Halo2Status>) -> Halo2Status {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => Halo2Status::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => Halo2Status::Panic,
+    }
+}
+
+/// Returns the `len` bytes at `ptr`, which may be null if `len` is zero.
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], Halo2Status> {
+    // `slice::from_raw_parts` requires a non-null pointer and a length of at most
+    // `isize::MAX` bytes.
+    if len > isize::MAX as usize {
+        return Err(Halo2Status::InvalidInput);
+    }
+    if ptr.is_null() {
+        if len == 0 {
+            Ok(&[])
+        } else {
+            Err(Halo2Status::NullPointer)
+        }
+    } else {
+        Ok(slice::from_raw_parts(ptr, len))
+    }
+}
+
+unsafe fn handle<'a, T>(ptr: *const T) -> Result<&'a T, Halo2Status> {
+    ptr.as_ref().ok_or(Halo2Status::NullPointer)
+}
+
+unsafe fn check_out<T>(out: *mut T) -> Result<(), Halo2Status> {
+    if out.is_null() {
+        Err(Halo2Status::NullPointer)
+    } else {
+        Ok(())
+    }
+}
+
+/// Reads the columns of field elements encoded in `bytes`, all of which must be
+/// consumed.
+fn read_columns(mut bytes: &[u8]) -> io::Result<Vec<Vec<Fr>>> {
+    fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+        let mut value = [0u8; 4];
+        io::Read::read_exact(reader, &mut value)?;
+        Ok(u32::from_be_bytes(value))
+    }
+
+    let reader = &mut bytes;
+    let num_columns = read_u32(reader)?;
+    let columns = (0..num_columns)
+        .map(|_| {
+            let len = read_u32(reader)?;
+            (0..len)
+                .map(|_| Fr::read(reader, SerdeFormat::Processed))
+                .collect()
+        })
+        .collect::<io::Result<Vec<Vec<Fr>>>>()?;
+    if !reader.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "trailing bytes after columns",
+        ));
+    }
+    Ok(columns)
+}
+
+/// Reads parameters encoded as by
+/// [`Params::write`](crate::poly::commitment::Params::write), checking that every point is
+/// on the curve.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes, and `out` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_params_read(
+    bytes: *const u8,
+    len: usize,
+    out: *mut *mut Halo2Params,
+) -> Halo2Status {
+    guard(|| {
+        let mut bytes = self::bytes(bytes, len)?;
+        check_out(out)?;
+        let params = ParamsKZG::read_custom(&mut bytes, SerdeFormat::RawBytes)
+            .map_err(|_| Halo2Status::InvalidInput)?;
+        *out = Box::into_raw(Box::new(Halo2Params(params)));
+        Ok(())
+    })
+}
+
+/// Releases parameters returned by [`halo2_params_read`]. Does nothing if
+/// `params` is null.
+///
+/// # Safety
+///
+/// `params` must be null or a handle that has not been released.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_params_free(params: *mut Halo2Params) {
+    if !params.is_null() {
+        drop(Box::from_raw(params));
+    }
+}
+
+/// Generates the verifying key of the circuit described by `circuit`.
+///
+/// # Safety
+///
+/// `params` must be a valid handle, `circuit` must point to `circuit_len`
+/// readable bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_verifying_key_new(
+    params: *const Halo2Params,
+    circuit: *const u8,
+    circuit_len: usize,
+    out: *mut *mut Halo2VerifyingKey,
+) -> Halo2Status {
+    guard(|| {
+        let params = &handle(params)?.0;
+        let description = CircuitDescription::from_bytes(bytes(circuit, circuit_len)?)
+            .map_err(|_| Halo2Status::InvalidInput)?;
+        check_out(out)?;
+        let vk =
+            keygen_vk_from_description(params, &description).map_err(|_| Halo2Status::Synthesis)?;
+        *out = Box::into_raw(Box::new(Halo2VerifyingKey(vk)));
+        Ok(())
+    })
+}
+
+/// Releases a verifying key returned by [`halo2_verifying_key_new`]. Does nothing
+/// if `vk` is null.
+///
+/// # Safety
+///
+/// `vk` must be null or a handle that has not been released.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_verifying_key_free(vk: *mut Halo2VerifyingKey) {
+    if !vk.is_null() {
+        drop(Box::from_raw(vk));
+    }
+}
+
+/// Generates the proving key of the circuit described by `circuit`.
+///
+/// # Safety
+///
+/// `params` must be a valid handle, `circuit` must point to `circuit_len`
+/// readable bytes, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_proving_key_new(
+    params: *const Halo2Params,
+    circuit: *const u8,
+    circuit_len: usize,
+    out: *mut *mut Halo2ProvingKey,
+) -> Halo2Status {
+    guard(|| {
+        let params = &handle(params)?.0;
+        let description = CircuitDescription::from_bytes(bytes(circuit, circuit_len)?)
+            .map_err(|_| Halo2Status::InvalidInput)?;
+        check_out(out)?;
+        let vk =
+            keygen_vk_from_description(params, &description).map_err(|_| Halo2Status::Synthesis)?;
+        let pk = keygen_pk_from_description(params, vk, &description)
+            .map_err(|_| Halo2Status::Synthesis)?;
+        *out = Box::into_raw(Box::new(Halo2ProvingKey { pk, description }));
+        Ok(())
+    })
+}
+
+/// Releases a proving key returned by [`halo2_proving_key_new`]. Does nothing if
+/// `pk` is null.
+///
+/// # Safety
+///
+/// `pk` must be null or a handle that has not been released.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_proving_key_free(pk: *mut Halo2ProvingKey) {
+    if !pk.is_null() {
+        drop(Box::from_raw(pk));
+    }
+}
+
+/// Creates a proof from the values of every advice column of the circuit and of
+/// its instance columns.
+///
+/// On success, the proof is written to `proof_out` and its length to
+/// `proof_len_out`. It must be released with [`halo2_bytes_free`].
+///
+/// # Safety
+///
+/// `params` and `pk` must be valid handles, `witness` and `instance` must point
+/// to `witness_len` and `instance_len` readable bytes, and `proof_out` and
+/// `proof_len_out` must be valid for writes.
+#[allow(clippy::too_many_arguments)]
+#[no_mangle]
+pub unsafe extern "C" fn halo2_prove(
+    params: *const Halo2Params,
+    pk: *const Halo2ProvingKey,
+    witness: *const u8,
+    witness_len: usize,
+    instance: *const u8,
+    instance_len: usize,
+    proof_out: *mut *mut u8,
+    proof_len_out: *mut usize,
+) -> Halo2Status {
+    guard(|| {
+        let params = &handle(params)?.0;
+        let pk = handle(pk)?;
+        let witness =
+            read_columns(bytes(witness, witness_len)?).map_err(|_| Halo2Status::InvalidInput)?;
+        let instance =
+            read_columns(bytes(instance, instance_len)?).map_err(|_| Halo2Status::InvalidInput)?;
+        check_out(proof_out)?;
+        check_out(proof_len_out)?;
+
+        let witness = DescribedWitness::new(&pk.description, witness)
+            .map_err(|_| Halo2Status::InvalidInput)?;
+        let instance: Vec<&[Fr]> = instance.iter().map(Vec::as_slice).collect();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            &pk.pk,
+            &[witness],
+            &[&instance[..]],
+            OsRng,
+            &mut transcript,
+        )
+        .map_err(|_| Halo2Status::Synthesis)?;
+
+        let proof = transcript.finalize().into_boxed_slice();
+        *proof_len_out = proof.len();
+        *proof_out = Box::into_raw(proof) as *mut u8;
+        Ok(())
+    })
+}
+
+/// Verifies a proof against the values of the instance columns.
+///
+/// Returns [`Halo2Status::VerificationFailed`] if the proof is invalid or is not
+/// for `instance`.
+///
+/// # Safety
+///
+/// `params` and `vk` must be valid handles, and `instance` and `proof` must
+/// point to `instance_len` and `proof_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_verify(
+    params: *const Halo2Params,
+    vk: *const Halo2VerifyingKey,
+    instance: *const u8,
+    instance_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+) -> Halo2Status {
+    guard(|| {
+        let params = &handle(params)?.0;
+        let vk = &handle(vk)?.0;
+        let instance =
+            read_columns(bytes(instance, instance_len)?).map_err(|_| Halo2Status::InvalidInput)?;
+        let proof = bytes(proof, proof_len)?;
+
+        let instance: Vec<&[Fr]> = instance.iter().map(Vec::as_slice).collect();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params,
+            vk,
+            SingleStrategy::new(params),
+            &[&instance[..]],
+            &mut transcript,
+        )
+        .map_err(|_| Halo2Status::VerificationFailed)
+    })
+}
+
+/// Releases bytes returned by [`halo2_prove`]. Does nothing if `ptr` is null or
+/// `len` is over `isize::MAX`, neither of which [`halo2_prove`] returns.
+///
+/// # Safety
+///
+/// `ptr` and `len` must have been returned together, and not released.
+#[no_mangle]
+pub unsafe extern "C" fn halo2_bytes_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() && len <= isize::MAX as usize {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use ff::PrimeField;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    use super::*;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::{
+            commitment::{Params, ParamsProver},
+            Rotation,
+        },
+    };
+
+    const K: u32 = 4;
+
+    /// Exposes the square of its witness as an instance.
+    #[derive(Clone, Copy)]
+    struct SquareCircuit;
+
+    impl Circuit<Fr> for SquareCircuit {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SquareCircuit
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            let s = meta.selector();
+            meta.create_gate("square", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let instance = meta.query_instance(instance, Rotation::cur());
+                vec![s * (a.clone() * a - instance)]
+            });
+            (a, instance, s)
+        }
+
+        fn synthesize(
+            &self,
+            (_, _, s): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(|| "square", |mut region| s.enable(&mut region, 0))
+        }
+    }
+
+    fn columns(columns: &[&[u64]]) -> Vec<u8> {
+        let mut bytes = (columns.len() as u32).to_be_bytes().to_vec();
+        for column in columns {
+            bytes.extend((column.len() as u32).to_be_bytes());
+            for value in column.iter() {
+                bytes.extend(Fr::from(*value).to_repr());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let mut params = vec![];
+        ParamsKZG::<Bn256>::new(K).write(&mut params).unwrap();
+        let circuit = CircuitDescription::from_circuit(K, &SquareCircuit)
+            .unwrap()
+            .to_bytes();
+
+        unsafe {
+            let mut handle = ptr::null_mut();
+            assert_eq!(
+                halo2_params_read(params.as_ptr(), params.len(), &mut handle),
+                Halo2Status::Ok
+            );
+            let params = handle;
+
+            let mut pk = ptr::null_mut();
+            assert_eq!(
+                halo2_proving_key_new(params, circuit.as_ptr(), circuit.len(), &mut pk),
+                Halo2Status::Ok
+            );
+            let mut vk = ptr::null_mut();
+            assert_eq!(
+                halo2_verifying_key_new(params, circuit.as_ptr(), circuit.len(), &mut vk),
+                Halo2Status::Ok
+            );
+
+            let witness = columns(&[&[3]]);
+            let instance = columns(&[&[9]]);
+            let (mut proof, mut proof_len) = (ptr::null_mut(), 0);
+            assert_eq!(
+                halo2_prove(
+                    params,
+                    pk,
+                    witness.as_ptr(),
+                    witness.len(),
+                    instance.as_ptr(),
+                    instance.len(),
+                    &mut proof,
+                    &mut proof_len,
+                ),
+                Halo2Status::Ok
+            );
+
+            assert_eq!(
+                halo2_verify(
+                    params,
+                    vk,
+                    instance.as_ptr(),
+                    instance.len(),
+                    proof,
+                    proof_len
+                ),
+                Halo2Status::Ok
+            );
+            let wrong_instance = columns(&[&[10]]);
+            assert_eq!(
+                halo2_verify(
+                    params,
+                    vk,
+                    wrong_instance.as_ptr(),
+                    wrong_instance.len(),
+                    proof,
+                    proof_len
+                ),
+                Halo2Status::VerificationFailed
+            );
+
+            // Malformed inputs and null handles are reported.
+            let truncated = &instance[..instance.len() - 1];
+            assert_eq!(
+                halo2_verify(
+                    params,
+                    vk,
+                    truncated.as_ptr(),
+                    truncated.len(),
+                    proof,
+                    proof_len
+                ),
+                Halo2Status::InvalidInput
+            );
+            assert_eq!(
+                halo2_verify(
+                    params,
+                    ptr::null(),
+                    instance.as_ptr(),
+                    instance.len(),
+                    proof,
+                    proof_len
+                ),
+                Halo2Status::NullPointer
+            );
+
+            // Buffers and output pointers are checked before they are used.
+            assert_eq!(
+                halo2_verify(params, vk, ptr::null(), 1, proof, proof_len),
+                Halo2Status::NullPointer
+            );
+            assert_eq!(
+                halo2_verify(params, vk, ptr::null(), 0, proof, proof_len),
+                Halo2Status::InvalidInput
+            );
+            assert_eq!(
+                halo2_verify(
+                    params,
+                    vk,
+                    instance.as_ptr(),
+                    isize::MAX as usize + 1,
+                    proof,
+                    proof_len
+                ),
+                Halo2Status::InvalidInput
+            );
+            assert_eq!(
+                halo2_verifying_key_new(params, circuit.as_ptr(), circuit.len(), ptr::null_mut()),
+                Halo2Status::NullPointer
+            );
+
+            halo2_bytes_free(proof, proof_len);
+            halo2_verifying_key_free(vk);
+            halo2_proving_key_free(pk);
+            halo2_params_free(params);
+        }
+    }
+}
@@ -39,20 +39,20 @@ pub trait SerdeCurveAffine: CurveAffine + SerdeObject {
     /// Checks that field elements are less than modulus, and then checks that the point is on the curve.
     /// - `RawBytesUnchecked`: Reads an uncompressed curve element with coordinates in Montgomery form;
     /// does not perform any checks
-    fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Self {
+    fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
         match format {
-            SerdeFormat::Processed => <Self as CurveRead>::read(reader).unwrap(),
-            SerdeFormat::RawBytes => <Self as SerdeObject>::read_raw(reader).unwrap(),
-            SerdeFormat::RawBytesUnchecked => <Self as SerdeObject>::read_raw_unchecked(reader),
+            SerdeFormat::Processed => <Self as CurveRead>::read(reader),
+            SerdeFormat::RawBytes => <Self as SerdeObject>::read_raw(reader),
+            SerdeFormat::RawBytesUnchecked => Ok(<Self as SerdeObject>::read_raw_unchecked(reader)),
         }
     }
     /// Writes a curve element according to `format`:
     /// - `Processed`: Writes a compressed curve element
     /// - Otherwise: Writes an uncompressed curve element with coordinates in Montgomery form
-    fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) {
+    fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         match format {
-            SerdeFormat::Processed => writer.write_all(self.to_bytes().as_ref()).unwrap(),
-            _ => self.write_raw(writer).unwrap(),
+            SerdeFormat::Processed => writer.write_all(self.to_bytes().as_ref()),
+            _ => self.write_raw(writer),
         }
     }
 }
@@ -65,16 +65,17 @@ pub trait SerdePrimeField: PrimeField + SerdeObject {
     /// - `RawBytes`: Reads a field element from raw bytes in its internal Montgomery representations,
     /// and checks that the element is less than the modulus.
     /// - `RawBytesUnchecked`: Reads a field element in Montgomery form and performs no checks.
-    fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Self {
+    fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
         match format {
             SerdeFormat::Processed => {
                 let mut compressed = Self::Repr::default();
-                reader.read_exact(compressed.as_mut()).unwrap();
-                Option::from(Self::from_repr(compressed))
-                    .unwrap_or_else(|| panic!("Invalid prime field point encoding"))
+                reader.read_exact(compressed.as_mut())?;
+                Option::from(Self::from_repr(compressed)).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "Invalid prime field point encoding")
+                })
             }
-            SerdeFormat::RawBytes => <Self as SerdeObject>::read_raw(reader).unwrap(),
-            SerdeFormat::RawBytesUnchecked => <Self as SerdeObject>::read_raw_unchecked(reader),
+            SerdeFormat::RawBytes => <Self as SerdeObject>::read_raw(reader),
+            SerdeFormat::RawBytesUnchecked => Ok(<Self as SerdeObject>::read_raw_unchecked(reader)),
         }
     }
 
@@ -83,10 +84,10 @@ pub trait SerdePrimeField: PrimeField + SerdeObject {
     /// `PrimeField` implementation.
     /// - Otherwise: Writes a field element into raw bytes in its internal Montgomery representation,
     /// WITHOUT performing the expensive Montgomery reduction.
-    fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) {
+    fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         match format {
-            SerdeFormat::Processed => writer.write_all(self.to_repr().as_ref()).unwrap(),
-            _ => self.write_raw(writer).unwrap(),
+            SerdeFormat::Processed => writer.write_all(self.to_repr().as_ref()),
+            _ => self.write_raw(writer),
         }
     }
 }
@@ -115,9 +116,9 @@ pub fn unpack(byte: u8, bits: &mut [bool]) {
 pub(crate) fn read_polynomial_vec<R: io::Read, F: SerdePrimeField, B>(
     reader: &mut R,
     format: SerdeFormat,
-) -> Vec<Polynomial<F, B>> {
+) -> io::Result<Vec<Polynomial<F, B>>> {
     let mut len = [0u8; 4];
-    reader.read_exact(&mut len).unwrap();
+    reader.read_exact(&mut len)?;
     let len = u32::from_be_bytes(len);
 
     (0..len)
@@ -126,21 +127,199 @@ pub(crate) fn read_polynomial_vec<R: io::Read, F: SerdePrimeField, B>(
 }
 
 /// Writes a slice of polynomials to buffer
+#[cfg(feature = "prover")]
 pub(crate) fn write_polynomial_slice<W: io::Write, F: SerdePrimeField, B>(
     slice: &[Polynomial<F, B>],
     writer: &mut W,
     format: SerdeFormat,
-) {
-    writer
-        .write_all(&(slice.len() as u32).to_be_bytes())
-        .unwrap();
+) -> io::Result<()> {
+    writer.write_all(&(slice.len() as u32).to_be_bytes())?;
     for poly in slice.iter() {
-        poly.write(writer, format);
+        poly.write(writer, format)?;
     }
+    Ok(())
 }
 
 /// Gets the total number of bytes of a slice of polynomials, assuming all polynomials are the same length
+#[cfg(feature = "prover")]
 pub(crate) fn polynomial_slice_byte_length<F: PrimeField, B>(slice: &[Polynomial<F, B>]) -> usize {
     let field_len = F::default().to_repr().as_ref().len();
     4 + slice.len() * (4 + field_len * slice.get(0).map(|poly| poly.len()).unwrap_or(0))
 }
+
+/// Returns the in-memory representation of each polynomial in a slice
+#[cfg(all(feature = "mmap", feature = "prover"))]
+pub(crate) fn mapped_polynomial_slice<F: SerdePrimeField, B>(
+    slice: &[Polynomial<F, B>],
+) -> impl Iterator<Item = &[u8]> {
+    slice.iter().map(|poly| crate::storage::as_bytes(&poly[..]))
+}
+
+/// Maps a vector of polynomials of `len` coefficients from consecutive sections of
+/// a file
+#[cfg(all(feature = "mmap", feature = "prover"))]
+pub(crate) fn map_polynomial_vec<F: SerdePrimeField, B>(
+    file: &crate::storage::MappedFile,
+    sections: std::ops::Range<usize>,
+    len: usize,
+) -> io::Result<Vec<Polynomial<F, B>>> {
+    sections
+        .map(|index| file.section(index, len).map(Polynomial::from_storage))
+        .collect()
+}
+
+/// Overwrites each field element of a slice with zero, in a way that is not optimized
+/// away.
+#[cfg(feature = "zeroize")]
+pub(crate) fn zeroize_field<F: ff::Field>(values: &mut [F]) {
+    for value in values.iter_mut() {
+        // `value` is a valid, aligned reference, and zero is a valid field element.
+        unsafe { std::ptr::write_volatile(value, F::zero()) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Empties a vector and overwrites its whole allocation with zero bytes, in a way
+/// that is not optimized away.
+///
+/// The elements are removed before their memory is overwritten, so no value of `T`
+/// is ever left with an invalid representation. Elements with drop glue are left
+/// untouched, since they would be dropped later.
+#[cfg(feature = "zeroize")]
+pub(crate) fn zeroize_vec<T>(values: &mut Vec<T>) {
+    if std::mem::needs_drop::<T>() {
+        return;
+    }
+    values.clear();
+    let spare = values.spare_capacity_mut();
+    let bytes = spare.as_mut_ptr() as *mut u8;
+    for i in 0..std::mem::size_of_val(spare) {
+        // The spare capacity is allocated and holds no values, so any byte may be
+        // written to it.
+        unsafe { std::ptr::write_volatile(bytes.add(i), 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Serializes bytes as a hex string in human-readable formats such as JSON, and as
+/// a byte string otherwise.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_bytes<S: serde::Serializer>(
+    bytes: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        serializer.serialize_str(&hex)
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+/// Deserializes bytes serialized with [`serialize_bytes`]. Hex strings may have a
+/// `0x` prefix, and formats without byte strings may use sequences of bytes.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_bytes<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    use serde::de::{self, SeqAccess, Unexpected, Visitor};
+    use std::fmt;
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a byte string or a hex string")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<u8>, E> {
+            let hex = value.strip_prefix("0x").unwrap_or(value);
+            if !hex.is_ascii() || hex.len() % 2 != 0 {
+                return Err(E::invalid_value(Unexpected::Str(value), &self));
+            }
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| {
+                    u8::from_str_radix(&hex[i..i + 2], 16)
+                        .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+                })
+                .collect()
+        }
+
+        fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(value.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(value)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(BytesVisitor)
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::{deserialize_bytes, serialize_bytes};
+
+    #[test]
+    fn bytes_as_hex() {
+        let json = serialize_bytes(&[0x01, 0xab, 0xff], serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!("01abff"));
+        assert_eq!(deserialize_bytes(json).unwrap(), vec![0x01, 0xab, 0xff]);
+
+        assert_eq!(
+            deserialize_bytes(serde_json::json!("0x01AB")).unwrap(),
+            vec![0x01, 0xab]
+        );
+        assert!(deserialize_bytes(serde_json::json!("0x1")).is_err());
+        assert!(deserialize_bytes(serde_json::json!("zz")).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod zeroize_tests {
+    use super::{zeroize_field, zeroize_vec};
+    use crate::plonk::Assigned;
+    use ff::Field;
+    use halo2curves::pasta::Fp;
+
+    #[test]
+    fn zeroize_field_elements() {
+        let mut values = vec![Fp::from(3), -Fp::one(), Fp::from(u64::MAX)];
+        zeroize_field(&mut values);
+        assert_eq!(values, vec![Fp::zero(); 3]);
+    }
+
+    #[test]
+    fn zeroize_vec_wipes_allocation() {
+        // The layout of `Assigned` is unspecified, so zero bytes need not be a
+        // valid value: the elements must be removed before their memory is wiped.
+        let mut values = vec![Assigned::Rational(Fp::from(5), Fp::from(7)); 10];
+        values.truncate(4);
+        let capacity = values.capacity();
+        zeroize_vec(&mut values);
+
+        assert!(values.is_empty());
+        assert_eq!(values.capacity(), capacity);
+        let spare = values.spare_capacity_mut();
+        let bytes = unsafe {
+            std::slice::from_raw_parts(spare.as_ptr() as *const u8, std::mem::size_of_val(spare))
+        };
+        assert!(bytes.iter().all(|byte| *byte == 0));
+    }
+}
@@ -26,13 +26,18 @@
 #![allow(clippy::derive_partial_eq_without_eq)]
 
 pub mod arithmetic;
+pub mod backend;
 pub mod circuit;
 pub use halo2curves;
 mod multicore;
 pub mod plonk;
 pub mod poly;
+mod storage;
 pub mod transcript;
 
 pub mod dev;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
 mod helpers;
 pub use helpers::SerdeFormat;
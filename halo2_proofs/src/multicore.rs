@@ -1,5 +1,190 @@
 //! An interface for dealing with the kinds of parallel computations involved in
 //! `halo2`. It's currently just a (very!) thin wrapper around [`rayon`] but may
 //! be extended in the future to allow for various parallelism strategies.
+//!
+//! When the `multicore` feature is disabled (for example when targeting
+//! `wasm32-unknown-unknown` without thread support), the same interface is
+//! provided by serial implementations that run every task on the calling thread.
 
-pub use rayon::{current_num_threads, scope, Scope};
+#[cfg(feature = "multicore")]
+pub use rayon::{current_num_threads, iter, join, prelude, scope, slice, Scope};
+
+#[cfg(not(feature = "multicore"))]
+pub use serial::{current_num_threads, iter, join, prelude, scope, slice, Scope};
+
+#[cfg(not(feature = "multicore"))]
+mod serial {
+    use std::marker::PhantomData;
+
+    /// Returns the number of threads that tasks are spread across, which is always one.
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    /// Runs `a` and then `b` on the calling thread, returning both results.
+    pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB,
+    {
+        (a(), b())
+    }
+
+    /// A scope in which tasks are spawned, mirroring `rayon::Scope`.
+    #[derive(Debug)]
+    pub struct Scope<'scope> {
+        _marker: PhantomData<&'scope mut &'scope ()>,
+    }
+
+    impl<'scope> Scope<'scope> {
+        /// Runs `body` to completion on the calling thread.
+        pub fn spawn<BODY>(&self, body: BODY)
+        where
+            BODY: FnOnce(&Scope<'scope>) + 'scope,
+        {
+            body(self)
+        }
+    }
+
+    /// Creates a scope in which tasks are spawned, and runs `op` within it.
+    pub fn scope<'scope, OP, R>(op: OP) -> R
+    where
+        OP: FnOnce(&Scope<'scope>) -> R,
+    {
+        op(&Scope {
+            _marker: PhantomData,
+        })
+    }
+
+    /// Serial counterparts of the `rayon::iter` traits, which produce standard
+    /// iterators.
+    pub mod iter {
+        /// Marker trait standing in for `rayon::iter::ParallelIterator`.
+        pub trait ParallelIterator: Iterator {}
+
+        impl<I: Iterator> ParallelIterator for I {}
+
+        /// Marker trait standing in for `rayon::iter::IndexedParallelIterator`.
+        pub trait IndexedParallelIterator: Iterator {}
+
+        impl<I: Iterator> IndexedParallelIterator for I {}
+
+        /// Converts a value into an iterator, as `rayon::iter::IntoParallelIterator`.
+        pub trait IntoParallelIterator {
+            /// The iterator produced.
+            type Iter: Iterator<Item = Self::Item>;
+            /// The items of the iterator.
+            type Item;
+
+            /// Converts `self` into an iterator.
+            fn into_par_iter(self) -> Self::Iter;
+        }
+
+        impl<I: IntoIterator> IntoParallelIterator for I {
+            type Iter = I::IntoIter;
+            type Item = I::Item;
+
+            fn into_par_iter(self) -> Self::Iter {
+                self.into_iter()
+            }
+        }
+
+        /// Iterates over references, as `rayon::iter::IntoParallelRefIterator`.
+        pub trait IntoParallelRefIterator<'data> {
+            /// The iterator produced.
+            type Iter: Iterator<Item = Self::Item>;
+            /// The items of the iterator.
+            type Item: 'data;
+
+            /// Returns an iterator over references to the elements of `self`.
+            fn par_iter(&'data self) -> Self::Iter;
+        }
+
+        impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+        where
+            &'data I: IntoIterator,
+        {
+            type Iter = <&'data I as IntoIterator>::IntoIter;
+            type Item = <&'data I as IntoIterator>::Item;
+
+            fn par_iter(&'data self) -> Self::Iter {
+                self.into_iter()
+            }
+        }
+
+        /// Iterates over mutable references, as
+        /// `rayon::iter::IntoParallelRefMutIterator`.
+        pub trait IntoParallelRefMutIterator<'data> {
+            /// The iterator produced.
+            type Iter: Iterator<Item = Self::Item>;
+            /// The items of the iterator.
+            type Item: 'data;
+
+            /// Returns an iterator over mutable references to the elements of `self`.
+            fn par_iter_mut(&'data mut self) -> Self::Iter;
+        }
+
+        impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+        where
+            &'data mut I: IntoIterator,
+        {
+            type Iter = <&'data mut I as IntoIterator>::IntoIter;
+            type Item = <&'data mut I as IntoIterator>::Item;
+
+            fn par_iter_mut(&'data mut self) -> Self::Iter {
+                self.into_iter()
+            }
+        }
+    }
+
+    /// Serial counterparts of the `rayon::slice` traits.
+    pub mod slice {
+        use std::slice::{Chunks, ChunksMut};
+
+        /// Chunked iteration over a slice, as `rayon::slice::ParallelSlice`.
+        pub trait ParallelSlice<T> {
+            /// Returns an iterator over `chunk_size` elements of the slice at a time.
+            fn par_chunks(&self, chunk_size: usize) -> Chunks<'_, T>;
+        }
+
+        impl<T> ParallelSlice<T> for [T] {
+            fn par_chunks(&self, chunk_size: usize) -> Chunks<'_, T> {
+                self.chunks(chunk_size)
+            }
+        }
+
+        /// Chunked iteration and sorting of a mutable slice, as
+        /// `rayon::slice::ParallelSliceMut`.
+        pub trait ParallelSliceMut<T> {
+            /// Returns an iterator over `chunk_size` elements of the slice at a time.
+            fn par_chunks_mut(&mut self, chunk_size: usize) -> ChunksMut<'_, T>;
+
+            /// Sorts the slice without preserving the order of equal elements.
+            fn par_sort_unstable(&mut self)
+            where
+                T: Ord;
+        }
+
+        impl<T> ParallelSliceMut<T> for [T] {
+            fn par_chunks_mut(&mut self, chunk_size: usize) -> ChunksMut<'_, T> {
+                self.chunks_mut(chunk_size)
+            }
+
+            fn par_sort_unstable(&mut self)
+            where
+                T: Ord,
+            {
+                self.sort_unstable()
+            }
+        }
+    }
+
+    /// Serial counterpart of `rayon::prelude`.
+    pub mod prelude {
+        pub use super::iter::{
+            IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+            IntoParallelRefMutIterator, ParallelIterator,
+        };
+        pub use super::slice::{ParallelSlice, ParallelSliceMut};
+    }
+}
@@ -10,38 +10,67 @@ use ff::PrimeField;
 use group::ff::Field;
 
 use crate::arithmetic::{CurveAffine, FieldExt};
-use crate::helpers::{
-    polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice, SerdeCurveAffine,
-    SerdePrimeField,
-};
+#[cfg(feature = "prover")]
+use crate::helpers::{polynomial_slice_byte_length, write_polynomial_slice};
+use crate::helpers::{read_polynomial_vec, SerdeCurveAffine, SerdePrimeField};
 use crate::poly::{
     commitment::Params, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff,
     PinnedEvaluationDomain, Polynomial,
 };
 use crate::transcript::{ChallengeScalar, EncodedChallenge, Transcript};
 use crate::SerdeFormat;
+#[cfg(all(feature = "mmap", feature = "prover"))]
+use crate::{
+    helpers::{map_polynomial_vec, mapped_polynomial_slice},
+    storage::{as_bytes, write_mapped, MappedFile, KIND_PROVING_KEY},
+};
 
 mod assigned;
 mod circuit;
+mod description;
 mod error;
+#[cfg(feature = "prover")]
 mod evaluation;
+pub mod folding;
 mod keygen;
 mod lookup;
 pub(crate) mod permutation;
+#[cfg(feature = "prover")]
+pub(crate) mod profiling;
+mod shuffle;
 mod vanishing;
 
+#[cfg(feature = "prover")]
 mod prover;
+mod schedule;
 mod verifier;
+mod wire_format;
 
 pub use assigned::*;
 pub use circuit::*;
+pub use description::*;
 pub use error::*;
 pub use keygen::*;
+#[cfg(feature = "profile")]
+pub use profiling::ProverTimings;
+#[cfg(feature = "prover")]
 pub use prover::*;
+pub use schedule::*;
 pub use verifier::*;
+pub use wire_format::*;
+
+#[cfg(feature = "derive")]
+pub use halo2_derive::ChipConfig;
 
+#[cfg(feature = "prover")]
 use evaluation::Evaluator;
-use std::io;
+use std::io::{self, Read};
+
+/// The version of the protocol that proofs are created and verified with.
+///
+/// It is hashed into the transcript of every proof, so proofs created under one
+/// version of the protocol do not verify under another.
+pub const PROTOCOL_VERSION: u32 = 1;
 
 /// This is a verifying key which allows for the verification of proofs for a
 /// particular circuit.
@@ -56,6 +85,8 @@ pub struct VerifyingKey<C: CurveAffine> {
     /// The representative of this `VerifyingKey` in transcripts.
     transcript_repr: C::Scalar,
     selectors: Vec<Vec<bool>>,
+    /// The application-supplied domain-separation label.
+    label: Vec<u8>,
 }
 
 impl<C: SerdeCurveAffine> VerifyingKey<C>
@@ -71,21 +102,25 @@ where
     /// - Otherwise: Writes an uncompressed curve element with coordinates in Montgomery form
     /// Writes a field element into raw bytes in its internal Montgomery representation,
     /// WITHOUT performing the expensive Montgomery reduction.
+    ///
+    /// The key starts with [`PROTOCOL_VERSION`] and the key's label, prefixed by its
+    /// length, as big-endian `u32`s.
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
-        writer.write_all(&self.domain.k().to_be_bytes()).unwrap();
-        writer
-            .write_all(&(self.fixed_commitments.len() as u32).to_be_bytes())
-            .unwrap();
+        writer.write_all(&PROTOCOL_VERSION.to_be_bytes())?;
+        writer.write_all(&(self.label.len() as u32).to_be_bytes())?;
+        writer.write_all(&self.label)?;
+        writer.write_all(&self.domain.k().to_be_bytes())?;
+        writer.write_all(&(self.fixed_commitments.len() as u32).to_be_bytes())?;
         for commitment in &self.fixed_commitments {
-            commitment.write(writer, format);
+            commitment.write(writer, format)?;
         }
-        self.permutation.write(writer, format);
+        self.permutation.write(writer, format)?;
 
         // write self.selectors
         for selector in &self.selectors {
             // since `selector` is filled with `bool`, we pack them 8 at a time into bytes and then write
             for bits in selector.chunks(8) {
-                writer.write_all(&[crate::helpers::pack(bits)]).unwrap();
+                writer.write_all(&[crate::helpers::pack(bits)])?;
             }
         }
         Ok(())
@@ -101,45 +136,77 @@ where
     /// Checks that field elements are less than modulus, and then checks that the point is on the curve.
     /// - `RawBytesUnchecked`: Reads an uncompressed curve element with coordinates in Montgomery form;
     /// does not perform any checks
+    ///
+    /// Fails if the key was written under a protocol version other than
+    /// [`PROTOCOL_VERSION`]. The key has the label that it was written with.
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
     ) -> io::Result<Self> {
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        if u32::from_be_bytes(version) != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unsupported protocol version",
+            ));
+        }
+        let mut label_len = [0u8; 4];
+        reader.read_exact(&mut label_len)?;
+        let label_len = u32::from_be_bytes(label_len) as usize;
+        let mut label = Vec::new();
+        reader
+            .by_ref()
+            .take(label_len as u64)
+            .read_to_end(&mut label)?;
+        if label.len() != label_len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
         let mut k = [0u8; 4];
         reader.read_exact(&mut k)?;
         let k = u32::from_be_bytes(k);
-        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(k);
+        // Check `k` before the domain is created, so that a malformed key cannot
+        // make it panic or allocate `2^k` rows.
+        let mut cs = ConstraintSystem::with_k(k);
+        ConcreteCircuit::configure(&mut cs);
+        let degree = cs.degree() as u32;
+        if !EvaluationDomain::<C::Scalar>::supports(degree, k) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "k is too large for the scalar field",
+            ));
+        }
+        let domain = EvaluationDomain::new(degree, k);
         let mut num_fixed_columns = [0u8; 4];
-        reader.read_exact(&mut num_fixed_columns).unwrap();
+        reader.read_exact(&mut num_fixed_columns)?;
         let num_fixed_columns = u32::from_be_bytes(num_fixed_columns);
 
-        let fixed_commitments: Vec<_> = (0..num_fixed_columns)
+        let fixed_commitments = (0..num_fixed_columns)
             .map(|_| C::read(reader, format))
-            .collect();
+            .collect::<io::Result<Vec<_>>>()?;
 
-        let permutation = permutation::VerifyingKey::read(reader, &cs.permutation, format);
+        let permutation = permutation::VerifyingKey::read(reader, &cs.permutation, format)?;
 
         // read selectors
         let selectors: Vec<Vec<bool>> = vec![vec![false; 1 << k]; cs.num_selectors]
             .into_iter()
             .map(|mut selector| {
                 let mut selector_bytes = vec![0u8; (selector.len() + 7) / 8];
-                reader.read_exact(&mut selector_bytes).unwrap();
+                reader.read_exact(&mut selector_bytes)?;
                 for (bits, byte) in selector.chunks_mut(8).into_iter().zip(selector_bytes) {
                     crate::helpers::unpack(byte, bits);
                 }
-                selector
+                Ok(selector)
             })
-            .collect();
-        let (cs, _) = cs.compress_selectors(selectors.clone());
-
-        Ok(Self::from_parts(
-            domain,
-            fixed_commitments,
-            permutation,
-            cs,
-            selectors,
-        ))
+            .collect::<io::Result<_>>()?;
+        let (cs, _) = cs
+            .compress_selectors(selectors.clone())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+        let vk = Self::from_parts(domain, fixed_commitments, permutation, cs, selectors)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(vk.with_label(&label))
     }
 
     /// Writes a verifying key to a vector of bytes using [`Self::write`].
@@ -156,11 +223,47 @@ where
     ) -> io::Result<Self> {
         Self::read::<_, ConcreteCircuit>(&mut bytes, format)
     }
+
+    /// Deserializes a verifying key for `ConcreteCircuit` that was serialized with
+    /// its `Serialize` implementation.
+    ///
+    /// `VerifyingKey` does not implement `Deserialize`, because the constraint
+    /// system is not serialized with the key. Fields of a serde type can name the
+    /// circuit with `#[serde(deserialize_with =
+    /// "VerifyingKey::deserialize_with_circuit::<MyCircuit, _>")]`.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn deserialize_with_circuit<'de, ConcreteCircuit, D>(
+        deserializer: D,
+    ) -> Result<Self, D::Error>
+    where
+        ConcreteCircuit: Circuit<C::Scalar>,
+        D: serde::Deserializer<'de>,
+    {
+        let bytes = crate::helpers::deserialize_bytes(deserializer)?;
+        Self::from_bytes::<ConcreteCircuit>(&bytes, SerdeFormat::Processed)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes the key as [`VerifyingKey::to_bytes`] with
+/// [`SerdeFormat::Processed`], as a hex string in human-readable formats and as a
+/// byte string otherwise.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<C: SerdeCurveAffine> serde::Serialize for VerifyingKey<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::helpers::serialize_bytes(&self.to_bytes(SerdeFormat::Processed), serializer)
+    }
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
     fn bytes_length(&self) -> usize {
-        8 + (self.fixed_commitments.len() * C::default().to_bytes().as_ref().len())
+        16 + self.label.len()
+            + (self.fixed_commitments.len() * C::default().to_bytes().as_ref().len())
             + self.permutation.bytes_length()
             + self.selectors.len()
                 * (self
@@ -170,13 +273,24 @@ impl<C: CurveAffine> VerifyingKey<C> {
                     .unwrap_or(0))
     }
 
+    // Fails if there is not one commitment per fixed and permutation column of
+    // `cs`, and one vector of `2^k` activations per selector.
     fn from_parts(
         domain: EvaluationDomain<C::Scalar>,
         fixed_commitments: Vec<C>,
         permutation: permutation::VerifyingKey<C>,
         cs: ConstraintSystem<C::Scalar>,
         selectors: Vec<Vec<bool>>,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        let n = 1 << domain.k();
+        if fixed_commitments.len() != cs.num_fixed_columns
+            || permutation.commitments().len() != cs.permutation.columns.len()
+            || selectors.len() != cs.num_selectors
+            || selectors.iter().any(|selector| selector.len() != n)
+        {
+            return Err(Error::VerifyingKeyMismatch);
+        }
+
         // Compute cached values.
         let cs_degree = cs.degree();
 
@@ -189,22 +303,50 @@ impl<C: CurveAffine> VerifyingKey<C> {
             // Temporary, this is not pinned.
             transcript_repr: C::Scalar::zero(),
             selectors,
+            label: vec![],
         };
+        vk.update_transcript_repr();
+
+        Ok(vk)
+    }
 
+    fn update_transcript_repr(&mut self) {
         let mut hasher = Blake2bParams::new()
             .hash_length(64)
             .personal(b"Halo2-Verify-Key")
             .to_state();
 
-        let s = format!("{:?}", vk.pinned());
+        // Domain separation: the protocol version and the application's label.
+        hasher.update(&PROTOCOL_VERSION.to_le_bytes());
+        hasher.update(&(self.label.len() as u64).to_le_bytes());
+        hasher.update(&self.label);
+
+        let s = format!("{:?}", self.pinned());
 
         hasher.update(&(s.len() as u64).to_le_bytes());
         hasher.update(s.as_bytes());
 
         // Hash in final Blake2bState
-        vk.transcript_repr = C::Scalar::from_bytes_wide(hasher.finalize().as_array());
+        self.transcript_repr = C::Scalar::from_bytes_wide(hasher.finalize().as_array());
+    }
+
+    /// Sets a label that separates the proofs of this circuit from those of
+    /// structurally identical circuits in other deployments or applications.
+    ///
+    /// The label is hashed into the transcript along with [`PROTOCOL_VERSION`],
+    /// before anything else, so a proof only verifies with a verifying key that has
+    /// the label the proof was created with (see `ProvingKey::with_label`). Keys
+    /// have an empty label by default. The label is serialized with the key, so a
+    /// key that is read back has the label it was written with.
+    pub fn with_label(mut self, label: &[u8]) -> Self {
+        self.label = label.to_vec();
+        self.update_transcript_repr();
+        self
+    }
 
-        vk
+    /// Returns the domain-separation label of this key.
+    pub fn label(&self) -> &[u8] {
+        &self.label
     }
 
     /// Hashes a verification key into a transcript.
@@ -248,7 +390,10 @@ impl<C: CurveAffine> VerifyingKey<C> {
 
 /// Minimal representation of a verification key that can be used to identify
 /// its active contents.
-#[allow(dead_code)]
+///
+/// Every part of the verifying key is reachable through its accessors, and its
+/// `Debug` representation is deterministic, so it can be used to generate
+/// standalone verifiers.
 #[derive(Debug)]
 pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     base_modulus: &'static str,
@@ -258,8 +403,43 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     fixed_commitments: &'a Vec<C>,
     permutation: &'a permutation::VerifyingKey<C>,
 }
+
+impl<'a, C: CurveAffine> PinnedVerificationKey<'a, C> {
+    /// Returns the modulus of the base field, as a hex string.
+    pub fn base_modulus(&self) -> &'static str {
+        self.base_modulus
+    }
+
+    /// Returns the modulus of the scalar field, as a hex string.
+    pub fn scalar_modulus(&self) -> &'static str {
+        self.scalar_modulus
+    }
+
+    /// Returns the evaluation domain.
+    pub fn domain(&self) -> &PinnedEvaluationDomain<'a, C::Scalar> {
+        &self.domain
+    }
+
+    /// Returns the constraint system.
+    pub fn cs(&self) -> &PinnedConstraintSystem<'a, C::Scalar> {
+        &self.cs
+    }
+
+    /// Returns the commitments to the fixed columns, including the columns that
+    /// selectors were compressed into.
+    pub fn fixed_commitments(&self) -> &'a [C] {
+        self.fixed_commitments
+    }
+
+    /// Returns the commitments to the permutation polynomials, one for each
+    /// column in [`PinnedConstraintSystem::permutation_columns`].
+    pub fn permutation_commitments(&self) -> &'a [C] {
+        self.permutation.commitments()
+    }
+}
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
+#[cfg(feature = "prover")]
 #[derive(Clone, Debug)]
 pub struct ProvingKey<C: CurveAffine> {
     vk: VerifyingKey<C>,
@@ -273,12 +453,44 @@ pub struct ProvingKey<C: CurveAffine> {
     ev: Evaluator<C>,
 }
 
+#[cfg(feature = "prover")]
 impl<C: CurveAffine> ProvingKey<C> {
     /// Get the underlying [`VerifyingKey`].
     pub fn get_vk(&self) -> &VerifyingKey<C> {
         &self.vk
     }
 
+    /// Sets the domain-separation label of the underlying [`VerifyingKey`]; see
+    /// [`VerifyingKey::with_label`]. Proofs created with this key only verify with
+    /// a verifying key that has the same label.
+    pub fn with_label(mut self, label: &[u8]) -> Self {
+        self.vk = self.vk.with_label(label);
+        self
+    }
+
+    /// Makes the prover compute the quotient polynomial `h(X)` on `cosets`
+    /// cosets of the original domain at a time, rather than on the whole extended
+    /// domain, or restores the default with `None`.
+    ///
+    /// The extended domain is made of `2^(extended_k - k)` such cosets, and the
+    /// advice, instance, lookup and permutation polynomials are only held in the
+    /// extended domain for the cosets being evaluated, so smaller chunks lower the
+    /// prover's peak memory at the cost of recomputing some FFTs. Proofs are
+    /// identical either way.
+    ///
+    /// This is a setting of the prover, so it is not serialized with the key.
+    ///
+    /// Panics if `cosets` is not a power of two.
+    pub fn set_quotient_chunk(&mut self, cosets: Option<usize>) {
+        if let Some(cosets) = cosets {
+            assert!(
+                cosets.is_power_of_two(),
+                "the number of cosets per chunk must be a power of two"
+            );
+        }
+        self.ev.quotient_chunk = cosets;
+    }
+
     /// Gets the total number of bytes in the serialization of `self`
     fn bytes_length(&self) -> usize {
         let scalar_len = C::Scalar::default().to_repr().as_ref().len();
@@ -292,6 +504,7 @@ impl<C: CurveAffine> ProvingKey<C> {
     }
 }
 
+#[cfg(feature = "prover")]
 impl<C: SerdeCurveAffine> ProvingKey<C>
 where
     C::Scalar: SerdePrimeField,
@@ -307,14 +520,14 @@ where
     /// WITHOUT performing the expensive Montgomery reduction.
     /// Does so by first writing the verifying key and then serializing the rest of the data (in the form of field polynomials)
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
-        self.vk.write(writer, format).unwrap();
-        self.l0.write(writer, format);
-        self.l_last.write(writer, format);
-        self.l_active_row.write(writer, format);
-        write_polynomial_slice(&self.fixed_values, writer, format);
-        write_polynomial_slice(&self.fixed_polys, writer, format);
-        write_polynomial_slice(&self.fixed_cosets, writer, format);
-        self.permutation.write(writer, format);
+        self.vk.write(writer, format)?;
+        self.l0.write(writer, format)?;
+        self.l_last.write(writer, format)?;
+        self.l_active_row.write(writer, format)?;
+        write_polynomial_slice(&self.fixed_values, writer, format)?;
+        write_polynomial_slice(&self.fixed_polys, writer, format)?;
+        write_polynomial_slice(&self.fixed_cosets, writer, format)?;
+        self.permutation.write(writer, format)?;
         Ok(())
     }
 
@@ -333,14 +546,14 @@ where
         reader: &mut R,
         format: SerdeFormat,
     ) -> io::Result<Self> {
-        let vk = VerifyingKey::<C>::read::<R, ConcreteCircuit>(reader, format).unwrap();
-        let l0 = Polynomial::read(reader, format);
-        let l_last = Polynomial::read(reader, format);
-        let l_active_row = Polynomial::read(reader, format);
-        let fixed_values = read_polynomial_vec(reader, format);
-        let fixed_polys = read_polynomial_vec(reader, format);
-        let fixed_cosets = read_polynomial_vec(reader, format);
-        let permutation = permutation::ProvingKey::read(reader, format);
+        let vk = VerifyingKey::<C>::read::<R, ConcreteCircuit>(reader, format)?;
+        let l0 = Polynomial::read(reader, format)?;
+        let l_last = Polynomial::read(reader, format)?;
+        let l_active_row = Polynomial::read(reader, format)?;
+        let fixed_values = read_polynomial_vec(reader, format)?;
+        let fixed_polys = read_polynomial_vec(reader, format)?;
+        let fixed_cosets = read_polynomial_vec(reader, format)?;
+        let permutation = permutation::ProvingKey::read(reader, format)?;
         let ev = Evaluator::new(vk.cs());
         Ok(Self {
             vk,
@@ -369,6 +582,89 @@ where
     ) -> io::Result<Self> {
         Self::read::<_, ConcreteCircuit>(&mut bytes, format)
     }
+
+    /// Writes a proving key in a layout that [`Self::read_mapped`] maps into
+    /// memory without parsing.
+    ///
+    /// The verifying key is written with [`SerdeFormat::RawBytes`], and the
+    /// polynomials in their in-memory representation, so the file can only be
+    /// mapped by builds with the same field implementation and endianness.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub fn write_mapped<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let element_size = std::mem::size_of::<C::Scalar>();
+        let sections: Vec<_> = [&self.l0, &self.l_last, &self.l_active_row]
+            .into_iter()
+            .map(|poly| as_bytes(&poly[..]))
+            .chain(mapped_polynomial_slice(&self.fixed_values))
+            .chain(mapped_polynomial_slice(&self.fixed_polys))
+            .chain(mapped_polynomial_slice(&self.fixed_cosets))
+            .chain(self.permutation.mapped_sections())
+            .map(|bytes| (bytes, element_size))
+            .collect();
+        write_mapped(
+            writer,
+            KIND_PROVING_KEY,
+            &[as_bytes(&[C::Scalar::one()])],
+            &self.vk.to_bytes(SerdeFormat::RawBytes),
+            &sections,
+        )
+    }
+
+    /// Maps a proving key written by [`Self::write_mapped`] into memory.
+    ///
+    /// The polynomials are mapped copy-on-write, so processes that load the same
+    /// file share its pages, and are only read from disk as the prover touches
+    /// them. Like [`SerdeFormat::RawBytesUnchecked`], this does not check the
+    /// polynomials, so the file must be trusted.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub fn read_mapped<P: AsRef<std::path::Path>, ConcreteCircuit: Circuit<C::Scalar>>(
+        path: P,
+    ) -> io::Result<Self> {
+        let file = MappedFile::open(path, KIND_PROVING_KEY, &[as_bytes(&[C::Scalar::one()])])?;
+        let vk =
+            VerifyingKey::<C>::read::<_, ConcreteCircuit>(&mut file.meta(), SerdeFormat::RawBytes)?;
+
+        let num_fixed = vk.fixed_commitments.len();
+        let num_permutation = vk.permutation.commitments().len();
+        if file.num_sections() != 3 * (1 + num_fixed + num_permutation) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected number of polynomials",
+            ));
+        }
+
+        // Every section is checked to hold a polynomial over the domain of the
+        // verifying key before it is mapped.
+        let n = 1 << vk.domain.k();
+        let extended_len = vk.domain.extended_len();
+        let l0 = Polynomial::from_storage(file.section(0, extended_len)?);
+        let l_last = Polynomial::from_storage(file.section(1, extended_len)?);
+        let l_active_row = Polynomial::from_storage(file.section(2, extended_len)?);
+        let fixed = |i: usize| 3 + i * num_fixed..3 + (i + 1) * num_fixed;
+        let fixed_values = map_polynomial_vec(&file, fixed(0), n)?;
+        let fixed_polys = map_polynomial_vec(&file, fixed(1), n)?;
+        let fixed_cosets = map_polynomial_vec(&file, fixed(2), extended_len)?;
+        let permutation = permutation::ProvingKey::read_mapped(
+            &file,
+            3 * (1 + num_fixed),
+            num_permutation,
+            &vk.domain,
+        )?;
+        let ev = Evaluator::new(vk.cs());
+        Ok(Self {
+            vk,
+            l0,
+            l_last,
+            l_active_row,
+            fixed_values,
+            fixed_polys,
+            fixed_cosets,
+            permutation,
+            ev,
+        })
+    }
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
@@ -1,12 +1,12 @@
-use core::cmp::max;
+use core::cmp::{max, min};
 use core::ops::{Add, Mul};
-use ff::Field;
+use ff::{Field, PrimeField};
 use std::{
     convert::TryFrom,
-    ops::{Neg, Sub},
+    ops::{Neg, Range, Sub},
 };
 
-use super::{lookup, permutation, Assigned, Error};
+use super::{lookup, permutation, shuffle, Assigned, Error};
 use crate::{
     circuit::{Layouter, Region, Value},
     poly::Rotation,
@@ -393,6 +393,106 @@ impl Selector {
     pub fn is_simple(&self) -> bool {
         self.1
     }
+
+    /// Index of this selector.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A group of mutually exclusive selectors that are encoded in a single fixed column.
+///
+/// On each row the fixed column holds either `0` (no selector in the group is enabled)
+/// or `i + 1` (the `i`-th selector of the group is enabled). This lets chips with many
+/// disjoint operations, such as an ALU with one gate per opcode, spend one fixed column
+/// instead of one per operation. The indicator expression of each selector has degree
+/// equal to the size of the group, so groups should be kept small.
+///
+/// ```
+/// use halo2_proofs::poly::Rotation;
+/// # use halo2curves::pasta::Fp;
+/// # use halo2_proofs::plonk::ConstraintSystem;
+///
+/// # let mut meta = ConstraintSystem::<Fp>::default();
+/// let a = meta.advice_column();
+/// let b = meta.advice_column();
+/// let c = meta.advice_column();
+/// let ops = meta.selector_group(2);
+///
+/// meta.create_gate("alu", |meta| {
+///     let a = meta.query_advice(a, Rotation::cur());
+///     let b = meta.query_advice(b, Rotation::cur());
+///     let c = meta.query_advice(c, Rotation::cur());
+///     let indicators = ops.query_all(meta);
+///
+///     vec![
+///         indicators[0].clone() * (a.clone() + b.clone() - c.clone()),
+///         indicators[1].clone() * (a * b - c),
+///     ]
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SelectorGroup {
+    column: Column<Fixed>,
+    size: usize,
+}
+
+impl SelectorGroup {
+    /// Returns the fixed column in which this group is encoded.
+    pub fn column(&self) -> Column<Fixed> {
+        self.column
+    }
+
+    /// Returns the number of selectors in this group.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Enables the `index`-th selector of this group at the given offset within the
+    /// given region. This implicitly disables every other selector of the group on
+    /// that row.
+    ///
+    /// Panics if `index` is out of range for this group.
+    pub fn enable<F: PrimeField>(&self, region: &mut Region<F>, index: usize, offset: usize) {
+        assert!(index < self.size, "selector index out of range for group");
+        region.assign_fixed(self.column, offset, F::from(index as u64 + 1));
+    }
+
+    /// Queries the indicator expression of the `index`-th selector of this group.
+    ///
+    /// The expression has the form `c * q * Prod[r = 1..=size, r != index + 1](r - q)`,
+    /// where the constant `c` scales it to `1` on the rows where this selector is
+    /// enabled; it is `0` on every other row.
+    ///
+    /// Panics if `index` is out of range for this group.
+    pub fn query<F: PrimeField>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        index: usize,
+    ) -> Expression<F> {
+        assert!(index < self.size, "selector index out of range for group");
+        let enabled = index as u64 + 1;
+        let roots = (1..=self.size as u64).filter(|root| *root != enabled);
+        // The product is non-zero, as its factors are small non-zero integers.
+        let scale = roots
+            .clone()
+            .fold(F::from(enabled), |acc, root| {
+                acc * (F::from(root) - F::from(enabled))
+            })
+            .invert()
+            .unwrap();
+        let q = meta.query_fixed(self.column, Rotation::cur());
+        roots.fold(q.clone() * Expression::Constant(scale), |expr, root| {
+            expr * (Expression::Constant(F::from(root)) - q.clone())
+        })
+    }
+
+    /// Queries the indicator expressions of every selector in this group, in order.
+    pub fn query_all<F: PrimeField>(&self, meta: &mut VirtualCells<'_, F>) -> Vec<Expression<F>> {
+        (0..self.size)
+            .map(|index| self.query(meta, index))
+            .collect()
+    }
 }
 
 /// Query of fixed column at a certain relative location
@@ -499,6 +599,35 @@ impl TableColumn {
     }
 }
 
+/// An instance column whose values the verifier derives from the values of other
+/// instance columns, instead of receiving them alongside the proof.
+///
+/// The derivation is identified by its name, which is bound in the verifying key
+/// together with the derived and source columns.
+#[derive(Clone, Debug)]
+pub struct DerivedInstance {
+    pub(crate) name: &'static str,
+    pub(crate) column: Column<Instance>,
+    pub(crate) sources: Vec<Column<Instance>>,
+}
+
+impl DerivedInstance {
+    /// Returns the name of the derivation.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the derived instance column.
+    pub fn column(&self) -> Column<Instance> {
+        self.column
+    }
+
+    /// Returns the instance columns whose values the derivation takes as input.
+    pub fn sources(&self) -> &[Column<Instance>] {
+        &self.sources
+    }
+}
+
 /// A challenge squeezed from transcript after advice columns at the phase have been committed.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub struct Challenge {
@@ -618,6 +747,22 @@ pub trait Assignment<F: Field> {
     /// Commit advice columns in current phase and squeeze challenges. This can be
     /// called DURING synthesize.
     fn next_phase(&mut self) {}
+
+    /// Returns whether this assignment only collects advice values.
+    ///
+    /// This is the case while proving, where the fixed columns, selectors and lookup
+    /// tables are already part of the proving key. Layouters may then skip assigning
+    /// lookup tables and constants, which can be expensive to compute. Copy
+    /// constraints involving instance columns must still be reported.
+    ///
+    /// This is only an optimization: of the floor planners in this crate, only
+    /// [`SimpleFloorPlanner`] skips that work, and the `V1` planner (which is not
+    /// currently built) would assign everything regardless.
+    ///
+    /// [`SimpleFloorPlanner`]: crate::circuit::SimpleFloorPlanner
+    fn is_witness_only(&self) -> bool {
+        false
+    }
 }
 
 /// A floor planning strategy for a circuit.
@@ -1186,6 +1331,18 @@ pub struct VirtualCell {
     pub(crate) rotation: Rotation,
 }
 
+impl VirtualCell {
+    /// Column of this cell.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Rotation at which this cell is queried.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+}
+
 impl<Col: Into<Column<Any>>> From<(Col, Rotation)> for VirtualCell {
     fn from((column, rotation): (Col, Rotation)) -> Self {
         VirtualCell {
@@ -1318,11 +1475,14 @@ pub struct Gate<F: Field> {
 }
 
 impl<F: Field> Gate<F> {
-    pub(crate) fn name(&self) -> &'static str {
+    /// Returns the name of this gate
+    pub fn name(&self) -> &'static str {
         self.name
     }
 
-    pub(crate) fn constraint_name(&self, constraint_index: usize) -> &'static str {
+    /// Returns the name of the constraint at `constraint_index`, which is empty if
+    /// the constraint was not named
+    pub fn constraint_name(&self, constraint_index: usize) -> &'static str {
         self.constraint_names[constraint_index]
     }
 
@@ -1331,11 +1491,40 @@ impl<F: Field> Gate<F> {
         &self.polys
     }
 
-    pub(crate) fn queried_selectors(&self) -> &[Selector] {
+    /// Returns the maximum degree of the constraints of this gate
+    pub fn degree(&self) -> usize {
+        self.polys
+            .iter()
+            .map(|poly| poly.degree())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Returns an error naming the first constraint of this gate with a degree
+    // greater than `max_degree`.
+    fn check_degree(&self, max_degree: usize) -> Result<(), Error> {
+        for (index, poly) in self.polys.iter().enumerate() {
+            let degree = poly.degree();
+            if degree > max_degree {
+                return Err(Error::GateDegreeExceeded {
+                    gate: self.name,
+                    constraint: index,
+                    constraint_name: self.constraint_names[index],
+                    degree,
+                    max_degree,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the selectors queried by the constraints of this gate
+    pub fn queried_selectors(&self) -> &[Selector] {
         &self.queried_selectors
     }
 
-    pub(crate) fn queried_cells(&self) -> &[VirtualCell] {
+    /// Returns the cells queried by the constraints of this gate
+    pub fn queried_cells(&self) -> &[VirtualCell] {
         &self.queried_cells
     }
 }
@@ -1352,6 +1541,8 @@ pub struct ConstraintSystem<F: Field> {
 
     /// Contains the phase for each advice column. Should have same length as num_advice_columns.
     pub(crate) advice_column_phase: Vec<sealed::Phase>,
+    /// Contains the indices of the advice columns that the prover does not blind.
+    pub(crate) unblinded_advice_columns: Vec<usize>,
     /// Contains the phase for each challenge. Should have same length as num_challenges.
     pub(crate) challenge_phase: Vec<sealed::Phase>,
 
@@ -1376,15 +1567,39 @@ pub struct ConstraintSystem<F: Field> {
     // input expressions and a sequence of table expressions involved in the lookup.
     pub(crate) lookups: Vec<lookup::Argument<F>>,
 
+    // Vector of shuffle arguments, where each corresponds to a sequence of
+    // input expressions and a sequence of shuffle expressions involved in the shuffle.
+    pub(crate) shuffles: Vec<shuffle::Argument<F>>,
+
     // Vector of fixed columns, which can be used to store constant values
     // that are copied into advice columns.
     pub(crate) constants: Vec<Column<Fixed>>,
 
     pub(crate) minimum_degree: Option<usize>,
+
+    // The maximum degree of the constraints of any gate, if the circuit caps it.
+    pub(crate) max_degree: Option<usize>,
+
+    // The number of blinding factors set by the circuit, if it overrides the
+    // number needed for zero-knowledge.
+    pub(crate) blinding_factors: Option<usize>,
+
+    // The number of rows of the circuit being configured, as a power of two, if
+    // it is known at configure time.
+    pub(crate) k: Option<u32>,
+
+    // The instance cell that holds the nonce of each proof, if any.
+    pub(crate) nonce_cell: Option<(Column<Instance>, usize)>,
+
+    // Instance columns derived by the verifier, in the order in which they are
+    // derived.
+    pub(crate) derived_instances: Vec<DerivedInstance>,
+
+    // Whether instance columns are committed to and opened by every proof.
+    pub(crate) committed_instances: bool,
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
-#[allow(dead_code)]
 pub struct PinnedConstraintSystem<'a, F: Field> {
     num_fixed_columns: &'a usize,
     num_advice_columns: &'a usize,
@@ -1399,8 +1614,12 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     fixed_queries: &'a Vec<(Column<Fixed>, Rotation)>,
     permutation: &'a permutation::Argument,
     lookups: &'a Vec<lookup::Argument<F>>,
+    shuffles: &'a Vec<shuffle::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
+    blinding_factors: &'a Option<usize>,
+    derived_instances: &'a Vec<DerivedInstance>,
+    committed_instances: &'a bool,
 }
 
 impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
@@ -1424,13 +1643,133 @@ impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
             .field("instance_queries", self.instance_queries)
             .field("fixed_queries", self.fixed_queries)
             .field("permutation", self.permutation)
-            .field("lookups", self.lookups)
+            .field("lookups", self.lookups);
+        // Only show shuffles if there are any, so that the verifying keys of other
+        // circuits are unchanged.
+        if !self.shuffles.is_empty() {
+            debug_struct.field("shuffles", self.shuffles);
+        }
+        debug_struct
             .field("constants", self.constants)
             .field("minimum_degree", self.minimum_degree);
+        // Only show the number of blinding factors if the circuit sets it, so that
+        // the verifying keys of other circuits are unchanged.
+        if let Some(blinding_factors) = self.blinding_factors {
+            debug_struct.field("blinding_factors", blinding_factors);
+        }
+        // Only show derived instances if there are any, so that the verifying keys
+        // of other circuits are unchanged.
+        if !self.derived_instances.is_empty() {
+            debug_struct.field("derived_instances", self.derived_instances);
+        }
+        // Only show committed instances if the circuit sets it, so that the verifying
+        // keys of other circuits are unchanged.
+        if *self.committed_instances {
+            debug_struct.field("committed_instances", self.committed_instances);
+        }
         debug_struct.finish()
     }
 }
 
+impl<'a, F: Field> PinnedConstraintSystem<'a, F> {
+    /// Returns the number of fixed columns, including the columns that selectors
+    /// were compressed into.
+    pub fn num_fixed_columns(&self) -> usize {
+        *self.num_fixed_columns
+    }
+
+    /// Returns the number of advice columns.
+    pub fn num_advice_columns(&self) -> usize {
+        *self.num_advice_columns
+    }
+
+    /// Returns the number of instance columns.
+    pub fn num_instance_columns(&self) -> usize {
+        *self.num_instance_columns
+    }
+
+    /// Returns the number of selectors.
+    pub fn num_selectors(&self) -> usize {
+        *self.num_selectors
+    }
+
+    /// Returns the number of challenges.
+    pub fn num_challenges(&self) -> usize {
+        *self.num_challenges
+    }
+
+    /// Returns the phase of each advice column.
+    pub fn advice_column_phase(&self) -> Vec<u8> {
+        self.advice_column_phase
+            .iter()
+            .map(|phase| phase.0)
+            .collect()
+    }
+
+    /// Returns the phase of each challenge.
+    pub fn challenge_phase(&self) -> Vec<u8> {
+        self.challenge_phase.iter().map(|phase| phase.0).collect()
+    }
+
+    /// Returns the constraint polynomials of every gate, in order.
+    pub fn gates(&self) -> impl Iterator<Item = &'a Expression<F>> {
+        self.gates
+            .0
+            .iter()
+            .flat_map(|gate| gate.polynomials().iter())
+    }
+
+    /// Returns the advice queries, indexed by query index.
+    pub fn advice_queries(&self) -> &'a [(Column<Advice>, Rotation)] {
+        self.advice_queries
+    }
+
+    /// Returns the instance queries, indexed by query index.
+    pub fn instance_queries(&self) -> &'a [(Column<Instance>, Rotation)] {
+        self.instance_queries
+    }
+
+    /// Returns the fixed queries, indexed by query index.
+    pub fn fixed_queries(&self) -> &'a [(Column<Fixed>, Rotation)] {
+        self.fixed_queries
+    }
+
+    /// Returns the columns involved in the permutation argument, in order.
+    pub fn permutation_columns(&self) -> Vec<Column<Any>> {
+        self.permutation.get_columns()
+    }
+
+    /// Returns the lookup arguments.
+    pub fn lookups(&self) -> &'a [lookup::Argument<F>] {
+        self.lookups
+    }
+
+    /// Returns the shuffle arguments.
+    pub fn shuffles(&self) -> &'a [shuffle::Argument<F>] {
+        self.shuffles
+    }
+
+    /// Returns the fixed columns that hold constants.
+    pub fn constants(&self) -> &'a [Column<Fixed>] {
+        self.constants
+    }
+
+    /// Returns the minimum degree set by the circuit, if any.
+    pub fn minimum_degree(&self) -> Option<usize> {
+        *self.minimum_degree
+    }
+
+    /// Returns the number of blinding factors set by the circuit, if any.
+    pub fn blinding_factors(&self) -> Option<usize> {
+        *self.blinding_factors
+    }
+
+    /// Returns whether the circuit commits to its instance columns.
+    pub fn committed_instances(&self) -> bool {
+        *self.committed_instances
+    }
+}
+
 struct PinnedGates<'a, F: Field>(&'a Vec<Gate<F>>);
 
 impl<'a, F: Field> std::fmt::Debug for PinnedGates<'a, F> {
@@ -1450,6 +1789,7 @@ impl<F: Field> Default for ConstraintSystem<F> {
             num_selectors: 0,
             num_challenges: 0,
             advice_column_phase: Vec::new(),
+            unblinded_advice_columns: Vec::new(),
             challenge_phase: Vec::new(),
             selector_map: vec![],
             gates: vec![],
@@ -1459,13 +1799,28 @@ impl<F: Field> Default for ConstraintSystem<F> {
             instance_queries: Vec::new(),
             permutation: permutation::Argument::new(),
             lookups: Vec::new(),
+            shuffles: Vec::new(),
             constants: vec![],
             minimum_degree: None,
+            max_degree: None,
+            blinding_factors: None,
+            k: None,
+            nonce_cell: None,
+            derived_instances: Vec::new(),
+            committed_instances: false,
         }
     }
 }
 
 impl<F: Field> ConstraintSystem<F> {
+    /// Creates an empty constraint system for a circuit with `2^k` rows.
+    pub(crate) fn with_k(k: u32) -> Self {
+        ConstraintSystem {
+            k: Some(k),
+            ..Default::default()
+        }
+    }
+
     /// Obtain a pinned version of this constraint system; a structure with the
     /// minimal parameters needed to determine the rest of the constraint
     /// system.
@@ -1484,8 +1839,12 @@ impl<F: Field> ConstraintSystem<F> {
             instance_queries: &self.instance_queries,
             permutation: &self.permutation,
             lookups: &self.lookups,
+            shuffles: &self.shuffles,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
+            blinding_factors: &self.blinding_factors,
+            derived_instances: &self.derived_instances,
+            committed_instances: &self.committed_instances,
         }
     }
 
@@ -1502,6 +1861,12 @@ impl<F: Field> ConstraintSystem<F> {
     }
 
     /// Enable the ability to enforce equality over cells in this column
+    ///
+    /// Advice, fixed and instance columns can all be added to the permutation,
+    /// so cells in any of them can be constrained equal to one another. There is
+    /// no limit on the number of columns: the permutation argument is split into
+    /// chunks of `degree() - 2` columns, each with its own grand product, so
+    /// enabling more columns does not raise the degree of the circuit.
     pub fn enable_equality<C: Into<Column<Any>>>(&mut self, column: C) {
         let column = column.into();
         self.query_any_index(column, Rotation::cur());
@@ -1557,7 +1922,39 @@ impl<F: Field> ConstraintSystem<F> {
         index
     }
 
-    fn query_fixed_index(&mut self, column: Column<Fixed>, at: Rotation) -> usize {
+    /// Add a shuffle argument for some input expressions and shuffle expressions.
+    ///
+    /// `shuffle_map` returns a map between input expressions and the shuffle
+    /// expressions they need to match. The argument constrains the rows of the
+    /// input expressions, across the usable rows, to be a permutation of the rows
+    /// of the shuffle expressions. Unlike a lookup, every row is matched exactly
+    /// once and neither side needs to be sorted or fixed.
+    pub fn shuffle(
+        &mut self,
+        name: &'static str,
+        shuffle_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Expression<F>)>,
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let shuffle_map = shuffle_map(&mut cells)
+            .into_iter()
+            .map(|(input, shuffle)| {
+                if input.contains_simple_selector() || shuffle.contains_simple_selector() {
+                    panic!("expression containing simple selector supplied to shuffle argument");
+                }
+
+                (input, shuffle)
+            })
+            .collect();
+
+        let index = self.shuffles.len();
+
+        self.shuffles
+            .push(shuffle::Argument::new(name, shuffle_map));
+
+        index
+    }
+
+    pub(crate) fn query_fixed_index(&mut self, column: Column<Fixed>, at: Rotation) -> usize {
         // Return existing query, if it exists
         for (index, fixed_query) in self.fixed_queries.iter().enumerate() {
             if fixed_query == &(column, at) {
@@ -1588,7 +1985,7 @@ impl<F: Field> ConstraintSystem<F> {
         index
     }
 
-    fn query_instance_index(&mut self, column: Column<Instance>, at: Rotation) -> usize {
+    pub(crate) fn query_instance_index(&mut self, column: Column<Instance>, at: Rotation) -> usize {
         // Return existing query, if it exists
         for (index, instance_query) in self.instance_queries.iter().enumerate() {
             if instance_query == &(column, at) {
@@ -1659,6 +2056,145 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
+    /// Returns the number of rows of the circuit being configured as a power of
+    /// two, if it is known at configure time.
+    ///
+    /// This allows gadgets to adapt their layout to the size of the circuit, for
+    /// example choosing the size of a lookup table:
+    ///
+    /// ```
+    /// # use halo2curves::pasta::Fp;
+    /// # use halo2_proofs::plonk::ConstraintSystem;
+    /// # let meta = ConstraintSystem::<Fp>::default();
+    /// let table_bits = match meta.k() {
+    ///     Some(k) if k >= 17 => 16,
+    ///     _ => 8,
+    /// };
+    /// # assert_eq!(table_bits, 8);
+    /// ```
+    ///
+    /// Keygen, proving, verifying key deserialization and the [`MockProver`] all
+    /// configure circuits with the same `k`. Developer tools that only inspect a
+    /// circuit's gates configure it without a size, in which case this returns
+    /// `None`.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
+    pub fn k(&self) -> Option<u32> {
+        self.k
+    }
+
+    /// Designates the instance cell at `row` of `column` as holding a nonce or
+    /// context value for each proof, which [`verify_proof_with_nonce`] checks
+    /// against the value the verifier expects. This prevents a proof from being
+    /// replayed in another context.
+    ///
+    /// The column must have equality enabled, so that the cell is bound by the
+    /// proof even if no gate queries it.
+    ///
+    /// [`verify_proof_with_nonce`]: crate::plonk::verify_proof_with_nonce
+    pub fn set_nonce_cell(&mut self, column: Column<Instance>, row: usize) {
+        self.nonce_cell = Some((column, row));
+    }
+
+    /// Returns the instance cell designated by [`Self::set_nonce_cell`], if any.
+    pub fn nonce_cell(&self) -> Option<(Column<Instance>, usize)> {
+        self.nonce_cell
+    }
+
+    /// Allocates an instance column whose values the verifier derives from the
+    /// values of the `sources` columns, for example by hashing them, rather than
+    /// receiving them with the proof. This keeps the instance small while the
+    /// circuit still constrains the derived values.
+    ///
+    /// The derivation named `name` is computed by the hook passed to
+    /// [`ConstraintSystem::derive_instances`], which both the prover and the
+    /// verifier use to complete the instance. The sources may include previously
+    /// derived columns.
+    pub fn derived_instance_column(
+        &mut self,
+        name: &'static str,
+        sources: Vec<Column<Instance>>,
+    ) -> Column<Instance> {
+        let column = self.instance_column();
+        self.derived_instances.push(DerivedInstance {
+            name,
+            column,
+            sources,
+        });
+        column
+    }
+
+    /// Returns the instance columns allocated with
+    /// [`Self::derived_instance_column`], in the order in which they are derived.
+    pub fn derived_instances(&self) -> &[DerivedInstance] {
+        &self.derived_instances
+    }
+
+    /// Completes the instance of a single proof with the values of the derived
+    /// instance columns.
+    ///
+    /// `instances` holds the values of the instance columns that are not derived,
+    /// in column order. `derive` is called with the name of each derivation and
+    /// the values of its source columns, and returns the values of the derived
+    /// column. The result holds the values of every instance column, as expected
+    /// by `create_proof` and [`verify_proof`](super::verify_proof).
+    ///
+    /// Returns [`Error::InvalidInstances`] if `instances` does not have one entry
+    /// per instance column that is not derived, and
+    /// [`Error::InvalidDerivedInstance`] if a derivation has a source column that
+    /// is only derived after it, or that is not an instance column of this
+    /// constraint system.
+    pub fn derive_instances<D>(&self, instances: &[&[F]], derive: D) -> Result<Vec<Vec<F>>, Error>
+    where
+        D: Fn(&str, &[&[F]]) -> Vec<F>,
+    {
+        let is_derived = |index: usize| {
+            self.derived_instances
+                .iter()
+                .any(|derived| derived.column.index() == index)
+        };
+        let mut supplied = instances.iter();
+        let mut values = (0..self.num_instance_columns)
+            .map(|index| {
+                if is_derived(index) {
+                    Ok(vec![])
+                } else {
+                    supplied
+                        .next()
+                        .map(|values| values.to_vec())
+                        .ok_or(Error::InvalidInstances)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if supplied.next().is_some() {
+            return Err(Error::InvalidInstances);
+        }
+
+        // The columns whose values are known, which are the supplied columns and
+        // those derived so far.
+        let mut available = (0..self.num_instance_columns)
+            .map(|index| !is_derived(index))
+            .collect::<Vec<_>>();
+        for derived in self.derived_instances.iter() {
+            let sources = derived
+                .sources
+                .iter()
+                .map(|column| match available.get(column.index()) {
+                    Some(true) => Ok(&values[column.index()][..]),
+                    _ => Err(Error::InvalidDerivedInstance {
+                        name: derived.name,
+                        source: *column,
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let derived_values = derive(derived.name, &sources);
+            values[derived.column.index()] = derived_values;
+            available[derived.column.index()] = true;
+        }
+
+        Ok(values)
+    }
+
     /// Sets the minimum degree required by the circuit, which can be set to a
     /// larger amount than actually needed. This can be used, for example, to
     /// force the permutation argument to involve more columns in the same set.
@@ -1666,6 +2202,59 @@ impl<F: Field> ConstraintSystem<F> {
         self.minimum_degree = Some(degree);
     }
 
+    /// Caps the degree of the constraints of every gate, so that a gate that would
+    /// raise [`Self::degree`] (and with it the size of the extended domain) past
+    /// `degree` is caught when the circuit's keys are generated or it is run with
+    /// `MockProver`, which then return [`Error::GateDegreeExceeded`] naming the
+    /// offending gate and constraint. Selectors are only combined into fixed
+    /// columns as far as the cap allows, so combining them never raises the
+    /// degree of a gate past it.
+    ///
+    /// The cap applies to gates created before and after this call. It only
+    /// applies to gates; the lookup and permutation arguments have degrees of
+    /// their own.
+    pub fn set_max_degree(&mut self, degree: usize) {
+        self.max_degree = Some(degree);
+    }
+
+    /// Returns the maximum degree of the constraints of gates, if it was set with
+    /// [`Self::set_max_degree`].
+    pub fn max_degree(&self) -> Option<usize> {
+        self.max_degree
+    }
+
+    /// Sets the number of blinding factors, which is the number of rows at the end
+    /// of every column that the prover fills with random values and the layouter
+    /// may not assign, instead of the number needed for zero-knowledge.
+    ///
+    /// Fewer blinding factors than [`Self::blinding_factors`] would otherwise
+    /// return leave proofs without zero-knowledge, so this is intended for
+    /// applications that do not need it. With no blinding factors, every row but
+    /// the last is usable and the prover samples no randomness for the blinding rows.
+    pub fn set_blinding_factors(&mut self, factors: usize) {
+        self.blinding_factors = Some(factors);
+    }
+
+    /// Makes every proof commit to the instance columns and open them, like advice
+    /// columns, even with a multiopen argument that would otherwise have the
+    /// verifier evaluate them from their values.
+    ///
+    /// Verifiers can then check proofs against commitments to the instance columns
+    /// with [`verify_proof_with_instance_commitments`], doing constant work per
+    /// column instead of work linear in the number of instance values, at the cost
+    /// of slightly larger proofs.
+    ///
+    /// [`verify_proof_with_instance_commitments`]: crate::plonk::verify_proof_with_instance_commitments
+    pub fn commit_instances(&mut self) {
+        self.committed_instances = true;
+    }
+
+    /// Returns whether proofs commit to the instance columns, as set with
+    /// [`Self::commit_instances`].
+    pub fn committed_instances(&self) -> bool {
+        self.committed_instances
+    }
+
     /// Creates a new gate.
     ///
     /// # Panics
@@ -1709,11 +2298,23 @@ impl<F: Field> ConstraintSystem<F> {
     /// find which fixed column corresponds with a given `Selector`.
     ///
     /// Do not call this twice. Yes, this should be a builder pattern instead.
-    pub(crate) fn compress_selectors(mut self, selectors: Vec<Vec<bool>>) -> (Self, Vec<Vec<F>>) {
+    ///
+    /// Returns [`Error::GateDegreeExceeded`] if a gate exceeds the degree set with
+    /// [`Self::set_max_degree`].
+    pub(crate) fn compress_selectors(
+        mut self,
+        selectors: Vec<Vec<bool>>,
+    ) -> Result<(Self, Vec<Vec<F>>), Error> {
         // The number of provided selector assignments must be the number we
         // counted for this constraint system.
         assert_eq!(selectors.len(), self.num_selectors);
 
+        if let Some(max_degree) = self.max_degree {
+            for gate in self.gates.iter() {
+                gate.check_degree(max_degree)?;
+            }
+        }
+
         // Compute the maximal degree of every selector. We only consider the
         // expressions in gates, as lookup arguments cannot support simple
         // selectors. Selectors that are complex or do not appear in any gates
@@ -1726,8 +2327,11 @@ impl<F: Field> ConstraintSystem<F> {
         }
 
         // We will not increase the degree of the constraint system, so we limit
-        // ourselves to the largest existing degree constraint.
-        let max_degree = self.degree();
+        // ourselves to the largest existing degree constraint, and to the cap on
+        // the degree of gates if there is one.
+        let max_degree = self
+            .max_degree
+            .map_or(self.degree(), |max_degree| min(max_degree, self.degree()));
 
         let mut new_columns = vec![];
         let (polys, selector_assignment) = compress_selectors::process(
@@ -1815,7 +2419,18 @@ impl<F: Field> ConstraintSystem<F> {
             replace_selectors(expr, &selector_replacements, true);
         }
 
-        (self, polys)
+        // Substitute non-simple selectors for the real fixed columns in all
+        // shuffle expressions
+        for expr in self.shuffles.iter_mut().flat_map(|shuffle| {
+            shuffle
+                .input_expressions
+                .iter_mut()
+                .chain(shuffle.shuffle_expressions.iter_mut())
+        }) {
+            replace_selectors(expr, &selector_replacements, true);
+        }
+
+        Ok((self, polys))
     }
 
     /// Allocate a new (simple) selector. Simple selectors cannot be added to
@@ -1836,6 +2451,23 @@ impl<F: Field> ConstraintSystem<F> {
         Selector(index, false)
     }
 
+    /// Allocates a group of `size` mutually exclusive selectors that are encoded in a
+    /// single new fixed column. See [`SelectorGroup`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn selector_group(&mut self, size: usize) -> SelectorGroup {
+        assert!(
+            size > 0,
+            "a selector group must contain at least one selector"
+        );
+        SelectorGroup {
+            column: self.fixed_column(),
+            size,
+        }
+    }
+
     /// Allocates a new fixed column that can be used in a lookup table.
     pub fn lookup_table_column(&mut self) -> TableColumn {
         TableColumn {
@@ -1878,6 +2510,26 @@ impl<F: Field> ConstraintSystem<F> {
         tmp
     }
 
+    /// Allocate a new unblinded advice column at `FirstPhase`
+    pub fn unblinded_advice_column(&mut self) -> Column<Advice> {
+        self.unblinded_advice_column_in(FirstPhase)
+    }
+
+    /// Allocate a new unblinded advice column in given phase.
+    ///
+    /// The prover leaves the blinding rows of an unblinded column at zero and
+    /// commits to it with [`Blind::default`], so its commitment only depends on the
+    /// values assigned to it and is the same in every proof. This lets a column
+    /// commitment be shared between several proofs, but the column is not hidden:
+    /// anyone who can guess its values can check them against the commitment.
+    ///
+    /// [`Blind::default`]: crate::poly::commitment::Blind
+    pub fn unblinded_advice_column_in<P: Phase>(&mut self, phase: P) -> Column<Advice> {
+        let column = self.advice_column_in(phase);
+        self.unblinded_advice_columns.push(column.index());
+        column
+    }
+
     /// Allocate a new instance column
     pub fn instance_column(&mut self) -> Column<Instance> {
         let tmp = Column {
@@ -1948,6 +2600,17 @@ impl<F: Field> ConstraintSystem<F> {
                 .unwrap_or(1),
         );
 
+        // The shuffle argument also serves alongside the gates and must be accounted
+        // for.
+        degree = std::cmp::max(
+            degree,
+            self.shuffles
+                .iter()
+                .map(|s| s.required_degree())
+                .max()
+                .unwrap_or(1),
+        );
+
         // Account for each gate to ensure our quotient polynomial is the
         // correct degree and that our extended domain is the right size.
         degree = std::cmp::max(
@@ -1963,8 +2626,13 @@ impl<F: Field> ConstraintSystem<F> {
     }
 
     /// Compute the number of blinding factors necessary to perfectly blind
-    /// each of the prover's witness polynomials.
+    /// each of the prover's witness polynomials, unless the circuit sets it with
+    /// [`Self::set_blinding_factors`].
     pub fn blinding_factors(&self) -> usize {
+        if let Some(factors) = self.blinding_factors {
+            return factors;
+        }
+
         // All of the prover's advice columns are evaluated at no more than
         let factors = *self.num_advice_queries.iter().max().unwrap_or(&1);
         // distinct points during gate checks.
@@ -2003,6 +2671,33 @@ impl<F: Field> ConstraintSystem<F> {
             + 1 // for at least one row
     }
 
+    /// Returns the number of rows at the bottom of every column that circuits cannot
+    /// assign: the [blinding rows](Self::blinding_factors), and the row above them
+    /// on which the permutation and lookup arguments check their final products.
+    pub fn unusable_rows(&self) -> usize {
+        self.blinding_factors() + 1
+    }
+
+    /// Returns the rows that circuits with `2^k` rows can assign.
+    ///
+    /// The range is empty if `2^k` is smaller than [`Self::minimum_rows`].
+    pub fn usable_rows(&self, k: u32) -> Range<usize> {
+        let n = 1usize << k;
+        if n < self.minimum_rows() {
+            return 0..0;
+        }
+        0..n - self.unusable_rows()
+    }
+
+    /// Returns the smallest `k` for which circuits with `2^k` rows have room for
+    /// [`Self::minimum_rows`] rows.
+    ///
+    /// Circuits need more rows than this for every row assigned beyond the first.
+    pub fn minimum_k(&self) -> u32 {
+        let rows = self.minimum_rows();
+        usize::BITS - (rows - 1).leading_zeros()
+    }
+
     /// Returns number of fixed columns
     pub fn num_fixed_columns(&self) -> usize {
         self.num_fixed_columns
@@ -2018,6 +2713,11 @@ impl<F: Field> ConstraintSystem<F> {
         self.num_instance_columns
     }
 
+    /// Returns number of selectors
+    pub fn num_selectors(&self) -> usize {
+        self.num_selectors
+    }
+
     /// Returns number of challenges
     pub fn num_challenges(&self) -> usize {
         self.num_challenges
@@ -2031,6 +2731,11 @@ impl<F: Field> ConstraintSystem<F> {
             .collect()
     }
 
+    /// Returns the indices of the unblinded advice columns
+    pub fn unblinded_advice_columns(&self) -> &Vec<usize> {
+        &self.unblinded_advice_columns
+    }
+
     /// Returns phase of challenges
     pub fn challenge_phase(&self) -> Vec<u8> {
         self.challenge_phase.iter().map(|phase| phase.0).collect()
@@ -2061,11 +2766,22 @@ impl<F: Field> ConstraintSystem<F> {
         &self.permutation
     }
 
+    /// Returns the columns involved in the permutation argument, in the order in
+    /// which they were enabled for equality
+    pub fn permutation_columns(&self) -> &[Column<Any>] {
+        &self.permutation.columns
+    }
+
     /// Returns lookup arguments
     pub fn lookups(&self) -> &Vec<lookup::Argument<F>> {
         &self.lookups
     }
 
+    /// Returns shuffle arguments
+    pub fn shuffles(&self) -> &Vec<shuffle::Argument<F>> {
+        &self.shuffles
+    }
+
     /// Returns constants
     pub fn constants(&self) -> &Vec<Column<Fixed>> {
         &self.constants
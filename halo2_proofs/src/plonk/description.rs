@@ -0,0 +1,1288 @@
+//! Serializable descriptions of circuits, for keygen and proving without the
+//! circuit's Rust type.
+
+use std::io;
+use std::ops::Range;
+
+use ff::Field;
+
+use super::keygen::keygen_vk_with;
+use super::{
+    circuit::{
+        Advice, Any, Assignment, Challenge, Circuit, Column, ConstraintSystem, DerivedInstance,
+        FirstPhase, Fixed, FloorPlanner, Instance, SecondPhase, Selector, ThirdPhase, VirtualCells,
+    },
+    Assigned, Error, Expression, VerifyingKey,
+};
+#[cfg(feature = "prover")]
+use super::{keygen::keygen_pk_with, ProvingKey};
+use crate::{
+    arithmetic::CurveAffine,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    helpers::SerdePrimeField,
+    poly::{commitment::Params, EvaluationDomain, Rotation},
+    SerdeFormat,
+};
+
+/// Set in the phase of an advice column that the prover does not blind. Older
+/// descriptions never set it, since phases are at most 2.
+const UNBLINDED: u8 = 0x80;
+
+/// Upper bounds on the numbers of columns of each type, and of gates, lookup and
+/// shuffle arguments, in a description that is read. They keep a malformed
+/// description from making [`CircuitDescription::read`] allocate without bound.
+const MAX_COLUMNS: usize = 1 << 16;
+const MAX_GATES: usize = 1 << 16;
+
+/// A column, identified by its type and index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColumnRef {
+    Advice(usize),
+    Fixed(usize),
+    Instance(usize),
+}
+
+impl From<Column<Any>> for ColumnRef {
+    fn from(column: Column<Any>) -> Self {
+        match column.column_type() {
+            Any::Advice(_) => ColumnRef::Advice(column.index()),
+            Any::Fixed => ColumnRef::Fixed(column.index()),
+            Any::Instance => ColumnRef::Instance(column.index()),
+        }
+    }
+}
+
+/// An [`Expression`] with columns, selectors and challenges referred to by index.
+#[derive(Clone, Debug)]
+enum ExpressionDescription<F> {
+    Constant(F),
+    Selector(usize),
+    Fixed(usize, Rotation),
+    Advice(usize, Rotation),
+    Instance(usize, Rotation),
+    Challenge(usize),
+    Negated(Box<ExpressionDescription<F>>),
+    Sum(Box<ExpressionDescription<F>>, Box<ExpressionDescription<F>>),
+    Product(Box<ExpressionDescription<F>>, Box<ExpressionDescription<F>>),
+    Scaled(Box<ExpressionDescription<F>>, F),
+}
+
+impl<F: Field> From<&Expression<F>> for ExpressionDescription<F> {
+    fn from(expression: &Expression<F>) -> Self {
+        let boxed = |expression: &Expression<F>| Box::new(expression.into());
+        match expression {
+            Expression::Constant(c) => ExpressionDescription::Constant(*c),
+            Expression::Selector(s) => ExpressionDescription::Selector(s.0),
+            Expression::Fixed(q) => ExpressionDescription::Fixed(q.column_index, q.rotation),
+            Expression::Advice(q) => ExpressionDescription::Advice(q.column_index, q.rotation),
+            Expression::Instance(q) => ExpressionDescription::Instance(q.column_index, q.rotation),
+            Expression::Challenge(c) => ExpressionDescription::Challenge(c.index()),
+            Expression::Negated(a) => ExpressionDescription::Negated(boxed(a)),
+            Expression::Sum(a, b) => ExpressionDescription::Sum(boxed(a), boxed(b)),
+            Expression::Product(a, b) => ExpressionDescription::Product(boxed(a), boxed(b)),
+            Expression::Scaled(a, c) => ExpressionDescription::Scaled(boxed(a), *c),
+        }
+    }
+}
+
+impl<F: Field> ExpressionDescription<F> {
+    /// Rebuilds the expression, querying cells of the given columns.
+    fn to_expression(&self, cells: &mut VirtualCells<'_, F>, columns: &Columns) -> Expression<F> {
+        let mut boxed = |expression: &Self| Box::new(expression.to_expression(cells, columns));
+        match self {
+            ExpressionDescription::Constant(c) => Expression::Constant(*c),
+            ExpressionDescription::Selector(s) => cells.query_selector(columns.selectors[*s]),
+            ExpressionDescription::Fixed(column, at) => {
+                cells.query_fixed(columns.fixed[*column], *at)
+            }
+            ExpressionDescription::Advice(column, at) => {
+                cells.query_advice(columns.advice[*column], *at)
+            }
+            ExpressionDescription::Instance(column, at) => {
+                cells.query_instance(columns.instance[*column], *at)
+            }
+            ExpressionDescription::Challenge(c) => cells.query_challenge(columns.challenges[*c]),
+            ExpressionDescription::Negated(a) => Expression::Negated(boxed(a)),
+            ExpressionDescription::Sum(a, b) => {
+                let a = boxed(a);
+                Expression::Sum(a, boxed(b))
+            }
+            ExpressionDescription::Product(a, b) => {
+                let a = boxed(a);
+                Expression::Product(a, boxed(b))
+            }
+            ExpressionDescription::Scaled(a, c) => Expression::Scaled(boxed(a), *c),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct GateDescription<F> {
+    name: &'static str,
+    constraints: Vec<(&'static str, ExpressionDescription<F>)>,
+}
+
+#[derive(Clone, Debug)]
+struct LookupDescription<F> {
+    name: &'static str,
+    inputs: Vec<ExpressionDescription<F>>,
+    tables: Vec<ExpressionDescription<F>>,
+}
+
+#[derive(Clone, Debug)]
+struct ShuffleDescription<F> {
+    name: &'static str,
+    inputs: Vec<ExpressionDescription<F>>,
+    shuffles: Vec<ExpressionDescription<F>>,
+}
+
+/// The columns, selectors and challenges of a described circuit.
+#[derive(Clone, Debug)]
+struct Columns {
+    fixed: Vec<Column<Fixed>>,
+    advice: Vec<Column<Advice>>,
+    instance: Vec<Column<Instance>>,
+    selectors: Vec<Selector>,
+    challenges: Vec<Challenge>,
+}
+
+impl Columns {
+    fn get(&self, column: ColumnRef) -> Column<Any> {
+        match column {
+            ColumnRef::Advice(index) => self.advice[index].into(),
+            ColumnRef::Fixed(index) => self.fixed[index].into(),
+            ColumnRef::Instance(index) => self.instance[index].into(),
+        }
+    }
+}
+
+/// A description of a circuit that keygen and the prover can consume at runtime,
+/// without the circuit's Rust type.
+///
+/// It records the circuit's columns, gates, lookup and shuffle arguments and
+/// permutation, together with its fixed assignments, selectors and copy constraints
+/// for circuits of `2^k` rows. Services can compile a circuit into a description
+/// once, store it with [`CircuitDescription::write`], and later load new circuits
+/// without recompiling. The verifying key generated with
+/// [`keygen_vk_from_description`] is the same as the one generated from the
+/// circuit itself.
+///
+/// Gate, lookup and shuffle names are leaked once when a description is read, since
+/// the constraint system requires `'static` names.
+#[derive(Clone, Debug)]
+pub struct CircuitDescription<F: Field> {
+    k: u32,
+    num_fixed_columns: usize,
+    advice_column_phases: Vec<u8>,
+    num_instance_columns: usize,
+    simple_selectors: Vec<bool>,
+    challenge_phases: Vec<u8>,
+    fixed_queries: Vec<(usize, Rotation)>,
+    advice_queries: Vec<(usize, Rotation)>,
+    instance_queries: Vec<(usize, Rotation)>,
+    gates: Vec<GateDescription<F>>,
+    lookups: Vec<LookupDescription<F>>,
+    shuffles: Vec<ShuffleDescription<F>>,
+    permutation_columns: Vec<ColumnRef>,
+    constants: Vec<usize>,
+    minimum_degree: Option<usize>,
+    max_degree: Option<usize>,
+    blinding_factors: Option<usize>,
+    nonce_cell: Option<(usize, usize)>,
+    derived_instances: Vec<(&'static str, usize, Vec<usize>)>,
+    committed_instances: bool,
+    fixed: Vec<Vec<F>>,
+    selectors: Vec<Vec<bool>>,
+    copies: Vec<(ColumnRef, usize, ColumnRef, usize)>,
+}
+
+/// Records the fixed assignments, selectors and copy constraints of a circuit.
+struct Recorder<F: Field> {
+    k: u32,
+    fixed: Vec<Vec<F>>,
+    selectors: Vec<Vec<bool>>,
+    copies: Vec<(ColumnRef, usize, ColumnRef, usize)>,
+    // A range of available rows for assignment and copies.
+    usable_rows: Range<usize>,
+}
+
+impl<F: Field> Assignment<F> for Recorder<F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::row_not_available(self.k, row));
+        }
+
+        self.selectors[selector.0][row] = true;
+
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::row_not_available(self.k, row));
+        }
+
+        // There is no instance in this context.
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<'r, 'v>(
+        &'r mut self,
+        _: Column<Advice>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<Value<&'v Assigned<F>>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_fixed(&mut self, column: Column<Fixed>, row: usize, to: Assigned<F>) {
+        if !self.usable_rows.contains(&row) {
+            panic!("{:?}", Error::row_not_available(self.k, row));
+        }
+
+        *self
+            .fixed
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .unwrap_or_else(|| {
+                panic!(
+                    "{:?}",
+                    Error::BoundsFailure {
+                        column: column.into(),
+                        row
+                    }
+                )
+            }) = to.evaluate();
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) {
+        for row in [left_row, right_row] {
+            if !self.usable_rows.contains(&row) {
+                panic!("{:?}", Error::row_not_available(self.k, row));
+            }
+        }
+
+        self.copies
+            .push((left_column.into(), left_row, right_column.into(), right_row));
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        if !self.usable_rows.contains(&from_row) {
+            return Err(Error::row_not_available(self.k, from_row));
+        }
+
+        let col = self
+            .fixed
+            .get_mut(column.index())
+            .ok_or(Error::BoundsFailure {
+                column: column.into(),
+                row: from_row,
+            })?;
+
+        let filler = to.assign(column, from_row)?.evaluate();
+        for row in self.usable_rows.clone().skip(from_row) {
+            col[row] = filler;
+        }
+
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+impl<F: Field> CircuitDescription<F> {
+    /// Describes `circuit` for circuits of `2^k` rows.
+    pub fn from_circuit<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let n = 1usize << k;
+        let mut cs = ConstraintSystem::with_k(k);
+        let config = ConcreteCircuit::configure(&mut cs);
+
+        if n < cs.minimum_rows() {
+            return Err(Error::not_enough_rows_available(k));
+        }
+
+        let mut recorder = Recorder {
+            k,
+            fixed: vec![vec![F::zero(); n]; cs.num_fixed_columns],
+            selectors: vec![vec![false; n]; cs.num_selectors],
+            copies: vec![],
+            usable_rows: 0..n - cs.unusable_rows(),
+        };
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut recorder,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )?;
+
+        // Recover whether each selector is simple from the gates that query it.
+        // Selectors that no gate queries are only usable in lookup arguments, and
+        // are described as complex.
+        let mut simple_selectors = vec![false; cs.num_selectors];
+        for selector in cs.gates.iter().flat_map(|gate| gate.queried_selectors()) {
+            simple_selectors[selector.0] = selector.is_simple();
+        }
+
+        Ok(CircuitDescription {
+            k,
+            num_fixed_columns: cs.num_fixed_columns,
+            advice_column_phases: cs
+                .advice_column_phase
+                .iter()
+                .enumerate()
+                .map(|(index, phase)| {
+                    if cs.unblinded_advice_columns.contains(&index) {
+                        phase.to_u8() | UNBLINDED
+                    } else {
+                        phase.to_u8()
+                    }
+                })
+                .collect(),
+            num_instance_columns: cs.num_instance_columns,
+            simple_selectors,
+            challenge_phases: cs.challenge_phase.iter().map(|p| p.to_u8()).collect(),
+            fixed_queries: cs
+                .fixed_queries
+                .iter()
+                .map(|(column, at)| (column.index(), *at))
+                .collect(),
+            advice_queries: cs
+                .advice_queries
+                .iter()
+                .map(|(column, at)| (column.index(), *at))
+                .collect(),
+            instance_queries: cs
+                .instance_queries
+                .iter()
+                .map(|(column, at)| (column.index(), *at))
+                .collect(),
+            gates: cs
+                .gates
+                .iter()
+                .map(|gate| GateDescription {
+                    name: gate.name(),
+                    constraints: gate
+                        .polynomials()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, poly)| (gate.constraint_name(i), poly.into()))
+                        .collect(),
+                })
+                .collect(),
+            lookups: cs
+                .lookups
+                .iter()
+                .map(|lookup| LookupDescription {
+                    name: lookup.name(),
+                    inputs: lookup.input_expressions().iter().map(Into::into).collect(),
+                    tables: lookup.table_expressions().iter().map(Into::into).collect(),
+                })
+                .collect(),
+            shuffles: cs
+                .shuffles
+                .iter()
+                .map(|shuffle| ShuffleDescription {
+                    name: shuffle.name(),
+                    inputs: shuffle.input_expressions().iter().map(Into::into).collect(),
+                    shuffles: shuffle
+                        .shuffle_expressions()
+                        .iter()
+                        .map(Into::into)
+                        .collect(),
+                })
+                .collect(),
+            permutation_columns: cs
+                .permutation
+                .get_columns()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            constants: cs.constants.iter().map(|column| column.index()).collect(),
+            minimum_degree: cs.minimum_degree,
+            max_degree: cs.max_degree,
+            blinding_factors: cs.blinding_factors,
+            nonce_cell: cs.nonce_cell.map(|(column, row)| (column.index(), row)),
+            derived_instances: cs
+                .derived_instances
+                .iter()
+                .map(|derived| {
+                    (
+                        derived.name,
+                        derived.column.index(),
+                        derived
+                            .sources
+                            .iter()
+                            .map(|column| column.index())
+                            .collect(),
+                    )
+                })
+                .collect(),
+            committed_instances: cs.committed_instances,
+            fixed: recorder.fixed,
+            selectors: recorder.selectors,
+            copies: recorder.copies,
+        })
+    }
+
+    /// Returns the number of rows of the described circuit, as a power of two.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the constraint system of the described circuit.
+    pub fn constraint_system(&self) -> ConstraintSystem<F> {
+        let mut cs = ConstraintSystem::with_k(self.k);
+        self.configure(&mut cs);
+        cs
+    }
+
+    /// Allocates the columns of the described circuit in `meta`, and adds its
+    /// queries, gates, lookup arguments and permutation in their original order.
+    fn configure(&self, meta: &mut ConstraintSystem<F>) -> Columns {
+        let fixed = (0..self.num_fixed_columns)
+            .map(|_| meta.fixed_column())
+            .collect();
+        let advice = self
+            .advice_column_phases
+            .iter()
+            .map(
+                |&phase| match (phase & !UNBLINDED, phase & UNBLINDED != 0) {
+                    (0, false) => meta.advice_column_in(FirstPhase),
+                    (0, true) => meta.unblinded_advice_column_in(FirstPhase),
+                    (1, false) => meta.advice_column_in(SecondPhase),
+                    (1, true) => meta.unblinded_advice_column_in(SecondPhase),
+                    (_, false) => meta.advice_column_in(ThirdPhase),
+                    (_, true) => meta.unblinded_advice_column_in(ThirdPhase),
+                },
+            )
+            .collect();
+        let instance = (0..self.num_instance_columns)
+            .map(|_| meta.instance_column())
+            .collect();
+        let selectors = self
+            .simple_selectors
+            .iter()
+            .map(|&simple| {
+                if simple {
+                    meta.selector()
+                } else {
+                    meta.complex_selector()
+                }
+            })
+            .collect();
+        let challenges = self
+            .challenge_phases
+            .iter()
+            .map(|phase| match phase {
+                0 => meta.challenge_usable_after(FirstPhase),
+                1 => meta.challenge_usable_after(SecondPhase),
+                _ => meta.challenge_usable_after(ThirdPhase),
+            })
+            .collect();
+        let columns = Columns {
+            fixed,
+            advice,
+            instance,
+            selectors,
+            challenges,
+        };
+
+        // Register the queries first, so that they keep their original indices.
+        for &(column, at) in self.fixed_queries.iter() {
+            meta.query_fixed_index(columns.fixed[column], at);
+        }
+        for &(column, at) in self.advice_queries.iter() {
+            meta.query_advice_index(columns.advice[column], at);
+        }
+        for &(column, at) in self.instance_queries.iter() {
+            meta.query_instance_index(columns.instance[column], at);
+        }
+
+        for gate in self.gates.iter() {
+            meta.create_gate(gate.name, |cells| {
+                gate.constraints
+                    .iter()
+                    .map(|(name, poly)| (*name, poly.to_expression(cells, &columns)))
+                    .collect::<Vec<_>>()
+            });
+        }
+        for lookup in self.lookups.iter() {
+            meta.lookup_any(lookup.name, |cells| {
+                lookup
+                    .inputs
+                    .iter()
+                    .zip(lookup.tables.iter())
+                    .map(|(input, table)| {
+                        (
+                            input.to_expression(cells, &columns),
+                            table.to_expression(cells, &columns),
+                        )
+                    })
+                    .collect()
+            });
+        }
+        for shuffle in self.shuffles.iter() {
+            meta.shuffle(shuffle.name, |cells| {
+                shuffle
+                    .inputs
+                    .iter()
+                    .zip(shuffle.shuffles.iter())
+                    .map(|(input, shuffle)| {
+                        (
+                            input.to_expression(cells, &columns),
+                            shuffle.to_expression(cells, &columns),
+                        )
+                    })
+                    .collect()
+            });
+        }
+
+        for &column in self.permutation_columns.iter() {
+            meta.enable_equality(columns.get(column));
+        }
+        for &column in self.constants.iter() {
+            meta.enable_constant(columns.fixed[column]);
+        }
+        if let Some(degree) = self.minimum_degree {
+            meta.set_minimum_degree(degree);
+        }
+        if let Some(degree) = self.max_degree {
+            meta.set_max_degree(degree);
+        }
+        if let Some(factors) = self.blinding_factors {
+            meta.set_blinding_factors(factors);
+        }
+        if let Some((column, row)) = self.nonce_cell {
+            meta.set_nonce_cell(columns.instance[column], row);
+        }
+        for (name, column, sources) in self.derived_instances.iter() {
+            meta.derived_instances.push(DerivedInstance {
+                name: *name,
+                column: columns.instance[*column],
+                sources: sources
+                    .iter()
+                    .map(|column| columns.instance[*column])
+                    .collect(),
+            });
+        }
+        if self.committed_instances {
+            meta.commit_instances();
+        }
+
+        columns
+    }
+
+    /// Replays the fixed assignments, selectors and copy constraints of the
+    /// described circuit into `assignment`.
+    fn replay<A: Assignment<F>>(&self, columns: &Columns, assignment: &mut A) -> Result<(), Error> {
+        for (column, values) in columns.fixed.iter().zip(self.fixed.iter()) {
+            for (row, value) in values.iter().enumerate() {
+                if !bool::from(value.is_zero()) {
+                    assignment.assign_fixed(*column, row, Assigned::Trivial(*value));
+                }
+            }
+        }
+        for (selector, rows) in columns.selectors.iter().zip(self.selectors.iter()) {
+            for (row, _) in rows.iter().enumerate().filter(|(_, enabled)| **enabled) {
+                assignment.enable_selector(|| "", selector, row)?;
+            }
+        }
+        for &(left_column, left_row, right_column, right_row) in self.copies.iter() {
+            assignment.copy(
+                columns.get(left_column),
+                left_row,
+                columns.get(right_column),
+                right_row,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<F: SerdePrimeField> CircuitDescription<F> {
+    /// Writes the description to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u32(writer, self.k)?;
+        write_usize(writer, self.num_fixed_columns)?;
+        write_usize(writer, self.advice_column_phases.len())?;
+        writer.write_all(&self.advice_column_phases)?;
+        write_usize(writer, self.num_instance_columns)?;
+        write_usize(writer, self.simple_selectors.len())?;
+        for &simple in self.simple_selectors.iter() {
+            writer.write_all(&[simple as u8])?;
+        }
+        write_usize(writer, self.challenge_phases.len())?;
+        writer.write_all(&self.challenge_phases)?;
+        for queries in [
+            &self.fixed_queries,
+            &self.advice_queries,
+            &self.instance_queries,
+        ] {
+            write_usize(writer, queries.len())?;
+            for &(column, at) in queries.iter() {
+                write_usize(writer, column)?;
+                write_rotation(writer, at)?;
+            }
+        }
+
+        write_usize(writer, self.gates.len())?;
+        for gate in self.gates.iter() {
+            write_str(writer, &gate.name)?;
+            write_usize(writer, gate.constraints.len())?;
+            for (name, poly) in gate.constraints.iter() {
+                write_str(writer, name)?;
+                write_expression(writer, poly)?;
+            }
+        }
+        write_usize(writer, self.lookups.len())?;
+        for lookup in self.lookups.iter() {
+            write_str(writer, &lookup.name)?;
+            write_usize(writer, lookup.inputs.len())?;
+            for (input, table) in lookup.inputs.iter().zip(lookup.tables.iter()) {
+                write_expression(writer, input)?;
+                write_expression(writer, table)?;
+            }
+        }
+        write_usize(writer, self.shuffles.len())?;
+        for shuffle in self.shuffles.iter() {
+            write_str(writer, &shuffle.name)?;
+            write_usize(writer, shuffle.inputs.len())?;
+            for (input, shuffle) in shuffle.inputs.iter().zip(shuffle.shuffles.iter()) {
+                write_expression(writer, input)?;
+                write_expression(writer, shuffle)?;
+            }
+        }
+
+        write_usize(writer, self.permutation_columns.len())?;
+        for &column in self.permutation_columns.iter() {
+            write_column(writer, column)?;
+        }
+        write_usize(writer, self.constants.len())?;
+        for &column in self.constants.iter() {
+            write_usize(writer, column)?;
+        }
+        write_usize(writer, self.minimum_degree.map_or(0, |degree| degree + 1))?;
+        write_usize(writer, self.max_degree.map_or(0, |degree| degree + 1))?;
+        write_usize(
+            writer,
+            self.blinding_factors.map_or(0, |factors| factors + 1),
+        )?;
+        match self.nonce_cell {
+            Some((column, row)) => {
+                writer.write_all(&[1])?;
+                write_usize(writer, column)?;
+                write_usize(writer, row)?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+        write_usize(writer, self.derived_instances.len())?;
+        for (name, column, sources) in self.derived_instances.iter() {
+            write_str(writer, name)?;
+            write_usize(writer, *column)?;
+            write_usize(writer, sources.len())?;
+            for &source in sources.iter() {
+                write_usize(writer, source)?;
+            }
+        }
+        writer.write_all(&[self.committed_instances as u8])?;
+
+        // Fixed columns and selectors are sparse, so only their non-zero rows are
+        // written.
+        for values in self.fixed.iter() {
+            let assigned: Vec<_> = values
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| !bool::from(value.is_zero()))
+                .collect();
+            write_usize(writer, assigned.len())?;
+            for (row, value) in assigned {
+                write_usize(writer, row)?;
+                value.write(writer, SerdeFormat::Processed)?;
+            }
+        }
+        for rows in self.selectors.iter() {
+            let enabled: Vec<_> = rows
+                .iter()
+                .enumerate()
+                .filter(|(_, enabled)| **enabled)
+                .collect();
+            write_usize(writer, enabled.len())?;
+            for (row, _) in enabled {
+                write_usize(writer, row)?;
+            }
+        }
+        write_usize(writer, self.copies.len())?;
+        for &(left_column, left_row, right_column, right_row) in self.copies.iter() {
+            write_column(writer, left_column)?;
+            write_usize(writer, left_row)?;
+            write_column(writer, right_column)?;
+            write_usize(writer, right_row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a description from a buffer.
+    ///
+    /// Returns an error if `k` exceeds the two-adicity `F::S` of the field, or if
+    /// the description has more than `2^16` columns of a type, or gates, lookup or
+    /// shuffle arguments, or more copy constraints than cells in the permutation.
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let k = read_u32(reader)?;
+        if k > F::S || k >= usize::BITS {
+            return Err(invalid_data("k is too large"));
+        }
+        let n = 1usize << k;
+        let num_fixed_columns = read_count(reader, MAX_COLUMNS, "too many fixed columns")?;
+        let advice_column_phases = read_bytes(reader)?;
+        let num_instance_columns = read_count(reader, MAX_COLUMNS, "too many instance columns")?;
+        let simple_selectors = read_bytes(reader)?
+            .into_iter()
+            .map(|simple| simple != 0)
+            .collect::<Vec<_>>();
+        let challenge_phases = read_bytes(reader)?;
+        if advice_column_phases.len() > MAX_COLUMNS
+            || simple_selectors.len() > MAX_COLUMNS
+            || challenge_phases.len() > MAX_COLUMNS
+        {
+            return Err(invalid_data(
+                "too many advice columns, selectors or challenges",
+            ));
+        }
+        let mut read_queries = |num_columns: usize| {
+            (0..read_usize(reader)?)
+                .map(|_| {
+                    let column = read_index(reader, num_columns)?;
+                    Ok((column, read_rotation(reader)?))
+                })
+                .collect::<io::Result<Vec<_>>>()
+        };
+        let fixed_queries = read_queries(num_fixed_columns)?;
+        let advice_queries = read_queries(advice_column_phases.len())?;
+        let instance_queries = read_queries(num_instance_columns)?;
+
+        let shape = Shape {
+            num_fixed_columns,
+            num_advice_columns: advice_column_phases.len(),
+            num_instance_columns,
+            num_selectors: simple_selectors.len(),
+            num_challenges: challenge_phases.len(),
+        };
+        let gates = (0..read_count(reader, MAX_GATES, "too many gates")?)
+            .map(|_| {
+                let name = read_name(reader)?;
+                let constraints = (0..read_usize(reader)?)
+                    .map(|_| Ok((read_name(reader)?, read_expression(reader, &shape)?)))
+                    .collect::<io::Result<_>>()?;
+                Ok(GateDescription { name, constraints })
+            })
+            .collect::<io::Result<_>>()?;
+        let lookups = (0..read_count(reader, MAX_GATES, "too many lookup arguments")?)
+            .map(|_| {
+                let name = read_name(reader)?;
+                let (inputs, tables) = (0..read_usize(reader)?)
+                    .map(|_| {
+                        Ok((
+                            read_expression(reader, &shape)?,
+                            read_expression(reader, &shape)?,
+                        ))
+                    })
+                    .collect::<io::Result<Vec<_>>>()?
+                    .into_iter()
+                    .unzip();
+                Ok(LookupDescription {
+                    name,
+                    inputs,
+                    tables,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let shuffles = (0..read_count(reader, MAX_GATES, "too many shuffle arguments")?)
+            .map(|_| {
+                let name = read_name(reader)?;
+                let (inputs, shuffles) = (0..read_usize(reader)?)
+                    .map(|_| {
+                        Ok((
+                            read_expression(reader, &shape)?,
+                            read_expression(reader, &shape)?,
+                        ))
+                    })
+                    .collect::<io::Result<Vec<_>>>()?
+                    .into_iter()
+                    .unzip();
+                Ok(ShuffleDescription {
+                    name,
+                    inputs,
+                    shuffles,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        let permutation_columns: Vec<_> = (0..read_usize(reader)?)
+            .map(|_| read_column(reader, &shape))
+            .collect::<io::Result<_>>()?;
+        let constants = (0..read_usize(reader)?)
+            .map(|_| read_index(reader, num_fixed_columns))
+            .collect::<io::Result<_>>()?;
+        let minimum_degree = read_usize(reader)?.checked_sub(1);
+        let max_degree = read_usize(reader)?.checked_sub(1);
+        let blinding_factors = read_usize(reader)?.checked_sub(1);
+        if blinding_factors.map_or(false, |factors| factors >= n) {
+            return Err(invalid_data("too many blinding factors"));
+        }
+        let nonce_cell = match read_u8(reader)? {
+            0 => None,
+            _ => Some((
+                read_index(reader, num_instance_columns)?,
+                read_index(reader, n)?,
+            )),
+        };
+        let derived_instances = (0..read_usize(reader)?)
+            .map(|_| {
+                let name = read_name(reader)?;
+                let column = read_index(reader, num_instance_columns)?;
+                let sources = (0..read_usize(reader)?)
+                    .map(|_| read_index(reader, num_instance_columns))
+                    .collect::<io::Result<_>>()?;
+                Ok((name, column, sources))
+            })
+            .collect::<io::Result<_>>()?;
+        let committed_instances = read_u8(reader)? != 0;
+
+        let fixed = (0..num_fixed_columns)
+            .map(|_| {
+                let mut values = vec![F::zero(); n];
+                for _ in 0..read_usize(reader)? {
+                    let row = read_index(reader, n)?;
+                    values[row] = F::read(reader, SerdeFormat::Processed)?;
+                }
+                Ok(values)
+            })
+            .collect::<io::Result<_>>()?;
+        let selectors = (0..shape.num_selectors)
+            .map(|_| {
+                let mut rows = vec![false; n];
+                for _ in 0..read_usize(reader)? {
+                    rows[read_index(reader, n)?] = true;
+                }
+                Ok(rows)
+            })
+            .collect::<io::Result<_>>()?;
+        let max_copies = permutation_columns.len().saturating_mul(n);
+        let copies = (0..read_count(reader, max_copies, "too many copy constraints")?)
+            .map(|_| {
+                Ok((
+                    read_column(reader, &shape)?,
+                    read_index(reader, n)?,
+                    read_column(reader, &shape)?,
+                    read_index(reader, n)?,
+                ))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(CircuitDescription {
+            k,
+            num_fixed_columns,
+            advice_column_phases,
+            num_instance_columns,
+            simple_selectors,
+            challenge_phases,
+            fixed_queries,
+            advice_queries,
+            instance_queries,
+            gates,
+            lookups,
+            shuffles,
+            permutation_columns,
+            constants,
+            minimum_degree,
+            max_degree,
+            blinding_factors,
+            nonce_cell,
+            derived_instances,
+            committed_instances,
+            fixed,
+            selectors,
+            copies,
+        })
+    }
+
+    /// Writes the description to a vector of bytes using [`Self::write`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("Writing to vector should not fail");
+        bytes
+    }
+
+    /// Reads a description from a slice of bytes using [`Self::read`].
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        Self::read(&mut bytes)
+    }
+}
+
+/// The numbers of columns, selectors and challenges that indices read from a
+/// description are checked against.
+struct Shape {
+    num_fixed_columns: usize,
+    num_advice_columns: usize,
+    num_instance_columns: usize,
+    num_selectors: usize,
+    num_challenges: usize,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32<W: io::Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_be_bytes())
+}
+
+fn write_usize<W: io::Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    let value = u32::try_from(value).map_err(|_| invalid_data("value is too large"))?;
+    write_u32(writer, value)
+}
+
+fn write_str<W: io::Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_usize(writer, value.len())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn write_rotation<W: io::Write>(writer: &mut W, rotation: Rotation) -> io::Result<()> {
+    writer.write_all(&rotation.0.to_be_bytes())
+}
+
+fn write_column<W: io::Write>(writer: &mut W, column: ColumnRef) -> io::Result<()> {
+    let (tag, index) = match column {
+        ColumnRef::Advice(index) => (0, index),
+        ColumnRef::Fixed(index) => (1, index),
+        ColumnRef::Instance(index) => (2, index),
+    };
+    writer.write_all(&[tag])?;
+    write_usize(writer, index)
+}
+
+fn write_expression<W: io::Write, F: SerdePrimeField>(
+    writer: &mut W,
+    expression: &ExpressionDescription<F>,
+) -> io::Result<()> {
+    match expression {
+        ExpressionDescription::Constant(c) => {
+            writer.write_all(&[0])?;
+            c.write(writer, SerdeFormat::Processed)
+        }
+        ExpressionDescription::Selector(s) => {
+            writer.write_all(&[1])?;
+            write_usize(writer, *s)
+        }
+        ExpressionDescription::Fixed(column, at) => {
+            writer.write_all(&[2])?;
+            write_usize(writer, *column)?;
+            write_rotation(writer, *at)
+        }
+        ExpressionDescription::Advice(column, at) => {
+            writer.write_all(&[3])?;
+            write_usize(writer, *column)?;
+            write_rotation(writer, *at)
+        }
+        ExpressionDescription::Instance(column, at) => {
+            writer.write_all(&[4])?;
+            write_usize(writer, *column)?;
+            write_rotation(writer, *at)
+        }
+        ExpressionDescription::Challenge(c) => {
+            writer.write_all(&[5])?;
+            write_usize(writer, *c)
+        }
+        ExpressionDescription::Negated(a) => {
+            writer.write_all(&[6])?;
+            write_expression(writer, a)
+        }
+        ExpressionDescription::Sum(a, b) => {
+            writer.write_all(&[7])?;
+            write_expression(writer, a)?;
+            write_expression(writer, b)
+        }
+        ExpressionDescription::Product(a, b) => {
+            writer.write_all(&[8])?;
+            write_expression(writer, a)?;
+            write_expression(writer, b)
+        }
+        ExpressionDescription::Scaled(a, c) => {
+            writer.write_all(&[9])?;
+            write_expression(writer, a)?;
+            c.write(writer, SerdeFormat::Processed)
+        }
+    }
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut value = [0u8; 1];
+    reader.read_exact(&mut value)?;
+    Ok(value[0])
+}
+
+fn read_u32<R: io::Read>(reader: &mut R) -> io::Result<u32> {
+    let mut value = [0u8; 4];
+    reader.read_exact(&mut value)?;
+    Ok(u32::from_be_bytes(value))
+}
+
+fn read_usize<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    read_u32(reader).map(|value| value as usize)
+}
+
+/// Reads a count, checking that it is at most `max`.
+fn read_count<R: io::Read>(reader: &mut R, max: usize, message: &str) -> io::Result<usize> {
+    let count = read_usize(reader)?;
+    if count <= max {
+        Ok(count)
+    } else {
+        Err(invalid_data(message))
+    }
+}
+
+/// Reads an index, checking that it is less than `len`.
+fn read_index<R: io::Read>(reader: &mut R, len: usize) -> io::Result<usize> {
+    let index = read_usize(reader)?;
+    if index < len {
+        Ok(index)
+    } else {
+        Err(invalid_data("index out of bounds"))
+    }
+}
+
+fn read_bytes<R: io::Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_usize(reader)?;
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+/// Reads a name and leaks it, for constraint system APIs that require `'static`
+/// names.
+fn read_name<R: io::Read>(reader: &mut R) -> io::Result<&'static str> {
+    let name = String::from_utf8(read_bytes(reader)?)
+        .map_err(|_| invalid_data("invalid UTF-8 in name"))?;
+    Ok(Box::leak(name.into_boxed_str()))
+}
+
+fn read_rotation<R: io::Read>(reader: &mut R) -> io::Result<Rotation> {
+    let mut value = [0u8; 4];
+    reader.read_exact(&mut value)?;
+    Ok(Rotation(i32::from_be_bytes(value)))
+}
+
+fn read_column<R: io::Read>(reader: &mut R, shape: &Shape) -> io::Result<ColumnRef> {
+    match read_u8(reader)? {
+        0 => read_index(reader, shape.num_advice_columns).map(ColumnRef::Advice),
+        1 => read_index(reader, shape.num_fixed_columns).map(ColumnRef::Fixed),
+        2 => read_index(reader, shape.num_instance_columns).map(ColumnRef::Instance),
+        _ => Err(invalid_data("invalid column type")),
+    }
+}
+
+fn read_expression<R: io::Read, F: SerdePrimeField>(
+    reader: &mut R,
+    shape: &Shape,
+) -> io::Result<ExpressionDescription<F>> {
+    let mut boxed = |reader: &mut R| read_expression(reader, shape).map(Box::new);
+    Ok(match read_u8(reader)? {
+        0 => ExpressionDescription::Constant(F::read(reader, SerdeFormat::Processed)?),
+        1 => ExpressionDescription::Selector(read_index(reader, shape.num_selectors)?),
+        2 => ExpressionDescription::Fixed(
+            read_index(reader, shape.num_fixed_columns)?,
+            read_rotation(reader)?,
+        ),
+        3 => ExpressionDescription::Advice(
+            read_index(reader, shape.num_advice_columns)?,
+            read_rotation(reader)?,
+        ),
+        4 => ExpressionDescription::Instance(
+            read_index(reader, shape.num_instance_columns)?,
+            read_rotation(reader)?,
+        ),
+        5 => ExpressionDescription::Challenge(read_index(reader, shape.num_challenges)?),
+        6 => ExpressionDescription::Negated(boxed(reader)?),
+        7 => ExpressionDescription::Sum(boxed(reader)?, boxed(reader)?),
+        8 => ExpressionDescription::Product(boxed(reader)?, boxed(reader)?),
+        9 => {
+            ExpressionDescription::Scaled(boxed(reader)?, F::read(reader, SerdeFormat::Processed)?)
+        }
+        _ => return Err(invalid_data("invalid expression")),
+    })
+}
+
+/// Generates a `VerifyingKey` from a [`CircuitDescription`].
+///
+/// Returns [`Error::Synthesis`] if `params` are not for circuits of the described
+/// number of rows.
+pub fn keygen_vk_from_description<'params, C, P>(
+    params: &P,
+    description: &CircuitDescription<C::Scalar>,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
+    if params.k() != description.k {
+        return Err(Error::Synthesis);
+    }
+
+    let mut cs = ConstraintSystem::with_k(description.k);
+    let columns = description.configure(&mut cs);
+    let domain = EvaluationDomain::new(cs.degree() as u32, params.k());
+
+    keygen_vk_with(params, domain, cs, |assembly| {
+        description.replay(&columns, assembly)
+    })
+}
+
+/// Generates a `ProvingKey` from a `VerifyingKey` and a [`CircuitDescription`].
+///
+/// Returns [`Error::Synthesis`] if `params` are not for circuits of the described
+/// number of rows.
+#[cfg(feature = "prover")]
+pub fn keygen_pk_from_description<'params, C, P>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    description: &CircuitDescription<C::Scalar>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
+    if params.k() != description.k {
+        return Err(Error::Synthesis);
+    }
+
+    let mut cs = ConstraintSystem::with_k(description.k);
+    let columns = description.configure(&mut cs);
+
+    keygen_pk_with(params, vk, cs, |assembly| {
+        description.replay(&columns, assembly)
+    })
+}
+
+/// The witness of a described circuit, which can be passed to
+/// `create_proof` with a proving key generated by `keygen_pk_from_description`,
+/// both of which need the `prover` feature.
+///
+/// The witness is the value of every advice column, starting from the first row.
+/// Only circuits whose advice columns are all in the first phase can be proven
+/// this way, since later phases depend on challenges.
+///
+/// The circuit's `configure` does not describe the circuit, so it must not be
+/// used with [`keygen_vk`](super::keygen_vk) or `keygen_pk`.
+#[derive(Clone, Debug)]
+pub struct DescribedWitness<F: Field> {
+    advice: Vec<Column<Advice>>,
+    values: Vec<Vec<Value<F>>>,
+}
+
+impl<F: Field> DescribedWitness<F> {
+    /// Creates the witness of the described circuit, with `advice` holding the
+    /// values of each advice column.
+    ///
+    /// Returns [`Error::Synthesis`] if the circuit has advice columns in later
+    /// phases, or if `advice` does not have one entry per advice column.
+    pub fn new(description: &CircuitDescription<F>, advice: Vec<Vec<F>>) -> Result<Self, Error> {
+        if description
+            .advice_column_phases
+            .iter()
+            .any(|&phase| phase & !UNBLINDED != 0)
+            || advice.len() != description.advice_column_phases.len()
+        {
+            return Err(Error::Synthesis);
+        }
+
+        let columns = description.configure(&mut ConstraintSystem::default());
+        Ok(DescribedWitness {
+            advice: columns.advice,
+            values: advice
+                .into_iter()
+                .map(|column| column.into_iter().map(Value::known).collect())
+                .collect(),
+        })
+    }
+}
+
+impl<F: Field> Circuit<F> for DescribedWitness<F> {
+    type Config = ();
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        DescribedWitness {
+            advice: self.advice.clone(),
+            values: self
+                .values
+                .iter()
+                .map(|column| vec![Value::unknown(); column.len()])
+                .collect(),
+        }
+    }
+
+    fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {}
+
+    fn synthesize(&self, _: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "witness",
+            |mut region| {
+                for (column, values) in self.advice.iter().zip(self.values.iter()) {
+                    for (row, value) in values.iter().enumerate() {
+                        region.assign_advice(*column, row, *value)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
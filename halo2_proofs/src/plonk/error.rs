@@ -3,21 +3,27 @@ use std::error;
 use std::fmt;
 use std::io;
 
-use super::{Any, Column};
+use super::{Any, Column, Instance, TableColumn};
 
 /// This is an error that could occur during proving or circuit synthesis.
 // TODO: these errors need to be cleaned up
 #[derive(Debug)]
 pub enum Error {
-    /// This is an error that can occur during synthesis of the circuit, for
-    /// example, when the witness is not present.
+    /// This is an error that can occur during synthesis of the circuit. It is
+    /// returned by circuits and gadgets; failures of a cell detected by this
+    /// crate use one of the variants below that locates the cell.
     Synthesis,
     /// The provided instances do not match the circuit parameters.
     InvalidInstances,
     /// The constraint system is not satisfied.
     ConstraintSystemFailure,
     /// Out of bounds index passed to a backend
-    BoundsFailure,
+    BoundsFailure {
+        /// The column that was indexed.
+        column: Column<Any>,
+        /// The row that was indexed.
+        row: usize,
+    },
     /// Opening error
     Opening,
     /// Transcript error
@@ -26,7 +32,21 @@ pub enum Error {
     NotEnoughRowsAvailable {
         /// The current value of `k` being used.
         current_k: u32,
+        /// The row outside the usable rows, if the error came from assigning or
+        /// reading a cell.
+        row: Option<usize>,
+    },
+    /// The circuit needs more rows than the params support.
+    ParamsTooSmall {
+        /// The `k` of the params.
+        params_k: u32,
+        /// The smallest `k` the circuit fits in.
+        required_k: u32,
     },
+    /// The verifying key does not match the circuit: it does not have one
+    /// commitment per fixed and permutation column of the circuit, or it does not
+    /// match the circuit and params that a proving key was reconstructed from.
+    VerifyingKeyMismatch,
     /// Instance provided exceeds number of available rows
     InstanceTooLarge,
     /// Circuit synthesis requires global constants, but circuit configuration did not
@@ -37,6 +57,69 @@ pub enum Error {
     /// The instance sets up a copy constraint involving a column that has not been
     /// included in the permutation.
     ColumnNotInPermutation(Column<Any>),
+    /// The instance cell designated for the nonce does not hold the expected
+    /// nonce, or the circuit does not designate one.
+    InvalidNonce,
+    /// An error in a lookup table's assignment.
+    TableError(TableError),
+    /// The prover was given an instance value that differs from the value of a
+    /// cell constrained to equal it.
+    InstanceMismatch {
+        /// The column and row of the constrained cell.
+        cell: (Column<Any>, usize),
+        /// The column and row of the instance cell.
+        instance: (Column<Instance>, usize),
+    },
+    /// A cell was assigned a [`Value::unknown()`] where its value is needed.
+    ///
+    /// [`Value::unknown()`]: crate::circuit::Value::unknown
+    UnknownValue {
+        /// The column of the cell.
+        column: Column<Any>,
+        /// The row of the cell.
+        row: usize,
+    },
+    /// A cell was assigned at an offset outside the region being assigned, such
+    /// as one copy of a repeated region.
+    RowOutsideRegion {
+        /// The offset of the cell within the region.
+        offset: usize,
+        /// The number of rows of the region.
+        height: usize,
+    },
+    /// An instance cell was read within regions assigned in parallel, which do not
+    /// have access to the instance values.
+    InstanceInParallelRegion {
+        /// The instance column that was read.
+        column: Column<Instance>,
+        /// The row that was read.
+        row: usize,
+    },
+    /// A derived instance column has a source column whose values are not known
+    /// when it is derived, because the source is only derived after it or is not
+    /// an instance column of the circuit.
+    InvalidDerivedInstance {
+        /// The name of the derivation.
+        name: &'static str,
+        /// The source column.
+        source: Column<Instance>,
+    },
+    /// A constraint of a gate has a degree greater than the maximum set with
+    /// [`ConstraintSystem::set_max_degree`].
+    ///
+    /// [`ConstraintSystem::set_max_degree`]: crate::plonk::ConstraintSystem::set_max_degree
+    GateDegreeExceeded {
+        /// The name of the gate.
+        gate: &'static str,
+        /// The index of the constraint within the gate.
+        constraint: usize,
+        /// The name of the constraint, which is empty if it was not named.
+        constraint_name: &'static str,
+        /// The degree of the constraint.
+        degree: usize,
+        /// The maximum degree of the constraints of gates.
+        max_degree: usize,
+    },
 }
 
 impl From<io::Error> for Error {
@@ -47,9 +130,22 @@ impl From<io::Error> for Error {
 }
 
 impl Error {
-    /// Constructs an `Error::NotEnoughRowsAvailable`.
+    /// Constructs an `Error::NotEnoughRowsAvailable` for a circuit that does not
+    /// fit in `2^current_k` rows.
     pub(crate) fn not_enough_rows_available(current_k: u32) -> Self {
-        Error::NotEnoughRowsAvailable { current_k }
+        Error::NotEnoughRowsAvailable {
+            current_k,
+            row: None,
+        }
+    }
+
+    /// Constructs an `Error::NotEnoughRowsAvailable` for a cell at `row`, which is
+    /// outside the usable rows of `2^current_k` rows.
+    pub(crate) fn row_not_available(current_k: u32, row: usize) -> Self {
+        Error::NotEnoughRowsAvailable {
+            current_k,
+            row: Some(row),
+        }
     }
 }
 
@@ -59,14 +155,41 @@ impl fmt::Display for Error {
             Error::Synthesis => write!(f, "General synthesis error"),
             Error::InvalidInstances => write!(f, "Provided instances do not match the circuit"),
             Error::ConstraintSystemFailure => write!(f, "The constraint system is not satisfied"),
-            Error::BoundsFailure => write!(f, "An out-of-bounds index was passed to the backend"),
+            Error::BoundsFailure { column, row } => write!(
+                f,
+                "Row {} of {:?} is out of bounds for the backend",
+                row, column
+            ),
             Error::Opening => write!(f, "Multi-opening proof was invalid"),
             Error::Transcript(e) => write!(f, "Transcript error: {}", e),
-            Error::NotEnoughRowsAvailable { current_k } => write!(
+            Error::NotEnoughRowsAvailable {
+                current_k,
+                row: None,
+            } => write!(
                 f,
                 "k = {} is too small for the given circuit. Try using a larger value of k",
                 current_k,
             ),
+            Error::NotEnoughRowsAvailable {
+                current_k,
+                row: Some(row),
+            } => write!(
+                f,
+                "Row {} is not usable with k = {}. Try using a larger value of k",
+                row, current_k,
+            ),
+            Error::ParamsTooSmall {
+                params_k,
+                required_k,
+            } => write!(
+                f,
+                "The circuit needs k = {}, but the params only support k = {}",
+                required_k, params_k,
+            ),
+            Error::VerifyingKeyMismatch => write!(
+                f,
+                "The verifying key was not generated from this circuit and params"
+            ),
             Error::InstanceTooLarge => write!(f, "Instance vectors are larger than the circuit"),
             Error::NotEnoughColumnsForConstants => {
                 write!(
@@ -79,6 +202,46 @@ impl fmt::Display for Error {
                 "Column {:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
                 column
             ),
+            Error::InvalidNonce => write!(f, "The instance nonce does not match the expected nonce"),
+            Error::TableError(error) => write!(f, "{}", error),
+            Error::InstanceMismatch { cell, instance } => write!(
+                f,
+                "Cell {:?} at row {} does not equal instance cell {:?} at row {}",
+                cell.0, cell.1, instance.0, instance.1
+            ),
+            Error::UnknownValue { column, row } => write!(
+                f,
+                "Cell {:?} at row {} was assigned an unknown value",
+                column, row
+            ),
+            Error::RowOutsideRegion { offset, height } => write!(
+                f,
+                "Offset {} is outside a region of {} rows",
+                offset, height
+            ),
+            Error::InstanceInParallelRegion { column, row } => write!(
+                f,
+                "Instance cell {:?} at row {} cannot be read in regions assigned in parallel",
+                column, row
+            ),
+            Error::InvalidDerivedInstance { name, source } => write!(
+                f,
+                "Derived instance '{}' reads {:?} before its values are known",
+                name, source
+            ),
+            Error::GateDegreeExceeded {
+                gate,
+                constraint,
+                constraint_name,
+                degree,
+                max_degree,
+            } => {
+                write!(f, "Gate '{}' has degree {} in constraint {}", gate, degree, constraint)?;
+                if !constraint_name.is_empty() {
+                    write!(f, " ({})", constraint_name)?;
+                }
+                write!(f, ", which exceeds the maximum degree {}", max_degree)
+            }
         }
     }
 }
@@ -91,3 +254,40 @@ impl error::Error for Error {
         }
     }
 }
+
+/// This is an error that could occur during table synthesis.
+#[derive(Debug)]
+pub enum TableError {
+    /// A `TableColumn` has not been assigned at every row up to its length.
+    ColumnNotAssigned(TableColumn),
+    /// A table has columns of uneven lengths.
+    UnevenColumnLengths((TableColumn, usize), (TableColumn, usize)),
+    /// A `TableColumn` that has already been used by another table was assigned.
+    UsedColumn(TableColumn),
+    /// The default value of a `TableColumn`, its value at offset 0, was assigned
+    /// twice.
+    OverwriteDefault(TableColumn, String, String),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::ColumnNotAssigned(col) => write!(
+                f,
+                "{:?} not fully assigned. Help: assign a value at every offset up to the table's length, starting at offset 0",
+                col
+            ),
+            TableError::UnevenColumnLengths((col, col_len), (table, table_len)) => write!(
+                f,
+                "{:?} has length {} while {:?} has length {}",
+                col, col_len, table, table_len
+            ),
+            TableError::UsedColumn(col) => write!(f, "{:?} has already been used", col),
+            TableError::OverwriteDefault(col, default, val) => write!(
+                f,
+                "Attempted to overwrite default value {} with {} in {:?}",
+                default, val, col
+            ),
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use crate::multicore;
+use crate::multicore::prelude::*;
 use crate::plonk::lookup::prover::Committed;
 use crate::plonk::permutation::Argument;
-use crate::plonk::{lookup, permutation, AdviceQuery, Any, FixedQuery, InstanceQuery, ProvingKey};
+use crate::plonk::{
+    lookup, permutation, shuffle, AdviceQuery, Any, FixedQuery, InstanceQuery, ProvingKey,
+};
 use crate::poly::Basis;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
+    backend::Backend,
     poly::{
         commitment::Params, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff,
         Polynomial, ProverQuery, Rotation,
@@ -17,6 +21,7 @@ use group::{
     Curve,
 };
 use std::any::TypeId;
+use std::cmp;
 use std::convert::TryInto;
 use std::num::ParseIntError;
 use std::slice;
@@ -186,6 +191,14 @@ pub struct Evaluator<C: CurveAffine> {
     pub custom_gates: GraphEvaluator<C>,
     ///  Lookups evalution
     pub lookups: Vec<GraphEvaluator<C>>,
+    ///  Lookup input and table compressions, evaluated in the Lagrange basis
+    pub lookup_compressions: Vec<(GraphEvaluator<C>, GraphEvaluator<C>)>,
+    ///  Shuffle input and shuffle compressions, evaluated in the Lagrange basis
+    ///  and on the extended domain
+    pub shuffle_compressions: Vec<(GraphEvaluator<C>, GraphEvaluator<C>)>,
+    ///  Number of cosets of the original domain on which h is evaluated at once,
+    ///  or `None` to evaluate it on the whole extended domain
+    pub quotient_chunk: Option<usize>,
 }
 
 /// GraphEvaluator
@@ -271,6 +284,19 @@ impl<C: CurveAffine> Evaluator<C> {
             graph.add_calculation(Calculation::Mul(lc, right_gamma));
 
             ev.lookups.push(graph);
+
+            ev.lookup_compressions.push((
+                GraphEvaluator::compress(&lookup.input_expressions),
+                GraphEvaluator::compress(&lookup.table_expressions),
+            ));
+        }
+
+        // Shuffles
+        for shuffle in cs.shuffles.iter() {
+            ev.shuffle_compressions.push((
+                GraphEvaluator::compress(&shuffle.input_expressions),
+                GraphEvaluator::compress(&shuffle.shuffle_expressions),
+            ));
         }
 
         ev
@@ -280,6 +306,7 @@ impl<C: CurveAffine> Evaluator<C> {
     pub(in crate::plonk) fn evaluate_h(
         &self,
         pk: &ProvingKey<C>,
+        backend: &dyn Backend<C>,
         advice_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
         instance_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
         challenges: &[C::ScalarExt],
@@ -288,48 +315,156 @@ impl<C: CurveAffine> Evaluator<C> {
         gamma: C::ScalarExt,
         theta: C::ScalarExt,
         lookups: &[Vec<lookup::prover::Committed<C>>],
+        shuffles: &[Vec<shuffle::prover::Committed<C>>],
         permutations: &[permutation::prover::Committed<C>],
     ) -> Polynomial<C::ScalarExt, ExtendedLagrangeCoeff> {
         let domain = &pk.vk.domain;
-        let size = domain.extended_len();
-        let rot_scale = 1 << (domain.extended_k() - domain.k());
-        let fixed = &pk.fixed_cosets[..];
+        let num_cosets = 1 << (domain.extended_k() - domain.k());
+        let parts = match self.quotient_chunk {
+            Some(chunk) => num_cosets / cmp::min(chunk, num_cosets),
+            None => 1,
+        };
+
+        let mut values = domain.empty_extended();
+        if parts == 1 {
+            self.evaluate_h_part(
+                pk,
+                backend,
+                advice_polys,
+                instance_polys,
+                challenges,
+                y,
+                beta,
+                gamma,
+                theta,
+                lookups,
+                shuffles,
+                permutations,
+                1,
+                0,
+                &mut values,
+            );
+        } else {
+            // Evaluate each part on its own, so that only one part of each
+            // polynomial is held in the extended domain at a time.
+            let mut part_values = vec![C::ScalarExt::zero(); values.len() / parts];
+            for part in 0..parts {
+                part_values
+                    .iter_mut()
+                    .for_each(|value| *value = C::ScalarExt::zero());
+                self.evaluate_h_part(
+                    pk,
+                    backend,
+                    advice_polys,
+                    instance_polys,
+                    challenges,
+                    y,
+                    beta,
+                    gamma,
+                    theta,
+                    lookups,
+                    shuffles,
+                    permutations,
+                    parts,
+                    part,
+                    &mut part_values,
+                );
+                for (value, part_value) in values
+                    .iter_mut()
+                    .skip(part)
+                    .step_by(parts)
+                    .zip(part_values.iter())
+                {
+                    *value = *part_value;
+                }
+            }
+        }
+        values
+    }
+
+    /// Evaluates h on one of `parts` interleaved parts of the extended domain, as
+    /// given by [`EvaluationDomain::coeff_to_extended_part`].
+    fn evaluate_h_part(
+        &self,
+        pk: &ProvingKey<C>,
+        backend: &dyn Backend<C>,
+        advice_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
+        instance_polys: &[&[Polynomial<C::ScalarExt, Coeff>]],
+        challenges: &[C::ScalarExt],
+        y: C::ScalarExt,
+        beta: C::ScalarExt,
+        gamma: C::ScalarExt,
+        theta: C::ScalarExt,
+        lookups: &[Vec<lookup::prover::Committed<C>>],
+        shuffles: &[Vec<shuffle::prover::Committed<C>>],
+        permutations: &[permutation::prover::Committed<C>],
+        parts: usize,
+        part: usize,
+        values: &mut [C::ScalarExt],
+    ) {
+        let domain = &pk.vk.domain;
+        let size = domain.extended_len() / parts;
+        let rot_scale = (1 << (domain.extended_k() - domain.k())) / parts as i32;
         let extended_omega = domain.get_extended_omega();
+        let omega = extended_omega.pow_vartime(&[parts as u64, 0, 0, 0]);
+        let omega_start = extended_omega.pow_vartime(&[part as u64, 0, 0, 0]);
         let isize = size as i32;
         let one = C::ScalarExt::one();
-        let l0 = &pk.l0;
-        let l_last = &pk.l_last;
-        let l_active_row = &pk.l_active_row;
         let p = &pk.vk.cs.permutation;
 
-        // Calculate the advice and instance cosets
+        // Polynomials in the extended domain are computed from their coefficients,
+        // and those the proving key holds in the extended domain are selected.
+        let coset = |poly: &Polynomial<C::ScalarExt, Coeff>| {
+            domain.coeff_to_extended_part_with(backend, poly, parts, part)
+        };
+        let select = |polys: &[Polynomial<C::ScalarExt, ExtendedLagrangeCoeff>]| {
+            polys
+                .par_iter()
+                .map(|poly| domain.extended_part(poly, parts, part))
+                .collect::<Vec<_>>()
+        };
+        let (fixed_parts, permutation_parts, l_parts);
+        let (fixed, permutation_cosets, l0, l_last, l_active_row) = if parts == 1 {
+            (
+                &pk.fixed_cosets[..],
+                &pk.permutation.cosets[..],
+                &pk.l0,
+                &pk.l_last,
+                &pk.l_active_row,
+            )
+        } else {
+            fixed_parts = select(&pk.fixed_cosets);
+            permutation_parts = select(&pk.permutation.cosets);
+            l_parts = [&pk.l0, &pk.l_last, &pk.l_active_row]
+                .into_iter()
+                .map(|poly| domain.extended_part(poly, parts, part))
+                .collect::<Vec<_>>();
+            (
+                &fixed_parts[..],
+                &permutation_parts[..],
+                &l_parts[0],
+                &l_parts[1],
+                &l_parts[2],
+            )
+        };
+
+        // Calculate the advice and instance cosets, one column per task
         let advice: Vec<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> = advice_polys
             .iter()
-            .map(|advice_polys| {
-                advice_polys
-                    .iter()
-                    .map(|poly| domain.coeff_to_extended(poly.clone()))
-                    .collect()
-            })
+            .map(|advice_polys| advice_polys.par_iter().map(coset).collect())
             .collect();
         let instance: Vec<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> = instance_polys
             .iter()
-            .map(|instance_polys| {
-                instance_polys
-                    .iter()
-                    .map(|poly| domain.coeff_to_extended(poly.clone()))
-                    .collect()
-            })
+            .map(|instance_polys| instance_polys.par_iter().map(coset).collect())
             .collect();
 
-        let mut values = domain.empty_extended();
-
         // Core expression evaluations
         let num_threads = multicore::current_num_threads();
-        for (((advice, instance), lookups), permutation) in advice
+        for ((((advice, instance), lookups), shuffles), permutation) in advice
             .iter()
             .zip(instance.iter())
             .zip(lookups.iter())
+            .zip(shuffles.iter())
             .zip(permutations.iter())
         {
             // Custom gates
@@ -369,12 +504,18 @@ impl<C: CurveAffine> Evaluator<C> {
                 let chunk_len = pk.vk.cs.degree() - 2;
                 let delta_start = beta * &C::Scalar::ZETA;
 
-                let first_set = sets.first().unwrap();
-                let last_set = sets.last().unwrap();
+                // Calculated here so these only have to be kept in memory for the
+                // short time they are actually needed.
+                let product_cosets: Vec<_> = sets
+                    .par_iter()
+                    .map(|set| coset(&set.permutation_product_poly))
+                    .collect();
+                let first_set = product_cosets.first().unwrap();
+                let last_set = product_cosets.last().unwrap();
 
                 // Permutation constraints
-                parallelize(&mut values, |values, start| {
-                    let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                parallelize(values, |values, start| {
+                    let mut beta_term = omega_start * omega.pow_vartime(&[start as u64, 0, 0, 0]);
                     for (i, value) in values.iter_mut().enumerate() {
                         let idx = start + i;
                         let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
@@ -382,24 +523,17 @@ impl<C: CurveAffine> Evaluator<C> {
 
                         // Enforce only for the first set.
                         // l_0(X) * (1 - z_0(X)) = 0
-                        *value = *value * y
-                            + ((one - first_set.permutation_product_coset[idx]) * l0[idx]);
+                        *value = *value * y + ((one - first_set[idx]) * l0[idx]);
                         // Enforce only for the last set.
                         // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
                         *value = *value * y
-                            + ((last_set.permutation_product_coset[idx]
-                                * last_set.permutation_product_coset[idx]
-                                - last_set.permutation_product_coset[idx])
-                                * l_last[idx]);
+                            + ((last_set[idx] * last_set[idx] - last_set[idx]) * l_last[idx]);
                         // Except for the first set, enforce.
                         // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
-                        for (set_idx, set) in sets.iter().enumerate() {
+                        for (set_idx, set) in product_cosets.iter().enumerate() {
                             if set_idx != 0 {
                                 *value = *value * y
-                                    + ((set.permutation_product_coset[idx]
-                                        - permutation.sets[set_idx - 1].permutation_product_coset
-                                            [r_last])
-                                        * l0[idx]);
+                                    + ((set[idx] - product_cosets[set_idx - 1][r_last]) * l0[idx]);
                             }
                         }
                         // And for all the sets we enforce:
@@ -408,12 +542,12 @@ impl<C: CurveAffine> Evaluator<C> {
                         // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
                         // )
                         let mut current_delta = delta_start * beta_term;
-                        for ((set, columns), cosets) in sets
+                        for ((set, columns), cosets) in product_cosets
                             .iter()
                             .zip(p.columns.chunks(chunk_len))
-                            .zip(pk.permutation.cosets.chunks(chunk_len))
+                            .zip(permutation_cosets.chunks(chunk_len))
                         {
-                            let mut left = set.permutation_product_coset[r_next];
+                            let mut left = set[r_next];
                             for (values, permutation) in columns
                                 .iter()
                                 .map(|&column| match column.column_type() {
@@ -426,7 +560,7 @@ impl<C: CurveAffine> Evaluator<C> {
                                 left *= values[idx] + beta * permutation[idx] + gamma;
                             }
 
-                            let mut right = set.permutation_product_coset[idx];
+                            let mut right = set[idx];
                             for values in columns.iter().map(|&column| match column.column_type() {
                                 Any::Advice(_) => &advice[column.index()],
                                 Any::Fixed => &fixed[column.index()],
@@ -438,7 +572,7 @@ impl<C: CurveAffine> Evaluator<C> {
 
                             *value = *value * y + ((left - right) * l_active_row[idx]);
                         }
-                        beta_term *= &extended_omega;
+                        beta_term *= &omega;
                     }
                 });
             }
@@ -448,18 +582,18 @@ impl<C: CurveAffine> Evaluator<C> {
                 // Polynomials required for this lookup.
                 // Calculated here so these only have to be kept in memory for the short time
                 // they are actually needed.
-                let product_coset = pk.vk.domain.coeff_to_extended(lookup.product_poly.clone());
-                let permuted_input_coset = pk
-                    .vk
-                    .domain
-                    .coeff_to_extended(lookup.permuted_input_poly.clone());
-                let permuted_table_coset = pk
-                    .vk
-                    .domain
-                    .coeff_to_extended(lookup.permuted_table_poly.clone());
+                let (product_coset, (permuted_input_coset, permuted_table_coset)) = multicore::join(
+                    || coset(&lookup.product_poly),
+                    || {
+                        multicore::join(
+                            || coset(&lookup.permuted_input_poly),
+                            || coset(&lookup.permuted_table_poly),
+                        )
+                    },
+                );
 
                 // Lookup constraints
-                parallelize(&mut values, |values, start| {
+                parallelize(values, |values, start| {
                     let lookup_evaluator = &self.lookups[n];
                     let mut eval_data = lookup_evaluator.instance();
                     for (i, value) in values.iter_mut().enumerate() {
@@ -517,8 +651,73 @@ impl<C: CurveAffine> Evaluator<C> {
                     }
                 });
             }
+
+            // Shuffles
+            for (n, shuffle) in shuffles.iter().enumerate() {
+                // The product polynomial of this shuffle.
+                // Calculated here so it only has to be kept in memory for the short time
+                // it is actually needed.
+                let product_coset = coset(&shuffle.product_poly);
+
+                // Shuffle constraints
+                parallelize(values, |values, start| {
+                    let (input_evaluator, shuffle_evaluator) = &self.shuffle_compressions[n];
+                    let mut input_data = input_evaluator.instance();
+                    let mut shuffle_data = shuffle_evaluator.instance();
+                    for (i, value) in values.iter_mut().enumerate() {
+                        let idx = start + i;
+
+                        let input_value = input_evaluator.evaluate(
+                            &mut input_data,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            &C::ScalarExt::zero(),
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+                        let shuffle_value = shuffle_evaluator.evaluate(
+                            &mut shuffle_data,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            &C::ScalarExt::zero(),
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+
+                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+
+                        // l_0(X) * (1 - z(X)) = 0
+                        *value = *value * y + ((one - product_coset[idx]) * l0[idx]);
+                        // l_last(X) * (z(X)^2 - z(X)) = 0
+                        *value = *value * y
+                            + ((product_coset[idx] * product_coset[idx] - product_coset[idx])
+                                * l_last[idx]);
+                        // (1 - (l_last(X) + l_blind(X))) * (
+                        //   z(\omega X) (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+                        //   - z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \gamma)
+                        // ) = 0
+                        *value = *value * y
+                            + ((product_coset[r_next] * (shuffle_value + gamma)
+                                - product_coset[idx] * (input_value + gamma))
+                                * l_active_row[idx]);
+                    }
+                });
+            }
         }
-        values
     }
 }
 
@@ -539,6 +738,21 @@ impl<C: CurveAffine> Default for GraphEvaluator<C> {
 }
 
 impl<C: CurveAffine> GraphEvaluator<C> {
+    /// Compiles the combination of `expressions` by powers of theta
+    fn compress(expressions: &[Expression<C::ScalarExt>]) -> Self {
+        let mut graph = GraphEvaluator::default();
+        let parts = expressions
+            .iter()
+            .map(|expr| graph.add_expression(expr))
+            .collect();
+        graph.add_calculation(Calculation::Horner(
+            ValueSource::Constant(0),
+            parts,
+            ValueSource::Theta(),
+        ));
+        graph
+    }
+
     /// Adds a rotation
     fn add_rotation(&mut self, rotation: &Rotation) -> usize {
         let position = self.rotations.iter().position(|&c| c == rotation.0);
@@ -743,45 +957,85 @@ impl<C: CurveAffine> GraphEvaluator<C> {
             C::ScalarExt::zero()
         }
     }
+
+    /// Evaluates the graph on every row of polynomials in the Lagrange basis
+    pub fn evaluate_lagrange(
+        &self,
+        size: usize,
+        fixed: &[Polynomial<C::ScalarExt, LagrangeCoeff>],
+        advice: &[Polynomial<C::ScalarExt, LagrangeCoeff>],
+        instance: &[Polynomial<C::ScalarExt, LagrangeCoeff>],
+        challenges: &[C::ScalarExt],
+        theta: &C::ScalarExt,
+    ) -> Vec<C::ScalarExt> {
+        let zero = C::ScalarExt::zero();
+        let mut values = vec![zero; size];
+        let isize = size as i32;
+        parallelize(&mut values, |values, start| {
+            let mut data = self.instance();
+            for (i, value) in values.iter_mut().enumerate() {
+                *value = self.evaluate(
+                    &mut data,
+                    fixed,
+                    advice,
+                    instance,
+                    challenges,
+                    &zero,
+                    &zero,
+                    theta,
+                    &zero,
+                    &zero,
+                    start + i,
+                    1,
+                    isize,
+                );
+            }
+        });
+        values
+    }
 }
 
-/// Simple evaluation of an expression
-pub fn evaluate<F: FieldExt, B: Basis>(
-    expression: &Expression<F>,
-    size: usize,
-    rot_scale: i32,
-    fixed: &[Polynomial<F, B>],
-    advice: &[Polynomial<F, B>],
-    instance: &[Polynomial<F, B>],
-    challenges: &[F],
-) -> Vec<F> {
-    let mut values = vec![F::zero(); size];
-    let isize = size as i32;
-    parallelize(&mut values, |values, start| {
-        for (i, value) in values.iter_mut().enumerate() {
-            let idx = start + i;
-            *value = expression.evaluate(
-                &|scalar| scalar,
-                &|_| panic!("virtual selectors are removed during optimization"),
-                &|query| {
-                    fixed[query.column_index]
-                        [get_rotation_idx(idx, query.rotation.0, rot_scale, isize)]
-                },
-                &|query| {
-                    advice[query.column_index]
-                        [get_rotation_idx(idx, query.rotation.0, rot_scale, isize)]
-                },
-                &|query| {
-                    instance[query.column_index]
-                        [get_rotation_idx(idx, query.rotation.0, rot_scale, isize)]
-                },
-                &|challenge| challenges[challenge.index()],
-                &|a| -a,
-                &|a, b| a + &b,
-                &|a, b| a * b,
-                &|a, scalar| a * scalar,
-            );
+#[cfg(test)]
+mod tests {
+    use super::Evaluator;
+    use crate::plonk::ConstraintSystem;
+    use crate::poly::{EvaluationDomain, Rotation};
+    use group::ff::Field;
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    #[test]
+    fn lookup_compressions_match_expressions() {
+        const K: u32 = 3;
+        let n = 1 << K;
+
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let f = meta.fixed_column();
+        meta.lookup_any("lookup", |meta| {
+            let a_cur = meta.query_advice(a, Rotation::cur());
+            let f_next = meta.query_fixed(f, Rotation::next());
+            vec![
+                (a_cur.clone() * a_cur.clone() + f_next.clone(), f_next),
+                (-a_cur, meta.query_fixed(f, Rotation::prev())),
+            ]
+        });
+        let ev = Evaluator::<EqAffine>::new(&meta);
+
+        let domain = EvaluationDomain::<Fp>::new(1, K);
+        let advice = vec![domain.lagrange_from_vec((0..n).map(|i| Fp::from(i + 3)).collect())];
+        let fixed = vec![domain.lagrange_from_vec((0..n).map(|i| Fp::from(i * 7)).collect())];
+        let theta = Fp::from(5);
+
+        let (input, table) = &ev.lookup_compressions[0];
+        let input = input.evaluate_lagrange(n as usize, &fixed, &advice, &[], &[], &theta);
+        let table = table.evaluate_lagrange(n as usize, &fixed, &advice, &[], &[], &theta);
+        for row in 0..n as usize {
+            let (next, prev) = ((row + 1) % n as usize, (row + n as usize - 1) % n as usize);
+            let expected_input =
+                (advice[0][row].square() + fixed[0][next]) * theta - advice[0][row];
+            let expected_table = fixed[0][next] * theta + fixed[0][prev];
+            assert_eq!(input[row], expected_input);
+            assert_eq!(table[row], expected_table);
         }
-    });
-    values
+    }
 }
@@ -0,0 +1,461 @@
+//! Experimental support for prototyping folding schemes over halo2 circuits.
+//!
+//! A [`RelaxedConstraintSystem`] is the custom-gate part of a circuit's
+//! [`ConstraintSystem`] in the relaxed form used by Nova-style folding. Each
+//! constraint polynomial `G` is homogenized with a slack variable `u`, so that
+//! it has the same degree `d` in every monomial, and is compared against an
+//! error column `E` instead of zero:
+//!
+//! ```text
+//! G(u, w) = E
+//! ```
+//!
+//! Here `w` are the folded values: advice cells, instance cells and
+//! challenges. Fixed cells and selectors are shared by every instance of the
+//! circuit, so they are treated as constants. Two relaxed instances are folded
+//! with a random `r` as
+//!
+//! ```text
+//! u = u_1 + r u_2,  w = w_1 + r w_2,  E = E_1 + r T_1 + ... + r^{d-1} T_{d-1} + r^d E_2
+//! ```
+//!
+//! where the cross terms `T_i` are given by [`RelaxedConstraintSystem::cross_terms`].
+//! Everything here operates on assignments in the clear. A folding scheme would
+//! commit to the advice columns, the error columns and the cross terms, and fold
+//! those commitments with the same `r`.
+//!
+//! Lookup and permutation arguments are not included in the relaxed form.
+
+use ff::Field;
+
+use super::{Circuit, ConstraintSystem, Expression};
+use crate::arithmetic::{lagrange_interpolate, FieldExt};
+
+/// A custom gate in relaxed form.
+#[derive(Clone, Debug)]
+pub struct RelaxedGate<F: Field> {
+    name: &'static str,
+    polynomials: Vec<Expression<F>>,
+    degrees: Vec<usize>,
+}
+
+impl<F: Field> RelaxedGate<F> {
+    /// Returns the name of the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the constraint polynomials of the gate, before homogenization.
+    pub fn polynomials(&self) -> &[Expression<F>] {
+        &self.polynomials
+    }
+
+    /// Returns the degree of each constraint polynomial in the folded values,
+    /// which is the degree of its homogenization. Constraints that do not
+    /// depend on the folded values are treated as having degree 1.
+    pub fn degrees(&self) -> &[usize] {
+        &self.degrees
+    }
+}
+
+/// A satisfying assignment of a [`RelaxedConstraintSystem`].
+///
+/// The error columns are indexed by constraint, in the order given by
+/// [`RelaxedConstraintSystem::gates`], and then by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedInstance<F: Field> {
+    /// The slack variable.
+    pub u: F,
+    /// The advice columns.
+    pub advice: Vec<Vec<F>>,
+    /// The instance columns.
+    pub instance: Vec<Vec<F>>,
+    /// The values of the challenges.
+    pub challenges: Vec<F>,
+    /// The error columns.
+    pub error: Vec<Vec<F>>,
+}
+
+/// The fixed part of a circuit's assignment, which is shared by every
+/// instance being folded.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedAssignment<'a, F: Field> {
+    /// The fixed columns.
+    pub fixed: &'a [Vec<F>],
+    /// The selectors, indexed by selector and then by row.
+    pub selectors: &'a [Vec<bool>],
+}
+
+/// The custom gates of a circuit in relaxed form. See the
+/// [module documentation](self) for details.
+#[derive(Clone, Debug)]
+pub struct RelaxedConstraintSystem<F: Field> {
+    gates: Vec<RelaxedGate<F>>,
+}
+
+impl<F: FieldExt> RelaxedConstraintSystem<F> {
+    /// Extracts the relaxed form of the given circuit's custom gates.
+    pub fn new<C: Circuit<F>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        let _ = C::configure(&mut cs);
+        Self::from_constraint_system(&cs)
+    }
+
+    /// Extracts the relaxed form of the custom gates in `cs`, which must not
+    /// have had its selectors compressed into fixed columns.
+    pub fn from_constraint_system(cs: &ConstraintSystem<F>) -> Self {
+        let gates = cs
+            .gates
+            .iter()
+            .map(|gate| RelaxedGate {
+                name: gate.name(),
+                polynomials: gate.polynomials().to_vec(),
+                degrees: gate
+                    .polynomials()
+                    .iter()
+                    .map(|poly| folded_degree(poly).max(1))
+                    .collect(),
+            })
+            .collect();
+        RelaxedConstraintSystem { gates }
+    }
+
+    /// Returns the gates in relaxed form.
+    pub fn gates(&self) -> &[RelaxedGate<F>] {
+        &self.gates
+    }
+
+    /// Returns the maximum degree of the constraints in the folded values, which
+    /// is the number of cross terms plus one.
+    pub fn degree(&self) -> usize {
+        self.constraints()
+            .map(|(_, degree)| degree)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn constraints(&self) -> impl Iterator<Item = (&Expression<F>, usize)> {
+        self.gates
+            .iter()
+            .flat_map(|gate| gate.polynomials.iter().zip(gate.degrees.iter().copied()))
+    }
+
+    /// Relaxes a (not necessarily satisfying) assignment of the circuit by
+    /// setting `u = 1` and `E` to the value of each constraint. `E` is zero if
+    /// and only if the assignment satisfies every custom gate.
+    pub fn relax(
+        &self,
+        fixed: FixedAssignment<'_, F>,
+        advice: Vec<Vec<F>>,
+        instance: Vec<Vec<F>>,
+        challenges: Vec<F>,
+    ) -> RelaxedInstance<F> {
+        let mut relaxed = RelaxedInstance {
+            u: F::one(),
+            advice,
+            instance,
+            challenges,
+            error: vec![],
+        };
+        relaxed.error = self.evaluate(fixed, &relaxed);
+        relaxed
+    }
+
+    /// Returns `true` if `relaxed` satisfies the relaxed constraints.
+    pub fn is_satisfied(
+        &self,
+        fixed: FixedAssignment<'_, F>,
+        relaxed: &RelaxedInstance<F>,
+    ) -> bool {
+        self.evaluate(fixed, relaxed) == relaxed.error
+    }
+
+    /// Evaluates the homogenized constraints over every row of `relaxed`.
+    fn evaluate(&self, fixed: FixedAssignment<'_, F>, relaxed: &RelaxedInstance<F>) -> Vec<Vec<F>> {
+        let n = rows(relaxed);
+        self.constraints()
+            .map(|(poly, degree)| {
+                (0..n)
+                    .map(|row| {
+                        evaluate_homogenized(
+                            poly,
+                            degree,
+                            relaxed.u,
+                            fixed,
+                            &|column, rotation| relaxed.advice[column][rotate(row, rotation, n)],
+                            &|column, rotation| relaxed.instance[column][rotate(row, rotation, n)],
+                            &|index| relaxed.challenges[index],
+                            row,
+                            n,
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the cross terms `T_1, ..., T_{d-1}` of each constraint when
+    /// folding `lhs` with `rhs`, indexed by constraint, then by power of `r`
+    /// (starting from `T_1`) and then by row.
+    pub fn cross_terms(
+        &self,
+        fixed: FixedAssignment<'_, F>,
+        lhs: &RelaxedInstance<F>,
+        rhs: &RelaxedInstance<F>,
+    ) -> Vec<Vec<Vec<F>>> {
+        let n = rows(lhs);
+        assert_eq!(n, rows(rhs));
+
+        self.constraints()
+            .map(|(poly, degree)| {
+                // G(u_1 + X u_2, w_1 + X w_2) has degree `degree` in X; recover
+                // its coefficients by interpolating it at X = 0, ..., degree.
+                let points: Vec<F> = (0..=degree as u64).map(F::from).collect();
+                let mut terms = vec![vec![F::zero(); n]; degree - 1];
+                for row in 0..n {
+                    let evals: Vec<F> = points
+                        .iter()
+                        .map(|&x| {
+                            evaluate_homogenized(
+                                poly,
+                                degree,
+                                lhs.u + x * rhs.u,
+                                fixed,
+                                &|column, rotation| {
+                                    let row = rotate(row, rotation, n);
+                                    lhs.advice[column][row] + x * rhs.advice[column][row]
+                                },
+                                &|column, rotation| {
+                                    let row = rotate(row, rotation, n);
+                                    lhs.instance[column][row] + x * rhs.instance[column][row]
+                                },
+                                &|index| lhs.challenges[index] + x * rhs.challenges[index],
+                                row,
+                                n,
+                            )
+                        })
+                        .collect();
+                    let coeffs = lagrange_interpolate(&points, &evals);
+                    for (term, coeff) in terms.iter_mut().zip(coeffs.into_iter().skip(1)) {
+                        term[row] = coeff;
+                    }
+                }
+                terms
+            })
+            .collect()
+    }
+
+    /// Folds `lhs` and `rhs` with the challenge `r`, given the cross terms
+    /// returned by [`RelaxedConstraintSystem::cross_terms`].
+    pub fn fold(
+        &self,
+        lhs: &RelaxedInstance<F>,
+        rhs: &RelaxedInstance<F>,
+        cross_terms: &[Vec<Vec<F>>],
+        r: F,
+    ) -> RelaxedInstance<F> {
+        let fold_columns = |lhs: &[Vec<F>], rhs: &[Vec<F>]| -> Vec<Vec<F>> {
+            lhs.iter()
+                .zip(rhs.iter())
+                .map(|(lhs, rhs)| fold_values(lhs, rhs, r))
+                .collect()
+        };
+
+        let error = self
+            .constraints()
+            .zip(cross_terms.iter())
+            .enumerate()
+            .map(|(i, ((_, degree), terms))| {
+                let mut error = lhs.error[i].clone();
+                let mut power = r;
+                for term in terms {
+                    for (error, term) in error.iter_mut().zip(term.iter()) {
+                        *error += power * term;
+                    }
+                    power *= r;
+                }
+                debug_assert_eq!(power, r.pow_vartime(&[degree as u64]));
+                for (error, rhs) in error.iter_mut().zip(rhs.error[i].iter()) {
+                    *error += power * rhs;
+                }
+                error
+            })
+            .collect();
+
+        RelaxedInstance {
+            u: lhs.u + r * rhs.u,
+            advice: fold_columns(&lhs.advice, &rhs.advice),
+            instance: fold_columns(&lhs.instance, &rhs.instance),
+            challenges: fold_values(&lhs.challenges, &rhs.challenges, r),
+            error,
+        }
+    }
+}
+
+fn fold_values<F: Field>(lhs: &[F], rhs: &[F], r: F) -> Vec<F> {
+    lhs.iter()
+        .zip(rhs.iter())
+        .map(|(lhs, rhs)| *lhs + r * rhs)
+        .collect()
+}
+
+fn rows<F: Field>(relaxed: &RelaxedInstance<F>) -> usize {
+    relaxed
+        .error
+        .first()
+        .or_else(|| relaxed.advice.first())
+        .or_else(|| relaxed.instance.first())
+        .map(|column| column.len())
+        .unwrap_or(0)
+}
+
+fn rotate(row: usize, rotation: i32, n: usize) -> usize {
+    (row as i64 + rotation as i64).rem_euclid(n as i64) as usize
+}
+
+/// Returns the degree of an expression in the advice cells, instance cells and
+/// challenges.
+fn folded_degree<F: Field>(expression: &Expression<F>) -> usize {
+    expression.evaluate(
+        &|_| 0,
+        &|_| 0,
+        &|_| 0,
+        &|_| 1,
+        &|_| 1,
+        &|_| 1,
+        &|a| a,
+        &|a, b| a.max(b),
+        &|a, b| a + b,
+        &|a, _| a,
+    )
+}
+
+/// Evaluates the homogenization of an expression to the given degree at a row.
+#[allow(clippy::too_many_arguments)]
+fn evaluate_homogenized<F: Field>(
+    expression: &Expression<F>,
+    degree: usize,
+    u: F,
+    fixed: FixedAssignment<'_, F>,
+    advice: &impl Fn(usize, i32) -> F,
+    instance: &impl Fn(usize, i32) -> F,
+    challenge: &impl Fn(usize) -> F,
+    row: usize,
+    n: usize,
+) -> F {
+    // Multiply a lower-degree term by a power of `u` to raise it to the given
+    // degree.
+    let pad = |(value, from): (F, usize), to: usize| value * u.pow_vartime(&[(to - from) as u64]);
+
+    let value = expression.evaluate(
+        &|scalar| (scalar, 0),
+        &|selector| {
+            let enabled = fixed.selectors[selector.0][row];
+            (if enabled { F::one() } else { F::zero() }, 0)
+        },
+        &|query| {
+            let row = rotate(row, query.rotation.0, n);
+            (fixed.fixed[query.column_index][row], 0)
+        },
+        &|query| (advice(query.column_index, query.rotation.0), 1),
+        &|query| (instance(query.column_index, query.rotation.0), 1),
+        &|c| (challenge(c.index()), 1),
+        &|(a, degree)| (-a, degree),
+        &|a, b| {
+            let degree = a.1.max(b.1);
+            (pad(a, degree) + pad(b, degree), degree)
+        },
+        &|a, b| (a.0 * b.0, a.1 + b.1),
+        &|(a, degree), scalar| (a * scalar, degree),
+    );
+    pad(value, degree)
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2curves::pasta::Fp;
+    use rand_core::OsRng;
+
+    use super::{FixedAssignment, RelaxedConstraintSystem};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Circuit, ConstraintSystem, Error},
+        poly::Rotation,
+    };
+
+    #[derive(Clone, Default)]
+    struct MulCircuit;
+
+    impl Circuit<Fp> for MulCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.fixed_column();
+            let s = meta.selector();
+
+            // a * b + c = a_next, which is not homogeneous in the advice cells.
+            meta.create_gate("mul-add", |meta| {
+                let a_cur = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_fixed(c, Rotation::cur());
+                let a_next = meta.query_advice(a, Rotation::next());
+                let s = meta.query_selector(s);
+                vec![s * (a_cur * b + c - a_next)]
+            });
+        }
+
+        fn synthesize(&self, _: (), _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fold_satisfying_instances() {
+        const N: usize = 4;
+        let relaxed_cs = RelaxedConstraintSystem::<Fp>::new::<MulCircuit>();
+        assert_eq!(relaxed_cs.gates()[0].degrees(), &[2]);
+        assert_eq!(relaxed_cs.degree(), 2);
+
+        let fixed_columns = vec![(0..N as u64).map(Fp::from).collect::<Vec<_>>()];
+        let selectors = vec![vec![true, true, true, false]];
+        let fixed = FixedAssignment {
+            fixed: &fixed_columns,
+            selectors: &selectors,
+        };
+
+        let witness = || {
+            let b: Vec<Fp> = (0..N).map(|_| Fp::random(OsRng)).collect();
+            let mut a = vec![Fp::random(OsRng)];
+            for row in 0..N - 1 {
+                a.push(a[row] * b[row] + fixed_columns[0][row]);
+            }
+            relaxed_cs.relax(fixed, vec![a, b], vec![], vec![])
+        };
+        let lhs = witness();
+        let rhs = witness();
+        assert!(lhs.error.iter().flatten().all(|e| bool::from(e.is_zero())));
+        assert!(relaxed_cs.is_satisfied(fixed, &lhs));
+
+        let cross_terms = relaxed_cs.cross_terms(fixed, &lhs, &rhs);
+        let folded = relaxed_cs.fold(&lhs, &rhs, &cross_terms, Fp::random(OsRng));
+        assert!(relaxed_cs.is_satisfied(fixed, &folded));
+
+        // Folding again with a relaxed instance also works.
+        let cross_terms = relaxed_cs.cross_terms(fixed, &folded, &lhs);
+        let folded = relaxed_cs.fold(&folded, &lhs, &cross_terms, Fp::random(OsRng));
+        assert!(relaxed_cs.is_satisfied(fixed, &folded));
+
+        // Tampering with a witness is caught.
+        let mut bad = folded;
+        bad.advice[1][0] += Fp::one();
+        assert!(!relaxed_cs.is_satisfied(fixed, &bad));
+    }
+}
@@ -10,12 +10,12 @@ use super::{
         Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
         Selector,
     },
-    evaluation::Evaluator,
-    permutation, Assigned, Challenge, Error, Expression, LagrangeCoeff, Polynomial, ProvingKey,
-    VerifyingKey,
+    permutation, Assigned, Challenge, Error, Expression, LagrangeCoeff, Polynomial, VerifyingKey,
 };
+#[cfg(feature = "prover")]
+use super::{evaluation::Evaluator, ProvingKey};
 use crate::{
-    arithmetic::{parallelize, CurveAffine},
+    arithmetic::{parallelize, CurveAffine, FieldExt},
     circuit::Value,
     poly::{
         batch_invert_assigned,
@@ -35,7 +35,7 @@ where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let mut cs = ConstraintSystem::default();
+    let mut cs = ConstraintSystem::with_k(k);
     let config = ConcreteCircuit::configure(&mut cs);
 
     let degree = cs.degree();
@@ -47,7 +47,7 @@ where
 
 /// Assembly to be used in circuit synthesis.
 #[derive(Debug)]
-struct Assembly<F: Field> {
+pub(crate) struct Assembly<F: Field> {
     k: u32,
     fixed: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
     permutation: permutation::keygen::Assembly,
@@ -76,7 +76,7 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         self.selectors[selector.0][row] = true;
@@ -86,7 +86,7 @@ impl<F: Field> Assignment<F> for Assembly<F> {
 
     fn query_instance(&self, _: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, row));
         }
 
         // There is no instance in this context.
@@ -106,17 +106,22 @@ impl<F: Field> Assignment<F> for Assembly<F> {
 
     fn assign_fixed(&mut self, column: Column<Fixed>, row: usize, to: Assigned<F>) {
         if !self.usable_rows.contains(&row) {
-            panic!(
-                "Assign Fixed {:?}",
-                Error::not_enough_rows_available(self.k)
-            );
+            panic!("Assign Fixed {:?}", Error::row_not_available(self.k, row));
         }
 
         *self
             .fixed
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .unwrap_or_else(|| panic!("{:?}", Error::BoundsFailure)) = to;
+            .unwrap_or_else(|| {
+                panic!(
+                    "{:?}",
+                    Error::BoundsFailure {
+                        column: column.into(),
+                        row
+                    }
+                )
+            }) = to;
     }
 
     fn copy(
@@ -126,8 +131,10 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         right_column: Column<Any>,
         right_row: usize,
     ) {
-        if !self.usable_rows.contains(&left_row) || !self.usable_rows.contains(&right_row) {
-            panic!("{:?}", Error::not_enough_rows_available(self.k));
+        for row in [left_row, right_row] {
+            if !self.usable_rows.contains(&row) {
+                panic!("{:?}", Error::row_not_available(self.k, row));
+            }
         }
 
         self.permutation
@@ -142,15 +149,18 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         to: Value<Assigned<F>>,
     ) -> Result<(), Error> {
         if !self.usable_rows.contains(&from_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_available(self.k, from_row));
         }
 
         let col = self
             .fixed
             .get_mut(column.index())
-            .ok_or(Error::BoundsFailure)?;
+            .ok_or(Error::BoundsFailure {
+                column: column.into(),
+                row: from_row,
+            })?;
 
-        let filler = to.assign()?;
+        let filler = to.assign(column, from_row)?;
         for row in self.usable_rows.clone().skip(from_row) {
             col[row] = filler;
         }
@@ -175,7 +185,187 @@ impl<F: Field> Assignment<F> for Assembly<F> {
     }
 }
 
+/// Assembly that only records how many rows a circuit uses.
+#[derive(Debug, Default)]
+struct RowCounter {
+    rows: usize,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        // Instance rows are counted when they are copied into the circuit.
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<'r, 'v>(
+        &'r mut self,
+        _: Column<Advice>,
+        row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<Value<&'v Assigned<F>>, Error> {
+        self.touch(row);
+        Ok(Value::unknown())
+    }
+
+    fn assign_fixed(&mut self, _: Column<Fixed>, row: usize, _: Assigned<F>) {
+        self.touch(row);
+    }
+
+    fn copy(&mut self, _: Column<Any>, left_row: usize, _: Column<Any>, right_row: usize) {
+        self.touch(left_row);
+        self.touch(right_row);
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        from_row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.touch(from_row);
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+/// Returns the smallest `k` for which `circuit` fits in `2^k` rows.
+///
+/// The circuit is synthesized without storing any assignments, and `k` is
+/// chosen so that there are at least [`ConstraintSystem::minimum_rows`] rows,
+/// and every row on which the circuit assigns a cell, enables a selector or
+/// copies a cell is [usable](ConstraintSystem::usable_rows). Circuits whose
+/// configuration or layout depends on [`ConstraintSystem::k`] are measured
+/// again at the chosen `k`, until they fit.
+pub fn circuit_k<F, ConcreteCircuit>(circuit: &ConcreteCircuit) -> Result<u32, Error>
+where
+    F: FieldExt,
+    ConcreteCircuit: Circuit<F>,
+{
+    let mut k = 1;
+    loop {
+        let mut cs = ConstraintSystem::with_k(k);
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut counter = RowCounter::default();
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut counter,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )?;
+
+        let rows = counter.rows + cs.unusable_rows();
+        let required_k = cs.minimum_k().max(usize::BITS - (rows - 1).leading_zeros());
+        if required_k <= k {
+            return Ok(k);
+        }
+        if required_k > F::S {
+            return Err(Error::not_enough_rows_available(F::S));
+        }
+        k = required_k;
+    }
+}
+
+/// Generates a `ProvingKey` for `circuit`, measuring the `k` it needs with
+/// [`circuit_k`].
+///
+/// `params` only need to support at least that `k`, and are downsized to it in
+/// place, so that they can then be used to create and verify proofs with the
+/// returned key. Use [`keygen_with_k_hint`] to choose `k` instead.
+///
+/// Returns [`Error::ParamsTooSmall`] if the circuit needs more rows than
+/// `params` support.
+#[cfg(feature = "prover")]
+pub fn keygen<'params, C, P, ConcreteCircuit>(
+    params: &mut P,
+    circuit: &ConcreteCircuit,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let k = circuit_k(circuit)?;
+    keygen_with_k_hint(params, k, circuit)
+}
+
+/// Generates a `ProvingKey` for `circuit` with `2^k` rows, downsizing `params`
+/// to `k` in place.
+///
+/// Returns [`Error::ParamsTooSmall`] if `params` support a smaller `k`, and
+/// [`Error::NotEnoughRowsAvailable`] if the circuit does not fit in `2^k` rows.
+#[cfg(feature = "prover")]
+pub fn keygen_with_k_hint<'params, C, P, ConcreteCircuit>(
+    params: &mut P,
+    k: u32,
+    circuit: &ConcreteCircuit,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    if params.k() < k {
+        return Err(Error::ParamsTooSmall {
+            params_k: params.k(),
+            required_k: k,
+        });
+    }
+    if params.k() > k {
+        params.downsize(k);
+    }
+
+    let vk = keygen_vk(params, circuit)?;
+    keygen_pk(params, vk, circuit)
+}
+
 /// Generate a `VerifyingKey` from an instance of `Circuit`.
+///
+/// This is available without the `prover` feature, so that verifiers can
+/// derive the key of a circuit themselves rather than trusting a serialized
+/// one. It never computes the much larger `ProvingKey`, which `keygen_pk`
+/// derives from this key when the `prover` feature is enabled.
 pub fn keygen_vk<'params, C, P, ConcreteCircuit>(
     params: &P,
     circuit: &ConcreteCircuit,
@@ -186,7 +376,25 @@ where
     ConcreteCircuit: Circuit<C::Scalar>,
 {
     let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params.k());
+    let constants = cs.constants.clone();
+
+    keygen_vk_with(params, domain, cs, |assembly| {
+        ConcreteCircuit::FloorPlanner::synthesize(assembly, circuit, config, constants)
+    })
+}
 
+/// Generates a `VerifyingKey` for the constraint system `cs`, with the fixed
+/// assignments, selectors and copy constraints made by `synthesize`.
+pub(crate) fn keygen_vk_with<'params, C, P>(
+    params: &P,
+    domain: EvaluationDomain<C::Scalar>,
+    cs: ConstraintSystem<C::Scalar>,
+    synthesize: impl FnOnce(&mut Assembly<C::Scalar>) -> Result<(), Error>,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
     if (params.n() as usize) < cs.minimum_rows() {
         return Err(Error::not_enough_rows_available(params.k()));
     }
@@ -196,20 +404,15 @@ where
         fixed: vec![domain.empty_lagrange_assigned(); cs.num_fixed_columns],
         permutation: permutation::keygen::Assembly::new(params.n() as usize, &cs.permutation),
         selectors: vec![vec![false; params.n() as usize]; cs.num_selectors],
-        usable_rows: 0..params.n() as usize - (cs.blinding_factors() + 1),
+        usable_rows: 0..params.n() as usize - cs.unusable_rows(),
         _marker: std::marker::PhantomData,
     };
 
     // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(
-        &mut assembly,
-        circuit,
-        config,
-        cs.constants.clone(),
-    )?;
+    synthesize(&mut assembly)?;
 
     let mut fixed = batch_invert_assigned(assembly.fixed);
-    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors.clone());
+    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors.clone())?;
     fixed.extend(
         selector_polys
             .into_iter()
@@ -225,16 +428,17 @@ where
         .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
         .collect();
 
-    Ok(VerifyingKey::from_parts(
+    VerifyingKey::from_parts(
         domain,
         fixed_commitments,
         permutation_vk,
         cs,
         assembly.selectors,
-    ))
+    )
 }
 
 /// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`.
+#[cfg(feature = "prover")]
 pub fn keygen_pk<'params, C, P, ConcreteCircuit>(
     params: &P,
     vk: VerifyingKey<C>,
@@ -245,11 +449,28 @@ where
     P: Params<'params, C>,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let mut cs = ConstraintSystem::default();
+    let mut cs = ConstraintSystem::with_k(params.k());
     let config = ConcreteCircuit::configure(&mut cs);
+    let constants = cs.constants.clone();
 
-    let cs = cs;
+    keygen_pk_with(params, vk, cs, |assembly| {
+        ConcreteCircuit::FloorPlanner::synthesize(assembly, circuit, config, constants)
+    })
+}
 
+/// Generates a `ProvingKey` for the constraint system `cs` from its `VerifyingKey`,
+/// with the fixed assignments, selectors and copy constraints made by `synthesize`.
+#[cfg(feature = "prover")]
+pub(crate) fn keygen_pk_with<'params, C, P>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    cs: ConstraintSystem<C::Scalar>,
+    synthesize: impl FnOnce(&mut Assembly<C::Scalar>) -> Result<(), Error>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+{
     if (params.n() as usize) < cs.minimum_rows() {
         return Err(Error::not_enough_rows_available(params.k()));
     }
@@ -259,20 +480,15 @@ where
         fixed: vec![vk.domain.empty_lagrange_assigned(); cs.num_fixed_columns],
         permutation: permutation::keygen::Assembly::new(params.n() as usize, &cs.permutation),
         selectors: vec![vec![false; params.n() as usize]; cs.num_selectors],
-        usable_rows: 0..params.n() as usize - (cs.blinding_factors() + 1),
+        usable_rows: 0..params.n() as usize - cs.unusable_rows(),
         _marker: std::marker::PhantomData,
     };
 
     // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(
-        &mut assembly,
-        circuit,
-        config,
-        cs.constants.clone(),
-    )?;
+    synthesize(&mut assembly)?;
 
     let mut fixed = batch_invert_assigned(assembly.fixed);
-    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
+    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors)?;
     fixed.extend(
         selector_polys
             .into_iter()
@@ -341,3 +557,49 @@ where
         ev,
     })
 }
+
+/// Reconstructs the `ProvingKey` of `circuit` from its `VerifyingKey`.
+///
+/// The proving key is a deterministic function of `params`, `vk` and the
+/// circuit, so provers can persist only the much smaller verifying key and
+/// regenerate the proving key when they need it. The verifying key is rebuilt
+/// from the regenerated fixed and permutation polynomials and compared with
+/// `vk`, so this returns [`Error::VerifyingKeyMismatch`] if `vk` was generated
+/// from another circuit or with other params.
+#[cfg(feature = "prover")]
+pub fn reconstruct_pk<'params, C, P, ConcreteCircuit>(
+    params: &P,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    P: Params<'params, C>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    if vk.domain.k() != params.k() {
+        return Err(Error::VerifyingKeyMismatch);
+    }
+    let pk = keygen_pk(params, vk, circuit)?;
+
+    let (domain, cs, _) = create_domain::<C, ConcreteCircuit>(params.k());
+    let (cs, _) = cs.compress_selectors(pk.vk.selectors.clone())?;
+    let fixed_commitments = pk
+        .fixed_values
+        .iter()
+        .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+        .collect();
+    let rebuilt = VerifyingKey::from_parts(
+        domain,
+        fixed_commitments,
+        pk.permutation.build_vk(params),
+        cs,
+        pk.vk.selectors.clone(),
+    )?
+    .with_label(&pk.vk.label);
+    if rebuilt.transcript_repr != pk.vk.transcript_repr {
+        return Err(Error::VerifyingKeyMismatch);
+    }
+
+    Ok(pk)
+}
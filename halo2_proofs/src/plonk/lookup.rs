@@ -2,6 +2,7 @@ use super::circuit::Expression;
 use ff::Field;
 use std::fmt::{self, Debug};
 
+#[cfg(feature = "prover")]
 pub(crate) mod prover;
 pub(crate) mod verifier;
 
@@ -34,6 +35,11 @@ impl<F: Field> Argument<F> {
         }
     }
 
+    /// Returns the name of the lookup argument.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
     pub(crate) fn required_degree(&self) -> usize {
         assert_eq!(self.input_expressions.len(), self.table_expressions.len());
 
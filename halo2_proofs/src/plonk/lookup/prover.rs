@@ -3,11 +3,11 @@ use super::super::{
     ProvingKey,
 };
 use super::Argument;
-use crate::plonk::evaluation::evaluate;
+use crate::plonk::evaluation::GraphEvaluator;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
     poly::{
-        commitment::{Blind, Params},
+        commitment::{Blind, Params, SecretBlind},
         Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, ProverQuery,
         Rotation,
     },
@@ -30,21 +30,21 @@ pub(in crate::plonk) struct Permuted<C: CurveAffine> {
     compressed_input_expression: Polynomial<C::Scalar, LagrangeCoeff>,
     permuted_input_expression: Polynomial<C::Scalar, LagrangeCoeff>,
     permuted_input_poly: Polynomial<C::Scalar, Coeff>,
-    permuted_input_blind: Blind<C::Scalar>,
+    permuted_input_blind: SecretBlind<C::Scalar>,
     compressed_table_expression: Polynomial<C::Scalar, LagrangeCoeff>,
     permuted_table_expression: Polynomial<C::Scalar, LagrangeCoeff>,
     permuted_table_poly: Polynomial<C::Scalar, Coeff>,
-    permuted_table_blind: Blind<C::Scalar>,
+    permuted_table_blind: SecretBlind<C::Scalar>,
 }
 
 #[derive(Debug)]
 pub(in crate::plonk) struct Committed<C: CurveAffine> {
     pub(in crate::plonk) permuted_input_poly: Polynomial<C::Scalar, Coeff>,
-    permuted_input_blind: Blind<C::Scalar>,
+    permuted_input_blind: SecretBlind<C::Scalar>,
     pub(in crate::plonk) permuted_table_poly: Polynomial<C::Scalar, Coeff>,
-    permuted_table_blind: Blind<C::Scalar>,
+    permuted_table_blind: SecretBlind<C::Scalar>,
     pub(in crate::plonk) product_poly: Polynomial<C::Scalar, Coeff>,
-    product_blind: Blind<C::Scalar>,
+    product_blind: SecretBlind<C::Scalar>,
 }
 
 pub(in crate::plonk) struct Evaluated<C: CurveAffine> {
@@ -72,6 +72,7 @@ impl<F: FieldExt> Argument<F> {
     >(
         &self,
         pk: &ProvingKey<C>,
+        compressions: &(GraphEvaluator<C>, GraphEvaluator<C>),
         params: &P,
         domain: &EvaluationDomain<C::Scalar>,
         theta: ChallengeTheta<C>,
@@ -86,32 +87,26 @@ impl<F: FieldExt> Argument<F> {
         C: CurveAffine<ScalarExt = F>,
         C::Curve: Mul<F, Output = C::Curve> + MulAssign<F>,
     {
-        // Closure to get values of expressions and compress them
-        let compress_expressions = |expressions: &[Expression<C::Scalar>]| {
-            let compressed_expression = expressions
-                .iter()
-                .map(|expression| {
-                    pk.vk.domain.lagrange_from_vec(evaluate(
-                        expression,
-                        params.n() as usize,
-                        1,
-                        fixed_values,
-                        advice_values,
-                        instance_values,
-                        challenges,
-                    ))
-                })
-                .fold(domain.empty_lagrange(), |acc, expression| {
-                    acc * *theta + &expression
-                });
-            compressed_expression
+        // Closure to get values of expressions and compress them, using the
+        // evaluators compiled from them at keygen
+        let compress_expressions = |compression: &GraphEvaluator<C>| {
+            pk.vk
+                .domain
+                .lagrange_from_vec(compression.evaluate_lagrange(
+                    params.n() as usize,
+                    fixed_values,
+                    advice_values,
+                    instance_values,
+                    challenges,
+                    &theta,
+                ))
         };
 
         // Get values of input expressions involved in the lookup and compress them
-        let compressed_input_expression = compress_expressions(&self.input_expressions);
+        let compressed_input_expression = compress_expressions(&compressions.0);
 
         // Get values of table expressions involved in the lookup and compress them
-        let compressed_table_expression = compress_expressions(&self.table_expressions);
+        let compressed_table_expression = compress_expressions(&compressions.1);
 
         // Permute compressed (InputExpression, TableExpression) pair
         let (permuted_input_expression, permuted_table_expression) = permute_expression_pair(
@@ -125,9 +120,12 @@ impl<F: FieldExt> Argument<F> {
 
         // Closure to construct commitment to vector of values
         let mut commit_values = |values: &Polynomial<C::Scalar, LagrangeCoeff>| {
-            let poly = pk.vk.domain.lagrange_to_coeff(values.clone());
-            let blind = Blind(C::Scalar::random(&mut rng));
-            let commitment = params.commit_lagrange(values, blind).to_affine();
+            let poly = pk
+                .vk
+                .domain
+                .lagrange_to_coeff_with(params.backend(), values.clone());
+            let blind = SecretBlind(Blind(C::Scalar::random(&mut rng)));
+            let commitment = params.commit_lagrange(values, *blind).to_affine();
             (poly, blind, commitment)
         };
 
@@ -205,7 +203,7 @@ impl<C: CurveAffine> Permuted<C> {
 
         // Batch invert to obtain the denominators for the lookup product
         // polynomials
-        lookup_product.iter_mut().batch_invert();
+        params.backend().batch_invert(&mut lookup_product);
 
         // Finish the computation of the entire fraction by computing the numerators
         // (\theta^{m-1} a_0(\omega^i) + \theta^{m-2} a_1(\omega^i) + ... + \theta a_{m-2}(\omega^i) + a_{m-1}(\omega^i) + \beta)
@@ -287,9 +285,9 @@ impl<C: CurveAffine> Permuted<C> {
             assert_eq!(z[u], C::Scalar::one());
         }
 
-        let product_blind = Blind(C::Scalar::random(rng));
-        let product_commitment = params.commit_lagrange(&z, product_blind).to_affine();
-        let z = pk.vk.domain.lagrange_to_coeff(z);
+        let product_blind = SecretBlind(Blind(C::Scalar::random(rng)));
+        let product_commitment = params.commit_lagrange(&z, *product_blind).to_affine();
+        let z = pk.vk.domain.lagrange_to_coeff_with(params.backend(), z);
 
         // Hash product commitment
         transcript.write_point(product_commitment)?;
@@ -351,31 +349,31 @@ impl<C: CurveAffine> Evaluated<C> {
             .chain(Some(ProverQuery {
                 point: *x,
                 poly: &self.constructed.product_poly,
-                blind: self.constructed.product_blind,
+                blind: *self.constructed.product_blind,
             }))
             // Open lookup input commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
                 poly: &self.constructed.permuted_input_poly,
-                blind: self.constructed.permuted_input_blind,
+                blind: *self.constructed.permuted_input_blind,
             }))
             // Open lookup table commitments at x
             .chain(Some(ProverQuery {
                 point: *x,
                 poly: &self.constructed.permuted_table_poly,
-                blind: self.constructed.permuted_table_blind,
+                blind: *self.constructed.permuted_table_blind,
             }))
             // Open lookup input commitments at x_inv
             .chain(Some(ProverQuery {
                 point: x_inv,
                 poly: &self.constructed.permuted_input_poly,
-                blind: self.constructed.permuted_input_blind,
+                blind: *self.constructed.permuted_input_blind,
             }))
             // Open lookup product commitments at x_next
             .chain(Some(ProverQuery {
                 point: x_next,
                 poly: &self.constructed.product_poly,
-                blind: self.constructed.product_blind,
+                blind: *self.constructed.product_blind,
             }))
     }
 }
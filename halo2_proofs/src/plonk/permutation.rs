@@ -1,16 +1,28 @@
 use super::circuit::{Any, Column};
 use crate::{
     arithmetic::CurveAffine,
-    helpers::{
-        polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice,
-        SerdeCurveAffine, SerdePrimeField,
+    helpers::{SerdeCurveAffine, SerdePrimeField},
+    poly::{
+        commitment::{Blind, Params},
+        Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial,
     },
-    poly::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial},
     SerdeFormat,
 };
 use ff::PrimeField;
+use group::Curve;
+
+#[cfg(feature = "prover")]
+use crate::helpers::{polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice};
+
+#[cfg(all(feature = "mmap", feature = "prover"))]
+use crate::{
+    helpers::{map_polynomial_vec, mapped_polynomial_slice},
+    poly::EvaluationDomain,
+    storage::MappedFile,
+};
 
 pub(crate) mod keygen;
+#[cfg(feature = "prover")]
 pub(crate) mod prover;
 pub(crate) mod verifier;
 
@@ -72,6 +84,7 @@ impl Argument {
         }
     }
 
+    /// Returns the columns involved in the argument.
     pub fn get_columns(&self) -> Vec<Column<Any>> {
         self.columns.clone()
     }
@@ -89,27 +102,28 @@ impl<C: CurveAffine> VerifyingKey<C> {
         &self.commitments
     }
 
-    pub(crate) fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat)
+    pub(crate) fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()>
     where
         C: SerdeCurveAffine,
     {
         for commitment in &self.commitments {
-            commitment.write(writer, format);
+            commitment.write(writer, format)?;
         }
+        Ok(())
     }
 
     pub(crate) fn read<R: io::Read>(
         reader: &mut R,
         argument: &Argument,
         format: SerdeFormat,
-    ) -> Self
+    ) -> io::Result<Self>
     where
         C: SerdeCurveAffine,
     {
         let commitments = (0..argument.columns.len())
             .map(|_| C::read(reader, format))
-            .collect();
-        VerifyingKey { commitments }
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(VerifyingKey { commitments })
     }
 
     pub(crate) fn bytes_length(&self) -> usize {
@@ -118,6 +132,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
 }
 
 /// The proving key for a single permutation argument.
+#[cfg(feature = "prover")]
 #[derive(Clone, Debug)]
 pub(crate) struct ProvingKey<C: CurveAffine> {
     permutations: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
@@ -125,31 +140,80 @@ pub(crate) struct ProvingKey<C: CurveAffine> {
     pub(super) cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
 }
 
+#[cfg(feature = "prover")]
 impl<C: SerdeCurveAffine> ProvingKey<C>
 where
     C::Scalar: SerdePrimeField,
 {
     /// Reads proving key for a single permutation argument from buffer using `Polynomial::read`.  
-    pub(super) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Self {
-        let permutations = read_polynomial_vec(reader, format);
-        let polys = read_polynomial_vec(reader, format);
-        let cosets = read_polynomial_vec(reader, format);
-        ProvingKey {
+    pub(super) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let permutations = read_polynomial_vec(reader, format)?;
+        let polys = read_polynomial_vec(reader, format)?;
+        let cosets = read_polynomial_vec(reader, format)?;
+        Ok(ProvingKey {
             permutations,
             polys,
             cosets,
-        }
+        })
     }
 
     /// Writes proving key for a single permutation argument to buffer using `Polynomial::write`.  
-    pub(super) fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) {
-        write_polynomial_slice(&self.permutations, writer, format);
-        write_polynomial_slice(&self.polys, writer, format);
-        write_polynomial_slice(&self.cosets, writer, format);
+    pub(super) fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        write_polynomial_slice(&self.permutations, writer, format)?;
+        write_polynomial_slice(&self.polys, writer, format)?;
+        write_polynomial_slice(&self.cosets, writer, format)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "mmap", feature = "prover"))]
+impl<C: CurveAffine> ProvingKey<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    /// Maps the proving key for a permutation argument over `num_columns` columns
+    /// of `domain` from consecutive sections of `file`, starting at `first`.
+    pub(super) fn read_mapped(
+        file: &MappedFile,
+        first: usize,
+        num_columns: usize,
+        domain: &EvaluationDomain<C::Scalar>,
+    ) -> io::Result<Self> {
+        let sections = |i: usize| first + i * num_columns..first + (i + 1) * num_columns;
+        let n = 1 << domain.k();
+        Ok(ProvingKey {
+            permutations: map_polynomial_vec(file, sections(0), n)?,
+            polys: map_polynomial_vec(file, sections(1), n)?,
+            cosets: map_polynomial_vec(file, sections(2), domain.extended_len())?,
+        })
+    }
+
+    /// Returns the in-memory representation of each polynomial, in the order
+    /// expected by [`Self::read_mapped`].
+    pub(super) fn mapped_sections(&self) -> impl Iterator<Item = &[u8]> {
+        mapped_polynomial_slice(&self.permutations)
+            .chain(mapped_polynomial_slice(&self.polys))
+            .chain(mapped_polynomial_slice(&self.cosets))
     }
 }
 
+#[cfg(feature = "prover")]
 impl<C: CurveAffine> ProvingKey<C> {
+    /// Commits to the permutation polynomials, rebuilding the verifying key of
+    /// the argument.
+    pub(super) fn build_vk<'params, P: Params<'params, C>>(&self, params: &P) -> VerifyingKey<C> {
+        let commitments = self
+            .permutations
+            .iter()
+            .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+            .collect();
+        VerifyingKey { commitments }
+    }
+
     /// Gets the total number of bytes in the serialization of `self`
     pub(super) fn bytes_length(&self) -> usize {
         polynomial_slice_byte_length(&self.permutations)
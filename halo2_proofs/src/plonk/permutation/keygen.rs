@@ -1,7 +1,9 @@
 use ff::Field;
 use group::Curve;
 
-use super::{Argument, ProvingKey, VerifyingKey};
+#[cfg(feature = "prover")]
+use super::ProvingKey;
+use super::{Argument, VerifyingKey};
 use crate::{
     arithmetic::{CurveAffine, FieldExt},
     plonk::{Any, Column, Error},
@@ -59,10 +61,13 @@ impl Assembly {
             .ok_or(Error::ColumnNotInPermutation(right_column))?;
 
         // Check bounds
-        if left_row >= self.mapping[left_column].len()
-            || right_row >= self.mapping[right_column].len()
-        {
-            return Err(Error::BoundsFailure);
+        for (column, row) in [(left_column, left_row), (right_column, right_row)] {
+            if row >= self.mapping[column].len() {
+                return Err(Error::BoundsFailure {
+                    column: self.columns[column],
+                    row,
+                });
+            }
         }
 
         // See book/src/design/permutation.md for a description of this algorithm.
@@ -150,6 +155,7 @@ impl Assembly {
         VerifyingKey { commitments }
     }
 
+    #[cfg(feature = "prover")]
     pub(crate) fn build_pk<'params, C: CurveAffine, P: Params<'params, C>>(
         self,
         params: &P,
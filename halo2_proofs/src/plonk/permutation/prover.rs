@@ -1,3 +1,4 @@
+use crate::multicore::prelude::*;
 use group::{
     ff::{BatchInvert, Field},
     Curve,
@@ -9,10 +10,11 @@ use super::super::{circuit::Any, ChallengeBeta, ChallengeGamma, ChallengeX};
 use super::{Argument, ProvingKey};
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
+    multicore,
     plonk::{self, Error},
     poly::{
         self,
-        commitment::{Blind, Params},
+        commitment::{Blind, Params, SecretBlind},
         Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
     },
     transcript::{EncodedChallenge, TranscriptWrite},
@@ -20,8 +22,7 @@ use crate::{
 
 pub(crate) struct CommittedSet<C: CurveAffine> {
     pub(crate) permutation_product_poly: Polynomial<C::Scalar, Coeff>,
-    pub(crate) permutation_product_coset: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    permutation_product_blind: Blind<C::Scalar>,
+    permutation_product_blind: SecretBlind<C::Scalar>,
 }
 
 pub(crate) struct Committed<C: CurveAffine> {
@@ -30,7 +31,7 @@ pub(crate) struct Committed<C: CurveAffine> {
 
 pub struct ConstructedSet<C: CurveAffine> {
     permutation_product_poly: Polynomial<C::Scalar, Coeff>,
-    permutation_product_blind: Blind<C::Scalar>,
+    permutation_product_blind: SecretBlind<C::Scalar>,
 }
 
 pub(crate) struct Constructed<C: CurveAffine> {
@@ -72,73 +73,84 @@ impl Argument {
         let chunk_len = pk.vk.cs_degree - 2;
         let blinding_factors = pk.vk.cs.blinding_factors();
 
-        // Each column gets its own delta power.
-        let mut deltaomega = C::Scalar::one();
+        // Compute the fractions of every set of columns in parallel, since the sets
+        // are independent of each other until their products are chained below.
+        let omega = domain.get_omega();
+        let n = params.n() as usize;
+        let modified_values: Vec<Vec<C::Scalar>> = self
+            .columns
+            .par_chunks(chunk_len)
+            .zip(pkey.permutations.par_chunks(chunk_len))
+            .enumerate()
+            .map(|(set, (columns, permutations))| {
+                // Goal is to compute the products of fractions
+                //
+                // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
+                // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
+                //
+                // where p_j(X) is the jth column in this permutation,
+                // and i is the ith row of the column.
+
+                let mut modified_values = vec![C::Scalar::one(); n];
+
+                // Iterate over each column of the permutation
+                for (&column, permuted_column_values) in columns.iter().zip(permutations.iter()) {
+                    let values = match column.column_type() {
+                        Any::Advice(_) => advice,
+                        Any::Fixed => fixed,
+                        Any::Instance => instance,
+                    };
+                    parallelize(&mut modified_values, |modified_values, start| {
+                        for ((modified_values, value), permuted_value) in modified_values
+                            .iter_mut()
+                            .zip(values[column.index()][start..].iter())
+                            .zip(permuted_column_values[start..].iter())
+                        {
+                            *modified_values *= &(*beta * permuted_value + &*gamma + value);
+                        }
+                    });
+                }
+
+                // Invert to obtain the denominator for the permutation product polynomial
+                params.backend().batch_invert(&mut modified_values);
+
+                // Each column gets its own delta power.
+                let mut deltaomega =
+                    C::Scalar::DELTA.pow_vartime(&[(set * chunk_len) as u64, 0, 0, 0]);
+
+                // Iterate over each column again, this time finishing the computation
+                // of the entire fraction by computing the numerators
+                for &column in columns.iter() {
+                    let values = match column.column_type() {
+                        Any::Advice(_) => advice,
+                        Any::Fixed => fixed,
+                        Any::Instance => instance,
+                    };
+                    parallelize(&mut modified_values, |modified_values, start| {
+                        let mut deltaomega =
+                            deltaomega * &omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                        for (modified_values, value) in modified_values
+                            .iter_mut()
+                            .zip(values[column.index()][start..].iter())
+                        {
+                            // Multiply by p_j(\omega^i) + \delta^j \omega^i \beta
+                            *modified_values *= &(deltaomega * &*beta + &*gamma + value);
+                            deltaomega *= &omega;
+                        }
+                    });
+                    deltaomega *= &C::Scalar::DELTA;
+                }
+
+                modified_values
+            })
+            .collect();
 
         // Track the "last" value from the previous column set
         let mut last_z = C::Scalar::one();
 
         let mut sets = vec![];
 
-        for (columns, permutations) in self
-            .columns
-            .chunks(chunk_len)
-            .zip(pkey.permutations.chunks(chunk_len))
-        {
-            // Goal is to compute the products of fractions
-            //
-            // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
-            // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
-            //
-            // where p_j(X) is the jth column in this permutation,
-            // and i is the ith row of the column.
-
-            let mut modified_values = vec![C::Scalar::one(); params.n() as usize];
-
-            // Iterate over each column of the permutation
-            for (&column, permuted_column_values) in columns.iter().zip(permutations.iter()) {
-                let values = match column.column_type() {
-                    Any::Advice(_) => advice,
-                    Any::Fixed => fixed,
-                    Any::Instance => instance,
-                };
-                parallelize(&mut modified_values, |modified_values, start| {
-                    for ((modified_values, value), permuted_value) in modified_values
-                        .iter_mut()
-                        .zip(values[column.index()][start..].iter())
-                        .zip(permuted_column_values[start..].iter())
-                    {
-                        *modified_values *= &(*beta * permuted_value + &*gamma + value);
-                    }
-                });
-            }
-
-            // Invert to obtain the denominator for the permutation product polynomial
-            modified_values.batch_invert();
-
-            // Iterate over each column again, this time finishing the computation
-            // of the entire fraction by computing the numerators
-            for &column in columns.iter() {
-                let omega = domain.get_omega();
-                let values = match column.column_type() {
-                    Any::Advice(_) => advice,
-                    Any::Fixed => fixed,
-                    Any::Instance => instance,
-                };
-                parallelize(&mut modified_values, |modified_values, start| {
-                    let mut deltaomega = deltaomega * &omega.pow_vartime(&[start as u64, 0, 0, 0]);
-                    for (modified_values, value) in modified_values
-                        .iter_mut()
-                        .zip(values[column.index()][start..].iter())
-                    {
-                        // Multiply by p_j(\omega^i) + \delta^j \omega^i \beta
-                        *modified_values *= &(deltaomega * &*beta + &*gamma + value);
-                        deltaomega *= &omega;
-                    }
-                });
-                deltaomega *= &C::Scalar::DELTA;
-            }
-
+        for modified_values in modified_values {
             // The modified_values vector is a vector of products of fractions
             // of the form
             //
@@ -149,14 +161,8 @@ impl Argument {
             // the permutation
 
             // Compute the evaluations of the permutation product polynomial
-            // over our domain, starting with z[0] = 1
-            let mut z = vec![last_z];
-            for row in 1..(params.n() as usize) {
-                let mut tmp = z[row - 1];
-
-                tmp *= &modified_values[row - 1];
-                z.push(tmp);
-            }
+            // over our domain, starting with z[0] = last_z
+            let z = running_product(last_z, &modified_values);
             let mut z = domain.lagrange_from_vec(z);
             // Set blinding factors
             for z in &mut z[params.n() as usize - blinding_factors..] {
@@ -165,14 +171,11 @@ impl Argument {
             // Set new last_z
             last_z = z[params.n() as usize - (blinding_factors + 1)];
 
-            let blind = Blind(C::Scalar::random(&mut rng));
+            let blind = SecretBlind(Blind(C::Scalar::random(&mut rng)));
 
-            let permutation_product_commitment_projective = params.commit_lagrange(&z, blind);
+            let permutation_product_commitment_projective = params.commit_lagrange(&z, *blind);
             let permutation_product_blind = blind;
-            let z = domain.lagrange_to_coeff(z);
-            let permutation_product_poly = z.clone();
-
-            let permutation_product_coset = domain.coeff_to_extended(z.clone());
+            let permutation_product_poly = domain.lagrange_to_coeff_with(params.backend(), z);
 
             let permutation_product_commitment =
                 permutation_product_commitment_projective.to_affine();
@@ -182,7 +185,6 @@ impl Argument {
 
             sets.push(CommittedSet {
                 permutation_product_poly,
-                permutation_product_coset,
                 permutation_product_blind,
             });
         }
@@ -191,6 +193,50 @@ impl Argument {
     }
 }
 
+/// Returns the running products `z[0] = init` and `z[i] = z[i - 1] * values[i - 1]`
+/// for every row of `values`.
+///
+/// The products of chunks of rows are computed in parallel, and then each chunk is
+/// scaled by the product of all the chunks before it.
+fn running_product<F: Field>(init: F, values: &[F]) -> Vec<F> {
+    let n = values.len();
+    let mut z = vec![init; n];
+    if n < 2 {
+        return z;
+    }
+
+    let chunk = (n - 1 + multicore::current_num_threads() - 1) / multicore::current_num_threads();
+    z[1..]
+        .par_chunks_mut(chunk)
+        .zip(values[..n - 1].par_chunks(chunk))
+        .for_each(|(z, values)| {
+            let mut product = F::one();
+            for (z, value) in z.iter_mut().zip(values.iter()) {
+                product *= value;
+                *z = product;
+            }
+        });
+
+    let offsets: Vec<F> = z[1..]
+        .chunks(chunk)
+        .scan(init, |product, chunk| {
+            let offset = *product;
+            *product *= chunk[chunk.len() - 1];
+            Some(offset)
+        })
+        .collect();
+    z[1..]
+        .par_chunks_mut(chunk)
+        .zip(offsets.par_iter())
+        .for_each(|(z, offset)| {
+            for z in z.iter_mut() {
+                *z *= offset;
+            }
+        });
+
+    z
+}
+
 impl<C: CurveAffine> Committed<C> {
     pub(in crate::plonk) fn construct(self) -> Constructed<C> {
         Constructed {
@@ -199,7 +245,7 @@ impl<C: CurveAffine> Committed<C> {
                 .iter()
                 .map(|set| ConstructedSet {
                     permutation_product_poly: set.permutation_product_poly.clone(),
-                    permutation_product_blind: set.permutation_product_blind,
+                    permutation_product_blind: set.permutation_product_blind.clone(),
                 })
                 .collect(),
         }
@@ -299,12 +345,12 @@ impl<C: CurveAffine> Evaluated<C> {
                     .chain(Some(ProverQuery {
                         point: *x,
                         poly: &set.permutation_product_poly,
-                        blind: set.permutation_product_blind,
+                        blind: *set.permutation_product_blind,
                     }))
                     .chain(Some(ProverQuery {
                         point: x_next,
                         poly: &set.permutation_product_poly,
-                        blind: set.permutation_product_blind,
+                        blind: *set.permutation_product_blind,
                     }))
             }))
             // Open it at \omega^{last} x for all but the last set. This rotation is only
@@ -320,9 +366,31 @@ impl<C: CurveAffine> Evaluated<C> {
                         Some(ProverQuery {
                             point: x_last,
                             poly: &set.permutation_product_poly,
-                            blind: set.permutation_product_blind,
+                            blind: *set.permutation_product_blind,
                         })
                     }),
             )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::running_product;
+    use crate::arithmetic::Field;
+    use halo2curves::pasta::Fp;
+    use rand_core::OsRng;
+
+    #[test]
+    fn running_product_matches_serial() {
+        for n in [1, 2, 3, 17, 1000] {
+            let init = Fp::random(OsRng);
+            let values = (0..n).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+
+            let mut expected = vec![init];
+            for value in values[..n - 1].iter() {
+                expected.push(expected[expected.len() - 1] * value);
+            }
+            assert_eq!(running_product(init, &values), expected);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Wall-clock timing of the phases of the prover.
+//!
+//! Without the `profile` feature, [`time`] only runs the phase, so the
+//! instrumentation costs nothing.
+
+#[cfg(feature = "profile")]
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+/// A phase of [`create_proof`](super::create_proof).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Synthesis,
+    AdviceCommit,
+    Lookups,
+    Permutation,
+    Vanishing,
+    Evaluation,
+    Multiopen,
+    Ipa,
+}
+
+#[cfg(feature = "profile")]
+impl Phase {
+    fn name(&self) -> &'static str {
+        match self {
+            Phase::Synthesis => "synthesis",
+            Phase::AdviceCommit => "advice_commit",
+            Phase::Lookups => "lookups",
+            Phase::Permutation => "permutation",
+            Phase::Vanishing => "vanishing",
+            Phase::Evaluation => "evaluation",
+            Phase::Multiopen => "multiopen",
+            Phase::Ipa => "ipa",
+        }
+    }
+}
+
+/// The wall-clock time spent in each phase of a call to
+/// [`create_proof_with_timings`](super::create_proof_with_timings).
+///
+/// Phases that run inside another phase, such as the commitments to advice
+/// columns made between phases of synthesis, or the inner product argument of
+/// the IPA multiopen argument, are only counted in the inner phase. Time spent
+/// outside of these phases, such as in committing to instance columns, is not
+/// counted.
+#[cfg(feature = "profile")]
+#[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverTimings {
+    /// Synthesizing the circuits to obtain the witness.
+    pub synthesis: Duration,
+    /// Committing to the advice columns.
+    pub advice_commit: Duration,
+    /// Committing to the lookup and shuffle arguments.
+    pub lookups: Duration,
+    /// Committing to the permutation argument.
+    pub permutation: Duration,
+    /// Computing the quotient polynomial `h(X)` and committing to the vanishing
+    /// argument.
+    pub vanishing: Duration,
+    /// Evaluating the polynomials at `x`.
+    pub evaluation: Duration,
+    /// Creating the multiopen argument.
+    pub multiopen: Duration,
+    /// Creating the inner product argument, with the IPA commitment scheme.
+    pub ipa: Duration,
+}
+
+#[cfg(feature = "profile")]
+impl ProverTimings {
+    /// Returns the total time spent in all phases.
+    pub fn total(&self) -> Duration {
+        self.synthesis
+            + self.advice_commit
+            + self.lookups
+            + self.permutation
+            + self.vanishing
+            + self.evaluation
+            + self.multiopen
+            + self.ipa
+    }
+
+    /// Returns the name and duration of each phase, in the order in which the
+    /// prover runs them. The names are those of the fields and of the `tracing`
+    /// spans.
+    pub fn phases(&self) -> [(&'static str, Duration); 8] {
+        [
+            Phase::Synthesis,
+            Phase::AdviceCommit,
+            Phase::Lookups,
+            Phase::Permutation,
+            Phase::Vanishing,
+            Phase::Evaluation,
+            Phase::Multiopen,
+            Phase::Ipa,
+        ]
+        .map(|phase| (phase.name(), *self.phase(phase)))
+    }
+
+    fn phase(&self, phase: Phase) -> &Duration {
+        match phase {
+            Phase::Synthesis => &self.synthesis,
+            Phase::AdviceCommit => &self.advice_commit,
+            Phase::Lookups => &self.lookups,
+            Phase::Permutation => &self.permutation,
+            Phase::Vanishing => &self.vanishing,
+            Phase::Evaluation => &self.evaluation,
+            Phase::Multiopen => &self.multiopen,
+            Phase::Ipa => &self.ipa,
+        }
+    }
+
+    fn phase_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::Synthesis => &mut self.synthesis,
+            Phase::AdviceCommit => &mut self.advice_commit,
+            Phase::Lookups => &mut self.lookups,
+            Phase::Permutation => &mut self.permutation,
+            Phase::Vanishing => &mut self.vanishing,
+            Phase::Evaluation => &mut self.evaluation,
+            Phase::Multiopen => &mut self.multiopen,
+            Phase::Ipa => &mut self.ipa,
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+struct Recorder {
+    timings: ProverTimings,
+    /// The time spent in phases nested in each of the phases being timed.
+    nested: Vec<Duration>,
+}
+
+#[cfg(feature = "profile")]
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = RefCell::new(None);
+}
+
+/// Runs `phase`. With the `profile` feature, the phase is recorded as a
+/// `tracing` span, and its duration is added to the timings being collected on
+/// this thread, if any.
+pub(crate) fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "profile")]
+    {
+        let _span = tracing::info_span!("prover", phase = phase.name()).entered();
+        let recording = RECORDER.with(|recorder| match recorder.borrow_mut().as_mut() {
+            Some(recorder) => {
+                recorder.nested.push(Duration::ZERO);
+                true
+            }
+            None => false,
+        });
+        if !recording {
+            return f();
+        }
+
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        RECORDER.with(|recorder| {
+            let mut recorder = recorder.borrow_mut();
+            let recorder = recorder.as_mut().expect("recording started above");
+            let nested = recorder.nested.pop().expect("pushed above");
+            *recorder.timings.phase_mut(phase) += elapsed - nested;
+            if let Some(parent) = recorder.nested.last_mut() {
+                *parent += elapsed;
+            }
+        });
+        result
+    }
+
+    #[cfg(not(feature = "profile"))]
+    {
+        let _ = phase;
+        f()
+    }
+}
+
+/// Runs `f`, collecting the time spent in each phase that it runs on this
+/// thread.
+#[cfg(feature = "profile")]
+pub(crate) fn collect<T>(f: impl FnOnce() -> T) -> (T, ProverTimings) {
+    let outer = RECORDER.with(|recorder| {
+        recorder.replace(Some(Recorder {
+            timings: ProverTimings::default(),
+            nested: vec![],
+        }))
+    });
+    let result = f();
+    let recorder = RECORDER.with(|recorder| recorder.replace(outer));
+    (result, recorder.expect("recording started above").timings)
+}
+
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use super::{collect, time, Phase};
+
+    #[test]
+    fn nested_phases_are_exclusive() {
+        let ((), timings) = collect(|| {
+            time(Phase::Multiopen, || {
+                sleep(Duration::from_millis(5));
+                time(Phase::Ipa, || sleep(Duration::from_millis(20)));
+            })
+        });
+        assert!(timings.ipa >= Duration::from_millis(20));
+        assert!(timings.multiopen >= Duration::from_millis(5));
+        assert_eq!(timings.synthesis, Duration::ZERO);
+        assert_eq!(timings.total(), timings.multiopen + timings.ipa);
+    }
+
+    #[test]
+    fn phases_outside_collect_are_not_recorded() {
+        time(Phase::Synthesis, || ());
+        let ((), timings) = collect(|| ());
+        assert_eq!(timings.total(), Duration::ZERO);
+    }
+}
@@ -1,7 +1,8 @@
+use blake2b_simd::{Params as Blake2bParams, BLAKE2B_OUTBYTES};
 use ff::Field;
 use group::Curve;
 use halo2curves::CurveExt;
-use rand_core::RngCore;
+use rand_core::{RngCore, SeedableRng};
 use std::collections::BTreeSet;
 use std::env::var;
 use std::marker::PhantomData;
@@ -17,8 +18,10 @@ use super::{
         Advice, Any, Assignment, Challenge, Circuit, Column, ConstraintSystem, FirstPhase, Fixed,
         FloorPlanner, Instance, Selector,
     },
-    lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, Expression, ProvingKey,
+    lookup, permutation,
+    profiling::{self, time, Phase},
+    shuffle, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY,
+    Error, Expression, ProvingKey,
 };
 use crate::poly::batch_invert_assigned_ref;
 use crate::poly::commitment::ParamsProver;
@@ -29,7 +32,7 @@ use crate::{
     plonk::Assigned,
     poly::{
         self,
-        commitment::{Blind, CommitmentScheme, Params, Prover},
+        commitment::{Blind, CommitmentScheme, Params, Prover, SecretBlind},
         Basis, Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, ProverQuery,
     },
 };
@@ -39,10 +42,101 @@ use crate::{
 };
 use group::prime::PrimeCurveAffine;
 
+/// A deterministic RNG for reproducible proofs, such as for golden-file tests of
+/// proof serialization or differential testing against other implementations.
+///
+/// It expands a 32-byte seed with BLAKE2b in counter mode. Anyone who knows the
+/// seed can recompute the blinding factors of a proof created with it and learn
+/// the witness, so it must not be used to create proofs that are meant to be
+/// zero-knowledge.
+#[derive(Clone, Debug)]
+pub struct DeterministicRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; BLAKE2B_OUTBYTES],
+    position: usize,
+}
+
+impl SeedableRng for DeterministicRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        DeterministicRng {
+            seed,
+            counter: 0,
+            block: [0; BLAKE2B_OUTBYTES],
+            position: BLAKE2B_OUTBYTES,
+        }
+    }
+}
+
+impl RngCore for DeterministicRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            if self.position == BLAKE2B_OUTBYTES {
+                let block = Blake2bParams::new()
+                    .hash_length(BLAKE2B_OUTBYTES)
+                    .personal(b"Halo2-ProverRng")
+                    .to_state()
+                    .update(&self.seed)
+                    .update(&self.counter.to_le_bytes())
+                    .finalize();
+                self.block.copy_from_slice(block.as_bytes());
+                self.counter += 1;
+                self.position = 0;
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
 /// This creates a proof for the provided `circuit` when given the public
 /// parameters `params` and the proving key [`ProvingKey`] that was
 /// generated previously for the same circuit. The provided `instances`
 /// are zero-padded internally.
+///
+/// Several copies of the circuit can be proven at once by passing a slice of
+/// `circuits` with one entry of `instances` per circuit. The result is a single
+/// proof covering all of them, which shares the opening argument and is
+/// verified by passing the same instances to [`verify_proof`]. The challenges of
+/// each phase are then squeezed once the advice of every circuit in that phase is
+/// committed, so a circuit that moves to the next phase during synthesis with
+/// [`Layouter::next_phase`] is synthesized again for each phase instead.
+///
+/// All blinding factors are drawn from `rng`, and no other source of randomness
+/// is used, so a deterministic `rng` produces a reproducible proof. Passing a
+/// [`DeterministicRng`] with a fixed seed gives byte-identical proofs for the
+/// same witness, transcript and parameters.
+///
+/// Returns [`Error::InstanceMismatch`] if a cell that is constrained to equal an
+/// instance cell holds a different value than the provided instance, since the
+/// proof would fail to verify.
+///
+/// With the `zeroize` feature, the witness, every intermediate polynomial and the
+/// blinding factors of the advice, lookup, shuffle, permutation and vanishing
+/// commitments are overwritten with zeros as they are freed, including when an
+/// error is returned, so that they are not left behind in freed heap memory.
+///
+/// [`verify_proof`]: super::verify_proof
+/// [`Layouter::next_phase`]: crate::circuit::Layouter::next_phase
 pub fn create_proof<
     'params,
     'a,
@@ -60,6 +154,10 @@ pub fn create_proof<
     mut rng: R,
     mut transcript: &'a mut T,
 ) -> Result<(), Error> {
+    if circuits.len() != instances.len() {
+        return Err(Error::InvalidInstances);
+    }
+
     for instance in instances.iter() {
         if instance.len() != pk.vk.cs.num_instance_columns {
             return Err(Error::InvalidInstances);
@@ -70,12 +168,14 @@ pub fn create_proof<
     pk.vk.hash_into(transcript)?;
 
     let domain = &pk.vk.domain;
-    let mut meta = ConstraintSystem::default();
+    let mut meta = ConstraintSystem::with_k(domain.k());
     let config = ConcreteCircuit::configure(&mut meta);
 
     // Selector optimizations cannot be applied here; use the ConstraintSystem
     // from the verification key.
     let meta = &pk.vk.cs;
+    // Whether instance columns are committed to and opened like advice columns.
+    let query_instance = P::QUERY_INSTANCE || meta.committed_instances;
 
     struct InstanceSingle<C: CurveAffine> {
         pub instance_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
@@ -90,7 +190,7 @@ pub fn create_proof<
                 .map(|values| {
                     let mut poly = domain.empty_lagrange();
                     assert_eq!(poly.len(), params.n() as usize);
-                    if values.len() > (poly.len() - (meta.blinding_factors() + 1)) {
+                    if values.len() > (poly.len() - meta.unusable_rows()) {
                         panic!("Error::InstanceTooLarge");
                     }
                     for (poly, value) in poly.iter_mut().zip(values.iter()) {
@@ -104,7 +204,7 @@ pub fn create_proof<
                 .iter()
                 .map(|poly| {
                     let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
-                    domain.lagrange_to_coeff(lagrange_vec)
+                    domain.lagrange_to_coeff_with(params.backend(), lagrange_vec)
                 })
                 .collect();
 
@@ -115,10 +215,56 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    // Absorb the instances of every circuit into the transcript, before any advice
+    // is committed.
+    for (values, instance_single) in instances.iter().zip(instance.iter()) {
+        if !query_instance {
+            for values in values.iter() {
+                for value in values.iter() {
+                    transcript.common_scalar(*value)?;
+                }
+            }
+        } else {
+            let instance_commitments_projective: Vec<_> = instance_single
+                .instance_values
+                .iter()
+                .map(|poly| params.commit_lagrange(poly, Blind::default()))
+                .collect();
+            let mut instance_commitments =
+                vec![Scheme::Curve::identity(); instance_commitments_projective.len()];
+            <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
+                &instance_commitments_projective,
+                &mut instance_commitments,
+            );
+            let instance_commitments = instance_commitments;
+            drop(instance_commitments_projective);
+
+            for commitment in &instance_commitments {
+                transcript.common_point(*commitment)?;
+            }
+        }
+    }
+
     #[derive(Clone)]
     struct AdviceSingle<C: CurveAffine, B: Basis> {
         pub advice_polys: Vec<Polynomial<C::Scalar, B>>,
-        pub advice_blinds: Vec<Blind<C::Scalar>>,
+        pub advice_blinds: Vec<SecretBlind<C::Scalar>>,
+    }
+
+    // Squeezes the challenges of a phase, once the advice of every circuit in that
+    // phase has been committed.
+    fn squeeze_challenges<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        transcript: &mut T,
+        challenges: &mut HashMap<usize, C::Scalar>,
+        challenge_indices: &[usize],
+    ) {
+        for challenge_index in challenge_indices.iter() {
+            let existing = challenges.insert(
+                *challenge_index,
+                *transcript.squeeze_challenge_scalar::<()>(),
+            );
+            assert!(existing.is_none());
+        }
     }
 
     struct WitnessCollection<'params, 'a, 'b, Scheme, P, C, E, R, T>
@@ -136,13 +282,19 @@ pub fn create_proof<
         challenges: &'b mut HashMap<usize, C::Scalar>,
         instances: &'b [&'a [C::Scalar]],
         usable_rows: RangeTo<usize>,
-        advice_single: AdviceSingle<C, LagrangeCoeff>,
-        instance_single: &'b InstanceSingle<C>,
+        advice_single: &'b mut AdviceSingle<C, LagrangeCoeff>,
         rng: &'b mut R,
         transcript: &'b mut &'a mut T,
         column_indices: [Vec<usize>; 3],
         challenge_indices: [Vec<usize>; 3],
+        unblinded_advice_columns: &'b [usize],
         unusable_rows_start: usize,
+        // Whether the circuit is the only one in the proof, so that it can commit its
+        // advice and squeeze the challenges of a phase during synthesis.
+        next_phase_in_synthesis: bool,
+        // Copy constraints involving instance columns, checked once the witness
+        // has been collected.
+        instance_copies: Vec<(Column<Any>, usize, Column<Any>, usize)>,
         _marker: PhantomData<(P, E)>,
     }
 
@@ -181,14 +333,17 @@ pub fn create_proof<
 
         fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
             if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.params.k()));
+                return Err(Error::row_not_available(self.params.k(), row));
             }
 
             self.instances
                 .get(column.index())
                 .and_then(|column| column.get(row))
                 .map(|v| Value::known(*v))
-                .ok_or(Error::BoundsFailure)
+                .ok_or(Error::BoundsFailure {
+                    column: column.into(),
+                    row,
+                })
         }
 
         fn assign_advice<'r, 'v>(
@@ -206,7 +361,7 @@ pub fn create_proof<
             }
 
             if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.params.k()));
+                return Err(Error::row_not_available(self.params.k(), row));
             }
 
             let advice_get_mut = self
@@ -223,9 +378,7 @@ pub fn create_proof<
                     .get_unchecked_mut(row)
             };
             */
-            *advice_get_mut = to
-                .assign()
-                .expect("No Value::unknown() in advice column allowed during create_proof");
+            *advice_get_mut = to.assign(column, row)?;
             let immutable_raw_ptr = advice_get_mut as *const Assigned<F>;
             Ok(Value::known(unsafe { &*immutable_raw_ptr }))
         }
@@ -234,8 +387,20 @@ pub fn create_proof<
             // We only care about advice columns here
         }
 
-        fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) {
-            // We only care about advice columns here
+        fn copy(
+            &mut self,
+            left_column: Column<Any>,
+            left_row: usize,
+            right_column: Column<Any>,
+            right_row: usize,
+        ) {
+            // We only care about copies of instance cells here
+            if *left_column.column_type() == Any::Instance
+                || *right_column.column_type() == Any::Instance
+            {
+                self.instance_copies
+                    .push((left_column, left_row, right_column, right_row));
+            }
         }
 
         fn fill_from_row(
@@ -267,43 +432,42 @@ pub fn create_proof<
             // Do nothing; we don't care about namespaces in this context.
         }
 
+        fn is_witness_only(&self) -> bool {
+            true
+        }
+
         fn next_phase(&mut self) {
-            let phase = self.current_phase.to_u8() as usize;
-            if phase == 0 {
-                // Absorb instances into transcript.
-                // Do this here and not earlier in case we want to be able to mutate
-                // the instances during synthesize in FirstPhase in the future
-                if !P::QUERY_INSTANCE {
-                    for values in self.instances.iter() {
-                        for value in values.iter() {
-                            self.transcript
-                                .common_scalar(*value)
-                                .expect("Absorbing instance value to transcript failed");
-                        }
-                    }
-                } else {
-                    let instance_commitments_projective: Vec<_> = self
-                        .instance_single
-                        .instance_values
-                        .iter()
-                        .map(|poly| self.params.commit_lagrange(poly, Blind::default()))
-                        .collect();
-                    let mut instance_commitments =
-                        vec![C::identity(); instance_commitments_projective.len()];
-                    C::CurveExt::batch_normalize(
-                        &instance_commitments_projective,
-                        &mut instance_commitments,
-                    );
-                    let instance_commitments = instance_commitments;
-                    drop(instance_commitments_projective);
-
-                    for commitment in &instance_commitments {
-                        self.transcript
-                            .common_point(*commitment)
-                            .expect("Absorbing instance commitment to transcript failed");
-                    }
-                }
+            // With several circuits, the challenges of this phase depend on advice
+            // that has not been synthesized yet. The circuit is synthesized again
+            // in the next phase instead, once they have been squeezed.
+            if !self.next_phase_in_synthesis {
+                return;
             }
+            let phase = self.current_phase.to_u8() as usize;
+            time(Phase::AdviceCommit, || self.commit_phase());
+            squeeze_challenges::<C, E, _>(
+                &mut **self.transcript,
+                self.challenges,
+                &self.challenge_indices[phase],
+            );
+            self.current_phase = self.current_phase.next();
+        }
+    }
+
+    impl<'params, 'a, 'b, F, Scheme, P, C, E, R, T>
+        WitnessCollection<'params, 'a, 'b, Scheme, P, C, E, R, T>
+    where
+        F: FieldExt,
+        Scheme: CommitmentScheme<Curve = C>,
+        P: Prover<'params, Scheme>,
+        C: CurveAffine<ScalarExt = F>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    {
+        // Commits to the advice columns in the current phase.
+        fn commit_phase(&mut self) {
+            let phase = self.current_phase.to_u8() as usize;
             // Commit the advice columns in the current phase
             let mut advice_values = batch_invert_assigned_ref::<F>(
                 self.column_indices
@@ -313,21 +477,33 @@ pub fn create_proof<
                     .map(|column_index| &self.advice[*column_index])
                     .collect(),
             );
-            // Add blinding factors to advice columns
-            for advice_values in &mut advice_values {
-                for cell in &mut advice_values[self.unusable_rows_start..] {
-                    *cell = F::random(&mut self.rng);
+            let unblinded = self.unblinded_advice_columns;
+            // Add blinding factors to advice columns, except for unblinded columns
+            // whose blinding rows are left at zero.
+            for (column_index, advice_values) in
+                self.column_indices[phase].iter().zip(&mut advice_values)
+            {
+                if !unblinded.contains(column_index) {
+                    for cell in &mut advice_values[self.unusable_rows_start..] {
+                        *cell = F::random(&mut self.rng);
+                    }
                 }
             }
             // Compute commitments to advice column polynomials
-            let blinds: Vec<_> = advice_values
+            let blinds: Vec<_> = self.column_indices[phase]
                 .iter()
-                .map(|_| Blind(F::random(&mut self.rng)))
+                .map(|column_index| {
+                    if unblinded.contains(column_index) {
+                        SecretBlind::default()
+                    } else {
+                        SecretBlind(Blind(F::random(&mut self.rng)))
+                    }
+                })
                 .collect();
             let advice_commitments_projective: Vec<_> = advice_values
                 .iter()
                 .zip(blinds.iter())
-                .map(|(poly, blind)| self.params.commit_lagrange(poly, *blind))
+                .map(|(poly, blind)| self.params.commit_lagrange(poly, **blind))
                 .collect();
             let mut advice_commitments = vec![C::identity(); advice_commitments_projective.len()];
             C::CurveExt::batch_normalize(&advice_commitments_projective, &mut advice_commitments);
@@ -347,14 +523,6 @@ pub fn create_proof<
                 self.advice_single.advice_polys[*column_index] = advice_poly;
                 self.advice_single.advice_blinds[*column_index] = blind;
             }
-            for challenge_index in self.challenge_indices[phase].iter() {
-                let existing = self.challenges.insert(
-                    *challenge_index,
-                    *self.transcript.squeeze_challenge_scalar::<()>(),
-                );
-                assert!(existing.is_none());
-            }
-            self.current_phase = self.current_phase.next();
         }
     }
 
@@ -368,63 +536,107 @@ pub fn create_proof<
     }
 
     let (advice, challenges) = {
-        let mut advice = Vec::with_capacity(instances.len());
+        let mut advice = vec![
+            AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
+                advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
+                advice_blinds: vec![SecretBlind::default(); meta.num_advice_columns],
+            };
+            circuits.len()
+        ];
+        let mut instance_copies = vec![vec![]; circuits.len()];
         let mut challenges = HashMap::<usize, Scheme::Scalar>::with_capacity(meta.num_challenges);
 
-        let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
+        let unusable_rows_start = params.n() as usize - meta.unusable_rows();
         let phases = pk.vk.cs.phases().collect::<Vec<_>>();
         let num_phases = phases.len();
-        // WARNING: this will currently not work if `circuits` has more than 1 circuit
-        // because the original API squeezes the challenges for a phase after running all circuits
-        // once in that phase.
-        assert_eq!(
-            circuits.len(),
-            1,
-            "New challenge API doesn't work with multiple circuits yet"
-        );
-        for ((circuit, instances), instance_single) in
-            circuits.iter().zip(instances).zip(instance.iter())
-        {
-            let mut witness: WitnessCollection<Scheme, P, _, E, _, _> = WitnessCollection {
-                params,
-                current_phase: phases[0],
-                advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
-                instances,
-                challenges: &mut challenges,
-                // The prover will not be allowed to assign values to advice
-                // cells that exist within inactive rows, which include some
-                // number of blinding factors and an extra row for use in the
-                // permutation argument.
-                usable_rows: ..unusable_rows_start,
-                advice_single: AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
-                    advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
-                    advice_blinds: vec![Blind::default(); meta.num_advice_columns],
-                },
-                instance_single,
-                rng: &mut rng,
-                transcript: &mut transcript,
-                column_indices: column_indices.clone(),
-                challenge_indices: challenge_indices.clone(),
-                unusable_rows_start,
-                _marker: PhantomData,
-            };
+        let next_phase_in_synthesis = circuits.len() == 1;
+
+        // Each circuit is synthesized and its advice in the current phase committed,
+        // before the challenges of the phase are squeezed. A single circuit may
+        // already have moved through several phases during synthesis, in which
+        // case the loop only runs once.
+        let mut phase = 0;
+        while phase < num_phases {
+            for (((circuit, instances), advice_single), instance_copies) in circuits
+                .iter()
+                .zip(instances)
+                .zip(advice.iter_mut())
+                .zip(instance_copies.iter_mut())
+            {
+                let mut witness: WitnessCollection<Scheme, P, _, E, _, _> = WitnessCollection {
+                    params,
+                    current_phase: phases[phase],
+                    advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
+                    instances,
+                    challenges: &mut challenges,
+                    // The prover will not be allowed to assign values to advice
+                    // cells that exist within inactive rows, which include some
+                    // number of blinding factors and an extra row for use in the
+                    // permutation argument.
+                    usable_rows: ..unusable_rows_start,
+                    advice_single,
+                    rng: &mut rng,
+                    transcript: &mut transcript,
+                    column_indices: column_indices.clone(),
+                    challenge_indices: challenge_indices.clone(),
+                    unblinded_advice_columns: &meta.unblinded_advice_columns,
+                    unusable_rows_start,
+                    next_phase_in_synthesis,
+                    instance_copies: vec![],
+                    _marker: PhantomData,
+                };
 
-            // while loop is for compatibility with circuits that do not use the new `next_phase` API to manage phases
-            // If the circuit uses the new API, then the while loop will only execute once
-            while witness.current_phase.to_u8() < num_phases as u8 {
                 // Synthesize the circuit to obtain the witness and other information.
-                ConcreteCircuit::FloorPlanner::synthesize(
-                    &mut witness,
-                    circuit,
-                    config.clone(),
-                    meta.constants.clone(),
-                )
+                time(Phase::Synthesis, || {
+                    ConcreteCircuit::FloorPlanner::synthesize(
+                        &mut witness,
+                        circuit,
+                        config.clone(),
+                        meta.constants.clone(),
+                    )
+                })
                 .unwrap();
-                if witness.current_phase.to_u8() < num_phases as u8 {
-                    witness.next_phase();
+                phase = witness.current_phase.to_u8() as usize;
+                if phase < num_phases {
+                    time(Phase::AdviceCommit, || witness.commit_phase());
+                }
+                *instance_copies = witness.instance_copies;
+            }
+            if phase < num_phases {
+                squeeze_challenges::<Scheme::Curve, E, _>(
+                    &mut *transcript,
+                    &mut challenges,
+                    &challenge_indices[phase],
+                );
+            }
+            phase += 1;
+        }
+
+        // A cell constrained to equal an instance cell must hold the provided
+        // instance value, or the proof would fail to verify.
+        for ((advice_single, instance_single), instance_copies) in advice
+            .iter()
+            .zip(instance.iter())
+            .zip(instance_copies.iter())
+        {
+            let value = |column: Column<Any>, row: usize| match column.column_type() {
+                Any::Advice(_) => advice_single.advice_polys[column.index()][row],
+                Any::Fixed => pk.fixed_values[column.index()][row],
+                Any::Instance => instance_single.instance_values[column.index()][row],
+            };
+            for &(left_column, left_row, right_column, right_row) in instance_copies.iter() {
+                if value(left_column, left_row) != value(right_column, right_row) {
+                    let (cell, instance) = if *right_column.column_type() == Any::Instance {
+                        ((left_column, left_row), (right_column, right_row))
+                    } else {
+                        ((right_column, right_row), (left_column, left_row))
+                    };
+                    return Err(Error::InstanceMismatch {
+                        cell,
+                        instance: (instance.0.try_into().unwrap(), instance.1),
+                    });
                 }
             }
-            advice.push(witness.advice_single);
         }
 
         assert_eq!(challenges.len(), meta.num_challenges);
@@ -438,208 +650,281 @@ pub fn create_proof<
     // Sample theta challenge for keeping lookup columns linearly independent
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
 
-    let lookups: Vec<Vec<lookup::prover::Permuted<Scheme::Curve>>> = instance
-        .iter()
-        .zip(advice.iter())
-        .map(|(instance, advice)| -> Result<Vec<_>, Error> {
-            // Construct and commit to permuted values for each lookup
-            pk.vk
-                .cs
-                .lookups
+    let lookups: Vec<Vec<lookup::prover::Permuted<Scheme::Curve>>> = time(Phase::Lookups, || {
+        instance
+            .iter()
+            .zip(advice.iter())
+            .map(|(instance, advice)| -> Result<Vec<_>, Error> {
+                // Construct and commit to permuted values for each lookup
+                pk.vk
+                    .cs
+                    .lookups
+                    .iter()
+                    .zip(pk.ev.lookup_compressions.iter())
+                    .map(|(lookup, compressions)| {
+                        lookup.commit_permuted(
+                            pk,
+                            compressions,
+                            params,
+                            domain,
+                            theta,
+                            &advice.advice_polys,
+                            &pk.fixed_values,
+                            &instance.instance_values,
+                            &challenges,
+                            &mut rng,
+                            transcript,
+                        )
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    // Sample beta challenge
+    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
+
+    // Sample gamma challenge
+    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
+
+    // Commit to permutations.
+    let permutations: Vec<permutation::prover::Committed<Scheme::Curve>> =
+        time(Phase::Permutation, || {
+            instance
                 .iter()
-                .map(|lookup| {
-                    lookup.commit_permuted(
-                        pk,
+                .zip(advice.iter())
+                .map(|(instance, advice)| {
+                    pk.vk.cs.permutation.commit(
                         params,
-                        domain,
-                        theta,
+                        pk,
+                        &pk.permutation,
                         &advice.advice_polys,
                         &pk.fixed_values,
                         &instance.instance_values,
-                        &challenges,
+                        beta,
+                        gamma,
                         &mut rng,
                         transcript,
                     )
                 })
-                .collect()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // Sample beta challenge
-    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
-
-    // Sample gamma challenge
-    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
-
-    // Commit to permutations.
-    let permutations: Vec<permutation::prover::Committed<Scheme::Curve>> = instance
-        .iter()
-        .zip(advice.iter())
-        .map(|(instance, advice)| {
-            pk.vk.cs.permutation.commit(
-                params,
-                pk,
-                &pk.permutation,
-                &advice.advice_polys,
-                &pk.fixed_values,
-                &instance.instance_values,
-                beta,
-                gamma,
-                &mut rng,
-                transcript,
-            )
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+    let lookups: Vec<Vec<lookup::prover::Committed<Scheme::Curve>>> = time(Phase::Lookups, || {
+        lookups
+            .into_iter()
+            .map(|lookups| -> Result<Vec<_>, _> {
+                // Construct and commit to products for each lookup
+                lookups
+                    .into_iter()
+                    .map(|lookup| {
+                        lookup.commit_product(pk, params, beta, gamma, &mut rng, transcript)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
 
-    let lookups: Vec<Vec<lookup::prover::Committed<Scheme::Curve>>> = lookups
-        .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
-            // Construct and commit to products for each lookup
-            lookups
-                .into_iter()
-                .map(|lookup| lookup.commit_product(pk, params, beta, gamma, &mut rng, transcript))
+    let shuffles: Vec<Vec<shuffle::prover::Committed<Scheme::Curve>>> =
+        time(Phase::Lookups, || {
+            instance
+                .iter()
+                .zip(advice.iter())
+                .map(|(instance, advice)| -> Result<Vec<_>, Error> {
+                    // Construct and commit to products for each shuffle
+                    pk.vk
+                        .cs
+                        .shuffles
+                        .iter()
+                        .zip(pk.ev.shuffle_compressions.iter())
+                        .map(|(shuffle, compressions)| {
+                            shuffle.commit_product(
+                                pk,
+                                compressions,
+                                params,
+                                domain,
+                                theta,
+                                gamma,
+                                &advice.advice_polys,
+                                &pk.fixed_values,
+                                &instance.instance_values,
+                                &challenges,
+                                &mut rng,
+                                transcript,
+                            )
+                        })
+                        .collect()
+                })
                 .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+        })?;
 
     // Commit to the vanishing argument's random polynomial for blinding h(x_3)
-    let vanishing = vanishing::Argument::commit(params, domain, &mut rng, transcript)?;
+    let vanishing = time(Phase::Vanishing, || {
+        vanishing::Argument::commit(params, domain, &mut rng, transcript)
+    })?;
 
     // Obtain challenge for keeping all separate gates linearly independent
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
 
     // Calculate the advice polys
-    let advice: Vec<AdviceSingle<Scheme::Curve, Coeff>> = advice
-        .into_iter()
-        .map(
-            |AdviceSingle {
-                 advice_polys,
-                 advice_blinds,
-             }| {
-                AdviceSingle {
-                    advice_polys: advice_polys
-                        .into_iter()
-                        .map(|poly| domain.lagrange_to_coeff(poly))
-                        .collect::<Vec<_>>(),
-                    advice_blinds,
-                }
-            },
-        )
-        .collect();
+    let advice: Vec<AdviceSingle<Scheme::Curve, Coeff>> = time(Phase::Vanishing, || {
+        advice
+            .into_iter()
+            .map(
+                |AdviceSingle {
+                     advice_polys,
+                     advice_blinds,
+                 }| {
+                    AdviceSingle {
+                        advice_polys: advice_polys
+                            .into_iter()
+                            .map(|poly| domain.lagrange_to_coeff_with(params.backend(), poly))
+                            .collect::<Vec<_>>(),
+                        advice_blinds,
+                    }
+                },
+            )
+            .collect()
+    });
 
     // Evaluate the h(X) polynomial
-    let h_poly = pk.ev.evaluate_h(
-        pk,
-        &advice
-            .iter()
-            .map(|a| a.advice_polys.as_slice())
-            .collect::<Vec<_>>(),
-        &instance
-            .iter()
-            .map(|i| i.instance_polys.as_slice())
-            .collect::<Vec<_>>(),
-        &challenges,
-        *y,
-        *beta,
-        *gamma,
-        *theta,
-        &lookups,
-        &permutations,
-    );
+    let h_poly = time(Phase::Vanishing, || {
+        pk.ev.evaluate_h(
+            pk,
+            params.backend(),
+            &advice
+                .iter()
+                .map(|a| a.advice_polys.as_slice())
+                .collect::<Vec<_>>(),
+            &instance
+                .iter()
+                .map(|i| i.instance_polys.as_slice())
+                .collect::<Vec<_>>(),
+            &challenges,
+            *y,
+            *beta,
+            *gamma,
+            *theta,
+            &lookups,
+            &shuffles,
+            &permutations,
+        )
+    });
 
     // Construct the vanishing argument's h(X) commitments
-    let vanishing = vanishing.construct(params, domain, h_poly, &mut rng, transcript)?;
+    let vanishing = time(Phase::Vanishing, || {
+        vanishing.construct(params, domain, h_poly, &mut rng, transcript)
+    })?;
 
     let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
     let xn = x.pow(&[params.n(), 0, 0, 0]);
 
-    if P::QUERY_INSTANCE {
-        // Compute and hash instance evals for each circuit instance
-        for instance in instance.iter() {
-            // Evaluate polynomials at omega^i x
-            let instance_evals: Vec<_> = meta
-                .instance_queries
+    let (vanishing, permutations, lookups, shuffles) =
+        time(Phase::Evaluation, || -> Result<_, Error> {
+            if query_instance {
+                // Compute and hash instance evals for each circuit instance
+                for instance in instance.iter() {
+                    // Evaluate polynomials at omega^i x
+                    let instance_evals: Vec<_> = meta
+                        .instance_queries
+                        .iter()
+                        .map(|&(column, at)| {
+                            eval_polynomial(
+                                &instance.instance_polys[column.index()],
+                                domain.rotate_omega(*x, at),
+                            )
+                        })
+                        .collect();
+
+                    // Hash each instance column evaluation
+                    for eval in instance_evals.iter() {
+                        transcript.write_scalar(*eval)?;
+                    }
+                }
+            }
+
+            // Compute and hash advice evals for each circuit instance
+            for advice in advice.iter() {
+                // Evaluate polynomials at omega^i x
+                let advice_evals: Vec<_> = meta
+                    .advice_queries
+                    .iter()
+                    .map(|&(column, at)| {
+                        eval_polynomial(
+                            &advice.advice_polys[column.index()],
+                            domain.rotate_omega(*x, at),
+                        )
+                    })
+                    .collect();
+
+                // Hash each advice column evaluation
+                for eval in advice_evals.iter() {
+                    transcript.write_scalar(*eval)?;
+                }
+            }
+
+            // Compute and hash fixed evals (shared across all circuit instances)
+            let fixed_evals: Vec<_> = meta
+                .fixed_queries
                 .iter()
                 .map(|&(column, at)| {
-                    eval_polynomial(
-                        &instance.instance_polys[column.index()],
-                        domain.rotate_omega(*x, at),
-                    )
+                    eval_polynomial(&pk.fixed_polys[column.index()], domain.rotate_omega(*x, at))
                 })
                 .collect();
 
-            // Hash each instance column evaluation
-            for eval in instance_evals.iter() {
+            // Hash each fixed column evaluation
+            for eval in fixed_evals.iter() {
                 transcript.write_scalar(*eval)?;
             }
-        }
-    }
-
-    // Compute and hash advice evals for each circuit instance
-    for advice in advice.iter() {
-        // Evaluate polynomials at omega^i x
-        let advice_evals: Vec<_> = meta
-            .advice_queries
-            .iter()
-            .map(|&(column, at)| {
-                eval_polynomial(
-                    &advice.advice_polys[column.index()],
-                    domain.rotate_omega(*x, at),
-                )
-            })
-            .collect();
-
-        // Hash each advice column evaluation
-        for eval in advice_evals.iter() {
-            transcript.write_scalar(*eval)?;
-        }
-    }
-
-    // Compute and hash fixed evals (shared across all circuit instances)
-    let fixed_evals: Vec<_> = meta
-        .fixed_queries
-        .iter()
-        .map(|&(column, at)| {
-            eval_polynomial(&pk.fixed_polys[column.index()], domain.rotate_omega(*x, at))
-        })
-        .collect();
 
-    // Hash each fixed column evaluation
-    for eval in fixed_evals.iter() {
-        transcript.write_scalar(*eval)?;
-    }
+            let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
 
-    let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
+            // Evaluate common permutation data
+            pk.permutation.evaluate(x, transcript)?;
 
-    // Evaluate common permutation data
-    pk.permutation.evaluate(x, transcript)?;
+            // Evaluate the permutations, if any, at omega^i x.
+            let permutations: Vec<permutation::prover::Evaluated<Scheme::Curve>> = permutations
+                .into_iter()
+                .map(|permutation| -> Result<_, _> {
+                    permutation.construct().evaluate(pk, x, transcript)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-    // Evaluate the permutations, if any, at omega^i x.
-    let permutations: Vec<permutation::prover::Evaluated<Scheme::Curve>> = permutations
-        .into_iter()
-        .map(|permutation| -> Result<_, _> { permutation.construct().evaluate(pk, x, transcript) })
-        .collect::<Result<Vec<_>, _>>()?;
+            // Evaluate the lookups, if any, at omega^i x.
+            let lookups: Vec<Vec<lookup::prover::Evaluated<Scheme::Curve>>> = lookups
+                .into_iter()
+                .map(|lookups| -> Result<Vec<_>, _> {
+                    lookups
+                        .into_iter()
+                        .map(|p| p.evaluate(pk, x, transcript))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
 
-    // Evaluate the lookups, if any, at omega^i x.
-    let lookups: Vec<Vec<lookup::prover::Evaluated<Scheme::Curve>>> = lookups
-        .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
-            lookups
+            // Evaluate the shuffles, if any, at omega^i x.
+            let shuffles: Vec<Vec<shuffle::prover::Evaluated<Scheme::Curve>>> = shuffles
                 .into_iter()
-                .map(|p| p.evaluate(pk, x, transcript))
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+                .map(|shuffles| -> Result<Vec<_>, _> {
+                    shuffles
+                        .into_iter()
+                        .map(|p| p.evaluate(pk, x, transcript))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok((vanishing, permutations, lookups, shuffles))
+        })?;
 
     let instances = instance
         .iter()
         .zip(advice.iter())
         .zip(permutations.iter())
         .zip(lookups.iter())
-        .flat_map(|(((instance, advice), permutation), lookups)| {
+        .zip(shuffles.iter())
+        .flat_map(|((((instance, advice), permutation), lookups), shuffles)| {
             iter::empty()
                 .chain(
-                    P::QUERY_INSTANCE
+                    query_instance
                         .then_some(pk.vk.cs.instance_queries.iter().map(move |&(column, at)| {
                             ProverQuery {
                                 point: domain.rotate_omega(*x, at),
@@ -658,11 +943,12 @@ pub fn create_proof<
                         .map(move |&(column, at)| ProverQuery {
                             point: domain.rotate_omega(*x, at),
                             poly: &advice.advice_polys[column.index()],
-                            blind: advice.advice_blinds[column.index()],
+                            blind: *advice.advice_blinds[column.index()],
                         }),
                 )
                 .chain(permutation.open(pk, x))
                 .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                .chain(shuffles.iter().flat_map(move |p| p.open(pk, x)).into_iter())
         })
         .chain(
             pk.vk
@@ -680,7 +966,37 @@ pub fn create_proof<
         .chain(vanishing.open(x));
 
     let prover = P::new(params);
-    prover
-        .create_proof(&mut rng, transcript, instances)
-        .map_err(|_| Error::ConstraintSystemFailure)
+    time(Phase::Multiopen, || {
+        prover.create_proof(&mut rng, transcript, instances)
+    })
+    .map_err(|_| Error::ConstraintSystemFailure)
+}
+
+/// Creates a proof like [`create_proof`], and returns the wall-clock time spent
+/// in each phase of the prover. Each phase is also recorded as a `tracing` span.
+#[cfg(feature = "profile")]
+#[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+pub fn create_proof_with_timings<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore + 'a,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&'a [Scheme::Scalar]]],
+    rng: R,
+    transcript: &'a mut T,
+) -> Result<profiling::ProverTimings, Error> {
+    let (result, timings) = profiling::collect(|| {
+        create_proof::<Scheme, P, E, R, T, ConcreteCircuit>(
+            params, pk, circuits, instances, rng, transcript,
+        )
+    });
+    result.map(|()| timings)
 }
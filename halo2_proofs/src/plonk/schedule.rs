@@ -0,0 +1,295 @@
+//! The order in which the verifier opens polynomial commitments, for generating
+//! external verifiers, and the size of the proofs that it reads.
+
+use ff::PrimeField;
+
+use super::VerifyingKey;
+use crate::{
+    arithmetic::CurveAffine,
+    poly::{
+        commitment::{CommitmentScheme, Verifier},
+        Rotation,
+    },
+};
+
+/// A polynomial that the verifier opens, identified by its commitment.
+///
+/// Polynomials committed in the proof are indexed by the circuit instance `proof`
+/// they belong to, in the order in which the instances were passed to the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueriedPolynomial {
+    /// An instance column. Only opened by verifiers that query instance columns.
+    Instance {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the instance column.
+        column: usize,
+    },
+    /// An advice column.
+    Advice {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the advice column.
+        column: usize,
+    },
+    /// The grand product of a set of permutation columns.
+    PermutationProduct {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the set of permutation columns.
+        set: usize,
+    },
+    /// The grand product of a lookup argument.
+    LookupProduct {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the lookup argument.
+        lookup: usize,
+    },
+    /// The permuted input expression of a lookup argument.
+    LookupPermutedInput {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the lookup argument.
+        lookup: usize,
+    },
+    /// The permuted table expression of a lookup argument.
+    LookupPermutedTable {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the lookup argument.
+        lookup: usize,
+    },
+    /// The grand product of a shuffle argument.
+    ShuffleProduct {
+        /// The circuit instance.
+        proof: usize,
+        /// The index of the shuffle argument.
+        shuffle: usize,
+    },
+    /// A fixed column, committed in the verifying key.
+    Fixed {
+        /// The index of the fixed column.
+        column: usize,
+    },
+    /// The permutation polynomial of a column in the permutation argument,
+    /// committed in the verifying key.
+    PermutationSigma {
+        /// The index of the column within the permutation argument.
+        column: usize,
+    },
+    /// The vanishing argument's quotient, which the verifier reconstructs from the
+    /// pieces of `h` committed in the proof.
+    VanishingQuotient,
+    /// The vanishing argument's random polynomial.
+    VanishingRandom,
+}
+
+/// A single opening of a polynomial at `x` rotated by `rotation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Query {
+    /// The opened polynomial.
+    pub polynomial: QueriedPolynomial,
+    /// The rotation of the opening point relative to `x`.
+    pub rotation: Rotation,
+}
+
+/// A set of polynomials that are all opened at the same set of rotations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RotationSet {
+    /// The rotations, in the order in which they are first queried.
+    pub rotations: Vec<Rotation>,
+    /// The polynomials, in the order in which they are first queried.
+    pub polynomials: Vec<QueriedPolynomial>,
+}
+
+/// The openings checked by the verifier, in the order in which it passes them to
+/// the multiopen argument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuerySchedule {
+    queries: Vec<Query>,
+}
+
+impl QuerySchedule {
+    /// Returns the queries in the order in which the verifier makes them.
+    pub fn queries(&self) -> &[Query] {
+        &self.queries
+    }
+
+    /// Groups the queried polynomials by the set of rotations at which each of them
+    /// is opened, as SHPLONK does. Sets are ordered by the first query of any of
+    /// their polynomials.
+    pub fn rotation_sets(&self) -> Vec<RotationSet> {
+        let mut polynomials: Vec<(QueriedPolynomial, Vec<Rotation>)> = vec![];
+        for query in self.queries.iter() {
+            match polynomials
+                .iter_mut()
+                .find(|(polynomial, _)| *polynomial == query.polynomial)
+            {
+                Some((_, rotations)) => {
+                    if !rotations.contains(&query.rotation) {
+                        rotations.push(query.rotation);
+                    }
+                }
+                None => polynomials.push((query.polynomial, vec![query.rotation])),
+            }
+        }
+
+        let mut sets: Vec<RotationSet> = vec![];
+        for (polynomial, rotations) in polynomials {
+            let same_rotations = |set: &&mut RotationSet| {
+                set.rotations.len() == rotations.len()
+                    && rotations.iter().all(|r| set.rotations.contains(r))
+            };
+            match sets.iter_mut().find(same_rotations) {
+                Some(set) => set.polynomials.push(polynomial),
+                None => sets.push(RotationSet {
+                    rotations,
+                    polynomials: vec![polynomial],
+                }),
+            }
+        }
+        sets
+    }
+}
+
+impl<C: CurveAffine> VerifyingKey<C> {
+    /// Returns the openings that the verifier checks for a proof of `num_proofs`
+    /// circuit instances, in the order in which it passes them to the multiopen
+    /// argument. `query_instance` selects whether instance columns are opened, as
+    /// determined by the verifier's `QUERY_INSTANCE` and by
+    /// [`ConstraintSystem::commit_instances`](super::ConstraintSystem::commit_instances).
+    pub fn query_schedule(&self, num_proofs: usize, query_instance: bool) -> QuerySchedule {
+        let cs = &self.cs;
+        let chunk_len = self.cs_degree - 2;
+        let num_permutation_sets = cs.permutation.columns.chunks(chunk_len).count();
+        let last_rotation = Rotation(-(cs.unusable_rows() as i32));
+        let query = |polynomial, rotation| Query {
+            polynomial,
+            rotation,
+        };
+
+        let mut queries = vec![];
+        for proof in 0..num_proofs {
+            if query_instance {
+                queries.extend(cs.instance_queries.iter().map(|&(column, at)| {
+                    query(
+                        QueriedPolynomial::Instance {
+                            proof,
+                            column: column.index(),
+                        },
+                        at,
+                    )
+                }));
+            }
+            queries.extend(cs.advice_queries.iter().map(|&(column, at)| {
+                query(
+                    QueriedPolynomial::Advice {
+                        proof,
+                        column: column.index(),
+                    },
+                    at,
+                )
+            }));
+            for set in 0..num_permutation_sets {
+                let product = QueriedPolynomial::PermutationProduct { proof, set };
+                queries.push(query(product, Rotation::cur()));
+                queries.push(query(product, Rotation::next()));
+            }
+            // All but the last set are also opened at the last usable row.
+            for set in (0..num_permutation_sets).rev().skip(1) {
+                let product = QueriedPolynomial::PermutationProduct { proof, set };
+                queries.push(query(product, last_rotation));
+            }
+            for lookup in 0..cs.lookups.len() {
+                let product = QueriedPolynomial::LookupProduct { proof, lookup };
+                let input = QueriedPolynomial::LookupPermutedInput { proof, lookup };
+                let table = QueriedPolynomial::LookupPermutedTable { proof, lookup };
+                queries.push(query(product, Rotation::cur()));
+                queries.push(query(input, Rotation::cur()));
+                queries.push(query(table, Rotation::cur()));
+                queries.push(query(input, Rotation::prev()));
+                queries.push(query(product, Rotation::next()));
+            }
+            for shuffle in 0..cs.shuffles.len() {
+                let product = QueriedPolynomial::ShuffleProduct { proof, shuffle };
+                queries.push(query(product, Rotation::cur()));
+                queries.push(query(product, Rotation::next()));
+            }
+        }
+        queries.extend(cs.fixed_queries.iter().map(|&(column, at)| {
+            query(
+                QueriedPolynomial::Fixed {
+                    column: column.index(),
+                },
+                at,
+            )
+        }));
+        queries.extend((0..self.permutation.commitments().len()).map(|column| {
+            query(
+                QueriedPolynomial::PermutationSigma { column },
+                Rotation::cur(),
+            )
+        }));
+        queries.push(query(QueriedPolynomial::VanishingQuotient, Rotation::cur()));
+        queries.push(query(QueriedPolynomial::VanishingRandom, Rotation::cur()));
+
+        QuerySchedule { queries }
+    }
+
+    /// Returns the size in bytes of a proof for `num_proofs` circuit instances,
+    /// opened with the multi-open verifier `V`, without creating it. Points are
+    /// counted compressed and scalars in their canonical representation, as the
+    /// transcripts in this crate write them.
+    pub fn proof_size<'params, Scheme, V>(&self, num_proofs: usize) -> usize
+    where
+        Scheme: CommitmentScheme<Curve = C>,
+        V: Verifier<'params, Scheme>,
+    {
+        let cs = &self.cs;
+        let num_permutation_sets = cs.permutation.columns.chunks(self.cs_degree - 2).count();
+        let query_instance = V::QUERY_INSTANCE || cs.committed_instances;
+        let num_instance_queries = if query_instance {
+            cs.instance_queries.len()
+        } else {
+            0
+        };
+
+        // Each circuit instance commits to its advice columns and to the grand
+        // products of its arguments, and evaluates them. All but the last
+        // permutation product are also evaluated at the last usable row.
+        let points_per_proof =
+            cs.num_advice_columns + num_permutation_sets + 3 * cs.lookups.len() + cs.shuffles.len();
+        let scalars_per_proof = num_instance_queries
+            + cs.advice_queries.len()
+            + (3 * num_permutation_sets).saturating_sub(1)
+            + 5 * cs.lookups.len()
+            + 2 * cs.shuffles.len();
+
+        // The vanishing argument commits to its random polynomial and to the
+        // pieces of h(X), and the columns in the verifying key are evaluated
+        // once for all instances.
+        let points = num_proofs * points_per_proof + 1 + self.domain.get_quotient_poly_degree();
+        let scalars = num_proofs * scalars_per_proof
+            + cs.fixed_queries.len()
+            + self.permutation.commitments().len()
+            + 1;
+
+        let schedule = self.query_schedule(num_proofs, query_instance);
+        let mut rotations: Vec<Rotation> = vec![];
+        for query in schedule.queries() {
+            if !rotations.contains(&query.rotation) {
+                rotations.push(query.rotation);
+            }
+        }
+        let (opening_points, opening_scalars) = V::proof_size(
+            self.domain.k(),
+            rotations.len(),
+            schedule.rotation_sets().len(),
+        );
+
+        let point_len = C::Repr::default().as_ref().len();
+        let scalar_len = <C::Scalar as PrimeField>::Repr::default().as_ref().len();
+        (points + opening_points) * point_len + (scalars + opening_scalars) * scalar_len
+    }
+}
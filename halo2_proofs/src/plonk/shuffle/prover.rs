@@ -0,0 +1,219 @@
+use super::super::{ChallengeGamma, ChallengeTheta, ChallengeX, Error, ProvingKey};
+use super::Argument;
+use crate::plonk::evaluation::GraphEvaluator;
+use crate::{
+    arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
+    poly::{
+        commitment::{Blind, Params, SecretBlind},
+        Coeff, EvaluationDomain, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
+    },
+    transcript::{EncodedChallenge, TranscriptWrite},
+};
+use group::{ff::Field, Curve};
+use rand_core::RngCore;
+use std::{
+    iter,
+    ops::{Mul, MulAssign},
+};
+
+#[derive(Debug)]
+pub(in crate::plonk) struct Committed<C: CurveAffine> {
+    pub(in crate::plonk) product_poly: Polynomial<C::Scalar, Coeff>,
+    product_blind: SecretBlind<C::Scalar>,
+}
+
+pub(in crate::plonk) struct Evaluated<C: CurveAffine> {
+    constructed: Committed<C>,
+}
+
+impl<F: FieldExt> Argument<F> {
+    /// Given a Shuffle with input expressions [A_0, A_1, ..., A_{m-1}] and shuffle
+    /// expressions [S_0, S_1, ..., S_{m-1}], this method
+    /// - constructs A_compressed = \theta^{m-1} A_0 + theta^{m-2} A_1 + ... + \theta A_{m-2} + A_{m-1}
+    ///   and S_compressed = \theta^{m-1} S_0 + theta^{m-2} S_1 + ... + \theta S_{m-2} + S_{m-1},
+    /// - constructs the grand product polynomial over the shuffle, and
+    /// - commits to it.
+    /// The Committed<C> struct is returned.
+    pub(in crate::plonk) fn commit_product<
+        'a,
+        'params: 'a,
+        C,
+        P: Params<'params, C>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    >(
+        &self,
+        pk: &ProvingKey<C>,
+        compressions: &(GraphEvaluator<C>, GraphEvaluator<C>),
+        params: &P,
+        domain: &EvaluationDomain<C::Scalar>,
+        theta: ChallengeTheta<C>,
+        gamma: ChallengeGamma<C>,
+        advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        challenges: &'a [C::Scalar],
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Committed<C>, Error>
+    where
+        C: CurveAffine<ScalarExt = F>,
+        C::Curve: Mul<F, Output = C::Curve> + MulAssign<F>,
+    {
+        let blinding_factors = pk.vk.cs.blinding_factors();
+
+        // Closure to get values of expressions and compress them, using the
+        // evaluators compiled from them at keygen
+        let compress_expressions = |compression: &GraphEvaluator<C>| {
+            domain.lagrange_from_vec(compression.evaluate_lagrange(
+                params.n() as usize,
+                fixed_values,
+                advice_values,
+                instance_values,
+                challenges,
+                &theta,
+            ))
+        };
+
+        // Get values of input expressions involved in the shuffle and compress them
+        let compressed_input_expression = compress_expressions(&compressions.0);
+
+        // Get values of shuffle expressions involved in the shuffle and compress them
+        let compressed_shuffle_expression = compress_expressions(&compressions.1);
+
+        // Goal is to compute the products of fractions
+        //
+        // Numerator: (\theta^{m-1} a_0(\omega^i) + \theta^{m-2} a_1(\omega^i) + ... + \theta a_{m-2}(\omega^i) + a_{m-1}(\omega^i) + \gamma)
+        // Denominator: (\theta^{m-1} s_0(\omega^i) + \theta^{m-2} s_1(\omega^i) + ... + \theta s_{m-2}(\omega^i) + s_{m-1}(\omega^i) + \gamma)
+        //
+        // where a_j(X) is the jth input expression in this shuffle,
+        // s_j(X) is the jth shuffle expression in this shuffle,
+        // and i is the ith row of the expression.
+        let mut shuffle_product = vec![C::Scalar::zero(); params.n() as usize];
+        // Denominator uses the compressed shuffle expression
+        parallelize(&mut shuffle_product, |shuffle_product, start| {
+            for (shuffle_product, shuffle_value) in shuffle_product
+                .iter_mut()
+                .zip(compressed_shuffle_expression[start..].iter())
+            {
+                *shuffle_product = *gamma + shuffle_value;
+            }
+        });
+
+        // Batch invert to obtain the denominators for the shuffle product
+        // polynomials
+        params.backend().batch_invert(&mut shuffle_product);
+
+        // Finish the computation of the entire fraction by computing the numerators
+        // (\theta^{m-1} a_0(\omega^i) + \theta^{m-2} a_1(\omega^i) + ... + \theta a_{m-2}(\omega^i) + a_{m-1}(\omega^i) + \gamma)
+        parallelize(&mut shuffle_product, |product, start| {
+            for (i, product) in product.iter_mut().enumerate() {
+                let i = i + start;
+
+                *product *= &(compressed_input_expression[i] + &*gamma);
+            }
+        });
+
+        // Compute the evaluations of the shuffle product polynomial
+        // over our domain, starting with z[0] = 1
+        let z = iter::once(C::Scalar::one())
+            .chain(shuffle_product)
+            .scan(C::Scalar::one(), |state, cur| {
+                *state *= &cur;
+                Some(*state)
+            })
+            // Take all rows including the "last" row which should
+            // be a boolean (and ideally 1, else soundness is broken)
+            .take(params.n() as usize - blinding_factors)
+            // Chain random blinding factors.
+            .chain((0..blinding_factors).map(|_| C::Scalar::random(&mut rng)))
+            .collect::<Vec<_>>();
+        assert_eq!(z.len(), params.n() as usize);
+        let z = domain.lagrange_from_vec(z);
+
+        #[cfg(feature = "sanity-checks")]
+        // This test works only with intermediate representations in this method.
+        // It can be used for debugging purposes.
+        {
+            // While in Lagrange basis, check that product is correctly constructed
+            let u = (params.n() as usize) - (blinding_factors + 1);
+
+            // l_0(X) * (1 - z(X)) = 0
+            assert_eq!(z[0], C::Scalar::one());
+
+            // z(\omega X) (s(X) + \gamma) - z(X) (a(X) + \gamma)
+            for i in 0..u {
+                let left = z[i + 1] * &(compressed_shuffle_expression[i] + &*gamma);
+                let right = z[i] * &(compressed_input_expression[i] + &*gamma);
+                assert_eq!(left, right);
+            }
+
+            // l_last(X) * (z(X)^2 - z(X)) = 0
+            // Assertion will fail only when soundness is broken, in which
+            // case this z[u] value will be zero. (bad!)
+            assert_eq!(z[u], C::Scalar::one());
+        }
+
+        let product_blind = SecretBlind(Blind(C::Scalar::random(rng)));
+        let product_commitment = params.commit_lagrange(&z, *product_blind).to_affine();
+        let z = domain.lagrange_to_coeff_with(params.backend(), z);
+
+        // Hash product commitment
+        transcript.write_point(product_commitment)?;
+
+        Ok(Committed::<C> {
+            product_poly: z,
+            product_blind,
+        })
+    }
+}
+
+impl<C: CurveAffine> Committed<C> {
+    pub(in crate::plonk) fn evaluate<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        self,
+        pk: &ProvingKey<C>,
+        x: ChallengeX<C>,
+        transcript: &mut T,
+    ) -> Result<Evaluated<C>, Error> {
+        let domain = &pk.vk.domain;
+        let x_next = domain.rotate_omega(*x, Rotation::next());
+
+        let product_eval = eval_polynomial(&self.product_poly, *x);
+        let product_next_eval = eval_polynomial(&self.product_poly, x_next);
+
+        // Hash each advice evaluation
+        for eval in iter::empty()
+            .chain(Some(product_eval))
+            .chain(Some(product_next_eval))
+        {
+            transcript.write_scalar(eval)?;
+        }
+
+        Ok(Evaluated { constructed: self })
+    }
+}
+
+impl<C: CurveAffine> Evaluated<C> {
+    pub(in crate::plonk) fn open<'a>(
+        &'a self,
+        pk: &'a ProvingKey<C>,
+        x: ChallengeX<C>,
+    ) -> impl Iterator<Item = ProverQuery<'a, C>> + Clone {
+        let x_next = pk.vk.domain.rotate_omega(*x, Rotation::next());
+
+        iter::empty()
+            // Open shuffle product commitments at x
+            .chain(Some(ProverQuery {
+                point: *x,
+                poly: &self.constructed.product_poly,
+                blind: *self.constructed.product_blind,
+            }))
+            // Open shuffle product commitments at x_next
+            .chain(Some(ProverQuery {
+                point: x_next,
+                poly: &self.constructed.product_poly,
+                blind: *self.constructed.product_blind,
+            }))
+    }
+}
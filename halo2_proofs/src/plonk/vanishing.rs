@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 
 use crate::arithmetic::CurveAffine;
 
+#[cfg(feature = "prover")]
 mod prover;
 mod verifier;
 
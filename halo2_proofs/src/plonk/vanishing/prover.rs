@@ -10,7 +10,7 @@ use crate::{
     plonk::{ChallengeX, ChallengeY, Error},
     poly::{
         self,
-        commitment::{Blind, ParamsProver},
+        commitment::{Blind, ParamsProver, SecretBlind},
         Coeff, EvaluationDomain, ExtendedLagrangeCoeff, Polynomial, ProverQuery,
     },
     transcript::{EncodedChallenge, TranscriptWrite},
@@ -18,18 +18,18 @@ use crate::{
 
 pub(in crate::plonk) struct Committed<C: CurveAffine> {
     random_poly: Polynomial<C::Scalar, Coeff>,
-    random_blind: Blind<C::Scalar>,
+    random_blind: SecretBlind<C::Scalar>,
 }
 
 pub(in crate::plonk) struct Constructed<C: CurveAffine> {
     h_pieces: Vec<Polynomial<C::Scalar, Coeff>>,
-    h_blinds: Vec<Blind<C::Scalar>>,
+    h_blinds: Vec<SecretBlind<C::Scalar>>,
     committed: Committed<C>,
 }
 
 pub(in crate::plonk) struct Evaluated<C: CurveAffine> {
     h_poly: Polynomial<C::Scalar, Coeff>,
-    h_blind: Blind<C::Scalar>,
+    h_blind: SecretBlind<C::Scalar>,
     committed: Committed<C>,
 }
 
@@ -52,10 +52,10 @@ impl<C: CurveAffine> Argument<C> {
             *coeff = C::Scalar::random(&mut rng);
         }
         // Sample a random blinding factor
-        let random_blind = Blind(C::Scalar::random(rng));
+        let random_blind = SecretBlind(Blind(C::Scalar::random(rng)));
 
         // Commit
-        let c = params.commit(&random_poly, random_blind).to_affine();
+        let c = params.commit(&random_poly, *random_blind).to_affine();
         transcript.write_point(c)?;
 
         Ok(Committed {
@@ -84,7 +84,7 @@ impl<C: CurveAffine> Committed<C> {
         let h_poly = domain.divide_by_vanishing_poly(h_poly);
 
         // Obtain final h(X) polynomial
-        let h_poly = domain.extended_to_coeff(h_poly);
+        let h_poly = domain.extended_to_coeff_with(params.backend(), h_poly);
 
         // Split h(X) up into pieces
         let h_pieces = h_poly
@@ -94,14 +94,14 @@ impl<C: CurveAffine> Committed<C> {
         drop(h_poly);
         let h_blinds: Vec<_> = h_pieces
             .iter()
-            .map(|_| Blind(C::Scalar::random(&mut rng)))
+            .map(|_| SecretBlind(Blind(C::Scalar::random(&mut rng))))
             .collect();
 
         // Compute commitments to each h(X) piece
         let h_commitments_projective: Vec<_> = h_pieces
             .iter()
             .zip(h_blinds.iter())
-            .map(|(h_piece, blind)| params.commit(h_piece, *blind))
+            .map(|(h_piece, blind)| params.commit(h_piece, **blind))
             .collect();
         let mut h_commitments = vec![C::identity(); h_commitments_projective.len()];
         C::Curve::batch_normalize(&h_commitments_projective, &mut h_commitments);
@@ -134,13 +134,14 @@ impl<C: CurveAffine> Constructed<C> {
             .rev()
             .fold(domain.empty_coeff(), |acc, eval| acc * xn + eval);
 
-        let h_blind = self
-            .h_blinds
-            .iter()
-            .rev()
-            .fold(Blind(C::Scalar::zero()), |acc, eval| {
-                acc * Blind(xn) + *eval
-            });
+        let h_blind = SecretBlind(
+            self.h_blinds
+                .iter()
+                .rev()
+                .fold(Blind(C::Scalar::zero()), |acc, eval| {
+                    acc * Blind(xn) + **eval
+                }),
+        );
 
         let random_eval = eval_polynomial(&self.committed.random_poly, *x);
         transcript.write_scalar(random_eval)?;
@@ -162,12 +163,12 @@ impl<C: CurveAffine> Evaluated<C> {
             .chain(Some(ProverQuery {
                 point: *x,
                 poly: &self.h_poly,
-                blind: self.h_blind,
+                blind: *self.h_blind,
             }))
             .chain(Some(ProverQuery {
                 point: *x,
                 poly: &self.committed.random_poly,
-                blind: self.committed.random_blind,
+                blind: *self.committed.random_blind,
             }))
     }
 }
@@ -4,17 +4,22 @@ use rand_core::RngCore;
 use std::iter;
 
 use super::{
-    vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Error,
-    VerifyingKey,
+    vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Circuit,
+    Error, TranscriptPhase, VerifyingKey,
 };
 use crate::arithmetic::{compute_inner_product, CurveAffine, FieldExt};
+use crate::helpers::{read_polynomial_vec, SerdeCurveAffine, SerdePrimeField};
 use crate::poly::commitment::{CommitmentScheme, Verifier};
 use crate::poly::VerificationStrategy;
 use crate::poly::{
     commitment::{Blind, Params, MSM},
-    Guard, VerifierQuery,
+    Guard, LagrangeCoeff, VerifierQuery,
 };
-use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, TranscriptRead};
+use crate::transcript::{
+    read_n_points, read_n_scalars, Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead,
+    TranscriptReadBuffer,
+};
+use crate::SerdeFormat;
 
 #[cfg(feature = "batch")]
 mod batch;
@@ -45,22 +50,13 @@ pub fn verify_proof<
         }
     }
 
-    let instance_commitments = if V::QUERY_INSTANCE {
+    let instance_commitments = if V::QUERY_INSTANCE || vk.cs.committed_instances {
         instances
             .iter()
             .map(|instance| {
                 instance
                     .iter()
-                    .map(|instance| {
-                        if instance.len() > params.n() as usize - (vk.cs.blinding_factors() + 1) {
-                            return Err(Error::InstanceTooLarge);
-                        }
-                        let mut poly = instance.to_vec();
-                        poly.resize(params.n() as usize, Scheme::Scalar::zero());
-                        let poly = vk.domain.lagrange_from_vec(poly);
-
-                        Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
-                    })
+                    .map(|instance| commit_instance(params, vk, instance))
                     .collect::<Result<Vec<_>, _>>()
             })
             .collect::<Result<Vec<_>, _>>()?
@@ -68,12 +64,113 @@ pub fn verify_proof<
         vec![vec![]; instances.len()]
     };
 
+    verify_proof_inner::<_, V, _, _, _, _>(
+        params,
+        vk,
+        strategy,
+        instances,
+        instance_commitments,
+        transcript,
+        |_, _| {},
+    )
+}
+
+/// Verifies a proof like [`verify_proof`], against commitments to the values of
+/// the instance columns, as computed by [`commit_instance`], rather than the
+/// values themselves. The verifier's work is then constant in the number of
+/// instance values.
+///
+/// This requires the circuit to commit to its instance columns with
+/// [`ConstraintSystem::commit_instances`], unless the multiopen argument `V` opens
+/// instance columns anyway. Returns [`Error::InvalidInstances`] if it does not, or
+/// if any proof does not have one commitment per instance column.
+///
+/// [`ConstraintSystem::commit_instances`]: super::ConstraintSystem::commit_instances
+pub fn verify_proof_with_instance_commitments<
+    'params,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instance_commitments: &[&[Scheme::Curve]],
+    transcript: &mut T,
+) -> Result<Strategy::Output, Error> {
+    if !(V::QUERY_INSTANCE || vk.cs.committed_instances)
+        || instance_commitments
+            .iter()
+            .any(|commitments| commitments.len() != vk.cs.num_instance_columns)
+    {
+        return Err(Error::InvalidInstances);
+    }
+
+    verify_proof_inner::<_, V, _, _, _, _>(
+        params,
+        vk,
+        strategy,
+        &[],
+        instance_commitments
+            .iter()
+            .map(|commitments| commitments.to_vec())
+            .collect(),
+        transcript,
+        |_, _| {},
+    )
+}
+
+/// Commits to the values of an instance column of the circuit of `vk`, for
+/// [`verify_proof_with_instance_commitments`].
+///
+/// Returns [`Error::InstanceTooLarge`] if there are more values than usable rows.
+pub fn commit_instance<'params, C: CurveAffine, P: Params<'params, C>>(
+    params: &P,
+    vk: &VerifyingKey<C>,
+    values: &[C::Scalar],
+) -> Result<C, Error> {
+    if values.len() > params.n() as usize - vk.cs.unusable_rows() {
+        return Err(Error::InstanceTooLarge);
+    }
+    let mut poly = values.to_vec();
+    poly.resize(params.n() as usize, C::Scalar::zero());
+    let poly = vk.domain.lagrange_from_vec(poly);
+
+    Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
+}
+
+// Verifies a proof given the values of the instance columns, or their commitments
+// if the instance columns are opened. `on_phase` is called with the transcript as
+// the verifier enters each phase of the protocol, before its first operation on
+// the transcript in that phase.
+pub(super) fn verify_proof_inner<
+    'params,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+    OnPhase: FnMut(&mut T, TranscriptPhase),
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instances: &[&[&[Scheme::Scalar]]],
+    instance_commitments: Vec<Vec<Scheme::Curve>>,
+    transcript: &mut T,
+    mut on_phase: OnPhase,
+) -> Result<Strategy::Output, Error> {
+    let query_instance = V::QUERY_INSTANCE || vk.cs.committed_instances;
     let num_proofs = instance_commitments.len();
 
     // Hash verification key into transcript
+    on_phase(transcript, TranscriptPhase::VerifyingKey);
     vk.hash_into(transcript)?;
 
-    if V::QUERY_INSTANCE {
+    on_phase(transcript, TranscriptPhase::Instance);
+    if query_instance {
         for instance_commitments in instance_commitments.iter() {
             // Hash the instance (external) commitments into the transcript
             for commitment in instance_commitments {
@@ -96,7 +193,8 @@ pub fn verify_proof<
             vec![vec![Scheme::Curve::default(); vk.cs.num_advice_columns]; num_proofs];
         let mut challenges = vec![Scheme::Scalar::zero(); vk.cs.num_challenges];
 
-        for current_phase in vk.cs.phases() {
+        for (index, current_phase) in vk.cs.phases().enumerate() {
+            on_phase(transcript, TranscriptPhase::Advice(index as u8));
             for advice_commitments in advice_commitments.iter_mut() {
                 for (phase, commitment) in vk
                     .cs
@@ -120,6 +218,7 @@ pub fn verify_proof<
     };
 
     // Sample theta challenge for keeping lookup columns linearly independent
+    on_phase(transcript, TranscriptPhase::LookupPermuted);
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
 
     let lookups_permuted = (0..num_proofs)
@@ -134,6 +233,7 @@ pub fn verify_proof<
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample beta challenge
+    on_phase(transcript, TranscriptPhase::Products);
     let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
 
     // Sample gamma challenge
@@ -157,6 +257,18 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let shuffles_committed = (0..num_proofs)
+        .map(|_| -> Result<Vec<_>, _> {
+            // Hash each shuffle product commitment
+            vk.cs
+                .shuffles
+                .iter()
+                .map(|argument| argument.read_product_commitment(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    on_phase(transcript, TranscriptPhase::Vanishing);
     let vanishing = vanishing::Argument::read_commitments_before_y(transcript)?;
 
     // Sample y challenge, which keeps the gates linearly independent.
@@ -166,8 +278,9 @@ pub fn verify_proof<
 
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
+    on_phase(transcript, TranscriptPhase::Evaluations);
     let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
-    let instance_evals = if V::QUERY_INSTANCE {
+    let instance_evals = if query_instance {
         (0..num_proofs)
             .map(|_| -> Result<Vec<_>, _> {
                 read_n_scalars(transcript, vk.cs.instance_queries.len())
@@ -239,6 +352,16 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let shuffles_evaluated = shuffles_committed
+        .into_iter()
+        .map(|shuffles| -> Result<Vec<_>, _> {
+            shuffles
+                .into_iter()
+                .map(|shuffle| shuffle.evaluate(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // This check ensures the circuit is satisfied so long as the polynomial
     // commitments open to the correct values.
     let vanishing = {
@@ -262,63 +385,88 @@ pub fn verify_proof<
             .zip(instance_evals.iter())
             .zip(permutations_evaluated.iter())
             .zip(lookups_evaluated.iter())
-            .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
-                let challenges = &challenges;
-                let fixed_evals = &fixed_evals;
-                std::iter::empty()
-                    // Evaluate the circuit using the custom gates provided
-                    .chain(vk.cs.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |poly| {
-                            poly.evaluate(
-                                &|scalar| scalar,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|query| fixed_evals[query.index],
-                                &|query| advice_evals[query.index],
-                                &|query| instance_evals[query.index],
-                                &|challenge| challenges[challenge.index()],
-                                &|a| -a,
-                                &|a, b| a + &b,
-                                &|a, b| a * &b,
-                                &|a, scalar| a * &scalar,
-                            )
-                        })
-                    }))
-                    .chain(permutation.expressions(
-                        vk,
-                        &vk.cs.permutation,
-                        &permutations_common,
-                        advice_evals,
-                        fixed_evals,
-                        instance_evals,
-                        l_0,
-                        l_last,
-                        l_blind,
-                        beta,
-                        gamma,
-                        x,
-                    ))
-                    .chain(
-                        lookups
-                            .iter()
-                            .zip(vk.cs.lookups.iter())
-                            .flat_map(move |(p, argument)| {
-                                p.expressions(
-                                    l_0,
-                                    l_last,
-                                    l_blind,
-                                    argument,
-                                    theta,
-                                    beta,
-                                    gamma,
-                                    advice_evals,
-                                    fixed_evals,
-                                    instance_evals,
-                                    challenges,
+            .zip(shuffles_evaluated.iter())
+            .flat_map(
+                |((((advice_evals, instance_evals), permutation), lookups), shuffles)| {
+                    let challenges = &challenges;
+                    let fixed_evals = &fixed_evals;
+                    std::iter::empty()
+                        // Evaluate the circuit using the custom gates provided
+                        .chain(vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |poly| {
+                                poly.evaluate(
+                                    &|scalar| scalar,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|query| fixed_evals[query.index],
+                                    &|query| advice_evals[query.index],
+                                    &|query| instance_evals[query.index],
+                                    &|challenge| challenges[challenge.index()],
+                                    &|a| -a,
+                                    &|a, b| a + &b,
+                                    &|a, b| a * &b,
+                                    &|a, scalar| a * &scalar,
                                 )
                             })
-                            .into_iter(),
-                    )
-            });
+                        }))
+                        .chain(permutation.expressions(
+                            vk,
+                            &vk.cs.permutation,
+                            &permutations_common,
+                            advice_evals,
+                            fixed_evals,
+                            instance_evals,
+                            l_0,
+                            l_last,
+                            l_blind,
+                            beta,
+                            gamma,
+                            x,
+                        ))
+                        .chain(
+                            lookups
+                                .iter()
+                                .zip(vk.cs.lookups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                        .chain(
+                            shuffles
+                                .iter()
+                                .zip(vk.cs.shuffles.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                },
+            );
 
         vanishing.verify(params, expressions, y, xn)
     };
@@ -330,17 +478,24 @@ pub fn verify_proof<
         .zip(advice_evals.iter())
         .zip(permutations_evaluated.iter())
         .zip(lookups_evaluated.iter())
+        .zip(shuffles_evaluated.iter())
         .flat_map(
             |(
                 (
-                    (((instance_commitments, instance_evals), advice_commitments), advice_evals),
-                    permutation,
+                    (
+                        (
+                            ((instance_commitments, instance_evals), advice_commitments),
+                            advice_evals,
+                        ),
+                        permutation,
+                    ),
+                    lookups,
                 ),
-                lookups,
+                shuffles,
             )| {
                 iter::empty()
                     .chain(
-                        V::QUERY_INSTANCE
+                        query_instance
                             .then_some(vk.cs.instance_queries.iter().enumerate().map(
                                 move |(query_index, &(column, at))| {
                                     VerifierQuery::new_commitment(
@@ -369,6 +524,12 @@ pub fn verify_proof<
                             .flat_map(move |p| p.queries(vk, x))
                             .into_iter(),
                     )
+                    .chain(
+                        shuffles
+                            .iter()
+                            .flat_map(move |p| p.queries(vk, x))
+                            .into_iter(),
+                    )
             },
         )
         .chain(
@@ -390,6 +551,7 @@ pub fn verify_proof<
     // We are now convinced the circuit is satisfied so long as the
     // polynomial commitments open to the correct values.
 
+    on_phase(transcript, TranscriptPhase::Multiopen);
     let verifier = V::new(params);
     strategy.process(|msm| {
         verifier
@@ -397,3 +559,237 @@ pub fn verify_proof<
             .map_err(|_| Error::Opening)
     })
 }
+
+/// Verifies a proof like [`verify_proof`], additionally checking that the instance
+/// cell designated with [`ConstraintSystem::set_nonce_cell`] holds the expected
+/// nonce in each of the proof's instances.
+///
+/// Returns [`Error::InvalidNonce`] if the circuit does not designate a nonce cell,
+/// if `nonces` does not have one nonce per circuit instance, or if a nonce does
+/// not match.
+///
+/// [`ConstraintSystem::set_nonce_cell`]: super::ConstraintSystem::set_nonce_cell
+pub fn verify_proof_with_nonce<
+    'params,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instances: &[&[&[Scheme::Scalar]]],
+    nonces: &[Scheme::Scalar],
+    transcript: &mut T,
+) -> Result<Strategy::Output, Error> {
+    let (column, row) = vk.cs.nonce_cell.ok_or(Error::InvalidNonce)?;
+    if nonces.len() != instances.len() {
+        return Err(Error::InvalidNonce);
+    }
+    for (instance, nonce) in instances.iter().zip(nonces.iter()) {
+        let value = instance
+            .get(column.index())
+            .and_then(|column| column.get(row));
+        if value != Some(nonce) {
+            return Err(Error::InvalidNonce);
+        }
+    }
+
+    verify_proof::<_, V, _, _, _>(params, vk, strategy, instances, transcript)
+}
+
+/// Verifies a proof like [`verify_proof`], with `instances` holding only the
+/// values of the instance columns that are not derived. The values of the columns
+/// allocated with [`ConstraintSystem::derived_instance_column`] are computed by
+/// `derive` with [`ConstraintSystem::derive_instances`].
+///
+/// Returns [`Error::InvalidInstances`] if any proof's instance does not have one
+/// entry per instance column that is not derived.
+///
+/// [`ConstraintSystem::derived_instance_column`]: super::ConstraintSystem::derived_instance_column
+/// [`ConstraintSystem::derive_instances`]: super::ConstraintSystem::derive_instances
+pub fn verify_proof_with_derived_instances<
+    'params,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E>,
+    Strategy: VerificationStrategy<'params, Scheme, V>,
+    D: Fn(&str, &[&[Scheme::Scalar]]) -> Vec<Scheme::Scalar>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    instances: &[&[&[Scheme::Scalar]]],
+    derive: D,
+    transcript: &mut T,
+) -> Result<Strategy::Output, Error> {
+    let instances = instances
+        .iter()
+        .map(|instance| vk.cs.derive_instances(instance, &derive))
+        .collect::<Result<Vec<_>, _>>()?;
+    let instances = instances
+        .iter()
+        .map(|instance| {
+            instance
+                .iter()
+                .map(|values| &values[..])
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let instances = instances
+        .iter()
+        .map(|instance| &instance[..])
+        .collect::<Vec<_>>();
+
+    verify_proof::<_, V, _, _, _>(params, vk, strategy, &instances, transcript)
+}
+
+/// Verifies a batch of proofs that were created for the same verifying key.
+///
+/// Every proof is processed into `strategy` in turn, so that the expensive
+/// final check (a multiexponentiation for IPA, a pairing for KZG) is only
+/// performed once for the whole batch. Each item of `proofs` pairs the
+/// instances of a proof with a transcript over that proof.
+///
+/// Returns `Ok(false)` if *some* proof was invalid. If the caller needs to
+/// identify specific failing proofs, it must re-process the proofs separately.
+pub fn verify_proofs<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E> + 'a,
+    Strategy: VerificationStrategy<'params, Scheme, V, Output = Strategy>,
+    I: IntoIterator<Item = (&'a [&'a [&'a [Scheme::Scalar]]], &'a mut T)>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    strategy: Strategy,
+    proofs: I,
+) -> Result<bool, Error>
+where
+    Scheme::Scalar: 'a,
+{
+    let strategy = proofs
+        .into_iter()
+        .try_fold(strategy, |strategy, (instances, transcript)| {
+            verify_proof::<_, V, _, _, _>(params, vk, strategy, instances, transcript)
+        })?;
+
+    Ok(strategy.finalize())
+}
+
+/// Verifies a batch of proofs that may have been created for different
+/// circuits, sharing the same parameters.
+///
+/// Each item of `proofs` pairs the verifying key and instances of a proof with a
+/// transcript over that proof. As with [`verify_proofs`], the expensive final
+/// check is only performed once for the whole batch, so the cost of verifying
+/// proofs for many circuits grows with the number of proofs only through the
+/// cheap per-proof work.
+///
+/// Returns `Ok(false)` if *some* proof was invalid. If the caller needs to
+/// identify specific failing proofs, it must re-process the proofs separately.
+pub fn verify_proofs_multi_vk<
+    'params,
+    'a,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptRead<Scheme::Curve, E> + 'a,
+    Strategy: VerificationStrategy<'params, Scheme, V, Output = Strategy>,
+    I: IntoIterator<
+        Item = (
+            &'a VerifyingKey<Scheme::Curve>,
+            &'a [&'a [&'a [Scheme::Scalar]]],
+            &'a mut T,
+        ),
+    >,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    strategy: Strategy,
+    proofs: I,
+) -> Result<bool, Error>
+where
+    Scheme::Scalar: 'a,
+{
+    let strategy =
+        proofs
+            .into_iter()
+            .try_fold(strategy, |strategy, (vk, instances, transcript)| {
+                verify_proof::<_, V, _, _, _>(params, vk, strategy, instances, transcript)
+            })?;
+
+    Ok(strategy.finalize())
+}
+
+/// Encodes the instance columns of a single proof in the format expected by
+/// [`verify_proof_bytes`]: the number of columns as a big-endian `u32`, then for
+/// each column its length as a big-endian `u32` followed by its values in
+/// [`SerdeFormat::Processed`] form.
+pub fn instances_to_bytes<F: SerdePrimeField>(instances: &[&[F]]) -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(&(instances.len() as u32).to_be_bytes());
+    for column in instances {
+        bytes.extend_from_slice(&(column.len() as u32).to_be_bytes());
+        for value in column.iter() {
+            value
+                .write(&mut bytes, SerdeFormat::Processed)
+                .expect("Writing to vector should not fail");
+        }
+    }
+    bytes
+}
+
+/// Verifies a single proof given only byte buffers, for use behind interfaces
+/// such as a WASM module that cannot pass structured values.
+///
+/// `vk` is read with [`VerifyingKey::read`] for `ConcreteCircuit` in the given
+/// `format`, `instances` is encoded as by [`instances_to_bytes`], and `proof` is
+/// read with a [`Blake2bRead`] transcript.
+pub fn verify_proof_bytes<
+    'params,
+    ConcreteCircuit: Circuit<Scheme::Scalar>,
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    Strategy: VerificationStrategy<'params, Scheme, V, Output = Strategy>,
+>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &[u8],
+    format: SerdeFormat,
+    instances: &[u8],
+    proof: &[u8],
+) -> Result<bool, Error>
+where
+    Scheme::Curve: SerdeCurveAffine,
+    Scheme::Scalar: SerdePrimeField,
+{
+    let vk = VerifyingKey::<Scheme::Curve>::from_bytes::<ConcreteCircuit>(vk, format)?;
+
+    let mut reader = instances;
+    let instances: Vec<Vec<Scheme::Scalar>> =
+        read_polynomial_vec::<_, _, LagrangeCoeff>(&mut reader, SerdeFormat::Processed)
+            .map_err(|_| Error::InvalidInstances)?
+            .into_iter()
+            .map(|column| column.to_vec())
+            .collect();
+    if !reader.is_empty() {
+        return Err(Error::InvalidInstances);
+    }
+    let instances: Vec<&[Scheme::Scalar]> = instances.iter().map(|column| &column[..]).collect();
+
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let strategy = verify_proof::<_, V, _, _, _>(
+        params,
+        &vk,
+        Strategy::new(params),
+        &[&instances[..]],
+        &mut transcript,
+    )?;
+
+    Ok(strategy.finalize())
+}
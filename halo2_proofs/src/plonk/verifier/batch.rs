@@ -3,11 +3,10 @@ use std::{io, marker::PhantomData};
 use group::ff::Field;
 use halo2curves::CurveAffine;
 use rand_core::{OsRng, RngCore};
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
 use super::{verify_proof, VerificationStrategy};
 use crate::{
-    multicore,
+    multicore::prelude::*,
     plonk::{Error, VerifyingKey},
     poly::{
         commitment::{Params, MSM},
@@ -100,30 +99,33 @@ impl<C: CurveAffine> BatchVerifier<C> {
             acc
         }
 
-        let final_msm = self
-            .items
-            .into_par_iter()
-            .enumerate()
-            .map(|(i, item)| {
-                let instances: Vec<Vec<_>> = item
-                    .instances
-                    .iter()
-                    .map(|i| i.iter().map(|c| &c[..]).collect())
-                    .collect();
-                let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
-
-                let strategy = BatchStrategy::new(params);
-                let mut transcript = Blake2bRead::init(&item.proof[..]);
-                verify_proof(params, vk, strategy, &instances, &mut transcript).map_err(|e| {
-                    tracing::debug!("Batch item {} failed verification: {}", i, e);
-                    e
-                })
+        let msms = self.items.into_par_iter().enumerate().map(|(i, item)| {
+            let instances: Vec<Vec<_>> = item
+                .instances
+                .iter()
+                .map(|i| i.iter().map(|c| &c[..]).collect())
+                .collect();
+            let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
+
+            let strategy = BatchStrategy::new(params);
+            let mut transcript = Blake2bRead::init(&item.proof[..]);
+            verify_proof(params, vk, strategy, &instances, &mut transcript).map_err(|e| {
+                tracing::debug!("Batch item {} failed verification: {}", i, e);
+                e
             })
+        });
+
+        #[cfg(feature = "multicore")]
+        let final_msm = msms
             .try_fold(
                 || params.empty_msm(),
                 |msm, res| res.map(|proof_msm| accumulate_msm(msm, proof_msm)),
             )
             .try_reduce(|| params.empty_msm(), |a, b| Ok(accumulate_msm(a, b)));
+        #[cfg(not(feature = "multicore"))]
+        let final_msm = msms.try_fold(params.empty_msm(), |msm, res| {
+            res.map(|proof_msm| accumulate_msm(msm, proof_msm))
+        });
 
         match final_msm {
             Ok(msm) => msm.check(),
@@ -0,0 +1,378 @@
+//! The layout of the transcript that the verifier reads, for writing verifiers
+//! in other languages and checking proofs against it.
+
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
+use ff::{Field, PrimeField};
+
+use super::{verifier::verify_proof_inner, Error, VerifyingKey};
+use crate::{
+    arithmetic::{CurveAffine, FieldExt},
+    poly::{
+        commitment::{CommitmentScheme, Params, Verifier},
+        VerificationStrategy,
+    },
+    transcript::{Challenge255, EncodedChallenge, Transcript, TranscriptRead},
+};
+
+/// A phase of the protocol, in the order in which the verifier goes through them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptPhase {
+    /// The verifying key, absorbed as a single scalar.
+    VerifyingKey,
+    /// The instance columns of each proof: their values, or their commitments if
+    /// the verifier opens instance columns.
+    Instance,
+    /// The advice commitments of each proof in the given phase of the circuit,
+    /// followed by the challenges of that phase.
+    Advice(u8),
+    /// The `theta` challenge, followed by the permuted input and table
+    /// commitments of each proof's lookups.
+    LookupPermuted,
+    /// The `beta` and `gamma` challenges, followed by the permutation, lookup and
+    /// shuffle product commitments of each proof.
+    Products,
+    /// The vanishing argument's random commitment, the `y` challenge, and the
+    /// commitments to the pieces of the quotient.
+    Vanishing,
+    /// The `x` challenge, followed by the evaluations of the queried polynomials.
+    Evaluations,
+    /// The multiopen argument.
+    Multiopen,
+}
+
+/// The kind of an operation on the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptOperationKind {
+    /// A point known to the verifier is absorbed.
+    CommonPoint,
+    /// A scalar known to the verifier is absorbed.
+    CommonScalar,
+    /// A compressed point is read from the proof and absorbed.
+    ReadPoint,
+    /// A scalar is read from the proof in its canonical representation and
+    /// absorbed.
+    ReadScalar,
+    /// A challenge is squeezed.
+    SqueezeChallenge,
+}
+
+/// An operation on the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TranscriptOperation {
+    /// The kind of the operation.
+    pub kind: TranscriptOperationKind,
+    /// The phase of the protocol in which the operation occurs.
+    pub phase: TranscriptPhase,
+    /// The size in bytes of the point or scalar: a compressed point, or the
+    /// canonical representation of a scalar. Only reads take up bytes of the
+    /// proof.
+    pub size: usize,
+}
+
+impl TranscriptOperation {
+    /// Returns whether the operation reads from the proof.
+    pub fn is_read(&self) -> bool {
+        matches!(
+            self.kind,
+            TranscriptOperationKind::ReadPoint | TranscriptOperationKind::ReadScalar
+        )
+    }
+}
+
+/// A mismatch between a proof and a [`TranscriptLayout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireFormatError {
+    /// The proof ended before the read at index `operation` of the layout, which
+    /// starts at byte `offset`.
+    Truncated {
+        /// The index of the operation in the layout.
+        operation: usize,
+        /// The offset of the operation in the proof.
+        offset: usize,
+    },
+    /// The bytes read by the operation at index `operation` of the layout,
+    /// starting at byte `offset`, do not encode a point on the curve.
+    InvalidPoint {
+        /// The index of the operation in the layout.
+        operation: usize,
+        /// The offset of the operation in the proof.
+        offset: usize,
+    },
+    /// The bytes read by the operation at index `operation` of the layout,
+    /// starting at byte `offset`, are not the canonical representation of a
+    /// scalar.
+    InvalidScalar {
+        /// The index of the operation in the layout.
+        operation: usize,
+        /// The offset of the operation in the proof.
+        offset: usize,
+    },
+    /// The proof has `len` more bytes after the last read, starting at byte
+    /// `offset`.
+    TrailingBytes {
+        /// The length of the proof according to the layout.
+        offset: usize,
+        /// The number of bytes after it.
+        len: usize,
+    },
+}
+
+impl fmt::Display for WireFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireFormatError::Truncated { operation, offset } => write!(
+                f,
+                "proof ends before operation {} at byte {}",
+                operation, offset
+            ),
+            WireFormatError::InvalidPoint { operation, offset } => write!(
+                f,
+                "operation {} at byte {} does not read a valid point",
+                operation, offset
+            ),
+            WireFormatError::InvalidScalar { operation, offset } => write!(
+                f,
+                "operation {} at byte {} does not read a canonical scalar",
+                operation, offset
+            ),
+            WireFormatError::TrailingBytes { offset, len } => {
+                write!(f, "{} trailing bytes after byte {}", len, offset)
+            }
+        }
+    }
+}
+
+impl error::Error for WireFormatError {}
+
+/// The operations that the verifier performs on the transcript, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptLayout<C: CurveAffine> {
+    operations: Vec<TranscriptOperation>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: CurveAffine> TranscriptLayout<C> {
+    /// Returns the operations in the order in which the verifier performs them.
+    pub fn operations(&self) -> &[TranscriptOperation] {
+        &self.operations
+    }
+
+    /// Checks that `proof` consists of exactly the reads of this layout, and that
+    /// each of them decodes to a point or a scalar. Returns the first mismatch. A
+    /// proof that passes has the length given by [`VerifyingKey::proof_size`].
+    ///
+    /// This does not check that the proof verifies.
+    pub fn check(&self, proof: &[u8]) -> Result<(), WireFormatError> {
+        let mut offset = 0;
+        for (index, operation) in self.operations.iter().enumerate() {
+            if !operation.is_read() {
+                continue;
+            }
+            let bytes =
+                proof
+                    .get(offset..offset + operation.size)
+                    .ok_or(WireFormatError::Truncated {
+                        operation: index,
+                        offset,
+                    })?;
+            match operation.kind {
+                TranscriptOperationKind::ReadPoint => {
+                    let mut repr = C::Repr::default();
+                    repr.as_mut().copy_from_slice(bytes);
+                    if bool::from(C::from_bytes(&repr).is_none()) {
+                        return Err(WireFormatError::InvalidPoint {
+                            operation: index,
+                            offset,
+                        });
+                    }
+                }
+                _ => {
+                    let mut repr = <C::Scalar as PrimeField>::Repr::default();
+                    repr.as_mut().copy_from_slice(bytes);
+                    if bool::from(C::Scalar::from_repr(repr).is_none()) {
+                        return Err(WireFormatError::InvalidScalar {
+                            operation: index,
+                            offset,
+                        });
+                    }
+                }
+            }
+            offset += operation.size;
+        }
+
+        if offset < proof.len() {
+            return Err(WireFormatError::TrailingBytes {
+                offset,
+                len: proof.len() - offset,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine> VerifyingKey<C> {
+    /// Returns the operations that [`verify_proof`](super::verify_proof) performs
+    /// on the transcript when verifying a proof with the multiopen verifier `V`,
+    /// for proofs whose instance columns have the lengths in `instance_lengths`,
+    /// one entry per circuit instance in the proof.
+    ///
+    /// The layout is recorded by running the verifier itself over a transcript
+    /// that reads placeholder values, so it is exactly the layout that the
+    /// verifier expects. The strategy `Strategy` only affects how the final
+    /// check is performed, not the layout.
+    ///
+    /// Instance values are only absorbed if the verifier does not open instance
+    /// columns, so the layout then depends on their lengths. Returns
+    /// [`Error::InvalidInstances`] if an entry of `instance_lengths` does not have
+    /// one length per instance column, and [`Error::InstanceTooLarge`] if a
+    /// length exceeds the number of usable rows.
+    pub fn transcript_layout<'params, Scheme, V, Strategy>(
+        &self,
+        params: &'params Scheme::ParamsVerifier,
+        instance_lengths: &[&[usize]],
+    ) -> Result<TranscriptLayout<C>, Error>
+    where
+        Scheme: CommitmentScheme<Curve = C>,
+        V: Verifier<'params, Scheme>,
+        Strategy: VerificationStrategy<'params, Scheme, V>,
+    {
+        let usable_rows = params.n() as usize - self.cs.unusable_rows();
+        for lengths in instance_lengths.iter() {
+            if lengths.len() != self.cs.num_instance_columns {
+                return Err(Error::InvalidInstances);
+            }
+            if lengths.iter().any(|&len| len > usable_rows) {
+                return Err(Error::InstanceTooLarge);
+            }
+        }
+
+        let instance_commitments =
+            vec![vec![C::generator(); self.cs.num_instance_columns]; instance_lengths.len()];
+        let instances = if V::QUERY_INSTANCE || self.cs.committed_instances {
+            vec![]
+        } else {
+            instance_lengths
+                .iter()
+                .map(|lengths| {
+                    lengths
+                        .iter()
+                        .map(|&len| vec![C::Scalar::zero(); len])
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+        let instances = instances
+            .iter()
+            .map(|instance| {
+                instance
+                    .iter()
+                    .map(|values| &values[..])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let instances = instances
+            .iter()
+            .map(|instance| &instance[..])
+            .collect::<Vec<_>>();
+
+        let mut recorder = Recorder::<C>::new();
+        let result = verify_proof_inner::<_, V, _, _, _, _>(
+            params,
+            self,
+            Strategy::new(params),
+            &instances,
+            instance_commitments,
+            &mut recorder,
+            |recorder: &mut Recorder<C>, phase| recorder.phase = phase,
+        );
+        // The placeholder proof is not expected to pass the final check, but
+        // nothing before the multiopen argument may fail.
+        match result {
+            Err(error) if recorder.phase != TranscriptPhase::Multiopen => Err(error),
+            _ => Ok(TranscriptLayout {
+                operations: recorder.operations,
+                _marker: PhantomData,
+            }),
+        }
+    }
+}
+
+/// A transcript that records the operations performed on it. Reads return
+/// placeholder values, and challenges are derived from everything absorbed so
+/// far so that the verifier's arithmetic does not hit degenerate cases.
+struct Recorder<C: CurveAffine> {
+    state: Blake2bState,
+    phase: TranscriptPhase,
+    operations: Vec<TranscriptOperation>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: CurveAffine> Recorder<C> {
+    fn new() -> Self {
+        Recorder {
+            state: Blake2bParams::new()
+                .hash_length(64)
+                .personal(b"Halo2-WireFormat")
+                .to_state(),
+            phase: TranscriptPhase::VerifyingKey,
+            operations: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    fn record(&mut self, kind: TranscriptOperationKind) {
+        let size = match kind {
+            TranscriptOperationKind::CommonPoint | TranscriptOperationKind::ReadPoint => {
+                C::Repr::default().as_ref().len()
+            }
+            _ => <C::Scalar as PrimeField>::Repr::default().as_ref().len(),
+        };
+        self.operations.push(TranscriptOperation {
+            kind,
+            phase: self.phase,
+            size,
+        });
+    }
+
+    fn hash(&mut self) -> [u8; 64] {
+        self.state.update(&[0]);
+        self.state.clone().finalize().as_bytes().try_into().unwrap()
+    }
+}
+
+impl<C: CurveAffine> Transcript<C, Challenge255<C>> for Recorder<C> {
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.record(TranscriptOperationKind::SqueezeChallenge);
+        Challenge255::new(&self.hash())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.record(TranscriptOperationKind::CommonPoint);
+        self.state.update(point.to_bytes().as_ref());
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.record(TranscriptOperationKind::CommonScalar);
+        self.state.update(scalar.to_repr().as_ref());
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine> TranscriptRead<C, Challenge255<C>> for Recorder<C> {
+    fn read_point(&mut self) -> io::Result<C> {
+        self.record(TranscriptOperationKind::ReadPoint);
+        Ok(C::generator())
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.record(TranscriptOperationKind::ReadScalar);
+        Ok(C::Scalar::from_bytes_wide(&self.hash()))
+    }
+}
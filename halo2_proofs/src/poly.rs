@@ -5,6 +5,7 @@
 use crate::arithmetic::parallelize;
 use crate::helpers::SerdePrimeField;
 use crate::plonk::Assigned;
+use crate::storage::Storage;
 use crate::SerdeFormat;
 
 use ff::PrimeField;
@@ -27,7 +28,7 @@ pub mod ipa;
 /// KZG commitment scheme
 pub mod kzg;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod multiopen_test;
 
 pub use domain::*;
@@ -67,7 +68,7 @@ impl Basis for ExtendedLagrangeCoeff {}
 /// basis.
 #[derive(Clone, Debug)]
 pub struct Polynomial<F, B> {
-    values: Vec<F>,
+    values: Storage<F>,
     _marker: PhantomData<B>,
 }
 
@@ -147,30 +148,48 @@ impl<F, B> Polynomial<F, B> {
     }
 }
 
+#[cfg(all(feature = "mmap", feature = "prover"))]
+impl<F, B> Polynomial<F, B> {
+    /// Wraps values that were mapped from a file.
+    pub(crate) fn from_storage(values: Storage<F>) -> Self {
+        Polynomial {
+            values,
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<F: SerdePrimeField, B> Polynomial<F, B> {
     /// Reads polynomial from buffer using `SerdePrimeField::read`.  
-    pub(crate) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Self {
+    pub(crate) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
         let mut poly_len = [0u8; 4];
-        reader.read_exact(&mut poly_len).unwrap();
+        reader.read_exact(&mut poly_len)?;
         let poly_len = u32::from_be_bytes(poly_len);
-        Self {
-            values: (0..poly_len).map(|_| F::read(reader, format)).collect(),
+        Ok(Self {
+            values: (0..poly_len)
+                .map(|_| F::read(reader, format))
+                .collect::<io::Result<_>>()?,
             _marker: PhantomData,
-        }
+        })
     }
 
     /// Writes polynomial to buffer using `SerdePrimeField::write`.  
-    pub(crate) fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) {
-        writer
-            .write_all(&(self.values.len() as u32).to_be_bytes())
-            .unwrap();
+    #[cfg(feature = "prover")]
+    pub(crate) fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        writer.write_all(&(self.values.len() as u32).to_be_bytes())?;
         for value in self.values.iter() {
-            value.write(writer, format);
+            value.write(writer, format)?;
         }
+        Ok(())
     }
 }
 
 /// Invert each polynomial in place for memory efficiency
+#[cfg(feature = "prover")]
 pub(crate) fn batch_invert_assigned_ref<F: FieldExt>(
     assigned: Vec<&Polynomial<Assigned<F>, LagrangeCoeff>>,
 ) -> Vec<Polynomial<F, LagrangeCoeff>> {
@@ -308,7 +327,7 @@ impl<F: Field, B: Basis> Mul<F> for Polynomial<F, B> {
     fn mul(mut self, rhs: F) -> Polynomial<F, B> {
         if rhs == F::zero() {
             return Polynomial {
-                values: vec![F::zero(); self.len()],
+                values: vec![F::zero(); self.len()].into(),
                 _marker: PhantomData,
             };
         }
@@ -3,8 +3,11 @@ use super::{
     strategy::Guard,
     Coeff, LagrangeCoeff, Polynomial,
 };
+use crate::backend::{Backend, Cpu};
+use crate::helpers::SerdePrimeField;
 use crate::poly::Error;
 use crate::transcript::{EncodedChallenge, TranscriptRead, TranscriptWrite};
+use crate::SerdeFormat;
 use ff::Field;
 use group::Curve;
 use halo2curves::{CurveAffine, CurveExt, FieldExt};
@@ -12,7 +15,8 @@ use rand_core::RngCore;
 use std::{
     fmt::Debug,
     io::{self, Read, Write},
-    ops::{Add, AddAssign, Mul, MulAssign},
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
 /// Defines components of a commitment scheme.
@@ -51,13 +55,22 @@ pub trait Params<'params, C: CurveAffine>: Sized + Clone {
     /// Size of the circuit
     fn n(&self) -> u64;
 
-    /// Downsize `Params` with smaller `k`.
+    /// Downsize `Params` with smaller `k`, so that parameters generated (or
+    /// read) once for a large circuit can be reused for smaller circuits.
+    ///
+    /// Panics if `k` is larger than the current `k`.
     fn downsize(&mut self, k: u32);
 
     /// Generates an empty multiscalar multiplication struct using the
     /// appropriate params.
     fn empty_msm(&'params self) -> Self::MSM;
 
+    /// Returns the backend that performs the expensive arithmetic of the prover
+    /// with these parameters.
+    fn backend(&self) -> &dyn Backend<C> {
+        &Cpu
+    }
+
     /// This commits to a polynomial using its evaluations over the $2^k$ size
     /// evaluation domain. The commitment will be blinded by the blinding factor
     /// `r`.
@@ -165,6 +178,11 @@ pub trait Verifier<'params, Scheme: CommitmentScheme> {
     /// Creates new verifier instance
     fn new(params: &'params Scheme::ParamsVerifier) -> Self;
 
+    /// Returns the number of curve points and scalars in a multi-open proof
+    /// over parameters of size `2^k`, for queries at `num_points` distinct
+    /// points that fall into `num_point_sets` distinct sets of points.
+    fn proof_size(k: u32, num_points: usize, num_point_sets: usize) -> (usize, usize);
+
     /// Process the proof and returns unfinished result named `Guard`
     fn verify_proof<
         'com,
@@ -203,6 +221,68 @@ impl<F: FieldExt> Blind<F> {
     pub fn new<R: RngCore>(rng: &mut R) -> Self {
         Blind(F::random(rng))
     }
+
+    /// Returns the zero blinding factor, i.e. a commitment made with it is
+    /// not hiding.
+    pub fn zero() -> Self {
+        Blind(F::zero())
+    }
+
+    /// Returns the underlying blinding scalar.
+    pub fn inner(&self) -> F {
+        self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl<F: Field> zeroize::Zeroize for Blind<F> {
+    fn zeroize(&mut self) {
+        crate::helpers::zeroize_field(std::slice::from_mut(&mut self.0));
+    }
+}
+
+/// A blinding factor held by the prover.
+///
+/// With the `zeroize` feature it is overwritten with zero when it is dropped, so
+/// it is wiped even if the prover returns early with an error.
+#[cfg(feature = "prover")]
+#[derive(Clone, Debug)]
+pub(crate) struct SecretBlind<F: Field>(pub(crate) Blind<F>);
+
+#[cfg(feature = "prover")]
+impl<F: FieldExt> Default for SecretBlind<F> {
+    fn default() -> Self {
+        SecretBlind(Blind::default())
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<F: Field> std::ops::Deref for SecretBlind<F> {
+    type Target = Blind<F>;
+
+    fn deref(&self) -> &Blind<F> {
+        &self.0
+    }
+}
+
+#[cfg(all(feature = "zeroize", feature = "prover"))]
+impl<F: Field> Drop for SecretBlind<F> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+impl<F: SerdePrimeField> Blind<F> {
+    /// Writes the blinding factor to a buffer according to `format`.
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
+        self.0.write(writer, format)
+    }
+
+    /// Reads a blinding factor from a buffer according to `format`.
+    pub fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        F::read(reader, format).map(Blind)
+    }
 }
 
 impl<F: FieldExt> Add for Blind<F> {
@@ -213,6 +293,22 @@ impl<F: FieldExt> Add for Blind<F> {
     }
 }
 
+impl<F: FieldExt> Sub for Blind<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Blind<F>) -> Self {
+        Blind(self.0 - rhs.0)
+    }
+}
+
+impl<F: FieldExt> Neg for Blind<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Blind(-self.0)
+    }
+}
+
 impl<F: FieldExt> Mul for Blind<F> {
     type Output = Self;
 
@@ -227,6 +323,20 @@ impl<F: FieldExt> AddAssign for Blind<F> {
     }
 }
 
+impl<F: FieldExt> Mul<F> for Blind<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self {
+        Blind(self.0 * rhs)
+    }
+}
+
+impl<F: FieldExt> SubAssign for Blind<F> {
+    fn sub_assign(&mut self, rhs: Blind<F>) {
+        self.0 -= rhs.0;
+    }
+}
+
 impl<F: FieldExt> MulAssign for Blind<F> {
     fn mul_assign(&mut self, rhs: Blind<F>) {
         self.0 *= rhs.0;
@@ -244,3 +354,9 @@ impl<F: FieldExt> MulAssign<F> for Blind<F> {
         self.0 *= rhs;
     }
 }
+
+impl<F: FieldExt> Sum for Blind<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Blind::zero(), |acc, blind| acc + blind)
+    }
+}
@@ -2,7 +2,8 @@
 //! domain that is of a suitable size for the application.
 
 use crate::{
-    arithmetic::{best_fft, parallelize, FieldExt, Group},
+    arithmetic::{best_fft, parallelize, powers, CurveAffine, FieldExt, Group},
+    backend::Backend,
     plonk::Assigned,
 };
 
@@ -34,6 +35,17 @@ pub struct EvaluationDomain<G: Group> {
 }
 
 impl<G: Group> EvaluationDomain<G> {
+    /// Returns `true` if the scalar field has a multiplicative subgroup large enough
+    /// for a domain of `2^k` rows and constraints of degree `j`, i.e. if
+    /// [`EvaluationDomain::new`] would not panic.
+    pub(crate) fn supports(j: u32, k: u32) -> bool {
+        if k > G::Scalar::S {
+            return false;
+        }
+        let required = (1u64 << k).saturating_mul(j.saturating_sub(1) as u64);
+        (k..=G::Scalar::S).any(|extended_k| (1u64 << extended_k) >= required)
+    }
+
     /// This constructs a new evaluation domain object based on the provided
     /// values $j, k$.
     pub fn new(j: u32, k: u32) -> Self {
@@ -148,7 +160,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(values.len(), self.n as usize);
 
         Polynomial {
-            values,
+            values: values.into(),
             _marker: PhantomData,
         }
     }
@@ -160,7 +172,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(values.len(), self.n as usize);
 
         Polynomial {
-            values,
+            values: values.into(),
             _marker: PhantomData,
         }
     }
@@ -172,7 +184,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(values.len(), self.n as usize);
 
         Polynomial {
-            values,
+            values: values.into(),
             _marker: PhantomData,
         }
     }
@@ -180,7 +192,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns an empty (zero) polynomial in the coefficient basis
     pub fn empty_coeff(&self) -> Polynomial<G, Coeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.n as usize],
+            values: vec![G::group_zero(); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -188,7 +200,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns an empty (zero) polynomial in the Lagrange coefficient basis
     pub fn empty_lagrange(&self) -> Polynomial<G, LagrangeCoeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.n as usize],
+            values: vec![G::group_zero(); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -200,7 +212,7 @@ impl<G: Group> EvaluationDomain<G> {
         G: Field,
     {
         Polynomial {
-            values: vec![G::group_zero().into(); self.n as usize],
+            values: vec![Assigned::from(G::group_zero()); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -208,7 +220,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns a constant polynomial in the Lagrange coefficient basis
     pub fn constant_lagrange(&self, scalar: G) -> Polynomial<G, LagrangeCoeff> {
         Polynomial {
-            values: vec![scalar; self.n as usize],
+            values: vec![scalar; self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -217,7 +229,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// basis
     pub fn empty_extended(&self) -> Polynomial<G, ExtendedLagrangeCoeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.extended_len()],
+            values: vec![G::group_zero(); self.extended_len()].into(),
             _marker: PhantomData,
         }
     }
@@ -226,7 +238,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// basis
     pub fn constant_extended(&self, scalar: G) -> Polynomial<G, ExtendedLagrangeCoeff> {
         Polynomial {
-            values: vec![scalar; self.extended_len()],
+            values: vec![scalar; self.extended_len()].into(),
             _marker: PhantomData,
         }
     }
@@ -235,11 +247,25 @@ impl<G: Group> EvaluationDomain<G> {
     ///
     /// This function will panic if the provided vector is not the correct
     /// length.
-    pub fn lagrange_to_coeff(&self, mut a: Polynomial<G, LagrangeCoeff>) -> Polynomial<G, Coeff> {
+    pub fn lagrange_to_coeff(&self, a: Polynomial<G, LagrangeCoeff>) -> Polynomial<G, Coeff> {
+        self.lagrange_to_coeff_by(a, best_fft)
+    }
+
+    fn lagrange_to_coeff_by(
+        &self,
+        mut a: Polynomial<G, LagrangeCoeff>,
+        fft: impl Fn(&mut [G], G::Scalar, u32),
+    ) -> Polynomial<G, Coeff> {
         assert_eq!(a.values.len(), 1 << self.k);
 
         // Perform inverse FFT to obtain the polynomial in coefficient form
-        Self::ifft(&mut a.values, self.omega_inv, self.k, self.ifft_divisor);
+        Self::ifft(
+            &mut a.values,
+            self.omega_inv,
+            self.k,
+            self.ifft_divisor,
+            fft,
+        );
 
         Polynomial {
             values: a.values,
@@ -250,17 +276,91 @@ impl<G: Group> EvaluationDomain<G> {
     /// This takes us from an n-length coefficient vector into a coset of the extended
     /// evaluation domain, rotating by `rotation` if desired.
     pub fn coeff_to_extended(
+        &self,
+        a: Polynomial<G, Coeff>,
+    ) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        self.coeff_to_extended_by(a, best_fft)
+    }
+
+    fn coeff_to_extended_by(
         &self,
         mut a: Polynomial<G, Coeff>,
+        fft: impl Fn(&mut [G], G::Scalar, u32),
     ) -> Polynomial<G, ExtendedLagrangeCoeff> {
         assert_eq!(a.values.len(), 1 << self.k);
 
         self.distribute_powers_zeta(&mut a.values, true);
-        a.values.resize(self.extended_len(), G::group_zero());
-        best_fft(&mut a.values, self.extended_omega, self.extended_k);
+        let mut values = a.values.into_vec();
+        values.resize(self.extended_len(), G::group_zero());
+        fft(&mut values, self.extended_omega, self.extended_k);
 
         Polynomial {
-            values: a.values,
+            values: values.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// This takes us from an n-length coefficient vector into one of `parts`
+    /// interleaved parts of the coset of the extended evaluation domain. The part
+    /// at `index` holds every `parts`th value of [`Self::coeff_to_extended`],
+    /// starting at `index`, and is itself a coset of a smaller domain.
+    ///
+    /// `parts` must be a power of two no larger than `extended_len() / n`.
+    pub fn coeff_to_extended_part(
+        &self,
+        a: &Polynomial<G, Coeff>,
+        parts: usize,
+        index: usize,
+    ) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        self.coeff_to_extended_part_by(a, parts, index, best_fft)
+    }
+
+    fn coeff_to_extended_part_by(
+        &self,
+        a: &Polynomial<G, Coeff>,
+        parts: usize,
+        index: usize,
+        fft: impl Fn(&mut [G], G::Scalar, u32),
+    ) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        assert_eq!(a.values.len(), 1 << self.k);
+        assert!(parts.is_power_of_two() && parts <= 1 << (self.extended_k - self.k));
+        let part_k = self.extended_k - parts.trailing_zeros();
+        let shift = self.g_coset * self.extended_omega.pow_vartime(&[index as u64]);
+
+        let mut values = vec![G::group_zero(); 1 << part_k];
+        parallelize(&mut values[..a.len()], |values, start| {
+            let mut power = shift.pow_vartime(&[start as u64]);
+            for (value, a) in values.iter_mut().zip(a[start..].iter()) {
+                *value = *a;
+                value.group_scale(&power);
+                power *= &shift;
+            }
+        });
+        fft(
+            &mut values,
+            self.extended_omega.pow_vartime(&[parts as u64]),
+            part_k,
+        );
+
+        Polynomial {
+            values: values.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns every `parts`th value of a polynomial in the extended Lagrange
+    /// basis, starting at `index`, as [`Self::coeff_to_extended_part`] computes
+    /// from the coefficients.
+    pub fn extended_part(
+        &self,
+        a: &Polynomial<G, ExtendedLagrangeCoeff>,
+        parts: usize,
+        index: usize,
+    ) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        assert_eq!(a.values.len(), self.extended_len());
+
+        Polynomial {
+            values: a.iter().skip(index).step_by(parts).cloned().collect(),
             _marker: PhantomData,
         }
     }
@@ -290,7 +390,15 @@ impl<G: Group> EvaluationDomain<G> {
     /// This function will panic if the provided vector is not the correct
     /// length.
     // TODO/FIXME: caller should be responsible for truncating
-    pub fn extended_to_coeff(&self, mut a: Polynomial<G, ExtendedLagrangeCoeff>) -> Vec<G> {
+    pub fn extended_to_coeff(&self, a: Polynomial<G, ExtendedLagrangeCoeff>) -> Vec<G> {
+        self.extended_to_coeff_by(a, best_fft)
+    }
+
+    fn extended_to_coeff_by(
+        &self,
+        mut a: Polynomial<G, ExtendedLagrangeCoeff>,
+        fft: impl Fn(&mut [G], G::Scalar, u32),
+    ) -> Vec<G> {
         assert_eq!(a.values.len(), self.extended_len());
 
         // Inverse FFT
@@ -299,6 +407,7 @@ impl<G: Group> EvaluationDomain<G> {
             self.extended_omega_inv,
             self.extended_k,
             self.extended_ifft_divisor,
+            fft,
         );
 
         // Distribute powers to move from coset; opposite from the
@@ -311,7 +420,7 @@ impl<G: Group> EvaluationDomain<G> {
         a.values
             .truncate((&self.n * self.quotient_poly_degree) as usize);
 
-        a.values
+        a.values.into_vec()
     }
 
     /// This divides the polynomial (in the extended domain) by the vanishing
@@ -362,8 +471,14 @@ impl<G: Group> EvaluationDomain<G> {
         });
     }
 
-    fn ifft(a: &mut [G], omega_inv: G::Scalar, log_n: u32, divisor: G::Scalar) {
-        best_fft(a, omega_inv, log_n);
+    fn ifft(
+        a: &mut [G],
+        omega_inv: G::Scalar,
+        log_n: u32,
+        divisor: G::Scalar,
+        fft: impl Fn(&mut [G], G::Scalar, u32),
+    ) {
+        fft(a, omega_inv, log_n);
         parallelize(a, |a, _| {
             for a in a {
                 // Finish iFFT
@@ -488,8 +603,85 @@ impl<G: Group> EvaluationDomain<G> {
     }
 }
 
+impl<F: FieldExt> EvaluationDomain<F> {
+    /// Evaluates the polynomial with the values `poly` on the domain at an arbitrary
+    /// point `x`, without converting it to coefficient form.
+    ///
+    /// # Implementation
+    ///
+    /// This uses the barycentric formula
+    ///     $$p(x) = \frac{x^n - 1}{n} \sum_{i=0}^{n - 1} \frac{\omega^i p(\omega^i)}{x - \omega^i},$$
+    /// which follows from the barycentric weights described in [`Self::l_i_range`],
+    /// and takes a single batch inversion and $O(n)$ multiplications. If $x$ is in
+    /// the domain, the corresponding value of `poly` is returned.
+    pub fn evaluate_lagrange(&self, poly: &Polynomial<F, LagrangeCoeff>, x: F) -> F {
+        assert_eq!(poly.len() as u64, self.n);
+
+        let mut denominators: Vec<F> = powers(self.omega)
+            .take(poly.len())
+            .map(|omega_i| x - omega_i)
+            .collect();
+        if let Some(i) = denominators
+            .iter()
+            .position(|denominator| denominator.is_zero_vartime())
+        {
+            return poly[i];
+        }
+        denominators.iter_mut().batch_invert();
+
+        let sum = poly
+            .iter()
+            .zip(powers(self.omega))
+            .zip(denominators)
+            .fold(F::zero(), |acc, ((value, omega_i), denominator)| {
+                acc + *value * omega_i * denominator
+            });
+        let xn = x.pow_vartime(&[self.n]);
+        sum * (xn - F::one()) * self.barycentric_weight
+    }
+
+    /// Like [`Self::lagrange_to_coeff`], but performs the FFT with `backend`.
+    pub fn lagrange_to_coeff_with<C: CurveAffine<ScalarExt = F>>(
+        &self,
+        backend: &dyn Backend<C>,
+        a: Polynomial<F, LagrangeCoeff>,
+    ) -> Polynomial<F, Coeff> {
+        self.lagrange_to_coeff_by(a, |a, omega, log_n| backend.fft(a, omega, log_n))
+    }
+
+    /// Like [`Self::coeff_to_extended`], but performs the FFT with `backend`.
+    pub fn coeff_to_extended_with<C: CurveAffine<ScalarExt = F>>(
+        &self,
+        backend: &dyn Backend<C>,
+        a: Polynomial<F, Coeff>,
+    ) -> Polynomial<F, ExtendedLagrangeCoeff> {
+        self.coeff_to_extended_by(a, |a, omega, log_n| backend.fft(a, omega, log_n))
+    }
+
+    /// Like [`Self::coeff_to_extended_part`], but performs the FFT with `backend`.
+    pub fn coeff_to_extended_part_with<C: CurveAffine<ScalarExt = F>>(
+        &self,
+        backend: &dyn Backend<C>,
+        a: &Polynomial<F, Coeff>,
+        parts: usize,
+        index: usize,
+    ) -> Polynomial<F, ExtendedLagrangeCoeff> {
+        self.coeff_to_extended_part_by(a, parts, index, |a, omega, log_n| {
+            backend.fft(a, omega, log_n)
+        })
+    }
+
+    /// Like [`Self::extended_to_coeff`], but performs the FFT with `backend`.
+    pub fn extended_to_coeff_with<C: CurveAffine<ScalarExt = F>>(
+        &self,
+        backend: &dyn Backend<C>,
+        a: Polynomial<F, ExtendedLagrangeCoeff>,
+    ) -> Vec<F> {
+        self.extended_to_coeff_by(a, |a, omega, log_n| backend.fft(a, omega, log_n))
+    }
+}
+
 /// Represents the minimal parameters that determine an `EvaluationDomain`.
-#[allow(dead_code)]
 #[derive(Debug)]
 pub struct PinnedEvaluationDomain<'a, G: Group> {
     k: &'a u32,
@@ -497,6 +689,23 @@ pub struct PinnedEvaluationDomain<'a, G: Group> {
     omega: &'a G::Scalar,
 }
 
+impl<'a, G: Group> PinnedEvaluationDomain<'a, G> {
+    /// Returns the base-2 logarithm of the size of the domain.
+    pub fn k(&self) -> u32 {
+        *self.k
+    }
+
+    /// Returns the base-2 logarithm of the size of the extended domain.
+    pub fn extended_k(&self) -> u32 {
+        *self.extended_k
+    }
+
+    /// Returns the generator of the domain.
+    pub fn omega(&self) -> &'a G::Scalar {
+        self.omega
+    }
+}
+
 #[test]
 fn test_rotate() {
     use rand_core::OsRng;
@@ -567,3 +776,29 @@ fn test_l_i() {
         assert_eq!(eval_polynomial(&l[(8 - i) % 8][..], x), evaluations[7 - i]);
     }
 }
+
+#[test]
+fn test_evaluate_lagrange() {
+    use rand_core::OsRng;
+
+    use crate::arithmetic::eval_polynomial;
+    use halo2curves::pasta::pallas::Scalar;
+
+    let domain = EvaluationDomain::<Scalar>::new(1, 3);
+    let mut poly = domain.empty_lagrange();
+    for value in poly.iter_mut() {
+        *value = Scalar::random(OsRng);
+    }
+    let coeffs = domain.lagrange_to_coeff(poly.clone());
+
+    let x = Scalar::random(OsRng);
+    assert_eq!(
+        domain.evaluate_lagrange(&poly, x),
+        eval_polynomial(&coeffs[..], x)
+    );
+    // Points in the domain give the corresponding value.
+    assert_eq!(
+        domain.evaluate_lagrange(&poly, domain.rotate_omega(Scalar::one(), Rotation(3))),
+        poly[3]
+    );
+}
@@ -6,6 +6,7 @@
 use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
+use crate::backend::{Backend, Cpu};
 use crate::helpers::CurveRead;
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::ipa::msm::MSMIPA;
@@ -15,10 +16,13 @@ use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group as _};
 use std::marker::PhantomData;
 use std::ops::{Add, AddAssign, Mul, MulAssign};
+use std::sync::Arc;
 
+#[cfg(feature = "prover")]
 mod prover;
 mod verifier;
 
+#[cfg(feature = "prover")]
 pub use prover::create_proof;
 pub use verifier::verify_proof;
 
@@ -33,6 +37,7 @@ pub struct ParamsIPA<C: CurveAffine> {
     pub(crate) g_lagrange: Vec<C>,
     pub(crate) w: C,
     pub(crate) u: C,
+    pub(crate) backend: Arc<dyn Backend<C>>,
 }
 
 /// Concrete IPA commitment scheme
@@ -57,6 +62,15 @@ impl<C: CurveAffine> CommitmentScheme for IPACommitmentScheme<C> {
     }
 }
 
+impl<C: CurveAffine> ParamsIPA<C> {
+    /// Sets the backend that performs the expensive arithmetic of the prover with
+    /// these parameters, which is the CPU by default.
+    pub fn with_backend(mut self, backend: impl Backend<C> + 'static) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+}
+
 /// Verifier parameters
 pub type ParamsVerifierIPA<C> = ParamsIPA<C>;
 
@@ -103,7 +117,11 @@ impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
         tmp_bases.extend(self.g_lagrange.iter());
         tmp_bases.push(self.w);
 
-        best_multiexp::<C>(&tmp_scalars, &tmp_bases)
+        self.backend.msm(&tmp_scalars, &tmp_bases)
+    }
+
+    fn backend(&self) -> &dyn Backend<C> {
+        self.backend.as_ref()
     }
 
     /// Writes params to a buffer.
@@ -142,6 +160,7 @@ impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
             g_lagrange,
             w,
             u,
+            backend: Arc::new(Cpu),
         })
     }
 }
@@ -207,6 +226,7 @@ impl<'params, C: CurveAffine> ParamsProver<'params, C> for ParamsIPA<C> {
             g_lagrange,
             w,
             u,
+            backend: Arc::new(Cpu),
         }
     }
 
@@ -223,7 +243,7 @@ impl<'params, C: CurveAffine> ParamsProver<'params, C> for ParamsIPA<C> {
         tmp_bases.extend(self.g.iter());
         tmp_bases.push(self.w);
 
-        best_multiexp::<C>(&tmp_scalars, &tmp_bases)
+        self.backend.msm(&tmp_scalars, &tmp_bases)
     }
 
     fn get_g(&self) -> &[C] {
@@ -231,7 +251,28 @@ impl<'params, C: CurveAffine> ParamsProver<'params, C> for ParamsIPA<C> {
     }
 }
 
-#[cfg(test)]
+/// Serializes the parameters as [`Params::write`], as a hex string in
+/// human-readable formats and as a byte string otherwise.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<C: CurveAffine> serde::Serialize for ParamsIPA<C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = vec![];
+        Params::write(self, &mut bytes).expect("Writing to vector should not fail");
+        crate::helpers::serialize_bytes(&bytes, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, C: CurveAffine> serde::Deserialize<'de> for ParamsIPA<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::helpers::deserialize_bytes(deserializer)?;
+        <Self as Params<C>>::read(&mut &bytes[..]).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
 mod test {
 
     use crate::arithmetic::{
@@ -242,6 +283,7 @@ mod test {
     use crate::poly::commitment::{Blind, CommitmentScheme, Params, MSM};
     use crate::poly::ipa::commitment::{create_proof, verify_proof, ParamsIPA};
     use crate::poly::ipa::msm::MSMIPA;
+    use crate::poly::ipa::strategy::Accumulator;
     use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
 
     use ff::{Field, PrimeField};
@@ -301,6 +343,73 @@ mod test {
         assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
     }
 
+    #[test]
+    fn test_blind_combination() {
+        const K: u32 = 4;
+
+        use rand_core::OsRng;
+
+        use crate::poly::EvaluationDomain;
+        use crate::SerdeFormat;
+        use halo2curves::pasta::{EqAffine, Fp};
+
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let domain = EvaluationDomain::new(1, K);
+
+        let mut a = domain.empty_coeff();
+        let mut b = domain.empty_coeff();
+        for (i, (a, b)) in a.iter_mut().zip(b.iter_mut()).enumerate() {
+            *a = Fp::from(i as u64);
+            *b = Fp::from((i * i) as u64);
+        }
+        let a_blind = Blind::new(&mut OsRng);
+        let b_blind = Blind::new(&mut OsRng);
+        let x = Fp::random(OsRng);
+
+        // Commitments are additively homomorphic in both the polynomial and
+        // the blinding factor.
+        let combined = params.commit(&a, a_blind) + params.commit(&b, b_blind) * x;
+        assert_eq!(
+            combined,
+            params.commit(&(a.clone() + &(b.clone() * x)), a_blind + b_blind * x)
+        );
+        assert_eq!(
+            params.commit(&a, a_blind) - params.commit(&a, Blind::zero()),
+            params.commit(&domain.empty_coeff(), a_blind)
+        );
+        assert_eq!(
+            [a_blind, b_blind].into_iter().sum::<Blind<_>>(),
+            a_blind + b_blind
+        );
+        assert_eq!(a_blind - a_blind, Blind::zero());
+        assert_eq!(-a_blind + a_blind, Blind::zero());
+
+        let mut data = vec![];
+        a_blind.write(&mut data, SerdeFormat::Processed).unwrap();
+        assert_eq!(
+            Blind::<Fp>::read(&mut &data[..], SerdeFormat::Processed).unwrap(),
+            a_blind
+        );
+    }
+
+    #[test]
+    fn test_downsize() {
+        const K: u32 = 4;
+
+        use halo2curves::pasta::EqAffine;
+
+        let mut params = ParamsIPA::<EqAffine>::new(K + 2);
+        params.downsize(K);
+        let expected = ParamsIPA::<EqAffine>::new(K);
+
+        assert_eq!(params.k, expected.k);
+        assert_eq!(params.n, expected.n);
+        assert_eq!(params.g, expected.g);
+        assert_eq!(params.g_lagrange, expected.g_lagrange);
+        assert_eq!(params.w, expected.w);
+        assert_eq!(params.u, expected.u);
+    }
+
     #[test]
     fn test_opening_proof() {
         const K: u32 = 6;
@@ -377,8 +486,21 @@ mod test {
 
             // Test use_g()
             let g = guard.compute_g();
-            let (msm_g, _accumulator) = guard.clone().use_g(g);
+            let (msm_g, accumulator) = guard.clone().use_g(g);
             assert!(msm_g.check());
+
+            // Test deciding the accumulator, alone and in a batch
+            assert!(Accumulator::decide(&params, &[accumulator.clone()]));
+            let mut bad_accumulator = accumulator.clone();
+            bad_accumulator.g = (bad_accumulator.g.to_curve() + params.g[0]).to_affine();
+            assert!(!Accumulator::decide(
+                &params,
+                &[accumulator.clone(), bad_accumulator]
+            ));
+            assert!(Accumulator::decide(
+                &params,
+                &[accumulator.clone(), accumulator]
+            ));
         }
     }
 }
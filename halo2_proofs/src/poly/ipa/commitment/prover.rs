@@ -80,7 +80,7 @@ pub fn create_proof<
     let mut f = p_prime_blind.0;
 
     // Initialize the vector `p_prime` as the coefficients of the polynomial.
-    let mut p_prime = p_prime_poly.values;
+    let mut p_prime = p_prime_poly.values.into_vec();
     assert_eq!(p_prime.len(), params.n as usize);
 
     // Initialize the vector `b` as the powers of `x_3`. The inner product of
@@ -106,8 +106,8 @@ pub fn create_proof<
         //
         // TODO: If we modify multiexp to take "extra" bases, we could speed
         // this piece up a bit by combining the multiexps.
-        let l_j = best_multiexp(&p_prime[half..], &g_prime[0..half]);
-        let r_j = best_multiexp(&p_prime[0..half], &g_prime[half..]);
+        let l_j = params.backend.msm(&p_prime[half..], &g_prime[0..half]);
+        let r_j = params.backend.msm(&p_prime[0..half], &g_prime[half..]);
         let value_l_j = compute_inner_product(&p_prime[half..], &b[0..half]);
         let value_r_j = compute_inner_product(&p_prime[0..half], &b[half..]);
         let l_j_randomness = C::Scalar::random(&mut rng);
@@ -12,9 +12,11 @@ use crate::{
     transcript::ChallengeScalar,
 };
 
+#[cfg(feature = "prover")]
 mod prover;
 mod verifier;
 
+#[cfg(feature = "prover")]
 pub use prover::ProverIPA;
 pub use verifier::VerifierIPA;
 
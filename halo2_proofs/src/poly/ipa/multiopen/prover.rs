@@ -2,6 +2,7 @@ use super::{
     construct_intermediate_sets, ChallengeX1, ChallengeX2, ChallengeX3, ChallengeX4, Query,
 };
 use crate::arithmetic::{eval_polynomial, kate_division, CurveAffine, FieldExt};
+use crate::plonk::profiling::{time, Phase};
 use crate::poly::commitment::ParamsProver;
 use crate::poly::commitment::{Blind, Params, Prover};
 use crate::poly::ipa::commitment::{self, IPACommitmentScheme, ParamsIPA};
@@ -77,12 +78,12 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
             .fold(None, |q_prime_poly, (points, poly)| {
                 let mut poly = points
                     .iter()
-                    .fold(poly.clone().unwrap().values, |poly, point| {
+                    .fold(poly.clone().unwrap().values.into_vec(), |poly, point| {
                         kate_division(&poly, *point)
                     });
                 poly.resize(self.params.n as usize, C::Scalar::zero());
                 let poly = Polynomial {
-                    values: poly,
+                    values: poly.into(),
                     _marker: PhantomData,
                 };
 
@@ -119,6 +120,8 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
             },
         );
 
-        commitment::create_proof(self.params, rng, transcript, &p_poly, p_poly_blind, *x_3)
+        time(Phase::Ipa, || {
+            commitment::create_proof(self.params, rng, transcript, &p_poly, p_poly_blind, *x_3)
+        })
     }
 }
@@ -36,6 +36,13 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
         Self { params }
     }
 
+    fn proof_size(k: u32, _num_points: usize, num_point_sets: usize) -> (usize, usize) {
+        // q' and one evaluation per set of points, followed by the polynomial
+        // commitment opening: the commitment to s(X), an L and R for each round
+        // of the inner product argument, and the final c and f.
+        (1 + 1 + 2 * k as usize, num_point_sets + 2)
+    }
+
     fn verify_proof<'com, E: EncodedChallenge<C>, T: TranscriptRead<C, E>, I>(
         &self,
         transcript: &mut T,
@@ -39,6 +39,23 @@ pub struct Accumulator<C: CurveAffine> {
     pub u_packed: Vec<C::Scalar>,
 }
 
+impl<C: CurveAffine> Accumulator<C> {
+    /// Checks a batch of accumulators with a single MSM, by checking a random
+    /// linear combination of their claims `G = ⟨s(u), params.g⟩`.
+    ///
+    /// Returns `false` if *some* accumulator was invalid.
+    #[must_use]
+    pub fn decide(params: &ParamsIPA<C>, accumulators: &[Accumulator<C>]) -> bool {
+        let mut msm = MSMIPA::new(params);
+        for accumulator in accumulators {
+            let r = C::Scalar::random(OsRng);
+            msm.add_to_g_scalars(&compute_s(&accumulator.u_packed, r));
+            msm.append_term(-r, accumulator.g.into());
+        }
+        msm.check()
+    }
+}
+
 /// Define accumulator type as `MSMIPA`
 impl<'params, C: CurveAffine> Guard<IPACommitmentScheme<C>> for GuardIPA<'params, C> {
     type MSMAccumulator = MSMIPA<'params, C>;
@@ -116,6 +133,113 @@ impl<'params, C: CurveAffine>
     }
 }
 
+/// A verifier that checks multiple proofs in a batch, except for the claimed
+/// `G'_0` of each proof, which is output as an [`Accumulator`].
+///
+/// Checking an accumulator takes time linear in the size of the parameters, so
+/// recursive verifiers can instead pass the accumulators on to the next proof and
+/// eventually [decide](Accumulator::decide) all of them with a single MSM.
+#[derive(Debug)]
+pub struct DeferredStrategy<'params, C: CurveAffine> {
+    msm: MSMIPA<'params, C>,
+    deferred: Vec<DeferredProof<C>>,
+}
+
+/// The challenges of a processed proof whose `G'_0` has not been computed yet.
+#[derive(Debug)]
+struct DeferredProof<C: CurveAffine> {
+    /// The scalar by which `G'_0` enters the batch MSM.
+    neg_c: C::Scalar,
+    u: Vec<C::Scalar>,
+    u_packed: Vec<C::Scalar>,
+}
+
+impl<'params, C: CurveAffine> DeferredStrategy<'params, C> {
+    /// Returns the accumulators of the processed proofs, or `None` if the rest
+    /// of the batch was invalid.
+    ///
+    /// This computes `G'_0` for every processed proof, which takes time linear in
+    /// the size of the parameters per proof.
+    pub fn into_accumulators(self) -> Option<Vec<Accumulator<C>>> {
+        let mut msm = self.msm;
+        let accumulators: Vec<_> = self
+            .deferred
+            .into_iter()
+            .map(|proof| {
+                let s = compute_s(&proof.u, C::Scalar::one());
+                let g = best_multiexp(&s, &msm.params.g).to_affine();
+                msm.append_term(proof.neg_c, g.into());
+
+                Accumulator {
+                    g,
+                    u_packed: proof.u_packed,
+                }
+            })
+            .collect();
+
+        if msm.check() {
+            Some(accumulators)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'params, C: CurveAffine>
+    VerificationStrategy<'params, IPACommitmentScheme<C>, VerifierIPA<'params, C>>
+    for DeferredStrategy<'params, C>
+{
+    type Output = Self;
+
+    fn new(params: &'params ParamsIPA<C>) -> Self {
+        DeferredStrategy {
+            msm: MSMIPA::new(params),
+            deferred: vec![],
+        }
+    }
+
+    fn process(
+        mut self,
+        f: impl FnOnce(MSMIPA<'params, C>) -> Result<GuardIPA<'params, C>, Error>,
+    ) -> Result<Self::Output, Error> {
+        // The `G'_0` terms of the earlier proofs are not in the MSM yet, so they
+        // must be scaled along with it.
+        let r = C::Scalar::random(OsRng);
+        self.msm.scale(r);
+        for proof in self.deferred.iter_mut() {
+            proof.neg_c *= r;
+        }
+
+        let guard = f(self.msm)?;
+        self.deferred.push(DeferredProof {
+            neg_c: guard.neg_c,
+            u: guard.u,
+            u_packed: guard.u_packed,
+        });
+
+        Ok(Self {
+            msm: guard.msm,
+            deferred: self.deferred,
+        })
+    }
+
+    /// Finalizes the batch and checks its validity, including the accumulators.
+    ///
+    /// The `G'_0` of every proof is checked within the same MSM as the rest of
+    /// the batch, without being computed.
+    ///
+    /// Returns `false` if *some* proof was invalid. If the caller needs to identify
+    /// specific failing proofs, it must re-process the proofs separately.
+    #[must_use]
+    fn finalize(self) -> bool {
+        let mut msm = self.msm;
+        for proof in self.deferred {
+            msm.add_to_g_scalars(&compute_s(&proof.u, proof.neg_c));
+        }
+        msm.check()
+    }
+}
+
 /// A verifier that checks single proof
 #[derive(Debug)]
 pub struct SingleStrategy<'params, C: CurveAffine> {
@@ -1,18 +1,23 @@
 use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
+use crate::backend::{Backend, Cpu};
 use crate::helpers::SerdeCurveAffine;
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
+use crate::storage::Storage;
+#[cfg(feature = "mmap")]
+use crate::storage::{as_bytes, write_mapped, MappedFile, KIND_PARAMS_KZG};
 use crate::SerdeFormat;
 
 use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group as _};
-use halo2curves::pairing::Engine;
+use halo2curves::{pairing::Engine, serde::SerdeObject};
 use rand_core::{OsRng, RngCore};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::{Add, AddAssign, Mul, MulAssign};
+use std::sync::Arc;
 
 use std::io;
 
@@ -23,10 +28,11 @@ use super::msm::MSMKZG;
 pub struct ParamsKZG<E: Engine> {
     pub(crate) k: u32,
     pub(crate) n: u64,
-    pub(crate) g: Vec<E::G1Affine>,
-    pub(crate) g_lagrange: Vec<E::G1Affine>,
+    pub(crate) g: Storage<E::G1Affine>,
+    pub(crate) g_lagrange: Storage<E::G1Affine>,
     pub(crate) g2: E::G2Affine,
     pub(crate) s_g2: E::G2Affine,
+    pub(crate) backend: Arc<dyn Backend<E::G1Affine>>,
 }
 
 /// Umbrella commitment scheme construction for all KZG variants
@@ -121,10 +127,47 @@ impl<E: Engine + Debug> ParamsKZG<E> {
         Self {
             k,
             n,
-            g,
-            g_lagrange,
+            g: g.into(),
+            g_lagrange: g_lagrange.into(),
+            g2,
+            s_g2,
+            backend: Arc::new(Cpu),
+        }
+    }
+
+    /// Constructs parameters from the output of an existing trusted setup, e.g. a
+    /// powers-of-tau ceremony, where `g` holds `[s^i] G1` for `i` in `0..2^k`.
+    ///
+    /// If `g_lagrange` is `None` it is derived from `g`.
+    ///
+    /// Panics if `g` (or `g_lagrange`, when given) does not have `2^k` elements.
+    pub fn from_parts(
+        k: u32,
+        g: Vec<E::G1Affine>,
+        g_lagrange: Option<Vec<E::G1Affine>>,
+        g2: E::G2Affine,
+        s_g2: E::G2Affine,
+    ) -> Self {
+        assert!(k <= E::Scalar::S);
+        let n: u64 = 1 << k;
+        assert_eq!(g.len() as u64, n);
+
+        let g_lagrange = match g_lagrange {
+            Some(g_lagrange) => {
+                assert_eq!(g_lagrange.len() as u64, n);
+                g_lagrange
+            }
+            None => g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k),
+        };
+
+        Self {
+            k,
+            n,
+            g: g.into(),
+            g_lagrange: g_lagrange.into(),
             g2,
             s_g2,
+            backend: Arc::new(Cpu),
         }
     }
 
@@ -138,31 +181,39 @@ impl<E: Engine + Debug> ParamsKZG<E> {
         self.s_g2
     }
 
+    /// Sets the backend that performs the expensive arithmetic of the prover with
+    /// these parameters, which is the CPU by default.
+    pub fn with_backend(mut self, backend: impl Backend<E::G1Affine> + 'static) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
     /// Writes parameters to buffer
-    pub fn write_custom<W: io::Write>(&self, writer: &mut W, format: SerdeFormat)
+    pub fn write_custom<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()>
     where
         E::G1Affine: SerdeCurveAffine,
         E::G2Affine: SerdeCurveAffine,
     {
-        writer.write_all(&self.k.to_le_bytes()).unwrap();
+        writer.write_all(&self.k.to_le_bytes())?;
         for el in self.g.iter() {
-            el.write(writer, format);
+            el.write(writer, format)?;
         }
         for el in self.g_lagrange.iter() {
-            el.write(writer, format);
+            el.write(writer, format)?;
         }
-        self.g2.write(writer, format);
-        self.s_g2.write(writer, format);
+        self.g2.write(writer, format)?;
+        self.s_g2.write(writer, format)?;
+        Ok(())
     }
 
     /// Reads params from a buffer.
-    pub fn read_custom<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Self
+    pub fn read_custom<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self>
     where
         E::G1Affine: SerdeCurveAffine,
         E::G2Affine: SerdeCurveAffine,
     {
         let mut k = [0u8; 4];
-        reader.read_exact(&mut k[..]).unwrap();
+        reader.read_exact(&mut k[..])?;
         let k = u32::from_le_bytes(k);
         let n = 1 << k;
 
@@ -170,11 +221,11 @@ impl<E: Engine + Debug> ParamsKZG<E> {
             SerdeFormat::Processed => {
                 use group::GroupEncoding;
                 let load_points_from_file_parallelly =
-                    |reader: &mut R| -> Vec<Option<E::G1Affine>> {
+                    |reader: &mut R| -> io::Result<Vec<Option<E::G1Affine>>> {
                         let mut points_compressed =
                             vec![<<E as Engine>::G1Affine as GroupEncoding>::Repr::default(); n];
                         for points_compressed in points_compressed.iter_mut() {
-                            reader.read_exact((*points_compressed).as_mut()).unwrap();
+                            reader.read_exact((*points_compressed).as_mut())?;
                         }
 
                         let mut points = vec![Option::<E::G1Affine>::None; n];
@@ -185,53 +236,130 @@ impl<E: Engine + Debug> ParamsKZG<E> {
                                 ));
                             }
                         });
-                        points
+                        Ok(points)
                     };
-
-                let g = load_points_from_file_parallelly(reader);
-                let g: Vec<<E as Engine>::G1Affine> = g
-                    .iter()
-                    .map(|point| point.unwrap_or_else(|| panic!("invalid point encoding")))
-                    .collect();
-                let g_lagrange = load_points_from_file_parallelly(reader);
-                let g_lagrange: Vec<<E as Engine>::G1Affine> = g_lagrange
-                    .iter()
-                    .map(|point| point.unwrap_or_else(|| panic!("invalid point encoding")))
-                    .collect();
+                let invalid_point =
+                    || io::Error::new(io::ErrorKind::Other, "invalid point encoding");
+
+                let g = load_points_from_file_parallelly(reader)?
+                    .into_iter()
+                    .map(|point| point.ok_or_else(invalid_point))
+                    .collect::<io::Result<Vec<_>>>()?;
+                let g_lagrange = load_points_from_file_parallelly(reader)?
+                    .into_iter()
+                    .map(|point| point.ok_or_else(invalid_point))
+                    .collect::<io::Result<Vec<_>>>()?;
                 (g, g_lagrange)
             }
             SerdeFormat::RawBytes => {
                 let g = (0..n)
                     .map(|_| <E::G1Affine as SerdeCurveAffine>::read(reader, format))
-                    .collect();
+                    .collect::<io::Result<Vec<_>>>()?;
                 let g_lagrange = (0..n)
                     .map(|_| <E::G1Affine as SerdeCurveAffine>::read(reader, format))
-                    .collect();
+                    .collect::<io::Result<Vec<_>>>()?;
                 (g, g_lagrange)
             }
             SerdeFormat::RawBytesUnchecked => {
                 // avoid try branching for performance
                 let g = (0..n)
-                    .map(|_| <E::G1Affine as SerdeCurveAffine>::read(reader, format))
+                    .map(|_| <E::G1Affine as SerdeObject>::read_raw_unchecked(reader))
                     .collect::<Vec<_>>();
                 let g_lagrange = (0..n)
-                    .map(|_| <E::G1Affine as SerdeCurveAffine>::read(reader, format))
+                    .map(|_| <E::G1Affine as SerdeObject>::read_raw_unchecked(reader))
                     .collect::<Vec<_>>();
                 (g, g_lagrange)
             }
         };
 
-        let g2 = E::G2Affine::read(reader, format);
-        let s_g2 = E::G2Affine::read(reader, format);
+        let g2 = E::G2Affine::read(reader, format)?;
+        let s_g2 = E::G2Affine::read(reader, format)?;
 
-        Self {
+        Ok(Self {
             k,
             n: n as u64,
+            g: g.into(),
+            g_lagrange: g_lagrange.into(),
+            g2,
+            s_g2,
+            backend: Arc::new(Cpu),
+        })
+    }
+
+    /// Writes parameters in a layout that [`Self::read_mapped`] maps into memory
+    /// without parsing.
+    ///
+    /// The bases are written in their in-memory representation, so the file can
+    /// only be mapped by builds with the same curve implementation and endianness.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub fn write_mapped<W: io::Write>(&self, writer: &mut W) -> io::Result<()>
+    where
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+    {
+        let mut meta = self.k.to_le_bytes().to_vec();
+        self.g2.write(&mut meta, SerdeFormat::RawBytes)?;
+        self.s_g2.write(&mut meta, SerdeFormat::RawBytes)?;
+
+        let element_size = std::mem::size_of::<E::G1Affine>();
+        write_mapped(
+            writer,
+            KIND_PARAMS_KZG,
+            &[as_bytes(&[E::G1Affine::generator()])],
+            &meta,
+            &[
+                (as_bytes(&self.g), element_size),
+                (as_bytes(&self.g_lagrange), element_size),
+            ],
+        )
+    }
+
+    /// Maps parameters written by [`Self::write_mapped`] into memory.
+    ///
+    /// The bases are mapped copy-on-write, so processes that load the same file
+    /// share its pages. Like [`SerdeFormat::RawBytesUnchecked`], this does not
+    /// check that the bases are valid points, so the file must be trusted.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub fn read_mapped<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self>
+    where
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+    {
+        use std::io::Read;
+
+        let file = MappedFile::open(
+            path,
+            KIND_PARAMS_KZG,
+            &[as_bytes(&[E::G1Affine::generator()])],
+        )?;
+        let reader = &mut file.meta();
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k[..])?;
+        let k = u32::from_le_bytes(k);
+        let g2 = E::G2Affine::read(reader, SerdeFormat::RawBytes)?;
+        let s_g2 = E::G2Affine::read(reader, SerdeFormat::RawBytes)?;
+
+        if k >= usize::BITS || file.num_sections() != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected number of bases",
+            ));
+        }
+        let n: u64 = 1 << k;
+        let g = file.section(0, n as usize)?;
+        let g_lagrange = file.section(1, n as usize)?;
+
+        Ok(Self {
+            k,
+            n,
             g,
             g_lagrange,
             g2,
             s_g2,
-        }
+            backend: Arc::new(Cpu),
+        })
     }
 }
 
@@ -262,7 +390,7 @@ where
         self.n = 1 << k;
 
         self.g.truncate(self.n as usize);
-        self.g_lagrange = g_to_lagrange(self.g.iter().map(|g| g.to_curve()).collect(), k);
+        self.g_lagrange = g_to_lagrange(self.g.iter().map(|g| g.to_curve()).collect(), k).into();
     }
 
     fn empty_msm(&'params self) -> MSMKZG<E> {
@@ -276,17 +404,21 @@ where
     ) -> E::G1 {
         let size = poly.len();
         assert!(self.n() >= size as u64);
-        best_multiexp(poly, &self.g_lagrange[0..size])
+        self.backend.msm(poly, &self.g_lagrange[0..size])
+    }
+
+    fn backend(&self) -> &dyn Backend<E::G1Affine> {
+        self.backend.as_ref()
     }
 
     /// Writes params to a buffer.
     fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
-        Ok(self.write_custom(writer, SerdeFormat::RawBytesUnchecked))
+        self.write_custom(writer, SerdeFormat::RawBytesUnchecked)
     }
 
     /// Reads params from a buffer.
     fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
-        Ok(Self::read_custom(reader, SerdeFormat::RawBytesUnchecked))
+        Self::read_custom(reader, SerdeFormat::RawBytesUnchecked)
     }
 }
 
@@ -315,7 +447,7 @@ where
     fn commit(&self, poly: &Polynomial<E::Scalar, Coeff>, _: Blind<E::Scalar>) -> E::G1 {
         let size = poly.len();
         assert!(self.n() >= size as u64);
-        best_multiexp(poly, &self.g[0..size])
+        self.backend.msm(poly, &self.g[0..size])
     }
 
     fn get_g(&self) -> &[E::G1Affine] {
@@ -323,6 +455,37 @@ where
     }
 }
 
+/// Serializes the parameters as [`ParamsKZG::write_custom`] with
+/// [`SerdeFormat::Processed`], as a hex string in human-readable formats and as a
+/// byte string otherwise.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<E: Engine + Debug> serde::Serialize for ParamsKZG<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = vec![];
+        self.write_custom(&mut bytes, SerdeFormat::Processed)
+            .expect("Writing to vector should not fail");
+        crate::helpers::serialize_bytes(&bytes, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, E: Engine + Debug> serde::Deserialize<'de> for ParamsKZG<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::helpers::deserialize_bytes(deserializer)?;
+        Self::read_custom(&mut &bytes[..], SerdeFormat::Processed).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::arithmetic::{
@@ -332,7 +495,6 @@ mod test {
     use crate::poly::commitment::{Blind, CommitmentScheme, Params, MSM};
     use crate::poly::kzg::commitment::{ParamsKZG, ParamsVerifierKZG};
     use crate::poly::kzg::msm::MSMKZG;
-    use crate::poly::kzg::multiopen::ProverSHPLONK;
     use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
 
     use ff::{Field, PrimeField};
@@ -395,4 +557,68 @@ mod test {
         assert_eq!(params0.g2, params1.g2);
         assert_eq!(params0.s_g2, params1.s_g2);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_parameter_serde_roundtrip() {
+        use halo2curves::bn256::Bn256;
+
+        let params0 = ParamsKZG::<Bn256>::new(3);
+        let json = serde_json::to_string(&params0).unwrap();
+        let params1: ParamsKZG<Bn256> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(params0.k, params1.k);
+        assert_eq!(params0.g, params1.g);
+        assert_eq!(params0.g_lagrange, params1.g_lagrange);
+        assert_eq!(params0.g2, params1.g2);
+        assert_eq!(params0.s_g2, params1.s_g2);
+    }
+
+    #[test]
+    fn test_downsize() {
+        const K: u32 = 6;
+
+        use rand_core::OsRng;
+
+        use crate::poly::EvaluationDomain;
+        use halo2curves::bn256::{Bn256, Fr};
+
+        let mut params = ParamsKZG::<Bn256>::new(K + 2);
+        params.downsize(K);
+        assert_eq!(params.k(), K);
+        assert_eq!(params.n(), 1 << K);
+        assert_eq!(params.g.len(), 1 << K);
+        assert_eq!(params.g_lagrange.len(), 1 << K);
+
+        // Downsized parameters still serialize, and commit consistently in
+        // both bases.
+        let mut data = vec![];
+        <ParamsKZG<_> as Params<_>>::write(&params, &mut data).unwrap();
+        let params: ParamsKZG<Bn256> = Params::read::<_>(&mut &data[..]).unwrap();
+
+        let domain = EvaluationDomain::new(1, K);
+        let mut a = domain.empty_lagrange();
+        for (i, a) in a.iter_mut().enumerate() {
+            *a = Fr::from(i as u64);
+        }
+        let b = domain.lagrange_to_coeff(a.clone());
+        let alpha = Blind(Fr::random(OsRng));
+
+        assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
+    }
+
+    #[test]
+    fn test_from_parts() {
+        const K: u32 = 4;
+
+        use halo2curves::bn256::Bn256;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let imported =
+            ParamsKZG::<Bn256>::from_parts(K, params.g.to_vec(), None, params.g2, params.s_g2);
+
+        assert_eq!(imported.k, params.k);
+        assert_eq!(imported.n, params.n);
+        assert_eq!(imported.g_lagrange, params.g_lagrange);
+    }
 }
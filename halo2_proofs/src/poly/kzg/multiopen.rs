@@ -3,3 +3,116 @@ mod shplonk;
 
 pub use gwc::*;
 pub use shplonk::*;
+
+use std::{fmt::Debug, hash::Hash};
+
+use halo2curves::pairing::MultiMillerLoop;
+use rand_core::RngCore;
+
+use super::{
+    commitment::{KZGCommitmentScheme, ParamsKZG},
+    strategy::AccumulatorStrategy,
+};
+#[cfg(feature = "prover")]
+use crate::plonk::ProvingKey;
+use crate::{
+    helpers::SerdeCurveAffine,
+    plonk::{self, Circuit, Error, VerifyingKey},
+    transcript::{EncodedChallenge, TranscriptRead, TranscriptWrite},
+};
+
+/// Selects the multiopen argument that KZG proofs are created and verified with,
+/// so that the choice can be made at runtime. A proof can only be verified with
+/// the argument it was created with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiopen {
+    /// The argument of [GWC19], implemented by [`ProverGWC`] and [`VerifierGWC`].
+    /// The prover is cheaper, but the verifier performs a scalar multiplication
+    /// per distinct set of rotations.
+    ///
+    /// [GWC19]: https://eprint.iacr.org/2019/953
+    GWC,
+    /// The argument of [BDFG20], implemented by [`ProverSHPLONK`] and
+    /// [`VerifierSHPLONK`]. Proofs open every rotation with a single commitment,
+    /// which reduces the number of verifier scalar multiplications when many
+    /// rotations are opened.
+    ///
+    /// [BDFG20]: https://eprint.iacr.org/2020/081
+    SHPLONK,
+}
+
+impl Multiopen {
+    /// Creates a proof with this multiopen argument. See
+    /// [`plonk::create_proof`].
+    #[cfg(feature = "prover")]
+    pub fn create_proof<'params, 'a, E, Ch, R, T, ConcreteCircuit>(
+        self,
+        params: &'params ParamsKZG<E>,
+        pk: &ProvingKey<E::G1Affine>,
+        circuits: &[ConcreteCircuit],
+        instances: &[&[&'a [E::Scalar]]],
+        rng: R,
+        transcript: &'a mut T,
+    ) -> Result<(), Error>
+    where
+        E: MultiMillerLoop + Debug,
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+        E::Scalar: Hash,
+        Ch: EncodedChallenge<E::G1Affine>,
+        R: RngCore + 'a,
+        T: TranscriptWrite<E::G1Affine, Ch>,
+        ConcreteCircuit: Circuit<E::Scalar>,
+    {
+        match self {
+            Multiopen::GWC => plonk::create_proof::<_, ProverGWC<'_, E>, _, _, _, _>(
+                params, pk, circuits, instances, rng, transcript,
+            ),
+            Multiopen::SHPLONK => plonk::create_proof::<_, ProverSHPLONK<'_, E>, _, _, _, _>(
+                params, pk, circuits, instances, rng, transcript,
+            ),
+        }
+    }
+
+    /// Verifies a proof with this multiopen argument, accumulating it into
+    /// `strategy`. See [`plonk::verify_proof`].
+    pub fn verify_proof<'params, E, Ch, T>(
+        self,
+        params: &'params ParamsKZG<E>,
+        vk: &VerifyingKey<E::G1Affine>,
+        strategy: AccumulatorStrategy<'params, E>,
+        instances: &[&[&[E::Scalar]]],
+        transcript: &mut T,
+    ) -> Result<AccumulatorStrategy<'params, E>, Error>
+    where
+        E: MultiMillerLoop + Debug,
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+        E::Scalar: Hash,
+        Ch: EncodedChallenge<E::G1Affine>,
+        T: TranscriptRead<E::G1Affine, Ch>,
+    {
+        match self {
+            Multiopen::GWC => plonk::verify_proof::<_, VerifierGWC<'params, E>, _, _, _>(
+                params, vk, strategy, instances, transcript,
+            ),
+            Multiopen::SHPLONK => plonk::verify_proof::<_, VerifierSHPLONK<'params, E>, _, _, _>(
+                params, vk, strategy, instances, transcript,
+            ),
+        }
+    }
+    /// Returns the size in bytes of a proof for `num_proofs` circuit instances
+    /// with this multiopen argument. See [`VerifyingKey::proof_size`].
+    pub fn proof_size<E>(self, vk: &VerifyingKey<E::G1Affine>, num_proofs: usize) -> usize
+    where
+        E: MultiMillerLoop + Debug,
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+        E::Scalar: Hash,
+    {
+        match self {
+            Multiopen::GWC => vk.proof_size::<_, VerifierGWC<'_, E>>(num_proofs),
+            Multiopen::SHPLONK => vk.proof_size::<_, VerifierSHPLONK<'_, E>>(num_proofs),
+        }
+    }
+}
@@ -1,6 +1,8 @@
+#[cfg(feature = "prover")]
 mod prover;
 mod verifier;
 
+#[cfg(feature = "prover")]
 pub use prover::ProverGWC;
 pub use verifier::VerifierGWC;
 
@@ -20,7 +20,10 @@ use std::fmt::Debug;
 use std::io::{self, Write};
 use std::marker::PhantomData;
 
-/// Concrete KZG prover with GWC variant
+/// Concrete KZG prover with GWC variant, following [GWC19](https://eprint.iacr.org/2019/953).
+///
+/// Produces one opening commitment per distinct query point, so proofs grow with
+/// the number of rotations; [`ProverSHPLONK`](super::ProverSHPLONK) avoids this.
 #[derive(Debug)]
 pub struct ProverGWC<'params, E: Engine> {
     params: &'params ParamsKZG<E>,
@@ -77,7 +80,7 @@ where
 
             let poly_batch = &poly_batch - eval_batch;
             let witness_poly = Polynomial {
-                values: kate_division(&poly_batch.values, z),
+                values: kate_division(&poly_batch.values[..], z).into(),
                 _marker: PhantomData,
             };
             let w = self
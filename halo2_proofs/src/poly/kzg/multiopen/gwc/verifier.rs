@@ -25,7 +25,7 @@ use halo2curves::pairing::{Engine, MillerLoopResult, MultiMillerLoop};
 use rand_core::OsRng;
 
 #[derive(Debug)]
-/// Concrete KZG verifier with GWC variant
+/// Concrete KZG verifier with GWC variant, following [GWC19](https://eprint.iacr.org/2019/953).
 pub struct VerifierGWC<'params, E: Engine> {
     params: &'params ParamsKZG<E>,
 }
@@ -45,6 +45,11 @@ where
         Self { params }
     }
 
+    fn proof_size(_k: u32, num_points: usize, _num_point_sets: usize) -> (usize, usize) {
+        // One witness commitment per point.
+        (num_points, 0)
+    }
+
     fn verify_proof<
         'com,
         Ch: EncodedChallenge<E::G1Affine>,
@@ -1,17 +1,19 @@
+#[cfg(feature = "prover")]
 mod prover;
 mod verifier;
 
+use crate::multicore::prelude::*;
+#[cfg(feature = "prover")]
 pub use prover::ProverSHPLONK;
-use rayon::prelude::*;
 use rustc_hash::FxHashSet;
 pub use verifier::VerifierSHPLONK;
 
+use crate::multicore::prelude::*;
 use crate::{
     arithmetic::{eval_polynomial, lagrange_interpolate, CurveAffine, FieldExt},
     poly::{query::Query, Coeff, Polynomial},
     transcript::ChallengeScalar,
 };
-use rayon::prelude::*;
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
     hash::Hash,
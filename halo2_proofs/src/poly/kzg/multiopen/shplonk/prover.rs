@@ -13,11 +13,11 @@ use crate::poly::Rotation;
 use crate::poly::{commitment::Params, Coeff, Polynomial};
 use crate::transcript::{EncodedChallenge, TranscriptWrite};
 
+use crate::multicore::prelude::*;
 use ff::Field;
 use group::Curve;
 use halo2curves::pairing::Engine;
 use rand_core::RngCore;
-use rayon::prelude::*;
 use rustc_hash::FxHashSet;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -26,9 +26,9 @@ use std::marker::PhantomData;
 use std::ops::MulAssign;
 
 fn div_by_vanishing<F: FieldExt>(poly: Polynomial<F, Coeff>, roots: &[F]) -> Vec<F> {
-    let poly = roots
-        .iter()
-        .fold(poly.values, |poly, point| kate_division(&poly, *point));
+    let poly = roots.iter().fold(poly.values.into_vec(), |poly, point| {
+        kate_division(&poly, *point)
+    });
 
     poly
 }
@@ -43,7 +43,7 @@ impl<'a, C: CurveAffine> Commitment<C::Scalar, PolynomialPointer<'a, C>> {
         let poly = lagrange_interpolate(points, &self.evals()[..]);
 
         let low_degree_equivalent = Polynomial {
-            values: poly,
+            values: poly.into(),
             _marker: PhantomData,
         };
 
@@ -90,7 +90,10 @@ impl<'a, C: CurveAffine> RotationSet<C::Scalar, PolynomialPointer<'a, C>> {
     }
 }
 
-/// Concrete KZG prover with SHPLONK variant
+/// Concrete KZG prover with SHPLONK variant, following [BDFG20](https://eprint.iacr.org/2020/081).
+///
+/// Opens all queries with two commitments regardless of how many distinct
+/// rotations the circuit uses; pair with [`VerifierSHPLONK`](super::VerifierSHPLONK).
 #[derive(Debug)]
 pub struct ProverSHPLONK<'a, E: Engine> {
     params: &'a ParamsKZG<E>,
@@ -168,7 +171,7 @@ where
                 poly.resize(self.params.n as usize, E::Scalar::zero());
 
                 Polynomial {
-                    values: poly,
+                    values: poly.into(),
                     _marker: PhantomData,
                 }
             };
@@ -276,7 +279,7 @@ where
         }
 
         let h_x = Polynomial {
-            values: h_x,
+            values: h_x.into(),
             _marker: PhantomData,
         };
 
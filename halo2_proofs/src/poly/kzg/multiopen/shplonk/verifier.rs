@@ -29,7 +29,8 @@ use halo2curves::pairing::{Engine, MillerLoopResult, MultiMillerLoop};
 use rand_core::OsRng;
 use std::ops::MulAssign;
 
-/// Concrete KZG multiopen verifier with SHPLONK variant
+/// Concrete KZG multiopen verifier with SHPLONK variant, following
+/// [BDFG20](https://eprint.iacr.org/2020/081).
 #[derive(Debug)]
 pub struct VerifierSHPLONK<'params, E: Engine> {
     params: &'params ParamsKZG<E>,
@@ -51,6 +52,11 @@ where
         Self { params }
     }
 
+    fn proof_size(_k: u32, _num_points: usize, _num_point_sets: usize) -> (usize, usize) {
+        // The commitments to h(X) and to the linearised quotient.
+        (2, 0)
+    }
+
     /// Verify a multi-opening proof
     fn verify_proof<
         'com,
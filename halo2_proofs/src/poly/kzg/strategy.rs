@@ -64,6 +64,11 @@ impl<'params, E: MultiMillerLoop + Debug> AccumulatorStrategy<'params, E> {
     pub fn with(msm_accumulator: DualMSM<'params, E>) -> Self {
         AccumulatorStrategy { msm_accumulator }
     }
+
+    /// Checks the accumulated proofs, returning `true` if all of them are valid.
+    pub fn finalize(self) -> bool {
+        self.msm_accumulator.check()
+    }
 }
 
 /// A verifier that checks a single proof
@@ -0,0 +1,357 @@
+//! Backing storage for large vectors of field and curve elements, which can be
+//! memory-mapped from disk with the `mmap` feature.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+#[cfg(all(feature = "mmap", feature = "prover"))]
+pub(crate) use mapped::KIND_PROVING_KEY;
+#[cfg(feature = "mmap")]
+pub(crate) use mapped::{as_bytes, write_mapped, MappedFile, KIND_PARAMS_KZG};
+
+/// A vector of elements, either owned or memory-mapped from a file.
+pub(crate) enum Storage<T> {
+    /// Elements on the heap.
+    Owned(Vec<T>),
+    /// Elements in a private, copy-on-write mapping of a file. Pages that are
+    /// never written are shared with every other process mapping the same file.
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: memmap2::MmapMut,
+        len: usize,
+        _marker: std::marker::PhantomData<T>,
+    },
+}
+
+impl<T> Storage<T> {
+    /// Returns the elements as a vector, copying them if they are mapped.
+    pub(crate) fn into_vec(mut self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        match &mut self {
+            // Take the elements rather than moving them out, since `Storage` is
+            // wiped on drop with the `zeroize` feature.
+            Storage::Owned(values) => std::mem::take(values),
+            #[cfg(feature = "mmap")]
+            Storage::Mapped { .. } => self.to_vec(),
+        }
+    }
+
+    /// Shortens the storage to `len` elements, like [`Vec::truncate`].
+    pub(crate) fn truncate(&mut self, new_len: usize) {
+        match self {
+            Storage::Owned(values) => values.truncate(new_len),
+            #[cfg(feature = "mmap")]
+            Storage::Mapped { len, .. } => *len = std::cmp::min(*len, new_len),
+        }
+    }
+}
+
+impl<T> Deref for Storage<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Storage::Owned(values) => values,
+            // The mapping is aligned to a page and holds `len` elements, as checked
+            // by `MappedFile::section`.
+            #[cfg(feature = "mmap")]
+            Storage::Mapped { map, len, .. } => unsafe {
+                std::slice::from_raw_parts(map.as_ptr() as *const T, *len)
+            },
+        }
+    }
+}
+
+impl<T> DerefMut for Storage<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Storage::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            Storage::Mapped { map, len, .. } => unsafe {
+                std::slice::from_raw_parts_mut(map.as_mut_ptr() as *mut T, *len)
+            },
+        }
+    }
+}
+
+// Witness polynomials are held in owned storage, so it is wiped before being freed.
+// Mapped storage only holds proving keys.
+#[cfg(feature = "zeroize")]
+impl<T> Drop for Storage<T> {
+    fn drop(&mut self) {
+        if let Storage::Owned(values) = self {
+            crate::helpers::zeroize_vec(values);
+        }
+    }
+}
+
+impl<T: Clone> Clone for Storage<T> {
+    fn clone(&self) -> Self {
+        Storage::Owned(self.to_vec())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Storage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Storage<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
+    }
+}
+
+impl<T> From<Vec<T>> for Storage<T> {
+    fn from(values: Vec<T>) -> Self {
+        Storage::Owned(values)
+    }
+}
+
+impl<T> FromIterator<T> for Storage<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Storage::Owned(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "mmap")]
+mod mapped {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::marker::PhantomData;
+    use std::mem;
+    use std::path::Path;
+
+    use halo2curves::serde::SerdeObject;
+    use memmap2::MmapOptions;
+
+    use super::Storage;
+
+    /// Identifies files written by [`write_mapped`].
+    const MAGIC: [u8; 8] = *b"halo2map";
+
+    /// The version of the layout written by [`write_mapped`].
+    const VERSION: u32 = 1;
+
+    /// The kind of a file holding KZG parameters.
+    pub(crate) const KIND_PARAMS_KZG: u32 = 0;
+
+    /// The kind of a file holding a proving key.
+    #[cfg(feature = "prover")]
+    pub(crate) const KIND_PROVING_KEY: u32 = 1;
+
+    /// The alignment of every section, which is a multiple of the page size on all
+    /// supported platforms, so that each section can be mapped on its own.
+    const ALIGNMENT: u64 = 1 << 16;
+
+    fn invalid_data(message: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    }
+
+    /// Returns the in-memory representation of `values`, which are field or curve
+    /// elements.
+    pub(crate) fn as_bytes<T: SerdeObject + Copy>(values: &[T]) -> &[u8] {
+        // Field and curve elements are plain arrays of limbs without padding.
+        unsafe {
+            std::slice::from_raw_parts(values.as_ptr() as *const u8, mem::size_of_val(values))
+        }
+    }
+
+    fn align(offset: u64) -> u64 {
+        (offset + ALIGNMENT - 1) / ALIGNMENT * ALIGNMENT
+    }
+
+    /// Writes a file that [`MappedFile::open`] can map.
+    ///
+    /// The header holds the file's `kind`, the in-memory representation of one
+    /// known value of each element type (`probes`), a block of serialized `meta`
+    /// data, and the position of each section. Each section holds the in-memory
+    /// representation of its elements, starting at an aligned offset.
+    pub(crate) fn write_mapped<W: Write>(
+        writer: &mut W,
+        kind: u32,
+        probes: &[&[u8]],
+        meta: &[u8],
+        sections: &[(&[u8], usize)],
+    ) -> io::Result<()> {
+        let header_len = 8
+            + 4
+            + 4
+            + 4
+            + probes.iter().map(|probe| 4 + probe.len()).sum::<usize>()
+            + 8
+            + meta.len()
+            + 4
+            + sections.len() * (8 + 8 + 4);
+
+        let mut header = Vec::with_capacity(header_len);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&VERSION.to_be_bytes());
+        header.extend_from_slice(&kind.to_be_bytes());
+        header.extend_from_slice(&(probes.len() as u32).to_be_bytes());
+        for probe in probes {
+            header.extend_from_slice(&(probe.len() as u32).to_be_bytes());
+            header.extend_from_slice(probe);
+        }
+        header.extend_from_slice(&(meta.len() as u64).to_be_bytes());
+        header.extend_from_slice(meta);
+        header.extend_from_slice(&(sections.len() as u32).to_be_bytes());
+        let mut offset = align(header_len as u64);
+        for (bytes, element_size) in sections {
+            header.extend_from_slice(&offset.to_be_bytes());
+            header.extend_from_slice(&((bytes.len() / element_size) as u64).to_be_bytes());
+            header.extend_from_slice(&(*element_size as u32).to_be_bytes());
+            offset = align(offset + bytes.len() as u64);
+        }
+        debug_assert_eq!(header.len(), header_len);
+
+        writer.write_all(&header)?;
+        let mut position = header_len as u64;
+        for (bytes, _) in sections {
+            let padding = align(position) - position;
+            writer.write_all(&vec![0; padding as usize])?;
+            writer.write_all(bytes)?;
+            position += padding + bytes.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// A file written by [`write_mapped`], whose header has been validated.
+    #[derive(Debug)]
+    pub(crate) struct MappedFile {
+        file: File,
+        meta: Vec<u8>,
+        // The offset, number of elements and element size of each section.
+        sections: Vec<(u64, u64, u32)>,
+    }
+
+    fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn read_vec<R: Read>(reader: &mut R, len: u64) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        reader.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes)
+    }
+
+    impl MappedFile {
+        /// Opens a file written by [`write_mapped`], checking that it is of the
+        /// given `kind` and that its elements have the same in-memory
+        /// representation as `probes` in this build.
+        pub(crate) fn open<P: AsRef<Path>>(
+            path: P,
+            kind: u32,
+            probes: &[&[u8]],
+        ) -> io::Result<Self> {
+            let file = File::open(path)?;
+            let file_len = file.metadata()?.len();
+            let mut reader = io::BufReader::new(&file);
+
+            let mut magic = [0; 8];
+            reader.read_exact(&mut magic)?;
+            if magic != MAGIC {
+                return Err(invalid_data("not a memory-mappable file"));
+            }
+            if read_u32(&mut reader)? != VERSION {
+                return Err(invalid_data("unsupported memory-mappable file version"));
+            }
+            if read_u32(&mut reader)? != kind {
+                return Err(invalid_data("unexpected memory-mappable file kind"));
+            }
+            if read_u32(&mut reader)? as usize != probes.len() {
+                return Err(invalid_data("incompatible element representation"));
+            }
+            for probe in probes {
+                let len = read_u32(&mut reader)?;
+                if read_vec(&mut reader, len as u64)? != *probe {
+                    return Err(invalid_data("incompatible element representation"));
+                }
+            }
+            let meta_len = read_u64(&mut reader)?;
+            let meta = read_vec(&mut reader, meta_len)?;
+            let sections = (0..read_u32(&mut reader)?)
+                .map(|_| {
+                    let offset = read_u64(&mut reader)?;
+                    let len = read_u64(&mut reader)?;
+                    let element_size = read_u32(&mut reader)?;
+                    let end = len
+                        .checked_mul(element_size as u64)
+                        .and_then(|size| size.checked_add(offset));
+                    if offset % ALIGNMENT != 0 || end.map_or(true, |end| end > file_len) {
+                        return Err(invalid_data("section out of bounds"));
+                    }
+                    Ok((offset, len, element_size))
+                })
+                .collect::<io::Result<_>>()?;
+            drop(reader);
+
+            Ok(MappedFile {
+                file,
+                meta,
+                sections,
+            })
+        }
+
+        /// Returns the serialized metadata of the file.
+        pub(crate) fn meta(&self) -> &[u8] {
+            &self.meta
+        }
+
+        /// Returns the number of sections in the file.
+        pub(crate) fn num_sections(&self) -> usize {
+            self.sections.len()
+        }
+
+        /// Maps the section at `index` as `len` field or curve elements of type `T`.
+        ///
+        /// The length of the section is checked, but its elements are not
+        /// validated, so the file must be trusted.
+        pub(crate) fn section<T: SerdeObject + Copy>(
+            &self,
+            index: usize,
+            len: usize,
+        ) -> io::Result<Storage<T>> {
+            let (offset, section_len, element_size) = *self
+                .sections
+                .get(index)
+                .ok_or_else(|| invalid_data("missing section"))?;
+            if element_size as usize != mem::size_of::<T>() {
+                return Err(invalid_data("unexpected element size"));
+            }
+            if section_len != len as u64 {
+                return Err(invalid_data("unexpected section length"));
+            }
+            if len == 0 {
+                return Ok(Storage::Owned(vec![]));
+            }
+
+            let map = unsafe {
+                MmapOptions::new()
+                    .offset(offset)
+                    .len((len * element_size as u64) as usize)
+                    .map_copy(&self.file)?
+            };
+            Ok(Storage::Mapped {
+                map,
+                len: len as usize,
+                _marker: PhantomData,
+            })
+        }
+    }
+}
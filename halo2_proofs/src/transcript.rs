@@ -1,8 +1,21 @@
 //! This module contains utilities and traits for dealing with Fiat-Shamir
 //! transcripts.
+//!
+//! The prover writes its messages into a [`TranscriptWrite`], and the verifier
+//! consumes them from a [`TranscriptRead`]. Both absorb the same messages into
+//! the same [`Transcript`] state, and squeeze challenges through an
+//! [`EncodedChallenge`], so a custom proof encoding or hash function only needs
+//! to implement these traits for its reader and writer. This crate provides:
+//!
+//! - [`Blake2bRead`] and [`Blake2bWrite`], with [`Challenge255`] challenges;
+//! - [`Keccak256Read`] and [`Keccak256Write`], with [`Challenge255`] challenges,
+//!   for verifiers running on the EVM;
+//! - [`PoseidonRead`] and [`PoseidonWrite`], with [`ChallengeNative`] challenges,
+//!   for verifiers expressed in a circuit.
 
 use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
 use group::ff::PrimeField;
+use sha3::{Digest, Keccak256};
 use std::convert::TryInto;
 
 use halo2curves::{Coordinates, CurveAffine, FieldExt};
@@ -10,6 +23,9 @@ use halo2curves::{Coordinates, CurveAffine, FieldExt};
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
+mod poseidon;
+pub use self::poseidon::*;
+
 /// Prefix to a prover's message soliciting a challenge
 const BLAKE2B_PREFIX_CHALLENGE: u8 = 0;
 
@@ -19,6 +35,23 @@ const BLAKE2B_PREFIX_POINT: u8 = 1;
 /// Prefix to a prover's message containing a scalar
 const BLAKE2B_PREFIX_SCALAR: u8 = 2;
 
+/// Prefix to a prover's message soliciting a challenge
+const KECCAK256_PREFIX_CHALLENGE: u8 = 0;
+
+/// First prefix to a prover's message soliciting a challenge
+/// Not included in the growing state!
+const KECCAK256_PREFIX_CHALLENGE_LO: u8 = 10;
+
+/// Second prefix to a prover's message soliciting a challenge
+/// Not included in the growing state!
+const KECCAK256_PREFIX_CHALLENGE_HI: u8 = 11;
+
+/// Prefix to a prover's message containing a curve point
+const KECCAK256_PREFIX_POINT: u8 = 1;
+
+/// Prefix to a prover's message containing a scalar
+const KECCAK256_PREFIX_SCALAR: u8 = 2;
+
 /// Generic transcript view (from either the prover or verifier's perspective)
 pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
     /// Squeeze an encoded verifier challenge from the transcript.
@@ -80,7 +113,61 @@ pub trait TranscriptWriterBuffer<W: Write, C: CurveAffine, E: EncodedChallenge<C
     fn finalize(self) -> W;
 }
 
-/// We will replace BLAKE2b with an algebraic hash function in a later version.
+/// The bytes of a proof, as returned by [`TranscriptWriterBuffer::finalize`] for a
+/// transcript written to a `Vec<u8>`.
+///
+/// With the `serde` feature, proofs serialize as a hex string in human-readable
+/// formats, and as a byte string otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proof(Vec<u8>);
+
+impl Proof {
+    /// Wraps the bytes of a proof.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Proof(bytes)
+    }
+
+    /// Returns the bytes of the proof, for reading it with a
+    /// [`TranscriptReadBuffer`].
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the bytes of the proof.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for Proof {
+    fn from(bytes: Vec<u8>) -> Self {
+        Proof(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Proof {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::Serialize for Proof {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::helpers::serialize_bytes(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for Proof {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::helpers::deserialize_bytes(deserializer).map(Proof)
+    }
+}
+
+/// BLAKE2b hash function reader
 #[derive(Debug, Clone)]
 pub struct Blake2bRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
@@ -165,7 +252,7 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 }
 
-/// We will replace BLAKE2b with an algebraic hash function in a later version.
+/// BLAKE2b hash function writer
 #[derive(Debug, Clone)]
 pub struct Blake2bWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
     state: Blake2bState,
@@ -240,6 +327,175 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 }
 
+/// Keccak256 hash function reader for EVM compatibility
+#[derive(Debug, Clone)]
+pub struct Keccak256Read<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: Keccak256,
+    reader: R,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
+    for Keccak256Read<R, C, Challenge255<C>>
+{
+    /// Initialize a transcript given an input buffer.
+    fn init(reader: R) -> Self {
+        let mut state = Keccak256::new();
+        state.update(b"Halo2-Transcript");
+        Keccak256Read {
+            state,
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
+    for Keccak256Read<R, C, Challenge255<C>>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
+    for Keccak256Read<R, C, Challenge255<C>>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.state.update([KECCAK256_PREFIX_CHALLENGE]);
+        Challenge255::<C>::new(&keccak256_challenge_input(&self.state))
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.update([KECCAK256_PREFIX_POINT]);
+        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot write points at infinity to the transcript",
+            )
+        })?;
+        self.state.update(coords.x().to_repr().as_ref());
+        self.state.update(coords.y().to_repr().as_ref());
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.update([KECCAK256_PREFIX_SCALAR]);
+        self.state.update(scalar.to_repr().as_ref());
+
+        Ok(())
+    }
+}
+
+/// Keccak256 hash function writer for EVM compatibility
+#[derive(Debug, Clone)]
+pub struct Keccak256Write<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: Keccak256,
+    writer: W,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
+    for Keccak256Write<W, C, Challenge255<C>>
+{
+    /// Initialize a transcript given an output buffer.
+    fn init(writer: W) -> Self {
+        let mut state = Keccak256::new();
+        state.update(b"Halo2-Transcript");
+        Keccak256Write {
+            state,
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    fn finalize(self) -> W {
+        // TODO: handle outstanding scalars? see issue #138
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
+    for Keccak256Write<W, C, Challenge255<C>>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
+    for Keccak256Write<W, C, Challenge255<C>>
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        self.state.update([KECCAK256_PREFIX_CHALLENGE]);
+        Challenge255::<C>::new(&keccak256_challenge_input(&self.state))
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.update([KECCAK256_PREFIX_POINT]);
+        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot write points at infinity to the transcript",
+            )
+        })?;
+        self.state.update(coords.x().to_repr().as_ref());
+        self.state.update(coords.y().to_repr().as_ref());
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.update([KECCAK256_PREFIX_SCALAR]);
+        self.state.update(scalar.to_repr().as_ref());
+
+        Ok(())
+    }
+}
+
+/// Keccak256 only produces 32 bytes, so the 64 bytes of input to a challenge are
+/// two hashes of the current state, under different prefixes.
+fn keccak256_challenge_input(state: &Keccak256) -> [u8; 64] {
+    let mut state_lo = state.clone();
+    let mut state_hi = state.clone();
+    state_lo.update([KECCAK256_PREFIX_CHALLENGE_LO]);
+    state_hi.update([KECCAK256_PREFIX_CHALLENGE_HI]);
+
+    let mut result = [0u8; 64];
+    result[..32].copy_from_slice(state_lo.finalize().as_slice());
+    result[32..].copy_from_slice(state_hi.finalize().as_slice());
+    result
+}
+
 /// The scalar representation of a verifier challenge.
 ///
 /// The `Type` type can be used to scope the challenge to a specific context, or
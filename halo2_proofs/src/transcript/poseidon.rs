@@ -0,0 +1,217 @@
+//! A transcript over an algebraic sponge, for verifiers that will themselves be
+//! expressed in a circuit.
+
+use ::poseidon::Poseidon;
+use group::ff::PrimeField;
+use halo2curves::{Coordinates, CurveAffine, FieldExt};
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use super::{
+    EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+    TranscriptWriterBuffer,
+};
+
+/// The width of the Poseidon permutation.
+const T: usize = 5;
+
+/// The rate of the Poseidon sponge.
+const RATE: usize = 4;
+
+/// The number of full rounds of the Poseidon permutation.
+const R_F: usize = 8;
+
+/// The number of partial rounds of the Poseidon permutation.
+const R_P: usize = 60;
+
+/// Prefix to a prover's message soliciting a challenge
+const POSEIDON_PREFIX_CHALLENGE: u64 = 0;
+
+/// Prefix to a prover's message containing a curve point
+const POSEIDON_PREFIX_POINT: u64 = 1;
+
+/// Prefix to a prover's message containing a scalar
+const POSEIDON_PREFIX_SCALAR: u64 = 2;
+
+/// A challenge that is a scalar field element, as squeezed from an algebraic
+/// hash over the scalar field.
+#[derive(Copy, Clone, Debug)]
+pub struct ChallengeNative<C: CurveAffine>(C::Scalar);
+
+impl<C: CurveAffine> std::ops::Deref for ChallengeNative<C> {
+    type Target = C::Scalar;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for ChallengeNative<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        ChallengeNative(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.0
+    }
+}
+
+/// Poseidon transcript reader. Points are absorbed as 128-bit limbs of their
+/// coordinates, so that the base field need not fit in the scalar field.
+#[derive(Debug, Clone)]
+pub struct PoseidonRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: Poseidon<C::Scalar, T, RATE>,
+    reader: R,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, ChallengeNative<C>>
+    for PoseidonRead<R, C, ChallengeNative<C>>
+{
+    /// Initialize a transcript given an input buffer.
+    fn init(reader: R) -> Self {
+        PoseidonRead {
+            state: Poseidon::new(R_F, R_P),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine> TranscriptRead<C, ChallengeNative<C>>
+    for PoseidonRead<R, C, ChallengeNative<C>>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine> Transcript<C, ChallengeNative<C>>
+    for PoseidonRead<R, C, ChallengeNative<C>>
+{
+    fn squeeze_challenge(&mut self) -> ChallengeNative<C> {
+        self.state
+            .update(&[C::Scalar::from(POSEIDON_PREFIX_CHALLENGE)]);
+        ChallengeNative::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.update(&point_limbs(point)?);
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state
+            .update(&[C::Scalar::from(POSEIDON_PREFIX_SCALAR), scalar]);
+
+        Ok(())
+    }
+}
+
+/// Poseidon transcript writer.
+#[derive(Debug, Clone)]
+pub struct PoseidonWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: Poseidon<C::Scalar, T, RATE>,
+    writer: W,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, ChallengeNative<C>>
+    for PoseidonWrite<W, C, ChallengeNative<C>>
+{
+    /// Initialize a transcript given an output buffer.
+    fn init(writer: W) -> Self {
+        PoseidonWrite {
+            state: Poseidon::new(R_F, R_P),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine> TranscriptWrite<C, ChallengeNative<C>>
+    for PoseidonWrite<W, C, ChallengeNative<C>>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine> Transcript<C, ChallengeNative<C>>
+    for PoseidonWrite<W, C, ChallengeNative<C>>
+{
+    fn squeeze_challenge(&mut self) -> ChallengeNative<C> {
+        self.state
+            .update(&[C::Scalar::from(POSEIDON_PREFIX_CHALLENGE)]);
+        ChallengeNative::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.update(&point_limbs(point)?);
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state
+            .update(&[C::Scalar::from(POSEIDON_PREFIX_SCALAR), scalar]);
+
+        Ok(())
+    }
+}
+
+/// Encodes a point as the point prefix followed by the 128-bit little-endian
+/// limbs of its coordinates.
+fn point_limbs<C: CurveAffine>(point: C) -> io::Result<Vec<C::Scalar>> {
+    let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        )
+    })?;
+
+    let mut limbs = vec![C::Scalar::from(POSEIDON_PREFIX_POINT)];
+    for coord in [coords.x(), coords.y()] {
+        limbs.extend(coord.to_repr().as_ref().chunks(16).map(|chunk| {
+            let mut bytes = [0u8; 16];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            C::Scalar::from_u128(u128::from_le_bytes(bytes))
+        }));
+    }
+    Ok(limbs)
+}
@@ -0,0 +1,116 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::{
+        Advice, ChipConfig, Circuit, Column, ConstraintSystem, Error, Instance, Selector,
+        TableColumn,
+    },
+    poly::Rotation,
+};
+use halo2curves::pasta::Fp;
+
+#[derive(ChipConfig)]
+struct AddConfig<F: FieldExt> {
+    #[equality]
+    advice: [Column<Advice>; 3],
+    #[equality]
+    instance: Column<Instance>,
+    s_add: Selector,
+    #[complex]
+    s_range: Selector,
+    table: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Default)]
+struct AddCircuit {
+    a: Value<Fp>,
+    b: Value<Fp>,
+}
+
+impl Circuit<Fp> for AddCircuit {
+    type Config = AddConfig<Fp>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = AddConfig::configure(meta);
+        let [a, b, c] = config.advice;
+
+        meta.create_gate("add", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            let s_add = meta.query_selector(config.s_add);
+            vec![s_add * (a + b - c)]
+        });
+
+        meta.lookup("range", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let s_range = meta.query_selector(config.s_range);
+            vec![(s_range * a, config.table)]
+        });
+
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range",
+            |mut table| {
+                for i in 0..8 {
+                    table.assign_cell(
+                        || "range",
+                        config.table,
+                        i,
+                        || Value::known(Fp::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        let c = layouter.assign_region(
+            || "add",
+            |mut region| {
+                config.s_add.enable(&mut region, 0)?;
+                config.s_range.enable(&mut region, 0)?;
+                region.assign_advice(config.advice[0], 0, self.a)?;
+                region.assign_advice(config.advice[1], 0, self.b)?;
+                let c = region.assign_advice(config.advice[2], 0, self.a + self.b)?;
+                Ok(*c.cell())
+            },
+        )?;
+        layouter.constrain_instance(c, config.instance, 0);
+        Ok(())
+    }
+}
+
+#[test]
+fn chip_config_derive() {
+    let circuit = AddCircuit {
+        a: Value::known(Fp::from(3)),
+        b: Value::known(Fp::from(4)),
+    };
+
+    let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(7)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(8)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let config = AddCircuit::configure(&mut meta);
+    assert_eq!(meta.num_advice_columns(), 3);
+    assert!(format!("{:?}", config.clone()).starts_with("AddConfig { advice: ["));
+}
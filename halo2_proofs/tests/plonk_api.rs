@@ -6,20 +6,83 @@ use halo2_proofs::arithmetic::{Field, FieldExt};
 use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::plonk::{
-    create_proof as create_plonk_proof, keygen_pk, keygen_vk, verify_proof as verify_plonk_proof,
-    Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, ProvingKey, TableColumn,
-    VerifyingKey,
+    create_proof as create_plonk_proof, keygen_pk, keygen_pk_from_description, keygen_vk,
+    keygen_vk_from_description, verify_proof as verify_plonk_proof,
+    verify_proof_with_derived_instances, verify_proof_with_nonce,
+    verify_proofs as verify_plonk_proofs, verify_proofs_multi_vk, Advice, Assigned, Circuit,
+    CircuitDescription, Column, ConstraintSystem, DescribedWitness, DeterministicRng, Error, Fixed,
+    Instance, ProvingKey, QueriedPolynomial, Query, Selector, TableColumn, TranscriptOperationKind,
+    TranscriptPhase, VerifyingKey, WireFormatError,
 };
 use halo2_proofs::poly::commitment::{CommitmentScheme, ParamsProver, Prover, Verifier};
 use halo2_proofs::poly::Rotation;
 use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
-    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
-    TranscriptWriterBuffer,
+    Blake2bRead, Blake2bWrite, Challenge255, ChallengeNative, EncodedChallenge, Keccak256Read,
+    Keccak256Write, PoseidonRead, PoseidonWrite, TranscriptReadBuffer, TranscriptWriterBuffer,
 };
-use rand_core::{OsRng, RngCore};
+use halo2_proofs::SerdeFormat;
+use rand_core::{OsRng, RngCore, SeedableRng};
 use std::marker::PhantomData;
 
+/// Proves and verifies over BN254 with SHPLONK and a Blake2b transcript, the
+/// setup shared by most of the tests below.
+mod shplonk {
+    use super::*;
+    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    /// Creates a proof of `circuits` for the given instances.
+    pub(crate) fn prove<C: Circuit<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuits: &[C],
+        instances: &[&[&[Fr]]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_plonk_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            pk,
+            circuits,
+            instances,
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    /// Verifies a proof created by [`prove`].
+    pub(crate) fn verify(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[&[&[Fr]]],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_plonk_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params,
+            vk,
+            SingleStrategy::new(params),
+            instances,
+            &mut transcript,
+        )
+    }
+
+    /// Proves `circuits`, checks that the proof verifies and returns it.
+    pub(crate) fn prove_and_verify<C: Circuit<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuits: &[C],
+        instances: &[&[&[Fr]]],
+    ) -> Vec<u8> {
+        let proof = prove(params, pk, circuits, instances).unwrap();
+        verify(params, pk.get_vk(), instances, &proof).unwrap();
+        proof
+    }
+}
+
 #[test]
 fn plonk_api() {
     const K: u32 = 5;
@@ -430,6 +493,7 @@ fn plonk_api() {
                 keygen_vk(&much_too_small_params, &empty_circuit),
                 Err(Error::NotEnoughRowsAvailable {
                     current_k,
+                    row: None,
                 }) if current_k == 1
             );
 
@@ -440,6 +504,7 @@ fn plonk_api() {
                 keygen_vk(&slightly_too_small_params, &empty_circuit),
                 Err(Error::NotEnoughRowsAvailable {
                     current_k,
+                    ..
                 }) if current_k == K - 1
             );
         }};
@@ -491,6 +556,19 @@ fn plonk_api() {
         )
         .expect("proof generation should not fail");
 
+        // Each circuit needs its own instances.
+        assert_matches!(
+            create_plonk_proof::<Scheme, P, _, _, _, _>(
+                params,
+                pk,
+                &[circuit.clone(), circuit.clone()],
+                &[&[&[instance]]],
+                OsRng,
+                &mut T::init(vec![]),
+            ),
+            Err(Error::InvalidInstances)
+        );
+
         // Check this circuit is satisfied.
         let prover = match MockProver::run(K, &circuit, vec![vec![instance]]) {
             Ok(prover) => prover,
@@ -530,6 +608,21 @@ fn plonk_api() {
         .unwrap();
 
         assert!(strategy.finalize());
+
+        // Verify the same proof twice as a single batch.
+        let instances: &[&[&[Scheme::Scalar]]] = &[&[&pubinputs[..]], &[&pubinputs[..]]];
+        let mut transcripts = [T::init(proof), T::init(proof)];
+        let batch_ok = verify_plonk_proofs::<_, V, _, _, _, _>(
+            params_verifier,
+            vk,
+            Strategy::new(params_verifier),
+            transcripts
+                .iter_mut()
+                .map(|transcript| (instances, transcript)),
+        )
+        .unwrap();
+
+        assert!(batch_ok);
     }
 
     fn test_plonk_api_gwc() {
@@ -546,9 +639,92 @@ fn plonk_api() {
 
         let pk = keygen::<KZGCommitmentScheme<_>>(&params);
 
+        // Keys survive a round trip through their byte representation.
+        let pk_bytes = pk.to_bytes(SerdeFormat::RawBytes);
+        let pk = ProvingKey::from_bytes::<MyCircuit<_>>(&pk_bytes, SerdeFormat::RawBytes)
+            .expect("proving key should deserialize");
+        let vk_bytes = pk.get_vk().to_bytes(SerdeFormat::Processed);
+        let vk = VerifyingKey::<<Scheme as CommitmentScheme>::Curve>::from_bytes::<MyCircuit<_>>(
+            &vk_bytes,
+            SerdeFormat::Processed,
+        )
+        .expect("verifying key should deserialize");
+        assert_eq!(
+            format!("{:?}", vk.pinned()),
+            format!("{:?}", pk.get_vk().pinned())
+        );
+        // Truncated keys are reported as errors rather than panics.
+        assert!(
+            VerifyingKey::<<Scheme as CommitmentScheme>::Curve>::from_bytes::<MyCircuit<_>>(
+                &vk_bytes[..vk_bytes.len() - 1],
+                SerdeFormat::Processed
+            )
+            .is_err()
+        );
+
         let proof = create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
             rng, &params, &pk,
         );
+        assert_eq!(
+            proof.len(),
+            pk.get_vk().proof_size::<Scheme, VerifierGWC<_>>(2)
+        );
+
+        // Keys and proofs also embed in serde types.
+        #[cfg(feature = "serde")]
+        {
+            use halo2_proofs::transcript::Proof;
+
+            let json = serde_json::to_string(pk.get_vk()).unwrap();
+            let vk =
+                VerifyingKey::<<Scheme as CommitmentScheme>::Curve>::deserialize_with_circuit::<
+                    MyCircuit<_>,
+                    _,
+                >(&mut serde_json::Deserializer::from_str(&json))
+                .expect("verifying key should deserialize");
+            assert_eq!(
+                format!("{:?}", vk.pinned()),
+                format!("{:?}", pk.get_vk().pinned())
+            );
+
+            let json = serde_json::to_string(&Proof::from(proof.clone())).unwrap();
+            let deserialized: Proof = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized.as_bytes(), &proof[..]);
+        }
+
+        // Deterministic proving gives byte-identical proofs for the same seed.
+        let deterministic_proof = |seed| {
+            create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                DeterministicRng::from_seed(seed),
+                &params,
+                &pk,
+            )
+        };
+        let golden = deterministic_proof([1; 32]);
+        assert_eq!(golden, deterministic_proof([1; 32]));
+        assert_ne!(golden, deterministic_proof([2; 32]));
+
+        // Computing the quotient a few cosets at a time gives the same proof.
+        let mut chunked_pk = pk.clone();
+        for cosets in [1, 2] {
+            chunked_pk.set_quotient_chunk(Some(cosets));
+            assert_eq!(
+                golden,
+                create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                    DeterministicRng::from_seed([1; 32]),
+                    &params,
+                    &chunked_pk,
+                )
+            );
+        }
+
+        verify_proof::<
+            _,
+            VerifierGWC<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(params.verifier_params(), pk.get_vk(), &golden[..]);
 
         let verifier_params = params.verifier_params();
 
@@ -575,9 +751,47 @@ fn plonk_api() {
 
         let pk = keygen::<KZGCommitmentScheme<_>>(&params);
 
+        // Parameters and keys can be mapped from disk instead of parsed.
+        #[cfg(feature = "mmap")]
+        let (params, pk) = {
+            let dir = std::env::temp_dir();
+            let params_path = dir.join(format!("halo2-params-{}.bin", std::process::id()));
+            let pk_path = dir.join(format!("halo2-pk-{}.bin", std::process::id()));
+            params
+                .write_mapped(&mut std::fs::File::create(&params_path).unwrap())
+                .unwrap();
+            pk.write_mapped(&mut std::fs::File::create(&pk_path).unwrap())
+                .unwrap();
+            let mapped = (
+                ParamsKZG::<Bn256>::read_mapped(&params_path).expect("params should map"),
+                ProvingKey::read_mapped::<_, MyCircuit<_>>(&pk_path)
+                    .expect("proving key should map"),
+            );
+
+            // Files whose sections do not match their `k` are rejected. `k` starts
+            // the metadata, after the magic, version, kind and probe of the header.
+            let bad_path = dir.join(format!("halo2-params-bad-{}.bin", std::process::id()));
+            let mut bytes = std::fs::read(&params_path).unwrap();
+            let probe_len = std::mem::size_of::<halo2curves::bn256::G1Affine>();
+            let k_offset = 8 + 4 + 4 + 4 + 4 + probe_len + 8;
+            bytes[k_offset] += 1;
+            std::fs::write(&bad_path, bytes).unwrap();
+            assert!(ParamsKZG::<Bn256>::read_mapped(&bad_path).is_err());
+
+            // Mapped files can't be removed on every platform.
+            let _ = std::fs::remove_file(params_path);
+            let _ = std::fs::remove_file(pk_path);
+            let _ = std::fs::remove_file(bad_path);
+            mapped
+        };
+
         let proof = create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
             rng, &params, &pk,
         );
+        assert_eq!(
+            proof.len(),
+            pk.get_vk().proof_size::<Scheme, VerifierSHPLONK<_>>(2)
+        );
 
         let verifier_params = params.verifier_params();
 
@@ -588,12 +802,156 @@ fn plonk_api() {
             Blake2bRead<_, _, Challenge255<_>>,
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
+
+        // The proof consists of exactly the reads that the verifier performs.
+        let layout = pk
+            .get_vk()
+            .transcript_layout::<Scheme, VerifierSHPLONK<_>, AccumulatorStrategy<_>>(
+                verifier_params,
+                &[&[1], &[1]],
+            )
+            .unwrap();
+        assert_eq!(
+            pk.get_vk().proof_size::<Scheme, VerifierSHPLONK<_>>(2),
+            proof.len()
+        );
+        assert_eq!(layout.check(&proof), Ok(()));
+        let first = layout.operations()[0];
+        assert_eq!(first.kind, TranscriptOperationKind::CommonScalar);
+        assert_eq!(first.phase, TranscriptPhase::VerifyingKey);
+        // Instance values are absorbed, one scalar per value.
+        assert_eq!(
+            layout
+                .operations()
+                .iter()
+                .filter(|operation| operation.phase == TranscriptPhase::Instance)
+                .count(),
+            2
+        );
+        let last = *layout.operations().last().unwrap();
+        assert_eq!(last.kind, TranscriptOperationKind::ReadPoint);
+        assert_eq!(last.phase, TranscriptPhase::Multiopen);
+        let last_index = layout.operations().len() - 1;
+
+        assert_eq!(
+            layout.check(&proof[..proof.len() - 1]),
+            Err(WireFormatError::Truncated {
+                operation: last_index,
+                offset: proof.len() - last.size,
+            })
+        );
+        let mut padded = proof.clone();
+        padded.push(0);
+        assert_eq!(
+            layout.check(&padded),
+            Err(WireFormatError::TrailingBytes {
+                offset: proof.len(),
+                len: 1,
+            })
+        );
+        let mut corrupted = proof.clone();
+        for byte in corrupted[proof.len() - last.size..].iter_mut() {
+            *byte = 0xff;
+        }
+        assert_eq!(
+            layout.check(&corrupted),
+            Err(WireFormatError::InvalidPoint {
+                operation: last_index,
+                offset: proof.len() - last.size,
+            })
+        );
+
+        // The blinding factors only come from the provided RNG.
+        let seeded_proof = |seed| {
+            create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                DeterministicRng::seed_from_u64(seed),
+                &params,
+                &pk,
+            )
+        };
+        assert_eq!(seeded_proof(1), seeded_proof(1));
+        assert_ne!(seeded_proof(1), seeded_proof(2));
+
+        // The same proof system with a Keccak256 transcript, as verified on the EVM.
+        let proof = create_proof::<_, ProverSHPLONK<_>, _, _, Keccak256Write<_, _, Challenge255<_>>>(
+            rng, &params, &pk,
+        );
+
+        verify_proof::<
+            _,
+            VerifierSHPLONK<_>,
+            _,
+            Keccak256Read<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+    }
+
+    fn test_plonk_api_multiopen() {
+        use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+        use halo2_proofs::poly::kzg::multiopen::Multiopen;
+        use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+        use halo2curves::bn256::Bn256;
+
+        type Scheme = KZGCommitmentScheme<Bn256>;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let pk = keygen::<Scheme>(&params);
+
+        let (a, instance, lookup_table) = common!(Scheme);
+        let circuit: MyCircuit<<Scheme as CommitmentScheme>::Scalar> = MyCircuit {
+            a: Value::known(a),
+            lookup_table,
+        };
+        let instances: &[&[&[_]]] = &[&[&[instance]]];
+
+        let prove = |multiopen: Multiopen| {
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            multiopen
+                .create_proof(
+                    &params,
+                    &pk,
+                    &[circuit.clone()],
+                    instances,
+                    OsRng,
+                    &mut transcript,
+                )
+                .expect("proof generation should not fail");
+            transcript.finalize()
+        };
+        let verify = |multiopen: Multiopen, proof: &[u8]| {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+            multiopen
+                .verify_proof(
+                    &params,
+                    pk.get_vk(),
+                    AccumulatorStrategy::new(&params),
+                    instances,
+                    &mut transcript,
+                )
+                .map_or(false, |strategy| strategy.finalize())
+        };
+
+        let gwc_proof = prove(Multiopen::GWC);
+        let shplonk_proof = prove(Multiopen::SHPLONK);
+        assert!(verify(Multiopen::GWC, &gwc_proof));
+        assert!(verify(Multiopen::SHPLONK, &shplonk_proof));
+
+        // A proof only verifies with the argument it was created with.
+        assert!(!verify(Multiopen::SHPLONK, &gwc_proof));
+        assert!(!verify(Multiopen::GWC, &shplonk_proof));
+
+        // The size of a proof is known without creating it.
+        assert_eq!(Multiopen::GWC.proof_size(pk.get_vk(), 1), gwc_proof.len());
+        assert_eq!(
+            Multiopen::SHPLONK.proof_size(pk.get_vk(), 1),
+            shplonk_proof.len()
+        );
     }
 
     fn test_plonk_api_ipa() {
         use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
         use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
-        use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+        use halo2_proofs::poly::ipa::strategy::{AccumulatorStrategy, DeferredStrategy};
         use halo2curves::pasta::EqAffine;
 
         type Scheme = IPACommitmentScheme<EqAffine>;
@@ -607,6 +965,10 @@ fn plonk_api() {
         let proof = create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
             rng, &params, &pk,
         );
+        assert_eq!(
+            proof.len(),
+            pk.get_vk().proof_size::<Scheme, VerifierIPA<_>>(2)
+        );
 
         let verifier_params = params.verifier_params();
 
@@ -618,6 +980,59 @@ fn plonk_api() {
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
 
+        // Instance columns are committed to, so the layout does not depend on
+        // their lengths.
+        let layout = pk
+            .get_vk()
+            .transcript_layout::<Scheme, VerifierIPA<_>, AccumulatorStrategy<_>>(
+                verifier_params,
+                &[&[1], &[1]],
+            )
+            .unwrap();
+        assert_eq!(layout.check(&proof), Ok(()));
+        assert!(layout
+            .operations()
+            .iter()
+            .filter(|operation| operation.phase == TranscriptPhase::Instance)
+            .all(|operation| operation.kind == TranscriptOperationKind::CommonPoint));
+        assert_eq!(
+            pk.get_vk()
+                .transcript_layout::<Scheme, VerifierIPA<_>, AccumulatorStrategy<_>>(
+                    verifier_params,
+                    &[&[3], &[5]],
+                )
+                .unwrap(),
+            layout
+        );
+        assert_matches!(
+            pk.get_vk()
+                .transcript_layout::<Scheme, VerifierIPA<_>, AccumulatorStrategy<_>>(
+                    verifier_params,
+                    &[&[1, 1]],
+                ),
+            Err(Error::InvalidInstances)
+        );
+
+        // Verify the same proof, deferring the check of its accumulators.
+        verify_proof::<_, VerifierIPA<_>, _, Blake2bRead<_, _, Challenge255<_>>, DeferredStrategy<_>>(
+            verifier_params,
+            pk.get_vk(),
+            &proof[..],
+        );
+
+        // The same proof system with a Poseidon transcript, as verified in-circuit.
+        let proof = create_proof::<_, ProverIPA<_>, _, _, PoseidonWrite<_, _, ChallengeNative<_>>>(
+            rng, &params, &pk,
+        );
+
+        verify_proof::<
+            _,
+            VerifierIPA<_>,
+            _,
+            PoseidonRead<_, _, ChallengeNative<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+
         // Check that the verification key has not changed unexpectedly
         {
             //panic!("{:#?}", pk.get_vk().pinned());
@@ -1019,9 +1434,1761 @@ fn plonk_api() {
 }"#####
             );
         }
+
+        // The pinned verification key exposes what a verifier generator needs.
+        {
+            let vk = pk.get_vk();
+            let pinned = vk.pinned();
+            assert_eq!(pinned.domain().k(), K);
+            assert_eq!(pinned.domain().extended_k(), 7);
+            assert_eq!(pinned.fixed_commitments(), &vk.fixed_commitments()[..]);
+            assert_eq!(
+                pinned.permutation_commitments().len(),
+                pinned.cs().permutation_columns().len()
+            );
+            assert_eq!(pinned.cs().num_advice_columns(), 5);
+            assert_eq!(
+                pinned.cs().gates().count(),
+                vk.cs()
+                    .gates()
+                    .iter()
+                    .map(|gate| gate.polynomials().len())
+                    .sum::<usize>()
+            );
+            assert_eq!(pinned.cs().lookups().len(), 1);
+            assert_eq!(pinned.cs().lookups()[0].input_expressions().len(), 1);
+
+            // So does the order in which the verifier opens commitments.
+            let one = vk.query_schedule(1, false);
+            let two = vk.query_schedule(2, false);
+            let per_proof = two.queries().len() - one.queries().len();
+            assert_eq!(
+                one.queries().len() - per_proof,
+                pinned.cs().fixed_queries().len() + pinned.permutation_commitments().len() + 2
+            );
+            assert_eq!(
+                vk.query_schedule(1, true).queries().len() - one.queries().len(),
+                pinned.cs().instance_queries().len()
+            );
+            let (column, rotation) = pinned.cs().advice_queries()[0];
+            assert_eq!(
+                two.queries()[0],
+                Query {
+                    polynomial: QueriedPolynomial::Advice {
+                        proof: 0,
+                        column: column.index()
+                    },
+                    rotation,
+                }
+            );
+            assert_eq!(
+                two.queries().last().unwrap().polynomial,
+                QueriedPolynomial::VanishingRandom
+            );
+            // Each polynomial belongs to exactly one rotation set.
+            let polynomials: Vec<_> = two
+                .rotation_sets()
+                .into_iter()
+                .flat_map(|set| set.polynomials)
+                .collect();
+            for (i, polynomial) in polynomials.iter().enumerate() {
+                assert!(!polynomials[i + 1..].contains(polynomial));
+                assert!(two.queries().iter().any(|q| q.polynomial == *polynomial));
+            }
+        }
     }
 
     test_plonk_api_ipa();
     test_plonk_api_gwc();
     test_plonk_api_shplonk();
+    test_plonk_api_multiopen();
+}
+
+#[test]
+fn aggregate_different_circuits() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+    use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 5;
+
+    #[derive(Clone)]
+    struct SquaringConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+        s: Selector,
+    }
+
+    /// Proves knowledge of `x` such that squaring it `N` times gives the instance.
+    #[derive(Clone, Default)]
+    struct SquaringCircuit<const N: usize> {
+        x: Value<Fr>,
+    }
+
+    impl<const N: usize> Circuit<Fr> for SquaringCircuit<N> {
+        type Config = SquaringConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> SquaringConfig {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            let s = meta.selector();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+
+            meta.create_gate("square", |meta| {
+                let cur = meta.query_advice(a, Rotation::cur());
+                let next = meta.query_advice(a, Rotation::next());
+                let s = meta.query_selector(s);
+                vec![s * (cur.clone() * cur - next)]
+            });
+
+            SquaringConfig { a, instance, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquaringConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let out = layouter.assign_region(
+                || "squarings",
+                |mut region| {
+                    let mut value = self.x;
+                    let mut cell = region.assign_advice(config.a, 0, value)?;
+                    for row in 0..N {
+                        config.s.enable(&mut region, row)?;
+                        value = value.map(|v| v.square());
+                        cell = region.assign_advice(config.a, row + 1, value)?;
+                    }
+                    Ok(*cell.cell())
+                },
+            )?;
+            layouter.constrain_instance(out, config.instance, 0);
+            Ok(())
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let x = Fr::from(3);
+
+    fn prove<C: Circuit<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        circuit: C,
+        instance: Fr,
+    ) -> (VerifyingKey<halo2curves::bn256::G1Affine>, Vec<u8>) {
+        let vk = keygen_vk(params, &circuit).unwrap();
+        let pk = keygen_pk(params, vk.clone(), &circuit).unwrap();
+        let proof = shplonk::prove(params, &pk, &[circuit], &[&[&[instance]]]).unwrap();
+        (vk, proof)
+    }
+
+    let x2 = x.square();
+    let x8 = x2.square().square();
+    let (vk1, proof1) = prove(&params, SquaringCircuit::<1> { x: Value::known(x) }, x2);
+    let (vk3, proof3) = prove(&params, SquaringCircuit::<3> { x: Value::known(x) }, x8);
+    assert_ne!(format!("{:?}", vk1.pinned()), format!("{:?}", vk3.pinned()));
+
+    let verify = |instances: [Fr; 2]| {
+        let instances1: &[&[&[Fr]]] = &[&[&instances[..1]]];
+        let instances3: &[&[&[Fr]]] = &[&[&instances[1..]]];
+        let mut transcript1 = Blake2bRead::<_, _, Challenge255<_>>::init(&proof1[..]);
+        let mut transcript3 = Blake2bRead::<_, _, Challenge255<_>>::init(&proof3[..]);
+        verify_proofs_multi_vk::<_, VerifierSHPLONK<_>, _, _, _, _>(
+            &params,
+            AccumulatorStrategy::new(&params),
+            [
+                (&vk1, instances1, &mut transcript1),
+                (&vk3, instances3, &mut transcript3),
+            ],
+        )
+        .map_or(false, |ok| ok)
+    };
+    assert!(verify([x2, x8]));
+    assert!(!verify([x2, x2]));
+}
+
+#[test]
+fn multiple_circuits() {
+    use halo2_proofs::plonk::{Challenge, FirstPhase, SecondPhase};
+    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct PhasedConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+        // A second-phase column constrained by `b = a * c` for a challenge `c`.
+        second_phase: Option<(Column<Advice>, Selector, Challenge)>,
+    }
+
+    /// Exposes `a` in the first instance row, and with `CHALLENGE` also witnesses
+    /// `a * c` in the second phase, moving to it during synthesis.
+    #[derive(Clone, Default)]
+    struct PhasedCircuit<const CHALLENGE: bool> {
+        a: Value<Fr>,
+    }
+
+    impl<const CHALLENGE: bool> Circuit<Fr> for PhasedCircuit<CHALLENGE> {
+        type Config = PhasedConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column_in(FirstPhase);
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            let second_phase = CHALLENGE.then(|| {
+                let b = meta.advice_column_in(SecondPhase);
+                let s = meta.selector();
+                let c = meta.challenge_usable_after(FirstPhase);
+                meta.create_gate("b = a * c", |meta| {
+                    let s = meta.query_selector(s);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    let c = meta.query_challenge(c);
+                    vec![s * (b - a * c)]
+                });
+                (b, s, c)
+            });
+            PhasedConfig {
+                a,
+                instance,
+                second_phase,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let a = layouter.assign_region(
+                || "a",
+                |mut region| {
+                    let a = *region.assign_advice(config.a, 0, self.a)?.cell();
+                    if let Some((b, s, c)) = config.second_phase {
+                        s.enable(&mut region, 0)?;
+                        region.next_phase();
+                        let c = region.get_challenge(c);
+                        region.assign_advice(b, 0, self.a.zip(c).map(|(a, c)| a * c))?;
+                    }
+                    Ok(a)
+                },
+            )?;
+            layouter.constrain_instance(a, config.instance, 0);
+            Ok(())
+        }
+    }
+
+    fn round_trip<const CHALLENGE: bool>(params: &ParamsKZG<Bn256>, values: &[u64]) {
+        let circuits = values
+            .iter()
+            .map(|&a| PhasedCircuit::<CHALLENGE> {
+                a: Value::known(Fr::from(a)),
+            })
+            .collect::<Vec<_>>();
+        let instances = values.iter().map(|&a| [Fr::from(a)]).collect::<Vec<_>>();
+        let instances = instances
+            .iter()
+            .map(|instance| [&instance[..]])
+            .collect::<Vec<_>>();
+        let instances = instances
+            .iter()
+            .map(|instance| &instance[..])
+            .collect::<Vec<_>>();
+
+        let vk = keygen_vk(params, &circuits[0]).unwrap();
+        let pk = keygen_pk(params, vk, &circuits[0]).unwrap();
+        let proof = shplonk::prove_and_verify(params, &pk, &circuits, &instances);
+        assert_eq!(
+            pk.get_vk()
+                .proof_size::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(values.len()),
+            proof.len()
+        );
+
+        // The instances are bound to the circuit they were proven for.
+        if values.len() > 1 {
+            let mut swapped = instances.clone();
+            swapped.reverse();
+            assert!(shplonk::verify(params, pk.get_vk(), &swapped, &proof).is_err());
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    round_trip::<false>(&params, &[2]);
+    round_trip::<false>(&params, &[2, 3]);
+    round_trip::<true>(&params, &[2]);
+    round_trip::<true>(&params, &[2, 3]);
+    round_trip::<true>(&params, &[2, 3, 5]);
+}
+
+#[test]
+fn verify_with_nonce() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct NonceConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `x` in the first instance row, and binds a nonce in the second.
+    #[derive(Clone, Default)]
+    struct NonceCircuit {
+        x: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for NonceCircuit {
+        type Config = NonceConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> NonceConfig {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            meta.set_nonce_cell(instance, 1);
+            NonceConfig { a, instance }
+        }
+
+        fn synthesize(
+            &self,
+            config: NonceConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(config.a, 0, self.x).map(|x| *x.cell()),
+            )?;
+            layouter.constrain_instance(x, config.instance, 0);
+            Ok(())
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let circuit = NonceCircuit {
+        x: Value::known(Fr::from(7)),
+    };
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+    let nonce = Fr::from(42);
+    let instance = [Fr::from(7), nonce];
+    let proof = shplonk::prove(&params, &pk, &[circuit], &[&[&instance]]).unwrap();
+
+    let verify = |instance: &[Fr], nonce: Fr| {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof_with_nonce::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[instance]],
+            &[nonce],
+            &mut transcript,
+        )
+    };
+    assert!(verify(&instance, nonce).is_ok());
+    assert_matches!(verify(&instance, Fr::from(43)), Err(Error::InvalidNonce));
+    // A proof replayed with a different nonce in the instance does not verify.
+    assert!(verify(&[Fr::from(7), Fr::from(43)], Fr::from(43)).is_err());
+}
+
+#[test]
+fn domain_separation_label() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    const K: u32 = 4;
+
+    /// Exposes `x` in the first instance row.
+    #[derive(Clone, Default)]
+    struct PublicCircuit {
+        x: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for PublicCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            (a, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (a, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(a, 0, self.x).map(|x| *x.cell()),
+            )?;
+            layouter.constrain_instance(x, instance, 0);
+            Ok(())
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let circuit = PublicCircuit {
+        x: Value::known(Fr::from(7)),
+    };
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &circuit)
+        .unwrap()
+        .with_label(b"deployment A");
+    assert_eq!(pk.get_vk().label(), b"deployment A");
+    assert!(vk.label().is_empty());
+    let instance = [Fr::from(7)];
+    let proof = shplonk::prove_and_verify(&params, &pk, &[circuit], &[&[&instance]]);
+
+    let verify = |vk: &VerifyingKey<_>| shplonk::verify(&params, vk, &[&[&instance]], &proof);
+    assert!(verify(&vk.clone().with_label(b"deployment A")).is_ok());
+    // The same circuit in another deployment, or without a label, rejects the proof.
+    assert!(verify(&vk.clone().with_label(b"deployment B")).is_err());
+    assert!(verify(&vk).is_err());
+
+    // The label is serialized with the key.
+    let read_back = |vk: &VerifyingKey<G1Affine>| {
+        VerifyingKey::<G1Affine>::from_bytes::<PublicCircuit>(
+            &vk.to_bytes(SerdeFormat::RawBytes),
+            SerdeFormat::RawBytes,
+        )
+        .unwrap()
+    };
+    let labelled = read_back(pk.get_vk());
+    assert_eq!(labelled.label(), b"deployment A");
+    assert!(verify(&labelled).is_ok());
+    assert!(verify(&labelled.with_label(b"deployment B")).is_err());
+    let unlabelled = read_back(&vk);
+    assert!(unlabelled.label().is_empty());
+    assert!(verify(&unlabelled).is_err());
+
+    let pk_bytes = pk.to_bytes(SerdeFormat::RawBytes);
+    let read_pk =
+        ProvingKey::<G1Affine>::from_bytes::<PublicCircuit>(&pk_bytes, SerdeFormat::RawBytes)
+            .unwrap();
+    assert_eq!(read_pk.get_vk().label(), b"deployment A");
+
+    // Keys written under another protocol version are rejected.
+    let mut vk_bytes = pk.get_vk().to_bytes(SerdeFormat::RawBytes);
+    vk_bytes[3] ^= 1;
+    assert!(VerifyingKey::<G1Affine>::from_bytes::<PublicCircuit>(
+        &vk_bytes,
+        SerdeFormat::RawBytes
+    )
+    .is_err());
+
+    // Keys with a `k` that the scalar field cannot support are rejected, rather
+    // than panicking. `k` follows the version and the label.
+    let mut vk_bytes = pk.get_vk().to_bytes(SerdeFormat::RawBytes);
+    let k_offset = 8 + b"deployment A".len();
+    vk_bytes[k_offset..k_offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(VerifyingKey::<G1Affine>::from_bytes::<PublicCircuit>(
+        &vk_bytes,
+        SerdeFormat::RawBytes
+    )
+    .is_err());
+
+    // Keys with a fixed commitment for a circuit without fixed columns are
+    // rejected. The fixed commitments follow `k`, and are followed by the two
+    // permutation commitments, so repeating the first of those keeps the rest of
+    // the key readable.
+    let mut vk_bytes = pk.get_vk().to_bytes(SerdeFormat::RawBytes);
+    let fixed_offset = k_offset + 4;
+    assert_eq!(vk_bytes[fixed_offset..fixed_offset + 4], 0u32.to_be_bytes());
+    vk_bytes[fixed_offset..fixed_offset + 4].copy_from_slice(&1u32.to_be_bytes());
+    let commitments = fixed_offset + 4;
+    let commitment_len = (vk_bytes.len() - commitments) / 2;
+    let commitment = vk_bytes[commitments..commitments + commitment_len].to_vec();
+    vk_bytes.splice(commitments..commitments, commitment);
+    let error =
+        VerifyingKey::<G1Affine>::from_bytes::<PublicCircuit>(&vk_bytes, SerdeFormat::RawBytes)
+            .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn committed_instances() {
+    use halo2_proofs::plonk::{commit_instance, verify_proof_with_instance_commitments};
+    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    const K: u32 = 4;
+
+    /// Exposes `x` in the first instance row, committing to the instance column
+    /// if `COMMIT` is set.
+    #[derive(Clone, Default)]
+    struct PublicCircuit<const COMMIT: bool> {
+        x: Value<Fr>,
+    }
+
+    impl<const COMMIT: bool> Circuit<Fr> for PublicCircuit<COMMIT> {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            if COMMIT {
+                meta.commit_instances();
+            }
+            (a, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (a, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(a, 0, self.x).map(|x| *x.cell()),
+            )?;
+            layouter.constrain_instance(x, instance, 0);
+            Ok(())
+        }
+    }
+
+    fn prove<const COMMIT: bool>(
+        params: &ParamsKZG<Bn256>,
+        instance: &[Fr],
+    ) -> (VerifyingKey<G1Affine>, Vec<u8>) {
+        let circuit = PublicCircuit::<COMMIT> {
+            x: Value::known(Fr::from(7)),
+        };
+        let vk = keygen_vk(params, &circuit).unwrap();
+        let pk = keygen_pk(params, vk.clone(), &circuit).unwrap();
+        let proof = shplonk::prove(params, &pk, &[circuit], &[&[instance]]).unwrap();
+        (vk, proof)
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let instance = [Fr::from(7)];
+    let (vk, proof) = prove::<true>(&params, &instance);
+    assert!(vk.cs().committed_instances());
+    assert_eq!(
+        vk.proof_size::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>>(1),
+        proof.len()
+    );
+
+    let verify_committed = |vk: &VerifyingKey<G1Affine>, proof: &[u8], commitment: G1Affine| {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_proof_with_instance_commitments::<_, VerifierSHPLONK<_>, _, _, _>(
+            &params,
+            vk,
+            SingleStrategy::new(&params),
+            &[&[commitment]],
+            &mut transcript,
+        )
+    };
+    let commitment = commit_instance(&params, &vk, &instance).unwrap();
+    assert!(verify_committed(&vk, &proof, commitment).is_ok());
+    let wrong_commitment = commit_instance(&params, &vk, &[Fr::from(8)]).unwrap();
+    assert!(verify_committed(&vk, &proof, wrong_commitment).is_err());
+
+    // The proof also verifies against the instance values.
+    assert!(shplonk::verify(&params, &vk, &[&[&instance]], &proof).is_ok());
+
+    // Without committed instances, the KZG verifier needs the values.
+    let (vk, proof) = prove::<false>(&params, &instance);
+    assert_matches!(
+        verify_committed(&vk, &proof, commitment),
+        Err(Error::InvalidInstances)
+    );
+}
+
+#[test]
+fn keygen_from_description() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+    use halo2curves::group::ff::PrimeField;
+
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct StepConfig {
+        a: Column<Advice>,
+        f: Column<Fixed>,
+        instance: Column<Instance>,
+        s: Selector,
+    }
+
+    /// Proves knowledge of `x` such that two steps of `a -> a^2 + f` give the
+    /// instance.
+    #[derive(Clone, Default)]
+    struct StepCircuit {
+        x: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for StepCircuit {
+        type Config = StepConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> StepConfig {
+            let a = meta.advice_column();
+            let f = meta.fixed_column();
+            let instance = meta.instance_column();
+            let s = meta.selector();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+
+            meta.create_gate("step", |meta| {
+                let cur = meta.query_advice(a, Rotation::cur());
+                let next = meta.query_advice(a, Rotation::next());
+                let f = meta.query_fixed(f, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![("step", s * (cur.clone() * cur + f - next))]
+            });
+
+            StepConfig { a, f, instance, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: StepConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let out = layouter.assign_region(
+                || "steps",
+                |mut region| {
+                    let mut value = self.x;
+                    let mut cell = region.assign_advice(config.a, 0, value)?;
+                    for row in 0..2 {
+                        let f = Fr::from(row as u64 + 5);
+                        config.s.enable(&mut region, row)?;
+                        region.assign_fixed(config.f, row, f);
+                        value = value.map(|v| v.square() + f);
+                        cell = region.assign_advice(config.a, row + 1, value)?;
+                    }
+                    Ok(*cell.cell())
+                },
+            )?;
+            layouter.constrain_instance(out, config.instance, 0);
+            Ok(())
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let circuit = StepCircuit::default();
+    let description = CircuitDescription::from_circuit(K, &circuit).unwrap();
+    let description = CircuitDescription::from_bytes(&description.to_bytes()).unwrap();
+
+    // The description generates the same keys as the circuit itself.
+    let vk = keygen_vk_from_description(&params, &description).unwrap();
+    assert_eq!(
+        format!("{:?}", vk.pinned()),
+        format!("{:?}", keygen_vk(&params, &circuit).unwrap().pinned())
+    );
+    let pk = keygen_pk_from_description(&params, vk.clone(), &description).unwrap();
+
+    let x = Fr::from(3);
+    let y = x.square() + Fr::from(5);
+    let z = y.square() + Fr::from(6);
+    let witness = DescribedWitness::new(&description, vec![vec![x, y, z]]).unwrap();
+    let proof = shplonk::prove(&params, &pk, &[witness], &[&[&[z]]]).unwrap();
+    assert!(shplonk::verify(&params, &vk, &[&[&[z]]], &proof).is_ok());
+
+    assert_matches!(
+        DescribedWitness::new(&description, vec![]),
+        Err(Error::Synthesis)
+    );
+    assert_matches!(
+        keygen_vk_from_description(&ParamsKZG::<Bn256>::new(K + 1), &description),
+        Err(Error::Synthesis)
+    );
+
+    // Malformed descriptions are rejected before anything is allocated for them.
+    let bytes = description.to_bytes();
+    let mut too_many_rows = bytes.clone();
+    too_many_rows[..4].copy_from_slice(&(Fr::S + 1).to_be_bytes());
+    assert!(CircuitDescription::<Fr>::from_bytes(&too_many_rows).is_err());
+    let mut too_many_columns = bytes;
+    too_many_columns[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(CircuitDescription::<Fr>::from_bytes(&too_many_columns).is_err());
+}
+
+#[test]
+fn create_proof_rejects_mismatched_instance() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+
+    /// Exposes `x` in the third instance row.
+    #[derive(Clone, Default)]
+    struct ExposeCircuit {
+        x: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for ExposeCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            (a, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (a, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(a, 1, self.x).map(|x| *x.cell()),
+            )?;
+            layouter.constrain_instance(x, instance, 2);
+            Ok(())
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let circuit = ExposeCircuit {
+        x: Value::known(Fr::from(7)),
+    };
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+    let prove = |instance: &[Fr]| shplonk::prove(&params, &pk, &[circuit.clone()], &[&[instance]]);
+    assert!(prove(&[Fr::zero(), Fr::zero(), Fr::from(7)]).is_ok());
+    assert_matches!(
+        prove(&[Fr::zero(), Fr::zero(), Fr::from(8)]),
+        Err(Error::InstanceMismatch {
+            cell: (_, 1),
+            instance: (_, 2),
+        })
+    );
+}
+
+#[test]
+fn derived_instances() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+
+    /// An instance-only circuit, checking that the derived instance holds the sum
+    /// of the first two rows of the supplied instance.
+    #[derive(Clone, Default)]
+    struct SumCircuit;
+
+    impl Circuit<Fr> for SumCircuit {
+        type Config = Selector;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Selector {
+            let a = meta.instance_column();
+            let sum = meta.derived_instance_column("sum", vec![a]);
+            let s = meta.selector();
+            meta.create_gate("sum", |meta| {
+                let s = meta.query_selector(s);
+                let cur = meta.query_instance(a, Rotation::cur());
+                let next = meta.query_instance(a, Rotation::next());
+                let sum = meta.query_instance(sum, Rotation::cur());
+                vec![s * (cur + next - sum)]
+            });
+            s
+        }
+
+        fn synthesize(&self, s: Selector, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            layouter.assign_region(|| "sum", |mut region| s.enable(&mut region, 0))
+        }
+    }
+
+    let derive = |name: &str, sources: &[&[Fr]]| {
+        assert_eq!(name, "sum");
+        vec![sources[0].iter().fold(Fr::zero(), |acc, value| acc + value)]
+    };
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &SumCircuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &SumCircuit).unwrap();
+    // The derivation is bound in the verifying key.
+    assert!(format!("{:?}", vk.pinned()).contains("derived_instances"));
+
+    let supplied = [Fr::from(2), Fr::from(3)];
+    let instance = vk.cs().derive_instances(&[&supplied], derive).unwrap();
+    assert_eq!(instance[1], vec![Fr::from(5)]);
+    let instance = instance.iter().map(|v| &v[..]).collect::<Vec<_>>();
+
+    let proof = shplonk::prove_and_verify(&params, &pk, &[SumCircuit], &[&instance]);
+
+    let verify = |supplied: &[Fr]| {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof_with_derived_instances::<_, VerifierSHPLONK<_>, _, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[supplied]],
+            derive,
+            &mut transcript,
+        )
+    };
+    assert!(verify(&supplied).is_ok());
+    assert!(verify(&[Fr::from(2), Fr::from(4)]).is_err());
+}
+
+#[test]
+fn derived_instance_sources_are_derived_first() {
+    use halo2curves::bn256::Fr;
+
+    // A column of another constraint system stands in for a column that is only
+    // derived after the derivation reading it.
+    let mut other = ConstraintSystem::<Fr>::default();
+    other.instance_column();
+    other.instance_column();
+    let later = other.instance_column();
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let a = meta.instance_column();
+    meta.derived_instance_column("early", vec![later]);
+    meta.derived_instance_column("late", vec![a]);
+
+    let derive = |_: &str, sources: &[&[Fr]]| sources[0].to_vec();
+    assert_matches!(
+        meta.derive_instances(&[&[Fr::one()]], derive),
+        Err(Error::InvalidDerivedInstance { name: "early", source }) if source == later
+    );
+}
+
+#[test]
+fn unblinded_advice_commitments() {
+    use group::{Curve, GroupEncoding};
+    use halo2_proofs::poly::commitment::{Blind, Params};
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+    const VALUES: [u64; 4] = [3, 1, 4, 1];
+
+    /// Copies the values of an unblinded column into a blinded one.
+    #[derive(Clone, Default)]
+    struct CopyCircuit;
+
+    impl Circuit<Fr> for CopyCircuit {
+        type Config = (Column<Advice>, Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.unblinded_advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("copy", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                vec![s * (a - b)]
+            });
+            (a, b, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, b, s): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    for (row, value) in VALUES.iter().enumerate() {
+                        s.enable(&mut region, row)?;
+                        let value = Value::known(Fr::from(*value));
+                        region.assign_advice(a, row, value)?;
+                        region.assign_advice(b, row, value)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &CopyCircuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &CopyCircuit).unwrap();
+    assert_eq!(vk.cs().unblinded_advice_columns(), &vec![0]);
+
+    let prove = || shplonk::prove_and_verify(&params, &pk, &[CopyCircuit], &[&[]]);
+    let (first, second) = (prove(), prove());
+
+    // The advice commitments are the first points in the proof, in column order.
+    let mut values = vec![Fr::zero(); 1 << K];
+    for (value, expected) in values.iter_mut().zip(VALUES.iter()) {
+        *value = Fr::from(*expected);
+    }
+    let expected = params
+        .commit_lagrange(&vk.get_domain().lagrange_from_vec(values), Blind::default())
+        .to_affine();
+    assert_eq!(&first[..32], expected.to_bytes().as_ref());
+    assert_eq!(first[..32], second[..32]);
+    assert_ne!(first[32..64], second[32..64]);
+}
+
+#[test]
+fn without_blinding_factors() {
+    use halo2_proofs::plonk::Expression;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+
+    /// Assigns one to every row but the last, which is only usable without
+    /// blinding factors.
+    #[derive(Clone, Default)]
+    struct OnesCircuit;
+
+    impl Circuit<Fr> for OnesCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.set_blinding_factors(0);
+            let a = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("one", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![s * (a - Expression::Constant(Fr::one()))]
+            });
+            (a, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, s): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "ones",
+                |mut region| {
+                    for row in 0..(1 << K) - 1 {
+                        s.enable(&mut region, row)?;
+                        region.assign_advice(a, row, Value::known(Fr::one()))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &OnesCircuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &OnesCircuit).unwrap();
+    assert_eq!(vk.cs().blinding_factors(), 0);
+    assert_eq!(vk.pinned().cs().blinding_factors(), Some(0));
+
+    shplonk::prove_and_verify(&params, &pk, &[OnesCircuit], &[&[]]);
+}
+
+#[test]
+fn usable_rows() {
+    use halo2curves::bn256::Fr;
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let a = meta.advice_column();
+    meta.create_gate("square", |meta| {
+        let cur = meta.query_advice(a, Rotation::cur());
+        let next = meta.query_advice(a, Rotation::next());
+        vec![cur.clone() * cur - next]
+    });
+
+    let unusable = meta.unusable_rows();
+    assert_eq!(unusable, meta.blinding_factors() + 1);
+    assert_eq!(meta.usable_rows(6), 0..(1 << 6) - unusable);
+
+    // The smallest circuit fits the blinding rows, l_last, l_0 and one more row.
+    let k = meta.minimum_k();
+    assert!(1 << k >= meta.minimum_rows());
+    assert!(1 << (k - 1) < meta.minimum_rows());
+    assert!(!meta.usable_rows(k).is_empty());
+    assert!(meta.usable_rows(k - 1).is_empty());
+
+    meta.set_blinding_factors(0);
+    assert_eq!(meta.unusable_rows(), 1);
+    assert_eq!(meta.minimum_k(), 2);
+    assert_eq!(meta.usable_rows(2), 0..3);
+}
+
+#[test]
+fn constraint_system_introspection() {
+    use halo2_proofs::plonk::{Any, Expression};
+    use halo2curves::bn256::Fr;
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let a = meta.advice_column();
+    let f = meta.fixed_column();
+    let i = meta.instance_column();
+    let s = meta.selector();
+    meta.enable_equality(a);
+    meta.enable_equality(i);
+    meta.create_gate("mul", |meta| {
+        let s = meta.query_selector(s);
+        let a_cur = meta.query_advice(a, Rotation::cur());
+        let a_next = meta.query_advice(a, Rotation::next());
+        let f = meta.query_fixed(f, Rotation::cur());
+        vec![("product", s * (a_cur * f - a_next))]
+    });
+
+    let gates = meta.gates();
+    assert_eq!(gates.len(), 1);
+    assert_eq!(gates[0].name(), "mul");
+    assert_eq!(gates[0].constraint_name(0), "product");
+    assert_eq!(gates[0].polynomials().len(), 1);
+    assert!(matches!(gates[0].polynomials()[0], Expression::Product(..)));
+    assert_eq!(gates[0].queried_selectors(), &[s]);
+    assert_eq!(gates[0].queried_selectors()[0].index(), 0);
+    let cells: Vec<_> = gates[0]
+        .queried_cells()
+        .iter()
+        .map(|cell| (cell.column(), cell.rotation().0))
+        .collect();
+    assert_eq!(
+        cells,
+        vec![
+            (Column::<Any>::from(a), 0),
+            (Column::<Any>::from(a), 1),
+            (Column::<Any>::from(f), 0),
+        ]
+    );
+
+    assert_eq!(
+        meta.advice_queries(),
+        &vec![(a, Rotation::cur()), (a, Rotation::next())]
+    );
+    assert_eq!(meta.fixed_queries(), &vec![(f, Rotation::cur())]);
+    assert_eq!(meta.instance_queries(), &vec![(i, Rotation::cur())]);
+    assert_eq!(
+        meta.permutation_columns(),
+        &[Column::<Any>::from(a), Column::<Any>::from(i)]
+    );
+    assert_eq!(meta.num_selectors(), 1);
+    assert_eq!(meta.degree(), 3);
+}
+
+#[test]
+fn gate_degrees() {
+    use halo2curves::bn256::Fr;
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let a = meta.advice_column();
+    meta.set_max_degree(3);
+    meta.create_gate("cube", |meta| {
+        let a = meta.query_advice(a, Rotation::cur());
+        vec![a.clone() * a.clone() * a]
+    });
+    assert_eq!(meta.max_degree(), Some(3));
+    assert_eq!(meta.gates()[0].degree(), 3);
+    assert_eq!(meta.degree(), 3);
+}
+
+#[test]
+fn selector_group_indicators() {
+    use halo2curves::bn256::Fr;
+
+    const SIZE: usize = 3;
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let ops = meta.selector_group(SIZE);
+    meta.create_gate("indicators", |meta| ops.query_all(meta));
+
+    // Each indicator is one on the rows of its selector, and zero on every other row.
+    for (index, indicator) in meta.gates()[0].polynomials().iter().enumerate() {
+        for q in 0..=SIZE as u64 {
+            let value = indicator.evaluate(
+                &|scalar| scalar,
+                &|_| unreachable!(),
+                &|_| Fr::from(q),
+                &|_| unreachable!(),
+                &|_| unreachable!(),
+                &|_| unreachable!(),
+                &|a| -a,
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, scalar| a * scalar,
+            );
+            let expected = if q == index as u64 + 1 {
+                Fr::one()
+            } else {
+                Fr::zero()
+            };
+            assert_eq!(value, expected);
+        }
+    }
+}
+
+#[test]
+fn gate_exceeds_max_degree() {
+    use halo2curves::bn256::Fr;
+
+    #[derive(Clone, Default)]
+    struct QuarticCircuit;
+
+    impl Circuit<Fr> for QuarticCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) {
+            let a = meta.advice_column();
+            meta.set_max_degree(3);
+            meta.create_gate("quartic", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![
+                    ("square", a.clone() * a.clone()),
+                    ("fourth power", a.clone() * a.clone() * a.clone() * a),
+                ]
+            });
+        }
+
+        fn synthesize(&self, _: (), _: impl Layouter<Fr>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    let error = MockProver::run(4, &QuarticCircuit, vec![]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Gate 'quartic' has degree 4 in constraint 1 (fourth power), which exceeds the maximum degree 3"
+    );
+    assert_matches!(
+        error,
+        Error::GateDegreeExceeded {
+            gate: "quartic",
+            constraint: 1,
+            constraint_name: "fourth power",
+            degree: 4,
+            max_degree: 3,
+        }
+    );
+}
+
+#[test]
+fn selector_compression_respects_max_degree() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    /// Two gates on disjoint rows, whose selectors could share a fixed column if
+    /// the degree of gates were only bounded by that of the lookup.
+    #[derive(Clone, Default)]
+    struct CappedCircuit;
+
+    impl Circuit<Fr> for CappedCircuit {
+        type Config = [Selector; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> [Selector; 2] {
+            let a = meta.advice_column();
+            let selectors = [meta.selector(), meta.selector()];
+            meta.set_max_degree(2);
+            for (name, selector) in ["first", "second"].into_iter().zip(selectors) {
+                meta.create_gate(name, |meta| {
+                    let q = meta.query_selector(selector);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    vec![q * a]
+                });
+            }
+            meta.lookup_any("cube", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![(a.clone() * a.clone() * a.clone(), a)]
+            });
+            selectors
+        }
+
+        fn synthesize(
+            &self,
+            selectors: [Selector; 2],
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "selectors",
+                |mut region| {
+                    selectors[0].enable(&mut region, 0)?;
+                    selectors[1].enable(&mut region, 1)
+                },
+            )
+        }
+    }
+
+    let params = ParamsKZG::<Bn256>::new(4);
+    let vk = keygen_vk(&params, &CappedCircuit).unwrap();
+    assert!(vk.cs().degree() > 2);
+    assert!(vk.cs().gates().iter().all(|gate| gate.degree() <= 2));
+}
+
+#[test]
+fn shuffle_argument() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 5;
+
+    /// Shuffles the values of `a` into `b`, with the values of `c` and `d` moving
+    /// along with them.
+    #[derive(Clone, Default)]
+    struct ShuffleCircuit {
+        input: Vec<(u64, u64)>,
+        shuffled: Vec<(u64, u64)>,
+    }
+
+    impl Circuit<Fr> for ShuffleCircuit {
+        type Config = ([Column<Advice>; 4], Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let columns = [(); 4].map(|_| meta.advice_column());
+            let q = meta.complex_selector();
+            meta.shuffle("shuffle", |meta| {
+                let q = meta.query_selector(q);
+                let [a, b, c, d] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![(q.clone() * a, q.clone() * c), (q.clone() * b, q * d)]
+            });
+            (columns, q)
+        }
+
+        fn synthesize(
+            &self,
+            ([a, b, c, d], q): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "shuffle",
+                |mut region| {
+                    for (row, ((x, y), (z, w))) in
+                        self.input.iter().zip(self.shuffled.iter()).enumerate()
+                    {
+                        q.enable(&mut region, row)?;
+                        for (column, value) in [(a, x), (b, y), (c, z), (d, w)] {
+                            region.assign_advice(column, row, Value::known(Fr::from(*value)))?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let input = vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)];
+    let valid = ShuffleCircuit {
+        input: input.clone(),
+        shuffled: vec![(4, 40), (1, 10), (5, 50), (3, 30), (2, 20)],
+    };
+    // The values of `a` are shuffled, but their pairing with `b` is not preserved.
+    let invalid = ShuffleCircuit {
+        input,
+        shuffled: vec![(4, 10), (1, 40), (5, 50), (3, 30), (2, 20)],
+    };
+
+    assert_eq!(MockProver::run(K, &valid, vec![]).unwrap().verify(), Ok(()));
+    assert!(MockProver::run(K, &invalid, vec![])
+        .unwrap()
+        .verify()
+        .is_err());
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &ShuffleCircuit::default()).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &ShuffleCircuit::default()).unwrap();
+    assert_eq!(vk.cs().shuffles().len(), 1);
+
+    let verifies = |circuit: ShuffleCircuit| {
+        let proof = shplonk::prove(&params, &pk, &[circuit], &[&[]]).unwrap();
+        shplonk::verify(&params, &vk, &[&[]], &proof).is_ok()
+    };
+    assert!(verifies(valid));
+    assert!(!verifies(invalid));
+}
+
+#[test]
+fn fixed_copy_constraints() {
+    use halo2_proofs::plonk::Any;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+    const CONSTANT: u64 = 42;
+
+    /// Wires an advice cell to a constant held in a fixed column, without any gate.
+    #[derive(Clone, Default)]
+    struct FixedCopyCircuit {
+        value: u64,
+    }
+
+    impl Circuit<Fr> for FixedCopyCircuit {
+        type Config = (Column<Advice>, Column<Fixed>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let f = meta.fixed_column();
+            meta.enable_equality(a);
+            meta.enable_equality(f);
+            (a, f)
+        }
+
+        fn synthesize(
+            &self,
+            (a, f): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "fixed copy",
+                |mut region| {
+                    let constant = region.assign_fixed(f, 0, Fr::from(CONSTANT));
+                    let value = region.assign_advice(a, 1, Value::known(Fr::from(self.value)))?;
+                    region.constrain_equal(value.cell(), &constant);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let valid = FixedCopyCircuit { value: CONSTANT };
+    let invalid = FixedCopyCircuit {
+        value: CONSTANT + 1,
+    };
+
+    assert_eq!(MockProver::run(K, &valid, vec![]).unwrap().verify(), Ok(()));
+    assert!(MockProver::run(K, &invalid, vec![])
+        .unwrap()
+        .verify()
+        .is_err());
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &FixedCopyCircuit::default()).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &FixedCopyCircuit::default()).unwrap();
+    assert!(vk
+        .cs()
+        .permutation_columns()
+        .iter()
+        .any(|column| matches!(column.column_type(), Any::Fixed)));
+
+    let verifies = |circuit: FixedCopyCircuit| {
+        let proof = shplonk::prove(&params, &pk, &[circuit], &[&[]]).unwrap();
+        shplonk::verify(&params, &vk, &[&[]], &proof).is_ok()
+    };
+    assert!(verifies(valid));
+    assert!(!verifies(invalid));
+}
+
+#[test]
+fn permutation_over_many_columns() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 4;
+    const COLUMNS: usize = 32;
+
+    /// Copies a value along a chain of equality-enabled advice columns, with the
+    /// copy into the last column optionally broken.
+    #[derive(Clone, Default)]
+    struct ChainCircuit {
+        broken: bool,
+    }
+
+    impl Circuit<Fr> for ChainCircuit {
+        type Config = Vec<Column<Advice>>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            (0..COLUMNS)
+                .map(|_| {
+                    let column = meta.advice_column();
+                    meta.enable_equality(column);
+                    column
+                })
+                .collect()
+        }
+
+        fn synthesize(
+            &self,
+            columns: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "chain",
+                |mut region| {
+                    let mut previous: Option<Cell> = None;
+                    for (i, column) in columns.iter().enumerate() {
+                        let value = if self.broken && i == COLUMNS - 1 {
+                            8
+                        } else {
+                            7
+                        };
+                        let cell =
+                            region.assign_advice(*column, 0, Value::known(Fr::from(value)))?;
+                        if let Some(previous) = previous {
+                            region.constrain_equal(&previous, cell.cell());
+                        }
+                        previous = Some(*cell.cell());
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let valid = ChainCircuit { broken: false };
+    let invalid = ChainCircuit { broken: true };
+
+    assert_eq!(MockProver::run(K, &valid, vec![]).unwrap().verify(), Ok(()));
+    assert!(MockProver::run(K, &invalid, vec![])
+        .unwrap()
+        .verify()
+        .is_err());
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &valid).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &valid).unwrap();
+    // The columns are split across several grand products rather than raising
+    // the degree of the circuit.
+    assert_eq!(vk.cs().permutation_columns().len(), COLUMNS);
+    assert_eq!(vk.cs().degree(), 3);
+
+    let verifies = |circuit: ChainCircuit| {
+        let proof = shplonk::prove(&params, &pk, &[circuit], &[&[]]).unwrap();
+        shplonk::verify(&params, &vk, &[&[]], &proof).is_ok()
+    };
+    assert!(verifies(valid));
+    assert!(!verifies(invalid));
+}
+
+#[test]
+fn create_proof_skips_tables_and_constants() {
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const K: u32 = 5;
+
+    #[derive(Clone)]
+    struct TableConfig {
+        a: Column<Advice>,
+        s: Selector,
+        table: TableColumn,
+    }
+
+    /// Looks up a witness in a small table, and copies a constant.
+    #[derive(Clone, Default)]
+    struct TableCircuit {
+        value: Value<Fr>,
+        table_loads: Arc<AtomicUsize>,
+    }
+
+    impl Circuit<Fr> for TableCircuit {
+        type Config = TableConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            TableCircuit {
+                value: Value::unknown(),
+                table_loads: self.table_loads.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> TableConfig {
+            let a = meta.advice_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let s = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.lookup("table", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![(s * a, table)]
+            });
+            TableConfig { a, s, table }
+        }
+
+        fn synthesize(
+            &self,
+            config: TableConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut table| {
+                    self.table_loads.fetch_add(1, Ordering::SeqCst);
+                    for row in 0..8 {
+                        table.assign_cell(
+                            || "value",
+                            config.table,
+                            row,
+                            || Value::known(Fr::from(row as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "lookup",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(config.a, 0, self.value)?;
+                    region.assign_advice_from_constant(|| "one", config.a, 1, Fr::one())?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let circuit = TableCircuit {
+        value: Value::known(Fr::from(5)),
+        table_loads: Arc::new(AtomicUsize::new(0)),
+    };
+    assert_eq!(
+        MockProver::run(K, &circuit, vec![]).unwrap().verify(),
+        Ok(())
+    );
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+    let loads = circuit.table_loads.load(Ordering::SeqCst);
+
+    shplonk::prove_and_verify(&params, &pk, &[circuit.clone()], &[&[]]);
+    // The prover takes the table from the proving key.
+    assert_eq!(circuit.table_loads.load(Ordering::SeqCst), loads);
+}
+
+#[test]
+fn keygen_detects_k() {
+    use halo2_proofs::plonk::{circuit_k, keygen, keygen_with_k_hint, Expression};
+    use halo2_proofs::poly::commitment::Params;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const ROWS: usize = 20;
+
+    /// Assigns one to `ROWS` rows.
+    #[derive(Clone, Default)]
+    struct OnesCircuit;
+
+    impl Circuit<Fr> for OnesCircuit {
+        type Config = (Column<Advice>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let s = meta.selector();
+            meta.create_gate("one", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![s * (a - Expression::Constant(Fr::one()))]
+            });
+            (a, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, s): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "ones",
+                |mut region| {
+                    for row in 0..ROWS {
+                        s.enable(&mut region, row)?;
+                        region.assign_advice(a, row, Value::known(Fr::one()))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let k = circuit_k::<Fr, _>(&OnesCircuit).unwrap();
+    let mut meta = ConstraintSystem::<Fr>::default();
+    OnesCircuit::configure(&mut meta);
+    assert!(meta.usable_rows(k).len() >= ROWS);
+    assert!(meta.usable_rows(k - 1).len() < ROWS);
+    assert_eq!(
+        MockProver::run(k, &OnesCircuit, vec![]).unwrap().verify(),
+        Ok(())
+    );
+
+    // Params larger than needed are downsized to the circuit.
+    let mut params = ParamsKZG::<Bn256>::new(k + 2);
+    let pk = keygen(&mut params, &OnesCircuit).unwrap();
+    assert_eq!(params.k(), k);
+    assert_eq!(pk.get_vk().get_domain().k(), k);
+    shplonk::prove_and_verify(&params, &pk, &[OnesCircuit], &[&[]]);
+
+    // Params that are too small are rejected up front.
+    let mut small_params = ParamsKZG::<Bn256>::new(k - 1);
+    assert_matches!(
+        keygen(&mut small_params, &OnesCircuit),
+        Err(Error::ParamsTooSmall { params_k, required_k }) if params_k == k - 1 && required_k == k
+    );
+
+    // A k that is too small for the circuit is still reported by keygen.
+    assert_matches!(
+        keygen_with_k_hint(&mut small_params, k - 1, &OnesCircuit),
+        Err(Error::NotEnoughRowsAvailable { current_k, .. }) if current_k == k - 1
+    );
+}
+
+#[test]
+fn reconstruct_pk_from_vk() {
+    use halo2_proofs::plonk::reconstruct_pk;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+    const K: u32 = 4;
+
+    /// Constrains an advice cell to equal a fixed constant.
+    #[derive(Clone, Default)]
+    struct ConstantCircuit {
+        constant: u64,
+    }
+
+    impl Circuit<Fr> for ConstantCircuit {
+        type Config = (Column<Advice>, Column<Fixed>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let f = meta.fixed_column();
+            let s = meta.selector();
+            meta.create_gate("constant", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let f = meta.query_fixed(f, Rotation::cur());
+                vec![s * (a - f)]
+            });
+            (a, f, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, f, s): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "constant",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_fixed(f, 0, Fr::from(self.constant));
+                    region.assign_advice(a, 0, Value::known(Fr::from(self.constant)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let circuit = ConstantCircuit { constant: 7 };
+    let params = ParamsKZG::<Bn256>::new(K);
+    let vk_bytes = keygen_vk(&params, &circuit)
+        .unwrap()
+        .to_bytes(SerdeFormat::RawBytes);
+    let read_vk = || {
+        VerifyingKey::<G1Affine>::from_bytes::<ConstantCircuit>(&vk_bytes, SerdeFormat::RawBytes)
+            .unwrap()
+    };
+
+    // The proving key is regenerated from the persisted verifying key alone.
+    let pk = reconstruct_pk(&params, read_vk(), &circuit).unwrap();
+    let proof = shplonk::prove(&params, &pk, &[circuit.clone()], &[&[]]).unwrap();
+    assert!(shplonk::verify(&params, &read_vk(), &[&[]], &proof).is_ok());
+
+    // A circuit with the same gates but other fixed values has another key.
+    assert_matches!(
+        reconstruct_pk(&params, read_vk(), &ConstantCircuit { constant: 8 }),
+        Err(Error::VerifyingKeyMismatch)
+    );
+
+    // So do params of another size.
+    let larger_params = ParamsKZG::<Bn256>::new(K + 1);
+    assert_matches!(
+        reconstruct_pk(&larger_params, read_vk(), &circuit),
+        Err(Error::VerifyingKeyMismatch)
+    );
 }
@@ -173,7 +173,7 @@ impl<F: FieldExt, const T: usize, const RATE: usize> MDSMatrix<F, T, RATE> {
     }
 
     /// Inverts the MDS matrix
-    fn invert(&self) -> Self {
+    pub fn invert(&self) -> Self {
         Self(self.0.invert())
     }
 
@@ -316,6 +316,14 @@ impl<F: FieldExt, const T: usize, const RATE: usize> Spec<F, T, RATE> {
         }
     }
 
+    /// Returns the unoptimized round constants and the MDS matrix, as generated
+    /// by the reference `Grain` for the given number of rounds. Circuit
+    /// implementations that apply every round in full use these rather than the
+    /// optimized constants of a `Spec`.
+    pub fn reference_constants(r_f: usize, r_p: usize) -> (Vec<[F; T]>, MDSMatrix<F, T, RATE>) {
+        Grain::generate(r_f, r_p)
+    }
+
     fn calculate_optimized_constants(
         r_f: usize,
         r_p: usize,